@@ -0,0 +1,712 @@
+//! Exact contact generation for pairs of simple shapes.
+//!
+//! These are cheaper than GJK/EPA, and have no numerical noise from approximating curved shapes.
+//! Sphere-sphere, sphere-box and box-box pairs of collision primitives are tested using these by
+//! the [`DispatchNarrowPhase`](../dispatch/struct.DispatchNarrowPhase.html). Capsules and
+//! half-spaces are not collision primitives, so contacts with them can only be generated by
+//! calling the functions directly, e.g. from a character controller.
+//!
+//! All shapes are given in world space, and all functions work for both 2D and 3D.
+
+use std::ops::Neg;
+use std::vec::Vec;
+
+use cgmath::{Point3, Vector3};
+use cgmath::prelude::*;
+use collision::{CollisionStrategy, Contact};
+
+use Real;
+
+/// Relative tolerance used when comparing separating axes, so face axes are preferred over edge
+/// axes, and the left shape over the right shape, when the overlap is nearly the same
+const AXIS_TOLERANCE: Real = 1e-4;
+
+/// Box axes that are closer to perpendicular to a direction than this are considered
+/// perpendicular when finding the furthest feature of a box
+const FEATURE_TOLERANCE: Real = 1e-3;
+
+/// Squared length below which a capsule segment is treated as a point
+const SEGMENT_TOLERANCE: Real = 1e-10;
+
+/// Sphere or circle in world space
+#[derive(Debug, Clone)]
+pub struct Ball<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+{
+    /// Center of the sphere
+    pub center: P,
+    /// Radius of the sphere
+    pub radius: Real,
+}
+
+impl<P> Ball<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace,
+{
+    /// Create a new sphere
+    pub fn new(center: P, radius: Real) -> Self {
+        Self { center, radius }
+    }
+
+    /// Transform a sphere given in model space into world space. The radius is scaled using the
+    /// length of the transformed first axis, which assumes a uniform scale.
+    pub(crate) fn transformed<T>(radius: Real, transform: &T, axes: &[P::Diff]) -> Self
+    where
+        T: Transform<P>,
+    {
+        Self {
+            center: transform.transform_point(P::origin()),
+            radius: radius * transform.transform_vector(axes[0]).magnitude(),
+        }
+    }
+}
+
+/// Box or rectangle in world space
+#[derive(Debug, Clone)]
+pub struct OrientedBox<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+{
+    /// Center of the box
+    pub center: P,
+    /// Unit length axes of the box
+    pub axes: Vec<P::Diff>,
+    /// Half the size of the box along each axis
+    pub half_extents: Vec<Real>,
+}
+
+impl<P> OrientedBox<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace,
+{
+    /// Create a new box.
+    ///
+    /// # Parameters:
+    ///
+    /// - `center`: Center of the box
+    /// - `axes`: Axes of the box, will be normalized
+    /// - `half_extents`: Half the size of the box along each axis
+    pub fn new(center: P, axes: Vec<P::Diff>, half_extents: Vec<Real>) -> Self {
+        Self {
+            center,
+            axes: axes.into_iter().map(|a| a.normalize()).collect(),
+            half_extents,
+        }
+    }
+
+    /// Transform a box given in model space, by the corner with all positive coordinates, into
+    /// world space
+    pub(crate) fn transformed<T>(corner: P, transform: &T, axes: &[P::Diff]) -> Self
+    where
+        T: Transform<P>,
+    {
+        let mut world_axes = Vec::with_capacity(axes.len());
+        let mut half_extents = Vec::with_capacity(axes.len());
+        for (i, axis) in axes.iter().enumerate() {
+            let world_axis = transform.transform_vector(*axis);
+            let scale = world_axis.magnitude();
+            world_axes.push(world_axis / scale);
+            half_extents.push(corner[i] * scale);
+        }
+        Self {
+            center: transform.transform_point(P::origin()),
+            axes: world_axes,
+            half_extents,
+        }
+    }
+
+    /// Half the length of the projection of the box onto the axis
+    fn radius(&self, axis: P::Diff) -> Real {
+        self.axes
+            .iter()
+            .zip(&self.half_extents)
+            .map(|(a, h)| h * a.dot(axis).abs())
+            .sum()
+    }
+
+    /// Center of the feature of the box furthest along the direction. This is a corner, or the
+    /// center of an edge or face that is perpendicular to the direction. The skipped axis is
+    /// treated as perpendicular, which gives the center of an edge along that axis.
+    fn support(&self, direction: P::Diff, skip: Option<usize>) -> P {
+        self.axes
+            .iter()
+            .zip(&self.half_extents)
+            .enumerate()
+            .fold(self.center, |point, (i, (a, h))| {
+                let d = a.dot(direction);
+                if skip == Some(i) || d.abs() < FEATURE_TOLERANCE {
+                    point
+                } else if d < 0. {
+                    point + *a * -*h
+                } else {
+                    point + *a * *h
+                }
+            })
+    }
+
+    /// Clamp the point to the inside of the box
+    fn clamp(&self, point: P) -> P {
+        let offset = point - self.center;
+        self.axes
+            .iter()
+            .zip(&self.half_extents)
+            .fold(self.center, |clamped, (a, h)| {
+                clamped + *a * offset.dot(*a).max(-h).min(*h)
+            })
+    }
+}
+
+/// Half-space in world space, the solid side is behind the plane
+#[derive(Debug, Clone)]
+pub struct HalfSpace<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+{
+    /// Unit normal of the plane, pointing out of the solid side
+    pub normal: P::Diff,
+    /// Distance of the plane from the origin, along the normal
+    pub offset: Real,
+}
+
+impl<P> HalfSpace<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace,
+{
+    /// Create a new half-space, the normal will be normalized
+    pub fn new(normal: P::Diff, offset: Real) -> Self {
+        Self {
+            normal: normal.normalize(),
+            offset,
+        }
+    }
+
+    /// Signed distance from the plane to the point, negative inside the solid
+    pub fn distance(&self, point: P) -> Real {
+        point.to_vec().dot(self.normal) - self.offset
+    }
+}
+
+/// Capsule in world space, all points within the radius of a line segment
+#[derive(Debug, Clone)]
+pub struct Capsule<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+{
+    /// Start of the segment
+    pub start: P,
+    /// End of the segment
+    pub end: P,
+    /// Radius of the capsule
+    pub radius: Real,
+}
+
+impl<P> Capsule<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+{
+    /// Create a new capsule
+    pub fn new(start: P, end: P, radius: Real) -> Self {
+        Self { start, end, radius }
+    }
+}
+
+/// Kind of separating axis between two boxes
+#[derive(Debug, Clone, Copy)]
+enum SeparatingAxis {
+    /// Face normal of the left box
+    LeftFace,
+    /// Face normal of the right box
+    RightFace,
+    /// Cross product of edge directions of the left and right boxes
+    Edges(usize, usize),
+}
+
+/// Swap the left and right side of a contact
+pub(crate) fn flip<P>(contact: Contact<P>) -> Contact<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: Neg<Output = P::Diff>,
+{
+    Contact::new_with_point(
+        CollisionStrategy::FullResolution,
+        -contact.normal,
+        contact.penetration_depth,
+        contact.contact_point + contact.normal * -contact.penetration_depth,
+    )
+}
+
+/// Contact between two spheres.
+///
+/// # Returns:
+///
+/// The contact, with the normal pointing from the left sphere towards the right sphere, and the
+/// contact point on the surface of the left sphere, if the spheres intersect.
+pub fn sphere_sphere<P>(left: &Ball<P>, right: &Ball<P>) -> Option<Contact<P>>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace + Array<Element = Real>,
+{
+    let offset = right.center - left.center;
+    let distance = offset.magnitude();
+    let depth = left.radius + right.radius - distance;
+    if depth < 0. {
+        return None;
+    }
+    let normal = if distance > 0. {
+        offset / distance
+    } else {
+        // concentric spheres, any direction separates them
+        let mut normal = P::Diff::zero();
+        normal[0] = 1.;
+        normal
+    };
+    Some(Contact::new_with_point(
+        CollisionStrategy::FullResolution,
+        normal,
+        depth,
+        left.center + normal * left.radius,
+    ))
+}
+
+/// Contact between a sphere and a box.
+///
+/// # Returns:
+///
+/// The contact, with the normal pointing from the sphere towards the box, and the contact point
+/// on the surface of the sphere, if the shapes intersect.
+pub fn sphere_box<P>(sphere: &Ball<P>, cube: &OrientedBox<P>) -> Option<Contact<P>>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace + Neg<Output = P::Diff>,
+{
+    let offset = sphere.center - cube.center;
+    let mut closest = cube.center;
+    let mut inside = true;
+    // axis with the smallest distance to a face, used when the center is inside the box
+    let mut face: Option<(P::Diff, Real)> = None;
+    for (axis, half) in cube.axes.iter().zip(&cube.half_extents) {
+        let d = offset.dot(*axis);
+        if d.abs() > *half {
+            inside = false;
+        }
+        closest = closest + *axis * d.max(-half).min(*half);
+        let distance = half - d.abs();
+        if face.map(|(_, f)| distance < f).unwrap_or(true) {
+            let axis = if d < 0. { *axis } else { -*axis };
+            face = Some((axis, distance));
+        }
+    }
+    if inside {
+        let (normal, distance) = face?;
+        return Some(Contact::new_with_point(
+            CollisionStrategy::FullResolution,
+            normal,
+            distance + sphere.radius,
+            sphere.center + normal * sphere.radius,
+        ));
+    }
+    let offset = closest - sphere.center;
+    let distance = offset.magnitude();
+    if distance > sphere.radius {
+        return None;
+    }
+    let normal = offset / distance;
+    Some(Contact::new_with_point(
+        CollisionStrategy::FullResolution,
+        normal,
+        sphere.radius - distance,
+        sphere.center + normal * sphere.radius,
+    ))
+}
+
+/// Separating axis test for two boxes, using the face axes of both boxes, and the given edge axes,
+/// with the indices of the box axes they were computed from
+pub(crate) fn box_box<P>(
+    left: &OrientedBox<P>,
+    right: &OrientedBox<P>,
+    edges: &[(P::Diff, usize, usize)],
+) -> Option<Contact<P>>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace + Neg<Output = P::Diff>,
+{
+    let offset = right.center - left.center;
+    let mut best: Option<(P::Diff, Real, SeparatingAxis)> = None;
+    let candidates = left.axes
+        .iter()
+        .map(|a| (*a, SeparatingAxis::LeftFace))
+        .chain(right.axes.iter().map(|a| (*a, SeparatingAxis::RightFace)))
+        .chain(
+            edges
+                .iter()
+                .map(|&(a, i, j)| (a, SeparatingAxis::Edges(i, j))),
+        );
+    for (axis, kind) in candidates {
+        let distance = offset.dot(axis);
+        let overlap = left.radius(axis) + right.radius(axis) - distance.abs();
+        if overlap < 0. {
+            return None;
+        }
+        let better = best.map(|(_, o, _)| overlap < o * (1. - AXIS_TOLERANCE) - AXIS_TOLERANCE)
+            .unwrap_or(true);
+        if better {
+            let normal = if distance < 0. { -axis } else { axis };
+            best = Some((normal, overlap, kind));
+        }
+    }
+    let (normal, depth, kind) = best?;
+    // for face contacts, use the center of the overlapping part of the incident feature, which
+    // keeps the contact point stable for resting boxes
+    let contact_point = match kind {
+        SeparatingAxis::LeftFace => left.clamp(right.support(-normal, None)) + normal * depth,
+        SeparatingAxis::RightFace => right.clamp(left.support(normal, None)),
+        SeparatingAxis::Edges(i, j) => {
+            // closest point on the left edge to the right edge
+            let left_center = left.support(normal, Some(i));
+            let right_center = right.support(-normal, Some(j));
+            let (left_axis, right_axis) = (left.axes[i], right.axes[j]);
+            let r = left_center - right_center;
+            let b = left_axis.dot(right_axis);
+            let s = (b * right_axis.dot(r) - left_axis.dot(r)) / (1. - b * b);
+            let h = left.half_extents[i];
+            left_center + left_axis * s.max(-h).min(h)
+        }
+    };
+    Some(Contact::new_with_point(
+        CollisionStrategy::FullResolution,
+        normal,
+        depth,
+        contact_point,
+    ))
+}
+
+/// Edge axes for the separating axis test of two boxes in 3D, the cross products of all pairs of
+/// box axes, with the indices of the box axes they were computed from
+pub(crate) fn edge_axes(
+    left: &OrientedBox<Point3<Real>>,
+    right: &OrientedBox<Point3<Real>>,
+) -> Vec<(Vector3<Real>, usize, usize)> {
+    let mut edges = Vec::with_capacity(9);
+    for (i, l) in left.axes.iter().enumerate() {
+        for (j, r) in right.axes.iter().enumerate() {
+            let axis = l.cross(*r);
+            // parallel edges are already covered by the face axes
+            if axis.magnitude2() > AXIS_TOLERANCE {
+                edges.push((axis.normalize(), i, j));
+            }
+        }
+    }
+    edges
+}
+
+/// Contact between a sphere and a half-space.
+///
+/// # Returns:
+///
+/// The contact, with the normal pointing from the sphere into the half-space, and the contact
+/// point on the surface of the sphere, if the shapes intersect.
+pub fn sphere_plane<P>(sphere: &Ball<P>, plane: &HalfSpace<P>) -> Option<Contact<P>>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace + Neg<Output = P::Diff>,
+{
+    let depth = sphere.radius - plane.distance(sphere.center);
+    if depth < 0. {
+        return None;
+    }
+    let normal = -plane.normal;
+    Some(Contact::new_with_point(
+        CollisionStrategy::FullResolution,
+        normal,
+        depth,
+        sphere.center + normal * sphere.radius,
+    ))
+}
+
+/// Contact between two capsules.
+///
+/// The closest points of the capsule segments are found, and the capsules are then treated as
+/// spheres around those points.
+///
+/// # Returns:
+///
+/// The contact, with the normal pointing from the left capsule towards the right capsule, and the
+/// contact point on the surface of the left capsule, if the capsules intersect.
+pub fn capsule_capsule<P>(left: &Capsule<P>, right: &Capsule<P>) -> Option<Contact<P>>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace + Array<Element = Real>,
+{
+    let (left_point, right_point) = closest_points(left.start, left.end, right.start, right.end);
+    sphere_sphere(
+        &Ball::new(left_point, left.radius),
+        &Ball::new(right_point, right.radius),
+    )
+}
+
+/// Closest points between two line segments
+fn closest_points<P>(left_start: P, left_end: P, right_start: P, right_end: P) -> (P, P)
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace,
+{
+    let left = left_end - left_start;
+    let right = right_end - right_start;
+    let offset = left_start - right_start;
+    let left_length = left.magnitude2();
+    let right_length = right.magnitude2();
+    let f = right.dot(offset);
+    let clamp = |v: Real| v.clamp(0., 1.);
+    let (s, t) = if left_length <= SEGMENT_TOLERANCE && right_length <= SEGMENT_TOLERANCE {
+        (0., 0.)
+    } else if left_length <= SEGMENT_TOLERANCE {
+        (0., clamp(f / right_length))
+    } else {
+        let c = left.dot(offset);
+        if right_length <= SEGMENT_TOLERANCE {
+            (clamp(-c / left_length), 0.)
+        } else {
+            let b = left.dot(right);
+            let denominator = left_length * right_length - b * b;
+            // parallel segments, any point on the left segment will do
+            let s = if denominator > 0. {
+                clamp((b * f - c * right_length) / denominator)
+            } else {
+                0.
+            };
+            let t = (b * s + f) / right_length;
+            if t < 0. {
+                (clamp(-c / left_length), 0.)
+            } else if t > 1. {
+                (clamp((b - c) / left_length), 1.)
+            } else {
+                (s, t)
+            }
+        }
+    };
+    (left_start + left * s, right_start + right * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Decomposed, Quaternion, Rad};
+    use collision::algorithm::minkowski::GJK3;
+    use collision::prelude::*;
+    use collision::primitive::{Cuboid, Sphere};
+
+    use super::*;
+
+    type Transform3 = Decomposed<Vector3<Real>, Quaternion<Real>>;
+    type Contact3 = Contact<Point3<Real>>;
+
+    fn transform(center: Point3<Real>, rotation: Quaternion<Real>) -> Transform3 {
+        Decomposed {
+            disp: center.to_vec(),
+            rot: rotation,
+            scale: 1.,
+        }
+    }
+
+    fn ball(center: Point3<Real>, radius: Real) -> (Ball<Point3<Real>>, Sphere<Real>, Transform3) {
+        (
+            Ball::new(center, radius),
+            Sphere::new(radius),
+            transform(center, Quaternion::one()),
+        )
+    }
+
+    fn cube(
+        center: Point3<Real>,
+        rotation: Quaternion<Real>,
+        half: Vector3<Real>,
+    ) -> (OrientedBox<Point3<Real>>, Cuboid<Real>, Transform3) {
+        let axes = vec![
+            rotation.rotate_vector(Vector3::unit_x()),
+            rotation.rotate_vector(Vector3::unit_y()),
+            rotation.rotate_vector(Vector3::unit_z()),
+        ];
+        (
+            OrientedBox::new(center, axes, vec![half.x, half.y, half.z]),
+            Cuboid::new(half.x * 2., half.y * 2., half.z * 2.),
+            transform(center, rotation),
+        )
+    }
+
+    fn gjk<L, R>(
+        left: &L,
+        left_transform: &Transform3,
+        right: &R,
+        right_transform: &Transform3,
+    ) -> Option<Contact3>
+    where
+        L: SupportFunction<Point = Point3<Real>>,
+        R: SupportFunction<Point = Point3<Real>>,
+    {
+        GJK3::new().intersection(
+            &CollisionStrategy::FullResolution,
+            left,
+            left_transform,
+            right,
+            right_transform,
+        )
+    }
+
+    /// EPA approximates curved shapes by polytopes, so only roughly the same contact is expected
+    fn assert_matches(expected: Option<Contact3>, contact: Option<Contact3>) {
+        let (expected, contact) = (expected.unwrap(), contact.unwrap());
+        assert_relative_eq!(
+            expected.penetration_depth,
+            contact.penetration_depth,
+            epsilon = 1e-2
+        );
+        assert_relative_eq!(expected.normal, contact.normal, epsilon = 1e-2);
+    }
+
+    /// Shapes that just touch are in contact with no depth, GJK may not report those at all
+    fn assert_touching(expected: Option<Contact3>, contact: Option<Contact3>) {
+        assert_relative_eq!(0., contact.unwrap().penetration_depth, epsilon = 1e-5);
+        if let Some(expected) = expected {
+            assert_relative_eq!(0., expected.penetration_depth, epsilon = 1e-2);
+        }
+    }
+
+    #[test]
+    fn test_sphere_sphere_matches_gjk() {
+        // EPA does not converge for many pairs of spheres, and gives no depth at all, which is
+        // what the closed form test avoids, so only pairs where it does converge are compared
+        let (left, left_sphere, left_transform) = ball(Point3::new(0., 0., 0.), 1.);
+        let expect = |center, radius| {
+            let (right, right_sphere, right_transform) = ball(center, radius);
+            (
+                gjk(&left_sphere, &left_transform, &right_sphere, &right_transform),
+                sphere_sphere(&left, &right),
+            )
+        };
+        let (expected, contact) = expect(Point3::new(0.9, 0.3, 0.2), 0.8);
+        assert_matches(expected, contact);
+        // fully contained
+        let (expected, contact) = expect(Point3::new(-0.3, 0.1, 0.2), 0.6);
+        assert_matches(expected, contact);
+        let (expected, contact) = expect(Point3::new(0., 1.5, 0.), 0.5);
+        assert_touching(expected, contact);
+        let (expected, contact) = expect(Point3::new(1.5, 0.1, 0.05), 0.5);
+        assert!(expected.is_none() && contact.is_none());
+    }
+
+    #[test]
+    fn test_sphere_box_matches_gjk() {
+        let rotation = Quaternion::from_angle_y(Rad(0.4));
+        let (cube, cuboid, cube_transform) =
+            cube(Point3::new(0., 0., 0.), rotation, Vector3::new(1., 0.5, 0.75));
+        let expect = |center, radius| {
+            let (sphere, ball_sphere, ball_transform) = ball(center, radius);
+            (
+                gjk(&ball_sphere, &ball_transform, &cuboid, &cube_transform),
+                sphere_box(&sphere, &cube),
+            )
+        };
+        // against a face, an edge and a corner
+        let face = rotation.rotate_point(Point3::new(0., 0.8, 0.1));
+        let (expected, contact) = expect(face, 0.5);
+        assert_matches(expected, contact);
+        let edge = rotation.rotate_point(Point3::new(1.2, 0.6, 0.));
+        let (expected, contact) = expect(edge, 0.4);
+        assert_matches(expected, contact);
+        let corner = rotation.rotate_point(Point3::new(1.1, 0.6, 0.85));
+        let (expected, contact) = expect(corner, 0.3);
+        assert_matches(expected, contact);
+        // fully contained
+        let inside = rotation.rotate_point(Point3::new(0.5, 0.1, 0.));
+        let (expected, contact) = expect(inside, 0.2);
+        assert_matches(expected, contact);
+        let touching = rotation.rotate_point(Point3::new(0., 1., 0.));
+        let (expected, contact) = expect(touching, 0.5);
+        assert_touching(expected, contact);
+        let apart = rotation.rotate_point(Point3::new(0., 1.1, 0.));
+        let (expected, contact) = expect(apart, 0.5);
+        assert!(expected.is_none() && contact.is_none());
+    }
+
+    #[test]
+    fn test_box_box_matches_gjk() {
+        let (left, left_cuboid, left_transform) = cube(
+            Point3::new(0., 0., 0.),
+            Quaternion::one(),
+            Vector3::new(1., 1., 1.),
+        );
+        let expect = |center, rotation, half| {
+            let (right, right_cuboid, right_transform) = cube(center, rotation, half);
+            (
+                gjk(&left_cuboid, &left_transform, &right_cuboid, &right_transform),
+                box_box(&left, &right, &edge_axes(&left, &right)),
+            )
+        };
+        // face and edge contacts
+        let (expected, contact) = expect(
+            Point3::new(1.3, 0.2, 0.1),
+            Quaternion::from_angle_z(Rad(0.2)),
+            Vector3::new(0.5, 0.5, 0.5),
+        );
+        assert_matches(expected, contact);
+        let edge = Quaternion::from_angle_y(Rad(0.785)) * Quaternion::from_angle_z(Rad(0.785));
+        let (expected, contact) = expect(
+            Point3::new(1.5, 0.1, 0.),
+            edge,
+            Vector3::new(0.5, 0.5, 0.5),
+        );
+        assert_matches(expected, contact);
+        // fully contained
+        let (expected, contact) = expect(
+            Point3::new(0.4, 0.1, -0.2),
+            Quaternion::from_angle_x(Rad(0.3)),
+            Vector3::new(0.3, 0.2, 0.2),
+        );
+        assert_matches(expected, contact);
+        let (expected, contact) = expect(
+            Point3::new(2., 0.5, 0.),
+            Quaternion::one(),
+            Vector3::new(1., 1., 1.),
+        );
+        assert_touching(expected, contact);
+        let (expected, contact) = expect(
+            Point3::new(2.1, 0.5, 0.),
+            Quaternion::one(),
+            Vector3::new(1., 1., 1.),
+        );
+        assert!(expected.is_none() && contact.is_none());
+    }
+
+    #[test]
+    fn test_analytic_contacts() {
+        let sphere = Ball::new(Point3::new(0., 0.5, 0.), 1.);
+        let ground = HalfSpace::new(Vector3::unit_y(), 0.);
+        let contact = sphere_plane(&sphere, &ground).unwrap();
+        assert_ulps_eq!(0.5, contact.penetration_depth);
+        assert_ulps_eq!(-Vector3::unit_y(), contact.normal);
+        assert_ulps_eq!(Point3::new(0., -0.5, 0.), contact.contact_point);
+
+        // crossing capsules, one above the other
+        let left = Capsule::new(Point3::new(-1., 0., 0.), Point3::new(1., 0., 0.), 0.5);
+        let right = Capsule::new(Point3::new(0.5, 0.8, -1.), Point3::new(0.5, 0.8, 1.), 0.5);
+        let contact = capsule_capsule(&left, &right).unwrap();
+        assert_relative_eq!(0.2, contact.penetration_depth, epsilon = 1e-6);
+        assert_ulps_eq!(Vector3::unit_y(), contact.normal);
+        assert_ulps_eq!(Point3::new(0.5, 0.5, 0.), contact.contact_point);
+        assert!(
+            capsule_capsule(
+                &left,
+                &Capsule::new(Point3::new(1.6, 0., 0.), Point3::new(3., 0., 0.), 0.5)
+            ).is_some()
+        );
+        assert!(
+            capsule_capsule(
+                &left,
+                &Capsule::new(Point3::new(2.1, 0., 0.), Point3::new(3., 0., 0.), 0.5)
+            ).is_none()
+        );
+    }
+}
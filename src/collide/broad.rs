@@ -2,6 +2,8 @@
 
 pub use collision::algorithm::broad_phase::*;
 
+use std::vec::Vec;
+
 use collision::prelude::*;
 
 /// Broad phase
@@ -0,0 +1,281 @@
+//! Baked bounding volume hierarchy, for static geometry.
+//!
+//! The hierarchy is built once, using the surface area heuristic, and can not be changed after
+//! that. The nodes are stored in a flat list in depth first order, which makes queries faster
+//! than in a [`DynamicBoundingVolumeTree`](../../../collision/dbvt/struct.DynamicBoundingVolumeTree.html),
+//! at the cost of having to rebuild the whole hierarchy if anything changes.
+
+use std::cmp::Ordering;
+use std::vec::Vec;
+
+use cgmath::prelude::*;
+use collision::dbvt::{TreeValue, Visitor};
+use collision::prelude::*;
+
+use Real;
+
+/// Maximum number of values in a leaf node
+const MAX_LEAF_SIZE: usize = 4;
+
+/// Cost of traversing a node, relative to the cost of testing a value
+const TRAVERSAL_COST: Real = 1.;
+
+#[derive(Debug, Clone)]
+enum NodeKind {
+    /// Values in the given range are contained in the leaf
+    Leaf { start: usize, end: usize },
+    /// Left child is always the next node, only the right child index is stored
+    Branch { right: usize },
+}
+
+#[derive(Debug, Clone)]
+struct Node<B> {
+    bound: B,
+    kind: NodeKind,
+}
+
+/// Immutable bounding volume hierarchy, built using the surface area heuristic.
+///
+/// Intended for static level geometry, that is constructed at load time and never changes.
+///
+/// # Type parameters:
+///
+/// - `T`: Value type stored in the hierarchy, needs to implement `TreeValue`
+#[derive(Debug, Clone)]
+pub struct BakedBoundingVolumeHierarchy<T>
+where
+    T: TreeValue,
+{
+    nodes: Vec<Node<T::Bound>>,
+    values: Vec<T>,
+}
+
+impl<T> BakedBoundingVolumeHierarchy<T>
+where
+    T: TreeValue,
+    T::Bound: Aabb<Scalar = Real>
+        + Clone
+        + Union<T::Bound, Output = T::Bound>
+        + SurfaceArea<Scalar = Real>,
+{
+    /// Build a new hierarchy from the given values
+    pub fn new(mut values: Vec<T>) -> Self {
+        let mut nodes = Vec::default();
+        if !values.is_empty() {
+            let len = values.len();
+            build(&mut nodes, &mut values, 0, len);
+        }
+        Self { nodes, values }
+    }
+
+    /// Get the values in the hierarchy. The order is decided when building the hierarchy.
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Get the number of values in the hierarchy
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true if the hierarchy contains no values
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Query the hierarchy for all values that the given visitor accepts.
+    ///
+    /// # Parameters:
+    ///
+    /// - `visitor`: The visitor to check for bounding volume tests.
+    ///
+    /// # Returns
+    ///
+    /// Will return a list of tuples of values accepted and the result returned by the visitor for
+    /// the acceptance test.
+    pub fn query<V>(&self, visitor: &mut V) -> Vec<(&T, V::Result)>
+    where
+        V: Visitor<Bound = T::Bound>,
+    {
+        let mut results = Vec::default();
+        let mut stack = Vec::default();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            match node.kind {
+                NodeKind::Leaf { start, end } => {
+                    if visitor.accept(&node.bound, false).is_none() {
+                        continue;
+                    }
+                    for value in &self.values[start..end] {
+                        if let Some(result) = visitor.accept(value.bound(), true) {
+                            results.push((value, result));
+                        }
+                    }
+                }
+                NodeKind::Branch { right } => {
+                    if visitor.accept(&node.bound, false).is_some() {
+                        stack.push(right);
+                        stack.push(index + 1);
+                    }
+                }
+            }
+        }
+        results
+    }
+}
+
+fn centroid<T>(value: &T, axis: usize) -> Real
+where
+    T: TreeValue,
+    T::Bound: Aabb<Scalar = Real>,
+{
+    value.bound().center()[axis]
+}
+
+fn union_all<T>(values: &[T]) -> T::Bound
+where
+    T: TreeValue,
+    T::Bound: Clone + Union<T::Bound, Output = T::Bound>,
+{
+    values
+        .iter()
+        .skip(1)
+        .fold(values[0].bound().clone(), |bound, v| bound.union(v.bound()))
+}
+
+/// Build the node for the values in the given range, and all its children.
+fn build<T>(nodes: &mut Vec<Node<T::Bound>>, values: &mut [T], start: usize, end: usize)
+where
+    T: TreeValue,
+    T::Bound: Aabb<Scalar = Real>
+        + Clone
+        + Union<T::Bound, Output = T::Bound>
+        + SurfaceArea<Scalar = Real>,
+{
+    let bound = union_all(&values[start..end]);
+    let count = end - start;
+    if count == 1 {
+        nodes.push(Node {
+            bound,
+            kind: NodeKind::Leaf { start, end },
+        });
+        return;
+    }
+
+    // split along the axis where the centroids are spread out the most
+    let first = values[start].bound().center();
+    let centroids = values[start + 1..end]
+        .iter()
+        .fold(T::Bound::new(first, first), |b, v| b.grow(v.bound().center()));
+    let dim = centroids.dim();
+    let dimensions = <T::Bound as Aabb>::Diff::from_value(1.).sum() as usize;
+    let axis = (1..dimensions).fold(0, |best, axis| {
+        if dim[axis] > dim[best] {
+            axis
+        } else {
+            best
+        }
+    });
+    values[start..end].sort_by(|a, b| {
+        centroid(a, axis)
+            .partial_cmp(&centroid(b, axis))
+            .unwrap_or(Ordering::Equal)
+    });
+
+    // find the split with the lowest cost, using the surface area heuristic
+    let mut right_areas = vec![0.; count];
+    let mut right_bound = values[end - 1].bound().clone();
+    for i in (1..count).rev() {
+        right_bound = right_bound.union(values[start + i].bound());
+        right_areas[i] = right_bound.surface_area();
+    }
+    let mut left_bound = values[start].bound().clone();
+    let mut best = (1, Real::INFINITY);
+    for i in 1..count {
+        left_bound = left_bound.union(values[start + i - 1].bound());
+        let cost =
+            left_bound.surface_area() * i as Real + right_areas[i] * (count - i) as Real;
+        if cost < best.1 {
+            best = (i, cost);
+        }
+    }
+
+    let area = bound.surface_area();
+    let split_cost = if area > 0. {
+        TRAVERSAL_COST + best.1 / area
+    } else {
+        TRAVERSAL_COST
+    };
+    if count <= MAX_LEAF_SIZE && split_cost >= count as Real {
+        nodes.push(Node {
+            bound,
+            kind: NodeKind::Leaf { start, end },
+        });
+        return;
+    }
+
+    let index = nodes.len();
+    nodes.push(Node {
+        bound,
+        kind: NodeKind::Branch { right: 0 },
+    });
+    let split = start + best.0;
+    build(nodes, values, start, split);
+    let right = nodes.len();
+    build(nodes, values, split, end);
+    nodes[index].kind = NodeKind::Branch { right };
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Point2, Vector2};
+    use collision::{Aabb2, Ray2};
+    use collision::dbvt::ContinuousVisitor;
+
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct Value {
+        id: usize,
+        bound: Aabb2<Real>,
+    }
+
+    impl TreeValue for Value {
+        type Bound = Aabb2<Real>;
+
+        fn bound(&self) -> &Aabb2<Real> {
+            &self.bound
+        }
+
+        fn fat_bound(&self) -> Aabb2<Real> {
+            self.bound
+        }
+    }
+
+    #[test]
+    fn test_query() {
+        let values = (0..100)
+            .map(|id| {
+                let x = (id % 10) as Real * 2.;
+                let y = (id / 10) as Real * 2.;
+                Value {
+                    id,
+                    bound: Aabb2::new(Point2::new(x, y), Point2::new(x + 1., y + 1.)),
+                }
+            })
+            .collect::<Vec<_>>();
+        let bvh = BakedBoundingVolumeHierarchy::new(values);
+        assert_eq!(100, bvh.len());
+
+        let ray = Ray2::new(Point2::new(-1., 4.5), Vector2::new(1., 0.));
+        let mut hits = bvh.query(&mut ContinuousVisitor::<_, Value>::new(&ray))
+            .iter()
+            .map(|&(v, _)| v.id)
+            .collect::<Vec<_>>();
+        hits.sort();
+        assert_eq!((20..30).collect::<Vec<_>>(), hits);
+    }
+}
@@ -0,0 +1,344 @@
+//! Narrow phase collision detection, with the algorithm selected per pair of primitive types.
+//!
+//! Sphere-sphere and sphere-box pairs are tested analytically, box-box pairs using the separating
+//! axis test, see [`analytic`](../analytic/index.html), and all other pairs using GJK/EPA.
+
+use std::fmt::Debug;
+use std::ops::Neg;
+
+use cgmath::{Point2, Point3, Vector2, Vector3};
+use cgmath::prelude::*;
+use collision::{CollisionStrategy, Contact, Interpolate, Primitive};
+use collision::algorithm::minkowski::{SimplexProcessor, EPA, GJK};
+use collision::prelude::*;
+use collision::primitive::{Primitive2, Primitive3};
+
+use Real;
+use collide::{CollisionMode, CollisionShape};
+use collide::analytic::{box_box, edge_axes, flip, sphere_box, sphere_sphere, Ball, OrientedBox};
+use collide::narrow::{collide_primitives, NarrowPhase, PrimitiveContact};
+
+/// Narrow phase algorithm used for a pair of primitive types
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PairAlgorithm {
+    /// Closed form test, used for sphere-sphere and sphere-box pairs
+    Analytic,
+    /// Separating axis test, used for box-box pairs
+    SeparatingAxis,
+    /// GJK/EPA, used for all other pairs
+    Gjk,
+}
+
+/// Primitive type with specialized intersection tests for some pairs of primitive types.
+///
+/// Implemented for `Primitive2` and `Primitive3`, see
+/// [`DispatchNarrowPhase`](struct.DispatchNarrowPhase.html).
+pub trait PairDispatch: Primitive {
+    /// Get the algorithm to use for testing this primitive against the other primitive
+    fn pair_algorithm(&self, other: &Self) -> PairAlgorithm;
+
+    /// Specialized intersection test.
+    ///
+    /// # Parameters:
+    ///
+    /// - `transform`: Model-to-world transform for this primitive
+    /// - `other`: The other primitive
+    /// - `other_transform`: Model-to-world transform for the other primitive
+    ///
+    /// # Returns:
+    ///
+    /// The full resolution contact, with the normal pointing from this primitive towards the other
+    /// primitive, if the primitives intersect. Always returns `None` for pairs that use
+    /// `PairAlgorithm::Gjk`.
+    fn pair_intersection<T>(
+        &self,
+        transform: &T,
+        other: &Self,
+        other_transform: &T,
+    ) -> Option<Contact<Self::Point>>
+    where
+        T: Transform<Self::Point>;
+}
+
+/// Narrow phase that selects the algorithm per pair of primitive types.
+///
+/// Uses closed form tests for sphere-sphere and sphere-box pairs, and the separating axis test for
+/// box-box pairs, which are both faster than GJK/EPA, and give exact normals and penetration
+/// depths. All other pairs are tested using GJK/EPA. See
+/// [`PairDispatch::pair_algorithm`](trait.PairDispatch.html#tymethod.pair_algorithm) for the
+/// dispatch table.
+///
+/// Time of impact for continuous shapes is always computed using GJK.
+///
+/// # Type parameters:
+///
+/// - `G`: Fallback algorithm, `GJK2` or `GJK3`
+#[derive(Debug)]
+pub struct DispatchNarrowPhase<G> {
+    gjk: G,
+}
+
+impl<S, E> DispatchNarrowPhase<GJK<S, E>>
+where
+    S: SimplexProcessor,
+    S::Point: EuclideanSpace<Scalar = Real>,
+    E: EPA<Point = S::Point>,
+{
+    /// Create a new dispatching narrow phase
+    pub fn new() -> Self {
+        Self { gjk: GJK::new() }
+    }
+}
+
+impl<S, E> Default for DispatchNarrowPhase<GJK<S, E>>
+where
+    S: SimplexProcessor,
+    S::Point: EuclideanSpace<Scalar = Real>,
+    E: EPA<Point = S::Point>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P, T, S, E> NarrowPhase<P, T> for DispatchNarrowPhase<GJK<S, E>>
+where
+    P: PairDispatch,
+    <P::Point as EuclideanSpace>::Diff: Debug
+        + InnerSpace
+        + Neg<Output = <P::Point as EuclideanSpace>::Diff>,
+    P::Aabb: Discrete<P::Aabb> + Aabb<Scalar = Real>,
+    S: SimplexProcessor<Point = P::Point> + Send,
+    E: EPA<Point = P::Point> + Send,
+    T: Transform<P::Point>
+        + Interpolate<<P::Point as EuclideanSpace>::Scalar>
+        + TranslationInterpolate<<P::Point as EuclideanSpace>::Scalar>,
+{
+    fn collide(
+        &self,
+        left: &CollisionShape<P, T>,
+        left_transform: &T,
+        right: &CollisionShape<P, T>,
+        right_transform: &T,
+    ) -> Option<PrimitiveContact<P::Point>> {
+        collide_primitives(
+            left,
+            left_transform,
+            right,
+            right_transform,
+            |strategy, left_primitive, left_transform, right_primitive, right_transform| {
+                // scaled primitives are no longer spheres and boxes, so always use GJK
+                let algorithm = if left_primitive.is_scaled() || right_primitive.is_scaled() {
+                    PairAlgorithm::Gjk
+                } else {
+                    left_primitive
+                        .primitive
+                        .pair_algorithm(right_primitive.primitive)
+                };
+                match algorithm {
+                    PairAlgorithm::Gjk => self.gjk.intersection(
+                        strategy,
+                        left_primitive,
+                        left_transform,
+                        right_primitive,
+                        right_transform,
+                    ),
+                    _ => left_primitive
+                        .primitive
+                        .pair_intersection(
+                            left_transform,
+                            right_primitive.primitive,
+                            right_transform,
+                        )
+                        .map(|contact| match *strategy {
+                            CollisionStrategy::CollisionOnly => Contact::new(strategy.clone()),
+                            CollisionStrategy::FullResolution => contact,
+                        }),
+                }
+            },
+        )
+    }
+
+    fn collide_continuous(
+        &self,
+        left: &CollisionShape<P, T>,
+        left_start_transform: &T,
+        left_end_transform: Option<&T>,
+        right: &CollisionShape<P, T>,
+        right_start_transform: &T,
+        right_end_transform: Option<&T>,
+    ) -> Option<PrimitiveContact<P::Point>> {
+        if left.mode == CollisionMode::Continuous || right.mode == CollisionMode::Continuous {
+            return self.gjk.collide_continuous(
+                left,
+                left_start_transform,
+                left_end_transform,
+                right,
+                right_start_transform,
+                right_end_transform,
+            );
+        }
+        self.collide(
+            left,
+            left_end_transform.unwrap_or(left_start_transform),
+            right,
+            right_end_transform.unwrap_or(right_start_transform),
+        )
+    }
+}
+
+impl PairDispatch for Primitive2<Real> {
+    fn pair_algorithm(&self, other: &Self) -> PairAlgorithm {
+        match (self, other) {
+            (&Primitive2::Circle(_), &Primitive2::Circle(_))
+            | (&Primitive2::Circle(_), &Primitive2::Rectangle(_))
+            | (&Primitive2::Rectangle(_), &Primitive2::Circle(_)) => PairAlgorithm::Analytic,
+            (&Primitive2::Rectangle(_), &Primitive2::Rectangle(_)) => {
+                PairAlgorithm::SeparatingAxis
+            }
+            _ => PairAlgorithm::Gjk,
+        }
+    }
+
+    fn pair_intersection<T>(
+        &self,
+        transform: &T,
+        other: &Self,
+        other_transform: &T,
+    ) -> Option<Contact<Point2<Real>>>
+    where
+        T: Transform<Point2<Real>>,
+    {
+        let axes = [Vector2::unit_x(), Vector2::unit_y()];
+        match (self, other) {
+            (Primitive2::Circle(left), Primitive2::Circle(right)) => sphere_sphere(
+                &Ball::transformed(left.radius, transform, &axes),
+                &Ball::transformed(right.radius, other_transform, &axes),
+            ),
+            (Primitive2::Circle(left), Primitive2::Rectangle(right)) => sphere_box(
+                &Ball::transformed(left.radius, transform, &axes),
+                &OrientedBox::transformed(right.get_bound().max, other_transform, &axes),
+            ),
+            (Primitive2::Rectangle(left), Primitive2::Circle(right)) => sphere_box(
+                &Ball::transformed(right.radius, other_transform, &axes),
+                &OrientedBox::transformed(left.get_bound().max, transform, &axes),
+            ).map(flip),
+            (Primitive2::Rectangle(left), Primitive2::Rectangle(right)) => box_box(
+                &OrientedBox::transformed(left.get_bound().max, transform, &axes),
+                &OrientedBox::transformed(right.get_bound().max, other_transform, &axes),
+                &[],
+            ),
+            _ => None,
+        }
+    }
+}
+
+impl PairDispatch for Primitive3<Real> {
+    fn pair_algorithm(&self, other: &Self) -> PairAlgorithm {
+        match (self, other) {
+            (&Primitive3::Sphere(_), &Primitive3::Sphere(_))
+            | (&Primitive3::Sphere(_), &Primitive3::Cuboid(_))
+            | (&Primitive3::Cuboid(_), &Primitive3::Sphere(_)) => PairAlgorithm::Analytic,
+            (&Primitive3::Cuboid(_), &Primitive3::Cuboid(_)) => PairAlgorithm::SeparatingAxis,
+            _ => PairAlgorithm::Gjk,
+        }
+    }
+
+    fn pair_intersection<T>(
+        &self,
+        transform: &T,
+        other: &Self,
+        other_transform: &T,
+    ) -> Option<Contact<Point3<Real>>>
+    where
+        T: Transform<Point3<Real>>,
+    {
+        let axes = [Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()];
+        match (self, other) {
+            (Primitive3::Sphere(left), Primitive3::Sphere(right)) => sphere_sphere(
+                &Ball::transformed(left.radius, transform, &axes),
+                &Ball::transformed(right.radius, other_transform, &axes),
+            ),
+            (Primitive3::Sphere(left), Primitive3::Cuboid(right)) => sphere_box(
+                &Ball::transformed(left.radius, transform, &axes),
+                &OrientedBox::transformed(right.get_bound().max, other_transform, &axes),
+            ),
+            (Primitive3::Cuboid(left), Primitive3::Sphere(right)) => sphere_box(
+                &Ball::transformed(right.radius, other_transform, &axes),
+                &OrientedBox::transformed(left.get_bound().max, transform, &axes),
+            ).map(flip),
+            (Primitive3::Cuboid(left), Primitive3::Cuboid(right)) => {
+                let left = OrientedBox::transformed(left.get_bound().max, transform, &axes);
+                let right = OrientedBox::transformed(right.get_bound().max, other_transform, &axes);
+                box_box(&left, &right, &edge_axes(&left, &right))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, Decomposed, Point2, Rad, Rotation2, Vector2};
+    use collision::algorithm::minkowski::GJK2;
+    use collision::primitive::{Circle, Rectangle};
+
+    use super::*;
+
+    fn transform(x: Real, y: Real, angle: Real) -> Decomposed<Vector2<Real>, Basis2<Real>> {
+        Decomposed {
+            disp: Vector2::new(x, y),
+            rot: Rotation2::from_angle(Rad(angle)),
+            scale: 1.,
+        }
+    }
+
+    fn shape(
+        primitive: Primitive2<Real>,
+    ) -> CollisionShape<Primitive2<Real>, Decomposed<Vector2<Real>, Basis2<Real>>> {
+        CollisionShape::new_simple(
+            CollisionStrategy::FullResolution,
+            CollisionMode::Discrete,
+            primitive,
+        )
+    }
+
+    #[test]
+    fn test_dispatch_matches_gjk() {
+        let dispatch = DispatchNarrowPhase::<GJK2<Real>>::new();
+        let gjk = GJK2::<Real>::new();
+        let pairs = [
+            (Circle::new(1.).into(), Circle::new(1.).into()),
+            (Circle::new(1.).into(), Rectangle::new(2., 2.).into()),
+            (Rectangle::new(2., 2.).into(), Circle::new(1.).into()),
+            (Rectangle::new(2., 2.).into(), Rectangle::new(2., 2.).into()),
+        ];
+        for (left, right) in pairs.iter() {
+            let left = shape(Primitive2::clone(left));
+            let right = shape(Primitive2::clone(right));
+            let left_transform = transform(0., 0.1, 0.);
+            let right_transform = transform(1.5, 0., 0.);
+            let (expected, _) = gjk.collide(&left, &left_transform, &right, &right_transform)
+                .unwrap();
+            let (contact, _) = dispatch
+                .collide(&left, &left_transform, &right, &right_transform)
+                .unwrap();
+            // EPA approximates circles by polygons, so only roughly the same normal is expected
+            assert_relative_eq!(
+                expected.penetration_depth,
+                contact.penetration_depth,
+                epsilon = 1e-3
+            );
+            assert_relative_eq!(expected.normal, contact.normal, epsilon = 1e-2);
+            assert_relative_eq!(expected.contact_point.x, contact.contact_point.x, epsilon = 1e-3);
+        }
+
+        let left = shape(Circle::new(1.).into());
+        let (contact, _) = dispatch
+            .collide(&left, &transform(0., 0., 0.), &left, &transform(0., 1.5, 0.))
+            .unwrap();
+        assert_ulps_eq!(0.5, contact.penetration_depth);
+        assert_ulps_eq!(Vector2::unit_y(), contact.normal);
+        assert_ulps_eq!(Point2::new(0., 1.), contact.contact_point);
+    }
+}
@@ -0,0 +1,97 @@
+//! Heightfield construction from convex primitives.
+//!
+//! collision-rs has no heightfield primitive, so heightfields are built as a list of convex
+//! primitives that can be used in a [`CollisionShape`](../struct.CollisionShape.html). This makes
+//! it possible to split a large terrain into chunks, one shape per chunk.
+
+use std::vec::Vec;
+
+use cgmath::{Point2, Point3, Transform};
+use collision::primitive::{ConvexPolygon, ConvexPolyhedron, Primitive2, Primitive3};
+
+use Real;
+
+/// Build the primitives for a 2D heightfield.
+///
+/// The heights are sampled along the x axis, starting at x = 0, with y up. Each segment between
+/// two samples becomes a convex polygon that reaches `depth` below the lowest of the two samples.
+///
+/// # Parameters:
+///
+/// - `heights`: Height samples
+/// - `spacing`: Distance between samples along the x axis
+/// - `depth`: How far below the surface the primitives reach
+pub fn heightfield_2d<T>(heights: &[Real], spacing: Real, depth: Real) -> Vec<(Primitive2<Real>, T)>
+where
+    T: Transform<Point2<Real>>,
+{
+    heights
+        .windows(2)
+        .enumerate()
+        .map(|(i, h)| {
+            let x0 = i as Real * spacing;
+            let x1 = x0 + spacing;
+            let base = h[0].min(h[1]) - depth;
+            let polygon = ConvexPolygon::new(vec![
+                Point2::new(x0, base),
+                Point2::new(x1, base),
+                Point2::new(x1, h[1]),
+                Point2::new(x0, h[0]),
+            ]);
+            (polygon.into(), T::one())
+        })
+        .collect()
+}
+
+/// Build the primitives for a 3D heightfield.
+///
+/// The heights are a row major grid, with `columns` samples along the x axis for each row along
+/// the z axis, starting at the origin, with y up. Each grid cell is split into two triangular
+/// prisms, that reach `depth` below the lowest sample of the cell.
+///
+/// # Parameters:
+///
+/// - `heights`: Height samples
+/// - `columns`: Number of samples in each row
+/// - `spacing`: Distance between samples along the x and z axes
+/// - `depth`: How far below the surface the primitives reach
+pub fn heightfield_3d<T>(
+    heights: &[Real],
+    columns: usize,
+    spacing: Real,
+    depth: Real,
+) -> Vec<(Primitive3<Real>, T)>
+where
+    T: Transform<Point3<Real>>,
+{
+    let mut primitives = Vec::default();
+    if columns < 2 {
+        return primitives;
+    }
+    let rows = heights.len() / columns;
+    for row in 0..rows.saturating_sub(1) {
+        for column in 0..columns - 1 {
+            let point = |r: usize, c: usize| {
+                Point3::new(
+                    c as Real * spacing,
+                    heights[r * columns + c],
+                    r as Real * spacing,
+                )
+            };
+            let corners = [
+                point(row, column),
+                point(row, column + 1),
+                point(row + 1, column + 1),
+                point(row + 1, column),
+            ];
+            for triangle in &[[0, 1, 2], [0, 2, 3]] {
+                let top = triangle.iter().map(|&i| corners[i]).collect::<Vec<_>>();
+                let base = top.iter().map(|p| p.y).fold(top[0].y, Real::min) - depth;
+                let mut vertices = top.clone();
+                vertices.extend(top.iter().map(|p| Point3::new(p.x, base, p.z)));
+                primitives.push((ConvexPolyhedron::new(vertices).into(), T::one()));
+            }
+        }
+    }
+    primitives
+}
@@ -4,15 +4,24 @@ pub use collision::{CollisionStrategy, Contact};
 pub use collision::prelude::Primitive;
 
 pub mod narrow;
+pub mod analytic;
+pub mod dispatch;
 pub mod broad;
+pub mod bvh;
+pub mod heightfield;
 pub mod util;
 pub mod prelude2d;
 pub mod prelude3d;
 
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
+use std::ops::{AddAssign, Neg};
+use std::vec::Vec;
 
+use cgmath::{Matrix3, Matrix4, Point2, Point3};
 use cgmath::prelude::*;
+use collision::Ray;
 use collision::prelude::*;
+use collision::primitive::{Primitive2, Primitive3};
 
 use Real;
 
@@ -24,6 +33,11 @@ pub enum CollisionMode {
 
     /// Continuous collision mode
     Continuous,
+
+    /// Query only mode, e.g. for hitboxes. The shape is kept in the spatial structures, and is
+    /// found by ray and region queries, but the collision systems never pair it with other shapes,
+    /// so it never produces contacts. Bounds are computed like in discrete mode.
+    QueryOnly,
 }
 
 /// Contains all the contacts found between two bodies in a single pass.
@@ -31,7 +45,7 @@ pub enum CollisionMode {
 /// # Type parameters
 ///
 /// - `ID`: The ID type of the body. This is supplied by the user of the library. In the ECS case,
-///         this will be [`Entity`](https://docs.rs/specs/0.9.5/specs/struct.Entity.html).
+///   this will be [`Entity`](https://docs.rs/specs/0.9.5/specs/struct.Entity.html).
 /// - `V`: cgmath vector type
 #[derive(Debug, Clone)]
 pub struct ContactEvent<ID, P>
@@ -44,6 +58,14 @@ where
 
     /// The contact between the colliding bodies
     pub contact: Contact<P>,
+
+    /// The user tags of the two colliding shapes, see
+    /// [`CollisionShape::with_tag`](struct.CollisionShape.html#method.with_tag)
+    pub tags: (Option<u64>, Option<u64>),
+
+    /// The indices of the primitives on the two colliding shapes that generated the contact, for
+    /// compound shapes
+    pub primitives: (usize, usize),
 }
 
 impl<ID, P> ContactEvent<ID, P>
@@ -54,13 +76,30 @@ where
 {
     /// Create a new contact set
     pub fn new(bodies: (ID, ID), contact: Contact<P>) -> Self {
-        Self { bodies, contact }
+        Self {
+            bodies,
+            contact,
+            tags: (None, None),
+            primitives: (0, 0),
+        }
     }
 
     /// Convenience function to create a contact set with a single [`Contact`](struct.Contact.html).
     pub fn new_single(strategy: CollisionStrategy, bodies: (ID, ID)) -> Self {
         Self::new(bodies, Contact::new(strategy))
     }
+
+    /// Set the user tags of the two colliding shapes
+    pub fn with_tags(mut self, tags: (Option<u64>, Option<u64>)) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Set the indices of the primitives that generated the contact
+    pub fn with_primitives(mut self, primitives: (usize, usize)) -> Self {
+        self.primitives = primitives;
+        self
+    }
 }
 
 /// Collision shape describing a complete collision object in the collision world.
@@ -71,7 +110,9 @@ where
 ///
 /// Contains cached information about the base bounding box containing all primitives,
 /// in model space coordinates. Also contains a cached version of the transformed bounding box,
-/// in world space coordinates.
+/// in world space coordinates. The rotated base bounding box is cached as well, so when only the
+/// position of the shape changes, the transformed bounding box is found by translating the cached
+/// box, and is only recomputed fully when the rotation changes.
 ///
 /// Also have details about what collision strategy to use for contact resolution with this shape.
 #[derive(Debug, Clone)]
@@ -81,9 +122,14 @@ where
 {
     /// Enable/Disable collision detection for this shape
     pub enabled: bool,
+    /// User tag, copied to all contact events for this shape
+    pub tag: Option<u64>,
     base_bound: P::Aabb,
     transformed_bound: P::Aabb,
+    rotated_bound: P::Aabb,
+    rotation_key: Vec<Real>,
     primitives: Vec<(P, T)>,
+    scales: Vec<Real>,
     strategy: CollisionStrategy,
     mode: CollisionMode,
 }
@@ -108,11 +154,15 @@ where
         mode: CollisionMode,
         primitives: Vec<(P, T)>,
     ) -> Self {
-        let bound = get_bound(&primitives);
+        let bound = get_bound(&primitives, &[]);
         Self {
             base_bound: bound.clone(),
             primitives,
+            scales: Vec::default(),
             enabled: true,
+            tag: None,
+            rotated_bound: bound.clone(),
+            rotation_key: Vec::default(),
             transformed_bound: bound,
             strategy,
             mode,
@@ -147,6 +197,13 @@ where
         Self::new_complex(strategy, mode, vec![(primitive, transform)])
     }
 
+    /// Set a user tag on the shape, that will be copied to all contact events for the shape. Can be
+    /// used to classify contacts without looking up other components.
+    pub fn with_tag(mut self, tag: u64) -> Self {
+        self.tag = Some(tag);
+        self
+    }
+
     /// Update the cached transformed bounding box in world space coordinates.
     ///
     /// If the end transform is given, that will always be used. If the collision mode of the shape
@@ -159,11 +216,11 @@ where
     /// - `end`: Optional model-to-world transform of the shaped at the end of the frame.
     pub fn update(&mut self, start: &T, end: Option<&T>) {
         self.transformed_bound = match end {
-            None => self.base_bound.transform(start),
+            None => self.transform_bound(start),
             Some(end_t) => {
-                let base = self.base_bound.transform(end_t);
+                let base = self.transform_bound(end_t);
                 if self.mode == CollisionMode::Continuous {
-                    base.union(&self.base_bound.transform(start))
+                    base.union(&self.transform_bound(start))
                 } else {
                     base
                 }
@@ -171,14 +228,319 @@ where
         };
     }
 
+    /// Transform the base bound, reusing the cached rotated bound if the rotation part of the
+    /// transform is unchanged.
+    ///
+    /// The rotation is identified by the images of the unit axes under the transform.
+    fn transform_bound(&mut self, transform: &T) -> P::Aabb {
+        let dimensions = <P::Aabb as Aabb>::Diff::from_value(1.).sum() as usize;
+        let mut changed = self.rotation_key.len() != dimensions * dimensions;
+        self.rotation_key.resize(dimensions * dimensions, 0.);
+        for axis in 0..dimensions {
+            let mut unit = <P::Aabb as Aabb>::Diff::zero();
+            unit[axis] = 1.;
+            let image = transform.transform_vector(unit);
+            for i in 0..dimensions {
+                let key = &mut self.rotation_key[axis * dimensions + i];
+                if *key != image[i] {
+                    *key = image[i];
+                    changed = true;
+                }
+            }
+        }
+        let translation = transform.transform_point(P::Point::origin()).to_vec();
+        if changed {
+            self.rotated_bound = self.base_bound.transform(transform).add_v(translation * -1.);
+        }
+        self.rotated_bound.add_v(translation)
+    }
+
     /// Return the current transformed bound for the shape
     ///
     pub fn bound(&self) -> &P::Aabb {
         &self.transformed_bound
     }
+
+    /// Return the base bound for the shape, in model space coordinates
+    pub fn base_bound(&self) -> &P::Aabb {
+        &self.base_bound
+    }
+
+    /// Return the collision mode of the shape
+    pub fn mode(&self) -> &CollisionMode {
+        &self.mode
+    }
+
+    /// Check if the shape is only used for queries, and never paired with other shapes
+    pub fn is_query_only(&self) -> bool {
+        self.mode == CollisionMode::QueryOnly
+    }
+
+    /// Borrow the primitives of the shape, with their local-to-model transforms
+    pub fn primitives(&self) -> &[(P, T)] {
+        &self.primitives
+    }
+
+    /// Set a non-uniform scale on a primitive, applied in the local space of the primitive.
+    ///
+    /// The scale is applied by the narrow phase and the bounding boxes, so the scale can be
+    /// animated without rebuilding the shape. Scaled primitives are always tested using GJK/EPA.
+    ///
+    /// The cached base bound is recomputed, and the transformed bound is refreshed on the next
+    /// call to [`update`](#method.update).
+    ///
+    /// # Parameters:
+    ///
+    /// - `index`: Index of the primitive
+    /// - `scale`: Scale along each local axis of the primitive
+    pub fn set_primitive_scale(
+        &mut self,
+        index: usize,
+        scale: <P::Point as EuclideanSpace>::Diff,
+    ) {
+        assert!(index < self.primitives.len(), "Primitive index out of bounds");
+        let dimensions = dimensions::<P>();
+        self.scales.resize(self.primitives.len() * dimensions, 1.);
+        for i in 0..dimensions {
+            self.scales[index * dimensions + i] = scale[i];
+        }
+        self.base_bound = get_bound(&self.primitives, &self.scales);
+        self.rotation_key.clear();
+    }
+
+    /// Get the scale of a primitive, see
+    /// [`set_primitive_scale`](#method.set_primitive_scale)
+    pub fn primitive_scale(&self, index: usize) -> <P::Point as EuclideanSpace>::Diff {
+        scale_of::<P>(&self.scales, index)
+            .unwrap_or_else(|| <P::Point as EuclideanSpace>::Diff::from_value(1.))
+    }
+
+    /// Get a primitive with its scale, for use with the narrow phase algorithms
+    pub fn scaled_primitive<'a>(&'a self, index: usize) -> ScaledPrimitive<'a, P> {
+        ScaledPrimitive {
+            primitive: &self.primitives[index].0,
+            scale: scale_of::<P>(&self.scales, index),
+        }
+    }
+}
+
+/// Collision primitive with an optional non-uniform scale, applied in the local space of the
+/// primitive, see
+/// [`CollisionShape::set_primitive_scale`](struct.CollisionShape.html#method.set_primitive_scale).
+///
+/// The support function assumes the transform the primitive is used with has no non-uniform
+/// scale, like [`BodyPose`](../struct.BodyPose.html) and
+/// [`ScaledPose`](../struct.ScaledPose.html).
+pub struct ScaledPrimitive<'a, P>
+where
+    P: Primitive + 'a,
+{
+    /// The primitive
+    pub primitive: &'a P,
+    /// The scale of the primitive, `None` if the primitive is unscaled
+    pub scale: Option<<P::Point as EuclideanSpace>::Diff>,
+}
+
+impl<'a, P> ScaledPrimitive<'a, P>
+where
+    P: Primitive + 'a,
+{
+    /// Is the primitive scaled
+    pub fn is_scaled(&self) -> bool {
+        self.scale.is_some()
+    }
+}
+
+impl<'a, P> Debug for ScaledPrimitive<'a, P>
+where
+    P: Primitive + 'a,
+    P::Aabb: Aabb<Scalar = Real>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let scale = self.scale.map(|scale| {
+            (0..dimensions::<P>())
+                .map(|i| scale[i])
+                .collect::<Vec<_>>()
+        });
+        f.debug_struct("ScaledPrimitive")
+            .field("primitive", self.primitive)
+            .field("scale", &scale)
+            .finish()
+    }
+}
+
+impl<'a, P> SupportFunction for ScaledPrimitive<'a, P>
+where
+    P: Primitive + 'a,
+    P::Aabb: Aabb<Scalar = Real>,
+{
+    type Point = P::Point;
+
+    fn support_point<T>(
+        &self,
+        direction: &<P::Point as EuclideanSpace>::Diff,
+        transform: &T,
+    ) -> P::Point
+    where
+        T: Transform<P::Point>,
+    {
+        let scale = match self.scale {
+            Some(scale) => scale,
+            None => return self.primitive.support_point(direction, transform),
+        };
+        // support of the scaled shape is the scaled support of the shape, in the scaled direction
+        let local_direction = transform
+            .inverse_transform()
+            .unwrap()
+            .transform_vector(*direction)
+            .mul_element_wise(scale);
+        let local_point = self.primitive
+            .support_point(&local_direction, &T::one())
+            .to_vec()
+            .mul_element_wise(scale);
+        transform.transform_point(P::Point::from_vec(local_point))
+    }
+}
+
+impl<P, T> CollisionShape<P, T>
+where
+    P: Primitive,
+    P::Aabb: Aabb<Scalar = Real>,
+    T: Transform<P::Point> + AddAssign<<P::Point as EuclideanSpace>::Diff>,
+{
+    /// Move all primitives by the given offset, in model space coordinates.
+    ///
+    /// The cached base bound is recomputed, and the transformed bound is refreshed on the next
+    /// call to [`update`](#method.update).
+    pub fn translate_primitives(&mut self, offset: <P::Point as EuclideanSpace>::Diff) {
+        for &mut (_, ref mut transform) in &mut self.primitives {
+            *transform += offset;
+        }
+        self.base_bound = get_bound(&self.primitives, &self.scales);
+        self.rotation_key.clear();
+    }
+}
+
+impl<P, T> CollisionShape<P, T>
+where
+    P: ContainsPoint,
+    P::Aabb: Aabb<Scalar = Real> + Contains<P::Point>,
+    <P::Point as EuclideanSpace>::Diff: ElementWise,
+    T: Transform<P::Point>,
+{
+    /// Check if a point is inside any of the primitives of the shape.
+    ///
+    /// # Parameters:
+    ///
+    /// - `transform`: Model-to-world transform of the shape, should be the transform the shape was
+    ///   last [`update`](#method.update)d with
+    /// - `point`: The point, in world space coordinates
+    pub fn contains_point(&self, transform: &T, point: &P::Point) -> bool {
+        if !self.transformed_bound.contains(point) {
+            return false;
+        }
+        self.primitives
+            .iter()
+            .enumerate()
+            .any(|(index, (primitive, local_transform))| {
+                let inverse = match transform.concat(local_transform).inverse_transform() {
+                    Some(inverse) => inverse,
+                    None => return false,
+                };
+                let mut local_point = inverse.transform_point(*point).to_vec();
+                if let Some(scale) = scale_of::<P>(&self.scales, index) {
+                    local_point = local_point.div_element_wise(scale);
+                }
+                primitive.contains_local_point(&P::Point::from_vec(local_point))
+            })
+    }
+}
+
+/// Primitive type that can be tested for containing a point.
+///
+/// Implemented for `Primitive2` and `Primitive3`, see
+/// [`CollisionShape::contains_point`](struct.CollisionShape.html#method.contains_point).
+pub trait ContainsPoint: Primitive {
+    /// Check if a point, given in the model space of the primitive, is inside the primitive.
+    ///
+    /// A ray is cast at the point from both sides, starting outside the bound of the primitive.
+    /// The point is inside the convex primitive if it lies between the two hit points. Particles
+    /// never contain a point.
+    fn contains_local_point(&self, point: &Self::Point) -> bool;
+}
+
+impl ContainsPoint for Primitive2<Real> {
+    fn contains_local_point(&self, point: &Point2<Real>) -> bool {
+        ray_contains(self, *point, &Matrix3::identity())
+    }
+}
+
+impl ContainsPoint for Primitive3<Real> {
+    fn contains_local_point(&self, point: &Point3<Real>) -> bool {
+        ray_contains(self, *point, &Matrix4::identity())
+    }
+}
+
+fn ray_contains<P, Q, T>(primitive: &P, point: Q, identity: &T) -> bool
+where
+    Q: EuclideanSpace<Scalar = Real>,
+    Q::Diff: InnerSpace + Neg<Output = Q::Diff>,
+    P: Primitive + ContinuousTransformed<Ray<Real, Q, Q::Diff>, Point = Q, Result = Q>,
+    P::Aabb: Aabb<Point = Q, Diff = Q::Diff> + Contains<Q>,
+    T: Transform<Q>,
+{
+    let bound = primitive.get_bound();
+    if !bound.contains(&point) {
+        return false;
+    }
+    let diagonal = bound.max() - bound.min();
+    if diagonal.magnitude2() == 0. {
+        return false;
+    }
+    // starting a diagonal away from a point inside the bound is always outside it
+    let direction = diagonal.normalize();
+    let forward = Ray::new(point + -diagonal, direction);
+    let backward = Ray::new(point + diagonal, -direction);
+    match (
+        primitive.intersection_transformed(&forward, identity),
+        primitive.intersection_transformed(&backward, identity),
+    ) {
+        (Some(entry), Some(exit)) => {
+            (point - entry).dot(direction) >= 0. && (exit - point).dot(direction) >= 0.
+        }
+        _ => false,
+    }
+}
+
+/// Get the scale of the primitive with the given index from the flattened scales of a shape, `None`
+/// if the primitive is unscaled
+fn scale_of<P>(scales: &[Real], index: usize) -> Option<<P::Point as EuclideanSpace>::Diff>
+where
+    P: Primitive,
+    P::Aabb: Aabb<Scalar = Real>,
+{
+    let dimensions = dimensions::<P>();
+    let components = scales.get(index * dimensions..(index + 1) * dimensions)?;
+    if components.iter().all(|&c| c == 1.) {
+        return None;
+    }
+    let mut scale = <P::Point as EuclideanSpace>::Diff::from_value(1.);
+    for (i, &c) in components.iter().enumerate() {
+        scale[i] = c;
+    }
+    Some(scale)
+}
+
+/// Number of dimensions of the space of the primitive
+fn dimensions<P>() -> usize
+where
+    P: Primitive,
+    P::Aabb: Aabb<Scalar = Real>,
+{
+    <P::Point as EuclideanSpace>::Diff::from_value(1.).sum() as usize
 }
 
-fn get_bound<P, T>(primitives: &Vec<(P, T)>) -> P::Aabb
+fn get_bound<P, T>(primitives: &[(P, T)], scales: &[Real]) -> P::Aabb
 where
     P: Primitive,
     P::Aabb: Aabb<Scalar = Real>,
@@ -186,6 +548,17 @@ where
 {
     primitives
         .iter()
-        .map(|&(ref p, ref t)| p.get_bound().transform(t))
+        .enumerate()
+        .map(|(index, (p, t))| {
+            let bound = p.get_bound();
+            let bound = match scale_of::<P>(scales, index) {
+                Some(scale) => P::Aabb::new(
+                    P::Point::from_vec(bound.min().to_vec().mul_element_wise(scale)),
+                    P::Point::from_vec(bound.max().to_vec().mul_element_wise(scale)),
+                ),
+                None => bound,
+            };
+            bound.transform(t)
+        })
         .fold(P::Aabb::zero(), |bound, b| bound.union(&b))
 }
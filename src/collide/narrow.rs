@@ -1,9 +1,11 @@
 //! Generic narrow phase collision detection algorithms.
 //!
-//! Currently only supports GJK/EPA.
+//! Currently only supports GJK/EPA, see [`dispatch`](../dispatch/index.html) for specialized
+//! algorithms for some pairs of primitive types.
 
 use std::fmt::Debug;
 use std::ops::Neg;
+use std::vec::Vec;
 
 use cgmath::prelude::*;
 use collision::{CollisionStrategy, Contact, Interpolate, Primitive};
@@ -11,7 +13,11 @@ use collision::algorithm::minkowski::{SimplexProcessor, EPA, GJK};
 use collision::prelude::*;
 
 use Real;
-use collide::{CollisionMode, CollisionShape};
+use collide::{CollisionMode, CollisionShape, ScaledPrimitive};
+
+/// Contact found by the narrow phase, and the indices of the primitives on the left and right
+/// shapes that generated the contact.
+pub type PrimitiveContact<P> = (Contact<P>, (usize, usize));
 
 /// Base trait implemented by all narrow phase algorithms.
 ///
@@ -36,14 +42,15 @@ where
     ///
     /// # Returns:
     ///
-    /// Optionally returns the contact manifold for the contact with largest penetration depth
+    /// Optionally returns the contact manifold for the contact with largest penetration depth,
+    /// and the indices of the primitives on the left and right shapes that generated it
     fn collide(
         &self,
         left: &CollisionShape<P, T>,
         left_transform: &T,
         right: &CollisionShape<P, T>,
         right_transform: &T,
-    ) -> Option<Contact<P::Point>>;
+    ) -> Option<PrimitiveContact<P::Point>>;
 
     /// Check if two shapes collides along the given transformation paths, and give a contact
     /// manifold for the contact with the earliest time of impact.
@@ -62,7 +69,8 @@ where
     ///
     /// # Returns:
     ///
-    /// Optionally returns the contact manifold for the contact with largest penetration depth
+    /// Optionally returns the contact manifold for the contact with largest penetration depth,
+    /// and the indices of the primitives on the left and right shapes that generated it
     fn collide_continuous(
         &self,
         left: &CollisionShape<P, T>,
@@ -71,7 +79,7 @@ where
         right: &CollisionShape<P, T>,
         right_start_transform: &T,
         right_end_transform: Option<&T>,
-    ) -> Option<Contact<P::Point>>;
+    ) -> Option<PrimitiveContact<P::Point>>;
 }
 
 impl<P, T, S, E> NarrowPhase<P, T> for GJK<S, E>
@@ -93,20 +101,21 @@ where
         left_transform: &T,
         right: &CollisionShape<P, T>,
         right_transform: &T,
-    ) -> Option<Contact<P::Point>> {
-        if !left.enabled || !right.enabled || left.primitives.is_empty()
-            || right.primitives.is_empty()
-        {
-            return None;
-        }
-
-        let strategy = max(&left.strategy, &right.strategy);
-        self.intersection_complex(
-            &strategy,
-            &left.primitives,
+    ) -> Option<PrimitiveContact<P::Point>> {
+        collide_primitives(
+            left,
             left_transform,
-            &right.primitives,
+            right,
             right_transform,
+            |strategy, left_primitive, left_transform, right_primitive, right_transform| {
+                self.intersection(
+                    strategy,
+                    left_primitive,
+                    left_transform,
+                    right_primitive,
+                    right_transform,
+                )
+            },
         )
     }
 
@@ -118,7 +127,7 @@ where
         right: &CollisionShape<P, T>,
         right_start_transform: &T,
         right_end_transform: Option<&T>,
-    ) -> Option<Contact<P::Point>> {
+    ) -> Option<PrimitiveContact<P::Point>> {
         // fallback to start transforms if end transforms are not available
         let left_end_transform = match left_end_transform {
             Some(t) => t,
@@ -130,25 +139,218 @@ where
         };
 
         if left.mode == CollisionMode::Continuous || right.mode == CollisionMode::Continuous {
-            let strategy = max(&left.strategy, &right.strategy);
             // if the start of the transformation path has collision, return that contact
-            self.collide(left, left_start_transform, right, right_start_transform)
-                .or_else(|| {
-                    // do time of impact calculation
-                    self.intersection_complex_time_of_impact(
-                        &strategy,
-                        &left.primitives,
-                        left_start_transform..left_end_transform,
-                        &right.primitives,
-                        right_start_transform..right_end_transform,
-                    )
-                })
+            if let Some(contact) =
+                self.collide(left, left_start_transform, right, right_start_transform)
+            {
+                return Some(contact);
+            }
+            if !left.enabled || !right.enabled {
+                return None;
+            }
+
+            // do time of impact calculation
+            let strategy = max(&left.strategy, &right.strategy);
+            let mut contacts = Vec::default();
+            for (left_index, (_, left_local_transform)) in left.primitives.iter().enumerate()
+            {
+                let left_primitive = left.scaled_primitive(left_index);
+                let left_start = left_start_transform.concat(left_local_transform);
+                let left_end = left_end_transform.concat(left_local_transform);
+                for (right_index, (_, right_local_transform)) in
+                    right.primitives.iter().enumerate()
+                {
+                    let right_primitive = right.scaled_primitive(right_index);
+                    let right_start = right_start_transform.concat(right_local_transform);
+                    let right_end = right_end_transform.concat(right_local_transform);
+                    if let Some(mut contact) = self.intersection_time_of_impact(
+                        &left_primitive,
+                        &left_start..&left_end,
+                        &right_primitive,
+                        &right_start..&right_end,
+                    ) {
+                        contact.strategy = strategy.clone();
+                        if strategy == CollisionStrategy::CollisionOnly {
+                            return Some((contact, (left_index, right_index)));
+                        }
+                        contacts.push((contact, (left_index, right_index)));
+                    }
+                }
+            }
+
+            contacts.into_iter().min_by(|l, r| {
+                l.0
+                    .time_of_impact
+                    .partial_cmp(&r.0.time_of_impact)
+                    .unwrap()
+            })
         } else {
             self.collide(left, left_end_transform, right, right_end_transform)
         }
     }
 }
 
+/// Penetration between two shapes, as computed by EPA.
+///
+/// # Type parameters:
+///
+/// - `P`: cgmath point type
+#[derive(Debug, Clone)]
+pub struct Penetration<P>
+where
+    P: EuclideanSpace,
+{
+    /// Penetration depth
+    pub depth: P::Scalar,
+    /// Contact normal, pointing from the left shape towards the right shape
+    pub normal: P::Diff,
+    /// Witness point on the surface of the left shape, in world space
+    pub left_point: P,
+    /// Witness point on the surface of the right shape, in world space
+    pub right_point: P,
+}
+
+/// Compute the penetration between two shapes, using GJK to detect the intersection, and EPA to
+/// compute the penetration depth.
+///
+/// Moving the right shape by `normal * depth` will separate the shapes. Disabled shapes are
+/// ignored, as in the narrow phase.
+///
+/// # Parameters:
+///
+/// - `gjk`: the GJK algorithm to use
+/// - `left`: the left shape
+/// - `left_transform`: model-to-world transform for the left shape
+/// - `right`: the right shape
+/// - `right_transform`: model-to-world transform for the right shape
+///
+/// # Returns:
+///
+/// Optionally returns the penetration, if the shapes intersect
+pub fn penetration<P, T, S, E>(
+    gjk: &GJK<S, E>,
+    left: &CollisionShape<P, T>,
+    left_transform: &T,
+    right: &CollisionShape<P, T>,
+    right_transform: &T,
+) -> Option<Penetration<P::Point>>
+where
+    P: Primitive,
+    <P::Point as EuclideanSpace>::Diff: Debug
+        + InnerSpace
+        + Neg<Output = <P::Point as EuclideanSpace>::Diff>,
+    P::Aabb: Discrete<P::Aabb> + Aabb<Scalar = Real>,
+    S: SimplexProcessor<Point = P::Point> + Send,
+    E: EPA<Point = P::Point> + Send,
+    T: Transform<P::Point>,
+{
+    collide_primitives_with_strategy(
+        CollisionStrategy::FullResolution,
+        left,
+        left_transform,
+        right,
+        right_transform,
+        |strategy, left_primitive, left_transform, right_primitive, right_transform| {
+            gjk.intersection(
+                strategy,
+                left_primitive,
+                left_transform,
+                right_primitive,
+                right_transform,
+            )
+        },
+    ).map(|(contact, _)| Penetration {
+        depth: contact.penetration_depth,
+        normal: contact.normal,
+        left_point: contact.contact_point,
+        right_point: contact.contact_point + contact.normal * -contact.penetration_depth,
+    })
+}
+
+/// Test all pairs of primitives of two shapes using the given intersection test, and give the
+/// contact with the largest penetration depth, or the first contact found when using
+/// `CollisionOnly`
+pub(crate) fn collide_primitives<P, T, F>(
+    left: &CollisionShape<P, T>,
+    left_transform: &T,
+    right: &CollisionShape<P, T>,
+    right_transform: &T,
+    intersection: F,
+) -> Option<PrimitiveContact<P::Point>>
+where
+    P: Primitive,
+    P::Aabb: Aabb<Scalar = Real>,
+    T: Transform<P::Point>,
+    F: Fn(&CollisionStrategy, &ScaledPrimitive<P>, &T, &ScaledPrimitive<P>, &T)
+        -> Option<Contact<P::Point>>,
+{
+    let strategy = max(&left.strategy, &right.strategy);
+    collide_primitives_with_strategy(
+        strategy,
+        left,
+        left_transform,
+        right,
+        right_transform,
+        intersection,
+    )
+}
+
+fn collide_primitives_with_strategy<P, T, F>(
+    strategy: CollisionStrategy,
+    left: &CollisionShape<P, T>,
+    left_transform: &T,
+    right: &CollisionShape<P, T>,
+    right_transform: &T,
+    intersection: F,
+) -> Option<PrimitiveContact<P::Point>>
+where
+    P: Primitive,
+    P::Aabb: Aabb<Scalar = Real>,
+    T: Transform<P::Point>,
+    F: Fn(&CollisionStrategy, &ScaledPrimitive<P>, &T, &ScaledPrimitive<P>, &T)
+        -> Option<Contact<P::Point>>,
+{
+    if !left.enabled || !right.enabled || left.primitives.is_empty()
+        || right.primitives.is_empty()
+    {
+        return None;
+    }
+
+    let mut contacts = Vec::default();
+    for (left_index, (_, left_local_transform)) in left.primitives.iter().enumerate() {
+        let left_primitive = left.scaled_primitive(left_index);
+        let left_transform = left_transform.concat(left_local_transform);
+        for (right_index, (_, right_local_transform)) in right.primitives.iter().enumerate() {
+            let right_primitive = right.scaled_primitive(right_index);
+            let right_transform = right_transform.concat(right_local_transform);
+            if let Some(contact) = intersection(
+                &strategy,
+                &left_primitive,
+                &left_transform,
+                &right_primitive,
+                &right_transform,
+            ) {
+                match strategy {
+                    CollisionStrategy::CollisionOnly => {
+                        return Some((contact, (left_index, right_index)));
+                    }
+                    CollisionStrategy::FullResolution => {
+                        contacts.push((contact, (left_index, right_index)))
+                    }
+                }
+            }
+        }
+    }
+
+    // Penetration depth can't be nan from EPA, so unwrapping is safe
+    contacts.into_iter().max_by(|l, r| {
+        l.0
+            .penetration_depth
+            .partial_cmp(&r.0.penetration_depth)
+            .unwrap()
+    })
+}
+
 fn max(left: &CollisionStrategy, right: &CollisionStrategy) -> CollisionStrategy {
     if left > right {
         left.clone()
@@ -166,7 +368,7 @@ mod tests {
 
     use Real;
     use collide::*;
-    use collide::narrow::NarrowPhase;
+    use collide::narrow::{penetration, NarrowPhase};
 
     fn transform(x: Real, y: Real, angle: Real) -> Decomposed<Vector2<Real>, Basis2<Real>> {
         Decomposed {
@@ -204,7 +406,7 @@ mod tests {
             ).is_none()
         );
 
-        let contact = gjk.collide_continuous(
+        let (contact, _) = gjk.collide_continuous(
             &left,
             &left_start_transform,
             Some(&left_end_transform),
@@ -213,8 +415,124 @@ mod tests {
             Some(&right_transform),
         ).unwrap();
 
-        assert_ulps_eq!(0.16666666666666666, contact.time_of_impact);
+        assert_ulps_eq!(1. / 6., contact.time_of_impact);
 
         println!("{:?}", contact);
     }
+
+    #[test]
+    fn test_scaled_pose_2d() {
+        use cgmath::Point2;
+        use collide::prelude2d::ScaledPose2;
+
+        let mut left = CollisionShape::new_simple(
+            CollisionStrategy::FullResolution,
+            CollisionMode::Discrete,
+            Rectangle::new(10., 10.),
+        );
+        let mut right = left.clone();
+        let left_transform =
+            ScaledPose2::new(Point2::new(0., 0.), Rotation2::from_angle(Rad(0.)), 2.);
+        let right_transform =
+            ScaledPose2::new(Point2::new(14., 0.), Rotation2::from_angle(Rad(0.)), 1.);
+        left.update(&left_transform, None);
+        right.update(&right_transform, None);
+        assert_ulps_eq!(10., left.bound().max.x);
+        assert_ulps_eq!(9., right.bound().min.x);
+
+        let gjk = GJK2::<Real>::new();
+        let (contact, _) = gjk.collide(&left, &left_transform, &right, &right_transform)
+            .unwrap();
+        assert_ulps_eq!(1., contact.penetration_depth);
+    }
+
+    #[test]
+    fn test_scaled_primitive_2d() {
+        let mut left = CollisionShape::new_simple(
+            CollisionStrategy::FullResolution,
+            CollisionMode::Discrete,
+            Rectangle::new(10., 10.),
+        );
+        let mut right = left.clone();
+        left.set_primitive_scale(0, Vector2::new(2., 1.));
+        assert_ulps_eq!(Vector2::new(2., 1.), left.primitive_scale(0));
+        assert_ulps_eq!(Vector2::new(1., 1.), right.primitive_scale(0));
+
+        let left_transform = transform(0., 0., 0.);
+        let right_transform = transform(14., 2., 0.);
+        left.update(&left_transform, None);
+        right.update(&right_transform, None);
+        assert_ulps_eq!(10., left.bound().max.x);
+        assert_ulps_eq!(5., left.bound().max.y);
+
+        let gjk = GJK2::<Real>::new();
+        let (contact, _) = gjk.collide(&left, &left_transform, &right, &right_transform)
+            .unwrap();
+        assert_ulps_eq!(1., contact.penetration_depth);
+        assert!(
+            gjk.collide(&left, &left_transform, &right, &transform(0., 14., 0.))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_penetration_2d() {
+        let left = CollisionShape::new_simple(
+            CollisionStrategy::CollisionOnly,
+            CollisionMode::Discrete,
+            Rectangle::new(10., 10.),
+        );
+        let right = left.clone();
+        let gjk = GJK2::<Real>::new();
+
+        assert!(
+            penetration(
+                &gjk,
+                &left,
+                &transform(15., 0., 0.),
+                &right,
+                &transform(0., 0., 0.)
+            ).is_none()
+        );
+
+        let p = penetration(
+            &gjk,
+            &left,
+            &transform(15., 0., 0.),
+            &right,
+            &transform(7., 2., 0.),
+        ).unwrap();
+
+        assert_ulps_eq!(2., p.depth);
+        assert_ulps_eq!(Vector2::new(-1., 0.), p.normal);
+        assert_ulps_eq!(10., p.left_point.x);
+        assert_ulps_eq!(12., p.right_point.x);
+    }
+
+    #[test]
+    fn test_gjk_compound_primitive_index() {
+        let left = CollisionShape::new_complex(
+            CollisionStrategy::FullResolution,
+            CollisionMode::Discrete,
+            vec![
+                (Rectangle::new(10., 10.), transform(0., 0., 0.)),
+                (Rectangle::new(10., 10.), transform(20., 0., 0.)),
+            ],
+        );
+        let right = CollisionShape::new_simple(
+            CollisionStrategy::FullResolution,
+            CollisionMode::Discrete,
+            Rectangle::new(10., 10.),
+        );
+        let gjk = GJK2::<Real>::new();
+
+        let (_, primitives) = gjk.collide(
+            &left,
+            &transform(0., 0., 0.),
+            &right,
+            &transform(22., 1., 0.),
+        ).unwrap();
+
+        assert_eq!((1, 0), primitives);
+    }
 }
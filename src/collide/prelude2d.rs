@@ -1,6 +1,7 @@
 //! Type wrappers and convenience functions for 2D collision detection
 
 pub use collide::{CollisionMode, CollisionStrategy};
+pub use collide::heightfield::heightfield_2d;
 pub use collision::algorithm::minkowski::GJK2;
 pub use collision::primitive::{Circle, ConvexPolygon, Particle2, Rectangle};
 
@@ -8,7 +9,7 @@ use cgmath::{Basis2, Point2};
 use collision::algorithm::broad_phase::BruteForce;
 use collision::primitive::Primitive2;
 
-use {BodyPose, Real};
+use {BodyPose, Real, ScaledPose};
 use collide::*;
 
 /// Collision shape for 2D, see [CollisionShape](../collide/struct.CollisionShape.html) for more
@@ -22,5 +23,13 @@ pub type BroadBruteForce2 = BruteForce;
 /// Broad phase sweep and prune algorithm
 pub type SweepAndPrune2 = ::collision::algorithm::broad_phase::SweepAndPrune2<Real>;
 
+/// Narrow phase with the algorithm selected per pair of primitive types for 2D, see
+/// [DispatchNarrowPhase](../collide/dispatch/struct.DispatchNarrowPhase.html) for more information.
+pub type DispatchNarrowPhase2 = dispatch::DispatchNarrowPhase<GJK2<Real>>;
+
 /// Body pose transform for 2D, see [BodyPose](../struct.BodyPose.html) for more information.
 pub type BodyPose2 = BodyPose<Point2<Real>, Basis2<Real>>;
+
+/// Uniformly scaled transform for 2D, see [ScaledPose](../struct.ScaledPose.html) for more
+/// information.
+pub type ScaledPose2 = ScaledPose<Point2<Real>, Basis2<Real>>;
@@ -1,6 +1,7 @@
 //! Type wrappers and convenience functions for 3D collision detection
 
 pub use collide::{CollisionMode, CollisionStrategy};
+pub use collide::heightfield::heightfield_3d;
 pub use collision::algorithm::minkowski::GJK3;
 pub use collision::primitive::{ConvexPolyhedron, Cuboid, Particle3, Sphere};
 
@@ -8,7 +9,7 @@ use cgmath::{Point3, Quaternion};
 use collision::algorithm::broad_phase::BruteForce;
 use collision::primitive::Primitive3;
 
-use {BodyPose, Real};
+use {BodyPose, Real, ScaledPose};
 use collide::*;
 
 /// Collision shape for 3D, see [CollisionShape](../collide/struct.CollisionShape.html) for more
@@ -22,5 +23,13 @@ pub type BroadBruteForce3 = BruteForce;
 /// Broad phase sweep and prune algorithm
 pub type SweepAndPrune3 = ::collision::algorithm::broad_phase::SweepAndPrune3<Real>;
 
+/// Narrow phase with the algorithm selected per pair of primitive types for 3D, see
+/// [DispatchNarrowPhase](../collide/dispatch/struct.DispatchNarrowPhase.html) for more information.
+pub type DispatchNarrowPhase3 = dispatch::DispatchNarrowPhase<GJK3<Real>>;
+
 /// Body pose transform for 3D, see [BodyPose](../struct.BodyPose.html) for more information.
 pub type BodyPose3 = BodyPose<Point3<Real>, Quaternion<Real>>;
+
+/// Uniformly scaled transform for 3D, see [ScaledPose](../struct.ScaledPose.html) for more
+/// information.
+pub type ScaledPose3 = ScaledPose<Point3<Real>, Quaternion<Real>>;
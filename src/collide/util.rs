@@ -1,11 +1,12 @@
 //! Utility structures for use with collision detection systems
 
 use std::fmt::Debug;
+use std::vec::Vec;
 
 use cgmath::prelude::*;
-use collision::{Aabb, Primitive};
+use collision::{Aabb, Contains, Primitive, SurfaceArea, Union};
 use collision::algorithm::broad_phase::HasBound;
-use collision::dbvt::TreeValue;
+use collision::dbvt::{DynamicBoundingVolumeTree, TreeValue};
 
 use Real;
 
@@ -85,3 +86,108 @@ where
         &self.bound
     }
 }
+
+/// Tree values with an adjustable fat bound margin.
+///
+/// The fat bound is the bound of the value, grown by the margin in every direction. A larger
+/// margin means values can move further before the tree needs to be refitted, at the cost of more
+/// false positives in the broad phase.
+pub trait FatMargin {
+    /// Set the margin used when computing the fat bound
+    fn set_fat_margin(&mut self, margin: Real);
+}
+
+impl<ID, P> FatMargin for ContainerShapeWrapper<ID, P>
+where
+    P: Primitive,
+    P::Aabb: Aabb<Scalar = Real>,
+    <P::Point as EuclideanSpace>::Diff: Debug,
+{
+    fn set_fat_margin(&mut self, margin: Real) {
+        self.fat_factor = <P::Point as EuclideanSpace>::Diff::from_value(margin);
+    }
+}
+
+/// Compact a DBVT, by rebuilding it from its values.
+///
+/// After large numbers of removals, the internal node storage of the tree will contain many free
+/// slots, which are only reused on insertion. Rebuilding the tree releases that memory, and puts
+/// the nodes close together again, which improves cache locality during queries.
+///
+/// Pending updates should be processed before compacting, using `update` and `do_refit`.
+///
+/// ### Returns
+///
+/// A list of `(old node index, new node index)` pairs, one for each value in the tree, that can
+/// be used to update any stored node indices.
+pub fn compact_tree<T>(tree: &mut DynamicBoundingVolumeTree<T>) -> Vec<(usize, usize)>
+where
+    T: TreeValue,
+    T::Bound: Clone + Debug + Contains<T::Bound> + Union<T::Bound, Output = T::Bound> + SurfaceArea,
+{
+    let mut compacted = DynamicBoundingVolumeTree::new();
+    let indices = tree.values()
+        .iter()
+        .map(|&(node_index, ref value)| (node_index, compacted.insert(value.clone())))
+        .collect();
+    *tree = compacted;
+    indices
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::Point2;
+    use collision::Aabb2;
+    use collision::dbvt::DiscreteVisitor;
+
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct Value {
+        id: usize,
+        bound: Aabb2<Real>,
+    }
+
+    impl TreeValue for Value {
+        type Bound = Aabb2<Real>;
+
+        fn bound(&self) -> &Aabb2<Real> {
+            &self.bound
+        }
+
+        fn fat_bound(&self) -> Aabb2<Real> {
+            self.bound
+        }
+    }
+
+    #[test]
+    fn test_compact_tree() {
+        let mut tree = DynamicBoundingVolumeTree::new();
+        let nodes = (0..100)
+            .map(|id| {
+                let x = id as Real * 2.;
+                tree.insert(Value {
+                    id,
+                    bound: Aabb2::new(Point2::new(x, 0.), Point2::new(x + 1., 1.)),
+                })
+            })
+            .collect::<Vec<_>>();
+        for node_index in &nodes[10..] {
+            tree.remove(*node_index);
+        }
+        tree.update();
+        tree.do_refit();
+
+        let indices = compact_tree(&mut tree);
+        assert_eq!(10, indices.len());
+        assert_eq!(19, tree.size());
+
+        let bound = Aabb2::new(Point2::new(-1., -1.), Point2::new(100., 2.));
+        let mut hits = tree.query(&mut DiscreteVisitor::<_, Value>::new(&bound))
+            .iter()
+            .map(|&(v, _)| v.id)
+            .collect::<Vec<_>>();
+        hits.sort();
+        assert_eq!((0..10).collect::<Vec<_>>(), hits);
+    }
+}
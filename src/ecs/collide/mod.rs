@@ -1,9 +1,13 @@
 //! Contains collision detection components, resources and systems for use with `specs`
+pub use self::query::*;
+pub use self::reader::*;
 pub use self::resources::*;
 pub use self::systems::*;
 
 pub mod prelude2d;
 pub mod prelude3d;
 
+mod query;
+mod reader;
 mod resources;
 mod systems;
@@ -8,23 +8,51 @@ pub use collide::prelude2d::*;
 
 use std::fmt::Debug;
 
-use cgmath::{Point2, Transform};
+use cgmath::{Point2, Transform, Vector2};
+use collision::Aabb2;
 use collision::dbvt::DynamicBoundingVolumeTree;
 use collision::primitive::Primitive2;
-use specs::{Component, Entity, World};
+use specs::{Component, Entity, Join, World};
 
 use {NextFrame, Real};
 use collide::ContactEvent;
+use collide::bvh::BakedBoundingVolumeHierarchy;
 use collide::util::ContainerShapeWrapper;
-use ecs::collide::{BasicCollisionSystem, Contacts, SpatialCollisionSystem, SpatialSortingSystem};
+use ecs::collide::{Activator, ActivatorSystem, ActiveRegionSystem, ActiveRegions, Assembly,
+                   BasicCollisionSystem, BroadPhaseStats, ChunkStreaming, ChunkStreamingSystem,
+                   CollisionExclusions, CollisionOnlyChannel, CollisionWorldStats,
+                   ContactEventSettings, ContactReader, Contacts, ContinuousQuery,
+                   ContinuousQuerySystem, Dormant, Frozen, QueryShape, SilentContacts,
+                   SpatialCollisionSystem, SpatialSortingSystem, StaticBoundingVolumeTree,
+                   StaticShape, Touching, TouchingSystem, WorldId};
 
 /// Contacts resource for 2D, see [Contacts](../collide/ecs/struct.Contacts.html) for more
 /// information.
 pub type Contacts2 = Contacts<Point2<Real>>;
 
+/// Silent contacts resource for 2D, see [SilentContacts](../collide/struct.SilentContacts.html)
+/// for more information.
+pub type SilentContacts2 = SilentContacts<Point2<Real>>;
+
+/// Collision only contact event channel for 2D, see
+/// [CollisionOnlyChannel](../collide/struct.CollisionOnlyChannel.html) for more information.
+pub type CollisionOnlyChannel2 = CollisionOnlyChannel<Point2<Real>>;
+
 /// Contact event for 2D
 pub type ContactEvent2 = ContactEvent<Entity, Point2<Real>>;
 
+/// Contact event reader for 2D, see [ContactReader](../collide/ecs/struct.ContactReader.html)
+/// for more information.
+pub type ContactReader2 = ContactReader<Point2<Real>>;
+
+/// Contact state component for 2D, see [Touching](../collide/ecs/struct.Touching.html) for more
+/// information.
+pub type Touching2 = Touching<Vector2<Real>>;
+
+/// Touching system for 2D, see [TouchingSystem](../collide/ecs/struct.TouchingSystem.html) for
+/// more information.
+pub type TouchingSystem2 = TouchingSystem<Point2<Real>>;
+
 /// Basic collision system for 2D, see
 /// [BasicCollisionSystem](../collide/ecs/struct.BasicCollisionSystem.html) for more information.
 pub type BasicCollisionSystem2<T> = BasicCollisionSystem<
@@ -56,6 +84,57 @@ pub type DynamicBoundingVolumeTree2 = DynamicBoundingVolumeTree<
     ContainerShapeWrapper<Entity, Primitive2<Real>>,
 >;
 
+/// Static bounding volume tree for 2D
+pub type StaticBoundingVolumeTree2 = StaticBoundingVolumeTree<
+    ContainerShapeWrapper<Entity, Primitive2<Real>>,
+>;
+
+/// Baked bounding volume hierarchy for 2D, see
+/// [`world_bake_static_shapes`](fn.world_bake_static_shapes.html).
+pub type BakedBoundingVolumeHierarchy2 = BakedBoundingVolumeHierarchy<
+    ContainerShapeWrapper<Entity, Primitive2<Real>>,
+>;
+
+/// Chunk streaming queue for 2D, see
+/// [ChunkStreaming](../collide/ecs/struct.ChunkStreaming.html) for more information.
+pub type ChunkStreaming2<K, T> = ChunkStreaming<K, Primitive2<Real>, T>;
+
+/// Chunk streaming system for 2D, see
+/// [ChunkStreamingSystem](../collide/ecs/struct.ChunkStreamingSystem.html) for more information.
+pub type ChunkStreamingSystem2<K, T> = ChunkStreamingSystem<K, Primitive2<Real>, T>;
+
+/// Detailed query shape for 2D, see [QueryShape](../collide/ecs/struct.QueryShape.html) for more
+/// information.
+pub type QueryShape2<T> = QueryShape<Primitive2<Real>, T>;
+
+/// Continuous query for 2D, see [ContinuousQuery](../collide/ecs/struct.ContinuousQuery.html) for
+/// more information.
+pub type ContinuousQuery2 = ContinuousQuery<Primitive2<Real>>;
+
+/// Continuous query system for 2D, see
+/// [ContinuousQuerySystem](../collide/ecs/struct.ContinuousQuerySystem.html) for more information.
+pub type ContinuousQuerySystem2<T> = ContinuousQuerySystem<
+    Primitive2<Real>,
+    T,
+    ContainerShapeWrapper<Entity, Primitive2<Real>>,
+>;
+
+/// Active regions resource for 2D, see [ActiveRegions](../collide/ecs/struct.ActiveRegions.html)
+/// for more information.
+pub type ActiveRegions2 = ActiveRegions<Aabb2<Real>>;
+
+/// Active region system for 2D, see
+/// [ActiveRegionSystem](../collide/ecs/struct.ActiveRegionSystem.html) for more information.
+pub type ActiveRegionSystem2<T> = ActiveRegionSystem<Primitive2<Real>, T>;
+
+/// Activator system for 2D, see
+/// [ActivatorSystem](../collide/ecs/struct.ActivatorSystem.html) for more information.
+pub type ActivatorSystem2<T> = ActivatorSystem<
+    Point2<Real>,
+    T,
+    ContainerShapeWrapper<Entity, Primitive2<Real>>,
+>;
+
 /// Utility method for registering 2D components and resources with
 /// [`specs::World`](https://docs.rs/specs/0.9.5/specs/struct.World.html).
 ///
@@ -75,6 +154,17 @@ where
     world.register::<T>();
     world.register::<NextFrame<T>>();
     world.register::<CollisionShape2<T>>();
+    world.register::<QueryShape2<T>>();
+    world.register::<ContinuousQuery2>();
+    world.register::<StaticShape>();
+    world.register::<WorldId>();
+    world.register::<Assembly>();
+    world.register::<Frozen>();
+    world.register::<Dormant>();
+    world.register::<Activator>();
+    world.register::<Touching2>();
+    world.add_resource(ContactEventSettings::default());
+    world.add_resource(CollisionExclusions::default());
     world.add_resource(Contacts2::default());
 }
 
@@ -98,5 +188,43 @@ where
     T: Transform<Point2<Real>> + Component + Clone + Debug + Send + Sync + 'static,
 {
     world_register::<T>(&mut world);
+    world.add_resource(BroadPhaseStats::default());
+    world.add_resource(CollisionWorldStats::default());
     world.add_resource(DynamicBoundingVolumeTree2::new());
+    world.add_resource(StaticBoundingVolumeTree2::new());
+}
+
+/// Utility method for baking all shapes with the [`StaticShape`](../struct.StaticShape.html)
+/// marker into a [`BakedBoundingVolumeHierarchy2`](type.BakedBoundingVolumeHierarchy2.html),
+/// which is added as a resource in the
+/// [world](https://docs.rs/specs/0.9.5/specs/struct.World.html).
+///
+/// Should be called once at load time, after all static shapes have been created. If any static
+/// shape changes or is removed after this, the hierarchy has to be baked again.
+///
+/// # Parameters
+///
+/// - `world`: The [world](https://docs.rs/specs/0.9.5/specs/struct.World.html) to bake in.
+///
+/// # Type parameters
+///
+/// - `T`: Transform type that implements `Transform`.
+pub fn world_bake_static_shapes<T>(world: &mut World)
+where
+    T: Transform<Point2<Real>> + Component + Clone + Debug + Send + Sync + 'static,
+{
+    let values = {
+        let entities = world.entities();
+        let poses = world.read::<T>();
+        let statics = world.read::<StaticShape>();
+        let mut shapes = world.write::<CollisionShape2<T>>();
+        (&*entities, &poses, &statics, &mut shapes)
+            .join()
+            .map(|(entity, pose, _, shape)| {
+                shape.update(pose, None);
+                (entity, &*shape).into()
+            })
+            .collect()
+    };
+    world.add_resource(BakedBoundingVolumeHierarchy2::new(values));
 }
@@ -8,23 +8,51 @@ pub use collide::prelude3d::*;
 
 use std::fmt::Debug;
 
-use cgmath::{Point3, Transform};
+use cgmath::{Point3, Transform, Vector3};
+use collision::Aabb3;
 use collision::dbvt::DynamicBoundingVolumeTree;
 use collision::primitive::Primitive3;
-use specs::{Component, Entity, World};
+use specs::{Component, Entity, Join, World};
 
 use {NextFrame, Real};
 use collide::ContactEvent;
+use collide::bvh::BakedBoundingVolumeHierarchy;
 use collide::util::ContainerShapeWrapper;
-use ecs::collide::{BasicCollisionSystem, Contacts, SpatialCollisionSystem, SpatialSortingSystem};
+use ecs::collide::{Activator, ActivatorSystem, ActiveRegionSystem, ActiveRegions, Assembly,
+                   BasicCollisionSystem, BroadPhaseStats, ChunkStreaming, ChunkStreamingSystem,
+                   CollisionExclusions, CollisionOnlyChannel, CollisionWorldStats,
+                   ContactEventSettings, ContactReader, Contacts, ContinuousQuery,
+                   ContinuousQuerySystem, Dormant, Frozen, QueryShape, SilentContacts,
+                   SpatialCollisionSystem, SpatialSortingSystem, StaticBoundingVolumeTree,
+                   StaticShape, Touching, TouchingSystem, WorldId};
 
 /// Contacts resource for 3D, see [Contacts](../collide/ecs/struct.Contacts.html) for more
 /// information.
 pub type Contacts3 = Contacts<Point3<Real>>;
 
+/// Silent contacts resource for 3D, see [SilentContacts](../collide/struct.SilentContacts.html)
+/// for more information.
+pub type SilentContacts3 = SilentContacts<Point3<Real>>;
+
+/// Collision only contact event channel for 3D, see
+/// [CollisionOnlyChannel](../collide/struct.CollisionOnlyChannel.html) for more information.
+pub type CollisionOnlyChannel3 = CollisionOnlyChannel<Point3<Real>>;
+
 /// Contact event for 2D
 pub type ContactEvent3 = ContactEvent<Entity, Point3<Real>>;
 
+/// Contact event reader for 3D, see [ContactReader](../collide/ecs/struct.ContactReader.html)
+/// for more information.
+pub type ContactReader3 = ContactReader<Point3<Real>>;
+
+/// Contact state component for 3D, see [Touching](../collide/ecs/struct.Touching.html) for more
+/// information.
+pub type Touching3 = Touching<Vector3<Real>>;
+
+/// Touching system for 3D, see [TouchingSystem](../collide/ecs/struct.TouchingSystem.html) for
+/// more information.
+pub type TouchingSystem3 = TouchingSystem<Point3<Real>>;
+
 /// ECS collision system for 3D, see
 /// [BasicCollisionSystem](../collide/ecs/struct.BasicCollisionSystem.html) for more information.
 pub type BasicCollisionSystem3<T> = BasicCollisionSystem<
@@ -55,6 +83,57 @@ pub type DynamicBoundingVolumeTree3 = DynamicBoundingVolumeTree<
     ContainerShapeWrapper<Entity, Primitive3<Real>>,
 >;
 
+/// Static bounding volume tree for 3D
+pub type StaticBoundingVolumeTree3 = StaticBoundingVolumeTree<
+    ContainerShapeWrapper<Entity, Primitive3<Real>>,
+>;
+
+/// Baked bounding volume hierarchy for 3D, see
+/// [`world_bake_static_shapes`](fn.world_bake_static_shapes.html).
+pub type BakedBoundingVolumeHierarchy3 = BakedBoundingVolumeHierarchy<
+    ContainerShapeWrapper<Entity, Primitive3<Real>>,
+>;
+
+/// Chunk streaming queue for 3D, see
+/// [ChunkStreaming](../collide/ecs/struct.ChunkStreaming.html) for more information.
+pub type ChunkStreaming3<K, T> = ChunkStreaming<K, Primitive3<Real>, T>;
+
+/// Chunk streaming system for 3D, see
+/// [ChunkStreamingSystem](../collide/ecs/struct.ChunkStreamingSystem.html) for more information.
+pub type ChunkStreamingSystem3<K, T> = ChunkStreamingSystem<K, Primitive3<Real>, T>;
+
+/// Detailed query shape for 3D, see [QueryShape](../collide/ecs/struct.QueryShape.html) for more
+/// information.
+pub type QueryShape3<T> = QueryShape<Primitive3<Real>, T>;
+
+/// Continuous query for 3D, see [ContinuousQuery](../collide/ecs/struct.ContinuousQuery.html) for
+/// more information.
+pub type ContinuousQuery3 = ContinuousQuery<Primitive3<Real>>;
+
+/// Continuous query system for 3D, see
+/// [ContinuousQuerySystem](../collide/ecs/struct.ContinuousQuerySystem.html) for more information.
+pub type ContinuousQuerySystem3<T> = ContinuousQuerySystem<
+    Primitive3<Real>,
+    T,
+    ContainerShapeWrapper<Entity, Primitive3<Real>>,
+>;
+
+/// Active regions resource for 3D, see [ActiveRegions](../collide/ecs/struct.ActiveRegions.html)
+/// for more information.
+pub type ActiveRegions3 = ActiveRegions<Aabb3<Real>>;
+
+/// Active region system for 3D, see
+/// [ActiveRegionSystem](../collide/ecs/struct.ActiveRegionSystem.html) for more information.
+pub type ActiveRegionSystem3<T> = ActiveRegionSystem<Primitive3<Real>, T>;
+
+/// Activator system for 3D, see
+/// [ActivatorSystem](../collide/ecs/struct.ActivatorSystem.html) for more information.
+pub type ActivatorSystem3<T> = ActivatorSystem<
+    Point3<Real>,
+    T,
+    ContainerShapeWrapper<Entity, Primitive3<Real>>,
+>;
+
 /// Utility method for registering 3D components and resources with
 /// [`specs::World`](https://docs.rs/specs/0.9.5/specs/struct.World.html).
 ///
@@ -74,6 +153,17 @@ where
     world.register::<T>();
     world.register::<NextFrame<T>>();
     world.register::<CollisionShape3<T>>();
+    world.register::<QueryShape3<T>>();
+    world.register::<ContinuousQuery3>();
+    world.register::<StaticShape>();
+    world.register::<WorldId>();
+    world.register::<Assembly>();
+    world.register::<Frozen>();
+    world.register::<Dormant>();
+    world.register::<Activator>();
+    world.register::<Touching3>();
+    world.add_resource(ContactEventSettings::default());
+    world.add_resource(CollisionExclusions::default());
     world.add_resource(Contacts3::default());
 }
 
@@ -97,5 +187,43 @@ where
     T: Transform<Point3<Real>> + Component + Clone + Debug + Send + Sync + 'static,
 {
     world_register::<T>(&mut world);
+    world.add_resource(BroadPhaseStats::default());
+    world.add_resource(CollisionWorldStats::default());
     world.add_resource(DynamicBoundingVolumeTree3::new());
+    world.add_resource(StaticBoundingVolumeTree3::new());
+}
+
+/// Utility method for baking all shapes with the [`StaticShape`](../struct.StaticShape.html)
+/// marker into a [`BakedBoundingVolumeHierarchy3`](type.BakedBoundingVolumeHierarchy3.html),
+/// which is added as a resource in the
+/// [world](https://docs.rs/specs/0.9.5/specs/struct.World.html).
+///
+/// Should be called once at load time, after all static shapes have been created. If any static
+/// shape changes or is removed after this, the hierarchy has to be baked again.
+///
+/// # Parameters
+///
+/// - `world`: The [world](https://docs.rs/specs/0.9.5/specs/struct.World.html) to bake in.
+///
+/// # Type parameters
+///
+/// - `T`: Transform type that implements `Transform`.
+pub fn world_bake_static_shapes<T>(world: &mut World)
+where
+    T: Transform<Point3<Real>> + Component + Clone + Debug + Send + Sync + 'static,
+{
+    let values = {
+        let entities = world.entities();
+        let poses = world.read::<T>();
+        let statics = world.read::<StaticShape>();
+        let mut shapes = world.write::<CollisionShape3<T>>();
+        (&*entities, &poses, &statics, &mut shapes)
+            .join()
+            .map(|(entity, pose, _, shape)| {
+                shape.update(pose, None);
+                (entity, &*shape).into()
+            })
+            .collect()
+    };
+    world.add_resource(BakedBoundingVolumeHierarchy3::new(values));
 }
@@ -0,0 +1,343 @@
+use std::cmp::Ordering;
+use std::fmt::Debug;
+
+use cgmath::prelude::*;
+use collision::Ray;
+use collision::dbvt::{ContinuousVisitor, DiscreteVisitor, DynamicBoundingVolumeTree, TreeValue};
+use collision::prelude::*;
+use specs::{Component, Entity, ReadStorage};
+
+use Real;
+use collide::{CollisionMode, CollisionShape, CollisionStrategy, Primitive};
+use collide::narrow::NarrowPhase;
+use ecs::collide::resources::{in_world, query_shape, GetEntity, QueryShape, WorldId};
+
+/// Find all entities with a collision shape that overlaps the given primitive.
+///
+/// The primitive is not attached to any entity, which makes this useful for validating spawn
+/// points, placement previews and range checks. Candidates are found using the given bounding
+/// volume tree, and then tested using the narrow phase. Entities with a
+/// [`QueryShape`](struct.QueryShape.html) are tested using the detailed shape.
+///
+/// Static shapes can be found by calling this function with the
+/// [`StaticBoundingVolumeTree`](struct.StaticBoundingVolumeTree.html) as well.
+///
+/// # Parameters:
+///
+/// - `primitive`: The collision primitive to test for overlaps with
+/// - `transform`: Model-to-world transform for the primitive
+/// - `tree`: Bounding volume tree containing the collision world
+/// - `shapes`: Collision shape storage
+/// - `details`: Optional detailed query shape storage
+/// - `poses`: Transform storage
+/// - `world`: Optional simulation world, with the world id storage, only entities in the world
+///   are found. Without a world, entities in all worlds are found.
+/// - `narrow`: Narrow phase algorithm used for testing the candidates
+///
+/// # Type parameters:
+///
+/// - `P`: Primitive type, needs to implement `Primitive`.
+/// - `T`: Transform type, needs to implement `Transform`.
+/// - `D`: Type of values stored in the DBVT
+/// - `N`: Narrow phase type
+#[allow(clippy::too_many_arguments)]
+pub fn query_overlap<P, T, D, N>(
+    primitive: P,
+    transform: &T,
+    tree: &DynamicBoundingVolumeTree<D>,
+    shapes: &ReadStorage<CollisionShape<P, T>>,
+    details: Option<&ReadStorage<QueryShape<P, T>>>,
+    poses: &ReadStorage<T>,
+    world: Option<(WorldId, &ReadStorage<WorldId>)>,
+    narrow: &N,
+) -> Vec<Entity>
+where
+    P: Primitive + Send + Sync + 'static,
+    P::Aabb: Aabb<Scalar = Real>
+        + Send
+        + Sync
+        + 'static
+        + Clone
+        + Debug
+        + Discrete<P::Aabb>
+        + Contains<P::Aabb>
+        + Union<P::Aabb, Output = P::Aabb>
+        + SurfaceArea<Scalar = Real>,
+    <P::Point as EuclideanSpace>::Diff: Debug,
+    T: Component + Transform<P::Point> + Send + Sync + 'static,
+    D: TreeValue<Bound = P::Aabb> + GetEntity,
+    N: NarrowPhase<P, T> + ?Sized,
+{
+    let mut shape = CollisionShape::new_simple(
+        CollisionStrategy::CollisionOnly,
+        CollisionMode::Discrete,
+        primitive,
+    );
+    shape.update(transform, None);
+    tree.query(&mut DiscreteVisitor::<P::Aabb, D>::new(shape.bound()))
+        .into_iter()
+        .map(|(value, _)| value.entity())
+        .filter(|&entity| in_query_world(world, entity))
+        .filter(|&entity| match (query_shape(entity, shapes, details), poses.get(entity)) {
+            (Some(other), Some(pose)) => narrow.collide(&shape, transform, other, pose).is_some(),
+            _ => false,
+        })
+        .collect()
+}
+
+/// Check if an entity is in the simulation world of a query, if any
+fn in_query_world(world: Option<(WorldId, &ReadStorage<WorldId>)>, entity: Entity) -> bool {
+    world
+        .map(|(world, ids)| in_world(ids, world, entity))
+        .unwrap_or(true)
+}
+
+/// Result of a [`query_sweep`](fn.query_sweep.html)
+#[derive(Debug, Clone, Default)]
+pub struct SweepResult {
+    /// Entities hit along the path, with the time of impact in the range [0, 1], earliest first
+    pub path: Vec<(Entity, Real)>,
+    /// Entities that overlap the primitive at the destination
+    pub destination: Vec<Entity>,
+}
+
+impl SweepResult {
+    /// Is the path from start to destination free
+    pub fn is_path_free(&self) -> bool {
+        self.path.is_empty()
+    }
+
+    /// Is the destination free
+    pub fn is_destination_free(&self) -> bool {
+        self.destination.is_empty()
+    }
+
+    /// Are both the path and the destination free
+    pub fn is_free(&self) -> bool {
+        self.is_path_free() && self.is_destination_free()
+    }
+
+    /// Get the first entity hit along the path, with the time of impact
+    pub fn first_hit(&self) -> Option<(Entity, Real)> {
+        self.path.first().cloned()
+    }
+}
+
+/// Sweep the given primitive from a start transform to an end transform, and find all entities
+/// with a collision shape that blocks the path or the destination.
+///
+/// The primitive is not attached to any entity, which makes this useful for spawn placement,
+/// building placement previews and reachability checks. Use a `Rectangle` or `Cuboid` primitive to
+/// sweep an axis aligned box. Candidates are found using the bounding box of the whole path in the
+/// given bounding volume tree, and then tested using the continuous narrow phase for the path, and
+/// the discrete narrow phase for the destination. Shapes in the collision world are tested at
+/// their current pose, using the detailed [`QueryShape`](struct.QueryShape.html) if present.
+///
+/// # Parameters:
+///
+/// - `primitive`: The collision primitive to sweep
+/// - `path`: Model-to-world transforms for the primitive at the start of the path, and at the
+///   destination
+/// - `tree`: Bounding volume tree containing the collision world
+/// - `shapes`: Collision shape storage
+/// - `details`: Optional detailed query shape storage
+/// - `poses`: Transform storage
+/// - `world`: Optional simulation world, with the world id storage, only entities in the world
+///   are found. Without a world, entities in all worlds are found.
+/// - `narrow`: Narrow phase algorithm used for testing the candidates
+///
+/// # Type parameters:
+///
+/// - `P`: Primitive type, needs to implement `Primitive`.
+/// - `T`: Transform type, needs to implement `Transform`.
+/// - `D`: Type of values stored in the DBVT
+/// - `N`: Narrow phase type
+#[allow(clippy::too_many_arguments)]
+pub fn query_sweep<P, T, D, N>(
+    primitive: P,
+    path: (&T, &T),
+    tree: &DynamicBoundingVolumeTree<D>,
+    shapes: &ReadStorage<CollisionShape<P, T>>,
+    details: Option<&ReadStorage<QueryShape<P, T>>>,
+    poses: &ReadStorage<T>,
+    world: Option<(WorldId, &ReadStorage<WorldId>)>,
+    narrow: &N,
+) -> SweepResult
+where
+    P: Primitive + Send + Sync + 'static,
+    P::Aabb: Aabb<Scalar = Real>
+        + Send
+        + Sync
+        + 'static
+        + Clone
+        + Debug
+        + Discrete<P::Aabb>
+        + Contains<P::Aabb>
+        + Union<P::Aabb, Output = P::Aabb>
+        + SurfaceArea<Scalar = Real>,
+    <P::Point as EuclideanSpace>::Diff: Debug,
+    T: Component + Transform<P::Point> + Send + Sync + 'static,
+    D: TreeValue<Bound = P::Aabb> + GetEntity,
+    N: NarrowPhase<P, T>,
+{
+    let mut shape = CollisionShape::new_simple(
+        CollisionStrategy::CollisionOnly,
+        CollisionMode::Continuous,
+        primitive,
+    );
+    let (start, end) = path;
+    shape.update(start, Some(end));
+    let mut result = SweepResult::default();
+    for (value, _) in tree.query(&mut DiscreteVisitor::<P::Aabb, D>::new(shape.bound())) {
+        let entity = value.entity();
+        if !in_query_world(world, entity) {
+            continue;
+        }
+        let (other, pose) = match (query_shape(entity, shapes, details), poses.get(entity)) {
+            (Some(other), Some(pose)) => (other, pose),
+            _ => continue,
+        };
+        if let Some((contact, _)) =
+            narrow.collide_continuous(&shape, start, Some(end), other, pose, None)
+        {
+            result.path.push((entity, contact.time_of_impact));
+        }
+        if narrow.collide(&shape, end, other, pose).is_some() {
+            result.destination.push(entity);
+        }
+    }
+    result
+        .path
+        .sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
+    result
+}
+
+/// Cast a ray into the collision world, and find the closest entity hit.
+///
+/// Candidates are found using the given bounding volume tree, and then tested against each
+/// primitive of their shape. Entities with a [`QueryShape`](struct.QueryShape.html) are tested
+/// using the detailed shape instead of the proxy shape used for contacts.
+///
+/// # Parameters:
+///
+/// - `ray`: The ray, in world space coordinates
+/// - `tree`: Bounding volume tree containing the collision world
+/// - `shapes`: Collision shape storage
+/// - `details`: Optional detailed query shape storage
+/// - `poses`: Transform storage
+/// - `world`: Optional simulation world, with the world id storage, only entities in the world
+///   are found. Without a world, entities in all worlds are found.
+///
+/// # Returns:
+///
+/// The closest entity hit, and the hit point in world space coordinates.
+///
+/// # Type parameters:
+///
+/// - `P`: Primitive type, needs to implement `Primitive`.
+/// - `T`: Transform type, needs to implement `Transform`.
+/// - `D`: Type of values stored in the DBVT
+/// - `Q`: Point type of the primitive, `Point2` or `Point3`
+pub fn query_ray<P, T, D, Q>(
+    ray: &Ray<Real, Q, Q::Diff>,
+    tree: &DynamicBoundingVolumeTree<D>,
+    shapes: &ReadStorage<CollisionShape<P, T>>,
+    details: Option<&ReadStorage<QueryShape<P, T>>>,
+    poses: &ReadStorage<T>,
+    world: Option<(WorldId, &ReadStorage<WorldId>)>,
+) -> Option<(Entity, Q)>
+where
+    Q: EuclideanSpace<Scalar = Real>,
+    Q::Diff: Debug + InnerSpace,
+    P: Primitive
+        + ContinuousTransformed<
+            Ray<Real, Q, Q::Diff>,
+            Point = Q,
+            Result = Q,
+        >
+        + Send
+        + Sync
+        + 'static,
+    P::Aabb: Aabb<Scalar = Real, Point = Q>
+        + Send
+        + Sync
+        + 'static
+        + Clone
+        + Debug
+        + Continuous<Ray<Real, Q, Q::Diff>>
+        + Discrete<Ray<Real, Q, Q::Diff>>
+        + Contains<P::Aabb>
+        + Union<P::Aabb, Output = P::Aabb>
+        + SurfaceArea<Scalar = Real>,
+    T: Component + Transform<Q> + Send + Sync + 'static,
+    D: TreeValue<Bound = P::Aabb> + GetEntity,
+{
+    closest_ray_hit(ray, tree, shapes, details, poses, |entity| {
+        in_query_world(world, entity)
+    })
+        .map(|(entity, point, _)| (entity, point))
+}
+
+/// Find the closest entity hit by a ray, among the entities accepted by the filter.
+///
+/// # Returns:
+///
+/// The closest entity hit, the hit point in world space coordinates, and the squared distance from
+/// the ray origin to the hit point.
+pub(crate) fn closest_ray_hit<P, T, D, Q, F>(
+    ray: &Ray<Real, Q, Q::Diff>,
+    tree: &DynamicBoundingVolumeTree<D>,
+    shapes: &ReadStorage<CollisionShape<P, T>>,
+    details: Option<&ReadStorage<QueryShape<P, T>>>,
+    poses: &ReadStorage<T>,
+    filter: F,
+) -> Option<(Entity, Q, Real)>
+where
+    Q: EuclideanSpace<Scalar = Real>,
+    Q::Diff: Debug + InnerSpace,
+    P: Primitive
+        + ContinuousTransformed<
+            Ray<Real, Q, Q::Diff>,
+            Point = Q,
+            Result = Q,
+        >
+        + Send
+        + Sync
+        + 'static,
+    P::Aabb: Aabb<Scalar = Real, Point = Q>
+        + Send
+        + Sync
+        + 'static
+        + Clone
+        + Debug
+        + Continuous<Ray<Real, Q, Q::Diff>>
+        + Discrete<Ray<Real, Q, Q::Diff>>
+        + Contains<P::Aabb>
+        + Union<P::Aabb, Output = P::Aabb>
+        + SurfaceArea<Scalar = Real>,
+    T: Component + Transform<Q> + Send + Sync + 'static,
+    D: TreeValue<Bound = P::Aabb> + GetEntity,
+    F: Fn(Entity) -> bool,
+{
+    let mut closest: Option<(Entity, Q, Real)> = None;
+    for (value, _) in tree.query(&mut ContinuousVisitor::<Ray<Real, Q, Q::Diff>, D>::new(ray)) {
+        let entity = value.entity();
+        if !filter(entity) {
+            continue;
+        }
+        let (shape, pose) = match (query_shape(entity, shapes, details), poses.get(entity)) {
+            (Some(shape), Some(pose)) if shape.enabled => (shape, pose),
+            _ => continue,
+        };
+        for (primitive, local_transform) in shape.primitives() {
+            let transform = pose.concat(local_transform);
+            if let Some(point) = primitive.intersection_transformed(ray, &transform) {
+                let distance = (point - ray.origin).magnitude2();
+                if closest.map(|(_, _, d)| distance < d).unwrap_or(true) {
+                    closest = Some((entity, point, distance));
+                }
+            }
+        }
+    }
+    closest
+}
@@ -0,0 +1,183 @@
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::mem;
+
+use cgmath::prelude::*;
+use shrev::{EventChannel, ReaderId};
+use specs::Entity;
+
+use Real;
+use collide::{CollisionStrategy, ContactEvent};
+
+/// Reader for contact events, with filtering.
+///
+/// Wraps a `ReaderId` for the contact event channel, and only returns the events that match the
+/// configured filters. Filters can be set on an entity, a shape tag, which can be used as a
+/// collision layer, and a collision strategy. All filters that are set must match.
+///
+/// The reader also keeps track of which pairs of entities were in contact on the previous read,
+/// so contacts that started or ended since then can be singled out. This assumes that the reader
+/// is read exactly once per frame.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+pub struct ContactReader<P>
+where
+    P: EuclideanSpace + 'static,
+    P::Diff: Debug,
+{
+    reader: ReaderId<ContactEvent<Entity, P>>,
+    entity: Option<Entity>,
+    tag: Option<u64>,
+    strategy: Option<CollisionStrategy>,
+    previous: HashSet<(Entity, Entity)>,
+    current: HashSet<(Entity, Entity)>,
+    ended: Vec<(Entity, Entity)>,
+}
+
+impl<P> ContactReader<P>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: Debug + Send + Sync + 'static,
+{
+    /// Create a new contact reader, registered with the given contact event channel
+    pub fn new(channel: &mut EventChannel<ContactEvent<Entity, P>>) -> Self {
+        Self {
+            reader: channel.register_reader(),
+            entity: None,
+            tag: None,
+            strategy: None,
+            previous: HashSet::default(),
+            current: HashSet::default(),
+            ended: Vec::default(),
+        }
+    }
+
+    /// Only return contacts involving the given entity
+    pub fn with_entity(mut self, entity: Entity) -> Self {
+        self.entity = Some(entity);
+        self
+    }
+
+    /// Only return contacts where either shape has the given tag, see
+    /// [`CollisionShape::with_tag`](../collide/struct.CollisionShape.html#method.with_tag)
+    pub fn with_tag(mut self, tag: u64) -> Self {
+        self.tag = Some(tag);
+        self
+    }
+
+    /// Only return contacts generated with the given collision strategy
+    pub fn with_strategy(mut self, strategy: CollisionStrategy) -> Self {
+        self.strategy = Some(strategy);
+        self
+    }
+
+    /// Check if a contact event matches the filters of the reader
+    pub fn matches(&self, event: &ContactEvent<Entity, P>) -> bool {
+        self.entity
+            .map(|e| event.bodies.0 == e || event.bodies.1 == e)
+            .unwrap_or(true)
+            && self.tag
+                .map(|t| event.tags.0 == Some(t) || event.tags.1 == Some(t))
+                .unwrap_or(true)
+            && self.strategy
+                .as_ref()
+                .map(|s| event.contact.strategy == *s)
+                .unwrap_or(true)
+    }
+
+    /// Read all new contact events from the channel.
+    ///
+    /// # Parameters:
+    ///
+    /// - `channel`: The contact event channel the reader was registered with
+    ///
+    /// # Returns:
+    ///
+    /// The new contact events that match the filters.
+    pub fn read<'a>(
+        &'a mut self,
+        channel: &'a EventChannel<ContactEvent<Entity, P>>,
+    ) -> ContactReads<'a, P> {
+        mem::swap(&mut self.previous, &mut self.current);
+        self.current.clear();
+        let mut events = Vec::default();
+        let mut started = Vec::default();
+        for event in channel.lossy_read(&mut self.reader) {
+            if !self.matches(event) {
+                continue;
+            }
+            let pair = pair_key(event.bodies);
+            if self.current.insert(pair) && !self.previous.contains(&pair) {
+                started.push(event);
+            }
+            events.push(event);
+        }
+        let current = &self.current;
+        self.ended.clear();
+        self.ended.extend(
+            self.previous
+                .iter()
+                .filter(|pair| !current.contains(pair))
+                .cloned(),
+        );
+        ContactReads {
+            events,
+            started,
+            ended: &self.ended,
+        }
+    }
+}
+
+/// Contact pairs are tracked independently of the order of the bodies in the event
+fn pair_key((left, right): (Entity, Entity)) -> (Entity, Entity) {
+    if left < right {
+        (left, right)
+    } else {
+        (right, left)
+    }
+}
+
+/// Contact events returned by a single read of a
+/// [`ContactReader`](struct.ContactReader.html)
+pub struct ContactReads<'a, P>
+where
+    P: EuclideanSpace + 'a,
+    P::Diff: Debug,
+{
+    events: Vec<&'a ContactEvent<Entity, P>>,
+    started: Vec<&'a ContactEvent<Entity, P>>,
+    ended: &'a [(Entity, Entity)],
+}
+
+impl<'a, P> ContactReads<'a, P>
+where
+    P: EuclideanSpace + 'a,
+    P::Diff: Debug,
+{
+    /// Get all contact events that matched the filters
+    pub fn events(&self) -> &[&'a ContactEvent<Entity, P>] {
+        &self.events
+    }
+
+    /// Get the contact events for pairs that were not in contact on the previous read
+    pub fn started(&self) -> &[&'a ContactEvent<Entity, P>] {
+        &self.started
+    }
+
+    /// Get the pairs that were in contact on the previous read, but are not any longer
+    pub fn ended(&self) -> &'a [(Entity, Entity)] {
+        self.ended
+    }
+
+    /// Number of contact events that matched the filters
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Check if no contact events matched the filters
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
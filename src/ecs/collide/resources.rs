@@ -1,11 +1,15 @@
+use std::collections::{HashMap, VecDeque};
 use std::fmt::Debug;
+use std::hash::Hash;
 use std::ops::{Deref, DerefMut};
 
 use cgmath::prelude::*;
-use collision::{Aabb, Primitive};
-use specs::{Component, DenseVecStorage, Entity, FlaggedStorage};
+use collision::{Aabb, CollisionStrategy, Contains, Discrete, Primitive, SurfaceArea, Union};
+use collision::dbvt::{DynamicBoundingVolumeTree, TreeValue};
+use shrev::{EventChannel, ReaderId};
+use specs::{Component, DenseVecStorage, Entity, Fetch, FlaggedStorage, NullStorage, ReadStorage};
 
-use {BodyPose, NextFrame, Real};
+use {BodyPose, NextFrame, Real, ScaledPose};
 use collide::{CollisionShape, ContactEvent};
 use collide::util::ContainerShapeWrapper;
 
@@ -17,6 +21,15 @@ where
     type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
 }
 
+impl<P, R> Component for ScaledPose<P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: Send + Sync + 'static,
+    R: Rotation<P> + Send + Sync + 'static,
+{
+    type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+}
+
 impl<T> Component for NextFrame<T>
 where
     T: Send + Sync + 'static,
@@ -36,7 +49,771 @@ where
     P: Primitive + Send + Sync + 'static,
     P::Aabb: Send + Sync + 'static,
 {
-    type Storage = DenseVecStorage<CollisionShape<P, T>>;
+    type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+}
+
+/// Detailed collision shape, only used for queries.
+///
+/// An entity can have a cheap proxy [`CollisionShape`](../collide/struct.CollisionShape.html),
+/// e.g. a convex hull, that is used for the broad phase and for solver contacts, and a detailed
+/// shape, e.g. a compound of many primitives, that is used instead of the proxy by
+/// [`query_ray`](fn.query_ray.html), [`query_overlap`](fn.query_overlap.html) and
+/// [`query_sweep`](fn.query_sweep.html). This is a common setup for characters and vehicles.
+///
+/// The bounding volume trees only contain the bounds of the proxy shapes, so the detailed shape
+/// must be contained in the bound of the proxy shape.
+#[derive(Debug, Clone)]
+pub struct QueryShape<P, T>(pub CollisionShape<P, T>)
+where
+    P: Primitive;
+
+impl<P, T> QueryShape<P, T>
+where
+    P: Primitive,
+{
+    /// Create a new query shape
+    pub fn new(shape: CollisionShape<P, T>) -> Self {
+        QueryShape(shape)
+    }
+}
+
+impl<P, T> Component for QueryShape<P, T>
+where
+    T: Send + Sync + 'static,
+    P: Primitive + Send + Sync + 'static,
+    P::Aabb: Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<QueryShape<P, T>>;
+}
+
+/// Get the shape used for queries for an entity, the detailed shape if present, and the proxy
+/// shape otherwise
+pub(crate) fn query_shape<'a, P, T>(
+    entity: Entity,
+    shapes: &'a ReadStorage<CollisionShape<P, T>>,
+    details: Option<&'a ReadStorage<QueryShape<P, T>>>,
+) -> Option<&'a CollisionShape<P, T>>
+where
+    T: Send + Sync + 'static,
+    P: Primitive + Send + Sync + 'static,
+    P::Aabb: Send + Sync + 'static,
+{
+    details
+        .and_then(|details| details.get(entity))
+        .map(|detail| &detail.0)
+        .or_else(|| shapes.get(entity))
+}
+
+/// Shape of a [`ContinuousQuery`](struct.ContinuousQuery.html), in model space of the entity the
+/// query is attached to
+#[derive(Debug, Clone)]
+pub enum QueryVolume<P>
+where
+    P: Primitive,
+    <P::Point as EuclideanSpace>::Diff: Debug,
+{
+    /// Ray cast from the origin along the direction, optionally limited to a maximum distance
+    Ray {
+        /// Origin of the ray
+        origin: P::Point,
+        /// Direction of the ray
+        direction: <P::Point as EuclideanSpace>::Diff,
+        /// Maximum distance of hits from the origin
+        max_distance: Option<Real>,
+    },
+    /// Volume that is tested for overlaps
+    Volume(P),
+}
+
+/// Persistent query, attached to an entity.
+///
+/// Evaluated every frame by the [`ContinuousQuerySystem`](struct.ContinuousQuerySystem.html),
+/// using the current pose of the entity, and the results are kept in the component until the next
+/// evaluation. This replaces immediate mode queries, using [`query_ray`](fn.query_ray.html) or
+/// [`query_overlap`](fn.query_overlap.html), issued from many systems, e.g. for ground probes,
+/// line of sight checks and trigger areas. The entity the query is attached to is never part of
+/// the results.
+///
+/// # Type parameters:
+///
+/// - `P`: Primitive type, needs to implement `Primitive`.
+#[derive(Debug, Clone)]
+pub struct ContinuousQuery<P>
+where
+    P: Primitive,
+    <P::Point as EuclideanSpace>::Diff: Debug,
+{
+    /// Shape of the query
+    pub volume: QueryVolume<P>,
+    pub(crate) hit: Option<(Entity, P::Point)>,
+    pub(crate) overlaps: Vec<Entity>,
+}
+
+impl<P> ContinuousQuery<P>
+where
+    P: Primitive,
+    <P::Point as EuclideanSpace>::Diff: Debug,
+{
+    /// Create a new persistent ray query.
+    ///
+    /// # Parameters:
+    ///
+    /// - `origin`: Origin of the ray, in model space
+    /// - `direction`: Direction of the ray, in model space
+    pub fn ray(origin: P::Point, direction: <P::Point as EuclideanSpace>::Diff) -> Self {
+        Self::new(QueryVolume::Ray {
+            origin,
+            direction,
+            max_distance: None,
+        })
+    }
+
+    /// Create a new persistent overlap query, with the primitive in model space
+    pub fn volume(primitive: P) -> Self {
+        Self::new(QueryVolume::Volume(primitive))
+    }
+
+    fn new(volume: QueryVolume<P>) -> Self {
+        Self {
+            volume,
+            hit: None,
+            overlaps: Vec::default(),
+        }
+    }
+
+    /// Limit the distance of ray hits, builder pattern. Does nothing for volume queries.
+    pub fn with_max_distance(mut self, distance: Real) -> Self {
+        if let QueryVolume::Ray {
+            ref mut max_distance,
+            ..
+        } = self.volume
+        {
+            *max_distance = Some(distance);
+        }
+        self
+    }
+
+    /// Get the closest entity hit by a ray query, with the hit point in world space coordinates
+    pub fn hit(&self) -> Option<(Entity, P::Point)> {
+        self.hit
+    }
+
+    /// Get the entities overlapping a volume query
+    pub fn overlaps(&self) -> &[Entity] {
+        &self.overlaps
+    }
+
+    /// Check if the query found nothing in the last evaluation
+    pub fn is_empty(&self) -> bool {
+        self.hit.is_none() && self.overlaps.is_empty()
+    }
+}
+
+impl<P> Component for ContinuousQuery<P>
+where
+    P: Primitive + Send + Sync + 'static,
+    P::Point: Send + Sync + 'static,
+    <P::Point as EuclideanSpace>::Diff: Debug + Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<ContinuousQuery<P>>;
+}
+
+/// Identifier of the simulation world an entity belongs to.
+///
+/// Multiple independent simulation worlds can live in the same specs `World`, e.g. for server
+/// instances or background simulations. Entities only collide and interact with entities in the
+/// same simulation world: pairs of shapes in different worlds are never tested, and joints,
+/// springs and constraints between bodies in different worlds are ignored. Entities without a
+/// `WorldId` are in the default world, `WorldId(0)`.
+///
+/// Queries only find entities in a given world when they are given the world, e.g.
+/// [`query_overlap`](fn.query_overlap.html). A single world can be stepped on its own, with its
+/// own `DeltaTime`, using the [`SteppedWorld`](struct.SteppedWorld.html) resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct WorldId(pub u32);
+
+impl Component for WorldId {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Resource that restricts a step to a single simulation world.
+///
+/// When present, the collision and physics systems only step the bodies in the given world, see
+/// [`WorldId`](struct.WorldId.html). Bodies in other worlds are handled like
+/// [`Frozen`](struct.Frozen.html) bodies: their shapes are not tested, the solvers do not move
+/// them and keep their velocities, and any forces added to them are dropped. Joints, springs and
+/// controllers, e.g. PID controllers and path followers, in other worlds are not advanced. This
+/// way a world, e.g. a preview or AI planning world, can be stepped on its own, by setting this
+/// resource and the `DeltaTime`, and running the systems once per world. Without the resource,
+/// all worlds are stepped together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SteppedWorld(pub WorldId);
+
+/// Check if an entity is in the given simulation world
+pub(crate) fn in_world(ids: &ReadStorage<WorldId>, world: WorldId, entity: Entity) -> bool {
+    ids.get(entity).cloned().unwrap_or_default() == world
+}
+
+/// Check if an entity is stepped, i.e. no world is stepped on its own, or the entity is in the
+/// stepped world
+pub(crate) fn is_stepped(
+    stepped: Option<&SteppedWorld>,
+    ids: &ReadStorage<WorldId>,
+    entity: Entity,
+) -> bool {
+    stepped
+        .map(|stepped| in_world(ids, stepped.0, entity))
+        .unwrap_or(true)
+}
+
+/// Identifier of the articulated assembly a body belongs to, e.g. the parts of a ragdoll or a
+/// vehicle that are connected by joints.
+///
+/// Parts of the same assembly never collide with each other, and are skipped in the broad phase.
+/// The spatial collision system keeps a coarse bounding volume per assembly, covering all parts,
+/// that is only refit and queried when a part changes. The hits are then tested against the
+/// individual parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Assembly(pub u32);
+
+impl Component for Assembly {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Marker for bodies that are frozen, e.g. when off-screen or during a cutscene.
+///
+/// A frozen body is removed from the simulation, without removing any of its components. The
+/// contact solver never moves a frozen body, and keeps its velocity for when it is unfrozen.
+/// Forces accumulated while frozen are discarded. By default, a frozen body still collides, and
+/// acts as an immovable obstacle for other bodies. Use
+/// [`without_collision`](#method.without_collision) to also remove it from collision detection.
+#[derive(Debug, Clone)]
+pub struct Frozen {
+    /// Does the frozen body still take part in collision detection
+    pub collide: bool,
+}
+
+impl Frozen {
+    /// Freeze a body, that still takes part in collision detection
+    pub fn new() -> Self {
+        Self { collide: true }
+    }
+
+    /// Freeze a body, and remove it from collision detection
+    pub fn without_collision() -> Self {
+        Self { collide: false }
+    }
+}
+
+impl Default for Frozen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Component for Frozen {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Check if the entity takes part in collision detection, i.e. is not frozen without collision
+pub(crate) fn collides(frozen: &ReadStorage<Frozen>, entity: Entity) -> bool {
+    frozen.get(entity).map(|f| f.collide).unwrap_or(true)
+}
+
+/// Marker for bodies outside all [`ActiveRegions`](struct.ActiveRegions.html), or sleeping.
+///
+/// Maintained by the [`ActiveRegionSystem`](struct.ActiveRegionSystem.html), or by the contact
+/// solvers when they put resting islands to sleep, and should not be added manually. A dormant body
+/// is treated like a [`Frozen`](struct.Frozen.html) body by the contact solver, pairs of dormant
+/// bodies are never collided, and the spatial sorting system leaves the tree leaves of dormant
+/// bodies untouched. A dormant body still acts as an immovable obstacle for active bodies.
+#[derive(Debug, Clone, Default)]
+pub struct Dormant;
+
+impl Component for Dormant {
+    type Storage = NullStorage<Self>;
+}
+
+/// Check if a pair of entities should be collided, i.e. at least one of them is not dormant
+pub(crate) fn any_active(dormant: &ReadStorage<Dormant>, left: Entity, right: Entity) -> bool {
+    dormant.get(left).is_none() || dormant.get(right).is_none()
+}
+
+/// Entity that wakes up [`Dormant`](struct.Dormant.html) bodies around it, e.g. a player or an
+/// explosion.
+///
+/// Maintained by the [`ActivatorSystem`](struct.ActivatorSystem.html), which wakes all bodies
+/// within the radius of the position of the activator, even outside the
+/// [`ActiveRegions`](struct.ActiveRegions.html).
+#[derive(Debug, Clone)]
+pub struct Activator {
+    /// Wake radius
+    pub radius: Real,
+}
+
+impl Activator {
+    /// Create a new activator with the given wake radius
+    pub fn new(radius: Real) -> Self {
+        Self { radius }
+    }
+}
+
+impl Component for Activator {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Regions of interest for simulation culling in large worlds, e.g. around the players.
+///
+/// If present, the [`ActiveRegionSystem`](struct.ActiveRegionSystem.html) marks all bodies with a
+/// collision shape that is outside all regions as [`Dormant`](struct.Dormant.html). Without this
+/// resource all bodies are active. The regions are expected to be updated by the game, as the
+/// players move.
+///
+/// # Type parameters:
+///
+/// - `B`: Bounding volume type, usually `Aabb2` or `Aabb3`
+#[derive(Debug, Clone)]
+pub struct ActiveRegions<B> {
+    regions: Vec<B>,
+}
+
+impl<B> ActiveRegions<B>
+where
+    B: Aabb<Scalar = Real> + Discrete<B>,
+{
+    /// Create a new empty set of regions, with no active bodies
+    pub fn new() -> Self {
+        Self {
+            regions: Vec::default(),
+        }
+    }
+
+    /// Add a region, builder pattern
+    pub fn with_region(mut self, region: B) -> Self {
+        self.add_region(region);
+        self
+    }
+
+    /// Add a region
+    pub fn add_region(&mut self, region: B) {
+        self.regions.push(region);
+    }
+
+    /// Remove all regions
+    pub fn clear(&mut self) {
+        self.regions.clear();
+    }
+
+    /// Get the regions
+    pub fn regions(&self) -> &[B] {
+        &self.regions
+    }
+
+    /// Check if the given bound overlaps any region
+    pub fn is_active(&self, bound: &B) -> bool {
+        self.regions.iter().any(|region| region.intersects(bound))
+    }
+}
+
+impl<B> Default for ActiveRegions<B>
+where
+    B: Aabb<Scalar = Real> + Discrete<B>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Pairs of entities that never collide with each other, e.g. bodies connected by a joint.
+///
+/// A pair can be excluded multiple times, e.g. by a user and by a
+/// [`JointCollisionSystem`](../physics/struct.JointCollisionSystem.html), and only collides again
+/// when every exclusion has been removed. Pairs are filtered in the broad phase, so excluded pairs
+/// are never tested by the narrow phase.
+#[derive(Debug, Clone, Default)]
+pub struct CollisionExclusions {
+    pairs: HashMap<(Entity, Entity), usize>,
+}
+
+impl CollisionExclusions {
+    /// Create a new empty set of exclusions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Exclude collision between two entities
+    pub fn insert(&mut self, left: Entity, right: Entity) {
+        *self.pairs.entry(ordered(left, right)).or_insert(0) += 1;
+    }
+
+    /// Remove an exclusion between two entities
+    pub fn remove(&mut self, left: Entity, right: Entity) {
+        let pair = ordered(left, right);
+        let remove = match self.pairs.get_mut(&pair) {
+            Some(count) => {
+                *count -= 1;
+                *count == 0
+            }
+            None => false,
+        };
+        if remove {
+            self.pairs.remove(&pair);
+        }
+    }
+
+    /// Check if collision between two entities is excluded
+    pub fn contains(&self, left: Entity, right: Entity) -> bool {
+        self.pairs.contains_key(&ordered(left, right))
+    }
+
+    /// Number of excluded pairs
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Check if no pairs are excluded
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+}
+
+fn ordered(left: Entity, right: Entity) -> (Entity, Entity) {
+    if left < right {
+        (left, right)
+    } else {
+        (right, left)
+    }
+}
+
+/// Check if collision between two entities is excluded, see
+/// [`CollisionExclusions`](struct.CollisionExclusions.html)
+pub(crate) fn excluded(
+    exclusions: &Option<Fetch<CollisionExclusions>>,
+    left: Entity,
+    right: Entity,
+) -> bool {
+    exclusions
+        .as_ref()
+        .map(|e| e.contains(left, right))
+        .unwrap_or(false)
+}
+
+/// Check if two entities are parts of the same assembly
+pub(crate) fn same_assembly(
+    assemblies: &ReadStorage<Assembly>,
+    left: Entity,
+    right: Entity,
+) -> bool {
+    match (assemblies.get(left), assemblies.get(right)) {
+        (Some(left), Some(right)) => left == right,
+        _ => false,
+    }
+}
+
+/// Check if two entities are in the same simulation world
+pub(crate) fn same_world(ids: &ReadStorage<WorldId>, left: Entity, right: Entity) -> bool {
+    ids.get(left).cloned().unwrap_or_default() == ids.get(right).cloned().unwrap_or_default()
+}
+
+/// Check if a pair of entities interacts in this step, i.e. both are in the same simulation world,
+/// and that world is stepped
+pub(crate) fn steps_pair(
+    stepped: Option<&SteppedWorld>,
+    ids: &ReadStorage<WorldId>,
+    left: Entity,
+    right: Entity,
+) -> bool {
+    same_world(ids, left, right) && is_stepped(stepped, ids, left)
+}
+
+/// Resource with a separate value for each simulation world.
+///
+/// Systems that support multiple simulation worlds will look for the value of the entity's world
+/// here, and fall back to the global resource when the world has no value.
+///
+/// # Type parameters:
+///
+/// - `R`: Resource type
+#[derive(Debug)]
+pub struct PerWorld<R> {
+    values: HashMap<WorldId, R>,
+}
+
+impl<R> PerWorld<R> {
+    /// Create a new empty resource
+    pub fn new() -> Self {
+        Self {
+            values: HashMap::default(),
+        }
+    }
+
+    /// Set the value for the given world
+    pub fn insert(&mut self, world: WorldId, value: R) -> Option<R> {
+        self.values.insert(world, value)
+    }
+
+    /// Remove the value for the given world
+    pub fn remove(&mut self, world: WorldId) -> Option<R> {
+        self.values.remove(&world)
+    }
+
+    /// Get the value for the given world
+    pub fn get(&self, world: WorldId) -> Option<&R> {
+        self.values.get(&world)
+    }
+
+    /// Get the value for the given world, mutably
+    pub fn get_mut(&mut self, world: WorldId) -> Option<&mut R> {
+        self.values.get_mut(&world)
+    }
+}
+
+impl<R> Default for PerWorld<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Marker component for shapes that never, or very rarely, move.
+///
+/// If a [`StaticBoundingVolumeTree`](struct.StaticBoundingVolumeTree.html) resource is present,
+/// the spatial sorting system will put shapes with this marker in the static tree instead of the
+/// dynamic tree, and the spatial collision system will never test static shapes against each
+/// other. The marker should be added when the shape is created.
+#[derive(Debug, Clone, Default)]
+pub struct StaticShape;
+
+impl Component for StaticShape {
+    type Storage = NullStorage<Self>;
+}
+
+/// Bounding volume tree used for static shapes.
+///
+/// Static shapes are kept separate from the dynamic shapes, so the dynamic tree doesn't have to
+/// be rebalanced around large numbers of static leaves.
+pub struct StaticBoundingVolumeTree<D>
+where
+    D: TreeValue,
+{
+    tree: DynamicBoundingVolumeTree<D>,
+}
+
+impl<D> StaticBoundingVolumeTree<D>
+where
+    D: TreeValue,
+    D::Bound: Clone
+        + Debug
+        + Contains<D::Bound>
+        + Union<D::Bound, Output = D::Bound>
+        + SurfaceArea,
+{
+    /// Create a new static tree
+    pub fn new() -> Self {
+        Self {
+            tree: DynamicBoundingVolumeTree::new(),
+        }
+    }
+}
+
+impl<D> Default for StaticBoundingVolumeTree<D>
+where
+    D: TreeValue,
+    D::Bound: Clone
+        + Debug
+        + Contains<D::Bound>
+        + Union<D::Bound, Output = D::Bound>
+        + SurfaceArea,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D> Deref for StaticBoundingVolumeTree<D>
+where
+    D: TreeValue,
+{
+    type Target = DynamicBoundingVolumeTree<D>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.tree
+    }
+}
+
+impl<D> DerefMut for StaticBoundingVolumeTree<D>
+where
+    D: TreeValue,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.tree
+    }
+}
+
+/// Broad phase quality metrics for the last frame.
+///
+/// If this resource is present, the [`SpatialSortingSystem`](struct.SpatialSortingSystem.html)
+/// writes the tree metrics and the current tuning values, and the
+/// [`SpatialCollisionSystem`](struct.SpatialCollisionSystem.html) writes the narrow phase
+/// metrics. The metrics are also used for automatic tuning of the broad phase, see
+/// [`BroadPhaseTuning`](struct.BroadPhaseTuning.html).
+#[derive(Debug, Clone, Default)]
+pub struct BroadPhaseStats {
+    /// Number of shapes updated in the dynamic tree
+    pub updates: usize,
+    /// Number of updated shapes that moved outside their fat bound, and needed refitting
+    pub escapes: usize,
+    /// Number of candidate pairs from the broad phase that were tested by the narrow phase
+    pub candidates: usize,
+    /// Number of tested candidate pairs that were in contact
+    pub contacts: usize,
+    /// Fat bound margin currently used for the dynamic tree
+    pub fat_margin: Real,
+    /// Number of frames between tree optimizations currently used, if any
+    pub optimization_interval: Option<usize>,
+}
+
+impl BroadPhaseStats {
+    /// Fraction of the tested candidate pairs that were not in contact
+    pub fn false_positive_ratio(&self) -> Real {
+        if self.candidates == 0 {
+            0.
+        } else {
+            (self.candidates - self.contacts) as Real / self.candidates as Real
+        }
+    }
+
+    /// Fraction of the updated shapes that moved outside their fat bound
+    pub fn escape_ratio(&self) -> Real {
+        if self.updates == 0 {
+            0.
+        } else {
+            self.escapes as Real / self.updates as Real
+        }
+    }
+}
+
+/// Size and estimated memory usage of the collision world, for monitoring long running
+/// simulations.
+///
+/// If this resource is present, the [`SpatialSortingSystem`](struct.SpatialSortingSystem.html)
+/// writes the shape and tree metrics, and the
+/// [`SpatialCollisionSystem`](struct.SpatialCollisionSystem.html) writes the narrow phase cache
+/// metrics. Counts that keep growing while the number of entities stays the same usually mean that
+/// shapes or pairs are not cleaned up.
+///
+/// Memory usage is estimated from the number of stored elements and their sizes, allocator
+/// overhead is not included.
+#[derive(Debug, Clone, Default)]
+pub struct CollisionWorldStats {
+    /// Number of shapes in the dynamic tree
+    pub shapes: usize,
+    /// Number of shapes in the static tree
+    pub static_shapes: usize,
+    /// Number of nodes in the dynamic tree, including branch nodes
+    pub tree_nodes: usize,
+    /// Height of the dynamic tree
+    pub tree_height: u32,
+    /// Number of nodes in the static tree, including branch nodes
+    pub static_tree_nodes: usize,
+    /// Height of the static tree
+    pub static_tree_height: u32,
+    /// Estimated memory used by the trees and the spatial sorting system, in bytes
+    pub tree_memory: usize,
+    /// Number of shape pairs with cached narrow phase results
+    pub cached_pairs: usize,
+    /// Number of cached pairs that are in contact
+    pub manifolds: usize,
+    /// Number of pairs deferred to later frames by the narrow phase budget
+    pub deferred_pairs: usize,
+    /// Estimated memory used by the narrow phase cache and pair buffers, in bytes
+    pub cache_memory: usize,
+}
+
+impl CollisionWorldStats {
+    /// Total number of shapes in the trees
+    pub fn total_shapes(&self) -> usize {
+        self.shapes + self.static_shapes
+    }
+
+    /// Total number of nodes in the trees
+    pub fn total_nodes(&self) -> usize {
+        self.tree_nodes + self.static_tree_nodes
+    }
+
+    /// Total estimated memory usage, in bytes
+    pub fn memory(&self) -> usize {
+        self.tree_memory + self.cache_memory
+    }
+}
+
+/// Settings for automatic tuning of the broad phase, used by the
+/// [`SpatialSortingSystem`](struct.SpatialSortingSystem.html).
+///
+/// Each frame, the fat bound margin is grown when too many shapes move outside their fat bounds,
+/// and shrunk when too many candidate pairs turn out not to be in contact. The tree optimization
+/// interval is shortened when most shapes move each frame, since the tree degrades faster, and
+/// lengthened when few shapes move.
+#[derive(Debug, Clone)]
+pub struct BroadPhaseTuning {
+    /// Smallest fat bound margin
+    pub min_fat_margin: Real,
+    /// Largest fat bound margin
+    pub max_fat_margin: Real,
+    /// Escape ratio above which the margin is grown
+    pub target_escape_ratio: Real,
+    /// False positive ratio above which the margin is shrunk
+    pub target_false_positive_ratio: Real,
+    /// Relative change of the margin per frame
+    pub adjust_rate: Real,
+    /// Smallest number of frames between tree optimizations
+    pub min_optimization_interval: usize,
+    /// Largest number of frames between tree optimizations
+    pub max_optimization_interval: usize,
+}
+
+impl Default for BroadPhaseTuning {
+    fn default() -> Self {
+        Self {
+            min_fat_margin: 0.05,
+            max_fat_margin: 4.,
+            target_escape_ratio: 0.1,
+            target_false_positive_ratio: 0.5,
+            adjust_rate: 0.1,
+            min_optimization_interval: 30,
+            max_optimization_interval: 600,
+        }
+    }
+}
+
+/// Request compaction of the spatial sorting trees.
+///
+/// If this resource is present, and compaction has been requested, the
+/// [`SpatialSortingSystem`](struct.SpatialSortingSystem.html) will compact both the dynamic and
+/// the static tree at the end of its next run, see
+/// [`compact_tree`](../../collide/util/fn.compact_tree.html). Useful after removing large numbers
+/// of shapes, for example when unloading a level.
+#[derive(Debug, Clone, Default)]
+pub struct TreeCompaction {
+    requested: bool,
+}
+
+impl TreeCompaction {
+    /// Request compaction on the next run of the spatial sorting system
+    pub fn request(&mut self) {
+        self.requested = true;
+    }
+
+    /// Returns true if compaction has been requested, and not yet done
+    pub fn is_requested(&self) -> bool {
+        self.requested
+    }
+
+    /// Take the request, clearing it
+    pub(crate) fn take(&mut self) -> bool {
+        let requested = self.requested;
+        self.requested = false;
+        requested
+    }
 }
 
 /// Contacts storage for use in ECS.
@@ -89,6 +866,423 @@ where
     }
 }
 
+/// Contact state of an entity, for polling contacts as component state instead of consuming the
+/// contact event channel.
+///
+/// Maintained by the [`TouchingSystem`](struct.TouchingSystem.html) for all entities that have
+/// this component, add `Touching::default()` to the entities that should be tracked. Contains the
+/// entities touched in the last frame, and the normal of the deepest contact.
+///
+/// # Type parameters:
+///
+/// - `V`: cgmath vector type
+#[derive(Debug, Clone)]
+pub struct Touching<V> {
+    entities: Vec<Entity>,
+    normal: Option<V>,
+    depth: Real,
+}
+
+impl<V> Touching<V>
+where
+    V: Copy,
+{
+    /// Create a new contact state, touching nothing
+    pub fn new() -> Self {
+        Self {
+            entities: Vec::default(),
+            normal: None,
+            depth: 0.,
+        }
+    }
+
+    /// Is the entity touching anything
+    pub fn is_touching(&self) -> bool {
+        !self.entities.is_empty()
+    }
+
+    /// Is the entity touching the given entity
+    pub fn touches(&self, entity: Entity) -> bool {
+        self.entities.contains(&entity)
+    }
+
+    /// Get the entities touched, each entity is only included once
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    /// Get the normal of the deepest contact, pointing away from the other entity, i.e. the
+    /// direction the entity is pushed in by the contact. `None` if not touching anything.
+    pub fn normal(&self) -> Option<V> {
+        self.normal
+    }
+
+    /// Get the penetration depth of the deepest contact
+    pub fn depth(&self) -> Real {
+        self.depth
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entities.clear();
+        self.normal = None;
+        self.depth = 0.;
+    }
+
+    pub(crate) fn add(&mut self, entity: Entity, normal: V, depth: Real) {
+        if !self.entities.contains(&entity) {
+            self.entities.push(entity);
+        }
+        if self.normal.is_none() || depth > self.depth {
+            self.normal = Some(normal);
+            self.depth = depth;
+        }
+    }
+}
+
+impl<V> Default for Touching<V>
+where
+    V: Copy,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> Component for Touching<V>
+where
+    V: Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// What to do when more contact events are generated in a single frame than the contact event
+/// channel can hold.
+///
+/// The capacity of the channel is decided when the channel is created, using
+/// `EventChannel::with_capacity`. Events that are still unread take up room in the channel, so
+/// the policy applies as soon as the new events no longer fit next to them. Events are unread if
+/// they were written to the channel after the collision system last wrote to it, for example by
+/// another collision system. Readers are expected to read the channel once per frame, after the
+/// collision systems have run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContactOverflowPolicy {
+    /// Write all events, the oldest events in the channel are dropped, unread or not.
+    DropOldest,
+    /// Only write the events that fit in the channel next to the unread events, the newest events
+    /// are dropped.
+    DropNewest,
+    /// Write the events that fit in the channel, and add the remaining events to the
+    /// [`Contacts`](struct.Contacts.html) resource, which grows as needed.
+    Grow,
+    /// Same as `DropNewest`, but will also write a [`ContactOverflow`](struct.ContactOverflow.html)
+    /// event.
+    Error,
+}
+
+/// Event written when contact events were dropped, when using
+/// [`ContactOverflowPolicy::Error`](enum.ContactOverflowPolicy.html).
+#[derive(Debug, Clone)]
+pub struct ContactOverflow {
+    /// Number of contact events that were dropped
+    pub dropped: usize,
+}
+
+/// Contact event settings for the collision systems.
+///
+/// If this resource is not present, `DropOldest` is used, and all contacts are published.
+#[derive(Debug, Clone)]
+pub struct ContactEventSettings {
+    /// Policy used when the contact event channel overflows
+    pub policy: ContactOverflowPolicy,
+    /// Minimum penetration depth for contacts to be published as contact events. Shallower
+    /// contacts are put in the [`SilentContacts`](struct.SilentContacts.html) resource instead,
+    /// where the contact solver will still resolve them. Has no effect if that resource is not
+    /// present. Contacts with the `CollisionOnly` strategy are always published.
+    pub min_event_depth: Real,
+    dropped: usize,
+}
+
+impl ContactEventSettings {
+    /// Create new settings with the given overflow policy
+    pub fn new(policy: ContactOverflowPolicy) -> Self {
+        Self {
+            policy,
+            min_event_depth: 0.,
+            dropped: 0,
+        }
+    }
+
+    /// Set the minimum penetration depth for contacts to be published as contact events
+    pub fn with_min_event_depth(mut self, min_event_depth: Real) -> Self {
+        self.min_event_depth = min_event_depth;
+        self
+    }
+
+    /// Total number of contact events that have been dropped
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Reset the dropped events counter
+    pub fn reset_dropped(&mut self) {
+        self.dropped = 0;
+    }
+}
+
+impl Default for ContactEventSettings {
+    fn default() -> Self {
+        Self::new(ContactOverflowPolicy::DropOldest)
+    }
+}
+
+/// Contacts that are resolved by the contact solver, but not published as contact events, because
+/// they are shallower than
+/// [`ContactEventSettings::min_event_depth`](struct.ContactEventSettings.html#structfield.min_event_depth).
+///
+/// Will contain the silent contacts found in the last collision detection run.
+///
+/// # Type parameters:
+///
+/// - `P`: cgmath point type
+#[derive(Debug)]
+pub struct SilentContacts<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    contacts: Vec<ContactEvent<Entity, P>>,
+}
+
+impl<P> Default for SilentContacts<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    fn default() -> Self {
+        Self {
+            contacts: Vec::default(),
+        }
+    }
+}
+
+impl<P> Deref for SilentContacts<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    type Target = Vec<ContactEvent<Entity, P>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.contacts
+    }
+}
+
+impl<P> DerefMut for SilentContacts<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.contacts
+    }
+}
+
+/// Separate event channel for contacts generated with the
+/// [`CollisionOnly`](https://docs.rs/collision/0.12.0/collision/enum.CollisionStrategy.html)
+/// strategy, e.g. for triggers and sensors.
+///
+/// Not added by default. When the resource is added to the world, collision only contacts are
+/// published here instead of on the main contact event channel, so gameplay code that only
+/// cares about triggers does not have to read through all solver contacts each frame, and the
+/// contact solvers do not have to skip the trigger contacts. The overflow policy of the
+/// [`ContactEventSettings`](struct.ContactEventSettings.html) only applies to the main channel,
+/// this channel always drops the oldest events.
+///
+/// Derefs to the event channel, so readers are registered and read the same way as for the main
+/// channel, including with a [`ContactReader`](struct.ContactReader.html).
+///
+/// # Type parameters:
+///
+/// - `P`: cgmath point type
+pub struct CollisionOnlyChannel<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    channel: EventChannel<ContactEvent<Entity, P>>,
+}
+
+impl<P> CollisionOnlyChannel<P>
+where
+    P: EuclideanSpace + Send + Sync + 'static,
+    P::Scalar: Send + Sync + 'static,
+    P::Diff: Debug + Send + Sync + 'static,
+{
+    /// Create a new channel, with the default capacity
+    pub fn new() -> Self {
+        Self {
+            channel: EventChannel::new(),
+        }
+    }
+
+    /// Create a new channel, that keeps at most the given number of events
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            channel: EventChannel::with_capacity(capacity),
+        }
+    }
+}
+
+impl<P> Default for CollisionOnlyChannel<P>
+where
+    P: EuclideanSpace + Send + Sync + 'static,
+    P::Scalar: Send + Sync + 'static,
+    P::Diff: Debug + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P> Deref for CollisionOnlyChannel<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    type Target = EventChannel<ContactEvent<Entity, P>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.channel
+    }
+}
+
+impl<P> DerefMut for CollisionOnlyChannel<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.channel
+    }
+}
+
+/// Move contacts that are too shallow to be published from the events buffer to the silent
+/// contacts resource.
+pub(crate) fn split_silent_contacts<P>(
+    events: &mut Vec<ContactEvent<Entity, P>>,
+    settings: Option<&ContactEventSettings>,
+    silent: Option<&mut SilentContacts<P>>,
+) where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: Debug,
+{
+    let silent = match silent {
+        Some(silent) => silent,
+        None => return,
+    };
+    silent.clear();
+    let min_depth = settings.map(|s| s.min_event_depth).unwrap_or(0.);
+    if min_depth <= 0. {
+        return;
+    }
+    let shallow = |event: &ContactEvent<Entity, P>| {
+        event.contact.strategy != CollisionStrategy::CollisionOnly
+            && event.contact.penetration_depth < min_depth
+    };
+    silent.extend(events.iter().filter(|e| shallow(e)).cloned());
+    events.retain(|e| !shallow(e));
+}
+
+/// Write the contact events for a frame, using the given overflow policy.
+///
+/// If there is a collision only channel, collision only contacts are written there first. If there
+/// is no event channel, all remaining events are added to the contacts resource. The events buffer
+/// is left empty, but keeps its allocation, so it can be reused for the next frame.
+///
+/// The reader is owned by the calling system, and is registered on the first write. It is used to
+/// count the events that were written to the channel since the last write, which are still unread,
+/// and is moved past the written events afterwards.
+pub(crate) fn write_contact_events<P>(
+    events: &mut Vec<ContactEvent<Entity, P>>,
+    collision_only: Option<&mut CollisionOnlyChannel<P>>,
+    channel: Option<&mut EventChannel<ContactEvent<Entity, P>>>,
+    reader: &mut Option<ReaderId<ContactEvent<Entity, P>>>,
+    contacts: Option<&mut Contacts<P>>,
+    settings: Option<&mut ContactEventSettings>,
+    overflow: Option<&mut EventChannel<ContactOverflow>>,
+) where
+    P: EuclideanSpace + Send + Sync + 'static,
+    P::Scalar: Send + Sync + 'static,
+    P::Diff: Debug + Send + Sync + 'static,
+{
+    if let Some(collision_only) = collision_only {
+        let is_collision_only = |event: &ContactEvent<Entity, P>| {
+            event.contact.strategy == CollisionStrategy::CollisionOnly
+        };
+        for event in events.iter().filter(|e| is_collision_only(e)) {
+            collision_only.single_write(event.clone());
+        }
+        events.retain(|e| !is_collision_only(e));
+    }
+    let channel = match channel {
+        Some(channel) => channel,
+        None => {
+            match contacts {
+                Some(contacts) => contacts.extend(events.drain(..)),
+                None => events.clear(),
+            }
+            return;
+        }
+    };
+    let policy = settings
+        .as_ref()
+        .map(|s| s.policy)
+        .unwrap_or(ContactOverflowPolicy::DropOldest);
+    let unread = match *reader {
+        Some(ref mut reader) => channel.lossy_read(reader).count(),
+        None => {
+            *reader = Some(channel.register_reader());
+            0
+        }
+    };
+    let capacity = channel.max_size().saturating_sub(unread);
+    let excess = events.len().saturating_sub(capacity);
+    let mut dropped = excess;
+    match policy {
+        ContactOverflowPolicy::DropOldest => for event in events.drain(..) {
+            channel.single_write(event);
+        },
+        ContactOverflowPolicy::DropNewest | ContactOverflowPolicy::Error => {
+            events.truncate(capacity);
+            for event in events.drain(..) {
+                channel.single_write(event);
+            }
+        }
+        ContactOverflowPolicy::Grow => {
+            let split = capacity.min(events.len());
+            for event in events.drain(..split) {
+                channel.single_write(event);
+            }
+            if let Some(contacts) = contacts {
+                contacts.extend(events.drain(..));
+                dropped = 0;
+            }
+            events.clear();
+        }
+    }
+    if let Some(ref mut reader) = *reader {
+        channel.lossy_read(reader).count();
+    }
+    if dropped > 0 {
+        if let Some(settings) = settings {
+            settings.dropped += dropped;
+        }
+        if let (ContactOverflowPolicy::Error, Some(overflow)) = (policy, overflow) {
+            overflow.single_write(ContactOverflow { dropped });
+        }
+    }
+}
+
 impl<'a, P, T> From<(Entity, &'a CollisionShape<P, T>)> for ContainerShapeWrapper<Entity, P>
 where
     P: Primitive,
@@ -111,3 +1305,222 @@ where
         self.id
     }
 }
+
+/// Queue of terrain chunks to load into, or unload from, the collision world.
+///
+/// Each chunk is a single entity with a collision shape and a pose, so adding or removing a chunk
+/// only inserts or removes a single value in the spatial tree, regardless of how many primitives
+/// the chunk consists of. Chunks are processed by
+/// [`ChunkStreamingSystem`](struct.ChunkStreamingSystem.html), which will only process a limited
+/// number of chunks per frame.
+///
+/// # Type parameters:
+///
+/// - `K`: Chunk key, e.g. the chunk coordinate
+/// - `P`: Collision primitive type
+/// - `T`: Transform type
+#[derive(Debug)]
+pub struct ChunkStreaming<K, P, T>
+where
+    K: Hash + Eq,
+    P: Primitive,
+{
+    max_chunks_per_frame: usize,
+    pending_loads: VecDeque<(K, CollisionShape<P, T>, T)>,
+    pending_unloads: VecDeque<K>,
+    loaded: HashMap<K, Entity>,
+}
+
+impl<K, P, T> ChunkStreaming<K, P, T>
+where
+    K: Hash + Eq,
+    P: Primitive,
+{
+    /// Create a new chunk streaming queue.
+    ///
+    /// # Parameters:
+    ///
+    /// - `max_chunks_per_frame`: Maximum number of chunk loads and unloads to process each frame
+    pub fn new(max_chunks_per_frame: usize) -> Self {
+        Self {
+            max_chunks_per_frame,
+            pending_loads: VecDeque::default(),
+            pending_unloads: VecDeque::default(),
+            loaded: HashMap::default(),
+        }
+    }
+
+    /// Queue a chunk for loading. If a chunk with the same key is already loaded, it is replaced.
+    pub fn load(&mut self, key: K, shape: CollisionShape<P, T>, pose: T) {
+        self.pending_loads.push_back((key, shape, pose));
+    }
+
+    /// Queue a chunk for unloading
+    pub fn unload(&mut self, key: K) {
+        self.pending_unloads.push_back(key);
+    }
+
+    /// Get the entity of a loaded chunk
+    pub fn entity(&self, key: &K) -> Option<Entity> {
+        self.loaded.get(key).cloned()
+    }
+
+    /// Number of loaded chunks
+    pub fn loaded_count(&self) -> usize {
+        self.loaded.len()
+    }
+
+    /// Number of chunk loads and unloads that are waiting to be processed
+    pub fn pending_count(&self) -> usize {
+        self.pending_loads.len() + self.pending_unloads.len()
+    }
+
+    pub(crate) fn max_chunks_per_frame(&self) -> usize {
+        self.max_chunks_per_frame
+    }
+
+    pub(crate) fn next_unload(&mut self) -> Option<Entity> {
+        while let Some(key) = self.pending_unloads.pop_front() {
+            if let Some(entity) = self.loaded.remove(&key) {
+                return Some(entity);
+            }
+        }
+        None
+    }
+
+    pub(crate) fn next_load(&mut self) -> Option<(K, CollisionShape<P, T>, T)> {
+        self.pending_loads.pop_front()
+    }
+
+    pub(crate) fn set_loaded(&mut self, key: K, entity: Entity) -> Option<Entity> {
+        self.loaded.insert(key, entity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Point2, Vector2};
+    use collision::{CollisionStrategy, Contact};
+    use shrev::{EventChannel, ReaderId};
+    use specs::{Entity, World};
+
+    use super::*;
+    use Real;
+    use collide::ContactEvent;
+
+    type Event = ContactEvent<Entity, Point2<Real>>;
+
+    struct Written {
+        channel: EventChannel<Event>,
+        consumer: ReaderId<Event>,
+        contacts: Contacts<Point2<Real>>,
+        settings: ContactEventSettings,
+        overflow: EventChannel<ContactOverflow>,
+        overflow_reader: ReaderId<ContactOverflow>,
+    }
+
+    impl Written {
+        fn read(&mut self) -> Vec<Real> {
+            self.channel
+                .lossy_read(&mut self.consumer)
+                .map(|e| e.contact.penetration_depth)
+                .collect()
+        }
+
+        fn overflows(&mut self) -> Vec<usize> {
+            self.overflow
+                .lossy_read(&mut self.overflow_reader)
+                .map(|o| o.dropped)
+                .collect()
+        }
+    }
+
+    fn event(entities: (Entity, Entity), depth: Real) -> Event {
+        ContactEvent::new(
+            entities,
+            Contact::new_impl(CollisionStrategy::FullResolution, Vector2::new(0., 1.), depth),
+        )
+    }
+
+    /// Write 3 events to a channel with room for 4, where another writer has already written 2
+    /// events that are still unread.
+    fn write(policy: ContactOverflowPolicy) -> Written {
+        let mut world = World::new();
+        let entities = (world.create_entity().build(), world.create_entity().build());
+        let channel = EventChannel::with_capacity(4);
+        let overflow = EventChannel::new();
+        let mut written = Written {
+            consumer: channel.register_reader(),
+            overflow_reader: overflow.register_reader(),
+            channel,
+            contacts: Contacts::default(),
+            settings: ContactEventSettings::new(policy),
+            overflow,
+        };
+        let mut reader = None;
+        write_contact_events(
+            &mut Vec::new(),
+            None,
+            Some(&mut written.channel),
+            &mut reader,
+            Some(&mut written.contacts),
+            Some(&mut written.settings),
+            Some(&mut written.overflow),
+        );
+        written.channel.single_write(event(entities, 10.));
+        written.channel.single_write(event(entities, 11.));
+        let mut events = vec![event(entities, 0.), event(entities, 1.), event(entities, 2.)];
+        write_contact_events(
+            &mut events,
+            None,
+            Some(&mut written.channel),
+            &mut reader,
+            Some(&mut written.contacts),
+            Some(&mut written.settings),
+            Some(&mut written.overflow),
+        );
+        assert!(events.is_empty());
+        written
+    }
+
+    #[test]
+    fn test_drop_oldest_drops_unread_events() {
+        let mut written = write(ContactOverflowPolicy::DropOldest);
+        assert_eq!(1, written.settings.dropped());
+        assert!(written.contacts.is_empty());
+        assert_eq!(vec![11., 0., 1., 2.], written.read());
+        assert!(written.overflows().is_empty());
+    }
+
+    #[test]
+    fn test_drop_newest_keeps_unread_events() {
+        let mut written = write(ContactOverflowPolicy::DropNewest);
+        assert_eq!(1, written.settings.dropped());
+        assert!(written.contacts.is_empty());
+        assert_eq!(vec![10., 11., 0., 1.], written.read());
+        assert!(written.overflows().is_empty());
+    }
+
+    #[test]
+    fn test_grow_moves_excess_to_contacts() {
+        let mut written = write(ContactOverflowPolicy::Grow);
+        assert_eq!(0, written.settings.dropped());
+        let grown = written
+            .contacts
+            .iter()
+            .map(|e| e.contact.penetration_depth)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![2.], grown);
+        assert_eq!(vec![10., 11., 0., 1.], written.read());
+        assert!(written.overflows().is_empty());
+    }
+
+    #[test]
+    fn test_error_reports_dropped_events() {
+        let mut written = write(ContactOverflowPolicy::Error);
+        assert_eq!(1, written.settings.dropped());
+        assert!(written.contacts.is_empty());
+        assert_eq!(vec![10., 11., 0., 1.], written.read());
+        assert_eq!(vec![1], written.overflows());
+    }
+}
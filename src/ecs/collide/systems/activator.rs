@@ -0,0 +1,104 @@
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use cgmath::{Array, EuclideanSpace, InnerSpace, Transform};
+use collision::{Aabb, Contains, Discrete, SurfaceArea, Union};
+use collision::dbvt::{DiscreteVisitor, DynamicBoundingVolumeTree, TreeValue};
+use specs::{Component, Entities, Fetch, Join, ReadStorage, System, WriteStorage};
+
+use Real;
+use ecs::collide::resources::{same_world, Activator, Dormant, GetEntity, WorldId};
+
+/// Activator [system](https://docs.rs/specs/0.9.5/specs/trait.System.html) for use with
+/// [`specs`](https://docs.rs/specs/0.9.5/specs/).
+///
+/// For each [`Activator`](struct.Activator.html), will query the spatial tree for shapes near the
+/// position of the activator, and wake up all [`Dormant`](struct.Dormant.html) bodies in the same
+/// simulation world whose bounding sphere is within the wake radius. Only the tree is queried, so
+/// the cost does not grow with the number of sleeping bodies.
+///
+/// Should run after the [`ActiveRegionSystem`](struct.ActiveRegionSystem.html), so bodies near an
+/// activator are awake even outside all active regions, and before collision detection. The tree
+/// leaves of dormant shapes are not updated, so the spatial sorting system can run either before
+/// or after this system.
+///
+/// ## Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `T`: Transform type
+/// - `D`: Type of values stored in the DBVT
+#[derive(Debug)]
+pub struct ActivatorSystem<P, T, D> {
+    marker: PhantomData<(P, T, D)>,
+}
+
+impl<P, T, D> ActivatorSystem<P, T, D> {
+    /// Create a new activator system.
+    pub fn new() -> Self {
+        Self {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<P, T, D> Default for ActivatorSystem<P, T, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, P, T, D> System<'a> for ActivatorSystem<P, T, D>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: InnerSpace + Array<Element = Real> + Debug + Send + Sync + 'static,
+    T: Component + Transform<P> + Send + Sync + 'static,
+    D: TreeValue + GetEntity + Send + Sync + 'static,
+    D::Bound: Aabb<Point = P, Diff = P::Diff, Scalar = Real>
+        + Discrete<D::Bound>
+        + Contains<D::Bound>
+        + Union<D::Bound, Output = D::Bound>
+        + SurfaceArea
+        + Clone
+        + Debug
+        + Send
+        + Sync
+        + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Activator>,
+        ReadStorage<'a, T>,
+        ReadStorage<'a, WorldId>,
+        Fetch<'a, DynamicBoundingVolumeTree<D>>,
+        WriteStorage<'a, Dormant>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, activators, poses, world_ids, tree, mut dormant) = data;
+
+        let mut woken = Vec::default();
+        for (activator_entity, activator, pose) in (&*entities, &activators, &poses).join() {
+            let position = pose.transform_point(P::origin());
+            let extent = P::Diff::from_value(activator.radius);
+            let bound = D::Bound::new(position + extent * -1., position + extent);
+            for (value, _) in tree.query(&mut DiscreteVisitor::<D::Bound, D>::new(&bound)) {
+                let entity = value.entity();
+                if dormant.get(entity).is_none()
+                    || !same_world(&world_ids, entity, activator_entity)
+                {
+                    continue;
+                }
+                let shape_bound = value.bound();
+                let distance = (shape_bound.center() - position).magnitude()
+                    - shape_bound.dim().magnitude() / 2.;
+                if distance <= activator.radius {
+                    woken.push(entity);
+                }
+            }
+        }
+
+        for entity in woken {
+            dormant.remove(entity);
+        }
+    }
+}
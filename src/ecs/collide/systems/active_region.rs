@@ -0,0 +1,145 @@
+use std::collections::HashSet;
+use std::marker::PhantomData;
+
+use cgmath::Transform;
+use collision::{Aabb, Discrete};
+use specs::{Component, Entities, Entity, Fetch, Join, ReadStorage, System, WriteStorage};
+
+use Real;
+use collide::{CollisionShape, Primitive};
+use ecs::collide::resources::{ActiveRegions, Dormant};
+
+/// Active region [system](https://docs.rs/specs/0.9.5/specs/trait.System.html) for use with
+/// [`specs`](https://docs.rs/specs/0.9.5/specs/).
+///
+/// Will mark all shapes whose bound is outside every region in the
+/// [`ActiveRegions`](struct.ActiveRegions.html) resource as [`Dormant`](struct.Dormant.html), and
+/// wake up shapes that are inside a region again. If the resource is not present, all shapes are
+/// active. Bodies without a collision shape are never made dormant.
+///
+/// Only the bodies this system made dormant are woken again, so bodies put to sleep by the contact
+/// solvers stay asleep inside the regions. A body woken by another system, e.g. an
+/// [`ActivatorSystem`](struct.ActivatorSystem.html), is no longer considered dormant by this
+/// system, and is made dormant again if it is still outside every region.
+///
+/// Should run after the spatial sorting system, and before collision detection.
+///
+/// ## Type parameters:
+///
+/// - `P`: Primitive type, needs to implement `Primitive`.
+/// - `T`: Transform type
+#[derive(Debug)]
+pub struct ActiveRegionSystem<P, T> {
+    dormant: HashSet<Entity>,
+    marker: PhantomData<(P, T)>,
+}
+
+impl<P, T> ActiveRegionSystem<P, T> {
+    /// Create a new active region system.
+    pub fn new() -> Self {
+        Self {
+            dormant: HashSet::default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<P, T> Default for ActiveRegionSystem<P, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, P, T> System<'a> for ActiveRegionSystem<P, T>
+where
+    P: Primitive + Send + Sync + 'static,
+    P::Aabb: Aabb<Scalar = Real> + Discrete<P::Aabb> + Send + Sync + 'static,
+    T: Component + Transform<P::Point> + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        Option<Fetch<'a, ActiveRegions<P::Aabb>>>,
+        ReadStorage<'a, CollisionShape<P, T>>,
+        WriteStorage<'a, Dormant>,
+    );
+
+    fn run(&mut self, (entities, regions, shapes, mut dormant): Self::SystemData) {
+        // forget bodies that were deleted, or woken by other systems since the last frame
+        self.dormant
+            .retain(|&entity| entities.is_alive(entity) && dormant.get(entity).is_some());
+        let regions = match regions {
+            Some(regions) => regions,
+            None => {
+                for entity in self.dormant.drain() {
+                    dormant.remove(entity);
+                }
+                return;
+            }
+        };
+        for (entity, shape) in (&*entities, &shapes).join() {
+            if regions.is_active(shape.bound()) {
+                if self.dormant.remove(&entity) {
+                    dormant.remove(entity);
+                }
+            } else if dormant.get(entity).is_none() {
+                dormant.insert(entity, Dormant);
+                self.dormant.insert(entity);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, One, Point2};
+    use collision::Aabb2;
+    use collision::primitive::Rectangle;
+    use specs::{Entity, RunNow, World};
+
+    use ecs::collide::Dormant;
+    use ecs::collide::prelude2d::*;
+    use Real;
+
+    fn body(world: &mut World, x: Real) -> Entity {
+        let pose = BodyPose2::new(Point2::new(x, 0.), Basis2::one());
+        let mut shape = CollisionShape2::<BodyPose2>::new_simple(
+            CollisionStrategy::FullResolution,
+            CollisionMode::Discrete,
+            Rectangle::new(1., 1.).into(),
+        );
+        shape.update(&pose, None);
+        world.create_entity().with(shape).with(pose).build()
+    }
+
+    #[test]
+    fn test_sleeping_bodies_stay_asleep() {
+        let mut world = World::new();
+        world_register::<BodyPose2>(&mut world);
+        let region = |x: Real| ActiveRegions2::new().with_region(Aabb2::new(
+            Point2::new(x - 5., -5.),
+            Point2::new(x + 5., 5.),
+        ));
+        let sleeping = body(&mut world, 0.);
+        let outside = body(&mut world, 20.);
+        // put to sleep by a contact solver
+        world.write::<Dormant>().insert(sleeping, Dormant);
+        let is_dormant = |world: &World, entity| world.read::<Dormant>().get(entity).is_some();
+
+        // without regions all bodies are active, but sleeping bodies stay asleep
+        let mut system = ActiveRegionSystem2::<BodyPose2>::new();
+        system.run_now(&world.res);
+        assert!(is_dormant(&world, sleeping));
+        assert!(!is_dormant(&world, outside));
+
+        world.add_resource(region(0.));
+        system.run_now(&world.res);
+        assert!(is_dormant(&world, sleeping));
+        assert!(is_dormant(&world, outside));
+
+        // moving the region wakes the body outside it, the sleeping body stays asleep
+        world.add_resource(region(20.));
+        system.run_now(&world.res);
+        assert!(is_dormant(&world, sleeping));
+        assert!(!is_dormant(&world, outside));
+    }
+}
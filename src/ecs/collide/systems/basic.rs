@@ -2,20 +2,36 @@ use std::fmt::Debug;
 
 use cgmath::prelude::*;
 use collision::prelude::*;
-use shrev::EventChannel;
-use specs::{Component, Entities, Entity, FetchMut, Join, ReadStorage, System, WriteStorage};
+use shrev::{EventChannel, ReaderId};
+use specs::{Component, Entities, Entity, Fetch, FetchMut, Join, ReadStorage, System, WriteStorage};
 
 use {NextFrame, Real};
 use collide::{CollisionShape, CollisionStrategy, ContactEvent, Primitive};
 use collide::broad::{BroadPhase, HasBound};
 use collide::narrow::NarrowPhase;
-use ecs::collide::resources::{Contacts, GetEntity};
+use ecs::collide::resources::{any_active, collides, excluded, same_assembly, steps_pair,
+                              split_silent_contacts, write_contact_events, Assembly,
+                              CollisionExclusions, CollisionOnlyChannel, ContactEventSettings,
+                              ContactOverflow, Contacts, Dormant, Frozen, GetEntity, SilentContacts,
+                              SteppedWorld, WorldId};
 
 /// Collision detection [system](https://docs.rs/specs/0.9.5/specs/trait.System.html) for use with
 /// [`specs`](https://docs.rs/specs/0.9.5/specs/).
 ///
 /// Has support for both broad phase and narrow phase collision detection. Will only do narrow phase
-/// if both broad and narrow phase is activated.
+/// if both broad and narrow phase is activated. Shapes are only tested against shapes in the same
+/// simulation world, see [`WorldId`](struct.WorldId.html), and only in the
+/// [`SteppedWorld`](struct.SteppedWorld.html), if present. Parts of the same
+/// [`Assembly`](struct.Assembly.html), and pairs in the
+/// [`CollisionExclusions`](struct.CollisionExclusions.html) resource, if present, are never tested
+/// against each other. Contact events are
+/// written using the overflow policy in the
+/// [`ContactEventSettings`](struct.ContactEventSettings.html) resource, if present. Contacts
+/// shallower than the minimum event depth in the settings are put in the
+/// [`SilentContacts`](struct.SilentContacts.html) resource instead, if present.
+///
+/// Transient per frame data (broad phase values, potential pairs and contact events) is kept in
+/// buffers owned by the system, that are cleared each frame but keep their allocation.
 ///
 /// Can handle any transform component type, as long as the type implements
 /// [`Transform`](https://docs.rs/cgmath/0.15.0/cgmath/trait.Transform.html), and as long as the
@@ -26,9 +42,14 @@ pub struct BasicCollisionSystem<P, T, D>
 where
     P: Primitive,
     P::Aabb: Clone + Debug + Aabb<Scalar = Real>,
+    <P::Point as EuclideanSpace>::Diff: Debug,
 {
     narrow: Option<Box<NarrowPhase<P, T>>>,
     broad: Option<Box<BroadPhase<D>>>,
+    info: Vec<D>,
+    potentials: Vec<(Entity, Entity)>,
+    events: Vec<ContactEvent<Entity, P::Point>>,
+    reader: Option<ReaderId<ContactEvent<Entity, P::Point>>>,
 }
 
 impl<P, T, D> BasicCollisionSystem<P, T, D>
@@ -44,6 +65,10 @@ where
         Self {
             narrow: None,
             broad: None,
+            info: Vec::default(),
+            potentials: Vec::default(),
+            events: Vec::default(),
+            reader: None,
         }
     }
 
@@ -67,7 +92,7 @@ where
     P::Point: Debug + Send + Sync + 'static,
     <P::Point as EuclideanSpace>::Diff: Debug + Send + Sync + 'static,
     T: Component + Transform<P::Point> + Send + Sync + Clone + 'static,
-    for<'b: 'a> D: HasBound<Bound = P::Aabb> + From<(Entity, &'b CollisionShape<P, T>)> + GetEntity,
+    for<'b> D: HasBound<Bound = P::Aabb> + From<(Entity, &'b CollisionShape<P, T>)> + GetEntity,
 {
     type SystemData = (
         Entities<'a>,
@@ -76,45 +101,97 @@ where
         WriteStorage<'a, CollisionShape<P, T>>,
         Option<FetchMut<'a, Contacts<P::Point>>>,
         Option<FetchMut<'a, EventChannel<ContactEvent<Entity, P::Point>>>>,
+        Option<FetchMut<'a, CollisionOnlyChannel<P::Point>>>,
+        ReadStorage<'a, WorldId>,
+        Option<Fetch<'a, SteppedWorld>>,
+        ReadStorage<'a, Assembly>,
+        Option<Fetch<'a, CollisionExclusions>>,
+        ReadStorage<'a, Frozen>,
+        ReadStorage<'a, Dormant>,
+        Option<FetchMut<'a, ContactEventSettings>>,
+        Option<FetchMut<'a, EventChannel<ContactOverflow>>>,
+        Option<FetchMut<'a, SilentContacts<P::Point>>>,
     );
 
     fn run(&mut self, system_data: Self::SystemData) {
-        let (entities, poses, next_poses, mut shapes, mut contacts, mut event_channel) =
-            system_data;
+        let (
+            entities,
+            poses,
+            next_poses,
+            mut shapes,
+            mut contacts,
+            mut event_channel,
+            mut collision_only,
+            world_ids,
+            stepped,
+            assemblies,
+            exclusions,
+            frozen,
+            dormant,
+            mut settings,
+            mut overflow,
+            mut silent,
+        ) = system_data;
 
         if let Some(ref mut c) = contacts {
             c.clear();
         }
+        if let Some(ref mut c) = silent {
+            c.clear();
+        }
 
         if let Some(ref mut broad) = self.broad {
-            let mut info = Vec::default();
+            let info = &mut self.info;
+            info.clear();
+            // updating the bounds is not a modification of the shape, so the flags are restored
+            // afterwards
+            // Uses FlaggedStorage
+            let modified = (&*entities, (&shapes).open().1)
+                .join()
+                .map(|(entity, _)| entity)
+                .collect::<Vec<_>>();
             for (entity, pose, shape) in (&*entities, &poses, &mut shapes).join() {
                 shape.update(&pose, next_poses.get(entity).map(|p| &p.value));
-                info.push((entity, &*shape).into());
+                // query only shapes are never paired, so are kept out of the broad phase
+                if !shape.is_query_only() {
+                    info.push((entity, &*shape).into());
+                }
             }
-            let potentials = broad
-                .find_potentials(&mut info)
-                .iter()
-                .map(|&(a, b)| (info[a].entity(), info[b].entity()))
-                .collect::<Vec<_>>();
+            let flags = (&mut shapes).open().1;
+            flags.clear_flags();
+            for entity in modified {
+                flags.flag(entity);
+            }
+            let potentials = &mut self.potentials;
+            potentials.clear();
+            potentials.extend(
+                broad
+                    .find_potentials(info)
+                    .iter()
+                    .map(|&(a, b)| (info[a].entity(), info[b].entity()))
+                    .filter(|&(a, b)| steps_pair(stepped.as_deref(), &world_ids, a, b))
+                    .filter(|&(a, b)| !same_assembly(&assemblies, a, b))
+                    .filter(|&(a, b)| !excluded(&exclusions, a, b))
+                    .filter(|&(a, b)| collides(&frozen, a) && collides(&frozen, b))
+                    .filter(|&(a, b)| any_active(&dormant, a, b)),
+            );
 
+            let events = &mut self.events;
+            events.clear();
             match self.narrow {
-                Some(ref mut narrow) => for (left_entity, right_entity) in potentials {
+                Some(ref mut narrow) => for &(left_entity, right_entity) in potentials.iter() {
                     let left_shape = shapes.get(left_entity).unwrap();
                     let right_shape = shapes.get(right_entity).unwrap();
                     let left_pose = poses.get(left_entity).unwrap();
                     let right_pose = poses.get(right_entity).unwrap();
                     match narrow.collide(left_shape, left_pose, right_shape, right_pose) {
-                        Some(contact) => {
+                        Some((contact, primitives)) => {
                             let event = ContactEvent::new(
                                 (left_entity.clone(), right_entity.clone()),
                                 contact,
-                            );
-                            if let Some(ref mut events) = event_channel {
-                                events.single_write(event);
-                            } else if let Some(ref mut c) = contacts {
-                                c.push(event);
-                            }
+                            ).with_tags((left_shape.tag, right_shape.tag))
+                                .with_primitives(primitives);
+                            events.push(event);
                         }
                         None => (),
                     };
@@ -123,19 +200,29 @@ where
                     // if we only have a broad phase, we generate contacts for aabb
                     // intersections
                     // right now, we only report the collision, no normal/depth calculation
-                    for (left_entity, right_entity) in potentials {
+                    for &(left_entity, right_entity) in potentials.iter() {
                         let event = ContactEvent::new_single(
                             CollisionStrategy::CollisionOnly,
                             (left_entity, right_entity),
-                        );
-                        if let Some(ref mut events) = event_channel {
-                            events.single_write(event);
-                        } else if let Some(ref mut c) = contacts {
-                            c.push(event);
-                        }
+                        ).with_tags((
+                            shapes.get(left_entity).and_then(|s| s.tag),
+                            shapes.get(right_entity).and_then(|s| s.tag),
+                        ));
+                        events.push(event);
                     }
                 }
             }
+
+            split_silent_contacts(events, settings.as_deref(), silent.as_deref_mut());
+            write_contact_events(
+                events,
+                collision_only.as_deref_mut(),
+                event_channel.as_deref_mut(),
+                &mut self.reader,
+                contacts.as_deref_mut(),
+                settings.as_deref_mut(),
+                overflow.as_deref_mut(),
+            );
         }
     }
 }
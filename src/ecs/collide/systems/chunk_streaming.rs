@@ -0,0 +1,88 @@
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use specs::{Component, Entities, FetchMut, System, WriteStorage};
+
+use collide::{CollisionShape, Primitive};
+use ecs::collide::resources::ChunkStreaming;
+
+/// Chunk streaming [system](https://docs.rs/specs/0.9.5/specs/trait.System.html) for use with
+/// [`specs`](https://docs.rs/specs/0.9.5/specs/).
+///
+/// Will process pending chunk loads and unloads from the
+/// [`ChunkStreaming`](struct.ChunkStreaming.html) resource, up to the configured maximum number of
+/// chunks per frame. Unloads are processed before loads. Each loaded chunk is created as a new
+/// entity with the chunk shape and pose.
+///
+/// Should run before the spatial sorting system, so new chunks are inserted into the tree in the
+/// same frame.
+///
+/// ## Type parameters:
+///
+/// - `K`: Chunk key
+/// - `P`: Primitive type, needs to implement `Primitive`.
+/// - `T`: Transform type
+#[derive(Debug)]
+pub struct ChunkStreamingSystem<K, P, T> {
+    marker: PhantomData<(K, P, T)>,
+}
+
+impl<K, P, T> ChunkStreamingSystem<K, P, T> {
+    /// Create a new chunk streaming system.
+    pub fn new() -> Self {
+        Self {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<K, P, T> Default for ChunkStreamingSystem<K, P, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, K, P, T> System<'a> for ChunkStreamingSystem<K, P, T>
+where
+    K: Hash + Eq + Send + Sync + 'static,
+    P: Primitive + Send + Sync + 'static,
+    P::Aabb: Send + Sync + 'static,
+    T: Component + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        FetchMut<'a, ChunkStreaming<K, P, T>>,
+        WriteStorage<'a, T>,
+        WriteStorage<'a, CollisionShape<P, T>>,
+    );
+
+    fn run(&mut self, (entities, mut streaming, mut poses, mut shapes): Self::SystemData) {
+        let mut budget = streaming.max_chunks_per_frame();
+
+        while budget > 0 {
+            match streaming.next_unload() {
+                Some(entity) => {
+                    // the entity can only be dead if the user deleted it, nothing to do then
+                    let _ = entities.delete(entity);
+                    budget -= 1;
+                }
+                None => break,
+            }
+        }
+
+        while budget > 0 {
+            match streaming.next_load() {
+                Some((key, shape, pose)) => {
+                    let entity = entities.create();
+                    poses.insert(entity, pose);
+                    shapes.insert(entity, shape);
+                    if let Some(previous) = streaming.set_loaded(key, entity) {
+                        let _ = entities.delete(previous);
+                    }
+                    budget -= 1;
+                }
+                None => break,
+            }
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use cgmath::prelude::*;
+use collision::Ray;
+use collision::dbvt::{DiscreteVisitor, DynamicBoundingVolumeTree, TreeValue};
+use collision::prelude::*;
+use specs::{Component, Entities, Fetch, Join, ReadStorage, System, WriteStorage};
+
+use Real;
+use collide::{CollisionMode, CollisionShape, CollisionStrategy, Primitive};
+use collide::narrow::NarrowPhase;
+use ecs::collide::query::{closest_ray_hit, query_overlap};
+use ecs::collide::resources::{same_world, ContinuousQuery, GetEntity, QueryShape, QueryVolume,
+                              StaticBoundingVolumeTree, WorldId};
+
+/// Continuous query [system](https://docs.rs/specs/0.9.5/specs/trait.System.html) for use with
+/// [`specs`](https://docs.rs/specs/0.9.5/specs/).
+///
+/// Will evaluate all [`ContinuousQuery`](struct.ContinuousQuery.html) components, using the
+/// current pose of their entity, and write the results back to the components. Both the dynamic
+/// bounding volume tree and, if present, the
+/// [`StaticBoundingVolumeTree`](struct.StaticBoundingVolumeTree.html) are queried. Only entities
+/// in the same simulation world as the query entity are found, see
+/// [`WorldId`](struct.WorldId.html).
+///
+/// Volume queries are tested using the narrow phase, if one is set, and only the bounding volumes
+/// are tested otherwise.
+///
+/// Should run after the spatial sorting system.
+///
+/// ### Type parameters:
+///
+/// - `P`: Primitive type, needs to implement `Primitive`.
+/// - `T`: Transform type, needs to implement `Transform`.
+/// - `D`: Type of values stored in the DBVT
+pub struct ContinuousQuerySystem<P, T, D>
+where
+    P: Primitive,
+    <P::Point as EuclideanSpace>::Diff: Debug,
+{
+    narrow: Option<Box<dyn NarrowPhase<P, T>>>,
+    m: PhantomData<D>,
+}
+
+impl<P, T, D> ContinuousQuerySystem<P, T, D>
+where
+    P: Primitive,
+    <P::Point as EuclideanSpace>::Diff: Debug,
+{
+    /// Create a new continuous query system, with no narrow phase activated
+    pub fn new() -> Self {
+        Self {
+            narrow: None,
+            m: PhantomData,
+        }
+    }
+
+    /// Specify what narrow phase algorithm to use for volume queries
+    pub fn with_narrow_phase<N: NarrowPhase<P, T> + 'static>(mut self, narrow: N) -> Self {
+        self.narrow = Some(Box::new(narrow));
+        self
+    }
+}
+
+impl<P, T, D> Default for ContinuousQuerySystem<P, T, D>
+where
+    P: Primitive,
+    <P::Point as EuclideanSpace>::Diff: Debug,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, P, T, D, Q> System<'a> for ContinuousQuerySystem<P, T, D>
+where
+    Q: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    Q::Diff: Debug + InnerSpace + Send + Sync + 'static,
+    P: Primitive<Point = Q>
+        + ContinuousTransformed<
+            Ray<Real, Q, Q::Diff>,
+            Point = Q,
+            Result = Q,
+        >
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    P::Aabb: Aabb<Scalar = Real, Point = Q>
+        + Send
+        + Sync
+        + 'static
+        + Clone
+        + Debug
+        + Continuous<Ray<Real, Q, Q::Diff>>
+        + Discrete<Ray<Real, Q, Q::Diff>>
+        + Discrete<P::Aabb>
+        + Contains<P::Aabb>
+        + Union<P::Aabb, Output = P::Aabb>
+        + SurfaceArea<Scalar = Real>,
+    T: Component + Transform<Q> + Send + Sync + 'static,
+    D: TreeValue<Bound = P::Aabb> + GetEntity + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, T>,
+        ReadStorage<'a, CollisionShape<P, T>>,
+        ReadStorage<'a, QueryShape<P, T>>,
+        ReadStorage<'a, WorldId>,
+        Fetch<'a, DynamicBoundingVolumeTree<D>>,
+        Option<Fetch<'a, StaticBoundingVolumeTree<D>>>,
+        WriteStorage<'a, ContinuousQuery<P>>,
+    );
+
+    fn run(&mut self, system_data: Self::SystemData) {
+        let (entities, poses, shapes, details, world_ids, tree, static_tree, mut queries) =
+            system_data;
+        let mut trees: Vec<&DynamicBoundingVolumeTree<D>> = vec![&*tree];
+        if let Some(ref static_tree) = static_tree {
+            trees.push(&**static_tree);
+        }
+
+        for (entity, pose, query) in (&*entities, &poses, &mut queries).join() {
+            let filter = |other| other != entity && same_world(&world_ids, entity, other);
+            query.hit = None;
+            query.overlaps.clear();
+            match query.volume {
+                QueryVolume::Ray {
+                    origin,
+                    direction,
+                    max_distance,
+                } => {
+                    let ray = Ray::new(
+                        pose.transform_point(origin),
+                        pose.transform_vector(direction).normalize(),
+                    );
+                    let closest = trees
+                        .iter()
+                        .filter_map(|tree| {
+                            closest_ray_hit(&ray, tree, &shapes, Some(&details), &poses, filter)
+                        })
+                        .filter(|&(_, _, d)| max_distance.map(|m| d <= m * m).unwrap_or(true))
+                        .fold(None, |closest: Option<(_, Q, Real)>, hit| match closest {
+                            Some(c) if c.2 <= hit.2 => Some(c),
+                            _ => Some(hit),
+                        });
+                    query.hit = closest.map(|(other, point, _)| (other, point));
+                }
+                QueryVolume::Volume(ref primitive) => for tree in &trees {
+                    match self.narrow {
+                        Some(ref narrow) => query.overlaps.extend(
+                            query_overlap(
+                                primitive.clone(),
+                                pose,
+                                tree,
+                                &shapes,
+                                Some(&details),
+                                &poses,
+                                None,
+                                &**narrow,
+                            ).into_iter()
+                                .filter(|&other| filter(other)),
+                        ),
+                        None => {
+                            let mut shape = CollisionShape::new_simple(
+                                CollisionStrategy::CollisionOnly,
+                                CollisionMode::Discrete,
+                                primitive.clone(),
+                            );
+                            shape.update(pose, None);
+                            query.overlaps.extend(
+                                tree.query(&mut DiscreteVisitor::<P::Aabb, D>::new(shape.bound()))
+                                    .into_iter()
+                                    .map(|(value, _)| value.entity())
+                                    .filter(|&other| filter(other)),
+                            )
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
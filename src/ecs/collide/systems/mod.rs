@@ -1,9 +1,19 @@
 //! Contains systems for collision detection and spatial querying
 
+pub use self::activator::ActivatorSystem;
+pub use self::active_region::ActiveRegionSystem;
 pub use self::basic::BasicCollisionSystem;
+pub use self::chunk_streaming::ChunkStreamingSystem;
+pub use self::continuous_query::ContinuousQuerySystem;
 pub use self::spatial_collision::SpatialCollisionSystem;
 pub use self::spatial_sort::SpatialSortingSystem;
+pub use self::touching::TouchingSystem;
 
+mod activator;
+mod active_region;
 mod basic;
+mod chunk_streaming;
+mod continuous_query;
 mod spatial_sort;
 mod spatial_collision;
+mod touching;
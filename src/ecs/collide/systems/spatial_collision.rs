@@ -1,16 +1,27 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::Debug;
+use std::mem::size_of;
+use std::time::{Duration, Instant};
 
 use cgmath::prelude::*;
 use collision::dbvt::{DiscreteVisitor, DynamicBoundingVolumeTree, TreeValue};
 use collision::prelude::*;
-use shrev::EventChannel;
-use specs::{Component, Entities, Entity, FetchMut, Join, ReadStorage, System};
+use shrev::{EventChannel, ReaderId};
+use specs::{Component, Entities, Entity, Fetch, FetchMut, Join, ReadStorage, System};
 
 use {NextFrame, Real};
 use collide::{CollisionShape, CollisionStrategy, ContactEvent, Primitive};
 use collide::broad::{BroadPhase, HasBound};
-use collide::narrow::NarrowPhase;
-use ecs::collide::resources::{Contacts, GetEntity};
+use collide::bvh::BakedBoundingVolumeHierarchy;
+use collide::util::ContainerShapeWrapper;
+use collide::narrow::{NarrowPhase, PrimitiveContact};
+use ecs::collide::resources::{any_active, collides, excluded, same_assembly, steps_pair,
+                              split_silent_contacts, write_contact_events, Assembly,
+                              BroadPhaseStats, CollisionExclusions, CollisionOnlyChannel,
+                              CollisionWorldStats, ContactEventSettings, ContactOverflow, Contacts,
+                              Dormant, Frozen, GetEntity, SilentContacts, StaticBoundingVolumeTree,
+                              StaticShape, SteppedWorld, WorldId};
 
 /// Collision detection [system](https://docs.rs/specs/0.9.5/specs/trait.System.html) for use with
 /// [`specs`](https://docs.rs/specs/0.9.5/specs/).
@@ -22,6 +33,62 @@ use ecs::collide::resources::{Contacts, GetEntity};
 /// broad phase that has complexity O(m log^2 n), where m is the number of shapes that have a dirty
 /// pose.
 ///
+/// Narrow phase results are cached per pair of shapes, and reused as long as neither pose nor
+/// shape changes, so contacts between resting shapes are still reported, without running the
+/// narrow phase again.
+/// Cached pairs are keyed by entity, which includes the entity generation, and are dropped when
+/// either entity is deleted, so recycled entities never inherit stale contacts.
+///
+/// Note that contacts are not only reported when they begin: every cached contact is emitted
+/// again each frame, for as long as the shapes stay in contact. A pose or shape counts as changed
+/// when it is flagged in its `FlaggedStorage`, and the flags are never cleared by this library, so
+/// once a pose or shape has been written to, the narrow phase runs for the pairs of that shape
+/// every frame. Newly inserted components are flagged as well. To get any use out of the cache,
+/// clear the flags of the pose and shape storages after collision detection, e.g. with
+/// `(&mut poses).open().1.clear_flags()`. The contact solvers only write to the poses of bodies
+/// they move, so pairs of frozen, sleeping and static bodies keep their cached results.
+///
+/// An optional per frame time budget can be set for the narrow phase, see
+/// [`with_narrow_phase_budget`](#method.with_narrow_phase_budget). Pairs that are not tested when
+/// the budget runs out are queued, and tested before any new pairs in the following frames,
+/// ordered by how much their bounding volumes overlap. This keeps frame times bounded when a large
+/// number of pairs needs testing at once, at the cost of delayed contacts. Without a budget the
+/// system never reads the system clock.
+///
+/// Transient per frame data (changed entities, potential pairs and contact events) is kept in
+/// buffers owned by the system, that are cleared each frame but keep their allocation. The cached
+/// contact manifolds hold no heap data, and are stored contiguously, sorted by pair, in a buffer
+/// that is reused across frames, so adding and dropping cached pairs does not allocate per pair.
+/// No arena allocator is used: the tree nodes are owned by the trees, which keep them in a single
+/// vector and reuse the slots of removed nodes, and tree queries still allocate their results.
+///
+/// If a [`StaticBoundingVolumeTree`](struct.StaticBoundingVolumeTree.html) resource is present,
+/// shapes are also tested against the static tree, and static shapes are never tested against
+/// each other. The same goes for shapes in a
+/// [`BakedBoundingVolumeHierarchy`](../collide/bvh/struct.BakedBoundingVolumeHierarchy.html)
+/// resource.
+///
+/// Contact events are written using the overflow policy in the
+/// [`ContactEventSettings`](struct.ContactEventSettings.html) resource, if present. Contacts
+/// shallower than the minimum event depth in the settings are put in the
+/// [`SilentContacts`](struct.SilentContacts.html) resource instead, if present.
+///
+/// If a [`BroadPhaseStats`](struct.BroadPhaseStats.html) resource is present, the number of pairs
+/// tested by the narrow phase, and the number of those that were in contact, are written to it.
+/// If a [`CollisionWorldStats`](struct.CollisionWorldStats.html) resource is present, the number
+/// of cached and deferred pairs, and the estimated memory usage of the cache and the per frame
+/// buffers, are written to it.
+///
+/// Shapes are only tested against shapes in the same simulation world, see
+/// [`WorldId`](struct.WorldId.html), and only in the [`SteppedWorld`](struct.SteppedWorld.html),
+/// if present. Pairs of [`Dormant`](struct.Dormant.html) shapes are never
+/// tested. Parts of the same [`Assembly`](struct.Assembly.html), and pairs in the
+/// [`CollisionExclusions`](struct.CollisionExclusions.html) resource, if present, are never tested
+/// against each other. Each assembly has a single coarse entry covering all its parts, in a
+/// bounding volume tree of assemblies owned by the system. The entry is refit when the bound of any
+/// part changes, or when parts are added or removed, and only then queried against the trees. The
+/// individual parts are only tested when the coarse entry overlaps something.
+///
 /// Can handle any transform component type, as long as the type implements
 /// [`Transform`](https://docs.rs/cgmath/0.15.0/cgmath/trait.Transform.html), and as long as the
 /// storage is wrapped in
@@ -31,14 +98,26 @@ pub struct SpatialCollisionSystem<P, T, D>
 where
     P: Primitive,
     P::Aabb: Aabb<Scalar = Real> + Clone + Debug,
+    P::Point: Debug,
+    <P::Point as EuclideanSpace>::Diff: Debug,
 {
     narrow: Option<Box<NarrowPhase<P, T>>>,
     broad: Option<Box<BroadPhase<D>>>,
+    cache: ManifoldCache<P::Point>,
+    assembly_tree: AssemblyTree<P>,
+    members: Vec<(Assembly, Entity)>,
+    changed: HashSet<Entity>,
+    potentials: Vec<(Entity, Entity)>,
+    pending: Vec<(Entity, Entity)>,
+    budget: Option<Duration>,
+    events: Vec<ContactEvent<Entity, P::Point>>,
+    reader: Option<ReaderId<ContactEvent<Entity, P::Point>>>,
 }
 
 impl<P, T, D> SpatialCollisionSystem<P, T, D>
 where
     P: Primitive + Send + Sync + 'static,
+    P::Point: Debug,
     <P::Point as EuclideanSpace>::Diff: Debug,
     P::Aabb: Clone
         + Debug
@@ -57,6 +136,15 @@ where
         Self {
             narrow: None,
             broad: None,
+            cache: ManifoldCache::default(),
+            assembly_tree: AssemblyTree::new(),
+            members: Vec::default(),
+            changed: HashSet::default(),
+            potentials: Vec::default(),
+            pending: Vec::default(),
+            budget: None,
+            events: Vec::default(),
+            reader: None,
         }
     }
 
@@ -71,6 +159,32 @@ where
         self.broad = Some(Box::new(broad));
         self
     }
+
+    /// Specify a time budget for the narrow phase each frame.
+    ///
+    /// At least one pair is tested each frame, even if the budget is exhausted. The budget is
+    /// measured using `std::time::Instant`, which is only read when a budget is set, so don't set
+    /// a budget on targets without a system clock, like `wasm32-unknown-unknown`.
+    pub fn with_narrow_phase_budget(mut self, budget: Duration) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Get the number of pairs that were deferred to the next frame by the narrow phase budget
+    pub fn deferred_pairs(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+/// Volume of the overlap of two bounding volumes, zero if they are disjoint
+fn overlap<A>(left: &A, right: &A) -> Real
+where
+    A: Aabb<Scalar = Real>,
+{
+    let min = MinMax::max(left.min(), right.min());
+    let max = MinMax::min(left.max(), right.max());
+    // clamps the extent to zero along axes where the volumes are disjoint
+    (MinMax::max(max, min) - min).product()
 }
 
 fn discrete_visitor<P, D>(bound: &P::Aabb) -> DiscreteVisitor<P::Aabb, D>
@@ -84,6 +198,289 @@ where
     DiscreteVisitor::<P::Aabb, D>::new(bound)
 }
 
+/// Add all values hit by a coarse tree query for an assembly as potential colliders with the parts
+/// of the assembly whose bounds they intersect. Values in any assembly are skipped, those are found
+/// through the coarse entries of their assemblies instead.
+fn add_assembly_potentials<A, D, R>(
+    parts: &[(Entity, &A)],
+    hits: Vec<(&D, R)>,
+    assemblies: &ReadStorage<Assembly>,
+    potentials: &mut Vec<(Entity, Entity)>,
+) where
+    A: Discrete<A>,
+    D: GetEntity + TreeValue<Bound = A>,
+{
+    for (v, _) in hits {
+        let e = v.entity();
+        if assemblies.get(e).is_some() {
+            continue;
+        }
+        for &(entity, bound) in parts {
+            if bound.intersects(TreeValue::bound(v)) {
+                potentials.push(if entity < e { (entity, e) } else { (e, entity) });
+            }
+        }
+    }
+}
+
+/// Add the parts of two assemblies whose bounds intersect as potential colliders. Pairs of static
+/// parts are skipped.
+fn add_part_potentials<A>(
+    parts: &[(Entity, &A)],
+    others: &[(Entity, &A)],
+    statics: &ReadStorage<StaticShape>,
+    potentials: &mut Vec<(Entity, Entity)>,
+) where
+    A: Discrete<A>,
+{
+    for &(entity, bound) in parts {
+        for &(e, other) in others {
+            if statics.get(entity).is_some() && statics.get(e).is_some() {
+                continue;
+            }
+            if bound.intersects(other) {
+                potentials.push(if entity < e { (entity, e) } else { (e, entity) });
+            }
+        }
+    }
+}
+
+/// Add all values hit by a tree query as potential colliders with the given entity. The pairs are
+/// ordered, but the potentials list may contain duplicates, and needs to be deduplicated after all
+/// queries are done.
+fn add_potentials<D, R>(entity: Entity, hits: Vec<(&D, R)>, potentials: &mut Vec<(Entity, Entity)>)
+where
+    D: GetEntity,
+{
+    for (v, _) in hits {
+        let e = v.entity();
+        if entity != e {
+            potentials.push(if entity < e { (entity, e) } else { (e, entity) });
+        }
+    }
+}
+
+/// Coarse bounding volumes of assemblies, one tree entry per assembly, covering all its parts.
+///
+/// Entries are kept between frames, and only refit when the parts of the assembly change.
+struct AssemblyTree<P>
+where
+    P: Primitive,
+    P::Aabb: Aabb<Scalar = Real> + Clone + Debug,
+    P::Point: Debug,
+    <P::Point as EuclideanSpace>::Diff: Debug,
+{
+    tree: DynamicBoundingVolumeTree<ContainerShapeWrapper<Assembly, P>>,
+    entries: HashMap<Assembly, (usize, Vec<Entity>)>,
+}
+
+impl<P> AssemblyTree<P>
+where
+    P: Primitive,
+    P::Aabb: Aabb<Scalar = Real>
+        + Clone
+        + Debug
+        + Contains<P::Aabb>
+        + Union<P::Aabb, Output = P::Aabb>
+        + SurfaceArea<Scalar = Real>,
+    P::Point: Debug,
+    <P::Point as EuclideanSpace>::Diff: Debug,
+{
+    fn new() -> Self {
+        Self {
+            tree: DynamicBoundingVolumeTree::new(),
+            entries: HashMap::default(),
+        }
+    }
+
+    /// Refit the entries of assemblies in the dirty set, and of assemblies that gained or lost
+    /// parts, which are added to the dirty set. Entries of assemblies without any parts left are
+    /// removed.
+    ///
+    /// # Parameters:
+    ///
+    /// - `members`: All parts, sorted by assembly
+    /// - `dirty`: Assemblies with changed parts
+    /// - `bound`: Current bound of a part
+    fn refit<'b, F>(
+        &mut self,
+        members: &[(Assembly, Entity)],
+        dirty: &mut BTreeSet<Assembly>,
+        bound: F,
+    ) where
+        F: Fn(Entity) -> &'b P::Aabb,
+        P::Aabb: 'b,
+    {
+        let mut start = 0;
+        while start < members.len() {
+            let assembly = members[start].0;
+            let end = start
+                + members[start..]
+                    .iter()
+                    .take_while(|&&(a, _)| a == assembly)
+                    .count();
+            let parts = &members[start..end];
+            start = end;
+            let same = self.entries
+                .get(&assembly)
+                .map(|(_, known)| known.iter().eq(parts.iter().map(|(_, e)| e)))
+                .unwrap_or(false);
+            if same && !dirty.contains(&assembly) {
+                continue;
+            }
+            dirty.insert(assembly);
+            let coarse = parts
+                .iter()
+                .skip(1)
+                .fold(bound(parts[0].1).clone(), |coarse, &(_, e)| coarse.union(bound(e)));
+            let value = ContainerShapeWrapper::new(assembly, &coarse);
+            match self.entries.get_mut(&assembly) {
+                Some((node_index, known)) => {
+                    known.clear();
+                    known.extend(parts.iter().map(|&(_, e)| e));
+                    self.tree.update_node(*node_index, value);
+                }
+                None => {
+                    let node_index = self.tree.insert(value);
+                    let known = parts.iter().map(|&(_, e)| e).collect();
+                    self.entries.insert(assembly, (node_index, known));
+                }
+            }
+        }
+        let tree = &mut self.tree;
+        self.entries.retain(|assembly, &mut (node_index, _)| {
+            let present = members
+                .binary_search_by_key(assembly, |&(a, _)| a)
+                .is_ok();
+            if !present {
+                tree.remove(node_index);
+            }
+            present
+        });
+        self.tree.tick();
+    }
+
+    /// Coarse bound and parts of an assembly
+    fn get(&self, assembly: Assembly) -> Option<(&P::Aabb, &[Entity])> {
+        self.entries.get(&assembly).and_then(|(node_index, parts)| {
+            self.tree
+                .value_index(*node_index)
+                .map(|index| (&self.tree.values()[index].1.bound, &parts[..]))
+        })
+    }
+
+    /// Other assemblies whose coarse bounds intersect the coarse bound of the given assembly
+    fn overlapping(&self, assembly: Assembly, coarse: &P::Aabb) -> Vec<Assembly>
+    where
+        P::Aabb: Discrete<P::Aabb>,
+    {
+        self.tree
+            .query(&mut DiscreteVisitor::<P::Aabb, ContainerShapeWrapper<Assembly, P>>::new(coarse))
+            .into_iter()
+            .map(|(v, _)| v)
+            .filter(|v| v.id != assembly && coarse.intersects(&v.bound))
+            .map(|v| v.id)
+            .collect()
+    }
+}
+
+type CachedManifold<P> = ((Entity, Entity), Option<PrimitiveContact<P>>);
+
+/// Narrow phase results per pair of shapes, kept sorted by pair in contiguous storage.
+///
+/// New results are staged while the narrow phase runs, and merged into the sorted entries when
+/// committed. All buffers keep their allocation between frames.
+struct ManifoldCache<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    entries: Vec<CachedManifold<P>>,
+    staged: Vec<CachedManifold<P>>,
+    scratch: Vec<CachedManifold<P>>,
+}
+
+impl<P> Default for ManifoldCache<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    fn default() -> Self {
+        Self {
+            entries: Vec::default(),
+            staged: Vec::default(),
+            scratch: Vec::default(),
+        }
+    }
+}
+
+impl<P> ManifoldCache<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    fn contains(&self, pair: &(Entity, Entity)) -> bool {
+        self.entries
+            .binary_search_by(|(bodies, _)| bodies.cmp(pair))
+            .is_ok()
+    }
+
+    fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&(Entity, Entity)) -> bool,
+    {
+        self.entries.retain(|(bodies, _)| keep(bodies));
+    }
+
+    /// Stage a new result, it is not visible until the cache is committed
+    fn insert(&mut self, pair: (Entity, Entity), contact: Option<PrimitiveContact<P>>) {
+        self.staged.push((pair, contact));
+    }
+
+    /// Merge the staged results into the sorted entries, staged results replace existing ones
+    fn commit(&mut self) {
+        if self.staged.is_empty() {
+            return;
+        }
+        self.staged.sort_by_key(|entry| entry.0);
+        self.scratch.clear();
+        self.scratch.append(&mut self.entries);
+        let mut existing = self.scratch.drain(..).peekable();
+        let mut staged = self.staged.drain(..).peekable();
+        loop {
+            let order = match (existing.peek(), staged.peek()) {
+                (Some(a), Some(b)) => a.0.cmp(&b.0),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break,
+            };
+            let next = match order {
+                Ordering::Less => existing.next(),
+                Ordering::Greater => staged.next(),
+                Ordering::Equal => {
+                    existing.next();
+                    staged.next()
+                }
+            };
+            self.entries.extend(next);
+        }
+    }
+
+    fn iter(&self) -> ::std::slice::Iter<'_, CachedManifold<P>> {
+        self.entries.iter()
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Estimated memory usage of the cache in bytes
+    fn memory(&self) -> usize {
+        (self.entries.capacity() + self.staged.capacity() + self.scratch.capacity())
+            * size_of::<CachedManifold<P>>()
+    }
+}
+
 impl<'a, P, T, D> System<'a> for SpatialCollisionSystem<P, T, (usize, D)>
 where
     P: Primitive + Send + Sync + 'static,
@@ -95,12 +492,19 @@ where
         + Aabb<Scalar = Real>
         + Discrete<P::Aabb>
         + Contains<P::Aabb>
+        + Union<P::Aabb, Output = P::Aabb>
         + SurfaceArea<Scalar = Real>,
     <P::Point as EuclideanSpace>::Diff: Debug + Send + Sync + 'static,
     P::Point: Debug + Send + Sync + 'static,
     T: Component + Clone + Debug + Transform<P::Point> + Send + Sync + 'static,
-    for<'b: 'a> &'b T::Storage: Join<Type = &'b T>,
-    D: Send + Sync + 'static + TreeValue<Bound = P::Aabb> + HasBound<Bound = P::Aabb> + GetEntity,
+    for<'b> &'b T::Storage: Join<Type = &'b T>,
+    D: Send
+        + Sync
+        + 'static
+        + Clone
+        + TreeValue<Bound = P::Aabb>
+        + HasBound<Bound = P::Aabb>
+        + GetEntity,
 {
     type SystemData = (
         Entities<'a>,
@@ -109,114 +513,608 @@ where
         ReadStorage<'a, CollisionShape<P, T>>,
         Option<FetchMut<'a, Contacts<P::Point>>>,
         Option<FetchMut<'a, EventChannel<ContactEvent<Entity, P::Point>>>>,
+        Option<FetchMut<'a, CollisionOnlyChannel<P::Point>>>,
         FetchMut<'a, DynamicBoundingVolumeTree<D>>,
+        Option<Fetch<'a, StaticBoundingVolumeTree<D>>>,
+        Option<Fetch<'a, BakedBoundingVolumeHierarchy<D>>>,
+        ReadStorage<'a, StaticShape>,
+        ReadStorage<'a, WorldId>,
+        Option<Fetch<'a, SteppedWorld>>,
+        ReadStorage<'a, Assembly>,
+        Option<Fetch<'a, CollisionExclusions>>,
+        ReadStorage<'a, Frozen>,
+        ReadStorage<'a, Dormant>,
+        Option<FetchMut<'a, ContactEventSettings>>,
+        Option<FetchMut<'a, EventChannel<ContactOverflow>>>,
+        Option<FetchMut<'a, SilentContacts<P::Point>>>,
+        Option<FetchMut<'a, BroadPhaseStats>>,
+        Option<FetchMut<'a, CollisionWorldStats>>,
     );
 
     fn run(&mut self, system_data: Self::SystemData) {
-        let (entities, poses, next_poses, shapes, mut contacts, mut event_channel, mut tree) =
-            system_data;
+        let (
+            entities,
+            poses,
+            next_poses,
+            shapes,
+            mut contacts,
+            mut event_channel,
+            mut collision_only,
+            mut tree,
+            static_tree,
+            baked,
+            statics,
+            world_ids,
+            stepped,
+            assemblies,
+            exclusions,
+            frozen,
+            dormant,
+            mut settings,
+            mut overflow,
+            mut silent,
+            mut stats,
+            world_stats,
+        ) = system_data;
 
         if let Some(ref mut c) = contacts {
             c.clear();
         }
 
-        let potentials = if let Some(ref mut broad) = self.broad {
-            // Overridden broad phase, use that
-            let potentials = broad.find_potentials(tree.values_mut());
-            tree.reindex_values();
-            potentials
-                .iter()
-                .map(|&(ref l, ref r)| {
+        // find entities where the pose, the next frame pose or the shape changed
+        // uses FlaggedStorage
+        let changed = &mut self.changed;
+        changed.clear();
+        changed.extend(
+            (&*entities, (&poses).open().1)
+                .join()
+                .map(|(entity, _)| entity)
+                .chain((&*entities, (&next_poses).open().1).join().map(|(entity, _)| entity))
+                .chain((&*entities, (&shapes).open().1).join().map(|(entity, _)| entity)),
+        );
+
+        let potentials = &mut self.potentials;
+        potentials.clear();
+        match self.broad {
+            Some(ref mut broad) if static_tree.is_none() && baked.is_none() => {
+                // Overridden broad phase, use that
+                let pairs = broad.find_potentials(tree.values_mut());
+                tree.reindex_values();
+                potentials.extend(pairs.iter().map(|&(l, r)| {
                     (
-                        tree.values()[*l].1.entity().clone(),
-                        tree.values()[*r].1.entity().clone(),
+                        tree.values()[l].1.entity(),
+                        tree.values()[r].1.entity(),
                     )
-                })
-                .collect()
-        } else {
-            // Fallback to DBVT based broad phase
-            let mut potentials = Vec::default();
-            // find changed values, do intersection tests against tree for each
-            // uses FlaggedStorage
-            for (entity, _, shape) in (&*entities, (&poses).open().1, &shapes).join() {
-                for (v, _) in tree.query(&mut discrete_visitor::<P, D>(shape.bound())) {
-                    let e = v.entity();
-                    if entity != e {
-                        let n = if entity < e {
-                            (entity, e.clone())
-                        } else {
-                            (e.clone(), entity)
-                        };
-                        if let Err(pos) = potentials.binary_search(&n) {
-                            potentials.insert(pos, n);
-                        }
-                    }
+                }));
+            }
+            Some(ref mut broad) => {
+                // Overridden broad phase, run it over the values in all trees
+                let mut values = tree.values().to_vec();
+                if let Some(ref static_tree) = static_tree {
+                    values.extend(static_tree.values().iter().cloned());
+                }
+                if let Some(ref baked) = baked {
+                    values.extend(baked.values().iter().map(|v| (0, v.clone())));
                 }
+                potentials.extend(
+                    broad
+                        .find_potentials(&mut values)
+                        .iter()
+                        .map(|&(l, r)| (values[l].1.entity(), values[r].1.entity()))
+                        .filter(|&(l, r)| statics.get(l).is_none() || statics.get(r).is_none()),
+                );
             }
-            // find changed next frame values, do intersection tests against tree for each
-            // uses FlaggedStorage
-            for (entity, _, shape) in (&*entities, (&next_poses).open().1, &shapes).join() {
-                for (v, _) in tree.query(&mut discrete_visitor::<P, D>(shape.bound())) {
-                    let e = v.entity();
-                    if entity != e {
-                        let n = if entity < e {
-                            (entity, e.clone())
-                        } else {
-                            (e.clone(), entity)
-                        };
-                        if let Err(pos) = potentials.binary_search(&n) {
-                            potentials.insert(pos, n);
+            None => {
+                // Fallback to DBVT based broad phase
+                // find changed values, changed next frame values and modified shapes, do
+                // intersection tests against the trees for each
+                // uses FlaggedStorage
+                let changed = (&*entities, (&poses).open().1, &shapes)
+                    .join()
+                    .map(|(entity, _, shape)| (entity, shape))
+                    .chain(
+                        (&*entities, (&next_poses).open().1, &shapes)
+                            .join()
+                            .map(|(entity, _, shape)| (entity, shape)),
+                    )
+                    .chain((&*entities, (&shapes).open().1).join());
+                let mut dirty = BTreeSet::new();
+                for (entity, shape) in changed {
+                    // query only shapes are found by the queries of other shapes below, and
+                    // removed from the pairs with those
+                    if shape.is_query_only() {
+                        continue;
+                    }
+                    // parts of assemblies are queried together below
+                    if let Some(assembly) = assemblies.get(entity) {
+                        dirty.insert(*assembly);
+                        continue;
+                    }
+                    let hits = tree.query(&mut discrete_visitor::<P, D>(shape.bound()));
+                    add_potentials(entity, hits, potentials);
+                    // static shapes are never tested against other static shapes
+                    if statics.get(entity).is_some() {
+                        continue;
+                    }
+                    if let Some(ref static_tree) = static_tree {
+                        let hits = static_tree.query(&mut discrete_visitor::<P, D>(shape.bound()));
+                        add_potentials(entity, hits, potentials);
+                    }
+                    if let Some(ref baked) = baked {
+                        let hits = baked.query(&mut discrete_visitor::<P, D>(shape.bound()));
+                        add_potentials(entity, hits, potentials);
+                    }
+                }
+                // refit the coarse entries of assemblies with changed parts, only those are
+                // queried, and the parts are only tested against what the coarse entry hits
+                let members = &mut self.members;
+                members.clear();
+                members.extend(
+                    (&*entities, &assemblies, &shapes)
+                        .join()
+                        .filter(|&(_, _, shape)| !shape.is_query_only())
+                        .map(|(entity, assembly, _)| (*assembly, entity)),
+                );
+                members.sort_unstable();
+                let bound = |entity| shapes.get(entity).unwrap().bound();
+                let assembly_tree = &mut self.assembly_tree;
+                assembly_tree.refit(members, &mut dirty, bound);
+                let parts_of = |parts: &[Entity]| {
+                    parts
+                        .iter()
+                        .map(|&entity| (entity, bound(entity)))
+                        .collect::<Vec<_>>()
+                };
+                for assembly in dirty {
+                    let (coarse, mut parts) = match assembly_tree.get(assembly) {
+                        Some((coarse, parts)) => (coarse, parts_of(parts)),
+                        None => continue,
+                    };
+                    for other in assembly_tree.overlapping(assembly, coarse) {
+                        if let Some((_, others)) = assembly_tree.get(other) {
+                            add_part_potentials(&parts, &parts_of(others), &statics, potentials);
                         }
                     }
+                    let hits = tree.query(&mut discrete_visitor::<P, D>(coarse));
+                    add_assembly_potentials(&parts, hits, &assemblies, potentials);
+                    // static shapes are never tested against other static shapes
+                    parts.retain(|&(entity, _)| statics.get(entity).is_none());
+                    if parts.is_empty() {
+                        continue;
+                    }
+                    if let Some(ref static_tree) = static_tree {
+                        let hits = static_tree.query(&mut discrete_visitor::<P, D>(coarse));
+                        add_assembly_potentials(&parts, hits, &assemblies, potentials);
+                    }
+                    if let Some(ref baked) = baked {
+                        let hits = baked.query(&mut discrete_visitor::<P, D>(coarse));
+                        add_assembly_potentials(&parts, hits, &assemblies, potentials);
+                    }
                 }
+                potentials.sort_unstable();
+                potentials.dedup();
             }
-            potentials
+        }
+
+        // query only shapes are never paired with other shapes
+        let query_only = |entity| {
+            shapes
+                .get(entity)
+                .map(|shape| shape.is_query_only())
+                .unwrap_or(false)
         };
+        potentials.retain(|&(left, right)| !query_only(left) && !query_only(right));
+        // parts of the same assembly never collide, and neither do excluded pairs
+        potentials.retain(|&(left, right)| {
+            !same_assembly(&assemblies, left, right) && !excluded(&exclusions, left, right)
+        });
+        // entities in different simulation worlds never collide, and only the stepped world is
+        // tested, if any
+        let stepped = stepped.as_deref();
+        potentials.retain(|&(left, right)| steps_pair(stepped, &world_ids, left, right));
+        // frozen entities without collision are removed from collision detection
+        potentials.retain(|&(left, right)| collides(&frozen, left) && collides(&frozen, right));
+        // dormant entities outside the active regions are never tested against each other
+        potentials.retain(|&(left, right)| any_active(&dormant, left, right));
 
+        let events = &mut self.events;
+        events.clear();
         match self.narrow {
-            Some(ref narrow) => for (left_entity, right_entity) in potentials {
-                let left_shape = shapes.get(left_entity).unwrap();
-                let right_shape = shapes.get(right_entity).unwrap();
-                let left_pose = poses.get(left_entity).unwrap();
-                let right_pose = poses.get(right_entity).unwrap();
-                let left_next_pose = next_poses.get(left_entity).as_ref().map(|p| &p.value);
-                let right_next_pose = next_poses.get(right_entity).as_ref().map(|p| &p.value);
-                match narrow.collide_continuous(
-                    left_shape,
-                    left_pose,
-                    left_next_pose,
-                    right_shape,
-                    right_pose,
-                    right_next_pose,
-                ) {
-                    Some(contact) => {
-                        let event =
-                            ContactEvent::new((left_entity.clone(), right_entity.clone()), contact);
-                        if let Some(ref mut events) = event_channel {
-                            events.single_write(event);
-                        } else if let Some(ref mut c) = contacts {
-                            c.push(event);
+            Some(ref narrow) => {
+                // reuse last frame's narrow phase results for pairs where neither pose changed,
+                // pairs are keyed by entity including the generation, and dropped as soon as either
+                // entity is deleted, so a recycled entity never inherits its predecessor's contacts
+                let cache = &mut self.cache;
+                cache.retain(|&(left_entity, right_entity)| {
+                    entities.is_alive(left_entity) && entities.is_alive(right_entity)
+                        && any_active(&dormant, left_entity, right_entity)
+                        && steps_pair(stepped, &world_ids, left_entity, right_entity)
+                        && collides(&frozen, left_entity) && collides(&frozen, right_entity)
+                        && !same_assembly(&assemblies, left_entity, right_entity)
+                        && !excluded(&exclusions, left_entity, right_entity)
+                        && !changed.contains(&left_entity) && !changed.contains(&right_entity)
+                        && shapes.get(left_entity).is_some()
+                        && shapes.get(right_entity).is_some()
+                });
+
+                // pairs deferred by the budget are tested before any new pairs, so every pair is
+                // eventually tested, even if the budget keeps running out
+                let pending = &mut self.pending;
+                pending.retain(|&(left, right)| {
+                    entities.is_alive(left) && entities.is_alive(right)
+                        && shapes.get(left).is_some() && shapes.get(right).is_some()
+                        && steps_pair(stepped, &world_ids, left, right)
+                        && !same_assembly(&assemblies, left, right)
+                        && !excluded(&exclusions, left, right)
+                        && collides(&frozen, left) && collides(&frozen, right)
+                        && any_active(&dormant, left, right)
+                        && !cache.contains(&(left, right))
+                });
+                pending.sort_unstable();
+                pending.dedup();
+                potentials.sort_unstable();
+                potentials.dedup();
+                // the trees are only updated by the sorting system, so they can still hold values
+                // of entities whose shape has been removed since
+                potentials.retain(|&(left, right)| {
+                    shapes.get(left).is_some() && shapes.get(right).is_some()
+                });
+                potentials.retain(|pair| {
+                    !cache.contains(pair) && pending.binary_search(pair).is_err()
+                });
+
+                // the clock is only read when there is a budget, so the system runs on targets
+                // without one, like wasm32-unknown-unknown, where `Instant::now` panics
+                let deadline = self.budget.map(|budget| (Instant::now(), budget));
+                if deadline.is_some() {
+                    // deepest overlaps first, those are the most likely to be in contact
+                    let by_overlap = |&(left_a, right_a): &(Entity, Entity),
+                                      &(left_b, right_b): &(Entity, Entity)| {
+                        let a = overlap(
+                            shapes.get(left_a).unwrap().bound(),
+                            shapes.get(right_a).unwrap().bound(),
+                        );
+                        let b = overlap(
+                            shapes.get(left_b).unwrap().bound(),
+                            shapes.get(right_b).unwrap().bound(),
+                        );
+                        b.partial_cmp(&a).unwrap_or(Ordering::Equal)
+                    };
+                    pending.sort_by(&by_overlap);
+                    potentials.sort_by(&by_overlap);
+                }
+                pending.extend(potentials.iter().cloned());
+
+                let mut tested = 0;
+                let mut in_contact = 0;
+                for &(left_entity, right_entity) in pending.iter() {
+                    if let Some((start, budget)) = deadline {
+                        if tested > 0 && start.elapsed() >= budget {
+                            break;
                         }
                     }
-                    None => (),
-                };
-            },
+                    tested += 1;
+                    let left_shape = shapes.get(left_entity).unwrap();
+                    let right_shape = shapes.get(right_entity).unwrap();
+                    let left_pose = poses.get(left_entity).unwrap();
+                    let right_pose = poses.get(right_entity).unwrap();
+                    let left_next_pose = next_poses.get(left_entity).as_ref().map(|p| &p.value);
+                    let right_next_pose = next_poses.get(right_entity).as_ref().map(|p| &p.value);
+                    let contact = narrow.collide_continuous(
+                        left_shape,
+                        left_pose,
+                        left_next_pose,
+                        right_shape,
+                        right_pose,
+                        right_next_pose,
+                    );
+                    if contact.is_some() {
+                        in_contact += 1;
+                    }
+                    cache.insert((left_entity, right_entity), contact);
+                }
+                pending.drain(..tested);
+                cache.commit();
+                if let Some(ref mut stats) = stats {
+                    stats.candidates = tested;
+                    stats.contacts = in_contact;
+                }
+
+                for &(bodies, ref contact) in cache.iter() {
+                    if let Some((ref contact, primitives)) = *contact {
+                        let event = ContactEvent::new(bodies, contact.clone())
+                            .with_tags((
+                                shapes.get(bodies.0).and_then(|s| s.tag),
+                                shapes.get(bodies.1).and_then(|s| s.tag),
+                            ))
+                            .with_primitives(primitives);
+                        events.push(event);
+                    }
+                }
+            }
             None => {
                 // if we only have a broad phase, we generate contacts for aabb
                 // intersections
                 // right now, we only report the collision, no normal/depth calculation
-                for (left_entity, right_entity) in potentials {
+                for &(left_entity, right_entity) in potentials.iter() {
                     let event = ContactEvent::new_single(
                         CollisionStrategy::CollisionOnly,
                         (left_entity, right_entity),
-                    );
-                    if let Some(ref mut events) = event_channel {
-                        events.single_write(event);
-                    } else if let Some(ref mut c) = contacts {
-                        c.push(event);
-                    }
+                    ).with_tags((
+                        shapes.get(left_entity).and_then(|s| s.tag),
+                        shapes.get(right_entity).and_then(|s| s.tag),
+                    ));
+                    events.push(event);
                 }
             }
         }
+
+        split_silent_contacts(events, settings.as_deref(), silent.as_deref_mut());
+        write_contact_events(
+            events,
+            collision_only.as_deref_mut(),
+            event_channel.as_deref_mut(),
+            &mut self.reader,
+            contacts.as_deref_mut(),
+            settings.as_deref_mut(),
+            overflow.as_deref_mut(),
+        );
+
+        if let Some(mut world_stats) = world_stats {
+            world_stats.cached_pairs = self.cache.len();
+            world_stats.manifolds = self.cache.iter().filter(|c| c.1.is_some()).count();
+            world_stats.deferred_pairs = self.pending.len();
+            world_stats.cache_memory = self.cache.memory()
+                + (self.potentials.capacity() + self.pending.capacity())
+                    * size_of::<(Entity, Entity)>()
+                + self.changed.capacity() * size_of::<Entity>()
+                + self.members.capacity() * size_of::<(Assembly, Entity)>()
+                + self.events.capacity() * size_of::<ContactEvent<Entity, P::Point>>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, Point2, Vector2};
+    use collision::primitive::Rectangle;
+    use specs::{RunNow, World};
+
+    use super::*;
+    use ecs::collide::prelude2d::*;
+
+    #[test]
+    fn test_manifold_cache_merges_sorted() {
+        let mut world = World::new();
+        let e = (0..4)
+            .map(|_| world.create_entity().build())
+            .collect::<Vec<_>>();
+        let mut cache = ManifoldCache::<Point2<Real>>::default();
+        cache.insert((e[2], e[3]), None);
+        cache.insert((e[0], e[1]), None);
+        assert!(!cache.contains(&(e[0], e[1])));
+        cache.commit();
+        assert!(cache.contains(&(e[0], e[1])));
+        cache.insert((e[1], e[2]), None);
+        cache.insert((e[0], e[1]), None);
+        cache.commit();
+        let pairs = cache.iter().map(|entry| entry.0).collect::<Vec<_>>();
+        assert_eq!(vec![(e[0], e[1]), (e[1], e[2]), (e[2], e[3])], pairs);
+        cache.retain(|&(left, _)| left != e[1]);
+        assert_eq!(2, cache.len());
+        assert!(!cache.contains(&(e[1], e[2])));
+    }
+
+    type Systems = (
+        SpatialSortingSystem2<BodyPose2>,
+        SpatialCollisionSystem2<BodyPose2>,
+    );
+
+    fn collide(world: &World, systems: &mut Systems) -> (usize, usize) {
+        systems.0.run_now(&world.res);
+        systems.1.run_now(&world.res);
+        let tested = world.read_resource::<BroadPhaseStats>().candidates;
+        let contacts = world.read_resource::<Contacts2>().len();
+        (tested, contacts)
+    }
+
+    fn move_to(world: &World, entity: Entity, position: Point2<Real>) {
+        world
+            .write::<BodyPose2>()
+            .get_mut(entity)
+            .unwrap()
+            .set_position(position);
+    }
+
+    fn clear_flags(world: &World) {
+        (&mut world.write::<BodyPose2>()).open().1.clear_flags();
+        (&mut world.write::<CollisionShape2<BodyPose2>>())
+            .open()
+            .1
+            .clear_flags();
+    }
+
+    /// A row of overlapping boxes
+    fn row(count: usize) -> World {
+        let mut world = World::new();
+        world_register_with_spatial::<BodyPose2>(&mut world);
+        for i in 0..count {
+            world
+                .create_entity()
+                .with(CollisionShape2::<BodyPose2>::new_simple(
+                    CollisionStrategy::FullResolution,
+                    CollisionMode::Discrete,
+                    Rectangle::new(1., 1.).into(),
+                ))
+                .with(BodyPose2::new(
+                    Point2::new(i as Real * 0.9, i as Real * 0.1),
+                    Basis2::one(),
+                ))
+                .build();
+        }
+        world
+    }
+
+    #[test]
+    fn test_cache_reuses_and_invalidates_narrow_phase_results() {
+        let world = row(2);
+        let bodies = (&*world.entities()).join().collect::<Vec<_>>();
+        let mut system = (
+            SpatialSortingSystem2::new(),
+            SpatialCollisionSystem2::new().with_narrow_phase(GJK2::new()),
+        );
+
+        assert_eq!((1, 1), collide(&world, &mut system));
+        // flags are never cleared by the library, so the pair is tested again
+        assert_eq!((1, 1), collide(&world, &mut system));
+
+        // unchanged poses reuse the cached result, and the contact is still reported
+        clear_flags(&world);
+        assert_eq!((0, 1), collide(&world, &mut system));
+        assert_eq!((0, 1), collide(&world, &mut system));
+
+        // moving either shape invalidates the cached result
+        move_to(&world, bodies[1], Point2::new(0.5, 0.2));
+        assert_eq!((1, 1), collide(&world, &mut system));
+        clear_flags(&world);
+        assert_eq!((0, 1), collide(&world, &mut system));
+        // a shape moved out of range drops the cached contact, without testing the pair again
+        move_to(&world, bodies[0], Point2::new(-3., 0.));
+        assert_eq!((0, 0), collide(&world, &mut system));
+    }
+
+    #[test]
+    fn test_modified_shape_invalidates_cached_contacts() {
+        let world = row(2);
+        let bodies = (&*world.entities()).join().collect::<Vec<_>>();
+        let mut system = (
+            SpatialSortingSystem2::new(),
+            SpatialCollisionSystem2::new().with_narrow_phase(GJK2::new()),
+        );
+        assert_eq!((1, 1), collide(&world, &mut system));
+        clear_flags(&world);
+        assert_eq!((0, 1), collide(&world, &mut system));
+
+        // shrink one of the resting shapes, so they no longer touch
+        world.write::<CollisionShape2<BodyPose2>>().insert(
+            bodies[1],
+            CollisionShape2::new_simple(
+                CollisionStrategy::FullResolution,
+                CollisionMode::Discrete,
+                Rectangle::new(0.2, 0.2).into(),
+            ),
+        );
+        assert_eq!(0, collide(&world, &mut system).1);
+        clear_flags(&world);
+        assert_eq!((0, 0), collide(&world, &mut system));
+
+        // and grow it again, the pair is found and tested again
+        world
+            .write::<CollisionShape2<BodyPose2>>()
+            .get_mut(bodies[1])
+            .unwrap()
+            .set_primitive_scale(0, Vector2::new(5., 5.));
+        assert_eq!((1, 1), collide(&world, &mut system));
+    }
+
+    #[test]
+    fn test_frozen_without_collision_drops_cached_contacts() {
+        let world = row(2);
+        let bodies = (&*world.entities()).join().collect::<Vec<_>>();
+        let mut system = (
+            SpatialSortingSystem2::new(),
+            SpatialCollisionSystem2::new().with_narrow_phase(GJK2::new()),
+        );
+        assert_eq!((1, 1), collide(&world, &mut system));
+        clear_flags(&world);
+        assert_eq!((0, 1), collide(&world, &mut system));
+
+        // freezing does not change the pose, but the contact is still gone in the next frame
+        world
+            .write::<Frozen>()
+            .insert(bodies[0], Frozen::without_collision());
+        assert_eq!((0, 0), collide(&world, &mut system));
+    }
+
+    #[test]
+    fn test_assemblies_keep_coarse_entries() {
+        let mut world = row(5);
+        let bodies = (&*world.entities()).join().collect::<Vec<_>>();
+        let mut system = (
+            SpatialSortingSystem2::new(),
+            SpatialCollisionSystem2::new().with_narrow_phase(GJK2::new()),
+        );
+        // two assemblies of two parts, that touch at the second and third box
+        for (i, &entity) in bodies[..4].iter().enumerate() {
+            world
+                .write::<Assembly>()
+                .insert(entity, Assembly(i as u32 / 2));
+        }
+        assert_eq!((2, 2), collide(&world, &mut system));
+        assert_eq!(2, system.1.assembly_tree.entries.len());
+
+        // unchanged assemblies are not queried again
+        clear_flags(&world);
+        assert_eq!((0, 2), collide(&world, &mut system));
+
+        // moving a part refits the coarse entry, and the pair with the other assembly is tested
+        move_to(&world, bodies[3], Point2::new(1.5, 0.3));
+        assert_eq!((1, 2), collide(&world, &mut system));
+        clear_flags(&world);
+
+        // removing all parts of an assembly removes its coarse entry
+        world.delete_entity(bodies[2]).unwrap();
+        world.delete_entity(bodies[3]).unwrap();
+        world.maintain();
+        assert_eq!((0, 0), collide(&world, &mut system));
+        assert_eq!(1, system.1.assembly_tree.entries.len());
+    }
+
+    #[test]
+    fn test_budget_skips_pairs_with_removed_shapes() {
+        let world = row(3);
+        let bodies = (&*world.entities()).join().collect::<Vec<_>>();
+        let mut system = (
+            SpatialSortingSystem2::new(),
+            SpatialCollisionSystem2::new()
+                .with_narrow_phase(GJK2::new())
+                .with_narrow_phase_budget(Duration::new(1, 0)),
+        );
+        assert_eq!((2, 2), collide(&world, &mut system));
+        clear_flags(&world);
+
+        // the tree still holds the removed shape, until the sorting system runs again
+        world.write::<CollisionShape2<BodyPose2>>().remove(bodies[1]);
+        move_to(&world, bodies[0], Point2::new(0., 0.));
+        system.1.run_now(&world.res);
+        assert_eq!(0, world.read_resource::<BroadPhaseStats>().candidates);
+        assert_eq!(0, world.read_resource::<Contacts2>().len());
+    }
+
+    #[test]
+    fn test_narrow_phase_budget_defers_pairs() {
+        // without a budget, all pairs are tested at once, and the clock is never read
+        let world = row(4);
+        let mut system = (
+            SpatialSortingSystem2::new(),
+            SpatialCollisionSystem2::new().with_narrow_phase(GJK2::new()),
+        );
+        assert_eq!((3, 3), collide(&world, &mut system));
+        assert_eq!(0, system.1.deferred_pairs());
+
+        // an exhausted budget tests a single pair per frame, the rest are deferred
+        let world = row(4);
+        let mut system = (
+            SpatialSortingSystem2::new(),
+            SpatialCollisionSystem2::new()
+                .with_narrow_phase(GJK2::new())
+                .with_narrow_phase_budget(Duration::new(0, 0)),
+        );
+        assert_eq!((1, 1), collide(&world, &mut system));
+        assert_eq!(2, system.1.deferred_pairs());
+        clear_flags(&world);
+        assert_eq!((1, 2), collide(&world, &mut system));
+        assert_eq!((1, 3), collide(&world, &mut system));
+        assert_eq!(0, system.1.deferred_pairs());
     }
 }
@@ -1,20 +1,53 @@
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::marker::PhantomData;
+use std::mem::size_of;
 
 use cgmath::prelude::*;
 use collision::dbvt::{DynamicBoundingVolumeTree, TreeValue};
 use collision::prelude::*;
-use specs::{Component, Entities, Entity, FetchMut, Join, ReadStorage, System, WriteStorage};
+use specs::{Component, Entities, Entity, Fetch, FetchMut, Join, ReadStorage, System,
+            WriteStorage};
 
 use {NextFrame, Real};
 use collide::{CollisionShape, Primitive};
+use collide::bvh::BakedBoundingVolumeHierarchy;
+use collide::util::{compact_tree, FatMargin};
+use ecs::collide::resources::{BroadPhaseStats, BroadPhaseTuning, CollisionWorldStats, Dormant,
+                              StaticBoundingVolumeTree, StaticShape, TreeCompaction};
+
+/// Default fat bound margin
+const DEFAULT_FAT_MARGIN: Real = 1.;
 
 /// Spatial sorting [system](https://docs.rs/specs/0.9.5/specs/trait.System.html) for use with
 /// [`specs`](https://docs.rs/specs/0.9.5/specs/).
 ///
 /// Will perform spatial sorting of the collision world. Uses a Dynamic Bounding Volume Tree for
-/// sorting. Will update entries in the tree where the pose is dirty.
+/// sorting. Will update entries in the tree where the pose is dirty, or where the shape was
+/// modified, i.e. is flagged in the `FlaggedStorage` of the shapes. Updating the bounds of a shape
+/// does not flag it.
+///
+/// If a [`StaticBoundingVolumeTree`](struct.StaticBoundingVolumeTree.html) resource is present,
+/// shapes with the [`StaticShape`](struct.StaticShape.html) marker are put in that tree instead.
+/// If a [`BakedBoundingVolumeHierarchy`](../collide/bvh/struct.BakedBoundingVolumeHierarchy.html)
+/// resource is present, shapes with the marker are assumed to be in the baked hierarchy, and are
+/// not put in any tree.
+///
+/// The tree entries of [`Dormant`](struct.Dormant.html) shapes are left untouched.
+///
+/// Removing shapes leaves free slots in the trees' node storage. The trees can be compacted
+/// automatically after a number of removals, see
+/// [`with_compaction_threshold`](#method.with_compaction_threshold), or on demand using the
+/// [`TreeCompaction`](struct.TreeCompaction.html) resource. The dynamic tree can also be
+/// optimized periodically, by rebuilding it, see
+/// [`with_optimization_interval`](#method.with_optimization_interval).
+///
+/// If a [`BroadPhaseStats`](struct.BroadPhaseStats.html) resource is present, tree metrics and
+/// the current fat bound margin and optimization interval are written to it. The margin and the
+/// interval can be tuned automatically from the metrics, see
+/// [`with_auto_tuning`](#method.with_auto_tuning). If a
+/// [`CollisionWorldStats`](struct.CollisionWorldStats.html) resource is present, the number of
+/// shapes, the size and height of the trees, and their estimated memory usage are written to it.
 ///
 /// Can handle any transform component type, as long as the type implements
 /// [`Transform`](https://docs.rs/cgmath/0.15.0/cgmath/trait.Transform.html), and as long as the
@@ -26,11 +59,19 @@ use collide::{CollisionShape, Primitive};
 /// - `P`: Primitive type, needs to implement `Primitive`.
 /// - `T`: Transform type, needs to implement `Transform` and have `FlaggedStorage`.
 /// - `D`: Type of values stored in the DBVT, needs to implement `TreeValue` and
-///        `From<(Entity, CollisionShape)>`
+///   `From<(Entity, CollisionShape)>` and `FatMargin`
 ///
 #[derive(Debug)]
 pub struct SpatialSortingSystem<P, T, D> {
     entities: HashMap<Entity, usize>,
+    static_entities: HashMap<Entity, usize>,
+    leaves: HashMap<Entity, D>,
+    removed: usize,
+    compaction_threshold: Option<usize>,
+    fat_margin: Real,
+    optimization_interval: Option<usize>,
+    frames: usize,
+    tuning: Option<BroadPhaseTuning>,
     marker: PhantomData<(P, T, D)>,
 }
 
@@ -39,9 +80,157 @@ impl<P, T, D> SpatialSortingSystem<P, T, D> {
     pub fn new() -> Self {
         Self {
             entities: HashMap::default(),
+            static_entities: HashMap::default(),
+            leaves: HashMap::default(),
+            removed: 0,
+            compaction_threshold: None,
+            fat_margin: DEFAULT_FAT_MARGIN,
+            optimization_interval: None,
+            frames: 0,
+            tuning: None,
             marker: PhantomData,
         }
     }
+
+    /// Compact the trees automatically, when the given number of shapes have been removed since
+    /// the last compaction.
+    pub fn with_compaction_threshold(mut self, removals: usize) -> Self {
+        self.compaction_threshold = Some(removals);
+        self
+    }
+
+    /// Set the margin used for the fat bounds of shapes in the dynamic tree
+    pub fn with_fat_margin(mut self, margin: Real) -> Self {
+        self.fat_margin = margin;
+        self
+    }
+
+    /// Optimize the dynamic tree by rebuilding it, every given number of frames
+    pub fn with_optimization_interval(mut self, frames: usize) -> Self {
+        self.optimization_interval = Some(frames);
+        self
+    }
+
+    /// Tune the fat bound margin and the tree optimization interval automatically, using the
+    /// metrics in the [`BroadPhaseStats`](struct.BroadPhaseStats.html) resource.
+    ///
+    /// If no optimization interval has been set, the largest interval of the tuning settings is
+    /// used as a starting point.
+    pub fn with_auto_tuning(mut self, tuning: BroadPhaseTuning) -> Self {
+        if self.optimization_interval.is_none() {
+            self.optimization_interval = Some(tuning.max_optimization_interval);
+        }
+        self.tuning = Some(tuning);
+        self
+    }
+
+    /// Get the fat bound margin currently used for the dynamic tree
+    pub fn fat_margin(&self) -> Real {
+        self.fat_margin
+    }
+
+    /// Get the number of frames between tree optimizations currently used, if any
+    pub fn optimization_interval(&self) -> Option<usize> {
+        self.optimization_interval
+    }
+
+    /// Adjust the margin and optimization interval from the metrics of the last frame
+    fn tune(&mut self, stats: &BroadPhaseStats, shapes: usize) {
+        let tuning = match self.tuning {
+            Some(ref tuning) => tuning,
+            None => return,
+        };
+        if stats.escape_ratio() > tuning.target_escape_ratio {
+            self.fat_margin = (self.fat_margin * (1. + tuning.adjust_rate))
+                .min(tuning.max_fat_margin);
+        } else if stats.false_positive_ratio() > tuning.target_false_positive_ratio {
+            self.fat_margin = (self.fat_margin * (1. - tuning.adjust_rate))
+                .max(tuning.min_fat_margin);
+        }
+        if let Some(interval) = self.optimization_interval {
+            let moving = if shapes == 0 {
+                0.
+            } else {
+                stats.updates as Real / shapes as Real
+            };
+            self.optimization_interval = Some(if moving > 0.5 {
+                (interval / 2).max(tuning.min_optimization_interval)
+            } else if moving < 0.1 {
+                (interval * 2).min(tuning.max_optimization_interval)
+            } else {
+                interval
+            });
+        }
+    }
+}
+
+/// Set the fat bound margin on a tree value
+fn with_margin<D: FatMargin>(mut value: D, margin: Real) -> D {
+    value.set_fat_margin(margin);
+    value
+}
+
+/// Check if the new value for a shape moved outside the fat bound of its tree leaf. The tree
+/// will refit the leaf in that case, so the value is stored as the new source of the leaf bound.
+fn escapes<D>(leaves: &mut HashMap<Entity, D>, entity: Entity, value: &D) -> bool
+where
+    D: TreeValue,
+    D::Bound: Contains<D::Bound>,
+{
+    let escaped = leaves
+        .get(&entity)
+        .map(|leaf| !leaf.fat_bound().contains(value.bound()))
+        .unwrap_or(true);
+    if escaped {
+        leaves.insert(entity, value.clone());
+    }
+    escaped
+}
+
+/// Reset the leaf sources after the tree has been rebuilt
+fn reset_leaves<D>(
+    leaves: &mut HashMap<Entity, D>,
+    tree: &DynamicBoundingVolumeTree<D>,
+    entities: &HashMap<Entity, usize>,
+) where
+    D: TreeValue,
+    D::Bound: Clone + Debug + Contains<D::Bound> + Union<D::Bound, Output = D::Bound> + SurfaceArea,
+{
+    leaves.clear();
+    leaves.extend(entities.iter().filter_map(|(&entity, &node_index)| {
+        tree.value_index(node_index)
+            .map(|index| (entity, tree.values()[index].1.clone()))
+    }));
+}
+
+/// Estimated memory usage of a tree in bytes, the nodes are assumed to hold a bound, and parent
+/// and child indices
+fn tree_memory<D>(tree: &DynamicBoundingVolumeTree<D>) -> usize
+where
+    D: TreeValue,
+    D::Bound: Clone + Debug + Contains<D::Bound> + Union<D::Bound, Output = D::Bound> + SurfaceArea,
+{
+    tree.size() * (size_of::<D::Bound>() + 4 * size_of::<usize>())
+        + tree.values().capacity() * size_of::<(usize, D)>()
+}
+
+/// Compact the tree, and update the stored node indices.
+///
+/// Entities whose node is no longer in the tree, e.g. because the tree resource was modified
+/// outside the system, are dropped, and their shapes are inserted again in the next frame.
+fn compact<D>(tree: &mut DynamicBoundingVolumeTree<D>, entities: &mut HashMap<Entity, usize>)
+where
+    D: TreeValue,
+    D::Bound: Clone + Debug + Contains<D::Bound> + Union<D::Bound, Output = D::Bound> + SurfaceArea,
+{
+    let indices = compact_tree(tree).into_iter().collect::<HashMap<_, _>>();
+    entities.retain(|_, node_index| match indices.get(node_index) {
+        Some(&index) => {
+            *node_index = index;
+            true
+        }
+        None => false,
+    });
 }
 
 impl<'a, P, T, D> System<'a> for SpatialSortingSystem<P, T, D>
@@ -57,29 +246,92 @@ where
     P::Point: Debug,
     <P::Point as EuclideanSpace>::Diff: Debug + Send + Sync,
     T: Component + Clone + Debug + Transform<P::Point> + Send + Sync,
-    for<'b: 'a> &'b T::Storage: Join<Type = &'b T>,
-    D: Send + Sync + 'static + TreeValue<Bound = P::Aabb>,
-    for<'c: 'a> D: From<(Entity, &'c CollisionShape<P, T>)>,
+    for<'b> &'b T::Storage: Join<Type = &'b T>,
+    D: Send + Sync + 'static + TreeValue<Bound = P::Aabb> + FatMargin,
+    for<'c> D: From<(Entity, &'c CollisionShape<P, T>)>,
 {
     type SystemData = (
         Entities<'a>,
         ReadStorage<'a, T>,
         ReadStorage<'a, NextFrame<T>>,
         WriteStorage<'a, CollisionShape<P, T>>,
+        ReadStorage<'a, StaticShape>,
+        ReadStorage<'a, Dormant>,
         FetchMut<'a, DynamicBoundingVolumeTree<D>>,
+        Option<FetchMut<'a, StaticBoundingVolumeTree<D>>>,
+        Option<Fetch<'a, BakedBoundingVolumeHierarchy<D>>>,
+        Option<FetchMut<'a, TreeCompaction>>,
+        Option<FetchMut<'a, BroadPhaseStats>>,
+        Option<FetchMut<'a, CollisionWorldStats>>,
     );
 
-    fn run(&mut self, (entities, poses, next_poses, mut shapes, mut tree): Self::SystemData) {
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            poses,
+            next_poses,
+            mut shapes,
+            statics,
+            dormant,
+            mut tree,
+            mut static_tree,
+            baked,
+            mut compaction,
+            mut stats,
+            world_stats,
+        ) = data;
+
+        if let Some(ref stats) = stats {
+            let shapes = self.entities.len();
+            self.tune(stats, shapes);
+        }
+        let mut updates = 0;
+        let mut escaped = 0;
         let mut keys = self.entities.keys().cloned().collect::<HashSet<Entity>>();
+        let mut static_keys = self.static_entities
+            .keys()
+            .cloned()
+            .collect::<HashSet<Entity>>();
+
+        // shapes modified since the flags were last cleared, updating the bounds below is not a
+        // modification of the shape, so the flags are restored afterwards
+        // Uses FlaggedStorage
+        let modified = (&*entities, (&shapes).open().1)
+            .join()
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
 
-        // Check for updated poses that are already in the tree
+        // Check for updated poses and modified shapes that are already in the tree
         // Uses FlaggedStorage
-        for (entity, pose, shape) in (&*entities, (&poses).open().1, &mut shapes).join() {
+        let mut updated = (&*entities, (&poses).open().1, &shapes)
+            .join()
+            .map(|(entity, _, _)| entity)
+            .chain(modified.iter().cloned())
+            .collect::<Vec<_>>();
+        updated.sort();
+        updated.dedup();
+        for entity in updated {
+            if dormant.get(entity).is_some() {
+                continue;
+            }
+            let (pose, shape) = match (poses.get(entity), shapes.get_mut(entity)) {
+                (Some(pose), Some(shape)) => (pose, shape),
+                _ => continue,
+            };
             shape.update(&pose, None);
 
             // Update the wrapper in the tree for the shape
             if let Some(node_index) = self.entities.get(&entity).cloned() {
-                tree.update_node(node_index, (entity, &*shape).into());
+                let value = with_margin((entity, &*shape).into(), self.fat_margin);
+                updates += 1;
+                if escapes(&mut self.leaves, entity, &value) {
+                    escaped += 1;
+                }
+                tree.update_node(node_index, value);
+            } else if let (Some(node_index), Some(ref mut static_tree)) =
+                (self.static_entities.get(&entity).cloned(), static_tree.as_mut())
+            {
+                static_tree.update_node(node_index, (entity, &*shape).into());
             }
         }
 
@@ -88,11 +340,30 @@ where
         for (entity, pose, next_pose, shape) in
             (&*entities, &poses, (&next_poses).open().1, &mut shapes).join()
         {
+            if dormant.get(entity).is_some() {
+                continue;
+            }
             shape.update(&pose, Some(&next_pose.value));
 
             // Update the wrapper in the tree for the shape
             if let Some(node_index) = self.entities.get(&entity).cloned() {
-                tree.update_node(node_index, (entity, &*shape).into());
+                let value = with_margin((entity, &*shape).into(), self.fat_margin);
+                updates += 1;
+                if escapes(&mut self.leaves, entity, &value) {
+                    escaped += 1;
+                }
+                tree.update_node(node_index, value);
+            } else if let (Some(node_index), Some(ref mut static_tree)) =
+                (self.static_entities.get(&entity).cloned(), static_tree.as_mut())
+            {
+                static_tree.update_node(node_index, (entity, &*shape).into());
+            }
+        }
+        {
+            let flags = (&mut shapes).open().1;
+            flags.clear_flags();
+            for entity in modified {
+                flags.flag(entity);
             }
         }
 
@@ -101,11 +372,27 @@ where
         for (entity, _, shape) in (&*entities, &poses, &shapes).join() {
             // entity still exists, remove from deletion list
             keys.remove(&entity);
+            static_keys.remove(&entity);
 
             // if entity does not exist in entities list, add it to the tree and entities list
-            if let None = self.entities.get(&entity) {
-                let node_index = tree.insert((entity, &*shape).into());
-                self.entities.insert(entity, node_index);
+            if self.entities.contains_key(&entity) || self.static_entities.contains_key(&entity) {
+                continue;
+            }
+            // static shapes are already in the baked hierarchy
+            if baked.is_some() && statics.get(entity).is_some() {
+                continue;
+            }
+            match static_tree.as_mut() {
+                Some(ref mut static_tree) if statics.get(entity).is_some() => {
+                    let node_index = static_tree.insert((entity, shape).into());
+                    self.static_entities.insert(entity, node_index);
+                }
+                _ => {
+                    let value: D = with_margin((entity, shape).into(), self.fat_margin);
+                    self.leaves.insert(entity, value.clone());
+                    let node_index = tree.insert(value);
+                    self.entities.insert(entity, node_index);
+                }
             }
         }
 
@@ -115,6 +402,8 @@ where
                 Some(node_index) => {
                     tree.remove(node_index);
                     self.entities.remove(&entity);
+                    self.leaves.remove(&entity);
+                    self.removed += 1;
                 }
                 None => (),
             }
@@ -125,5 +414,120 @@ where
 
         // do refitting
         tree.do_refit();
+
+        // same for the static tree, this should normally be a no-op
+        if let Some(ref mut static_tree) = static_tree {
+            for entity in static_keys {
+                if let Some(node_index) = self.static_entities.remove(&entity) {
+                    static_tree.remove(node_index);
+                    self.removed += 1;
+                }
+            }
+            static_tree.update();
+            static_tree.do_refit();
+        }
+
+        let requested = compaction.as_mut().map(|c| c.take()).unwrap_or(false);
+        let threshold = self.compaction_threshold
+            .map(|threshold| self.removed >= threshold)
+            .unwrap_or(false);
+        if requested || threshold {
+            compact(&mut tree, &mut self.entities);
+            reset_leaves(&mut self.leaves, &tree, &self.entities);
+            if let Some(ref mut static_tree) = static_tree {
+                compact(static_tree, &mut self.static_entities);
+            }
+            self.removed = 0;
+        }
+
+        // periodic optimization of the dynamic tree, rebuilding it improves the tree quality after
+        // many updates
+        self.frames += 1;
+        if let Some(interval) = self.optimization_interval {
+            if self.frames >= interval {
+                compact(&mut tree, &mut self.entities);
+                reset_leaves(&mut self.leaves, &tree, &self.entities);
+                self.frames = 0;
+            }
+        }
+
+        if let Some(ref mut stats) = stats {
+            stats.updates = updates;
+            stats.escapes = escaped;
+            stats.fat_margin = self.fat_margin;
+            stats.optimization_interval = self.optimization_interval;
+        }
+
+        if let Some(mut world_stats) = world_stats {
+            world_stats.shapes = self.entities.len();
+            world_stats.static_shapes = self.static_entities.len();
+            world_stats.tree_nodes = tree.size();
+            world_stats.tree_height = tree.height();
+            world_stats.static_tree_nodes = static_tree.as_ref().map(|t| t.size()).unwrap_or(0);
+            world_stats.static_tree_height = static_tree.as_ref().map(|t| t.height()).unwrap_or(0);
+            world_stats.tree_memory = tree_memory(&tree)
+                + static_tree.as_ref().map(|t| tree_memory(t)).unwrap_or(0)
+                + (self.entities.capacity() + self.static_entities.capacity())
+                    * size_of::<(Entity, usize)>()
+                + self.leaves.capacity() * size_of::<(Entity, D)>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, One, Point2};
+    use collision::primitive::Rectangle;
+    use specs::{Entity, RunNow, World};
+
+    use ecs::collide::GetEntity;
+    use ecs::collide::prelude2d::*;
+    use Real;
+
+    #[test]
+    fn test_compaction_drops_missing_nodes() {
+        let mut world = World::new();
+        world_register_with_spatial::<BodyPose2>(&mut world);
+        let bodies = (0..10)
+            .map(|i| {
+                world
+                    .create_entity()
+                    .with(CollisionShape2::<BodyPose2>::new_simple(
+                        CollisionStrategy::FullResolution,
+                        CollisionMode::Discrete,
+                        Rectangle::new(1., 1.).into(),
+                    ))
+                    .with(BodyPose2::new(Point2::new(i as Real * 2., 0.), Basis2::one()))
+                    .build()
+            })
+            .collect::<Vec<_>>();
+        let mut system = SpatialSortingSystem2::<BodyPose2>::new().with_compaction_threshold(3);
+        system.run_now(&world.res);
+
+        for &entity in &bodies[..3] {
+            world.delete_entity(entity).unwrap();
+        }
+        world.maintain();
+        // remove a leaf behind the back of the system
+        {
+            let mut tree = world.write_resource::<DynamicBoundingVolumeTree2>();
+            let node_index = tree.values()
+                .iter()
+                .find(|&(_, value)| value.entity() == bodies[5])
+                .map(|&(node_index, _)| node_index)
+                .unwrap();
+            tree.remove(node_index);
+        }
+        // the removals trigger a compaction, the missing leaf is inserted again in the next frame
+        system.run_now(&world.res);
+        system.run_now(&world.res);
+
+        let tree = world.read_resource::<DynamicBoundingVolumeTree2>();
+        let mut in_tree = tree.values()
+            .iter()
+            .map(|(_, value)| value.entity())
+            .collect::<Vec<Entity>>();
+        in_tree.sort();
+        assert_eq!(bodies[3..].to_vec(), in_tree);
     }
 }
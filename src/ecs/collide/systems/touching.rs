@@ -0,0 +1,66 @@
+use std::fmt::Debug;
+
+use cgmath::prelude::*;
+use shrev::{EventChannel, ReaderId};
+use specs::{Entity, Fetch, Join, System, WriteStorage};
+
+use Real;
+use collide::ContactEvent;
+use ecs::collide::Touching;
+
+/// Touching system, materializes the contacts of the current frame into the
+/// [`Touching`](struct.Touching.html) components of the entities.
+///
+/// All `Touching` components are cleared, and then updated with the contact events read from the
+/// contact event channel. Should run after collision detection.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+pub struct TouchingSystem<P>
+where
+    P: EuclideanSpace + 'static,
+    P::Diff: Debug,
+{
+    contact_reader: ReaderId<ContactEvent<Entity, P>>,
+}
+
+impl<P> TouchingSystem<P>
+where
+    P: EuclideanSpace + 'static,
+    P::Diff: Debug,
+{
+    /// Create a new touching system, reading contacts with the given reader
+    pub fn new(contact_reader: ReaderId<ContactEvent<Entity, P>>) -> Self {
+        Self { contact_reader }
+    }
+}
+
+impl<'a, P> System<'a> for TouchingSystem<P>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: VectorSpace<Scalar = Real> + Debug + Send + Sync + 'static,
+{
+    type SystemData = (
+        Fetch<'a, EventChannel<ContactEvent<Entity, P>>>,
+        WriteStorage<'a, Touching<P::Diff>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (contacts, mut touching) = data;
+        for touching in (&mut touching).join() {
+            touching.clear();
+        }
+        for contact in contacts.lossy_read(&mut self.contact_reader) {
+            let (left, right) = contact.bodies;
+            let normal = contact.contact.normal;
+            let depth = contact.contact.penetration_depth;
+            if let Some(touching) = touching.get_mut(left) {
+                touching.add(right, normal * -1., depth);
+            }
+            if let Some(touching) = touching.get_mut(right) {
+                touching.add(left, normal, depth);
+            }
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+use specs::{Dispatcher, World};
+
+use Real;
+use ecs::physics::resources::DeltaTime;
+
+enum Command {
+    Step(Real, Box<dyn FnMut(&mut World) + Send>),
+}
+
+/// Double buffered physics stepping on a background thread.
+///
+/// The physics simulation lives in its own `World`, with its own `Dispatcher` containing the
+/// collision and physics systems. Both are created on, and owned by, a worker thread, because a
+/// `Dispatcher` can not be moved between threads. When a step is started, the inputs for the step
+/// are applied to the simulation world, the dispatcher is run, and a snapshot of the results is
+/// extracted, all on the worker thread.
+///
+/// While the step is running, the main thread can read the snapshot from the previous step using
+/// [`front`](#method.front). At the sync point, [`sync`](#method.sync), the new snapshot replaces
+/// the previous one.
+///
+/// # Type parameters:
+///
+/// - `S`: Snapshot type, containing whatever the main thread needs from the simulation
+pub struct BackgroundPhysics<S> {
+    commands: Option<Sender<Command>>,
+    results: Receiver<S>,
+    worker: Option<JoinHandle<()>>,
+    running: bool,
+    front: Option<S>,
+}
+
+impl<S> BackgroundPhysics<S>
+where
+    S: Send + 'static,
+{
+    /// Create a new background physics stepper, and start its worker thread.
+    ///
+    /// # Parameters:
+    ///
+    /// - `setup`: Function that creates the simulation world and dispatcher, run on the worker
+    ///   thread. The world needs to have a `DeltaTime` resource.
+    /// - `extract`: Function that extracts a snapshot from the world after each step
+    pub fn new<F, E>(setup: F, mut extract: E) -> Self
+    where
+        F: FnOnce() -> (World, Dispatcher<'static, 'static>) + Send + 'static,
+        E: FnMut(&World) -> S + Send + 'static,
+    {
+        let (command_sender, command_receiver) = channel::<Command>();
+        let (result_sender, result_receiver) = channel();
+        let worker = thread::spawn(move || {
+            let (mut world, mut dispatcher) = setup();
+            for command in command_receiver {
+                match command {
+                    Command::Step(delta_seconds, mut apply) => {
+                        apply(&mut world);
+                        world.write_resource::<DeltaTime>().delta_seconds = delta_seconds;
+                        dispatcher.dispatch(&world.res);
+                        world.maintain();
+                        if result_sender.send(extract(&world)).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+        });
+        Self {
+            commands: Some(command_sender),
+            results: result_receiver,
+            worker: Some(worker),
+            running: false,
+            front: None,
+        }
+    }
+
+    /// Returns true if a step is currently running
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Start a physics step on the worker thread.
+    ///
+    /// Returns false, and does nothing, if a step is already running.
+    ///
+    /// # Parameters:
+    ///
+    /// - `delta_seconds`: Time step for the simulation
+    /// - `apply`: Function that applies inputs to the simulation world before the step is run
+    ///
+    /// # Panics
+    ///
+    /// If the worker thread has stopped, because an earlier step panicked on it, that panic is
+    /// resumed on the calling thread. If the worker thread stopped without panicking, this panics
+    /// with "Background physics worker thread has stopped".
+    pub fn start<F>(&mut self, delta_seconds: Real, apply: F) -> bool
+    where
+        F: FnMut(&mut World) + Send + 'static,
+    {
+        if self.running {
+            return false;
+        }
+        let sent = match self.commands {
+            Some(ref commands) => commands
+                .send(Command::Step(delta_seconds, Box::new(apply)))
+                .is_ok(),
+            None => false,
+        };
+        if !sent {
+            self.resume_worker_panic();
+        }
+        self.running = true;
+        true
+    }
+
+    /// Wait for the running step to finish, if any, and swap in the new snapshot.
+    ///
+    /// Returns the snapshot from the last finished step.
+    ///
+    /// # Panics
+    ///
+    /// If the step panicked on the worker thread, the panic is resumed on the calling thread.
+    pub fn sync(&mut self) -> Option<&S> {
+        if self.running {
+            self.running = false;
+            match self.results.recv() {
+                Ok(snapshot) => self.front = Some(snapshot),
+                Err(_) => self.resume_worker_panic(),
+            }
+        }
+        self.front.as_ref()
+    }
+
+    /// Get the snapshot from the last finished step, if any step has finished.
+    pub fn front(&self) -> Option<&S> {
+        self.front.as_ref()
+    }
+
+    fn resume_worker_panic(&mut self) {
+        if let Some(worker) = self.worker.take() {
+            if let Err(error) = worker.join() {
+                ::std::panic::resume_unwind(error);
+            }
+        }
+        panic!("Background physics worker thread has stopped");
+    }
+}
+
+impl<S> Drop for BackgroundPhysics<S> {
+    fn drop(&mut self) {
+        self.commands.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use specs::{DispatcherBuilder, World};
+
+    use super::*;
+    use Real;
+
+    struct Steps(usize);
+
+    fn physics() -> BackgroundPhysics<(usize, Real)> {
+        BackgroundPhysics::new(
+            || {
+                let mut world = World::new();
+                world.add_resource(DeltaTime { delta_seconds: 0. });
+                world.add_resource(Steps(0));
+                (world, DispatcherBuilder::new().build())
+            },
+            |world| {
+                (
+                    world.read_resource::<Steps>().0,
+                    world.read_resource::<DeltaTime>().delta_seconds,
+                )
+            },
+        )
+    }
+
+    fn step(world: &mut World) {
+        world.write_resource::<Steps>().0 += 1;
+    }
+
+    #[test]
+    fn test_front_is_swapped_at_sync() {
+        let mut physics = physics();
+        assert!(physics.front().is_none());
+        assert!(physics.sync().is_none());
+
+        assert!(physics.start(0.5, step));
+        assert!(physics.is_running());
+        assert!(physics.front().is_none());
+        assert_eq!(Some(&(1, 0.5)), physics.sync());
+        assert!(!physics.is_running());
+
+        assert!(physics.start(0.25, step));
+        assert_eq!(Some(&(1, 0.5)), physics.front());
+        assert_eq!(Some(&(2, 0.25)), physics.sync());
+        assert_eq!(Some(&(2, 0.25)), physics.front());
+        // nothing running, the snapshot stays the same
+        assert_eq!(Some(&(2, 0.25)), physics.sync());
+    }
+
+    #[test]
+    fn test_start_while_running_is_ignored() {
+        let mut physics = physics();
+        assert!(physics.start(0.5, step));
+        assert!(!physics.start(0.25, step));
+        assert_eq!(Some(&(1, 0.5)), physics.sync());
+        assert!(physics.start(0.25, step));
+        assert_eq!(Some(&(2, 0.25)), physics.sync());
+    }
+
+    #[test]
+    #[should_panic(expected = "input failed")]
+    fn test_worker_panic_is_resumed() {
+        let mut physics = physics();
+        assert!(physics.start(0.5, |_: &mut World| panic!("input failed")));
+        physics.sync();
+    }
+
+    #[test]
+    #[should_panic(expected = "Background physics worker thread has stopped")]
+    fn test_start_after_worker_stopped_panics() {
+        let mut physics = physics();
+        assert!(physics.start(0.5, |_: &mut World| panic!("input failed")));
+        let resumed = catch_unwind(AssertUnwindSafe(|| {
+            physics.sync();
+        }));
+        assert!(resumed.is_err());
+        physics.start(0.5, step);
+    }
+}
@@ -0,0 +1,108 @@
+use std::marker;
+
+use cgmath::{EuclideanSpace, Rotation, VectorSpace};
+use specs::{Entity, Join, World};
+
+use {BodyPose, Real};
+use ecs::physics::SequentialPhysics;
+use physics::Velocity;
+
+/// Logged state of a single body after a step
+#[derive(Clone)]
+pub struct BodyLog<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    R: Rotation<P>,
+{
+    /// The body
+    pub entity: Entity,
+    /// Pose of the body
+    pub pose: BodyPose<P, R>,
+    /// Velocity of the body, if it has one
+    pub velocity: Option<Velocity<P::Diff>>,
+}
+
+/// Logged state of all bodies after a step
+#[derive(Clone)]
+pub struct StepLog<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    R: Rotation<P>,
+{
+    /// Step number, starting at 1 for the first step
+    pub step: usize,
+    /// State of all bodies with a pose
+    pub bodies: Vec<BodyLog<P, R>>,
+}
+
+/// Headless batch simulation runner.
+///
+/// Runs the simulation for a given number of fixed steps, as fast as possible, without a
+/// dispatcher or a real time source, and logs the pose and velocity of all bodies after each
+/// step. Intended for parameter tuning, machine learning environments and determinism checks.
+///
+/// The `headless_physics` functions in the [2D](prelude2d/index.html) and
+/// [3D](prelude3d/index.html) preludes set up the full collision and contact solver pipeline.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct BatchRunner<'a, P, R> {
+    physics: SequentialPhysics<'a>,
+    delta_seconds: Real,
+    steps: usize,
+    m: marker::PhantomData<(P, R)>,
+}
+
+impl<'a, P, R> BatchRunner<'a, P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: VectorSpace<Scalar = Real> + Send + Sync + 'static,
+    R: Rotation<P> + Send + Sync + 'static,
+{
+    /// Create a new batch runner.
+    ///
+    /// # Parameters:
+    ///
+    /// - `physics`: The systems used to step the simulation
+    /// - `delta_seconds`: Fixed time step
+    pub fn new(physics: SequentialPhysics<'a>, delta_seconds: Real) -> Self {
+        Self {
+            physics,
+            delta_seconds,
+            steps: 0,
+            m: marker::PhantomData,
+        }
+    }
+
+    /// Get the number of steps run so far
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Run a single step, and log the state of all bodies
+    pub fn step(&mut self, world: &mut World) -> StepLog<P, R> {
+        self.physics.step(world, self.delta_seconds);
+        self.steps += 1;
+        let entities = world.entities();
+        let poses = world.read::<BodyPose<P, R>>();
+        let velocities = world.read::<Velocity<P::Diff>>();
+        StepLog {
+            step: self.steps,
+            bodies: (&*entities, &poses)
+                .join()
+                .map(|(entity, pose)| BodyLog {
+                    entity,
+                    pose: pose.clone(),
+                    velocity: velocities.get(entity).cloned(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Run the given number of steps, and return the log for each step
+    pub fn run(&mut self, world: &mut World, steps: usize) -> Vec<StepLog<P, R>> {
+        (0..steps).map(|_| self.step(world)).collect()
+    }
+}
@@ -0,0 +1,126 @@
+use cgmath::{EuclideanSpace, Rotation, Zero};
+use collision::{Aabb, Primitive};
+use specs::{Component, Entity, EntityBuilder, LazyUpdate};
+
+use {BodyPose, NextFrame, Real};
+use collide::CollisionShape;
+use ecs::collide::StaticShape;
+use physics::{BodyType, Density, ForceAccumulator, LinearVector, Mass, MaterialHandle, Velocity,
+              Volume};
+
+/// Extension trait for setting up physics entities, attaching a consistent set of components in
+/// one call.
+///
+/// Implemented for [`EntityBuilder`](https://docs.rs/specs/0.10.0/specs/struct.EntityBuilder.html),
+/// and for [`LazyEntityBuilder`](struct.LazyEntityBuilder.html), which wraps
+/// [`LazyUpdate`](https://docs.rs/specs/0.10.0/specs/struct.LazyUpdate.html).
+pub trait WithPhysics: Sized {
+    /// Add a component to the entity
+    fn with_component<C>(self, component: C) -> Self
+    where
+        C: Component + Send + Sync;
+
+    /// Add a dynamic rigid body.
+    ///
+    /// Adds the shape, pose and next frame pose, zero velocity and next frame velocity, a force
+    /// accumulator, the density and material, and the mass computed from the density and the
+    /// volume of the shape.
+    fn with_dynamic_rigid_body<P, R>(
+        self,
+        shape: CollisionShape<P, BodyPose<P::Point, R>>,
+        pose: BodyPose<P::Point, R>,
+        density: Density,
+        material: MaterialHandle,
+    ) -> Self
+    where
+        P: Primitive + Volume + Send + Sync + 'static,
+        P::Aabb: Aabb<Scalar = Real> + Send + Sync + 'static,
+        P::Point: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+        <P::Point as EuclideanSpace>::Diff: LinearVector + Send + Sync + 'static,
+        R: Rotation<P::Point> + Send + Sync + 'static,
+    {
+        let mass = density.mass(shape.volume());
+        let next_pose = NextFrame {
+            value: pose.clone(),
+        };
+        self.with_component(pose)
+            .with_component(next_pose)
+            .with_component(shape)
+            .with_component(Velocity {
+                linear: <P::Point as EuclideanSpace>::Diff::zero(),
+            })
+            .with_component(NextFrame {
+                value: Velocity {
+                    linear: <P::Point as EuclideanSpace>::Diff::zero(),
+                },
+            })
+            .with_component(ForceAccumulator::<<P::Point as EuclideanSpace>::Diff>::new())
+            .with_component(mass)
+            .with_component(density)
+            .with_component(material)
+    }
+
+    /// Add a static collider.
+    ///
+    /// Adds the shape and pose, the [`StaticShape`](../collide/struct.StaticShape.html) marker,
+    /// the static [`BodyType`](../../physics/enum.BodyType.html), and an infinite mass, so dynamic
+    /// bodies are resolved against the collider.
+    fn with_static_collider<P, R>(
+        self,
+        shape: CollisionShape<P, BodyPose<P::Point, R>>,
+        pose: BodyPose<P::Point, R>,
+    ) -> Self
+    where
+        P: Primitive + Send + Sync + 'static,
+        P::Aabb: Aabb<Scalar = Real> + Send + Sync + 'static,
+        P::Point: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+        R: Rotation<P::Point> + Send + Sync + 'static,
+    {
+        self.with_component(pose)
+            .with_component(shape)
+            .with_component(StaticShape)
+            .with_component(BodyType::Static)
+            .with_component(Mass::new(Real::INFINITY))
+    }
+}
+
+impl<'a> WithPhysics for EntityBuilder<'a> {
+    fn with_component<C>(self, component: C) -> Self
+    where
+        C: Component + Send + Sync,
+    {
+        self.with(component)
+    }
+}
+
+/// Builder that lazily inserts components for an entity, using
+/// [`LazyUpdate`](https://docs.rs/specs/0.10.0/specs/struct.LazyUpdate.html).
+///
+/// Used to set up physics entities from inside systems, see
+/// [`WithPhysics`](trait.WithPhysics.html).
+pub struct LazyEntityBuilder<'a> {
+    lazy: &'a LazyUpdate,
+    entity: Entity,
+}
+
+impl<'a> LazyEntityBuilder<'a> {
+    /// Create a new lazy builder for the given entity
+    pub fn new(lazy: &'a LazyUpdate, entity: Entity) -> Self {
+        Self { lazy, entity }
+    }
+
+    /// Get the entity
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+}
+
+impl<'a> WithPhysics for LazyEntityBuilder<'a> {
+    fn with_component<C>(self, component: C) -> Self
+    where
+        C: Component + Send + Sync,
+    {
+        self.lazy.insert(self.entity, component);
+        self
+    }
+}
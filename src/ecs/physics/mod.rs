@@ -1,9 +1,25 @@
 //! Contains physics components, resources and systems for use with `specs`
+#[cfg(not(any(target_arch = "wasm32", target_os = "emscripten")))]
+pub use self::background::BackgroundPhysics;
+pub use self::batch::{BatchRunner, BodyLog, StepLog};
+pub use self::builder::{LazyEntityBuilder, WithPhysics};
 pub use self::resources::*;
+pub use self::rewind::{PhysicsSnapshot, RewindBuffer};
+pub use self::scenario::{Scenario, ScenarioBody, ScenarioFrame, ScenarioResult};
+pub use self::sequential::SequentialPhysics;
 pub use self::systems::*;
+pub use self::teleport::teleport_group;
 
 pub mod prelude2d;
 pub mod prelude3d;
 
+#[cfg(not(any(target_arch = "wasm32", target_os = "emscripten")))]
+mod background;
+mod batch;
+mod builder;
 mod resources;
+mod rewind;
+mod scenario;
+mod sequential;
 mod systems;
+mod teleport;
@@ -2,34 +2,296 @@
 
 pub use ecs::collide::prelude2d::*;
 pub use physics::prelude2d::*;
-pub use ecs::physics::DeltaTime;
+pub use ecs::physics::{BodyHandle, BodyHandles, BuoyancySystem2, DeltaTime, GroundFrictionSystem2,
+                       JointAngleControlSystem2, MaterialRegistry, WheelJointSystem2};
 
-use cgmath::{Basis2, Point2};
+use cgmath::{Basis2, Point2, Vector2};
+use collision::primitive::Primitive2;
 use shrev::EventChannel;
-use specs::World;
+use specs::{Entity, Join, World};
 
 use {NextFrame, Real};
-use ecs::physics::LinearContactSolverSystem;
+use collide::util::ContainerShapeWrapper;
+use ecs::collide::ContactOverflow;
+use ecs::physics::{BatchRunner, BodyHandleSystem, CollisionResponse, CollisionResponseSystem,
+                   ContactSolverState, DistanceJointSystem, EnergyDiagnostics,
+                   EnergyDiagnosticsSystem, GravitySystem, JointCleanupSystem, JointCollisionSystem,
+                   JointSolverSystem, LinearContactSolverSystem, MagnetSystem, MassRecomputeSystem,
+                   MotionClampSystem, NextFrameSetupSystem, OrientationControlSystem,
+                   PathFollowSystem, PhysicsSnapshot, PidControlSystem, PoseCorrectionSystem,
+                   PoseHistorySystem, ResolvedContacts, RewindBuffer, Scenario, ScenarioBody,
+                   SequentialImpulseSolverSystem, SequentialPhysics, SpringSystem,
+                   WarmStartImpulses};
 
 /// Linear contact resolve system for 2D
 pub type LinearContactSolverSystem2 = LinearContactSolverSystem<Point2<Real>, Basis2<Real>>;
 
+/// Body handle system for 2D
+pub type BodyHandleSystem2 = BodyHandleSystem<Point2<Real>, Basis2<Real>>;
+
+/// Sequential impulse contact solver system for 2D
+pub type SequentialImpulseSolverSystem2 =
+    SequentialImpulseSolverSystem<Point2<Real>, Basis2<Real>>;
+
+/// Orientation control system for 2D
+pub type OrientationControlSystem2 = OrientationControlSystem<Point2<Real>, Basis2<Real>>;
+
+/// Spring system for 2D
+pub type SpringSystem2 = SpringSystem<Point2<Real>, Basis2<Real>>;
+
+/// Distance joint system for 2D
+pub type DistanceJointSystem2 = DistanceJointSystem<Point2<Real>, Basis2<Real>>;
+
+/// Joint solver system for 2D
+pub type JointSolverSystem2 = JointSolverSystem<Point2<Real>, Basis2<Real>>;
+
+/// Joint collision filtering system for joints
+pub type JointCollisionSystem2 = JointCollisionSystem<Joint2<Entity>>;
+
+/// Joint collision filtering system for distance joints
+pub type DistanceJointCollisionSystem = JointCollisionSystem<DistanceJoint<Entity>>;
+
+/// Joint collision filtering system for distance constraints
+pub type DistanceConstraintCollisionSystem = JointCollisionSystem<DistanceConstraint<Entity>>;
+
+/// Joint collision filtering system for wheel joints
+pub type WheelJointCollisionSystem2 = JointCollisionSystem<WheelJoint2<Entity>>;
+
+/// Joint cleanup system for joints
+pub type JointCleanupSystem2 = JointCleanupSystem<Joint2<Entity>>;
+
+/// Joint cleanup system for distance joints
+pub type DistanceJointCleanupSystem = JointCleanupSystem<DistanceJoint<Entity>>;
+
+/// Joint cleanup system for distance constraints
+pub type DistanceConstraintCleanupSystem = JointCleanupSystem<DistanceConstraint<Entity>>;
+
+/// Energy diagnostics resource for 2D
+pub type EnergyDiagnostics2 = EnergyDiagnostics<Vector2<Real>>;
+
+/// Energy diagnostics system for 2D
+pub type EnergyDiagnosticsSystem2 = EnergyDiagnosticsSystem<Point2<Real>, Basis2<Real>>;
+
+/// Impact event for 2D
+pub type ImpactEvent2 = ImpactEvent<Entity, Point2<Real>>;
+
+/// Solver instability event for 2D
+pub type InstabilityEvent2 = InstabilityEvent<Entity, Point2<Real>>;
+
+/// Resolved contacts resource for 2D
+pub type ResolvedContacts2 = ResolvedContacts<Point2<Real>>;
+
+/// Warm starting impulses resource for 2D
+pub type WarmStartImpulses2 = WarmStartImpulses<Point2<Real>>;
+
+/// Contact solver state resource for 2D
+pub type ContactSolverState2 = ContactSolverState<Point2<Real>>;
+
+/// Collision response component for 2D
+pub type CollisionResponse2 = CollisionResponse<Point2<Real>>;
+
+/// Collision response system for 2D
+pub type CollisionResponseSystem2 = CollisionResponseSystem<Point2<Real>>;
+
+/// Gravity system for 2D
+pub type GravitySystem2 = GravitySystem<Primitive2<Real>, Basis2<Real>>;
+
+/// Motion clamping system for 2D
+pub type MotionClampSystem2 = MotionClampSystem<Primitive2<Real>, Basis2<Real>>;
+
+/// Mass recomputation system for 2D
+pub type MassRecomputeSystem2 = MassRecomputeSystem<Primitive2<Real>, Basis2<Real>>;
+
+/// Next frame setup system for 2D
+pub type NextFrameSetupSystem2 = NextFrameSetupSystem<Point2<Real>, Basis2<Real>>;
+
+/// Path following system for 2D
+pub type PathFollowSystem2 = PathFollowSystem<Point2<Real>, Basis2<Real>>;
+
+/// PID control system for 2D
+pub type PidControlSystem2 = PidControlSystem<Point2<Real>, Basis2<Real>>;
+
+/// Pose correction system for 2D
+pub type PoseCorrectionSystem2 = PoseCorrectionSystem<Point2<Real>, Basis2<Real>>;
+
+/// Pose history system for 2D
+pub type PoseHistorySystem2 = PoseHistorySystem<Point2<Real>, Basis2<Real>>;
+
+/// Magnet system for 2D
+pub type MagnetSystem2 = MagnetSystem<
+    Point2<Real>,
+    Basis2<Real>,
+    ContainerShapeWrapper<Entity, Primitive2<Real>>,
+>;
+
+/// Physics snapshot for 2D
+pub type PhysicsSnapshot2 = PhysicsSnapshot<Point2<Real>, Basis2<Real>>;
+
+/// Rewind buffer for 2D
+pub type RewindBuffer2 = RewindBuffer<Point2<Real>, Basis2<Real>>;
+
+/// Simulation scenario for 2D
+pub type Scenario2 = Scenario<Primitive2<Real>, Basis2<Real>>;
+
+/// Simulation scenario body for 2D
+pub type ScenarioBody2 = ScenarioBody<Primitive2<Real>, Basis2<Real>>;
+
+/// Headless batch simulation runner for 2D
+pub type BatchRunner2<'a> = BatchRunner<'a, Point2<Real>, Basis2<Real>>;
+
+/// Component setup validation system for 2D
+#[cfg(feature = "debug")]
+pub type ValidationSystem2 = ::ecs::physics::ValidationSystem<Primitive2<Real>, Basis2<Real>>;
+
 /// Register required components and resources in world
 pub fn world_physics_register(world: &mut World) {
     world.add_resource(DeltaTime { delta_seconds: 0. });
+    world.add_resource(PositionCorrection::default());
+    world.add_resource(MaterialRegistry::default());
+    world.add_resource(BodyHandles::default());
+    world.add_resource(EnergyDiagnostics2::default());
+    world.add_resource(Gravity2::default());
     world.register::<Mass>();
+    world.register::<GravityScale>();
+    world.register::<BodyHandle>();
+    world.register::<Density>();
+    world.register::<Damping>();
+    world.register::<BodyType>();
+    world.register::<MaterialHandle>();
+    world.register::<SolverGroup>();
     world.register::<Velocity2>();
     world.register::<NextFrame<Velocity2>>();
+    world.register::<ForceAccumulator2>();
+    world.register::<AngularVelocity2>();
+    world.register::<Inertia2>();
+    world.register::<PidController2>();
+    world.register::<OrientationController2>();
+    world.register::<PathFollower2>();
+    world.register::<PoseCorrection2>();
+    world.register::<PoseHistory2>();
+    world.register::<ContactImpulse2>();
+    world.register::<ContactSensors>();
+    world.register::<GravityVolume2>();
+    world.register::<Magnet>();
+    world.register::<MagnetSusceptible>();
+    world.register::<DistanceJoint<Entity>>();
+    world.register::<DistanceConstraint<Entity>>();
+    world.register::<Spring2<Entity>>();
+    world.register::<Joint2<Entity>>();
+    world.register::<WheelJoint2<Entity>>();
+    world.register::<JointControl>();
+    world.register::<JointAngleController>();
+    world.register::<ArcadeBounce>();
+    world.register::<Rolling>();
+    world.register::<GroundFriction>();
+    world.register::<FrictionZone>();
+    world.register::<WaterArea2>();
+    world.register::<CollisionResponse2>();
     world.add_resource(EventChannel::<ContactEvent2>::new());
+    world.add_resource(EventChannel::<ContactOverflow>::new());
+    world.add_resource(EventChannel::<ImpactEvent2>::new());
+    world.add_resource(EventChannel::<InstabilityEvent2>::new());
+    #[cfg(feature = "debug")]
+    world.add_resource(EventChannel::<::ecs::physics::SetupDiagnostic>::new());
+    world.add_resource(ResolvedContacts2::default());
+    world.add_resource(WarmStartImpulses2::default());
+    world.add_resource(ContactSolverState2::default());
+    world.add_resource(SilentContacts2::default());
     world_register::<BodyPose2>(world);
 }
 
 /// Register required components and resources in world
 pub fn world_physics_register_with_spatial(world: &mut World) {
     world.add_resource(DeltaTime { delta_seconds: 0. });
+    world.add_resource(PositionCorrection::default());
+    world.add_resource(MaterialRegistry::default());
+    world.add_resource(BodyHandles::default());
+    world.add_resource(EnergyDiagnostics2::default());
+    world.add_resource(Gravity2::default());
     world.register::<Mass>();
+    world.register::<GravityScale>();
+    world.register::<BodyHandle>();
+    world.register::<Density>();
+    world.register::<Damping>();
+    world.register::<BodyType>();
+    world.register::<MaterialHandle>();
+    world.register::<SolverGroup>();
     world.register::<Velocity2>();
     world.register::<NextFrame<Velocity2>>();
+    world.register::<ForceAccumulator2>();
+    world.register::<AngularVelocity2>();
+    world.register::<Inertia2>();
+    world.register::<PidController2>();
+    world.register::<OrientationController2>();
+    world.register::<PathFollower2>();
+    world.register::<PoseCorrection2>();
+    world.register::<PoseHistory2>();
+    world.register::<ContactImpulse2>();
+    world.register::<ContactSensors>();
+    world.register::<GravityVolume2>();
+    world.register::<Magnet>();
+    world.register::<MagnetSusceptible>();
+    world.register::<DistanceJoint<Entity>>();
+    world.register::<DistanceConstraint<Entity>>();
+    world.register::<Spring2<Entity>>();
+    world.register::<Joint2<Entity>>();
+    world.register::<WheelJoint2<Entity>>();
+    world.register::<JointControl>();
+    world.register::<JointAngleController>();
+    world.register::<ArcadeBounce>();
+    world.register::<Rolling>();
+    world.register::<GroundFriction>();
+    world.register::<FrictionZone>();
+    world.register::<WaterArea2>();
+    world.register::<CollisionResponse2>();
     world.add_resource(EventChannel::<ContactEvent2>::new());
+    world.add_resource(EventChannel::<ContactOverflow>::new());
+    world.add_resource(EventChannel::<ImpactEvent2>::new());
+    world.add_resource(EventChannel::<InstabilityEvent2>::new());
+    #[cfg(feature = "debug")]
+    world.add_resource(EventChannel::<::ecs::physics::SetupDiagnostic>::new());
+    world.add_resource(ResolvedContacts2::default());
+    world.add_resource(WarmStartImpulses2::default());
+    world.add_resource(ContactSolverState2::default());
+    world.add_resource(SilentContacts2::default());
     world_register_with_spatial::<BodyPose2>(world);
 }
+
+/// Find all bodies that are connected to the given root body by wheel joints, directly or through
+/// other jointed bodies, e.g. for use with [`teleport_group`](../fn.teleport_group.html).
+///
+/// The root body is not included in the result.
+pub fn jointed_bodies(world: &World, root: Entity) -> Vec<Entity> {
+    let entities = world.entities();
+    let joints = world.read::<WheelJoint2<Entity>>();
+    let mut bodies = vec![root];
+    let mut index = 0;
+    while index < bodies.len() {
+        let parent = bodies[index];
+        for (entity, joint) in (&*entities, &joints).join() {
+            if joint.chassis == parent && !bodies.contains(&entity) {
+                bodies.push(entity);
+            }
+        }
+        index += 1;
+    }
+    bodies.remove(0);
+    bodies
+}
+
+/// Set up the full collision and contact solver pipeline, for use with a
+/// [`BatchRunner`](../struct.BatchRunner.html), or for stepping the world directly.
+///
+/// Runs spatial sorting, spatial collision detection using GJK, gravity, and the linear contact
+/// solver, in that order. The world needs to be set up using
+/// [`world_physics_register_with_spatial`](fn.world_physics_register_with_spatial.html).
+pub fn headless_physics(world: &mut World) -> SequentialPhysics<'static> {
+    let reader = world
+        .write_resource::<EventChannel<ContactEvent2>>()
+        .register_reader();
+    SequentialPhysics::new()
+        .with(SpatialSortingSystem2::<BodyPose2>::new())
+        .with(SpatialCollisionSystem2::<BodyPose2>::new().with_narrow_phase(GJK2::new()))
+        .with(GravitySystem2::new())
+        .with(LinearContactSolverSystem2::new(reader))
+}
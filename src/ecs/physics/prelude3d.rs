@@ -2,34 +2,263 @@
 
 pub use ecs::collide::prelude3d::*;
 pub use physics::prelude3d::*;
-pub use ecs::physics::DeltaTime;
+pub use ecs::physics::{BodyConstraintSystem3, BodyHandle, BodyHandles, DeltaTime,
+                       MaterialRegistry};
 
-use cgmath::{Point3, Quaternion};
+use cgmath::{Point3, Quaternion, Vector3};
+use collision::primitive::Primitive3;
 use shrev::EventChannel;
-use specs::World;
+use specs::{Entity, World};
 
 use {NextFrame, Real};
-use ecs::physics::LinearContactSolverSystem;
+use collide::util::ContainerShapeWrapper;
+use ecs::collide::ContactOverflow;
+use ecs::physics::{BatchRunner, BodyHandleSystem, CollisionResponse, CollisionResponseSystem,
+                   ContactSolverState, DistanceJointSystem, EnergyDiagnostics,
+                   EnergyDiagnosticsSystem, GravitySystem, JointCleanupSystem, JointCollisionSystem,
+                   JointSolverSystem, LinearContactSolverSystem, MagnetSystem, MassRecomputeSystem,
+                   MotionClampSystem, NextFrameSetupSystem, OrientationControlSystem,
+                   PathFollowSystem, PhysicsSnapshot, PidControlSystem, PoseCorrectionSystem,
+                   PoseHistorySystem, ResolvedContacts, RewindBuffer, Scenario, ScenarioBody,
+                   SequentialImpulseSolverSystem, SequentialPhysics, SpringSystem,
+                   WarmStartImpulses};
 
 /// Linear contact resolve system for 3D
 pub type LinearContactSolverSystem3 = LinearContactSolverSystem<Point3<Real>, Quaternion<Real>>;
 
+/// Body handle system for 3D
+pub type BodyHandleSystem3 = BodyHandleSystem<Point3<Real>, Quaternion<Real>>;
+
+/// Sequential impulse contact solver system for 3D
+pub type SequentialImpulseSolverSystem3 =
+    SequentialImpulseSolverSystem<Point3<Real>, Quaternion<Real>>;
+
+/// Orientation control system for 3D
+pub type OrientationControlSystem3 = OrientationControlSystem<Point3<Real>, Quaternion<Real>>;
+
+/// Spring system for 3D
+pub type SpringSystem3 = SpringSystem<Point3<Real>, Quaternion<Real>>;
+
+/// Distance joint system for 3D
+pub type DistanceJointSystem3 = DistanceJointSystem<Point3<Real>, Quaternion<Real>>;
+
+/// Joint solver system for 3D
+pub type JointSolverSystem3 = JointSolverSystem<Point3<Real>, Quaternion<Real>>;
+
+/// Joint collision filtering system for joints
+pub type JointCollisionSystem3 = JointCollisionSystem<Joint3<Entity>>;
+
+/// Joint collision filtering system for distance joints
+pub type DistanceJointCollisionSystem = JointCollisionSystem<DistanceJoint<Entity>>;
+
+/// Joint collision filtering system for distance constraints
+pub type DistanceConstraintCollisionSystem = JointCollisionSystem<DistanceConstraint<Entity>>;
+
+/// Joint cleanup system for joints
+pub type JointCleanupSystem3 = JointCleanupSystem<Joint3<Entity>>;
+
+/// Joint cleanup system for distance joints
+pub type DistanceJointCleanupSystem = JointCleanupSystem<DistanceJoint<Entity>>;
+
+/// Joint cleanup system for distance constraints
+pub type DistanceConstraintCleanupSystem = JointCleanupSystem<DistanceConstraint<Entity>>;
+
+/// Energy diagnostics resource for 3D
+pub type EnergyDiagnostics3 = EnergyDiagnostics<Vector3<Real>>;
+
+/// Energy diagnostics system for 3D
+pub type EnergyDiagnosticsSystem3 = EnergyDiagnosticsSystem<Point3<Real>, Quaternion<Real>>;
+
+/// Impact event for 3D
+pub type ImpactEvent3 = ImpactEvent<Entity, Point3<Real>>;
+
+/// Solver instability event for 3D
+pub type InstabilityEvent3 = InstabilityEvent<Entity, Point3<Real>>;
+
+/// Resolved contacts resource for 3D
+pub type ResolvedContacts3 = ResolvedContacts<Point3<Real>>;
+
+/// Warm starting impulses resource for 3D
+pub type WarmStartImpulses3 = WarmStartImpulses<Point3<Real>>;
+
+/// Contact solver state resource for 3D
+pub type ContactSolverState3 = ContactSolverState<Point3<Real>>;
+
+/// Collision response component for 3D
+pub type CollisionResponse3 = CollisionResponse<Point3<Real>>;
+
+/// Collision response system for 3D
+pub type CollisionResponseSystem3 = CollisionResponseSystem<Point3<Real>>;
+
+/// Gravity system for 3D
+pub type GravitySystem3 = GravitySystem<Primitive3<Real>, Quaternion<Real>>;
+
+/// Motion clamping system for 3D
+pub type MotionClampSystem3 = MotionClampSystem<Primitive3<Real>, Quaternion<Real>>;
+
+/// Mass recomputation system for 3D
+pub type MassRecomputeSystem3 = MassRecomputeSystem<Primitive3<Real>, Quaternion<Real>>;
+
+/// Next frame setup system for 3D
+pub type NextFrameSetupSystem3 = NextFrameSetupSystem<Point3<Real>, Quaternion<Real>>;
+
+/// Path following system for 3D
+pub type PathFollowSystem3 = PathFollowSystem<Point3<Real>, Quaternion<Real>>;
+
+/// PID control system for 3D
+pub type PidControlSystem3 = PidControlSystem<Point3<Real>, Quaternion<Real>>;
+
+/// Pose correction system for 3D
+pub type PoseCorrectionSystem3 = PoseCorrectionSystem<Point3<Real>, Quaternion<Real>>;
+
+/// Pose history system for 3D
+pub type PoseHistorySystem3 = PoseHistorySystem<Point3<Real>, Quaternion<Real>>;
+
+/// Magnet system for 3D
+pub type MagnetSystem3 = MagnetSystem<
+    Point3<Real>,
+    Quaternion<Real>,
+    ContainerShapeWrapper<Entity, Primitive3<Real>>,
+>;
+
+/// Physics snapshot for 3D
+pub type PhysicsSnapshot3 = PhysicsSnapshot<Point3<Real>, Quaternion<Real>>;
+
+/// Rewind buffer for 3D
+pub type RewindBuffer3 = RewindBuffer<Point3<Real>, Quaternion<Real>>;
+
+/// Simulation scenario for 3D
+pub type Scenario3 = Scenario<Primitive3<Real>, Quaternion<Real>>;
+
+/// Simulation scenario body for 3D
+pub type ScenarioBody3 = ScenarioBody<Primitive3<Real>, Quaternion<Real>>;
+
+/// Headless batch simulation runner for 3D
+pub type BatchRunner3<'a> = BatchRunner<'a, Point3<Real>, Quaternion<Real>>;
+
+/// Component setup validation system for 3D
+#[cfg(feature = "debug")]
+pub type ValidationSystem3 = ::ecs::physics::ValidationSystem<Primitive3<Real>, Quaternion<Real>>;
+
 /// Register required components and resources in world
 pub fn world_physics_register(world: &mut World) {
     world.add_resource(DeltaTime { delta_seconds: 0. });
+    world.add_resource(PositionCorrection::default());
+    world.add_resource(MaterialRegistry::default());
+    world.add_resource(BodyHandles::default());
+    world.add_resource(EnergyDiagnostics3::default());
+    world.add_resource(Gravity3::default());
     world.register::<Mass>();
+    world.register::<GravityScale>();
+    world.register::<BodyHandle>();
+    world.register::<Density>();
+    world.register::<Damping>();
+    world.register::<BodyType>();
+    world.register::<MaterialHandle>();
+    world.register::<SolverGroup>();
     world.register::<Velocity3>();
     world.register::<NextFrame<Velocity3>>();
+    world.register::<ForceAccumulator3>();
+    world.register::<AngularVelocity3>();
+    world.register::<Inertia3>();
+    world.register::<PidController3>();
+    world.register::<OrientationController3>();
+    world.register::<PathFollower3>();
+    world.register::<PoseCorrection3>();
+    world.register::<PoseHistory3>();
+    world.register::<ContactImpulse3>();
+    world.register::<ContactSensors>();
+    world.register::<ArcadeBounce>();
+    world.register::<Rolling>();
+    world.register::<GravityVolume3>();
+    world.register::<Magnet>();
+    world.register::<MagnetSusceptible>();
+    world.register::<DistanceJoint<Entity>>();
+    world.register::<DistanceConstraint<Entity>>();
+    world.register::<Spring3<Entity>>();
+    world.register::<Joint3<Entity>>();
+    world.register::<PlaneConstraint3>();
+    world.register::<UprightConstraint3>();
+    world.register::<CollisionResponse3>();
     world.add_resource(EventChannel::<ContactEvent3>::new());
+    world.add_resource(EventChannel::<ContactOverflow>::new());
+    world.add_resource(EventChannel::<ImpactEvent3>::new());
+    world.add_resource(EventChannel::<InstabilityEvent3>::new());
+    #[cfg(feature = "debug")]
+    world.add_resource(EventChannel::<::ecs::physics::SetupDiagnostic>::new());
+    world.add_resource(ResolvedContacts3::default());
+    world.add_resource(WarmStartImpulses3::default());
+    world.add_resource(ContactSolverState3::default());
+    world.add_resource(SilentContacts3::default());
     world_register::<BodyPose3>(world);
 }
 
 /// Register required components and resources in world
 pub fn world_physics_register_with_spatial(world: &mut World) {
     world.add_resource(DeltaTime { delta_seconds: 0. });
+    world.add_resource(PositionCorrection::default());
+    world.add_resource(MaterialRegistry::default());
+    world.add_resource(BodyHandles::default());
+    world.add_resource(EnergyDiagnostics3::default());
+    world.add_resource(Gravity3::default());
     world.register::<Mass>();
+    world.register::<GravityScale>();
+    world.register::<BodyHandle>();
+    world.register::<Density>();
+    world.register::<Damping>();
+    world.register::<BodyType>();
+    world.register::<MaterialHandle>();
+    world.register::<SolverGroup>();
     world.register::<Velocity3>();
     world.register::<NextFrame<Velocity3>>();
+    world.register::<ForceAccumulator3>();
+    world.register::<AngularVelocity3>();
+    world.register::<Inertia3>();
+    world.register::<PidController3>();
+    world.register::<OrientationController3>();
+    world.register::<PathFollower3>();
+    world.register::<PoseCorrection3>();
+    world.register::<PoseHistory3>();
+    world.register::<ContactImpulse3>();
+    world.register::<ContactSensors>();
+    world.register::<ArcadeBounce>();
+    world.register::<Rolling>();
+    world.register::<GravityVolume3>();
+    world.register::<Magnet>();
+    world.register::<MagnetSusceptible>();
+    world.register::<DistanceJoint<Entity>>();
+    world.register::<DistanceConstraint<Entity>>();
+    world.register::<Spring3<Entity>>();
+    world.register::<Joint3<Entity>>();
+    world.register::<PlaneConstraint3>();
+    world.register::<UprightConstraint3>();
+    world.register::<CollisionResponse3>();
     world.add_resource(EventChannel::<ContactEvent3>::new());
+    world.add_resource(EventChannel::<ContactOverflow>::new());
+    world.add_resource(EventChannel::<ImpactEvent3>::new());
+    world.add_resource(EventChannel::<InstabilityEvent3>::new());
+    #[cfg(feature = "debug")]
+    world.add_resource(EventChannel::<::ecs::physics::SetupDiagnostic>::new());
+    world.add_resource(ResolvedContacts3::default());
+    world.add_resource(WarmStartImpulses3::default());
+    world.add_resource(ContactSolverState3::default());
+    world.add_resource(SilentContacts3::default());
     world_register_with_spatial::<BodyPose3>(world);
 }
+
+/// Set up the full collision and contact solver pipeline, for use with a
+/// [`BatchRunner`](../struct.BatchRunner.html), or for stepping the world directly.
+///
+/// Runs spatial sorting, spatial collision detection using GJK, gravity, and the linear contact
+/// solver, in that order. The world needs to be set up using
+/// [`world_physics_register_with_spatial`](fn.world_physics_register_with_spatial.html).
+pub fn headless_physics(world: &mut World) -> SequentialPhysics<'static> {
+    let reader = world
+        .write_resource::<EventChannel<ContactEvent3>>()
+        .register_reader();
+    SequentialPhysics::new()
+        .with(SpatialSortingSystem3::<BodyPose3>::new())
+        .with(SpatialCollisionSystem3::<BodyPose3>::new().with_narrow_phase(GJK3::new()))
+        .with(GravitySystem3::new())
+        .with(LinearContactSolverSystem3::new(reader))
+}
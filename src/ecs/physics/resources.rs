@@ -1,7 +1,21 @@
-use specs::{Component, DenseVecStorage};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::mem;
+use std::ops::Deref;
+
+use cgmath::{EuclideanSpace, Rotation, Zero};
+use specs::{Component, DenseVecStorage, Entity, FlaggedStorage, LazyUpdate, NullStorage};
 
 use Real;
-use physics::{Mass, Velocity};
+use collide::ContactEvent;
+use ecs::collide::WorldId;
+use physics::{AngularVelocity, ArcadeBounce, BodyType, ContactImpulse, ContactSensors, Damping,
+              Density, DistanceConstraint, DistanceJoint, ForceAccumulator, FrictionZone,
+              GravityScale, GravityVolume, GroundFriction, ImpulseCache, Inertia, Island, Joint,
+              JointAngleController, JointControl, LinearVector, Magnet, MagnetSusceptible, Mass,
+              Material, MaterialHandle, OrientationController, PathFollower, PidController,
+              PlaneConstraint3, PoseCorrection, PoseHistory, Rolling, RotationalVector, SolverGroup,
+              Spring, UprightConstraint3, Velocity, WaterArea2, WheelJoint2};
 
 impl<V> Component for Velocity<V>
 where
@@ -14,8 +28,895 @@ impl Component for Mass {
     type Storage = DenseVecStorage<Self>;
 }
 
+impl Component for GravityScale {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Component for Density {
+    type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+}
+
+impl Component for Damping {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Component for BodyType {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<V> Component for AngularVelocity<V>
+where
+    V: LinearVector + Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<V> Component for Inertia<V>
+where
+    V: RotationalVector + Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<F> Component for ForceAccumulator<F>
+where
+    F: LinearVector + Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<V> Component for ContactImpulse<V>
+where
+    V: Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<V> Component for PoseCorrection<V>
+where
+    V: Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<P, R> Component for PoseHistory<P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: Send + Sync + 'static,
+    R: Rotation<P> + Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<P> Component for PathFollower<P>
+where
+    P: Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<P> Component for PidController<P>
+where
+    P: EuclideanSpace + Send + Sync + 'static,
+    P::Diff: Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Component for JointAngleController {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Component for ContactSensors {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Component for ArcadeBounce {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Component for Rolling {
+    type Storage = NullStorage<Self>;
+}
+
+impl Component for GroundFriction {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Component for FrictionZone {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<P, R> Component for GravityVolume<P, R>
+where
+    P: EuclideanSpace + Send + Sync + 'static,
+    P::Diff: Debug + Send + Sync + 'static,
+    R: Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Component for JointControl {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Component for Magnet {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Component for MagnetSusceptible {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Component for MaterialHandle {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Component for PlaneConstraint3 {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Component for UprightConstraint3 {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Component for SolverGroup {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Component for WaterArea2 {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<V, R> Component for OrientationController<V, R>
+where
+    V: LinearVector + Send + Sync + 'static,
+    R: Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<ID> Component for DistanceConstraint<ID>
+where
+    ID: Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<ID, P> Component for Spring<ID, P>
+where
+    ID: Send + Sync + 'static,
+    P: Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<ID> Component for DistanceJoint<ID>
+where
+    ID: Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<ID> Component for WheelJoint2<ID>
+where
+    ID: Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<ID, P, R> Component for Joint<ID, P, R>
+where
+    ID: Send + Sync + 'static,
+    P: EuclideanSpace + Send + Sync + 'static,
+    P::Diff: LinearVector + Send + Sync + 'static,
+    R: Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Stable handle to a body, for referencing bodies from outside the ECS, e.g. from scripting
+/// layers, or as network ids.
+///
+/// Unlike an `Entity`, a handle is never reused, so a stale handle can not reference a new body
+/// that was created in a recycled entity slot. Handles are mapped to entities by the
+/// [`BodyHandles`](struct.BodyHandles.html) resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BodyHandle(u64);
+
+impl BodyHandle {
+    /// Create a handle from a raw id, e.g. an id received over the network
+    pub fn from_id(id: u64) -> Self {
+        BodyHandle(id)
+    }
+
+    /// Get the raw id of the handle
+    pub fn id(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Component for BodyHandle {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Mapping between [`BodyHandle`](struct.BodyHandle.html)s and entities.
+///
+/// Handles are assigned to new bodies, and the mappings of deleted bodies are removed, by the
+/// [`BodyHandleSystem`](struct.BodyHandleSystem.html). Handles can also be created up front with
+/// [`create`](#method.create), e.g. to send the id of a new body over the network before the
+/// system has run.
+#[derive(Debug, Clone, Default)]
+pub struct BodyHandles {
+    next: u64,
+    entities: HashMap<BodyHandle, Entity>,
+    handles: HashMap<Entity, BodyHandle>,
+}
+
+impl BodyHandles {
+    /// Create a new empty mapping
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the handle of an entity, creating a new handle if the entity has none.
+    ///
+    /// The handle should also be added as a component to the entity, or it will be replaced by
+    /// a new handle when the [`BodyHandleSystem`](struct.BodyHandleSystem.html) runs.
+    pub fn create(&mut self, entity: Entity) -> BodyHandle {
+        if let Some(handle) = self.handles.get(&entity).cloned() {
+            return handle;
+        }
+        let handle = BodyHandle(self.next);
+        self.next += 1;
+        self.entities.insert(handle, entity);
+        self.handles.insert(entity, handle);
+        handle
+    }
+
+    /// Get the entity referenced by a handle, `None` if the body has been deleted
+    pub fn entity(&self, handle: BodyHandle) -> Option<Entity> {
+        self.entities.get(&handle).cloned()
+    }
+
+    /// Get the handle of an entity
+    pub fn handle(&self, entity: Entity) -> Option<BodyHandle> {
+        self.handles.get(&entity).cloned()
+    }
+
+    /// Number of mapped bodies
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Are no bodies mapped
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Map an entity to an existing handle, e.g. a handle restored from a save, or received over
+    /// the network. Any other mapping of the entity or the handle is replaced.
+    pub(crate) fn insert(&mut self, entity: Entity, handle: BodyHandle) {
+        if let Some(previous) = self.handles.insert(entity, handle) {
+            self.entities.remove(&previous);
+        }
+        if let Some(previous) = self.entities.insert(handle, entity) {
+            if previous != entity {
+                self.handles.remove(&previous);
+            }
+        }
+        self.next = self.next.max(handle.0 + 1);
+    }
+
+    /// Remove the mappings of all entities that the given predicate rejects
+    pub(crate) fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(Entity, BodyHandle) -> bool,
+    {
+        let handles = &mut self.handles;
+        self.entities.retain(|&handle, &mut entity| {
+            let keep = keep(entity, handle);
+            if !keep {
+                handles.remove(&entity);
+            }
+            keep
+        });
+    }
+}
+
 /// Used for computations
 pub struct DeltaTime {
     /// Delta time since last frame
     pub delta_seconds: Real,
 }
+
+/// Interpolation factor used when syncing poses to render transforms.
+///
+/// If present, the [`TransformSyncSystem`](struct.TransformSyncSystem.html) interpolates between
+/// the current pose and the next frame pose of each body, using `alpha` in `[0, 1]`. This is
+/// typically the fraction of a fixed physics step that has elapsed when rendering.
+#[derive(Debug, Clone, Default)]
+pub struct RenderInterpolation {
+    /// Interpolation factor, 0 is the current pose, and 1 is the next frame pose
+    pub alpha: Real,
+}
+
+/// Conversion from a physics transform into a user supplied render transform component.
+///
+/// Implement this for the render transform component, to have it updated by the
+/// [`TransformSyncSystem`](struct.TransformSyncSystem.html).
+///
+/// # Type parameters:
+///
+/// - `T`: Physics transform type, usually [`BodyPose`](../../struct.BodyPose.html)
+pub trait SyncTransform<T> {
+    /// Update the render transform from the given physics transform
+    fn sync_transform(&mut self, transform: &T);
+}
+
+/// Registry of named materials, referenced from bodies by
+/// [`MaterialHandle`](../../physics/struct.MaterialHandle.html).
+///
+/// Materials can be changed at runtime, and all bodies referencing a material will use the new
+/// values. Overrides can be set for specific pairs of materials, which are used instead of
+/// combining the two materials, see
+/// [`Material::combine`](../../physics/struct.Material.html#method.combine).
+///
+/// The default registry contains the presets `rubber`, `ice`, `wood`, `metal` and `bouncy`.
+#[derive(Debug, Clone)]
+pub struct MaterialRegistry {
+    materials: Vec<Material>,
+    names: HashMap<String, MaterialHandle>,
+    overrides: HashMap<(MaterialHandle, MaterialHandle), Material>,
+}
+
+impl MaterialRegistry {
+    /// Create a new empty registry
+    pub fn new() -> Self {
+        Self {
+            materials: Vec::default(),
+            names: HashMap::default(),
+            overrides: HashMap::default(),
+        }
+    }
+
+    /// Create a new registry with the preset materials
+    pub fn with_presets() -> Self {
+        let mut registry = Self::new();
+        registry.add("rubber", Material::rubber());
+        registry.add("ice", Material::ice());
+        registry.add("wood", Material::wood());
+        registry.add("metal", Material::metal());
+        registry.add("bouncy", Material::bouncy());
+        registry
+    }
+
+    /// Add a named material. If a material with the same name exists, it is replaced, and the
+    /// existing handle is returned.
+    pub fn add(&mut self, name: &str, material: Material) -> MaterialHandle {
+        if let Some(handle) = self.names.get(name).cloned() {
+            self.materials[handle.0] = material;
+            return handle;
+        }
+        let handle = MaterialHandle(self.materials.len());
+        self.materials.push(material);
+        self.names.insert(name.to_string(), handle);
+        handle
+    }
+
+    /// Look up the handle of a named material
+    pub fn handle(&self, name: &str) -> Option<MaterialHandle> {
+        self.names.get(name).cloned()
+    }
+
+    /// Get a material
+    pub fn get(&self, handle: MaterialHandle) -> Option<&Material> {
+        self.materials.get(handle.0)
+    }
+
+    /// Get a material for modification
+    pub fn get_mut(&mut self, handle: MaterialHandle) -> Option<&mut Material> {
+        self.materials.get_mut(handle.0)
+    }
+
+    /// Set the material to use for contacts between the two given materials
+    pub fn set_override(&mut self, a: MaterialHandle, b: MaterialHandle, material: Material) {
+        self.overrides.insert(pair_key(a, b), material);
+    }
+
+    /// Remove the override for the two given materials
+    pub fn remove_override(&mut self, a: MaterialHandle, b: MaterialHandle) -> Option<Material> {
+        self.overrides.remove(&pair_key(a, b))
+    }
+
+    /// Get the material to use for a contact between the two given materials. This is the
+    /// override for the pair if one is set, otherwise the combination of the two materials.
+    pub fn combined(&self, a: MaterialHandle, b: MaterialHandle) -> Option<Material> {
+        if let Some(material) = self.overrides.get(&pair_key(a, b)) {
+            return Some(material.clone());
+        }
+        match (self.get(a), self.get(b)) {
+            (Some(a), Some(b)) => Some(a.combine(b)),
+            _ => None,
+        }
+    }
+}
+
+impl Default for MaterialRegistry {
+    fn default() -> Self {
+        Self::with_presets()
+    }
+}
+
+fn pair_key(a: MaterialHandle, b: MaterialHandle) -> (MaterialHandle, MaterialHandle) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// Energy and momentum totals for a set of bodies, see
+/// [`EnergyDiagnostics`](struct.EnergyDiagnostics.html)
+///
+/// # Type parameters:
+///
+/// - `V`: cgmath vector type
+#[derive(Debug, Clone)]
+pub struct EnergyTotals<V>
+where
+    V: LinearVector,
+{
+    /// Total kinetic energy, translational and rotational
+    pub kinetic_energy: Real,
+    /// Rotational part of the total kinetic energy
+    pub rotational_energy: Real,
+    /// Total linear momentum
+    pub linear_momentum: V,
+    /// Total angular momentum around the world origin, the spin of the bodies, plus the angular
+    /// momentum of their linear motion around the origin
+    pub angular_momentum: V::Angular,
+    /// Number of bodies included in the totals
+    pub bodies: usize,
+}
+
+impl<V> EnergyTotals<V>
+where
+    V: RotationalVector,
+{
+    /// Create new empty totals
+    pub fn new() -> Self {
+        Self {
+            kinetic_energy: 0.,
+            rotational_energy: 0.,
+            linear_momentum: V::zero(),
+            angular_momentum: V::Angular::zero(),
+            bodies: 0,
+        }
+    }
+
+    /// Totals for a single body.
+    ///
+    /// # Parameters:
+    ///
+    /// - `mass`: Mass of the body
+    /// - `position`: Position of the body, relative to the world origin
+    /// - `velocity`: Linear velocity of the body
+    /// - `angular`: Angular velocity of the body, zero for bodies that do not rotate
+    /// - `inverse_inertia`: World space inverse inertia of the body, zero for bodies that can not
+    ///   be turned
+    pub fn body(
+        mass: Real,
+        position: V,
+        velocity: V,
+        angular: V::Angular,
+        inverse_inertia: &V::InverseInertia,
+    ) -> Self {
+        let spin = V::apply_inertia(inverse_inertia, angular);
+        let rotational_energy = 0.5 * V::angular_dot(angular, spin);
+        Self {
+            kinetic_energy: 0.5 * mass * velocity.magnitude2() + rotational_energy,
+            rotational_energy,
+            linear_momentum: velocity * mass,
+            angular_momentum: V::cross(position, velocity * mass) + spin,
+            bodies: 1,
+        }
+    }
+
+    /// Add other totals to these totals
+    pub fn merge(&mut self, other: &Self) {
+        self.kinetic_energy += other.kinetic_energy;
+        self.rotational_energy += other.rotational_energy;
+        self.linear_momentum = self.linear_momentum + other.linear_momentum;
+        self.angular_momentum = self.angular_momentum + other.angular_momentum;
+        self.bodies += other.bodies;
+    }
+}
+
+impl<V> Default for EnergyTotals<V>
+where
+    V: RotationalVector,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Energy and momentum diagnostics, updated each frame by the
+/// [`EnergyDiagnosticsSystem`](struct.EnergyDiagnosticsSystem.html).
+///
+/// Contains the total kinetic energy, and linear and angular momentum of all bodies with a finite
+/// mass, for the current and the previous frame, to detect energy gain or loss in the solver, or
+/// to validate conservation in a setup. The rotational terms include bodies with an
+/// [`AngularVelocity`](../../physics/struct.AngularVelocity.html) and an
+/// [`Inertia`](../../physics/struct.Inertia.html).
+///
+/// Totals can optionally be kept per simulation world, see
+/// [`WorldId`](../collide/struct.WorldId.html), and per island of the
+/// [`ContactIslands`](struct.ContactIslands.html) resource, see
+/// [`with_per_island`](#method.with_per_island).
+///
+/// # Type parameters:
+///
+/// - `V`: cgmath vector type
+#[derive(Debug, Clone)]
+pub struct EnergyDiagnostics<V>
+where
+    V: LinearVector,
+{
+    current: EnergyTotals<V>,
+    previous: Option<EnergyTotals<V>>,
+    per_world: Option<HashMap<WorldId, EnergyTotals<V>>>,
+    per_island: Option<Vec<EnergyTotals<V>>>,
+}
+
+impl<V> EnergyDiagnostics<V>
+where
+    V: RotationalVector,
+{
+    /// Create a new diagnostics resource, tracking totals for all bodies
+    pub fn new() -> Self {
+        Self {
+            current: EnergyTotals::new(),
+            previous: None,
+            per_world: None,
+            per_island: None,
+        }
+    }
+
+    /// Also track totals per simulation world
+    pub fn with_per_world(mut self) -> Self {
+        self.per_world = Some(HashMap::default());
+        self
+    }
+
+    /// Also track totals per contact island.
+    ///
+    /// The islands are taken from the [`ContactIslands`](struct.ContactIslands.html) resource, so
+    /// the resource must be present, and the diagnostics system must run after the contact
+    /// solver. Bodies that are not part of any island are only included in the overall totals.
+    pub fn with_per_island(mut self) -> Self {
+        self.per_island = Some(Vec::default());
+        self
+    }
+
+    /// Get the totals for the current frame
+    pub fn current(&self) -> &EnergyTotals<V> {
+        &self.current
+    }
+
+    /// Get the totals for the previous frame, if any
+    pub fn previous(&self) -> Option<&EnergyTotals<V>> {
+        self.previous.as_ref()
+    }
+
+    /// Get the change in kinetic energy since the previous frame. A positive value with no
+    /// external forces acting means the solver added energy.
+    pub fn energy_change(&self) -> Real {
+        self.previous
+            .as_ref()
+            .map(|p| self.current.kinetic_energy - p.kinetic_energy)
+            .unwrap_or(0.)
+    }
+
+    /// Get the totals for the given simulation world in the current frame. Only available if per
+    /// world tracking is enabled.
+    pub fn world(&self, world: WorldId) -> Option<&EnergyTotals<V>> {
+        self.per_world.as_ref().and_then(|w| w.get(&world))
+    }
+
+    /// Is per world tracking enabled
+    pub fn is_per_world(&self) -> bool {
+        self.per_world.is_some()
+    }
+
+    /// Get the totals for the island with the given index in the
+    /// [`ContactIslands`](struct.ContactIslands.html) resource, in the current frame. Only
+    /// available if per island tracking is enabled.
+    pub fn island(&self, index: usize) -> Option<&EnergyTotals<V>> {
+        self.per_island.as_ref().and_then(|i| i.get(index))
+    }
+
+    /// Is per island tracking enabled
+    pub fn is_per_island(&self) -> bool {
+        self.per_island.is_some()
+    }
+
+    /// Start a new frame, the current totals become the previous totals
+    pub(crate) fn begin_frame(&mut self, islands: usize) {
+        let current = mem::replace(&mut self.current, EnergyTotals::new());
+        self.previous = Some(current);
+        if let Some(ref mut per_world) = self.per_world {
+            per_world.clear();
+        }
+        if let Some(ref mut per_island) = self.per_island {
+            per_island.clear();
+            per_island.resize(islands, EnergyTotals::new());
+        }
+    }
+
+    /// Add the totals of a body to the totals of the current frame
+    pub(crate) fn add_body(
+        &mut self,
+        world: WorldId,
+        island: Option<usize>,
+        body: &EnergyTotals<V>,
+    ) {
+        self.current.merge(body);
+        if let Some(ref mut per_world) = self.per_world {
+            per_world
+                .entry(world)
+                .or_insert_with(EnergyTotals::new)
+                .merge(body);
+        }
+        if let Some(totals) = island.and_then(|i| self.per_island.as_mut()?.get_mut(i)) {
+            totals.merge(body);
+        }
+    }
+}
+
+impl<V> Default for EnergyDiagnostics<V>
+where
+    V: RotationalVector,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Warm starting impulses of the
+/// [`SequentialImpulseSolverSystem`](struct.SequentialImpulseSolverSystem.html).
+///
+/// If this resource is present, the solver system keeps the impulses used for warm starting the
+/// next step in it, instead of in the system, so they are part of the world state, and are
+/// captured and restored by a [`PhysicsSnapshot`](struct.PhysicsSnapshot.html).
+///
+/// # Type parameters:
+///
+/// - `P`: cgmath point type
+#[derive(Debug, Clone)]
+pub struct WarmStartImpulses<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    pub(crate) impulses: ImpulseCache<Entity, P::Diff>,
+}
+
+impl<P> Default for WarmStartImpulses<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    fn default() -> Self {
+        Self {
+            impulses: ImpulseCache::default(),
+        }
+    }
+}
+
+impl<P> WarmStartImpulses<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    /// Forget the impulses, e.g. after teleporting bodies
+    pub fn clear(&mut self) {
+        self.impulses.clear();
+    }
+
+    /// Number of cached contact impulses
+    pub fn len(&self) -> usize {
+        self.impulses.len()
+    }
+
+    /// Returns true if there are no cached contact impulses
+    pub fn is_empty(&self) -> bool {
+        self.impulses.is_empty()
+    }
+}
+
+/// State the contact solvers keep between steps, apart from the warm starting impulses.
+///
+/// Contains the contacts deferred to the next step by the contact budget of the
+/// [`LinearContactSolverSystem`](struct.LinearContactSolverSystem.html), and the sleeping state of
+/// the contact solvers: the number of frames each body has been at rest, and the bodies the
+/// solvers put to sleep. If this resource is present, the solver systems keep their state in it,
+/// instead of in the systems, so it is part of the world state, and is captured and restored by a
+/// [`PhysicsSnapshot`](struct.PhysicsSnapshot.html).
+///
+/// # Type parameters:
+///
+/// - `P`: cgmath point type
+#[derive(Debug, Clone)]
+pub struct ContactSolverState<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    pub(crate) deferred: Vec<ContactEvent<Entity, P>>,
+    pub(crate) resting: HashMap<Entity, usize>,
+    pub(crate) sleeping: HashSet<Entity>,
+}
+
+impl<P> Default for ContactSolverState<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    fn default() -> Self {
+        Self {
+            deferred: Vec::default(),
+            resting: HashMap::default(),
+            sleeping: HashSet::default(),
+        }
+    }
+}
+
+impl<P> ContactSolverState<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    /// Get the contacts that were deferred to the next step, because of the contact budget
+    pub fn deferred(&self) -> &[ContactEvent<Entity, P>] {
+        &self.deferred
+    }
+
+    /// Check if the contact solvers put the given body to sleep
+    pub fn is_sleeping(&self, entity: Entity) -> bool {
+        self.sleeping.contains(&entity)
+    }
+
+    /// Forget the state, e.g. after teleporting bodies
+    pub fn clear(&mut self) {
+        self.deferred.clear();
+        self.resting.clear();
+        self.sleeping.clear();
+    }
+}
+
+/// Contacts resolved by the contact solver in the last step, with the total impulse applied to
+/// the second body of each contact. The first body got the negated impulse.
+///
+/// The contact solver only records resolved contacts if this resource is present.
+///
+/// # Type parameters:
+///
+/// - `P`: cgmath point type
+#[derive(Debug)]
+pub struct ResolvedContacts<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    pub(crate) contacts: Vec<(ContactEvent<Entity, P>, P::Diff)>,
+}
+
+impl<P> Default for ResolvedContacts<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    fn default() -> Self {
+        Self {
+            contacts: Vec::default(),
+        }
+    }
+}
+
+impl<P> Deref for ResolvedContacts<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    type Target = Vec<(ContactEvent<Entity, P>, P::Diff)>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.contacts
+    }
+}
+
+/// Islands of the contacts resolved by the contact solver in the last step, see
+/// [`Island`](../../physics/struct.Island.html). Bodies without a mass, with an infinite mass, or
+/// that are frozen or dormant, are static, and do not connect islands.
+///
+/// The contact solver only records islands if this resource is present. The contact indices of
+/// the islands refer to the contacts of the [`ResolvedContacts`](struct.ResolvedContacts.html)
+/// resource.
+#[derive(Debug, Default)]
+pub struct ContactIslands {
+    pub(crate) islands: Vec<Island<Entity>>,
+}
+
+impl Deref for ContactIslands {
+    type Target = Vec<Island<Entity>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.islands
+    }
+}
+
+/// Boxed callback of a collision response
+type ResponseCallback<P> = Box<
+    dyn FnMut(Entity, &ContactEvent<Entity, P>, <P as EuclideanSpace>::Diff, &LazyUpdate)
+        + Send
+        + Sync
+        + 'static,
+>;
+
+/// Collision response callback of a body, for bodies with bespoke reactions to contacts, e.g.
+/// arrows that stick to what they hit, or bodies that shatter on impact.
+///
+/// The callback is invoked by the
+/// [`CollisionResponseSystem`](struct.CollisionResponseSystem.html) for each contact of the body
+/// that was resolved by the contact solver in the last step, with the entity of the body, the
+/// contact, and the impulse applied to the body. Changes to the world can be made through the
+/// given [`LazyUpdate`](https://docs.rs/specs/0.10.0/specs/struct.LazyUpdate.html), and are
+/// applied on the next `World::maintain`.
+///
+/// # Type parameters:
+///
+/// - `P`: cgmath point type
+pub struct CollisionResponse<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    callback: ResponseCallback<P>,
+}
+
+impl<P> CollisionResponse<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    /// Create a new collision response
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: FnMut(Entity, &ContactEvent<Entity, P>, P::Diff, &LazyUpdate) + Send + Sync + 'static,
+    {
+        Self {
+            callback: Box::new(callback),
+        }
+    }
+
+    /// Invoke the callback
+    pub(crate) fn respond(
+        &mut self,
+        entity: Entity,
+        contact: &ContactEvent<Entity, P>,
+        impulse: P::Diff,
+        lazy: &LazyUpdate,
+    ) {
+        (self.callback)(entity, contact, impulse, lazy)
+    }
+}
+
+impl<P> Component for CollisionResponse<P>
+where
+    P: EuclideanSpace + Send + Sync + 'static,
+    P::Diff: Debug + Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
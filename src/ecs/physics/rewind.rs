@@ -0,0 +1,452 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+
+use cgmath::{EuclideanSpace, Rotation, VectorSpace};
+use specs::{Entity, Join, World};
+
+use {BodyPose, NextFrame, Real};
+use ecs::collide::Dormant;
+use ecs::physics::resources::{ContactSolverState, WarmStartImpulses};
+use physics::{AngularVelocity, ForceAccumulator, LinearVector, Velocity};
+
+/// Simulation state of a single body
+#[derive(Clone)]
+struct BodyState<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: LinearVector + Debug,
+    R: Rotation<P>,
+{
+    pose: BodyPose<P, R>,
+    next_pose: Option<NextFrame<BodyPose<P, R>>>,
+    velocity: Option<Velocity<P::Diff>>,
+    next_velocity: Option<NextFrame<Velocity<P::Diff>>>,
+    angular_velocity: Option<AngularVelocity<P::Diff>>,
+    forces: Option<ForceAccumulator<P::Diff>>,
+    dormant: bool,
+}
+
+/// Snapshot of the simulation state of all bodies in a world.
+///
+/// Contains the pose, next frame pose, velocity, next frame velocity, angular velocity,
+/// accumulated forces and [`Dormant`](../collide/struct.Dormant.html) marker of all entities with
+/// a [`BodyPose`](../../struct.BodyPose.html), and the
+/// [`WarmStartImpulses`](struct.WarmStartImpulses.html) and
+/// [`ContactSolverState`](struct.ContactSolverState.html) resources, if present.
+///
+/// Restoring a snapshot rewrites the poses of the bodies, which marks them as changed, so the
+/// narrow phase results cached by the
+/// [`SpatialCollisionSystem`](../collide/struct.SpatialCollisionSystem.html) are recomputed for
+/// them. Without a `WarmStartImpulses` resource, the warm starting impulses kept in a
+/// [`SequentialImpulseSolverSystem`](struct.SequentialImpulseSolverSystem.html) are not part of
+/// the snapshot, and should be cleared when restoring. The same goes for the deferred contacts
+/// and the sleeping state of the contact solvers without a `ContactSolverState` resource.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+#[derive(Clone)]
+pub struct PhysicsSnapshot<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: LinearVector + Debug,
+    R: Rotation<P>,
+{
+    bodies: Vec<(Entity, BodyState<P, R>)>,
+    warm_start: Option<WarmStartImpulses<P>>,
+    solver_state: Option<ContactSolverState<P>>,
+}
+
+impl<P, R> PhysicsSnapshot<P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: LinearVector + VectorSpace<Scalar = Real> + Debug + Send + Sync + 'static,
+    R: Rotation<P> + Send + Sync + 'static,
+{
+    /// Capture the state of all bodies in the world
+    pub fn capture(world: &World) -> Self {
+        let entities = world.entities();
+        let poses = world.read::<BodyPose<P, R>>();
+        let next_poses = world.read::<NextFrame<BodyPose<P, R>>>();
+        let velocities = world.read::<Velocity<P::Diff>>();
+        let next_velocities = world.read::<NextFrame<Velocity<P::Diff>>>();
+        let angular_velocities = world.read::<AngularVelocity<P::Diff>>();
+        let forces = world.read::<ForceAccumulator<P::Diff>>();
+        let dormant = world.read::<Dormant>();
+        let bodies = (&*entities, &poses)
+            .join()
+            .map(|(entity, pose)| {
+                (
+                    entity,
+                    BodyState {
+                        pose: pose.clone(),
+                        next_pose: next_poses.get(entity).cloned(),
+                        velocity: velocities.get(entity).cloned(),
+                        next_velocity: next_velocities.get(entity).cloned(),
+                        angular_velocity: angular_velocities.get(entity).cloned(),
+                        forces: forces.get(entity).cloned(),
+                        dormant: dormant.get(entity).is_some(),
+                    },
+                )
+            })
+            .collect();
+        let warm_start = world
+            .res
+            .try_fetch::<WarmStartImpulses<P>>(0)
+            .map(|warm_start| warm_start.clone());
+        let solver_state = world
+            .res
+            .try_fetch::<ContactSolverState<P>>(0)
+            .map(|state| state.clone());
+        Self {
+            bodies,
+            warm_start,
+            solver_state,
+        }
+    }
+
+    /// Restore the state of all bodies in the snapshot.
+    ///
+    /// Entities that have been deleted since the snapshot was captured are skipped, and entities
+    /// that were created after the snapshot was captured are left unchanged.
+    pub fn restore(&self, world: &World) {
+        let entities = world.entities();
+        let mut poses = world.write::<BodyPose<P, R>>();
+        let mut next_poses = world.write::<NextFrame<BodyPose<P, R>>>();
+        let mut velocities = world.write::<Velocity<P::Diff>>();
+        let mut next_velocities = world.write::<NextFrame<Velocity<P::Diff>>>();
+        let mut angular_velocities = world.write::<AngularVelocity<P::Diff>>();
+        let mut forces = world.write::<ForceAccumulator<P::Diff>>();
+        let mut dormant = world.write::<Dormant>();
+        for &(entity, ref state) in &self.bodies {
+            if !entities.is_alive(entity) {
+                continue;
+            }
+            poses.insert(entity, state.pose.clone());
+            match state.next_pose {
+                Some(ref next_pose) => {
+                    next_poses.insert(entity, next_pose.clone());
+                }
+                None => {
+                    next_poses.remove(entity);
+                }
+            }
+            match state.velocity {
+                Some(ref velocity) => {
+                    velocities.insert(entity, velocity.clone());
+                }
+                None => {
+                    velocities.remove(entity);
+                }
+            }
+            match state.next_velocity {
+                Some(ref next_velocity) => {
+                    next_velocities.insert(entity, next_velocity.clone());
+                }
+                None => {
+                    next_velocities.remove(entity);
+                }
+            }
+            match state.angular_velocity {
+                Some(ref angular_velocity) => {
+                    angular_velocities.insert(entity, angular_velocity.clone());
+                }
+                None => {
+                    angular_velocities.remove(entity);
+                }
+            }
+            match state.forces {
+                Some(ref force) => {
+                    forces.insert(entity, force.clone());
+                }
+                None => {
+                    forces.remove(entity);
+                }
+            }
+            if state.dormant {
+                dormant.insert(entity, Dormant);
+            } else {
+                dormant.remove(entity);
+            }
+        }
+        if let Some(ref warm_start) = self.warm_start {
+            if let Some(mut current) = world.res.try_fetch_mut::<WarmStartImpulses<P>>(0) {
+                *current = warm_start.clone();
+            }
+        }
+        if let Some(ref solver_state) = self.solver_state {
+            if let Some(mut current) = world.res.try_fetch_mut::<ContactSolverState<P>>(0) {
+                *current = solver_state.clone();
+            }
+        }
+    }
+}
+
+/// History of simulation snapshots, used to rewind the simulation to a past tick and resimulate
+/// up to the present, e.g. for server side hit registration or rollback netcode.
+///
+/// Call [`record`](#method.record) at the start of each tick, before stepping the simulation. To
+/// apply late inputs, call [`resimulate`](#method.resimulate), which restores the snapshot of the
+/// tick the inputs belong to, and steps the simulation forward to the present again, applying the
+/// inputs for each tick along the way. The stepping needs to be deterministic for the
+/// resimulation to reproduce the original simulation.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct RewindBuffer<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: LinearVector + Debug,
+    R: Rotation<P>,
+{
+    capacity: usize,
+    tick: u64,
+    snapshots: VecDeque<(u64, PhysicsSnapshot<P, R>)>,
+}
+
+impl<P, R> RewindBuffer<P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: LinearVector + VectorSpace<Scalar = Real> + Debug + Send + Sync + 'static,
+    R: Rotation<P> + Send + Sync + 'static,
+{
+    /// Create a new rewind buffer.
+    ///
+    /// # Parameters:
+    ///
+    /// - `capacity`: Number of ticks to keep snapshots for
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            snapshots: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Get the current tick, the tick that will be recorded next
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Get the oldest tick that can be rewound to
+    pub fn oldest_tick(&self) -> Option<u64> {
+        self.snapshots.front().map(|&(tick, _)| tick)
+    }
+
+    /// Record a snapshot of the world for the current tick, and advance the tick
+    pub fn record(&mut self, world: &World) {
+        if self.capacity == 0 {
+            self.tick += 1;
+            return;
+        }
+        while self.snapshots.len() >= self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots
+            .push_back((self.tick, PhysicsSnapshot::capture(world)));
+        self.tick += 1;
+    }
+
+    /// Get the snapshot recorded for the given tick
+    pub fn snapshot(&self, tick: u64) -> Option<&PhysicsSnapshot<P, R>> {
+        self.snapshots
+            .iter()
+            .find(|&&(t, _)| t == tick)
+            .map(|(_, snapshot)| snapshot)
+    }
+
+    /// Rewind the world to the given tick, and resimulate up to the present.
+    ///
+    /// For each tick from the given tick up to, but not including, the current tick, a new
+    /// snapshot is recorded, `apply_input` is called, and then `step` is called.
+    ///
+    /// # Parameters:
+    ///
+    /// - `world`: The simulation world
+    /// - `tick`: The tick to rewind to
+    /// - `apply_input`: Applies the inputs for a tick to the world
+    /// - `step`: Steps the simulation one tick, using the same deterministic stepping as the
+    ///   original simulation
+    ///
+    /// # Returns:
+    ///
+    /// `false` if the tick is not in the history, in which case the world is left unchanged.
+    pub fn resimulate<I, S>(
+        &mut self,
+        world: &mut World,
+        tick: u64,
+        mut apply_input: I,
+        mut step: S,
+    ) -> bool
+    where
+        I: FnMut(&mut World, u64),
+        S: FnMut(&mut World),
+    {
+        let position = match self.snapshots.iter().position(|&(t, _)| t == tick) {
+            Some(position) => position,
+            None => return false,
+        };
+        self.snapshots[position].1.restore(world);
+        let present = self.tick;
+        self.snapshots.truncate(position);
+        self.tick = tick;
+        while self.tick < present {
+            let current = self.tick;
+            self.record(world);
+            apply_input(world, current);
+            step(world);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, One, Point2, Vector2, Zero};
+    use shrev::EventChannel;
+    use specs::{Entity, RunNow, World};
+
+    use {NextFrame, Real};
+    use ecs::collide::Dormant;
+    use ecs::physics::prelude2d::*;
+
+    fn body(world: &mut World, position: Point2<Real>, body_type: BodyType) -> Entity {
+        let pose = BodyPose2::new(position, Basis2::one());
+        let velocity = Velocity2 {
+            linear: Vector2::zero(),
+        };
+        world
+            .create_entity()
+            .with(CollisionShape2::<BodyPose2>::new_simple(
+                CollisionStrategy::FullResolution,
+                CollisionMode::Discrete,
+                Rectangle::new(1., 1.).into(),
+            ))
+            .with(pose.clone())
+            .with(NextFrame { value: pose })
+            .with(velocity.clone())
+            .with(NextFrame { value: velocity })
+            .with(Mass::new(1.))
+            .with(ForceAccumulator2::new())
+            .with(AngularVelocity2::default())
+            .with(Inertia2::new(1.))
+            .with(body_type)
+            .build()
+    }
+
+    #[test]
+    fn test_resimulate_without_input_is_bit_exact() {
+        let mut world = World::new();
+        world_physics_register(&mut world);
+        world.write_resource::<DeltaTime>().delta_seconds = 1. / 60.;
+        *world.write_resource::<Gravity2>() = Gravity2::new(Vector2::new(0., -10.));
+        let bodies = [
+            body(&mut world, Point2::new(0., 0.), BodyType::Static),
+            body(&mut world, Point2::new(0., 1.), BodyType::Dynamic),
+            body(&mut world, Point2::new(0.3, 1.98), BodyType::Dynamic),
+        ];
+        world
+            .write::<ForceAccumulator2>()
+            .get_mut(bodies[2])
+            .unwrap()
+            .add_torque(5.);
+        let reader = world
+            .write_resource::<EventChannel<ContactEvent2>>()
+            .register_reader();
+        let mut gravity = GravitySystem2::new();
+        let mut collision = BasicCollisionSystem2::<BodyPose2>::new()
+            .with_broad_phase(BroadBruteForce2::default())
+            .with_narrow_phase(GJK2::new());
+        let mut solver = SequentialImpulseSolverSystem2::new(reader);
+        let mut step = |world: &mut World| {
+            gravity.run_now(&world.res);
+            collision.run_now(&world.res);
+            solver.run_now(&world.res);
+            world.maintain();
+        };
+        let poses = |world: &World| {
+            let poses = world.read::<BodyPose2>();
+            bodies
+                .iter()
+                .map(|&entity| {
+                    let pose = poses.get(entity).unwrap();
+                    (*pose.position(), *pose.rotation())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut rewind = RewindBuffer2::new(60);
+        for _ in 0..40 {
+            rewind.record(&world);
+            step(&mut world);
+        }
+        assert!(!world.read_resource::<WarmStartImpulses2>().is_empty());
+        let expected = poses(&world);
+        assert!(rewind.resimulate(&mut world, 10, |_, _| {}, &mut step));
+        assert_eq!(40, rewind.tick());
+        assert_eq!(expected, poses(&world));
+    }
+
+    #[test]
+    fn test_resimulate_across_sleep_transition() {
+        let mut world = World::new();
+        world_physics_register(&mut world);
+        world.write_resource::<DeltaTime>().delta_seconds = 1. / 60.;
+        *world.write_resource::<Gravity2>() = Gravity2::new(Vector2::new(0., -10.));
+        let ground = body(&mut world, Point2::new(0., 0.), BodyType::Static);
+        let falling = body(&mut world, Point2::new(0.2, 1.2), BodyType::Dynamic);
+        // without restitution the box settles after the first bounce
+        let mut materials = MaterialRegistry::new();
+        let clay = materials.add("clay", Material::new(0., 0.5));
+        world.add_resource(materials);
+        for &entity in &[ground, falling] {
+            world.write::<MaterialHandle>().insert(entity, clay);
+        }
+        let reader = world
+            .write_resource::<EventChannel<ContactEvent2>>()
+            .register_reader();
+        let mut gravity = GravitySystem2::new();
+        let mut collision = BasicCollisionSystem2::<BodyPose2>::new()
+            .with_broad_phase(BroadBruteForce2::default())
+            .with_narrow_phase(GJK2::new());
+        let mut solver = SequentialImpulseSolverSystem2::new(reader).with_sleeping(0.1, 10);
+        let mut step = |world: &mut World| {
+            gravity.run_now(&world.res);
+            collision.run_now(&world.res);
+            solver.run_now(&world.res);
+            world.maintain();
+        };
+        let state = |world: &World| {
+            let pose = world.read::<BodyPose2>().get(falling).unwrap().clone();
+            let velocity = world.read::<Velocity2>().get(falling).unwrap().linear;
+            let dormant = world.read::<Dormant>().get(falling).is_some();
+            (*pose.position(), velocity, dormant)
+        };
+
+        let mut rewind = RewindBuffer2::new(120);
+        let mut asleep_at = None;
+        for tick in 0..120 {
+            rewind.record(&world);
+            step(&mut world);
+            if asleep_at.is_none() && state(&world).2 {
+                asleep_at = Some(tick);
+            }
+        }
+        let asleep_at = asleep_at.expect("the box falls asleep");
+        let expected = state(&world);
+        assert!(expected.2);
+        assert!(
+            world
+                .read_resource::<ContactSolverState2>()
+                .is_sleeping(falling)
+        );
+
+        // rewind to before the box settled, the box is awake again, and falls asleep again
+        assert!(asleep_at > 5);
+        assert!(rewind.resimulate(&mut world, 5, |_, _| {}, &mut step));
+        assert_eq!(expected, state(&world));
+    }
+}
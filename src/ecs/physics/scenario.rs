@@ -0,0 +1,605 @@
+use std::fmt::Debug;
+
+use cgmath::{EuclideanSpace, InnerSpace, Rotation, VectorSpace, Zero};
+use collision::{Aabb, Primitive};
+use specs::{Entity, World};
+
+use {BodyPose, NextFrame, Real};
+use collide::CollisionShape;
+use ecs::physics::SequentialPhysics;
+use physics::{ForceAccumulator, LinearVector, Mass, Velocity};
+
+/// Body in a [`Scenario`](struct.Scenario.html)
+///
+/// ### Type parameters:
+///
+/// - `P`: Collision primitive type
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct ScenarioBody<P, R>
+where
+    P: Primitive,
+    P::Aabb: Aabb<Scalar = Real>,
+    P::Point: EuclideanSpace<Scalar = Real>,
+    R: Rotation<P::Point>,
+{
+    shape: CollisionShape<P, BodyPose<P::Point, R>>,
+    pose: BodyPose<P::Point, R>,
+    velocity: <P::Point as EuclideanSpace>::Diff,
+    mass: Option<Real>,
+    force: <P::Point as EuclideanSpace>::Diff,
+}
+
+impl<P, R> ScenarioBody<P, R>
+where
+    P: Primitive,
+    P::Aabb: Aabb<Scalar = Real>,
+    P::Point: EuclideanSpace<Scalar = Real>,
+    <P::Point as EuclideanSpace>::Diff: VectorSpace<Scalar = Real>,
+    R: Rotation<P::Point>,
+{
+    /// Create a new body, at rest and without a mass
+    pub fn new(
+        shape: CollisionShape<P, BodyPose<P::Point, R>>,
+        pose: BodyPose<P::Point, R>,
+    ) -> Self {
+        Self {
+            shape,
+            pose,
+            velocity: Zero::zero(),
+            mass: None,
+            force: Zero::zero(),
+        }
+    }
+
+    /// Set the initial velocity
+    pub fn with_velocity(mut self, velocity: <P::Point as EuclideanSpace>::Diff) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    /// Set the mass, bodies without a mass are collision only
+    pub fn with_mass(mut self, mass: Real) -> Self {
+        self.mass = Some(mass);
+        self
+    }
+
+    /// Set a constant force, applied every frame
+    pub fn with_force(mut self, force: <P::Point as EuclideanSpace>::Diff) -> Self {
+        self.force = force;
+        self
+    }
+}
+
+/// State of all bodies in a [`Scenario`](struct.Scenario.html) at the end of a frame
+#[derive(Debug, Clone)]
+pub struct ScenarioFrame<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    /// Frame number, 0 is the initial state
+    pub frame: usize,
+    /// Positions of the bodies, in the order they were added to the scenario
+    pub positions: Vec<P>,
+    /// Velocities of the bodies, in the order they were added to the scenario
+    pub velocities: Vec<P::Diff>,
+    /// Total kinetic energy of all bodies with a finite mass
+    pub kinetic_energy: Real,
+}
+
+/// Result of running a [`Scenario`](struct.Scenario.html)
+#[derive(Debug, Clone)]
+pub struct ScenarioResult<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    /// The entities of the bodies, in the order they were added to the scenario
+    pub bodies: Vec<Entity>,
+    /// State of the bodies for each frame, including the initial state
+    pub frames: Vec<ScenarioFrame<P>>,
+    /// Invariant violations, as frame number and invariant name
+    pub violations: Vec<(usize, String)>,
+}
+
+impl<P> ScenarioResult<P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    /// Did all invariants hold for all frames
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Get the trajectory of the given body, as the position for each frame
+    pub fn trajectory(&self, body: usize) -> Vec<P> {
+        self.frames.iter().map(|f| f.positions[body]).collect()
+    }
+
+    /// Get the total kinetic energy for each frame
+    pub fn kinetic_energy(&self) -> Vec<Real> {
+        self.frames.iter().map(|f| f.kinetic_energy).collect()
+    }
+}
+
+/// Named check on a frame of a scenario
+type Invariant<P> = Box<dyn Fn(&ScenarioFrame<P>) -> bool>;
+
+/// Setup of the world, given the entities of the bodies
+type Setup = Box<dyn Fn(&mut World, &[Entity])>;
+
+/// Declarative simulation scenario, for writing physics regression tests.
+///
+/// A scenario is a list of bodies, and a list of named invariants that are checked at the end of
+/// every frame. Running the scenario spawns the bodies in the given world, runs any setup, e.g. to
+/// add joints between the bodies, steps the simulation for the given number of frames, and returns
+/// the trajectories and energy of the bodies, along with any invariant violations.
+///
+/// The world needs to have the physics components and resources registered, see
+/// `world_physics_register` in the [2D](prelude2d/index.html) and [3D](prelude3d/index.html)
+/// preludes.
+///
+/// ### Type parameters:
+///
+/// - `P`: Collision primitive type
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct Scenario<P, R>
+where
+    P: Primitive,
+    P::Aabb: Aabb<Scalar = Real>,
+    P::Point: EuclideanSpace<Scalar = Real>,
+    <P::Point as EuclideanSpace>::Diff: Debug,
+    R: Rotation<P::Point>,
+{
+    bodies: Vec<ScenarioBody<P, R>>,
+    invariants: Vec<(String, Invariant<P::Point>)>,
+    setups: Vec<Setup>,
+    delta_seconds: Real,
+}
+
+impl<P, R> Scenario<P, R>
+where
+    P: Primitive + Send + Sync + 'static,
+    P::Aabb: Aabb<Scalar = Real> + Send + Sync + 'static,
+    P::Point: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    <P::Point as EuclideanSpace>::Diff: LinearVector
+        + InnerSpace
+        + Debug
+        + Send
+        + Sync
+        + 'static,
+    R: Rotation<P::Point> + Send + Sync + 'static,
+{
+    /// Create a new empty scenario
+    ///
+    /// # Parameters:
+    ///
+    /// - `delta_seconds`: Time step used for each frame
+    pub fn new(delta_seconds: Real) -> Self {
+        Self {
+            bodies: Vec::default(),
+            invariants: Vec::default(),
+            setups: Vec::default(),
+            delta_seconds,
+        }
+    }
+
+    /// Add a body, builder pattern
+    pub fn with_body(mut self, body: ScenarioBody<P, R>) -> Self {
+        self.bodies.push(body);
+        self
+    }
+
+    /// Add a named invariant, that should hold at the end of every frame, builder pattern
+    pub fn with_invariant<F>(mut self, name: &str, invariant: F) -> Self
+    where
+        F: Fn(&ScenarioFrame<P::Point>) -> bool + 'static,
+    {
+        self.invariants.push((name.to_string(), Box::new(invariant)));
+        self
+    }
+
+    /// Add a setup step, run after the bodies are spawned, with the entities of the bodies in the
+    /// order they were added, builder pattern
+    pub fn with_setup<F>(mut self, setup: F) -> Self
+    where
+        F: Fn(&mut World, &[Entity]) + 'static,
+    {
+        self.setups.push(Box::new(setup));
+        self
+    }
+
+    /// Run the scenario.
+    ///
+    /// # Parameters:
+    ///
+    /// - `world`: The world to spawn the bodies in
+    /// - `physics`: The systems used to step the simulation
+    /// - `frames`: Number of frames to simulate
+    pub fn run(
+        self,
+        world: &mut World,
+        physics: &mut SequentialPhysics,
+        frames: usize,
+    ) -> ScenarioResult<P::Point> {
+        let mut bodies = Vec::with_capacity(self.bodies.len());
+        for body in self.bodies {
+            let mut builder = world
+                .create_entity()
+                .with(NextFrame {
+                    value: body.pose.clone(),
+                })
+                .with(body.pose)
+                .with(body.shape)
+                .with(Velocity {
+                    linear: body.velocity,
+                })
+                .with(NextFrame {
+                    value: Velocity {
+                        linear: body.velocity,
+                    },
+                })
+                .with(ForceAccumulator::<<P::Point as EuclideanSpace>::Diff>::new());
+            if let Some(mass) = body.mass {
+                builder = builder.with(Mass::new(mass));
+            }
+            bodies.push((builder.build(), body.force));
+        }
+        let entities = bodies.iter().map(|&(entity, _)| entity).collect::<Vec<_>>();
+        for setup in &self.setups {
+            setup(world, &entities);
+        }
+
+        let mut result = ScenarioResult {
+            bodies: entities,
+            frames: Vec::with_capacity(frames + 1),
+            violations: Vec::default(),
+        };
+        for frame in 0..frames + 1 {
+            if frame > 0 {
+                {
+                    let mut forces =
+                        world.write::<ForceAccumulator<<P::Point as EuclideanSpace>::Diff>>();
+                    for &(entity, force) in &bodies {
+                        if let Some(accumulator) = forces.get_mut(entity) {
+                            accumulator.add_force(force);
+                        }
+                    }
+                }
+                physics.step(world, self.delta_seconds);
+            }
+            let state = capture::<P, R>(world, &bodies, frame);
+            for (name, invariant) in &self.invariants {
+                if !invariant(&state) {
+                    result.violations.push((frame, name.clone()));
+                }
+            }
+            result.frames.push(state);
+        }
+        result
+    }
+}
+
+fn capture<P, R>(
+    world: &World,
+    bodies: &[(Entity, <P::Point as EuclideanSpace>::Diff)],
+    frame: usize,
+) -> ScenarioFrame<P::Point>
+where
+    P: Primitive,
+    P::Aabb: Aabb<Scalar = Real>,
+    P::Point: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    <P::Point as EuclideanSpace>::Diff: VectorSpace<Scalar = Real>
+        + InnerSpace
+        + Debug
+        + Send
+        + Sync
+        + 'static,
+    R: Rotation<P::Point> + Send + Sync + 'static,
+{
+    let poses = world.read::<BodyPose<P::Point, R>>();
+    let velocities = world.read::<Velocity<<P::Point as EuclideanSpace>::Diff>>();
+    let masses = world.read::<Mass>();
+    let mut state = ScenarioFrame {
+        frame,
+        positions: Vec::with_capacity(bodies.len()),
+        velocities: Vec::with_capacity(bodies.len()),
+        kinetic_energy: 0.,
+    };
+    for &(entity, _) in bodies {
+        let position = poses
+            .get(entity)
+            .map(|p| *p.position())
+            .unwrap_or_else(P::Point::origin);
+        let velocity = velocities
+            .get(entity)
+            .map(|v| v.linear)
+            .unwrap_or_else(Zero::zero);
+        if let Some(mass) = masses.get(entity) {
+            if mass.mass().is_finite() {
+                state.kinetic_energy += 0.5 * mass.mass() * velocity.magnitude2();
+            }
+        }
+        state.positions.push(position);
+        state.velocities.push(velocity);
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, InnerSpace, One, Point2, Vector2};
+    use collision::primitive::Primitive2;
+    use shrev::EventChannel;
+    use specs::{Join, System, World, WriteStorage};
+
+    use {NextFrame, Real};
+    use ecs::collide::{query_overlap, BroadPhaseStats, Dormant, SteppedWorld, WorldId};
+    use ecs::physics::SequentialPhysics;
+    use ecs::physics::prelude2d::*;
+
+    const DT: Real = 1. / 60.;
+
+    fn world() -> World {
+        let mut world = World::new();
+        world_physics_register_with_spatial(&mut world);
+        *world.write_resource::<Gravity2>() = Gravity2::new(Vector2::new(0., -10.));
+        world
+    }
+
+    fn physics(world: &mut World, sequential: bool) -> SequentialPhysics<'static> {
+        let reader = world
+            .write_resource::<EventChannel<ContactEvent2>>()
+            .register_reader();
+        let physics = SequentialPhysics::new()
+            .with(SpatialSortingSystem2::<BodyPose2>::new())
+            .with(SpatialCollisionSystem2::<BodyPose2>::new().with_narrow_phase(GJK2::new()))
+            .with(GravitySystem2::new())
+            .with(DistanceJointSystem2::new());
+        if sequential {
+            physics.with(SequentialImpulseSolverSystem2::new(reader))
+        } else {
+            physics.with(LinearContactSolverSystem2::new(reader))
+        }
+    }
+
+    fn body(x: Real, y: Real, width: Real, height: Real) -> ScenarioBody2 {
+        ScenarioBody2::new(
+            CollisionShape2::new_simple(
+                CollisionStrategy::FullResolution,
+                CollisionMode::Discrete,
+                Rectangle::new(width, height).into(),
+            ),
+            BodyPose2::new(Point2::new(x, y), Basis2::one()),
+        )
+    }
+
+    /// Three boxes stacked on an immovable ground
+    fn stack() -> Scenario2 {
+        Scenario2::new(DT)
+            .with_body(body(0., 0., 20., 1.).with_mass(Real::INFINITY))
+            .with_body(body(0., 1., 1., 1.).with_mass(1.))
+            .with_body(body(0., 2., 1., 1.).with_mass(1.))
+            .with_body(body(0., 3., 1., 1.).with_mass(1.))
+            .with_invariant("boxes do not sink into each other", |frame| {
+                (1..4).all(|i| frame.positions[i].y - frame.positions[i - 1].y > 0.9)
+            })
+    }
+
+    #[test]
+    fn test_gravity_fall() {
+        for &sequential in &[false, true] {
+            let mut world = world();
+            let mut physics = physics(&mut world, sequential);
+            let result = Scenario2::new(DT)
+                .with_body(body(0., 10., 1., 1.).with_mass(2.))
+                .with_invariant("falls straight down", |frame| {
+                    frame.positions[0].x == 0. && frame.velocities[0].y <= 0.
+                })
+                .run(&mut world, &mut physics, 30);
+            assert!(result.is_ok(), "{:?}", result.violations);
+            // the velocity of a frame is the next frame velocity of the frame before
+            let last = &result.frames[30];
+            assert_relative_eq!(last.velocities[0].y, -10. * 29. * DT, epsilon = 1e-4);
+            assert!(result.kinetic_energy().windows(2).all(|w| w[1] >= w[0]));
+        }
+    }
+
+    #[test]
+    fn test_resting_stack() {
+        for &sequential in &[false, true] {
+            let mut world = world();
+            let mut physics = physics(&mut world, sequential);
+            let result = stack().run(&mut world, &mut physics, 120);
+            assert!(result.is_ok(), "{:?}", result.violations);
+            // resolving each contact once leaves the stack jittering with a bounded energy, the
+            // sequential impulse solver brings it to rest
+            if sequential {
+                assert!(result.frames[120].kinetic_energy < 1e-6);
+            } else {
+                assert!(result.kinetic_energy().iter().all(|&energy| energy < 1.));
+            }
+        }
+    }
+
+    #[test]
+    fn test_pendulum_joint() {
+        for &sequential in &[false, true] {
+            let mut world = world();
+            let mut physics = physics(&mut world, sequential);
+            // the pivot has no mass, and acts as a fixed anchor
+            let result = Scenario2::new(DT)
+                .with_body(body(0., 0., 0.1, 0.1))
+                .with_body(body(1., 0., 0.1, 0.1).with_mass(1.))
+                .with_setup(|world, bodies| {
+                    world
+                        .create_entity()
+                        .with(DistanceJoint::new(bodies[0], bodies[1], 1.))
+                        .build();
+                })
+                .with_invariant("rod keeps its length", |frame| {
+                    ((frame.positions[1] - frame.positions[0]).magnitude() - 1.).abs() < 0.05
+                })
+                .run(&mut world, &mut physics, 60);
+            assert!(result.is_ok(), "{:?}", result.violations);
+            // the bob swings through the bottom
+            assert!(result.trajectory(1).iter().any(|position| position.y < -0.99));
+        }
+    }
+
+    #[test]
+    fn test_rewound_stack_resimulates_bit_exact() {
+        let mut world = world();
+        let mut physics = physics(&mut world, true);
+        let result = stack().run(&mut world, &mut physics, 30);
+        assert!(result.is_ok(), "{:?}", result.violations);
+        let positions = |world: &World| {
+            let poses = world.read::<BodyPose2>();
+            result
+                .bodies
+                .iter()
+                .map(|&entity| *poses.get(entity).unwrap().position())
+                .collect::<Vec<_>>()
+        };
+
+        let mut rewind = RewindBuffer2::new(60);
+        for _ in 0..30 {
+            rewind.record(&world);
+            physics.step(&mut world, DT);
+        }
+        let expected = positions(&world);
+        assert!(rewind.resimulate(&mut world, 10, |_, _| {}, |world| physics.step(world, DT)));
+        assert_eq!(expected, positions(&world));
+    }
+
+    #[test]
+    fn test_batch_runner_matches_scenario() {
+        let mut world = world();
+        let mut physics = physics(&mut world, true);
+        let expected = stack().run(&mut world, &mut physics, 60);
+
+        // spawn the same bodies without stepping, and step them with the batch runner instead
+        let mut world = self::world();
+        let physics = self::physics(&mut world, true);
+        let spawned = stack().run(&mut world, &mut SequentialPhysics::new(), 0);
+        let logs = BatchRunner2::new(physics, DT).run(&mut world, 60);
+        let last = logs.last().unwrap();
+        for (i, &entity) in spawned.bodies.iter().enumerate() {
+            let log = last.bodies.iter().find(|log| log.entity == entity).unwrap();
+            assert_eq!(expected.frames[60].positions[i], *log.pose.position());
+        }
+    }
+
+    /// Clears the pose and shape flags after collision detection, so the narrow phase only tests
+    /// the pairs of shapes moved since
+    struct ClearFlagsSystem;
+
+    impl<'a> System<'a> for ClearFlagsSystem {
+        type SystemData = (
+            WriteStorage<'a, BodyPose2>,
+            WriteStorage<'a, NextFrame<BodyPose2>>,
+            WriteStorage<'a, CollisionShape2<BodyPose2>>,
+        );
+
+        fn run(&mut self, (mut poses, mut next_poses, mut shapes): Self::SystemData) {
+            (&mut poses).open().1.clear_flags();
+            (&mut next_poses).open().1.clear_flags();
+            (&mut shapes).open().1.clear_flags();
+        }
+    }
+
+    #[test]
+    fn test_sleeping_stack_skips_narrow_phase() {
+        // the linear solver leaves the stack jittering, so only the sequential impulse solver
+        // puts it to sleep
+        let mut world = world();
+        let reader = world
+            .write_resource::<EventChannel<ContactEvent2>>()
+            .register_reader();
+        let mut physics = SequentialPhysics::new()
+            .with(SpatialSortingSystem2::<BodyPose2>::new())
+            .with(SpatialCollisionSystem2::<BodyPose2>::new().with_narrow_phase(GJK2::new()))
+            .with(ClearFlagsSystem)
+            .with(GravitySystem2::new())
+            .with(SequentialImpulseSolverSystem2::new(reader).with_sleeping(0.1, 10));
+        let result = stack()
+            .with_setup(|world, bodies| {
+                world.write::<BodyType>().insert(bodies[0], BodyType::Static);
+            })
+            .run(&mut world, &mut physics, 120);
+        assert!(result.is_ok(), "{:?}", result.violations);
+        let asleep = |world: &World| {
+            result.bodies[1..]
+                .iter()
+                .all(|&entity| world.read::<Dormant>().get(entity).is_some())
+        };
+        assert!(asleep(&world));
+
+        // the sleeping stack is not moved, so the cached contacts are reused, and the stack
+        // stays asleep
+        for _ in 0..10 {
+            physics.step(&mut world, DT);
+            assert_eq!(0, world.read_resource::<BroadPhaseStats>().candidates);
+            assert!(asleep(&world));
+        }
+        // pairs of sleeping boxes are not tested, the contact with the ground is still
+        // reported from the cache
+        assert_eq!(1, world.read_resource::<ResolvedContacts2>().contacts.len());
+    }
+
+    #[test]
+    fn test_stepped_world_leaves_other_worlds() {
+        for &sequential in &[false, true] {
+            let mut world = world();
+            let mut physics = physics(&mut world, sequential);
+            // two falling boxes in different worlds, pinned together by a joint across the worlds
+            let result = Scenario2::new(DT)
+                .with_body(body(0., 10., 1., 1.).with_mass(1.))
+                .with_body(body(0., 10., 1., 1.).with_mass(1.))
+                .with_setup(|world, bodies| {
+                    world.write::<WorldId>().insert(bodies[1], WorldId(1));
+                    world
+                        .create_entity()
+                        .with(DistanceJoint::new(bodies[0], bodies[1], 0.))
+                        .build();
+                })
+                .run(&mut world, &mut physics, 1);
+            let (first, second) = (result.bodies[0], result.bodies[1]);
+            let state = |world: &World, entity| {
+                let position = *world.read::<BodyPose2>().get(entity).unwrap().position();
+                let velocity = world.read::<Velocity2>().get(entity).unwrap().linear;
+                (position, velocity)
+            };
+            let before = state(&world, first);
+
+            world.add_resource(SteppedWorld(WorldId(1)));
+            for _ in 0..30 {
+                physics.step(&mut world, DT);
+            }
+            // the default world is not stepped, and the joint across the worlds is ignored
+            assert_eq!(before, state(&world, first));
+            let (position, velocity) = state(&world, second);
+            assert!(position.y < 9.);
+            assert_relative_eq!(velocity.y, -10. * 30. * DT, epsilon = 1e-4);
+
+            // queries only find the entities in the given world
+            let probe: Primitive2<Real> = Rectangle::new(0.5, 0.5).into();
+            let transform = BodyPose2::new(before.0 + Vector2::new(0.3, 0.2), Basis2::one());
+            let found = |id| {
+                query_overlap(
+                    probe.clone(),
+                    &transform,
+                    &world.read_resource::<DynamicBoundingVolumeTree2>(),
+                    &world.read::<CollisionShape2<BodyPose2>>(),
+                    None,
+                    &world.read::<BodyPose2>(),
+                    Some((id, &world.read::<WorldId>())),
+                    &GJK2::new(),
+                )
+            };
+            assert_eq!(vec![first], found(WorldId(0)));
+            assert!(found(WorldId(1)).is_empty());
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use specs::{RunNow, System, World};
+
+use Real;
+use ecs::physics::resources::DeltaTime;
+
+/// Synchronous physics stepping, without a multithreaded dispatcher.
+///
+/// Runs the added systems one after the other, in the order they were added, on the calling
+/// thread. Intended for targets without thread support, like `wasm32`, where the `specs`
+/// `Dispatcher` can not run systems in parallel.
+///
+/// Systems are typically added in the same order they would be added to a `DispatcherBuilder`,
+/// for example: spatial sort, collision detection, contact resolution, then any force systems.
+pub struct SequentialPhysics<'a> {
+    systems: Vec<Box<dyn for<'b> RunNow<'b> + 'a>>,
+}
+
+impl<'a> SequentialPhysics<'a> {
+    /// Create a new sequential stepper, without any systems
+    pub fn new() -> Self {
+        Self {
+            systems: Vec::default(),
+        }
+    }
+
+    /// Add a system, builder pattern
+    pub fn with<S>(mut self, system: S) -> Self
+    where
+        S: for<'b> System<'b> + 'a,
+    {
+        self.add(system);
+        self
+    }
+
+    /// Add a system
+    pub fn add<S>(&mut self, system: S)
+    where
+        S: for<'b> System<'b> + 'a,
+    {
+        self.systems.push(Box::new(system));
+    }
+
+    /// Run a single physics step.
+    ///
+    /// Updates the `DeltaTime` resource, runs all systems in order, and then maintains the world.
+    ///
+    /// # Parameters:
+    ///
+    /// - `world`: The simulation world, needs to have a `DeltaTime` resource
+    /// - `delta_seconds`: Time step for the simulation
+    pub fn step(&mut self, world: &mut World, delta_seconds: Real) {
+        world.write_resource::<DeltaTime>().delta_seconds = delta_seconds;
+        for system in &mut self.systems {
+            system.run_now(&world.res);
+        }
+        world.maintain();
+    }
+}
+
+impl<'a> Default for SequentialPhysics<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,75 @@
+use cgmath::{Point3, Quaternion, Vector3};
+use specs::{Entities, Fetch, Join, ReadStorage, System, WriteStorage};
+
+use {BodyPose, NextFrame, Real};
+use ecs::collide::{Dormant, Frozen};
+use ecs::physics::resources::DeltaTime;
+use physics::{solve_plane_constraint, solve_upright_constraint, BodyType, PlaneConstraint3,
+              UprightConstraint3, Velocity};
+use super::linear_impulse::{is_frozen, is_kinematic};
+
+/// Body constraint system for 3D.
+///
+/// Will resolve all [`PlaneConstraint3`](../../physics/struct.PlaneConstraint3.html) and
+/// [`UprightConstraint3`](../../physics/struct.UprightConstraint3.html) components, which are
+/// placed on the constrained body. Operates on the next frame velocities and poses, so should run
+/// before the contact solver.
+///
+/// [`Frozen`](../collide/struct.Frozen.html), [`Dormant`](../collide/struct.Dormant.html), static
+/// and kinematic bodies are not moved by the constraints, see
+/// [`BodyType`](../../physics/enum.BodyType.html).
+pub struct BodyConstraintSystem3;
+
+impl<'a> System<'a> for BodyConstraintSystem3 {
+    type SystemData = (
+        Entities<'a>,
+        Fetch<'a, DeltaTime>,
+        ReadStorage<'a, Frozen>,
+        ReadStorage<'a, Dormant>,
+        ReadStorage<'a, BodyType>,
+        ReadStorage<'a, PlaneConstraint3>,
+        ReadStorage<'a, UprightConstraint3>,
+        WriteStorage<'a, NextFrame<Velocity<Vector3<Real>>>>,
+        WriteStorage<'a, NextFrame<BodyPose<Point3<Real>, Quaternion<Real>>>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            time,
+            frozen,
+            dormant,
+            body_types,
+            planes,
+            uprights,
+            mut next_velocities,
+            mut next_poses,
+        ) = data;
+        let immovable = |entity| {
+            is_frozen(&frozen, &dormant, &body_types, entity) || is_kinematic(&body_types, entity)
+        };
+
+        for (entity, constraint, velocity, pose) in
+            (&*entities, &planes, &mut next_velocities, &next_poses).join()
+        {
+            if immovable(entity) {
+                continue;
+            }
+            velocity.value.linear = solve_plane_constraint(
+                constraint,
+                pose.value.position(),
+                velocity.value.linear,
+                time.delta_seconds,
+            );
+        }
+
+        for (entity, constraint, pose) in (&*entities, &uprights, &mut next_poses).join() {
+            if immovable(entity) {
+                continue;
+            }
+            let rotation =
+                solve_upright_constraint(constraint, pose.value.rotation(), time.delta_seconds);
+            pose.value.set_rotation(rotation);
+        }
+    }
+}
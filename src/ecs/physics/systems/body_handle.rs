@@ -0,0 +1,72 @@
+use std::marker;
+
+use cgmath::{EuclideanSpace, Rotation};
+use specs::{Entities, FetchMut, Join, ReadStorage, System, WriteStorage};
+
+use {BodyPose, Real};
+use ecs::physics::{BodyHandle, BodyHandles};
+
+/// Body handle system, maintains the [`BodyHandles`](struct.BodyHandles.html) mapping.
+///
+/// All bodies with a pose and no [`BodyHandle`](struct.BodyHandle.html) are given a new handle,
+/// and the mappings of deleted entities, and of entities where the handle component was removed,
+/// are dropped.
+///
+/// Should run after bodies are created and deleted, typically first in the frame.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct BodyHandleSystem<P, R> {
+    m: marker::PhantomData<(P, R)>,
+}
+
+impl<P, R> BodyHandleSystem<P, R> {
+    /// Create a new body handle system
+    pub fn new() -> Self {
+        Self {
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<P, R> Default for BodyHandleSystem<P, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, P, R> System<'a> for BodyHandleSystem<P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    R: Rotation<P> + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        FetchMut<'a, BodyHandles>,
+        ReadStorage<'a, BodyPose<P, R>>,
+        WriteStorage<'a, BodyHandle>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, mut mapping, poses, mut handles) = data;
+        mapping.retain(|entity, handle| {
+            entities.is_alive(entity) && handles.get(entity) == Some(&handle)
+        });
+        let new_bodies = (&*entities, &poses, !&handles)
+            .join()
+            .map(|(entity, _, _)| entity)
+            .collect::<Vec<_>>();
+        for entity in new_bodies {
+            let handle = mapping.create(entity);
+            handles.insert(entity, handle);
+        }
+        // handles added as components by the user, from another world or a previous session
+        for (entity, handle) in (&*entities, &handles).join() {
+            if mapping.handle(entity).is_none() {
+                mapping.insert(entity, *handle);
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use cgmath::{Basis2, Point2, Vector2};
+use collision::Discrete;
+use collision::primitive::Primitive2;
+use specs::{Entities, Join, ReadStorage, System, WriteStorage};
+
+use {BodyPose, Real};
+use collide::CollisionShape;
+use ecs::collide::{same_world, WorldId};
+use physics::{submerged_area, AngularVelocity, ForceAccumulator, Velocity, WaterArea2};
+
+/// Buoyancy system for 2D.
+///
+/// Will compute the submerged area for all bodies that overlap a
+/// [`WaterArea2`](../../physics/struct.WaterArea2.html), and add buoyancy and drag forces to the
+/// force accumulator of those bodies.
+///
+/// The forces are applied at the centroid of the submerged area, so the buoyancy rights tilted
+/// bodies that have an [`AngularVelocity`](../../physics/struct.AngularVelocity.html) and an
+/// [`Inertia`](../../physics/struct.Inertia.html). Bodies with an angular velocity also get an
+/// angular drag torque.
+///
+/// Should run after the collision shapes have been updated, and before the contact solver.
+pub struct BuoyancySystem2;
+
+impl<'a> System<'a> for BuoyancySystem2 {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, WaterArea2>,
+        ReadStorage<'a, BodyPose<Point2<Real>, Basis2<Real>>>,
+        ReadStorage<'a, Velocity<Vector2<Real>>>,
+        ReadStorage<'a, AngularVelocity<Vector2<Real>>>,
+        ReadStorage<'a, CollisionShape<Primitive2<Real>, BodyPose<Point2<Real>, Basis2<Real>>>>,
+        WriteStorage<'a, ForceAccumulator<Vector2<Real>>>,
+        ReadStorage<'a, WorldId>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            waters,
+            poses,
+            velocities,
+            angular_velocities,
+            shapes,
+            mut forces,
+            world_ids,
+        ) = data;
+
+        for (water_entity, water, water_pose, water_shape) in
+            (&*entities, &waters, &poses, &shapes).join()
+        {
+            for (entity, pose, velocity, shape, force) in
+                (&*entities, &poses, &velocities, &shapes, &mut forces).join()
+            {
+                if entity == water_entity || !same_world(&world_ids, entity, water_entity)
+                    || !shape.bound().intersects(water_shape.bound())
+                {
+                    continue;
+                }
+                let (area, centroid) = match submerged_area(shape, pose, water_shape, water_pose) {
+                    Some(submerged) => submerged,
+                    None => continue,
+                };
+                force.add_force_at_point(water.force(area, velocity.linear), centroid, pose);
+                if let Some(angular) = angular_velocities.get(entity) {
+                    force.add_torque(water.drag_torque(area, angular.angular));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, Point2, Rad, Rotation2, Vector2, Zero};
+    use collision::primitive::{Primitive2, Rectangle};
+    use specs::{Entity, RunNow, World};
+
+    use Real;
+    use ecs::physics::prelude2d::*;
+
+    fn floating(world: &mut World, angle: Real, angular: Real) -> Entity {
+        let pose = BodyPose2::new(Point2::new(0., 0.), Basis2::from_angle(Rad(angle)));
+        let mut shape = CollisionShape2::<BodyPose2>::new_simple(
+            CollisionStrategy::FullResolution,
+            CollisionMode::Discrete,
+            Primitive2::from(Rectangle::new(4., 1.)),
+        );
+        shape.update(&pose, None);
+        world
+            .create_entity()
+            .with(pose)
+            .with(Velocity2 {
+                linear: Vector2::zero(),
+            })
+            .with(AngularVelocity2::new(angular))
+            .with(shape)
+            .with(ForceAccumulator2::new())
+            .build()
+    }
+
+    #[test]
+    fn test_buoyancy_rights_tilted_body_and_drags_spin() {
+        let mut world = World::new();
+        world_physics_register(&mut world);
+        // water surface at y = 0
+        let water_pose = BodyPose2::new(Point2::new(0., -5.), Basis2::from_angle(Rad(0.)));
+        let mut water_shape = CollisionShape2::<BodyPose2>::new_simple(
+            CollisionStrategy::CollisionOnly,
+            CollisionMode::Discrete,
+            Primitive2::from(Rectangle::new(100., 10.)),
+        );
+        water_shape.update(&water_pose, None);
+        world
+            .create_entity()
+            .with(WaterArea2::new(1., 0., Vector2::new(0., -10.)).with_angular_drag(2.))
+            .with(water_pose)
+            .with(water_shape)
+            .build();
+        // tilted counter clockwise, the left side dips deeper
+        let tilted = floating(&mut world, 0.2, 0.);
+        let spinning = floating(&mut world, 0., 1.);
+        BuoyancySystem2.run_now(&world.res);
+
+        let forces = world.read::<ForceAccumulator2>();
+        let torque = forces.get(tilted).unwrap().peek_torque();
+        assert!(torque < 0.);
+        assert!(forces.get(tilted).unwrap().peek_force().y > 0.);
+        // the buoyancy of the upright box acts straight below its center, only the drag turns it
+        assert_ulps_eq!(forces.get(spinning).unwrap().peek_torque(), -2. * 2.);
+    }
+}
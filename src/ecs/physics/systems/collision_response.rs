@@ -0,0 +1,67 @@
+use std::fmt::Debug;
+use std::marker;
+
+use cgmath::{EuclideanSpace, VectorSpace};
+use specs::{Fetch, LazyUpdate, System, WriteStorage};
+
+use Real;
+use ecs::physics::resources::{CollisionResponse, ResolvedContacts};
+
+/// Collision response system.
+///
+/// Invokes the [`CollisionResponse`](struct.CollisionResponse.html) callbacks of the bodies of
+/// all contacts in the [`ResolvedContacts`](struct.ResolvedContacts.html) resource. Does nothing
+/// if that resource is not present.
+///
+/// The callbacks are invoked one after the other, on the thread running the system, so should run
+/// after the contact solver, and before `World::maintain` applies the lazy updates.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+pub struct CollisionResponseSystem<P> {
+    m: marker::PhantomData<P>,
+}
+
+impl<P> CollisionResponseSystem<P> {
+    /// Create a new collision response system
+    pub fn new() -> Self {
+        Self {
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<P> Default for CollisionResponseSystem<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, P> System<'a> for CollisionResponseSystem<P>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: VectorSpace<Scalar = Real> + Debug + Send + Sync + 'static,
+{
+    type SystemData = (
+        Option<Fetch<'a, ResolvedContacts<P>>>,
+        Fetch<'a, LazyUpdate>,
+        WriteStorage<'a, CollisionResponse<P>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (resolved, lazy, mut responses) = data;
+        let resolved = match resolved {
+            Some(resolved) => resolved,
+            None => return,
+        };
+        for &(ref contact, impulse) in resolved.iter() {
+            if let Some(response) = responses.get_mut(contact.bodies.0) {
+                response.respond(contact.bodies.0, contact, impulse * -1., &lazy);
+            }
+            if let Some(response) = responses.get_mut(contact.bodies.1) {
+                response.respond(contact.bodies.1, contact, impulse, &lazy);
+            }
+        }
+    }
+}
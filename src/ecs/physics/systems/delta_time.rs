@@ -0,0 +1,61 @@
+use std::time::Instant;
+
+use specs::{FetchMut, System};
+
+use Real;
+use ecs::physics::resources::DeltaTime;
+
+const DEFAULT_MAX_DELTA: Real = 0.1;
+
+/// Delta time system.
+///
+/// Will update the [`DeltaTime`](struct.DeltaTime.html) resource with the real time elapsed since
+/// the last time the system ran, measured using `std::time::Instant`. The delta is clamped to a
+/// maximum step, so a long stall, e.g. when the window is dragged or a debugger is paused, does not
+/// feed a huge time step into the integrator. The first run gives a delta of zero. Should run
+/// before all other physics systems.
+pub struct DeltaTimeSystem {
+    last: Option<Instant>,
+    max_delta: Real,
+}
+
+impl DeltaTimeSystem {
+    /// Create a new delta time system, clamping the delta to 0.1 seconds
+    pub fn new() -> Self {
+        Self {
+            last: None,
+            max_delta: DEFAULT_MAX_DELTA,
+        }
+    }
+
+    /// Set the maximum delta time in seconds, builder pattern
+    pub fn with_max_delta(mut self, max_delta: Real) -> Self {
+        self.max_delta = max_delta;
+        self
+    }
+
+    /// Restart the clock, the next run will give a delta of zero, e.g. when resuming after a pause
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+impl Default for DeltaTimeSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> System<'a> for DeltaTimeSystem {
+    type SystemData = FetchMut<'a, DeltaTime>;
+
+    fn run(&mut self, mut delta_time: Self::SystemData) {
+        let now = Instant::now();
+        let elapsed = match self.last {
+            Some(last) => now.duration_since(last).as_secs_f64() as Real,
+            None => 0.,
+        };
+        self.last = Some(now);
+        delta_time.delta_seconds = elapsed.min(self.max_delta);
+    }
+}
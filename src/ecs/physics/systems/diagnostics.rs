@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::marker;
+
+use cgmath::{EuclideanSpace, Rotation, Zero};
+use specs::{Entities, Fetch, FetchMut, Join, ReadStorage, System};
+
+use {BodyPose, Real};
+use ecs::collide::WorldId;
+use ecs::physics::resources::{ContactIslands, EnergyDiagnostics, EnergyTotals};
+use physics::{AngularRotation, AngularVelocity, Inertia, LinearVector, Mass, RotationalVector,
+              Velocity};
+
+/// Energy diagnostics system.
+///
+/// Will compute the total kinetic energy, and linear and angular momentum of all bodies with a
+/// finite mass, and store them in the [`EnergyDiagnostics`](struct.EnergyDiagnostics.html)
+/// resource. Bodies with an [`AngularVelocity`](../../physics/struct.AngularVelocity.html) and an
+/// [`Inertia`](../../physics/struct.Inertia.html) add their rotational energy and spin. Should run
+/// after the contact solver, so the islands of the
+/// [`ContactIslands`](struct.ContactIslands.html) resource are those of the same step.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct EnergyDiagnosticsSystem<P, R> {
+    m: marker::PhantomData<(P, R)>,
+}
+
+impl<P, R> EnergyDiagnosticsSystem<P, R> {
+    /// Create a new energy diagnostics system
+    pub fn new() -> Self {
+        Self {
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<P, R> Default for EnergyDiagnosticsSystem<P, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, P, R> System<'a> for EnergyDiagnosticsSystem<P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: RotationalVector + Send + Sync + 'static,
+    R: Rotation<P> + AngularRotation<P::Diff> + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        FetchMut<'a, EnergyDiagnostics<P::Diff>>,
+        Option<Fetch<'a, ContactIslands>>,
+        ReadStorage<'a, Mass>,
+        ReadStorage<'a, Velocity<P::Diff>>,
+        ReadStorage<'a, AngularVelocity<P::Diff>>,
+        ReadStorage<'a, Inertia<P::Diff>>,
+        ReadStorage<'a, BodyPose<P, R>>,
+        ReadStorage<'a, WorldId>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut diagnostics,
+            islands,
+            masses,
+            velocities,
+            angular_velocities,
+            inertias,
+            poses,
+            world_ids,
+        ) = data;
+        let islands = match islands {
+            Some(ref islands) if diagnostics.is_per_island() => &islands[..],
+            _ => &[],
+        };
+        let body_islands = islands
+            .iter()
+            .enumerate()
+            .flat_map(|(index, island)| island.bodies.iter().map(move |&body| (body, index)))
+            .collect::<HashMap<_, _>>();
+        diagnostics.begin_frame(islands.len());
+        for (entity, mass, velocity) in (&*entities, &masses, &velocities).join() {
+            if !mass.mass().is_finite() {
+                continue;
+            }
+            let position = poses
+                .get(entity)
+                .map(|pose| pose.position().to_vec())
+                .unwrap_or_else(P::Diff::zero);
+            let (angular, inverse_inertia) = match (
+                angular_velocities.get(entity),
+                inertias.get(entity),
+                poses.get(entity),
+            ) {
+                (Some(angular), Some(inertia), Some(pose)) => (
+                    angular.angular,
+                    pose.rotation()
+                        .world_inverse_inertia(&inertia.inverse_inertia()),
+                ),
+                _ => (
+                    <P::Diff as LinearVector>::Angular::zero(),
+                    <P::Diff as RotationalVector>::InverseInertia::zero(),
+                ),
+            };
+            let body = EnergyTotals::body(
+                mass.mass(),
+                position,
+                velocity.linear,
+                angular,
+                &inverse_inertia,
+            );
+            diagnostics.add_body(
+                world_ids.get(entity).cloned().unwrap_or_default(),
+                body_islands.get(&entity).cloned(),
+                &body,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, One, Point2, Vector2};
+    use specs::{Entity, RunNow, World};
+
+    use Real;
+    use ecs::physics::prelude2d::*;
+    use ecs::physics::ContactIslands;
+    use physics::Island;
+
+    fn body(world: &mut World, x: Real, linear: Vector2<Real>, angular: Real) -> Entity {
+        world
+            .create_entity()
+            .with(BodyPose2::new(Point2::new(x, 0.), Basis2::one()))
+            .with(Velocity2 { linear })
+            .with(AngularVelocity2::new(angular))
+            .with(Inertia2::new(0.5))
+            .with(Mass::new(2.))
+            .build()
+    }
+
+    #[test]
+    fn test_rotational_terms_and_islands() {
+        let mut world = World::new();
+        world_physics_register(&mut world);
+        world.add_resource(EnergyDiagnostics2::new().with_per_island());
+        let spinning = body(&mut world, 1., Vector2::new(0., 3.), 2.);
+        body(&mut world, -1., Vector2::new(0., 3.), 0.);
+        world.add_resource(ContactIslands {
+            islands: vec![
+                Island {
+                    bodies: vec![spinning],
+                    contacts: Vec::default(),
+                },
+            ],
+        });
+
+        EnergyDiagnosticsSystem2::new().run_now(&world.res);
+
+        let diagnostics = world.read_resource::<EnergyDiagnostics2>();
+        let totals = diagnostics.current();
+        assert_ulps_eq!(totals.kinetic_energy, 19.);
+        assert_ulps_eq!(totals.rotational_energy, 1.);
+        assert_ulps_eq!(totals.linear_momentum, Vector2::new(0., 12.));
+        // the orbits around the origin cancel out, only the spin is left
+        assert_ulps_eq!(totals.angular_momentum, 1.);
+        let island = diagnostics.island(0).unwrap();
+        assert_eq!(1, island.bodies);
+        assert_ulps_eq!(island.kinetic_energy, 10.);
+        assert_ulps_eq!(island.angular_momentum, 7.);
+        assert!(diagnostics.island(1).is_none());
+    }
+}
@@ -0,0 +1,230 @@
+use std::marker;
+
+use cgmath::{EuclideanSpace, InnerSpace, Rotation};
+use specs::{Entity, Fetch, Join, ReadStorage, System, WriteStorage};
+
+use {BodyPose, NextFrame, Real};
+use ecs::collide::{steps_pair, Dormant, Frozen, SteppedWorld, WorldId};
+use ecs::physics::resources::DeltaTime;
+use physics::{solve_distance_joint, BodyType, DistanceJoint, JointBodyData, Mass, Velocity};
+use super::linear_impulse::{is_frozen, is_kinematic};
+
+/// Default number of solver iterations over all joints
+const DEFAULT_ITERATIONS: usize = 10;
+
+/// Distance joint system.
+///
+/// Will resolve all [`DistanceJoint`](../../physics/struct.DistanceJoint.html) components. Joints
+/// are placed on their own entities, and are solved together at the velocity level over a number
+/// of iterations, so closed loops of joints are supported. Operates on the next frame velocities,
+/// so should run before the contact solver.
+///
+/// Bodies without a `Mass` or a `NextFrame<Velocity>` are not moved by the joints, and act as
+/// fixed pivots. The same goes for [`Frozen`](../collide/struct.Frozen.html),
+/// [`Dormant`](../collide/struct.Dormant.html) and static bodies. Kinematic bodies are not moved by
+/// the joints either, but act as pivots moving with their velocity, see
+/// [`BodyType`](../../physics/enum.BodyType.html).
+///
+/// Joints between bodies in different simulation worlds are ignored, and so are joints outside
+/// the [`SteppedWorld`](../collide/struct.SteppedWorld.html), if present.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct DistanceJointSystem<P, R> {
+    iterations: usize,
+    m: marker::PhantomData<(P, R)>,
+}
+
+impl<P, R> DistanceJointSystem<P, R> {
+    /// Create a new distance joint system
+    pub fn new() -> Self {
+        Self {
+            iterations: DEFAULT_ITERATIONS,
+            m: marker::PhantomData,
+        }
+    }
+
+    /// Set the number of solver iterations, builder pattern
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+}
+
+impl<P, R> Default for DistanceJointSystem<P, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, P, R> System<'a> for DistanceJointSystem<P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: InnerSpace + Send + Sync + 'static,
+    R: Rotation<P> + Send + Sync + 'static,
+{
+    type SystemData = (
+        Fetch<'a, DeltaTime>,
+        ReadStorage<'a, Mass>,
+        ReadStorage<'a, Frozen>,
+        ReadStorage<'a, Dormant>,
+        ReadStorage<'a, BodyType>,
+        ReadStorage<'a, BodyPose<P, R>>,
+        ReadStorage<'a, NextFrame<BodyPose<P, R>>>,
+        WriteStorage<'a, NextFrame<Velocity<P::Diff>>>,
+        WriteStorage<'a, DistanceJoint<Entity>>,
+        ReadStorage<'a, WorldId>,
+        Option<Fetch<'a, SteppedWorld>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            time,
+            masses,
+            frozen,
+            dormant,
+            body_types,
+            poses,
+            next_poses,
+            mut next_velocities,
+            mut joints,
+            world_ids,
+            stepped,
+        ) = data;
+        let stepped = stepped.as_deref();
+        let fixed = |entity| is_frozen(&frozen, &dormant, &body_types, entity);
+
+        for joint in (&mut joints).join() {
+            if steps_pair(stepped, &world_ids, joint.bodies.0, joint.bodies.1) {
+                joint.reset_impulse();
+            }
+        }
+
+        for _ in 0..self.iterations {
+            for joint in (&mut joints).join() {
+                let (a, b) = joint.bodies;
+                if !steps_pair(stepped, &world_ids, a, b) {
+                    continue;
+                }
+                let body_data = |entity: Entity| -> Option<JointBodyData<P>> {
+                    let position = match next_poses.get(entity) {
+                        Some(pose) => *pose.value.position(),
+                        None => *poses.get(entity)?.position(),
+                    };
+                    if fixed(entity) {
+                        return Some(JointBodyData::fixed(position));
+                    }
+                    Some(match (next_velocities.get(entity), masses.get(entity)) {
+                        (Some(velocity), _) if is_kinematic(&body_types, entity) => {
+                            JointBodyData {
+                                velocity: velocity.value.linear,
+                                ..JointBodyData::fixed(position)
+                            }
+                        }
+                        (Some(velocity), Some(mass)) => JointBodyData {
+                            position,
+                            velocity: velocity.value.linear,
+                            inverse_mass: mass.inverse_mass(),
+                        },
+                        _ => JointBodyData::fixed(position),
+                    })
+                };
+                let (a_data, b_data) = match (body_data(a), body_data(b)) {
+                    (Some(a_data), Some(b_data)) => (a_data, b_data),
+                    _ => continue,
+                };
+
+                let inverse_masses = (a_data.inverse_mass, b_data.inverse_mass);
+                let (a_velocity, b_velocity) =
+                    solve_distance_joint(joint, a_data, b_data, time.delta_seconds);
+
+                let bodies = [
+                    (a, inverse_masses.0, a_velocity),
+                    (b, inverse_masses.1, b_velocity),
+                ];
+                for &(entity, inverse_mass, velocity) in &bodies {
+                    if inverse_mass == 0. {
+                        continue;
+                    }
+                    if let Some(next_velocity) = next_velocities.get_mut(entity) {
+                        next_velocity.value.linear = velocity;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, One, Point2, Vector2};
+    use specs::{Entity, RunNow, World};
+
+    use {NextFrame, Real};
+    use ecs::physics::prelude2d::*;
+
+    fn body(world: &mut World, x: Real, velocity: Vector2<Real>, body_type: BodyType) -> Entity {
+        let pose = BodyPose2::new(Point2::new(x, 0.), Basis2::one());
+        world
+            .create_entity()
+            .with(pose.clone())
+            .with(NextFrame { value: pose })
+            .with(NextFrame {
+                value: Velocity2 { linear: velocity },
+            })
+            .with(Mass::new(1.))
+            .with(body_type)
+            .build()
+    }
+
+    fn velocity(world: &World, entity: Entity) -> Vector2<Real> {
+        world
+            .read::<NextFrame<Velocity2>>()
+            .get(entity)
+            .unwrap()
+            .value
+            .linear
+    }
+
+    #[test]
+    fn test_static_and_kinematic_bodies_are_immovable() {
+        let mut world = World::new();
+        world_physics_register(&mut world);
+        world.write_resource::<DeltaTime>().delta_seconds = 1. / 60.;
+        let moving = Vector2::new(-1., 0.);
+        let anchors = [
+            (BodyType::Static, Vector2::new(0., 0.)),
+            (BodyType::Kinematic, moving),
+            (BodyType::Dynamic, moving),
+        ];
+        let mut pairs = Vec::new();
+        for (i, &(body_type, anchor_velocity)) in anchors.iter().enumerate() {
+            let x = 10. * i as Real;
+            let anchor = body(&mut world, x, anchor_velocity, body_type);
+            let pendulum = body(&mut world, x + 1., Vector2::new(1., 0.), BodyType::Dynamic);
+            world
+                .create_entity()
+                .with(DistanceJoint::new(anchor, pendulum, 1.))
+                .build();
+            pairs.push((anchor, pendulum));
+        }
+
+        DistanceJointSystem2::new().run_now(&world.res);
+
+        let (anchor, pendulum) = pairs[0];
+        assert_ulps_eq!(velocity(&world, anchor), Vector2::new(0., 0.));
+        assert!(velocity(&world, pendulum).x.abs() < 0.05);
+
+        // the dynamic body follows the kinematic anchor, without pushing it back
+        let (anchor, pendulum) = pairs[1];
+        assert_ulps_eq!(velocity(&world, anchor), moving);
+        assert!((velocity(&world, pendulum).x - moving.x).abs() < 0.05);
+
+        // two dynamic bodies share the correction, and meet in the middle
+        let (anchor, pendulum) = pairs[2];
+        assert!(velocity(&world, anchor).x.abs() < 0.05);
+        assert!(velocity(&world, pendulum).x.abs() < 0.05);
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt::Debug;
+use std::marker;
+
+use cgmath::{EuclideanSpace, Rotation};
+use collision::{Aabb, Contains};
+use specs::{Entities, Fetch, Join, ReadStorage, System, WriteStorage};
+
+use {BodyPose, Real};
+use collide::{CollisionShape, ContainsPoint};
+use ecs::collide::{same_world, PerWorld, WorldId};
+use physics::{ForceAccumulator, Gravity, GravityScale, GravityVolume, LinearVector, Mass};
+
+/// Gravity system.
+///
+/// Will add gravitational forces to the force accumulators of all bodies with a finite mass.
+/// The global [`Gravity`](../../physics/struct.Gravity.html) is used, unless the position of the
+/// body is inside the collision shape of a
+/// [`GravityVolume`](../../physics/struct.GravityVolume.html). If a
+/// [`PerWorld`](../collide/struct.PerWorld.html) gravity resource is present, and has a value for
+/// the simulation world of the body, that is used instead of the global gravity. The gravity of
+/// bodies with a [`GravityScale`](../../physics/struct.GravityScale.html) is scaled accordingly.
+///
+/// Should run before the contact solver.
+///
+/// ### Type parameters:
+///
+/// - `P`: Collision primitive type, used for the shapes of gravity volumes
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct GravitySystem<P, R> {
+    m: marker::PhantomData<(P, R)>,
+}
+
+impl<P, R> GravitySystem<P, R> {
+    /// Create a new gravity system
+    pub fn new() -> Self {
+        Self {
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<P, R> Default for GravitySystem<P, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, P, R> System<'a> for GravitySystem<P, R>
+where
+    P: ContainsPoint + Send + Sync + 'static,
+    P::Aabb: Aabb<Scalar = Real> + Contains<P::Point> + Send + Sync + 'static,
+    P::Point: Send + Sync + 'static,
+    <P::Point as EuclideanSpace>::Diff: LinearVector + Debug + Send + Sync + 'static,
+    R: Rotation<P::Point> + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        Fetch<'a, Gravity<<P::Point as EuclideanSpace>::Diff>>,
+        ReadStorage<'a, Mass>,
+        ReadStorage<'a, BodyPose<P::Point, R>>,
+        ReadStorage<'a, GravityVolume<P::Point, R>>,
+        ReadStorage<'a, GravityScale>,
+        ReadStorage<'a, CollisionShape<P, BodyPose<P::Point, R>>>,
+        WriteStorage<'a, ForceAccumulator<<P::Point as EuclideanSpace>::Diff>>,
+        ReadStorage<'a, WorldId>,
+        Option<Fetch<'a, PerWorld<Gravity<<P::Point as EuclideanSpace>::Diff>>>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            gravity,
+            masses,
+            poses,
+            volumes,
+            scales,
+            shapes,
+            mut forces,
+            world_ids,
+            world_gravity,
+        ) = data;
+
+        for (entity, mass, pose) in (&*entities, &masses, &poses).join() {
+            if !mass.mass().is_finite() {
+                continue;
+            }
+            let world = world_ids.get(entity).cloned().unwrap_or_default();
+            let global = world_gravity
+                .as_ref()
+                .and_then(|g| g.get(world))
+                .unwrap_or(&*gravity)
+                .acceleration;
+            let acceleration = (&*entities, &volumes, &shapes, &poses)
+                .join()
+                .filter(|&(volume_entity, _, shape, volume_pose)| {
+                    same_world(&world_ids, entity, volume_entity)
+                        && shape.contains_point(volume_pose, pose.position())
+                })
+                .max_by_key(|&(_, volume, _, _)| volume.priority)
+                .map(|(_, volume, _, _)| volume.apply(global))
+                .unwrap_or(global);
+            let scale = scales.get(entity).cloned().unwrap_or_default().0;
+            if let Some(force) = forces.get_mut(entity) {
+                force.add_force(acceleration * (mass.mass() * scale));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_4;
+
+    use cgmath::{Basis2, One, Point2, Rad, Rotation2, Vector2};
+    use specs::{Entity, RunNow, World};
+
+    use Real;
+    use ecs::physics::prelude2d::*;
+
+    fn body(world: &mut World, x: Real, y: Real) -> Entity {
+        world
+            .create_entity()
+            .with(BodyPose2::new(Point2::new(x, y), Basis2::one()))
+            .with(Mass::new(1.))
+            .with(ForceAccumulator2::new())
+            .build()
+    }
+
+    #[test]
+    fn test_volume_uses_collision_shape() {
+        let mut world = World::new();
+        world_physics_register(&mut world);
+        *world.write_resource::<Gravity2>() = Gravity2::new(Vector2::new(0., -10.));
+        let pose = BodyPose2::new(Point2::new(0., 0.), Basis2::from_angle(Rad(FRAC_PI_4 as Real)));
+        let mut shape = CollisionShape2::<BodyPose2>::new_simple(
+            CollisionStrategy::CollisionOnly,
+            CollisionMode::Discrete,
+            Rectangle::new(2., 2.).into(),
+        );
+        shape.update(&pose, None);
+        world
+            .create_entity()
+            .with(shape)
+            .with(pose)
+            .with(GravityVolume2::new(GravityOverride2::Replace(Vector2::new(0., 10.))))
+            .build();
+        let inside = body(&mut world, 1.2, 0.);
+        // inside the bounding box of the rotated square, but outside the square
+        let corner = body(&mut world, 1., 1.);
+
+        GravitySystem2::new().run_now(&world.res);
+
+        let forces = world.read::<ForceAccumulator2>();
+        let force = |entity| forces.get(entity).unwrap().peek_force();
+        assert_eq!(Vector2::new(0., 10.), force(inside));
+        assert_eq!(Vector2::new(0., -10.), force(corner));
+    }
+}
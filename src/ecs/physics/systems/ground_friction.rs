@@ -0,0 +1,143 @@
+use cgmath::{Basis2, Point2, Vector2};
+use collision::Aabb2;
+use collision::dbvt::DiscreteVisitor;
+use collision::primitive::Primitive2;
+use specs::{Entities, Entity, Fetch, Join, ReadStorage, System, WriteStorage};
+
+use {BodyPose, Real};
+use collide::CollisionShape;
+use collide::util::ContainerShapeWrapper;
+use ecs::collide::{same_world, GetEntity, WorldId};
+use ecs::collide::prelude2d::DynamicBoundingVolumeTree2;
+use ecs::physics::resources::DeltaTime;
+use physics::{ground_friction_force, ForceAccumulator, FrictionZone, GroundFriction, Mass,
+              Velocity};
+
+/// Ground friction system for top-down 2D worlds.
+///
+/// Will add ground friction forces to the force accumulator of all bodies with a
+/// [`GroundFriction`](../../physics/struct.GroundFriction.html) component. The friction
+/// coefficient is modified by any [`FrictionZone`](../../physics/struct.FrictionZone.html) whose
+/// collision shape contains the position of the body.
+///
+/// Zones are found by querying the spatial tree, so this requires the spatial sorting system to be
+/// running. Should run before the contact solver.
+pub struct GroundFrictionSystem2;
+
+impl<'a> System<'a> for GroundFrictionSystem2 {
+    type SystemData = (
+        Entities<'a>,
+        Fetch<'a, DeltaTime>,
+        ReadStorage<'a, Mass>,
+        ReadStorage<'a, Velocity<Vector2<Real>>>,
+        ReadStorage<'a, BodyPose<Point2<Real>, Basis2<Real>>>,
+        ReadStorage<'a, GroundFriction>,
+        ReadStorage<'a, FrictionZone>,
+        ReadStorage<'a, CollisionShape<Primitive2<Real>, BodyPose<Point2<Real>, Basis2<Real>>>>,
+        WriteStorage<'a, ForceAccumulator<Vector2<Real>>>,
+        ReadStorage<'a, WorldId>,
+        Fetch<'a, DynamicBoundingVolumeTree2>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            time,
+            masses,
+            velocities,
+            poses,
+            frictions,
+            zones,
+            shapes,
+            mut forces,
+            world_ids,
+            tree,
+        ) = data;
+
+        for (entity, friction, velocity, pose, mass) in
+            (&*entities, &frictions, &velocities, &poses, &masses).join()
+        {
+            let position = *pose.position();
+            let bound = Aabb2::new(position, position);
+            let mut visitor = DiscreteVisitor::<
+                Aabb2<Real>,
+                ContainerShapeWrapper<Entity, Primitive2<Real>>,
+            >::new(&bound);
+            let multiplier = tree.query(&mut visitor)
+                .into_iter()
+                .map(|(value, _)| value.entity())
+                .filter(|&zone_entity| same_world(&world_ids, entity, zone_entity))
+                .filter_map(|zone_entity| {
+                    let zone = zones.get(zone_entity)?;
+                    let zone_pose = poses.get(zone_entity)?;
+                    match shapes.get(zone_entity) {
+                        Some(shape) if shape.contains_point(zone_pose, &position) => Some(zone),
+                        _ => None,
+                    }
+                })
+                .max_by_key(|zone| zone.priority)
+                .map(|zone| zone.multiplier)
+                .unwrap_or(1.);
+            if let Some(force) = forces.get_mut(entity) {
+                force.add_force(ground_friction_force(
+                    velocity.linear,
+                    mass,
+                    friction.coefficient * multiplier,
+                    time.delta_seconds,
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, One, Point2, Vector2};
+    use specs::{Entity, RunNow, World};
+
+    use Real;
+    use ecs::physics::prelude2d::*;
+
+    fn body(world: &mut World, x: Real, y: Real) -> Entity {
+        world
+            .create_entity()
+            .with(BodyPose2::new(Point2::new(x, y), Basis2::one()))
+            .with(Velocity2 {
+                linear: Vector2::new(1., 0.),
+            })
+            .with(Mass::new(1.))
+            .with(GroundFriction::new(0.5))
+            .with(ForceAccumulator2::new())
+            .build()
+    }
+
+    #[test]
+    fn test_zone_uses_collision_shape() {
+        let mut world = World::new();
+        world_physics_register_with_spatial(&mut world);
+        world.write_resource::<DeltaTime>().delta_seconds = 0.1;
+        world
+            .create_entity()
+            .with(CollisionShape2::<BodyPose2>::new_simple(
+                CollisionStrategy::CollisionOnly,
+                CollisionMode::Discrete,
+                Circle::new(2.).into(),
+            ))
+            .with(BodyPose2::new(Point2::new(0., 0.), Basis2::one()))
+            .with(FrictionZone::new(0.))
+            .build();
+        // inside the bounding box of the circle, but outside the circle
+        let corner = body(&mut world, 1.8, 1.8);
+        let inside = body(&mut world, 0.5, 0.5);
+        let outside = body(&mut world, 5., 0.);
+
+        SpatialSortingSystem2::<BodyPose2>::new().run_now(&world.res);
+        GroundFrictionSystem2.run_now(&world.res);
+
+        let forces = world.read::<ForceAccumulator2>();
+        let force = |entity| forces.get(entity).unwrap().peek_force();
+        assert_eq!(Vector2::new(0., 0.), force(inside));
+        assert_eq!(force(outside), force(corner));
+        assert!(force(corner).x < 0.);
+    }
+}
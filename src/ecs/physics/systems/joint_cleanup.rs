@@ -0,0 +1,54 @@
+use std::marker;
+
+use specs::{Component, Entities, Fetch, Join, LazyUpdate, ReadStorage, System};
+
+use super::ConnectedJoint;
+
+/// Joint cleanup system.
+///
+/// Removes joints of type `J` when any of the bodies they connect has been deleted, so the
+/// solvers never see joints to dead bodies, and the joint entities can be reused. The joint
+/// component is removed through `LazyUpdate`, the joint entity itself, and any other components
+/// on it, are left alone.
+///
+/// The removal is applied when `World::maintain` runs, so the system only reads the joints, and
+/// can run in parallel with the joint solvers.
+///
+/// ### Type parameters:
+///
+/// - `J`: Joint component type
+pub struct JointCleanupSystem<J> {
+    m: marker::PhantomData<J>,
+}
+
+impl<J> JointCleanupSystem<J> {
+    /// Create a new joint cleanup system
+    pub fn new() -> Self {
+        Self {
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<J> Default for JointCleanupSystem<J> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, J> System<'a> for JointCleanupSystem<J>
+where
+    J: Component + ConnectedJoint + Send + Sync + 'static,
+{
+    type SystemData = (Entities<'a>, ReadStorage<'a, J>, Fetch<'a, LazyUpdate>);
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, joints, lazy) = data;
+        for (entity, joint) in (&*entities, &joints).join() {
+            let (left, right) = joint.connected_bodies(entity);
+            if !entities.is_alive(left) || !entities.is_alive(right) {
+                lazy.remove::<J>(entity);
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::marker;
+
+use cgmath::EuclideanSpace;
+use specs::{Component, Entities, Entity, FetchMut, Join, ReadStorage, System};
+
+use ecs::collide::CollisionExclusions;
+use physics::{DistanceConstraint, DistanceJoint, Joint, JointCollision, LinearVector, WheelJoint2};
+
+/// Joint connecting two bodies, used for filtering collisions between the connected bodies, see
+/// [`JointCollisionSystem`](struct.JointCollisionSystem.html).
+pub trait ConnectedJoint {
+    /// Get the connected bodies, given the entity the joint component is placed on
+    fn connected_bodies(&self, entity: Entity) -> (Entity, Entity);
+
+    /// Get the collision setting of the joint
+    fn collision(&self) -> JointCollision;
+}
+
+impl ConnectedJoint for DistanceJoint<Entity> {
+    fn connected_bodies(&self, _: Entity) -> (Entity, Entity) {
+        self.bodies
+    }
+
+    fn collision(&self) -> JointCollision {
+        self.collision
+    }
+}
+
+impl ConnectedJoint for DistanceConstraint<Entity> {
+    fn connected_bodies(&self, _: Entity) -> (Entity, Entity) {
+        self.bodies
+    }
+
+    fn collision(&self) -> JointCollision {
+        self.collision
+    }
+}
+
+impl<P, R> ConnectedJoint for Joint<Entity, P, R>
+where
+    P: EuclideanSpace,
+    P::Diff: LinearVector,
+{
+    fn connected_bodies(&self, _: Entity) -> (Entity, Entity) {
+        self.bodies
+    }
+
+    fn collision(&self) -> JointCollision {
+        self.collision
+    }
+}
+
+impl ConnectedJoint for WheelJoint2<Entity> {
+    fn connected_bodies(&self, entity: Entity) -> (Entity, Entity) {
+        (entity, self.chassis)
+    }
+
+    fn collision(&self) -> JointCollision {
+        self.collision
+    }
+}
+
+/// Joint collision filtering system.
+///
+/// Excludes collision between the bodies connected by joints of type `J`, according to the
+/// [`JointCollision`](../../physics/enum.JointCollision.html) setting of each joint, by updating
+/// the [`CollisionExclusions`](../collide/struct.CollisionExclusions.html) resource. The exclusions
+/// added in the previous run are removed first, so changed and removed joints are picked up, and
+/// exclusions added by others are kept.
+///
+/// For the `DisableAssembly` setting, the assembly is formed by all bodies that are connected
+/// through joints of type `J` with that setting.
+///
+/// Should run before collision detection.
+///
+/// ### Type parameters:
+///
+/// - `J`: Joint component type
+pub struct JointCollisionSystem<J> {
+    excluded: Vec<(Entity, Entity)>,
+    m: marker::PhantomData<J>,
+}
+
+impl<J> JointCollisionSystem<J> {
+    /// Create a new joint collision filtering system
+    pub fn new() -> Self {
+        Self {
+            excluded: Vec::default(),
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<J> Default for JointCollisionSystem<J> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Find the root of the assembly of a body, with path halving
+fn root(parents: &mut HashMap<Entity, Entity>, mut entity: Entity) -> Entity {
+    while let Some(parent) = parents.get(&entity).cloned() {
+        if parent == entity {
+            break;
+        }
+        let grandparent = parents.get(&parent).cloned().unwrap_or(parent);
+        parents.insert(entity, grandparent);
+        entity = grandparent;
+    }
+    entity
+}
+
+impl<'a, J> System<'a> for JointCollisionSystem<J>
+where
+    J: Component + ConnectedJoint + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, J>,
+        FetchMut<'a, CollisionExclusions>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, joints, mut exclusions) = data;
+        for (left, right) in self.excluded.drain(..) {
+            exclusions.remove(left, right);
+        }
+
+        let mut parents = HashMap::new();
+        for (entity, joint) in (&*entities, &joints).join() {
+            let (left, right) = joint.connected_bodies(entity);
+            match joint.collision() {
+                JointCollision::Enabled => (),
+                JointCollision::DisableConnected => self.excluded.push((left, right)),
+                JointCollision::DisableAssembly => {
+                    parents.entry(left).or_insert(left);
+                    parents.entry(right).or_insert(right);
+                    let left = root(&mut parents, left);
+                    let right = root(&mut parents, right);
+                    parents.insert(left, right);
+                }
+            }
+        }
+
+        // all pairs of bodies within each assembly
+        let bodies = parents.keys().cloned().collect::<Vec<_>>();
+        let mut assemblies: HashMap<Entity, Vec<Entity>> = HashMap::new();
+        for body in bodies {
+            let assembly = root(&mut parents, body);
+            assemblies.entry(assembly).or_default().push(body);
+        }
+        for bodies in assemblies.values() {
+            for (i, &left) in bodies.iter().enumerate() {
+                for &right in &bodies[i + 1..] {
+                    self.excluded.push((left, right));
+                }
+            }
+        }
+
+        for &(left, right) in &self.excluded {
+            exclusions.insert(left, right);
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use std::marker;
+
+use cgmath::{EuclideanSpace, Rotation, Zero};
+use specs::{Entity, Fetch, Join, ReadStorage, System, WriteStorage};
+
+use {BodyPose, NextFrame, Real};
+use ecs::collide::{steps_pair, Dormant, Frozen, SteppedWorld, WorldId};
+use ecs::physics::resources::DeltaTime;
+use physics::{project_joint_rotation, solve_joint, AngularRotation, AngularVelocity, BodyType,
+              Inertia, Joint, Mass, RotationalResolveData, RotationalVector, Velocity};
+use super::linear_impulse::{is_frozen, is_kinematic};
+
+/// Default number of solver iterations over all joints
+const DEFAULT_ITERATIONS: usize = 10;
+
+/// Joint solver system.
+///
+/// Will resolve all [`Joint`](../../physics/struct.Joint.html) components. Like the
+/// [`DistanceJointSystem`](struct.DistanceJointSystem.html), joints are placed on their own
+/// entities, and the joints are solved together at the velocity level over a number of
+/// iterations. Operates on the next frame velocities, so should run before the contact solver.
+///
+/// The joint impulses turn bodies with an
+/// [`AngularVelocity`](../../physics/struct.AngularVelocity.html) and an
+/// [`Inertia`](../../physics/struct.Inertia.html), through the anchors and the rotational part of
+/// the joints. For joints where neither body has both, the rotational part is instead enforced once
+/// per frame on the next frame poses, before the iterations, see
+/// [`project_joint_rotation`](../../physics/fn.project_joint_rotation.html).
+///
+/// Bodies without a `Mass`, a `NextFrame<Velocity>` or a `NextFrame<BodyPose>` are not moved by
+/// the joints, and act as fixed anchors. The same goes for
+/// [`Frozen`](../collide/struct.Frozen.html), [`Dormant`](../collide/struct.Dormant.html) and
+/// static bodies. Kinematic bodies are not moved by the joints either, but act as anchors moving
+/// with their velocity, see
+/// [`BodyType`](../../physics/enum.BodyType.html).
+///
+/// Joints between bodies in different simulation worlds are ignored, and so are joints outside
+/// the [`SteppedWorld`](../collide/struct.SteppedWorld.html), if present.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct JointSolverSystem<P, R> {
+    iterations: usize,
+    m: marker::PhantomData<(P, R)>,
+}
+
+impl<P, R> JointSolverSystem<P, R> {
+    /// Create a new joint solver system
+    pub fn new() -> Self {
+        Self {
+            iterations: DEFAULT_ITERATIONS,
+            m: marker::PhantomData,
+        }
+    }
+
+    /// Set the number of solver iterations, builder pattern
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+}
+
+impl<P, R> Default for JointSolverSystem<P, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, P, R> System<'a> for JointSolverSystem<P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: RotationalVector + Send + Sync + 'static,
+    R: Rotation<P> + AngularRotation<P::Diff> + Send + Sync + 'static,
+{
+    type SystemData = (
+        Fetch<'a, DeltaTime>,
+        ReadStorage<'a, Mass>,
+        ReadStorage<'a, Inertia<P::Diff>>,
+        ReadStorage<'a, Frozen>,
+        ReadStorage<'a, Dormant>,
+        ReadStorage<'a, BodyType>,
+        ReadStorage<'a, BodyPose<P, R>>,
+        WriteStorage<'a, NextFrame<BodyPose<P, R>>>,
+        WriteStorage<'a, NextFrame<Velocity<P::Diff>>>,
+        WriteStorage<'a, AngularVelocity<P::Diff>>,
+        WriteStorage<'a, Joint<Entity, P, R>>,
+        ReadStorage<'a, WorldId>,
+        Option<Fetch<'a, SteppedWorld>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            time,
+            masses,
+            inertias,
+            frozen,
+            dormant,
+            body_types,
+            poses,
+            mut next_poses,
+            mut next_velocities,
+            mut angular_velocities,
+            mut joints,
+            world_ids,
+            stepped,
+        ) = data;
+        let stepped = stepped.as_deref();
+
+        let fixed = |entity| is_frozen(&frozen, &dormant, &body_types, entity);
+        let immovable = |entity| fixed(entity) || is_kinematic(&body_types, entity);
+
+        let pose = |next_poses: &WriteStorage<NextFrame<BodyPose<P, R>>>, entity: Entity| {
+            match next_poses.get(entity) {
+                Some(pose) => Some(pose.value.clone()),
+                None => poses.get(entity).cloned(),
+            }
+        };
+        let inverse_mass = |next_poses: &WriteStorage<NextFrame<BodyPose<P, R>>>,
+                            next_velocities: &WriteStorage<NextFrame<Velocity<P::Diff>>>,
+                            entity: Entity| {
+            match (
+                next_poses.get(entity),
+                next_velocities.get(entity),
+                masses.get(entity),
+            ) {
+                (Some(_), Some(_), Some(mass)) if !immovable(entity) => mass.inverse_mass(),
+                _ => 0.,
+            }
+        };
+        // bodies that are turned through their angular velocity
+        let rotates = |next_poses: &WriteStorage<NextFrame<BodyPose<P, R>>>,
+                       next_velocities: &WriteStorage<NextFrame<Velocity<P::Diff>>>,
+                       angular_velocities: &WriteStorage<AngularVelocity<P::Diff>>,
+                       entity: Entity| {
+            inverse_mass(next_poses, next_velocities, entity) > 0.
+                && angular_velocities.get(entity).is_some()
+                && inertias.get(entity).is_some()
+        };
+
+        for joint in (&mut joints).join() {
+            let (a, b) = joint.bodies;
+            if !steps_pair(stepped, &world_ids, a, b) {
+                continue;
+            }
+            joint.reset_impulse();
+            if rotates(&next_poses, &next_velocities, &angular_velocities, a)
+                || rotates(&next_poses, &next_velocities, &angular_velocities, b)
+            {
+                continue;
+            }
+
+            let (a_pose, b_pose) = match (pose(&next_poses, a), pose(&next_poses, b)) {
+                (Some(a_pose), Some(b_pose)) => (a_pose, b_pose),
+                _ => continue,
+            };
+            let (a_rotation, b_rotation) = project_joint_rotation(
+                joint,
+                (*a_pose.rotation(), inverse_mass(&next_poses, &next_velocities, a)),
+                (*b_pose.rotation(), inverse_mass(&next_poses, &next_velocities, b)),
+                time.delta_seconds,
+            );
+            for &(entity, rotation) in &[(a, a_rotation), (b, b_rotation)] {
+                if immovable(entity) {
+                    continue;
+                }
+                if let Some(next_pose) = next_poses.get_mut(entity) {
+                    next_pose.value.set_rotation(rotation);
+                }
+            }
+        }
+
+        for _ in 0..self.iterations {
+            for joint in (&mut joints).join() {
+                let (a, b) = joint.bodies;
+                if !steps_pair(stepped, &world_ids, a, b) {
+                    continue;
+                }
+                let body_data = |entity: Entity| -> Option<(RotationalResolveData<P>, R)> {
+                    let pose = pose(&next_poses, entity)?;
+                    let linear = match next_velocities.get(entity) {
+                        Some(velocity) if !fixed(entity) => velocity.value.linear,
+                        _ => P::Diff::zero(),
+                    };
+                    let angular = match angular_velocities.get(entity) {
+                        Some(angular) if !fixed(entity) => angular.angular,
+                        _ => Zero::zero(),
+                    };
+                    let inverse_inertia =
+                        if rotates(&next_poses, &next_velocities, &angular_velocities, entity) {
+                            pose.rotation()
+                                .world_inverse_inertia(&inertias.get(entity)?.inverse_inertia())
+                        } else {
+                            Zero::zero()
+                        };
+                    let data = RotationalResolveData {
+                        center: *pose.position(),
+                        linear,
+                        angular,
+                        inverse_mass: inverse_mass(&next_poses, &next_velocities, entity),
+                        inverse_inertia,
+                    };
+                    Some((data, *pose.rotation()))
+                };
+                let (a_data, a_rotation, b_data, b_rotation) = match (body_data(a), body_data(b)) {
+                    (Some((a_data, a_rotation)), Some((b_data, b_rotation))) => {
+                        (a_data, a_rotation, b_data, b_rotation)
+                    }
+                    _ => continue,
+                };
+
+                let resolution = solve_joint(
+                    joint,
+                    (a_data, &a_rotation),
+                    (b_data, &b_rotation),
+                    time.delta_seconds,
+                );
+
+                let (linear, angular) = (resolution.linear, resolution.angular);
+                let bodies = [(a, linear.0, angular.0), (b, linear.1, angular.1)];
+                for &(entity, linear, angular) in &bodies {
+                    if immovable(entity) {
+                        continue;
+                    }
+                    if let Some(next_velocity) = next_velocities.get_mut(entity) {
+                        next_velocity.value.linear = linear;
+                    }
+                    if let Some(angular_velocity) = angular_velocities.get_mut(entity) {
+                        angular_velocity.angular = angular;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, EuclideanSpace, InnerSpace, One, Point2, Rotation, Transform, Vector2,
+                 Zero};
+    use shrev::EventChannel;
+    use specs::{RunNow, World};
+
+    use NextFrame;
+    use ecs::physics::prelude2d::*;
+
+    #[test]
+    fn test_off_center_ball_joint_swings() {
+        let mut world = World::new();
+        world_physics_register(&mut world);
+        world.write_resource::<DeltaTime>().delta_seconds = 1. / 60.;
+        let pivot_pose = BodyPose2::new(Point2::origin(), Basis2::one());
+        let pivot = world
+            .create_entity()
+            .with(pivot_pose.clone())
+            .with(NextFrame { value: pivot_pose })
+            .with(BodyType::Static)
+            .build();
+        // a rod held out horizontally by its end
+        let pose = BodyPose2::new(Point2::new(1., 0.), Basis2::one());
+        let velocity = Velocity2 {
+            linear: Vector2::zero(),
+        };
+        let rod = world
+            .create_entity()
+            .with(pose.clone())
+            .with(NextFrame { value: pose })
+            .with(velocity.clone())
+            .with(NextFrame { value: velocity })
+            .with(Mass::new(1.))
+            .with(Inertia2::new(1. / 3.))
+            .with(AngularVelocity2::default())
+            .with(ForceAccumulator2::new())
+            .build();
+        let joint = Joint2::new(pivot, rod, JointKind2::Ball)
+            .with_anchors(Point2::origin(), Point2::new(-1., 0.))
+            .with_softness(0.);
+        world.create_entity().with(joint).build();
+
+        let reader = world
+            .write_resource::<EventChannel<ContactEvent2>>()
+            .register_reader();
+        let mut joints = JointSolverSystem2::new();
+        let mut solver = LinearContactSolverSystem2::new(reader);
+        for _ in 0..20 {
+            world
+                .write::<ForceAccumulator2>()
+                .get_mut(rod)
+                .unwrap()
+                .add_force(Vector2::new(0., -10.));
+            joints.run_now(&world.res);
+            solver.run_now(&world.res);
+        }
+
+        // the rod swings down around the pivot, instead of falling straight down
+        let angular = world.read::<AngularVelocity2>().get(rod).unwrap().angular;
+        assert!(angular < 0.);
+        let poses = world.read::<BodyPose2>();
+        let pose = poses.get(rod).unwrap();
+        let end = pose.transform_point(Point2::new(-1., 0.));
+        assert!(pose.rotation().rotate_vector(Vector2::new(1., 0.)).y < 0.);
+        assert!((end - Point2::origin()).magnitude() < 0.05);
+    }
+}
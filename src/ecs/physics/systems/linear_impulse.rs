@@ -1,25 +1,128 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::Debug;
 use std::marker;
+use std::mem;
+use std::ops::Deref;
 
-use cgmath::{EuclideanSpace, InnerSpace, Rotation, VectorSpace};
+use cgmath::{EuclideanSpace, InnerSpace, Rotation, VectorSpace, Zero};
 use shrev::{EventChannel, ReaderId};
-use specs::{Entity, Fetch, Join, ReadStorage, System, WriteStorage};
+use specs::{Entities, Entity, Fetch, FetchMut, Join, MaskedStorage, ReadStorage, Storage, System,
+            WriteStorage};
 
 use {BodyPose, NextFrame, Real};
 use collide::ContactEvent;
-use ecs::physics::resources::DeltaTime;
-use physics::{linear_resolve_contact, LinearResolveData, Mass, Velocity};
+use ecs::collide::{is_stepped, Dormant, Frozen, SilentContacts, SteppedWorld, WorldId};
+use ecs::physics::resources::{ContactIslands, ContactSolverState, DeltaTime, MaterialRegistry,
+                              ResolvedContacts};
+use physics::{arcade_resolve_contact, build_islands, flip_frame, integrate_body, integrate_rotation,
+              rotational_friction_impulse, solve_contact, AngularRotation, AngularVelocity,
+              ArcadeBounce, BodyType, ContactImpulse, ContactResolution, ContactSensors,
+              ContactSettings, Damping, ForceAccumulator, ImpactEvent, Inertia, InstabilityEvent,
+              InstabilityKind, Island, LinearResolveData, Mass, Material, MaterialHandle,
+              PositionCorrection, Rolling, RotationalResolveData, RotationalVector, SolverGroup,
+              Velocity};
 
 /// Linear contact solver system.
 ///
 /// Will do contact resolution, update positions and velocities and set up the next frames positions
-/// and velocities.
+/// and velocities. Any forces accumulated in the bodies
+/// [`ForceAccumulator`](../../physics/struct.ForceAccumulator.html) are consumed when computing the
+/// next frames velocities. Bodies with an
+/// [`AngularVelocity`](../../physics/struct.AngularVelocity.html) are rotated as well, and turned
+/// by the accumulated torque according to their [`Inertia`](../../physics/struct.Inertia.html),
+/// see [`integrate_rotation`](../../physics/fn.integrate_rotation.html).
+///
+/// Contacts with bodies that have no [`Mass`](../../physics/struct.Mass.html) are collision only,
+/// they are still emitted for gameplay use, but are never resolved.
+///
+/// Penetration is corrected using the
+/// [`PositionCorrection`](../../physics/struct.PositionCorrection.html) resource, if present,
+/// otherwise the default tunables are used.
+///
+/// Contacts in the [`SilentContacts`](../collide/struct.SilentContacts.html) resource, if present,
+/// are resolved along with the contact events.
+///
+/// Bodies with a [`MaterialHandle`](../../physics/struct.MaterialHandle.html) use the restitution
+/// and friction of their material from the [`MaterialRegistry`](struct.MaterialRegistry.html)
+/// resource. Bodies without a material are perfectly elastic and frictionless.
+///
+/// The total contact impulse applied to each body during the step is recorded in the bodies
+/// [`ContactImpulse`](../../physics/struct.ContactImpulse.html) component, if present. The contact
+/// state and normal force of the sensor primitives of each body are recorded in the bodies
+/// [`ContactSensors`](../../physics/struct.ContactSensors.html) component, if present.
+///
+/// The number of contacts resolved per frame can be limited, see
+/// [`with_contact_budget`](#method.with_contact_budget).
+///
+/// Contacts are resolved in separate passes per
+/// [`SolverGroup`](../../physics/struct.SolverGroup.html), and each group can be given more
+/// iterations, see [`with_group_iterations`](#method.with_group_iterations). Extra iterations only
+/// refine the velocities, penetration is corrected once per contact.
+///
+/// Contacts are resolved per contact [`Island`](../../physics/struct.Island.html), since islands
+/// never affect each other. The iterations of an island can stop early once the velocity changes
+/// of an iteration are small enough, see [`with_tolerance`](#method.with_tolerance). If sleeping
+/// is enabled, see [`with_sleeping`](#method.with_sleeping), an island whose bodies have all been
+/// at rest for a number of frames is put to sleep, by marking its bodies
+/// [`Dormant`](../collide/struct.Dormant.html) and stopping them. A sleeping body is woken when a
+/// moving body touches it, or when its `Dormant` marker is removed, e.g. by an
+/// [`Activator`](../collide/struct.Activator.html). The
+/// [`ActiveRegionSystem`](../collide/struct.ActiveRegionSystem.html) only wakes the bodies it made
+/// dormant itself, so sleeping bodies inside the active regions stay asleep.
+///
+/// If a [`ContactSolverState`](struct.ContactSolverState.html) resource is present, the contacts
+/// deferred by the contact budget and the sleeping state are kept in it between steps, instead of
+/// in the solver, so they can be captured and restored along with the bodies.
+///
+/// If an [`ImpactEvent`](../../physics/struct.ImpactEvent.html) channel resource is present, an
+/// impact event is emitted for each contact where the bodies approach each other faster than the
+/// impact threshold, see [`with_impact_threshold`](#method.with_impact_threshold).
+///
+/// Contacts where the bodies approach each other slower than the resting threshold are resting
+/// contacts, e.g. a body lying on the ground, and are resolved without restitution, so bodies
+/// settle instead of vibrating, see [`with_resting_threshold`](#method.with_resting_threshold).
+///
+/// If an [`InstabilityEvent`](../../physics/struct.InstabilityEvent.html) channel resource is
+/// present, an instability event is emitted for each contact whose resolution changes the
+/// velocity or position of a body by more than the sanity bounds, or produces a value that is not
+/// finite, see [`with_instability_bounds`](#method.with_instability_bounds).
+///
+/// If a [`ResolvedContacts`](struct.ResolvedContacts.html) resource is present, the resolved
+/// contacts of the step are recorded in it, with the total impulse applied for each contact. If a
+/// [`ContactIslands`](struct.ContactIslands.html) resource is present, the contact islands are
+/// recorded in it.
+///
+/// Contacts where either body has an [`ArcadeBounce`](../../physics/struct.ArcadeBounce.html) are
+/// resolved arcade style, the bouncing bodies have their velocity reflected, and no impulse is
+/// exchanged with the other body, see
+/// [`arcade_resolve_contact`](../../physics/fn.arcade_resolve_contact.html).
+///
+/// The contact friction of [`Rolling`](../../physics/struct.Rolling.html) bodies with an
+/// `AngularVelocity` is applied at the contact point, so friction turns their spin into motion
+/// along the surface, e.g. for driven wheels, see
+/// [`rotational_friction_impulse`](../../physics/fn.rotational_friction_impulse.html).
+///
+/// Bodies that are [`Frozen`](../collide/struct.Frozen.html) are never moved, and act as immovable
+/// obstacles in contacts. Their velocity is kept, and any accumulated forces are discarded. The
+/// same goes for [`Dormant`](../collide/struct.Dormant.html) bodies outside the active regions,
+/// and for static bodies, see [`BodyType`](../../physics/enum.BodyType.html). Kinematic bodies
+/// only move with their velocity, and act as immovable obstacles moving with that velocity.
 pub struct LinearContactSolverSystem<P, R>
 where
     P: EuclideanSpace,
     P::Diff: Debug,
 {
     contact_reader: ReaderId<ContactEvent<Entity, P>>,
+    budget: Option<usize>,
+    deferred: Vec<ContactEvent<Entity, P>>,
+    group_iterations: HashMap<SolverGroup, usize>,
+    tolerance: Real,
+    sleep: Option<IslandSleep>,
+    impact_threshold: Real,
+    resting_threshold: Real,
+    max_velocity_change: Real,
+    max_position_change: Real,
     m: marker::PhantomData<(P, R)>,
 }
 
@@ -32,95 +135,1396 @@ where
     pub fn new(contact_reader: ReaderId<ContactEvent<Entity, P>>) -> Self {
         Self {
             contact_reader,
+            budget: None,
+            deferred: Vec::default(),
+            group_iterations: HashMap::default(),
+            tolerance: 0.,
+            sleep: None,
+            impact_threshold: DEFAULT_IMPACT_THRESHOLD,
+            resting_threshold: DEFAULT_RESTING_THRESHOLD,
+            max_velocity_change: Real::INFINITY,
+            max_position_change: Real::INFINITY,
             m: marker::PhantomData,
         }
     }
+
+    /// Limit the number of contacts resolved per frame.
+    ///
+    /// When there are more contacts than the budget allows, the deepest contacts are resolved
+    /// first, and the rest are deferred to the next frame. A deferred contact is dropped if the
+    /// same pair of bodies has a new contact in the next frame. This bounds the worst case frame
+    /// time during pile-ups, at the cost of some penetration lingering for a few frames.
+    pub fn with_contact_budget(mut self, budget: usize) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Set the number of iterations used for contacts in the given solver group. Groups without a
+    /// configured iteration count use a single iteration.
+    pub fn with_group_iterations(mut self, group: SolverGroup, iterations: usize) -> Self {
+        self.group_iterations.insert(group, iterations.max(1));
+        self
+    }
+
+    /// Set the largest velocity change of an iteration below which the remaining iterations of an
+    /// island in a solver group are skipped. A tolerance of zero always runs all iterations.
+    pub fn with_tolerance(mut self, tolerance: Real) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Put contact islands to sleep when the linear and angular speeds of all their bodies have
+    /// been below the threshold for the given number of frames
+    pub fn with_sleeping(mut self, threshold: Real, frames: usize) -> Self {
+        self.sleep = Some(IslandSleep::new(threshold, frames));
+        self
+    }
+
+    /// Set the minimum relative speed along the contact normal for emitting impact events
+    pub fn with_impact_threshold(mut self, impact_threshold: Real) -> Self {
+        self.impact_threshold = impact_threshold;
+        self
+    }
+
+    /// Set the relative speed along the contact normal below which contacts are resting contacts,
+    /// and are resolved without restitution. A threshold of zero disables resting contacts.
+    pub fn with_resting_threshold(mut self, resting_threshold: Real) -> Self {
+        self.resting_threshold = resting_threshold;
+        self
+    }
+
+    /// Set the sanity bounds for the velocity and position change of a body from resolving a
+    /// single contact, above which an instability event is emitted. By default the bounds are
+    /// infinite, and only changes that are not finite are reported.
+    pub fn with_instability_bounds(
+        mut self,
+        max_velocity_change: Real,
+        max_position_change: Real,
+    ) -> Self {
+        self.max_velocity_change = max_velocity_change;
+        self.max_position_change = max_position_change;
+        self
+    }
+
+    /// Get the contacts that were deferred to the next frame, because of the contact budget. With
+    /// a [`ContactSolverState`](struct.ContactSolverState.html) resource, the deferred contacts
+    /// are kept in the resource instead.
+    pub fn deferred(&self) -> &[ContactEvent<Entity, P>] {
+        &self.deferred
+    }
+
+    /// Swap the deferred contacts and the sleeping state with the ones kept in the solver state
+    /// resource
+    fn swap_state(&mut self, state: &mut ContactSolverState<P>) {
+        mem::swap(&mut self.deferred, &mut state.deferred);
+        if let Some(sleep) = self.sleep.as_mut() {
+            sleep.swap_state(state);
+        }
+    }
+}
+
+const DEFAULT_RESTITUTION: Real = 1.;
+pub(crate) const DEFAULT_IMPACT_THRESHOLD: Real = 1.;
+const DEFAULT_RESTING_THRESHOLD: Real = 0.5;
+
+pub(crate) fn impact<P>(
+    contact: &ContactEvent<Entity, P>,
+    threshold: Real,
+    handles: &ReadStorage<MaterialHandle>,
+    next_velocities: &WriteStorage<NextFrame<Velocity<P::Diff>>>,
+) -> Option<ImpactEvent<Entity, P>>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: VectorSpace<Scalar = Real> + InnerSpace + Debug + Send + Sync + 'static,
+{
+    let velocity = |entity| {
+        next_velocities
+            .get(entity)
+            .map(|v| v.value.linear)
+            .unwrap_or_else(P::Diff::zero)
+    };
+    let relative_velocity = velocity(contact.bodies.1) - velocity(contact.bodies.0);
+    let speed = -relative_velocity.dot(contact.contact.normal);
+    if speed < threshold {
+        return None;
+    }
+    Some(ImpactEvent {
+        bodies: contact.bodies,
+        materials: (
+            handles.get(contact.bodies.0).cloned(),
+            handles.get(contact.bodies.1).cloned(),
+        ),
+        speed,
+        point: contact.contact.contact_point,
+    })
+}
+
+/// Emit instability events for a contact, if the velocity or position change of its bodies is not
+/// finite, or above the given bound. The changes are given as `(change, bound)` pairs.
+pub(crate) fn check_stability<P>(
+    channel: &mut EventChannel<InstabilityEvent<Entity, P>>,
+    contact: &ContactEvent<Entity, P>,
+    velocity: (Real, Real),
+    position: (Real, Real),
+) where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: Debug + Send + Sync + 'static,
+{
+    let changes = [
+        (InstabilityKind::Velocity, velocity),
+        (InstabilityKind::Position, position),
+    ];
+    for &(kind, (magnitude, bound)) in &changes {
+        if magnitude.is_nan() || magnitude > bound {
+            channel.single_write(InstabilityEvent {
+                bodies: contact.bodies,
+                contact: contact.contact.clone(),
+                kind,
+                magnitude,
+            });
+        }
+    }
+}
+
+/// Material of a contact, bodies without a material are perfectly elastic and frictionless
+pub(crate) fn contact_material<P>(
+    contact: &ContactEvent<Entity, P>,
+    registry: Option<&MaterialRegistry>,
+    handles: &ReadStorage<MaterialHandle>,
+) -> Material
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    let default = || Material::new(DEFAULT_RESTITUTION, 0.);
+    let registry = match registry {
+        Some(registry) => registry,
+        None => return default(),
+    };
+    let material = match (handles.get(contact.bodies.0), handles.get(contact.bodies.1)) {
+        (Some(a), Some(b)) => registry.combined(*a, *b),
+        (Some(handle), None) | (None, Some(handle)) => registry.get(*handle).cloned(),
+        (None, None) => None,
+    };
+    material.unwrap_or_else(default)
+}
+
+/// Check if a body is frozen, dormant or static, and should never be moved
+pub(crate) fn is_frozen<D>(
+    frozen: &ReadStorage<Frozen>,
+    dormant: &Storage<Dormant, D>,
+    body_types: &ReadStorage<BodyType>,
+    entity: Entity,
+) -> bool
+where
+    D: Deref<Target = MaskedStorage<Dormant>>,
+{
+    frozen.get(entity).is_some() || dormant.get(entity).is_some()
+        || body_types.get(entity) == Some(&BodyType::Static)
+}
+
+/// Check if a body is kinematic, and only moved by its velocity
+pub(crate) fn is_kinematic(body_types: &ReadStorage<BodyType>, entity: Entity) -> bool {
+    body_types.get(entity) == Some(&BodyType::Kinematic)
+}
+
+/// Body storages shared by the contact solver systems, part of their system data
+pub type SolverBodyData<'a, P, R> = (
+    ReadStorage<'a, Mass>,
+    ReadStorage<'a, Damping>,
+    ReadStorage<'a, Frozen>,
+    WriteStorage<'a, Dormant>,
+    ReadStorage<'a, BodyType>,
+    WriteStorage<'a, Velocity<<P as EuclideanSpace>::Diff>>,
+    WriteStorage<'a, NextFrame<Velocity<<P as EuclideanSpace>::Diff>>>,
+    WriteStorage<'a, BodyPose<P, R>>,
+    WriteStorage<'a, NextFrame<BodyPose<P, R>>>,
+    WriteStorage<'a, ForceAccumulator<<P as EuclideanSpace>::Diff>>,
+    ReadStorage<'a, Inertia<<P as EuclideanSpace>::Diff>>,
+    WriteStorage<'a, AngularVelocity<<P as EuclideanSpace>::Diff>>,
+    ReadStorage<'a, WorldId>,
+    Option<Fetch<'a, SteppedWorld>>,
+);
+
+/// Body storages used by the contact solvers to resolve contacts and advance the bodies
+pub(crate) struct SolverBodies<'a, P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: RotationalVector + Debug + Send + Sync + 'static,
+    R: AngularRotation<P::Diff> + Rotation<P> + Send + Sync + 'static,
+{
+    pub masses: ReadStorage<'a, Mass>,
+    pub dampings: ReadStorage<'a, Damping>,
+    pub frozen: ReadStorage<'a, Frozen>,
+    pub dormant: WriteStorage<'a, Dormant>,
+    pub body_types: ReadStorage<'a, BodyType>,
+    pub velocities: WriteStorage<'a, Velocity<P::Diff>>,
+    pub next_velocities: WriteStorage<'a, NextFrame<Velocity<P::Diff>>>,
+    pub poses: WriteStorage<'a, BodyPose<P, R>>,
+    pub next_poses: WriteStorage<'a, NextFrame<BodyPose<P, R>>>,
+    pub forces: WriteStorage<'a, ForceAccumulator<P::Diff>>,
+    pub inertias: ReadStorage<'a, Inertia<P::Diff>>,
+    pub angular_velocities: WriteStorage<'a, AngularVelocity<P::Diff>>,
+    pub world_ids: ReadStorage<'a, WorldId>,
+    pub stepped: Option<Fetch<'a, SteppedWorld>>,
+}
+
+impl<'a, P, R> SolverBodies<'a, P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: RotationalVector + Debug + Send + Sync + 'static,
+    R: AngularRotation<P::Diff> + Rotation<P> + Send + Sync + 'static,
+{
+    pub fn new(data: SolverBodyData<'a, P, R>) -> Self {
+        let (
+            masses,
+            dampings,
+            frozen,
+            dormant,
+            body_types,
+            velocities,
+            next_velocities,
+            poses,
+            next_poses,
+            forces,
+            inertias,
+            angular_velocities,
+            world_ids,
+            stepped,
+        ) = data;
+        Self {
+            masses,
+            dampings,
+            frozen,
+            dormant,
+            body_types,
+            velocities,
+            next_velocities,
+            poses,
+            next_poses,
+            forces,
+            inertias,
+            angular_velocities,
+            world_ids,
+            stepped,
+        }
+    }
+
+    /// Check if a body is in the stepped world, see
+    /// [`SteppedWorld`](../collide/struct.SteppedWorld.html)
+    pub fn is_stepped(&self, entity: Entity) -> bool {
+        is_stepped(self.stepped.as_deref(), &self.world_ids, entity)
+    }
+
+    /// Check if a body is frozen, dormant or static, and should never be moved
+    pub fn is_frozen(&self, entity: Entity) -> bool {
+        is_frozen(&self.frozen, &self.dormant, &self.body_types, entity)
+    }
+
+    /// Check if a body is kinematic, and only moved by its velocity
+    pub fn is_kinematic(&self, entity: Entity) -> bool {
+        is_kinematic(&self.body_types, entity)
+    }
+
+    /// Check if contacts can not move a body, e.g. for grouping contacts into islands
+    pub fn is_immovable(&self, entity: Entity) -> bool {
+        self.is_frozen(entity) || self.is_kinematic(entity) || !self.is_stepped(entity)
+            || self.next_velocities.get(entity).is_none() || self.masses
+            .get(entity)
+            .map(|mass| mass.inverse_mass() == 0.)
+            .unwrap_or(true)
+    }
+}
+
+/// Group the contacts into islands, bodies the contacts can not move are static
+pub(crate) fn find_islands<P, R>(
+    contacts: &[ContactEvent<Entity, P>],
+    bodies: &SolverBodies<P, R>,
+) -> Vec<Island<Entity>>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: RotationalVector + Debug + Send + Sync + 'static,
+    R: AngularRotation<P::Diff> + Rotation<P> + Send + Sync + 'static,
+{
+    build_islands(contacts, |&entity| bodies.is_immovable(entity))
+}
+
+/// Puts contact islands to sleep when all their bodies have been at rest for a number of frames,
+/// by marking the bodies `Dormant`, and wakes them again when a moving body touches them
+#[derive(Debug)]
+pub(crate) struct IslandSleep {
+    threshold: Real,
+    frames: usize,
+    resting: HashMap<Entity, usize>,
+    sleeping: HashSet<Entity>,
+}
+
+impl IslandSleep {
+    pub fn new(threshold: Real, frames: usize) -> Self {
+        Self {
+            threshold,
+            frames: frames.max(1),
+            resting: HashMap::default(),
+            sleeping: HashSet::default(),
+        }
+    }
+
+    /// Swap the resting and sleeping bodies with the ones kept in the solver state resource
+    pub fn swap_state<P>(&mut self, state: &mut ContactSolverState<P>)
+    where
+        P: EuclideanSpace,
+        P::Diff: Debug,
+    {
+        mem::swap(&mut self.resting, &mut state.resting);
+        mem::swap(&mut self.sleeping, &mut state.sleeping);
+    }
+
+    /// Forget the resting and sleeping bodies
+    pub fn clear(&mut self) {
+        self.resting.clear();
+        self.sleeping.clear();
+    }
+
+    /// Wake the sleeping bodies touched by moving bodies, before the islands are built. Waking a
+    /// body wakes the sleeping bodies it touches as well.
+    pub fn wake<P, R>(
+        &mut self,
+        contacts: &[ContactEvent<Entity, P>],
+        bodies: &mut SolverBodies<P, R>,
+    ) where
+        P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+        P::Diff: RotationalVector + Debug + Send + Sync + 'static,
+        R: AngularRotation<P::Diff> + Rotation<P> + Send + Sync + 'static,
+    {
+        // bodies woken by others, e.g. an activator, are no longer ours to wake
+        {
+            let dormant = &bodies.dormant;
+            self.sleeping.retain(|&entity| dormant.get(entity).is_some());
+        }
+        loop {
+            let mut woken = Vec::new();
+            for contact in contacts {
+                let (a, b) = contact.bodies;
+                for &(sleeping, other) in &[(a, b), (b, a)] {
+                    if self.sleeping.contains(&sleeping) && !bodies.is_immovable(other) {
+                        woken.push(sleeping);
+                    }
+                }
+            }
+            if woken.is_empty() {
+                break;
+            }
+            for entity in woken {
+                self.sleeping.remove(&entity);
+                bodies.dormant.remove(entity);
+            }
+        }
+    }
+
+    /// Count the frames the bodies of each island have been at rest, after the bodies have been
+    /// advanced, and put the islands where all bodies have been at rest long enough to sleep
+    pub fn sleep<P, R>(&mut self, islands: &[Island<Entity>], bodies: &mut SolverBodies<P, R>)
+    where
+        P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+        P::Diff: RotationalVector + Debug + Send + Sync + 'static,
+        R: AngularRotation<P::Diff> + Rotation<P> + Send + Sync + 'static,
+    {
+        let mut resting = HashMap::new();
+        for island in islands {
+            for &entity in &island.bodies {
+                let linear = bodies
+                    .velocities
+                    .get(entity)
+                    .map(|velocity| velocity.linear.magnitude())
+                    .unwrap_or(0.);
+                let angular = bodies
+                    .angular_velocities
+                    .get(entity)
+                    .map(|velocity| {
+                        P::Diff::angular_dot(velocity.angular, velocity.angular).sqrt()
+                    })
+                    .unwrap_or(0.);
+                if linear < self.threshold && angular < self.threshold {
+                    let frames = self.resting.get(&entity).cloned().unwrap_or(0) + 1;
+                    resting.insert(entity, frames);
+                }
+            }
+            let frames = self.frames;
+            let at_rest = island
+                .bodies
+                .iter()
+                .all(|entity| resting.get(entity).map(|&f| f >= frames).unwrap_or(false));
+            if !at_rest {
+                continue;
+            }
+            for &entity in &island.bodies {
+                resting.remove(&entity);
+                self.sleeping.insert(entity);
+                bodies.dormant.insert(entity, Dormant);
+                if let Some(velocity) = bodies.velocities.get_mut(entity) {
+                    velocity.linear = P::Diff::zero();
+                }
+                if let Some(next_velocity) = bodies.next_velocities.get_mut(entity) {
+                    next_velocity.value.linear = P::Diff::zero();
+                }
+                if let Some(velocity) = bodies.angular_velocities.get_mut(entity) {
+                    velocity.angular = Zero::zero();
+                }
+                if let (Some(pose), Some(next_pose)) =
+                    (bodies.poses.get(entity), bodies.next_poses.get_mut(entity))
+                {
+                    next_pose.value = pose.clone();
+                }
+            }
+        }
+        self.resting = resting;
+    }
+}
+
+/// Returns the impulse applied to the second body, and the largest velocity and position change
+/// applied to either body
+fn resolve_contact<P, R>(
+    contact: &ContactEvent<Entity, P>,
+    settings: &ContactSettings,
+    bodies: &mut SolverBodies<P, R>,
+    bounces: &ReadStorage<ArcadeBounce>,
+    rolling: &ReadStorage<Rolling>,
+    impulses: &mut WriteStorage<ContactImpulse<P::Diff>>,
+    sensors: &mut WriteStorage<ContactSensors>,
+) -> (Option<P::Diff>, Real, Real)
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: RotationalVector + Debug + Send + Sync + 'static,
+    R: AngularRotation<P::Diff> + Rotation<P> + Send + Sync + 'static,
+{
+    // frozen bodies are resolved as immovable bodies at rest, and kinematic bodies as immovable
+    // bodies moving with their velocity
+    let frozen_mass = Mass::new(Real::INFINITY);
+    let resolve_data = |entity| {
+        if bodies.is_frozen(entity) {
+            LinearResolveData {
+                velocity: None,
+                position: None,
+                mass: Some(&frozen_mass),
+            }
+        } else if bodies.is_kinematic(entity) {
+            LinearResolveData {
+                velocity: bodies.next_velocities.get(entity),
+                position: None,
+                mass: Some(&frozen_mass),
+            }
+        } else {
+            LinearResolveData {
+                velocity: bodies.next_velocities.get(entity),
+                position: bodies.next_poses.get(entity),
+                mass: bodies.masses.get(entity),
+            }
+        }
+    };
+    let arcade = (bounces.get(contact.bodies.0), bounces.get(contact.bodies.1));
+    let rolls = |entity| rolling.get(entity).is_some();
+    let resolution = if arcade.0.is_some() || arcade.1.is_some() {
+        arcade_resolve_contact(
+            contact,
+            resolve_data(contact.bodies.0),
+            resolve_data(contact.bodies.1),
+            arcade,
+            settings.position_correction,
+            settings.correct_position,
+            settings.delta_seconds,
+        )
+    } else if rolls(contact.bodies.0) || rolls(contact.bodies.1) {
+        // the friction is applied at the contact point, after the normal impulse
+        let normal_settings = ContactSettings {
+            material: Material {
+                static_friction: 0.,
+                dynamic_friction: 0.,
+                ..settings.material.clone()
+            },
+            ..*settings
+        };
+        solve_contact(
+            contact,
+            resolve_data(contact.bodies.0),
+            resolve_data(contact.bodies.1),
+            &normal_settings,
+        )
+    } else {
+        solve_contact(
+            contact,
+            resolve_data(contact.bodies.0),
+            resolve_data(contact.bodies.1),
+            settings,
+        )
+    };
+    let mut resolution = resolution;
+    if arcade.0.is_none() && arcade.1.is_none() {
+        rolling_friction(contact, &settings.material, bodies, rolling, &mut resolution);
+    }
+    if let Some(impulse) = resolution.impulse {
+        // arcade contacts exchange no impulse, only the bouncing bodies receive one
+        let exchange = arcade.0.is_none() && arcade.1.is_none();
+        if let Some(accumulator) = impulses.get_mut(contact.bodies.0) {
+            if exchange || arcade.0.is_some() {
+                accumulator.add_impulse(impulse * -1.);
+            }
+        }
+        if let Some(accumulator) = impulses.get_mut(contact.bodies.1) {
+            if exchange || arcade.1.is_some() {
+                accumulator.add_impulse(impulse);
+            }
+        }
+    }
+    if let Some(sensors) = sensors.get_mut(contact.bodies.0) {
+        sensors.record(contact.primitives.0, resolution.normal_force);
+    }
+    if let Some(sensors) = sensors.get_mut(contact.bodies.1) {
+        sensors.record(contact.primitives.1, resolution.normal_force);
+    }
+    let (update_pose_0, update_pose_1) = resolution.poses;
+    let (update_velocity_0, update_velocity_1) = resolution.velocities;
+    let mut position_change = 0.;
+    let mut velocity_change = 0.;
+    if let (Some(pose), Some(update_pose)) =
+        (bodies.next_poses.get_mut(contact.bodies.0), update_pose_0)
+    {
+        position_change = largest_change(
+            position_change,
+            (*update_pose.value.position() - *pose.value.position()).magnitude(),
+        );
+        *pose = update_pose;
+    }
+    if let (Some(pose), Some(update_pose)) =
+        (bodies.next_poses.get_mut(contact.bodies.1), update_pose_1)
+    {
+        position_change = largest_change(
+            position_change,
+            (*update_pose.value.position() - *pose.value.position()).magnitude(),
+        );
+        *pose = update_pose;
+    }
+    if let (Some(velocity), Some(update_velocity)) =
+        (bodies.next_velocities.get_mut(contact.bodies.0), update_velocity_0)
+    {
+        velocity_change = largest_change(
+            velocity_change,
+            (update_velocity.value.linear - velocity.value.linear).magnitude(),
+        );
+        *velocity = update_velocity;
+    }
+    if let (Some(velocity), Some(update_velocity)) =
+        (bodies.next_velocities.get_mut(contact.bodies.1), update_velocity_1)
+    {
+        velocity_change = largest_change(
+            velocity_change,
+            (update_velocity.value.linear - velocity.value.linear).magnitude(),
+        );
+        *velocity = update_velocity;
+    }
+    (resolution.impulse, velocity_change, position_change)
+}
+
+/// Apply the contact friction of rolling bodies at the contact point, on top of a resolution
+/// without friction. Contacts without rolling bodies are left untouched.
+fn rolling_friction<P, R>(
+    contact: &ContactEvent<Entity, P>,
+    material: &Material,
+    bodies: &mut SolverBodies<P, R>,
+    rolling: &ReadStorage<Rolling>,
+    resolution: &mut ContactResolution<P, R>,
+) where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: RotationalVector + Debug + Send + Sync + 'static,
+    R: AngularRotation<P::Diff> + Rotation<P> + Send + Sync + 'static,
+{
+    let (a, b) = contact.bodies;
+    let rolls = |entity| {
+        rolling.get(entity).is_some() && bodies.angular_velocities.get(entity).is_some()
+            && !bodies.is_frozen(entity)
+    };
+    let rolling_bodies = (rolls(a), rolls(b));
+    if !rolling_bodies.0 && !rolling_bodies.1 {
+        return;
+    }
+    let normal_impulse = match resolution.impulse {
+        Some(impulse) => impulse.magnitude(),
+        None => return,
+    };
+    let resolve_data = |entity, velocity: &Option<NextFrame<Velocity<P::Diff>>>| {
+        let frozen = bodies.is_frozen(entity);
+        let pose = if frozen {
+            bodies.poses.get(entity).cloned()
+        } else {
+            bodies.next_poses.get(entity).map(|pose| pose.value.clone())
+        }?;
+        let linear = if frozen {
+            P::Diff::zero()
+        } else {
+            velocity
+                .as_ref()
+                .or_else(|| bodies.next_velocities.get(entity))
+                .map(|velocity| velocity.value.linear)
+                .unwrap_or_else(P::Diff::zero)
+        };
+        let rolls = if entity == a {
+            rolling_bodies.0
+        } else {
+            rolling_bodies.1
+        };
+        let angular = match bodies.angular_velocities.get(entity) {
+            Some(angular) if rolls => angular.angular,
+            _ => Zero::zero(),
+        };
+        let movable = !bodies.is_immovable(entity);
+        let inverse_mass = match bodies.masses.get(entity) {
+            Some(mass) if movable => mass.inverse_mass(),
+            _ => 0.,
+        };
+        let inverse_inertia = match bodies.inertias.get(entity) {
+            Some(inertia) if movable && rolls => {
+                pose.rotation().world_inverse_inertia(&inertia.inverse_inertia())
+            }
+            _ => Zero::zero(),
+        };
+        Some(RotationalResolveData {
+            center: *pose.position(),
+            linear,
+            angular,
+            inverse_mass,
+            inverse_inertia,
+        })
+    };
+    let data = (
+        resolve_data(a, &resolution.velocities.0),
+        resolve_data(b, &resolution.velocities.1),
+    );
+    let friction = match data {
+        (Some(data_a), Some(data_b)) => rotational_friction_impulse(
+            contact,
+            &data_a,
+            &data_b,
+            normal_impulse,
+            material.static_friction,
+            material.dynamic_friction,
+        ),
+        _ => None,
+    };
+    let friction = match friction {
+        Some(friction) => friction,
+        None => return,
+    };
+    let updates = [
+        (a, rolling_bodies.0, friction.linear.0, friction.angular.0),
+        (b, rolling_bodies.1, friction.linear.1, friction.angular.1),
+    ];
+    for (index, &(entity, rolls, linear, angular)) in updates.iter().enumerate() {
+        if bodies.is_immovable(entity) {
+            continue;
+        }
+        let velocity = if index == 0 {
+            &mut resolution.velocities.0
+        } else {
+            &mut resolution.velocities.1
+        };
+        let next = velocity
+            .take()
+            .or_else(|| bodies.next_velocities.get(entity).cloned());
+        *velocity = next.map(|mut next| {
+            next.value.linear = linear;
+            next
+        });
+        if rolls {
+            if let Some(velocity) = bodies.angular_velocities.get_mut(entity) {
+                velocity.angular = angular;
+            }
+        }
+    }
+    resolution.impulse = resolution
+        .impulse
+        .map(|impulse| impulse + friction.impulse);
+}
+
+/// Largest of two changes, where a change that is not a number is larger than any other change
+pub(crate) fn largest_change(a: Real, b: Real) -> Real {
+    if a.is_nan() || a > b {
+        a
+    } else {
+        b
+    }
 }
 
 impl<'a, P, R> System<'a> for LinearContactSolverSystem<P, R>
 where
     P: EuclideanSpace<Scalar = Real> + Send + Sync + 'a + 'static,
-    P::Diff: VectorSpace<Scalar = Real> + InnerSpace + Debug + Send + Sync + 'static,
-    R: Rotation<P> + Send + Sync + 'static,
+    P::Diff: RotationalVector + Debug + Send + Sync + 'static,
+    R: AngularRotation<P::Diff> + Rotation<P> + Send + Sync + 'static,
 {
     type SystemData = (
+        Entities<'a>,
         Fetch<'a, DeltaTime>,
+        Option<Fetch<'a, PositionCorrection>>,
         Fetch<'a, EventChannel<ContactEvent<Entity, P>>>,
-        ReadStorage<'a, Mass>,
-        WriteStorage<'a, Velocity<P::Diff>>,
-        WriteStorage<'a, NextFrame<Velocity<P::Diff>>>,
-        WriteStorage<'a, BodyPose<P, R>>,
-        WriteStorage<'a, NextFrame<BodyPose<P, R>>>,
+        Option<Fetch<'a, SilentContacts<P>>>,
+        Option<Fetch<'a, MaterialRegistry>>,
+        ReadStorage<'a, ArcadeBounce>,
+        ReadStorage<'a, Rolling>,
+        ReadStorage<'a, MaterialHandle>,
+        ReadStorage<'a, SolverGroup>,
+        Option<FetchMut<'a, EventChannel<ImpactEvent<Entity, P>>>>,
+        Option<FetchMut<'a, EventChannel<InstabilityEvent<Entity, P>>>>,
+        Option<FetchMut<'a, ResolvedContacts<P>>>,
+        Option<FetchMut<'a, ContactIslands>>,
+        Option<FetchMut<'a, ContactSolverState<P>>>,
+        SolverBodyData<'a, P, R>,
+        WriteStorage<'a, ContactImpulse<P::Diff>>,
+        WriteStorage<'a, ContactSensors>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
+            entities,
             time,
+            position_correction,
             contacts,
-            masses,
-            mut velocities,
-            mut next_velocities,
-            mut poses,
-            mut next_poses,
+            silent,
+            materials,
+            bounces,
+            rolling,
+            handles,
+            groups,
+            mut impacts,
+            mut instabilities,
+            mut resolved,
+            mut islands,
+            mut state,
+            bodies,
+            mut impulses,
+            mut sensors,
         ) = data;
-        for contact in contacts.lossy_read(&mut self.contact_reader) {
-            let (update_pose_0, update_pose_1, update_velocity_0, update_velocity_1) =
-                linear_resolve_contact(
-                    contact,
-                    LinearResolveData {
-                        velocity: next_velocities.get(contact.bodies.0),
-                        position: next_poses.get(contact.bodies.0),
-                        mass: masses.get(contact.bodies.0),
-                    },
-                    LinearResolveData {
-                        velocity: next_velocities.get(contact.bodies.1),
-                        position: next_poses.get(contact.bodies.1),
-                        mass: masses.get(contact.bodies.1),
-                    },
-                );
-            if let (Some(pose), Some(update_pose)) =
-                (next_poses.get_mut(contact.bodies.0), update_pose_0)
-            {
-                *pose = update_pose;
-            }
-            if let (Some(pose), Some(update_pose)) =
-                (next_poses.get_mut(contact.bodies.1), update_pose_1)
-            {
-                *pose = update_pose;
+        let mut bodies = SolverBodies::new(bodies);
+        if let Some(state) = state.as_mut() {
+            self.swap_state(state);
+        }
+        for impulse in (&mut impulses).join() {
+            impulse.reset();
+        }
+        for sensor in (&mut sensors).join() {
+            sensor.reset();
+        }
+        let default_correction = PositionCorrection::default();
+        let position_correction = position_correction
+            .as_deref()
+            .unwrap_or(&default_correction);
+        let materials = materials.as_deref();
+        let pending = match self.budget {
+            None => contacts
+                .lossy_read(&mut self.contact_reader)
+                .chain(silent.iter().flat_map(|s| s.iter()))
+                .cloned()
+                .collect::<Vec<_>>(),
+            Some(budget) => {
+                let mut pending = contacts
+                    .lossy_read(&mut self.contact_reader)
+                    .chain(silent.iter().flat_map(|s| s.iter()))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                // new contacts replace deferred contacts for the same pair of bodies
+                let pairs = pending.iter().map(|c| c.bodies).collect::<HashSet<_>>();
+                for contact in self.deferred.drain(..) {
+                    if entities.is_alive(contact.bodies.0) && entities.is_alive(contact.bodies.1)
+                        && !pairs.contains(&contact.bodies)
+                    {
+                        pending.push(contact);
+                    }
+                }
+                if pending.len() > budget {
+                    // deepest first
+                    pending.sort_by(|a, b| {
+                        b.contact
+                            .penetration_depth
+                            .partial_cmp(&a.contact.penetration_depth)
+                            .unwrap_or(Ordering::Equal)
+                    });
+                    self.deferred = pending.split_off(budget);
+                }
+                pending
             }
-            if let (Some(velocity), Some(update_velocity)) =
-                (next_velocities.get_mut(contact.bodies.0), update_velocity_0)
-            {
-                *velocity = update_velocity;
+        };
+
+        if let Some(sleep) = self.sleep.as_mut() {
+            sleep.wake(&pending, &mut bodies);
+        }
+        let contact_islands = find_islands(&pending, &bodies);
+        if let Some(islands) = islands.as_mut() {
+            islands.islands = contact_islands.clone();
+        }
+        // contacts between bodies the contacts can not move are in no island, and are resolved
+        // on their own
+        let mut island_contacts = contact_islands
+            .iter()
+            .map(|island| island.contacts.clone())
+            .collect::<Vec<_>>();
+        let mut in_island = vec![false; pending.len()];
+        for &index in island_contacts.iter().flatten() {
+            in_island[index] = true;
+        }
+        island_contacts.push((0..pending.len()).filter(|&i| !in_island[i]).collect());
+
+        let contact_groups = pending
+            .iter()
+            .map(|contact| {
+                let a = groups.get(contact.bodies.0).cloned().unwrap_or_default();
+                let b = groups.get(contact.bodies.1).cloned().unwrap_or_default();
+                a.max(b)
+            })
+            .collect::<Vec<_>>();
+        let mut total_impulses = vec![P::Diff::zero(); pending.len()];
+        // one pass per island and solver group, lowest group first
+        let passes = island_contacts.iter().flat_map(|contacts| {
+            let island_groups = contacts.iter().map(|&index| contact_groups[index]);
+            island_groups
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .map(move |group| (contacts, group))
+        });
+        for (contacts, group) in passes {
+            let iterations = self.group_iterations.get(&group).cloned().unwrap_or(1);
+            for iteration in 0..iterations {
+                let mut pass_change = 0.;
+                for &index in contacts
+                    .iter()
+                    .filter(|&&index| contact_groups[index] == group)
+                {
+                    let contact = &pending[index];
+                    if iteration == 0 {
+                        let threshold = self.impact_threshold;
+                        let event = impacts
+                            .as_ref()
+                            .and_then(|_| {
+                                impact(contact, threshold, &handles, &bodies.next_velocities)
+                            });
+                        if let (Some(channel), Some(event)) = (impacts.as_mut(), event) {
+                            channel.single_write(event);
+                        }
+                    }
+                    let settings = ContactSettings {
+                        position_correction,
+                        material: contact_material(contact, materials, &handles),
+                        resting_threshold: self.resting_threshold,
+                        correct_position: iteration == 0,
+                        delta_seconds: time.delta_seconds,
+                    };
+                    let (impulse, velocity_change, position_change) = resolve_contact(
+                        contact,
+                        &settings,
+                        &mut bodies,
+                        &bounces,
+                        &rolling,
+                        &mut impulses,
+                        &mut sensors,
+                    );
+                    if let Some(impulse) = impulse {
+                        total_impulses[index] = total_impulses[index] + impulse;
+                    }
+                    pass_change = largest_change(pass_change, velocity_change);
+                    if let Some(channel) = instabilities.as_mut() {
+                        check_stability(
+                            channel,
+                            contact,
+                            (velocity_change, self.max_velocity_change),
+                            (position_change, self.max_position_change),
+                        );
+                    }
+                }
+                if pass_change < self.tolerance {
+                    break;
+                }
             }
-            if let (Some(velocity), Some(update_velocity)) =
-                (next_velocities.get_mut(contact.bodies.1), update_velocity_1)
-            {
-                *velocity = update_velocity;
+        }
+
+        if let Some(resolved) = resolved.as_mut() {
+            resolved.contacts.clear();
+            resolved
+                .contacts
+                .extend(pending.into_iter().zip(total_impulses));
+        }
+
+        advance_bodies(&entities, &mut bodies, time.delta_seconds);
+        if let Some(sleep) = self.sleep.as_mut() {
+            sleep.sleep(&contact_islands, &mut bodies);
+        }
+        if let Some(state) = state.as_mut() {
+            self.swap_state(state);
+            self.deferred.clear();
+            if let Some(sleep) = self.sleep.as_mut() {
+                sleep.clear();
             }
         }
+    }
+}
+
+/// Move the next frame poses and velocities of all bodies that are not frozen to the current
+/// frame, and compute the next frame poses and velocities, consuming the accumulated forces.
+/// Forces accumulated on bodies without mass are consumed and dropped. The poses of frozen bodies
+/// are not written to, unless they were moved since the last frame. Bodies outside the
+/// [`SteppedWorld`](../collide/struct.SteppedWorld.html), if present, are handled like frozen
+/// bodies.
+pub(crate) fn advance_bodies<P, R>(
+    entities: &Entities,
+    bodies: &mut SolverBodies<P, R>,
+    delta_seconds: Real,
+) where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: RotationalVector + Debug + Send + Sync + 'static,
+    R: AngularRotation<P::Diff> + Rotation<P> + Send + Sync + 'static,
+{
+    let SolverBodies {
+        ref masses,
+        ref dampings,
+        ref frozen,
+        ref dormant,
+        ref body_types,
+        ref mut velocities,
+        ref mut next_velocities,
+        ref mut poses,
+        ref mut next_poses,
+        ref mut forces,
+        ref inertias,
+        ref mut angular_velocities,
+        ref world_ids,
+        ref stepped,
+    } = *bodies;
+    let stepped = stepped.as_deref();
+    let fixed = |entity| {
+        !is_stepped(stepped, world_ids, entity) || is_frozen(frozen, dormant, body_types, entity)
+    };
 
-        // Update current pose
-        for (next, pose) in (&next_poses, &mut poses).join() {
-            *pose = next.value.clone();
+    // Only bodies that are actually moved are written to, writing flags the poses in their
+    // `FlaggedStorage`, and flagged poses invalidate the cached narrow phase results of the body
+    let moving = (&**entities, &*next_poses)
+        .join()
+        .map(|(entity, _)| entity)
+        .filter(|&entity| !fixed(entity))
+        .collect::<Vec<_>>();
+
+    // Update current pose
+    for &entity in &moving {
+        if let (Some(next), Some(pose)) = (next_poses.get(entity), poses.get_mut(entity)) {
+            flip_frame(pose, next);
         }
+    }
 
-        // Update current velocity
-        for (next, velocity) in (&next_velocities, &mut velocities).join() {
-            *velocity = next.value.clone();
+    // Update current velocity
+    for (entity, next, velocity) in (&**entities, &*next_velocities, &mut *velocities).join() {
+        if !fixed(entity) {
+            flip_frame(velocity, next);
         }
+    }
 
-        // Compute next frames position + velocity
-        for (velocity, next_velocity, pose, next_pose) in
-            (&velocities, &mut next_velocities, &poses, &mut next_poses).join()
-        {
-            next_pose.value = BodyPose::new(
-                *pose.position() + velocity.linear * time.delta_seconds,
-                pose.rotation().clone(),
-            );
-            next_velocity.value = Velocity {
-                linear: velocity.linear,
+    // Compute next frames position + velocity
+    let bodies = (
+        &**entities,
+        &*velocities,
+        &*next_velocities,
+        &*poses,
+        &*next_poses,
+    ).join()
+        .map(|(entity, ..)| entity)
+        .collect::<Vec<_>>();
+    for entity in bodies {
+        let (velocity, pose) = match (velocities.get(entity), poses.get(entity)) {
+            (Some(velocity), Some(pose)) => (velocity, pose),
+            _ => continue,
+        };
+        let torque = forces
+            .get_mut(entity)
+            .map(|force| force.consume_torque())
+            .unwrap_or_else(Zero::zero);
+        if fixed(entity) {
+            if let Some(force) = forces.get_mut(entity) {
+                force.consume_force();
+            }
+            // the next frame pose is only written to if the body was moved, e.g. teleported
+            let moved = next_poses
+                .get(entity)
+                .map(|next| !same_pose(&next.value, pose))
+                .unwrap_or(false);
+            if moved {
+                if let Some(next_pose) = next_poses.get_mut(entity) {
+                    next_pose.value = pose.clone();
+                }
+            }
+            if let Some(next_velocity) = next_velocities.get_mut(entity) {
+                next_velocity.value = velocity.clone();
+            }
+            continue;
+        }
+        let (next_pose, next_velocity) =
+            match (next_poses.get_mut(entity), next_velocities.get_mut(entity)) {
+                (Some(next_pose), Some(next_velocity)) => (next_pose, next_velocity),
+                _ => continue,
             };
+        if is_kinematic(body_types, entity) {
+            // kinematic bodies only move with their velocity
+            if let Some(force) = forces.get_mut(entity) {
+                force.consume_force();
+            }
+            let (mut next, velocity) =
+                integrate_body(pose, velocity, P::Diff::zero(), None, None, delta_seconds);
+            if let Some(angular) = angular_velocities.get(entity) {
+                let (rotation, _) = integrate_rotation(
+                    pose.rotation(),
+                    angular.angular,
+                    Zero::zero(),
+                    None,
+                    None,
+                    delta_seconds,
+                );
+                next = BodyPose::new(*next.position(), rotation);
+            }
+            next_pose.value = next;
+            next_velocity.value = velocity;
+            continue;
+        }
+        // the accumulator is drained every frame, forces on bodies without mass are dropped
+        let force = forces
+            .get_mut(entity)
+            .map(|force| force.consume_force())
+            .unwrap_or_else(P::Diff::zero);
+        let force = match masses.get(entity) {
+            Some(_) => force,
+            None => P::Diff::zero(),
+        };
+        let (pose, velocity) = integrate_body(
+            pose,
+            velocity,
+            force,
+            masses.get(entity),
+            dampings.get(entity),
+            delta_seconds,
+        );
+        next_pose.value = pose;
+        next_velocity.value = velocity;
+        if let Some(angular) = angular_velocities.get_mut(entity) {
+            let (rotation, angular_velocity) = integrate_rotation(
+                next_pose.value.rotation(),
+                angular.angular,
+                torque,
+                inertias.get(entity),
+                dampings.get(entity),
+                delta_seconds,
+            );
+            next_pose.value = BodyPose::new(*next_pose.value.position(), rotation);
+            angular.angular = angular_velocity;
         }
     }
 }
+
+/// Check if two poses are at the same position, with approximately the same rotation
+fn same_pose<P, R>(a: &BodyPose<P, R>, b: &BodyPose<P, R>) -> bool
+where
+    P: EuclideanSpace<Scalar = Real>,
+    R: Rotation<P>,
+{
+    (*a.position() - *b.position()).is_zero()
+        && a.rotation()
+            .ulps_eq(b.rotation(), R::default_epsilon(), R::default_max_ulps())
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, One, Point2, Rotation, Vector2, Zero};
+    use shrev::{EventChannel, ReaderId};
+    use specs::{Entity, Join, RunNow, World};
+
+    use {NextFrame, Real};
+    use collide::{CollisionStrategy, Contact, ContactEvent};
+    use ecs::collide::Dormant;
+    use ecs::physics::prelude2d::*;
+
+    fn spin<S>(create: fn(ReaderId<ContactEvent2>) -> S)
+    where
+        S: for<'a> RunNow<'a>,
+    {
+        let mut world = World::new();
+        world_physics_register(&mut world);
+        world.write_resource::<DeltaTime>().delta_seconds = 0.5;
+        let pose = BodyPose2::new(Point2::new(1., 0.), Basis2::one());
+        let velocity = Velocity2 {
+            linear: Vector2::zero(),
+        };
+        let body: Entity = world
+            .create_entity()
+            .with(pose.clone())
+            .with(NextFrame { value: pose.clone() })
+            .with(velocity.clone())
+            .with(NextFrame { value: velocity })
+            .with(Mass::new(1.))
+            .with(ForceAccumulator2::new())
+            .with(AngularVelocity2::default())
+            .with(Inertia2::new(2.))
+            .build();
+        world
+            .write::<ForceAccumulator2>()
+            .get_mut(body)
+            .unwrap()
+            .add_force_at_point(Vector2::new(0., 2.), Point2::new(2., 0.), &pose);
+        let reader = world
+            .write_resource::<EventChannel<ContactEvent2>>()
+            .register_reader();
+        let mut solver = create(reader);
+        solver.run_now(&world.res);
+
+        // torque of 2 on an inertia of 2 for half a second
+        let angular = world.read::<AngularVelocity2>().get(body).unwrap().angular;
+        assert_ulps_eq!(angular, 0.5);
+        let next = world.read::<NextFrame<BodyPose2>>().get(body).unwrap().value.clone();
+        assert_ulps_eq!(
+            next.rotation().rotate_vector(Vector2::new(1., 0.)),
+            Vector2::new((0.25 as Real).cos(), (0.25 as Real).sin())
+        );
+        let next = world.read::<NextFrame<Velocity2>>().get(body).unwrap().value.clone();
+        assert_ulps_eq!(next.linear, Vector2::new(0., 1.));
+    }
+
+    #[test]
+    fn test_force_at_point_spins_body() {
+        spin(LinearContactSolverSystem2::new);
+        spin(SequentialImpulseSolverSystem2::new);
+    }
+
+    fn drop_massless_force<S>(create: fn(ReaderId<ContactEvent2>) -> S)
+    where
+        S: for<'a> RunNow<'a>,
+    {
+        let mut world = World::new();
+        world_physics_register(&mut world);
+        world.write_resource::<DeltaTime>().delta_seconds = 0.5;
+        let pose = BodyPose2::new(Point2::new(0., 0.), Basis2::one());
+        let velocity = Velocity2 {
+            linear: Vector2::zero(),
+        };
+        let body = world
+            .create_entity()
+            .with(pose.clone())
+            .with(NextFrame { value: pose })
+            .with(velocity.clone())
+            .with(NextFrame { value: velocity })
+            .with(ForceAccumulator2::new())
+            .build();
+        let reader = world
+            .write_resource::<EventChannel<ContactEvent2>>()
+            .register_reader();
+        let mut solver = create(reader);
+        for _ in 0..3 {
+            world
+                .write::<ForceAccumulator2>()
+                .get_mut(body)
+                .unwrap()
+                .add_force(Vector2::new(10., 0.));
+            solver.run_now(&world.res);
+            let forces = world.read::<ForceAccumulator2>();
+            assert_ulps_eq!(forces.get(body).unwrap().peek_force(), Vector2::zero());
+        }
+
+        // adding a mass later does not release the forces of the previous frames
+        world.write::<Mass>().insert(body, Mass::new(1.));
+        world
+            .write::<ForceAccumulator2>()
+            .get_mut(body)
+            .unwrap()
+            .add_force(Vector2::new(2., 0.));
+        solver.run_now(&world.res);
+        let next = world.read::<NextFrame<Velocity2>>().get(body).unwrap().value.clone();
+        assert_ulps_eq!(next.linear, Vector2::new(1., 0.));
+    }
+
+    #[test]
+    fn test_forces_on_massless_bodies_are_dropped() {
+        drop_massless_force(LinearContactSolverSystem2::new);
+        drop_massless_force(SequentialImpulseSolverSystem2::new);
+    }
+
+    fn sleep<S>(create: fn(ReaderId<ContactEvent2>) -> S)
+    where
+        S: for<'a> RunNow<'a>,
+    {
+        let mut world = World::new();
+        world_physics_register(&mut world);
+        world.write_resource::<DeltaTime>().delta_seconds = 1. / 60.;
+        let body = |world: &mut World, y: Real, velocity: Vector2<Real>| {
+            let pose = BodyPose2::new(Point2::new(0., y), Basis2::one());
+            let velocity = Velocity2 { linear: velocity };
+            world
+                .create_entity()
+                .with(pose.clone())
+                .with(NextFrame { value: pose })
+                .with(velocity.clone())
+                .with(NextFrame { value: velocity })
+                .with(Mass::new(1.))
+                .with(ForceAccumulator2::new())
+                .build()
+        };
+        let ground = world
+            .create_entity()
+            .with(BodyPose2::new(Point2::new(0., 0.), Basis2::one()))
+            .with(Mass::new(1.))
+            .with(BodyType::Static)
+            .build();
+        let resting = body(&mut world, 1., Vector2::zero());
+        let reader = world
+            .write_resource::<EventChannel<ContactEvent2>>()
+            .register_reader();
+        let mut solver = create(reader);
+        let contact = |bodies| {
+            ContactEvent::new(
+                bodies,
+                Contact::new_impl(CollisionStrategy::FullResolution, Vector2::new(0., 1.), 0.),
+            )
+        };
+        let step = |world: &mut World, solver: &mut S, mut contacts: Vec<ContactEvent2>| {
+            for force in (&mut world.write::<ForceAccumulator2>()).join() {
+                force.add_force(Vector2::new(0., -10.));
+            }
+            world
+                .write_resource::<EventChannel<ContactEvent2>>()
+                .drain_vec_write(&mut contacts)
+                .unwrap();
+            solver.run_now(&world.res);
+        };
+        let dormant = |world: &World, entity| world.read::<Dormant>().get(entity).is_some();
+
+        for _ in 0..5 {
+            step(&mut world, &mut solver, vec![contact((ground, resting))]);
+        }
+        assert!(!dormant(&world, resting));
+        for _ in 0..10 {
+            step(&mut world, &mut solver, vec![contact((ground, resting))]);
+        }
+        assert!(dormant(&world, resting));
+        let next = world.read::<NextFrame<Velocity2>>().get(resting).unwrap().value.clone();
+        assert_ulps_eq!(next.linear, Vector2::zero());
+
+        // a moving body landing on the sleeping body wakes it
+        let falling = body(&mut world, 2., Vector2::new(0., -1.));
+        step(
+            &mut world,
+            &mut solver,
+            vec![contact((ground, resting)), contact((resting, falling))],
+        );
+        assert!(!dormant(&world, resting));
+    }
+
+    #[test]
+    fn test_resting_island_sleeps_and_wakes() {
+        sleep(|reader| LinearContactSolverSystem2::new(reader).with_sleeping(0.1, 10));
+        sleep(|reader| SequentialImpulseSolverSystem2::new(reader).with_sleeping(0.1, 10));
+    }
+
+    fn events_and_sensors<S>(create: fn(ReaderId<ContactEvent2>) -> S)
+    where
+        S: for<'a> RunNow<'a>,
+    {
+        let mut world = World::new();
+        world_physics_register(&mut world);
+        world.write_resource::<DeltaTime>().delta_seconds = 0.5;
+        let ground = world
+            .create_entity()
+            .with(BodyPose2::new(Point2::new(0., 0.), Basis2::one()))
+            .with(Mass::new(1.))
+            .with(BodyType::Static)
+            .build();
+        let pose = BodyPose2::new(Point2::new(0., 1.), Basis2::one());
+        let velocity = Velocity2 {
+            linear: Vector2::new(0., -2.),
+        };
+        let falling = world
+            .create_entity()
+            .with(pose.clone())
+            .with(NextFrame { value: pose })
+            .with(velocity.clone())
+            .with(NextFrame { value: velocity })
+            .with(Mass::new(1.))
+            .with(ForceAccumulator2::new())
+            .with(ContactSensors::new().with_sensor("feet", 0))
+            .build();
+        let reader = world
+            .write_resource::<EventChannel<ContactEvent2>>()
+            .register_reader();
+        let mut impact_reader = world
+            .write_resource::<EventChannel<ImpactEvent2>>()
+            .register_reader();
+        let mut instability_reader = world
+            .write_resource::<EventChannel<InstabilityEvent2>>()
+            .register_reader();
+        world
+            .write_resource::<EventChannel<ContactEvent2>>()
+            .single_write(ContactEvent::new(
+                (ground, falling),
+                Contact::new_impl(CollisionStrategy::FullResolution, Vector2::new(0., 1.), 0.),
+            ));
+        create(reader).run_now(&world.res);
+
+        let impacts = world
+            .read_resource::<EventChannel<ImpactEvent2>>()
+            .lossy_read(&mut impact_reader)
+            .map(|event| event.speed)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![2.], impacts);
+        let instabilities = world
+            .read_resource::<EventChannel<InstabilityEvent2>>()
+            .lossy_read(&mut instability_reader)
+            .map(|event| (event.bodies, event.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![((ground, falling), InstabilityKind::Velocity)], instabilities);
+        let sensors = world.read::<ContactSensors>();
+        let sensors = sensors.get(falling).unwrap();
+        assert!(sensors.in_contact("feet"));
+        assert!(sensors.normal_force("feet") > 0.);
+    }
+
+    #[test]
+    fn test_impact_instability_and_sensors() {
+        events_and_sensors(|reader| {
+            LinearContactSolverSystem2::new(reader).with_instability_bounds(1., Real::INFINITY)
+        });
+        events_and_sensors(|reader| {
+            SequentialImpulseSolverSystem2::new(reader).with_instability_bounds(1., Real::INFINITY)
+        });
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "sequential impulse solver does not support")]
+    fn test_sequential_solver_rejects_arcade_bounce() {
+        let mut world = World::new();
+        world_physics_register(&mut world);
+        let body = |world: &mut World, y: Real| {
+            let pose = BodyPose2::new(Point2::new(0., y), Basis2::one());
+            let velocity = Velocity2 {
+                linear: Vector2::zero(),
+            };
+            world
+                .create_entity()
+                .with(pose.clone())
+                .with(NextFrame { value: pose })
+                .with(velocity.clone())
+                .with(NextFrame { value: velocity })
+                .with(Mass::new(1.))
+                .build()
+        };
+        let a = body(&mut world, 0.);
+        let b = body(&mut world, 1.);
+        world.write::<ArcadeBounce>().insert(b, ArcadeBounce::new(1.));
+        let reader = world
+            .write_resource::<EventChannel<ContactEvent2>>()
+            .register_reader();
+        world
+            .write_resource::<EventChannel<ContactEvent2>>()
+            .single_write(ContactEvent::new(
+                (a, b),
+                Contact::new_impl(CollisionStrategy::FullResolution, Vector2::new(0., 1.), 0.),
+            ));
+        SequentialImpulseSolverSystem2::new(reader).run_now(&world.res);
+    }
+}
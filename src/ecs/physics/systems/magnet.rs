@@ -0,0 +1,109 @@
+use std::fmt::Debug;
+use std::marker;
+
+use cgmath::{Array, EuclideanSpace, InnerSpace, Rotation};
+use collision::{Aabb, Contains, Discrete, SurfaceArea, Union};
+use collision::dbvt::{DiscreteVisitor, DynamicBoundingVolumeTree, TreeValue};
+use specs::{Entities, Entity, Fetch, Join, ReadStorage, System, WriteStorage};
+
+use {BodyPose, Real};
+use ecs::collide::{same_world, GetEntity, WorldId};
+use physics::{ForceAccumulator, LinearVector, Magnet, MagnetSusceptible};
+
+/// Magnet system.
+///
+/// For each [`Magnet`](../../physics/struct.Magnet.html), will query the spatial tree for nearby
+/// bodies with a [`MagnetSusceptible`](../../physics/struct.MagnetSusceptible.html) component, and
+/// add attraction or repulsion forces to the force accumulators of both the susceptible body and
+/// the magnet.
+///
+/// Requires the spatial sorting system to be running, so the tree is up to date.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+/// - `D`: Type of values stored in the DBVT
+pub struct MagnetSystem<P, R, D> {
+    m: marker::PhantomData<(P, R, D)>,
+}
+
+impl<P, R, D> MagnetSystem<P, R, D> {
+    /// Create a new magnet system
+    pub fn new() -> Self {
+        Self {
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<P, R, D> Default for MagnetSystem<P, R, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, P, R, D> System<'a> for MagnetSystem<P, R, D>
+where
+    P: EuclideanSpace<Scalar = Real> + Debug + Send + Sync + 'static,
+    P::Diff: LinearVector
+        + InnerSpace
+        + Array<Element = Real>
+        + Debug
+        + Send
+        + Sync
+        + 'static,
+    R: Rotation<P> + Send + Sync + 'static,
+    D: TreeValue + GetEntity + Send + Sync + 'static,
+    D::Bound: Aabb<Point = P, Diff = P::Diff, Scalar = Real>
+        + Discrete<D::Bound>
+        + Contains<D::Bound>
+        + Union<D::Bound, Output = D::Bound>
+        + SurfaceArea
+        + Clone
+        + Debug
+        + Send
+        + Sync
+        + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Magnet>,
+        ReadStorage<'a, MagnetSusceptible>,
+        ReadStorage<'a, BodyPose<P, R>>,
+        WriteStorage<'a, ForceAccumulator<P::Diff>>,
+        Fetch<'a, DynamicBoundingVolumeTree<D>>,
+        ReadStorage<'a, WorldId>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, magnets, susceptibles, poses, mut forces, tree, world_ids) = data;
+
+        let mut applied: Vec<(Entity, Entity, P::Diff)> = Vec::default();
+        for (magnet_entity, magnet, magnet_pose) in (&*entities, &magnets, &poses).join() {
+            let position = *magnet_pose.position();
+            let extent = P::Diff::from_value(magnet.range);
+            let bound = D::Bound::new(position + extent * -1., position + extent);
+            for (value, _) in tree.query(&mut DiscreteVisitor::<D::Bound, D>::new(&bound)) {
+                let entity = value.entity();
+                if entity == magnet_entity || !same_world(&world_ids, entity, magnet_entity) {
+                    continue;
+                }
+                if let (Some(susceptible), Some(pose)) = (susceptibles.get(entity), poses.get(entity))
+                {
+                    let force = magnet.force(susceptible, *pose.position() - position);
+                    applied.push((magnet_entity, entity, force));
+                }
+            }
+        }
+
+        for (magnet_entity, entity, force) in applied {
+            if let Some(accumulator) = forces.get_mut(entity) {
+                accumulator.add_force(force);
+            }
+            if let Some(accumulator) = forces.get_mut(magnet_entity) {
+                accumulator.add_force(force * -1.);
+            }
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::marker;
+
+use cgmath::{EuclideanSpace, Rotation};
+use collision::{Aabb, Primitive};
+use specs::{Entities, Join, ReadStorage, System, WriteStorage};
+
+use {BodyPose, Real};
+use collide::CollisionShape;
+use physics::{Density, Inertia, Mass, ShapeInertia, Volume};
+
+/// Mass recomputation system, keeps the mass and inertia of density-driven bodies consistent with
+/// their collision shape.
+///
+/// For all bodies with a [`Density`](../../physics/struct.Density.html), the
+/// [`Mass`](../../physics/struct.Mass.html) and [`Inertia`](../../physics/struct.Inertia.html)
+/// are recomputed from the collision shape whenever the shape or the density is changed, e.g. by
+/// growth mechanics or destruction. Bodies without a mass or inertia get one. The inertia is
+/// computed around the position of the body.
+///
+/// Changes are tracked with the modification flags of the `CollisionShape` and `Density`
+/// storages. The flags are never cleared by the library, clear them after the frame, or the
+/// flagged bodies are recomputed every frame.
+///
+/// Should run before the contact solver.
+///
+/// ### Type parameters:
+///
+/// - `P`: Collision primitive type
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct MassRecomputeSystem<P, R> {
+    m: marker::PhantomData<(P, R)>,
+}
+
+impl<P, R> MassRecomputeSystem<P, R> {
+    /// Create a new mass recomputation system
+    pub fn new() -> Self {
+        Self {
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<P, R> Default for MassRecomputeSystem<P, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, P, R> System<'a> for MassRecomputeSystem<P, R>
+where
+    P: Primitive + ShapeInertia + Send + Sync + 'static,
+    P::Aabb: Aabb<Scalar = Real> + Send + Sync + 'static,
+    P::Point: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Vector: Send + Sync + 'static,
+    R: Rotation<P::Point> + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Density>,
+        ReadStorage<'a, CollisionShape<P, BodyPose<P::Point, R>>>,
+        WriteStorage<'a, Mass>,
+        WriteStorage<'a, Inertia<P::Vector>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, densities, shapes, mut masses, mut inertias) = data;
+        let mut modified = (&*entities, (&shapes).open().1, &densities)
+            .join()
+            .map(|(entity, _, _)| entity)
+            .chain(
+                (&*entities, &shapes, (&densities).open().1)
+                    .join()
+                    .map(|(entity, _, _)| entity),
+            )
+            .collect::<Vec<_>>();
+        modified.sort();
+        modified.dedup();
+        for entity in modified {
+            let (density, shape) = match (densities.get(entity), shapes.get(entity)) {
+                (Some(density), Some(shape)) => (density, shape),
+                _ => continue,
+            };
+            masses.insert(entity, density.mass(shape.volume()));
+            inertias.insert(entity, P::shape_inertia(shape, density.density));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, Point2, Rad, Rotation2};
+    use collision::primitive::{Primitive2, Rectangle};
+    use specs::{Entity, Join, RunNow, World};
+
+    use Real;
+    use ecs::physics::prelude2d::*;
+
+    fn rectangle(width: Real, height: Real) -> CollisionShape2<BodyPose2> {
+        CollisionShape2::<BodyPose2>::new_simple(
+            CollisionStrategy::FullResolution,
+            CollisionMode::Discrete,
+            Primitive2::from(Rectangle::new(width, height)),
+        )
+    }
+
+    fn body(world: &mut World, density: Real) -> Entity {
+        world
+            .create_entity()
+            .with(BodyPose2::new(Point2::new(0., 0.), Basis2::from_angle(Rad(0.))))
+            .with(rectangle(2., 4.))
+            .with(Density::new(density))
+            .build()
+    }
+
+    fn clear_flags(world: &World) {
+        (&mut world.write::<CollisionShape2<BodyPose2>>())
+            .open()
+            .1
+            .clear_flags();
+        (&mut world.write::<Density>()).open().1.clear_flags();
+    }
+
+    fn assert_mass_and_inertia(world: &World, entity: Entity, mass: Real, inverse_inertia: Real) {
+        assert_ulps_eq!(mass, world.read::<Mass>().get(entity).unwrap().mass());
+        assert_ulps_eq!(
+            inverse_inertia,
+            world.read::<Inertia2>().get(entity).unwrap().inverse_inertia()
+        );
+    }
+
+    #[test]
+    fn test_recomputes_only_modified_bodies() {
+        let mut world = World::new();
+        world_physics_register(&mut world);
+        let grown = body(&mut world, 3.);
+        let untouched = body(&mut world, 3.);
+        let mut system = MassRecomputeSystem2::new();
+        system.run_now(&world.res);
+        // 2 x 4 rectangle, mass 24, moment 24 * (4 + 16) / 12 = 40
+        assert_mass_and_inertia(&world, untouched, 24., 1. / 40.);
+        clear_flags(&world);
+
+        // manual overrides survive frames without modifications
+        world.write::<Mass>().insert(untouched, Mass::new(1.));
+        *world
+            .write::<CollisionShape2<BodyPose2>>()
+            .get_mut(grown)
+            .unwrap() = rectangle(4., 4.);
+        system.run_now(&world.res);
+        // 4 x 4 square, mass 48, moment 48 * (16 + 16) / 12 = 128
+        assert_mass_and_inertia(&world, grown, 48., 1. / 128.);
+        assert_ulps_eq!(1., world.read::<Mass>().get(untouched).unwrap().mass());
+        clear_flags(&world);
+
+        world.write::<Density>().get_mut(untouched).unwrap().density = 6.;
+        system.run_now(&world.res);
+        assert_mass_and_inertia(&world, untouched, 48., 1. / 80.);
+    }
+}
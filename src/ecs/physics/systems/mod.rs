@@ -1,5 +1,57 @@
 //! Physics systems
 
+pub use self::body_constraint::*;
+pub use self::body_handle::*;
+pub use self::buoyancy::*;
+pub use self::collision_response::*;
+pub use self::delta_time::*;
+pub use self::diagnostics::*;
+pub use self::distance_joint::*;
+pub use self::gravity::*;
+pub use self::joint_cleanup::*;
+pub use self::joint_collision::*;
+pub use self::joint_solver::*;
+pub use self::ground_friction::*;
 pub use self::linear_impulse::*;
+pub use self::magnet::*;
+pub use self::mass_recompute::*;
+pub use self::motion_clamp::*;
+pub use self::next_frame::*;
+pub use self::path::*;
+pub use self::pid::*;
+pub use self::pose_correction::*;
+pub use self::pose_history::*;
+pub use self::sequential_impulse::*;
+pub use self::spring::*;
+pub use self::transform_sync::*;
+#[cfg(feature = "debug")]
+pub use self::validation::*;
+pub use self::wheel::*;
 
+mod body_constraint;
+mod body_handle;
+mod buoyancy;
+mod collision_response;
+mod delta_time;
+mod diagnostics;
+mod distance_joint;
+mod gravity;
+mod joint_cleanup;
+mod joint_collision;
+mod joint_solver;
+mod ground_friction;
 mod linear_impulse;
+mod magnet;
+mod mass_recompute;
+mod motion_clamp;
+mod next_frame;
+mod path;
+mod pid;
+mod pose_correction;
+mod pose_history;
+mod sequential_impulse;
+mod spring;
+mod transform_sync;
+#[cfg(feature = "debug")]
+mod validation;
+mod wheel;
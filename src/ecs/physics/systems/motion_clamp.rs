@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::marker;
+
+use cgmath::{Array, EuclideanSpace, InnerSpace, Rotation, VectorSpace, Zero};
+use collision::{Aabb, Primitive};
+use specs::{Entities, Entity, Join, ReadStorage, System, WriteStorage};
+
+use {BodyPose, NextFrame, Real};
+use collide::{CollisionMode, CollisionShape};
+
+/// Motion clamping system, a cheap guard against tunneling.
+///
+/// For bodies with a continuous collision shape, the distance moved per step is clamped to a
+/// fraction of the smallest extent of the shape's bounding box. The remainder of the motion is
+/// carried over, and applied in the following steps, so fast bodies still cover the same distance
+/// over time, but never skip past thin geometry in a single step.
+///
+/// Should run after the contact solver, which computes the next frame poses.
+///
+/// ### Type parameters:
+///
+/// - `P`: Collision primitive type
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct MotionClampSystem<P, R>
+where
+    P: Primitive,
+{
+    fraction: Real,
+    remainders: HashMap<Entity, <P::Point as EuclideanSpace>::Diff>,
+    m: marker::PhantomData<R>,
+}
+
+impl<P, R> MotionClampSystem<P, R>
+where
+    P: Primitive,
+{
+    /// Create a new motion clamping system.
+    ///
+    /// # Parameters:
+    ///
+    /// - `fraction`: Fraction of the smallest shape extent a body is allowed to move per step
+    pub fn new(fraction: Real) -> Self {
+        Self {
+            fraction,
+            remainders: HashMap::default(),
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<P, R> Default for MotionClampSystem<P, R>
+where
+    P: Primitive,
+{
+    fn default() -> Self {
+        Self::new(0.5)
+    }
+}
+
+/// Smallest extent of a bounding box, along any axis
+fn smallest_extent<A>(bound: &A) -> Real
+where
+    A: Aabb<Scalar = Real>,
+    A::Diff: Array<Element = Real>,
+{
+    let dim = bound.dim();
+    let dimensions = A::Diff::from_value(1.).sum() as usize;
+    (1..dimensions).fold(dim[0], |extent, i| extent.min(dim[i]))
+}
+
+impl<'a, P, R> System<'a> for MotionClampSystem<P, R>
+where
+    P: Primitive + Send + Sync + 'static,
+    P::Aabb: Aabb<Scalar = Real> + Send + Sync + 'static,
+    <P::Aabb as Aabb>::Diff: Array<Element = Real>,
+    P::Point: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    <P::Point as EuclideanSpace>::Diff: VectorSpace<Scalar = Real>
+        + InnerSpace
+        + Debug
+        + Send
+        + Sync
+        + 'static,
+    R: Rotation<P::Point> + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, BodyPose<P::Point, R>>,
+        WriteStorage<'a, NextFrame<BodyPose<P::Point, R>>>,
+        ReadStorage<'a, CollisionShape<P, BodyPose<P::Point, R>>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, poses, mut next_poses, shapes) = data;
+        let mut remainders = HashMap::default();
+        for (entity, pose, next_pose, shape) in
+            (&*entities, &poses, &mut next_poses, &shapes).join()
+        {
+            if *shape.mode() != CollisionMode::Continuous {
+                continue;
+            }
+            let motion = (*next_pose.value.position() - *pose.position())
+                + self.remainders
+                    .get(&entity)
+                    .cloned()
+                    .unwrap_or_else(Zero::zero);
+            let max_distance = smallest_extent(shape.base_bound()) * self.fraction;
+            let motion = if motion.magnitude2() > max_distance * max_distance {
+                let clamped = motion.normalize_to(max_distance);
+                remainders.insert(entity, motion - clamped);
+                clamped
+            } else {
+                motion
+            };
+            next_pose.value = BodyPose::new(
+                *pose.position() + motion,
+                *next_pose.value.rotation(),
+            );
+        }
+        self.remainders = remainders;
+    }
+}
@@ -0,0 +1,79 @@
+use std::marker;
+
+use cgmath::{EuclideanSpace, Rotation, VectorSpace};
+use specs::{Entities, Fetch, Join, LazyUpdate, ReadStorage, System};
+
+use {BodyPose, NextFrame, Real};
+use physics::Velocity;
+
+/// Next frame setup system.
+///
+/// Will insert any missing `NextFrame<BodyPose>` and `NextFrame<Velocity>` components on bodies
+/// with a [`Velocity`](../../physics/struct.Velocity.html), initialized with the current pose and
+/// velocity. Without both of them the contact solver will never move the body. Bodies without a
+/// velocity are considered static, and are left alone.
+///
+/// The components are inserted through `LazyUpdate`, so the system only reads the storages, and
+/// does not conflict with the other systems. New bodies are set up when `World::maintain` runs,
+/// and start moving the frame after.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct NextFrameSetupSystem<P, R> {
+    m: marker::PhantomData<(P, R)>,
+}
+
+impl<P, R> NextFrameSetupSystem<P, R> {
+    /// Create a new next frame setup system
+    pub fn new() -> Self {
+        Self {
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<P, R> Default for NextFrameSetupSystem<P, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, P, R> System<'a> for NextFrameSetupSystem<P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: VectorSpace<Scalar = Real> + Send + Sync + 'static,
+    R: Rotation<P> + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, BodyPose<P, R>>,
+        ReadStorage<'a, Velocity<P::Diff>>,
+        ReadStorage<'a, NextFrame<BodyPose<P, R>>>,
+        ReadStorage<'a, NextFrame<Velocity<P::Diff>>>,
+        Fetch<'a, LazyUpdate>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, poses, velocities, next_poses, next_velocities, lazy) = data;
+        for (entity, pose, velocity) in (&*entities, &poses, &velocities).join() {
+            if next_poses.get(entity).is_none() {
+                lazy.insert(
+                    entity,
+                    NextFrame {
+                        value: pose.clone(),
+                    },
+                );
+            }
+            if next_velocities.get(entity).is_none() {
+                lazy.insert(
+                    entity,
+                    NextFrame {
+                        value: velocity.clone(),
+                    },
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::marker;
+
+use cgmath::{EuclideanSpace, InnerSpace, Rotation, Zero};
+use specs::{Entities, Fetch, Join, ReadStorage, System, WriteStorage};
+
+use {BodyPose, NextFrame, Real};
+use ecs::collide::{is_stepped, SteppedWorld, WorldId};
+use ecs::physics::resources::DeltaTime;
+use physics::{PathFollower, Velocity};
+
+/// Path following system, moves kinematic bodies along their paths.
+///
+/// For all bodies with a [`PathFollower`](../../physics/struct.PathFollower.html), the follower is
+/// advanced by the frame time, and the next frame pose is set to the new position on the path.
+/// The next frame velocity is set to the velocity needed to get there from the current pose, so
+/// the contact solver sees the correct velocity of the moving body. Followers outside the
+/// [`SteppedWorld`](../collide/struct.SteppedWorld.html), if present, are not advanced.
+///
+/// Should run after the contact solver has set up the next frame, and before the next collision
+/// detection.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct PathFollowSystem<P, R> {
+    m: marker::PhantomData<(P, R)>,
+}
+
+impl<P, R> PathFollowSystem<P, R> {
+    /// Create a new path following system
+    pub fn new() -> Self {
+        Self {
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<P, R> Default for PathFollowSystem<P, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, P, R> System<'a> for PathFollowSystem<P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: InnerSpace + Send + Sync + 'static,
+    R: Rotation<P> + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        Fetch<'a, DeltaTime>,
+        WriteStorage<'a, PathFollower<P>>,
+        ReadStorage<'a, BodyPose<P, R>>,
+        WriteStorage<'a, NextFrame<BodyPose<P, R>>>,
+        WriteStorage<'a, NextFrame<Velocity<P::Diff>>>,
+        ReadStorage<'a, WorldId>,
+        Option<Fetch<'a, SteppedWorld>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            time,
+            mut followers,
+            poses,
+            mut next_poses,
+            mut next_velocities,
+            world_ids,
+            stepped,
+        ) = data;
+        for (entity, follower, pose, next_pose, next_velocity) in (
+            &*entities,
+            &mut followers,
+            &poses,
+            &mut next_poses,
+            &mut next_velocities,
+        ).join()
+        {
+            if !is_stepped(stepped.as_deref(), &world_ids, entity) {
+                continue;
+            }
+            let position = follower.advance(time.delta_seconds);
+            next_velocity.value.linear = if time.delta_seconds > 0. {
+                (position - *pose.position()) / time.delta_seconds
+            } else {
+                P::Diff::zero()
+            };
+            next_pose.value = BodyPose::new(position, *pose.rotation());
+        }
+    }
+}
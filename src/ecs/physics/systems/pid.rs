@@ -0,0 +1,231 @@
+use std::marker;
+
+use cgmath::{EuclideanSpace, InnerSpace, Rotation, Zero};
+use specs::{Entities, Entity, Fetch, Join, ReadStorage, System, WriteStorage};
+
+use {BodyPose, Real};
+use ecs::collide::{is_stepped, SteppedWorld, WorldId};
+use ecs::physics::resources::DeltaTime;
+use physics::{ForceAccumulator, JointAngleController, JointControl, LinearVector,
+              OrientationController, OrientationError, PidController, RotationalVector, Velocity,
+              WheelJoint2};
+
+/// PID control system.
+///
+/// Will add the output force of all [`PidController`](../../physics/struct.PidController.html)
+/// components to the force accumulators of the bodies. Bodies without a velocity are treated as
+/// being at rest. Bodies outside the [`SteppedWorld`](../collide/struct.SteppedWorld.html), if
+/// present, are skipped, so their controllers do not accumulate any error.
+///
+/// Should run before the contact solver.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct PidControlSystem<P, R> {
+    m: marker::PhantomData<(P, R)>,
+}
+
+impl<P, R> PidControlSystem<P, R> {
+    /// Create a new PID control system
+    pub fn new() -> Self {
+        Self {
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<P, R> Default for PidControlSystem<P, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, P, R> System<'a> for PidControlSystem<P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: LinearVector + InnerSpace + Send + Sync + 'static,
+    R: Rotation<P> + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        Fetch<'a, DeltaTime>,
+        ReadStorage<'a, BodyPose<P, R>>,
+        ReadStorage<'a, Velocity<P::Diff>>,
+        WriteStorage<'a, PidController<P>>,
+        WriteStorage<'a, ForceAccumulator<P::Diff>>,
+        ReadStorage<'a, WorldId>,
+        Option<Fetch<'a, SteppedWorld>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, time, poses, velocities, mut controllers, mut forces, world_ids, stepped) =
+            data;
+        for (entity, pose, controller, force) in
+            (&*entities, &poses, &mut controllers, &mut forces).join()
+        {
+            if !is_stepped(stepped.as_deref(), &world_ids, entity) {
+                continue;
+            }
+            let velocity = velocities
+                .get(entity)
+                .map(|v| v.linear)
+                .unwrap_or_else(P::Diff::zero);
+            force.add_force(controller.control_force(
+                *pose.position(),
+                velocity,
+                time.delta_seconds,
+            ));
+        }
+    }
+}
+
+/// Orientation control system.
+///
+/// Will add the output torque of all
+/// [`OrientationController`](../../physics/struct.OrientationController.html) components to the
+/// force accumulators of the bodies.
+///
+/// Should run before the contact solver.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct OrientationControlSystem<P, R> {
+    m: marker::PhantomData<(P, R)>,
+}
+
+impl<P, R> OrientationControlSystem<P, R> {
+    /// Create a new orientation control system
+    pub fn new() -> Self {
+        Self {
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<P, R> Default for OrientationControlSystem<P, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, P, R> System<'a> for OrientationControlSystem<P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: RotationalVector + Send + Sync + 'static,
+    R: Rotation<P> + OrientationError<<P::Diff as LinearVector>::Angular> + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        Fetch<'a, DeltaTime>,
+        ReadStorage<'a, BodyPose<P, R>>,
+        WriteStorage<'a, OrientationController<P::Diff, R>>,
+        WriteStorage<'a, ForceAccumulator<P::Diff>>,
+        ReadStorage<'a, WorldId>,
+        Option<Fetch<'a, SteppedWorld>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, time, poses, mut controllers, mut forces, world_ids, stepped) = data;
+        for (entity, pose, controller, force) in
+            (&*entities, &poses, &mut controllers, &mut forces).join()
+        {
+            if !is_stepped(stepped.as_deref(), &world_ids, entity) {
+                continue;
+            }
+            force.add_torque(controller.control_torque(pose.rotation(), time.delta_seconds));
+        }
+    }
+}
+
+/// Joint angle control system for 2D.
+///
+/// Will command the output torque of all
+/// [`JointAngleController`](../../physics/struct.JointAngleController.html) components through
+/// the [`JointControl`](../../physics/struct.JointControl.html) on the same entity. Joints outside
+/// the [`SteppedWorld`](../collide/struct.SteppedWorld.html), if present, are skipped.
+///
+/// Should run before the [`WheelJointSystem2`](struct.WheelJointSystem2.html).
+pub struct JointAngleControlSystem2;
+
+impl<'a> System<'a> for JointAngleControlSystem2 {
+    type SystemData = (
+        Entities<'a>,
+        Fetch<'a, DeltaTime>,
+        ReadStorage<'a, WheelJoint2<Entity>>,
+        WriteStorage<'a, JointAngleController>,
+        WriteStorage<'a, JointControl>,
+        ReadStorage<'a, WorldId>,
+        Option<Fetch<'a, SteppedWorld>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, time, joints, mut controllers, mut controls, world_ids, stepped) = data;
+        for (entity, joint, controller, control) in
+            (&*entities, &joints, &mut controllers, &mut controls).join()
+        {
+            if !is_stepped(stepped.as_deref(), &world_ids, entity) {
+                continue;
+            }
+            control.command_torque(controller.control_torque(joint.angle(), time.delta_seconds));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{InnerSpace, Matrix3, One, Point3, Quaternion, Rad, Rotation3, SquareMatrix,
+                 Vector3, Zero};
+    use shrev::EventChannel;
+    use specs::{RunNow, World};
+
+    use NextFrame;
+    use ecs::physics::prelude3d::*;
+
+    #[test]
+    fn test_orientation_controller_turns_body() {
+        let mut world = World::new();
+        world_physics_register(&mut world);
+        world.write_resource::<DeltaTime>().delta_seconds = 1. / 60.;
+        let pose = BodyPose3::new(Point3::new(0., 0., 0.), Quaternion::one());
+        let velocity = Velocity3 {
+            linear: Vector3::zero(),
+        };
+        let target = Quaternion::from_angle_x(Rad(0.5));
+        let body = world
+            .create_entity()
+            .with(pose.clone())
+            .with(NextFrame { value: pose })
+            .with(velocity.clone())
+            .with(NextFrame { value: velocity })
+            .with(Mass::new(1.))
+            .with(ForceAccumulator3::new())
+            .with(AngularVelocity3::default())
+            .with(Inertia3::new(Matrix3::identity()))
+            .with(OrientationController3::new(target, 2., 1.))
+            .build();
+        let reader = world
+            .write_resource::<EventChannel<ContactEvent3>>()
+            .register_reader();
+        let mut control = OrientationControlSystem3::new();
+        let mut solver = LinearContactSolverSystem3::new(reader);
+        let error = |world: &World| {
+            world
+                .read::<BodyPose3>()
+                .get(body)
+                .unwrap()
+                .rotation()
+                .orientation_error(&target)
+                .magnitude()
+        };
+        assert_ulps_eq!(error(&world), 0.5);
+        for _ in 0..120 {
+            control.run_now(&world.res);
+            solver.run_now(&world.res);
+        }
+        assert!(error(&world) < 0.01);
+    }
+}
@@ -0,0 +1,64 @@
+use std::marker;
+
+use cgmath::{EuclideanSpace, Rotation, VectorSpace};
+use specs::{Entities, Join, System, WriteStorage};
+
+use {BodyPose, NextFrame, Real};
+use physics::PoseCorrection;
+
+/// Pose correction system, blends bodies towards authoritative positions.
+///
+/// For all bodies with an active [`PoseCorrection`](../../physics/struct.PoseCorrection.html),
+/// the offset for the frame is added to both the current and the next frame pose, so the velocity
+/// of the body is unaffected, and the local simulation continues from the corrected position.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct PoseCorrectionSystem<P, R> {
+    m: marker::PhantomData<(P, R)>,
+}
+
+impl<P, R> PoseCorrectionSystem<P, R> {
+    /// Create a new pose correction system
+    pub fn new() -> Self {
+        Self {
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<P, R> Default for PoseCorrectionSystem<P, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, P, R> System<'a> for PoseCorrectionSystem<P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: VectorSpace<Scalar = Real> + Send + Sync + 'static,
+    R: Rotation<P> + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, PoseCorrection<P::Diff>>,
+        WriteStorage<'a, BodyPose<P, R>>,
+        WriteStorage<'a, NextFrame<BodyPose<P, R>>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, mut corrections, mut poses, mut next_poses) = data;
+        for (entity, correction, pose) in (&*entities, &mut corrections, &mut poses).join() {
+            if !correction.is_active() {
+                continue;
+            }
+            let offset = correction.next_offset();
+            *pose += offset;
+            if let Some(next_pose) = next_poses.get_mut(entity) {
+                next_pose.value += offset;
+            }
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::marker;
+
+use cgmath::{EuclideanSpace, Rotation, VectorSpace};
+use specs::{Entities, Join, ReadStorage, System, WriteStorage};
+
+use {BodyPose, Real};
+use physics::{PoseHistory, Velocity};
+
+/// Pose history system.
+///
+/// Will record the current pose and velocity of all bodies with a
+/// [`PoseHistory`](../../physics/struct.PoseHistory.html). Should run after the contact solver.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct PoseHistorySystem<P, R> {
+    m: marker::PhantomData<(P, R)>,
+}
+
+impl<P, R> PoseHistorySystem<P, R> {
+    /// Create a new pose history system
+    pub fn new() -> Self {
+        Self {
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<P, R> Default for PoseHistorySystem<P, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, P, R> System<'a> for PoseHistorySystem<P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: VectorSpace<Scalar = Real> + Send + Sync + 'static,
+    R: Rotation<P> + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, BodyPose<P, R>>,
+        ReadStorage<'a, Velocity<P::Diff>>,
+        WriteStorage<'a, PoseHistory<P, R>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, poses, velocities, mut histories) = data;
+        for (entity, pose, history) in (&*entities, &poses, &mut histories).join() {
+            history.record(pose.clone(), velocities.get(entity).cloned());
+        }
+    }
+}
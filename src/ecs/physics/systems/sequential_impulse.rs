@@ -0,0 +1,417 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::marker;
+use std::mem;
+
+use cgmath::{EuclideanSpace, InnerSpace, Rotation};
+use shrev::{EventChannel, ReaderId};
+use specs::{Entities, Entity, Fetch, FetchMut, Join, ReadStorage, System, WriteStorage};
+
+use Real;
+use collide::ContactEvent;
+use ecs::collide::{steps_pair, SilentContacts};
+use ecs::physics::resources::{ContactIslands, ContactSolverState, DeltaTime, MaterialRegistry,
+                              ResolvedContacts, WarmStartImpulses};
+use physics::{AngularRotation, ArcadeBounce, ContactImpulse, ContactSensors, DistanceConstraint,
+              ImpactEvent, InstabilityEvent, Mass, MaterialHandle, PositionCorrection, Rolling,
+              RotationalVector, SequentialImpulseSolver, SolverBody, SolverGroup};
+use super::linear_impulse::{advance_bodies, check_stability, contact_material, find_islands,
+                            impact, largest_change, IslandSleep, SolverBodies, SolverBodyData,
+                            DEFAULT_IMPACT_THRESHOLD};
+
+/// Sequential impulse contact solver system.
+///
+/// An alternative to the [`LinearContactSolverSystem`](struct.LinearContactSolverSystem.html)
+/// for scenes with stacks and piles. All contacts of the frame are resolved together by a
+/// [`SequentialImpulseSolver`](../../physics/struct.SequentialImpulseSolver.html), which iterates
+/// until the impulses converge, and warm starts from the impulses of the last frame. Like the
+/// linear contact solver, the poses and velocities are updated, and the next frames poses and
+/// velocities are set up, consuming any accumulated forces.
+///
+/// Contacts with bodies that have no [`Mass`](../../physics/struct.Mass.html) are collision only,
+/// and are never resolved. Bodies that are [`Frozen`](../collide/struct.Frozen.html),
+/// [`Dormant`](../collide/struct.Dormant.html) or static, or that have no next frame pose or
+/// velocity, act as immovable obstacles. Kinematic bodies act as immovable obstacles moving with
+/// their velocity, see [`BodyType`](../../physics/enum.BodyType.html).
+///
+/// Penetration correction, materials, silent contacts, contact impulses, contact sensors, impact
+/// events, instability events, resolved contacts, contact islands and sleeping are supported,
+/// using the same components and resources as the linear contact solver. The contacts of each
+/// island are iterated until the impulses of that island converge. Impact events are emitted
+/// using the velocities from before the contacts are resolved. The normal force recorded by
+/// [`ContactSensors`](../../physics/struct.ContactSensors.html), and the velocity and position
+/// changes checked against the instability bounds, are those of the whole frame instead of a
+/// single resolution.
+///
+/// [`ArcadeBounce`](../../physics/struct.ArcadeBounce.html),
+/// [`Rolling`](../../physics/struct.Rolling.html) and
+/// [`SolverGroup`](../../physics/struct.SolverGroup.html) components are not supported. In debug
+/// builds, the system panics if a contact involves a body with any of them, use the linear
+/// contact solver for those bodies. There is no contact budget, all contacts of the frame are
+/// resolved.
+///
+/// If a [`WarmStartImpulses`](struct.WarmStartImpulses.html) resource is present, the impulses
+/// used for warm starting are kept in it between steps, instead of in the solver, so they can be
+/// captured and restored along with the bodies, e.g. when rewinding the simulation. Likewise, if
+/// a [`ContactSolverState`](struct.ContactSolverState.html) resource is present, the sleeping
+/// state is kept in it.
+///
+/// All [`DistanceConstraint`](../../physics/struct.DistanceConstraint.html) components are solved
+/// together with the contacts. Like joints, they are placed on their own entities. Constrained
+/// bodies without a `Mass`, or without a next frame pose or velocity, act as fixed anchors.
+/// Constraints between bodies in different simulation worlds, or outside the
+/// [`SteppedWorld`](../collide/struct.SteppedWorld.html), are ignored.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct SequentialImpulseSolverSystem<P, R>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    contact_reader: ReaderId<ContactEvent<Entity, P>>,
+    solver: SequentialImpulseSolver<Entity, P>,
+    sleep: Option<IslandSleep>,
+    impact_threshold: Real,
+    max_velocity_change: Real,
+    max_position_change: Real,
+    m: marker::PhantomData<R>,
+}
+
+impl<P, R> SequentialImpulseSolverSystem<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace + Debug,
+{
+    /// Create a sequential impulse solver system, with the default solver settings, see
+    /// [`SequentialImpulseSolver`](../../physics/struct.SequentialImpulseSolver.html)
+    pub fn new(contact_reader: ReaderId<ContactEvent<Entity, P>>) -> Self {
+        Self {
+            contact_reader,
+            solver: SequentialImpulseSolver::new(),
+            sleep: None,
+            impact_threshold: DEFAULT_IMPACT_THRESHOLD,
+            max_velocity_change: Real::INFINITY,
+            max_position_change: Real::INFINITY,
+            m: marker::PhantomData,
+        }
+    }
+
+    /// Set the maximum number of iterations per frame
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.solver = self.solver.with_iterations(iterations);
+        self
+    }
+
+    /// Set the impulse change below which the iterations are considered converged
+    pub fn with_tolerance(mut self, tolerance: Real) -> Self {
+        self.solver = self.solver.with_tolerance(tolerance);
+        self
+    }
+
+    /// Set the fraction of the impulses of the last frame used as starting impulses, zero
+    /// disables warm starting
+    pub fn with_warm_starting(mut self, factor: Real) -> Self {
+        self.solver = self.solver.with_warm_starting(factor);
+        self
+    }
+
+    /// Set the relative speed along the contact normal below which contacts are resting contacts,
+    /// and are resolved without restitution
+    pub fn with_resting_threshold(mut self, resting_threshold: Real) -> Self {
+        self.solver = self.solver.with_resting_threshold(resting_threshold);
+        self
+    }
+
+    /// Put contact islands to sleep when the linear and angular speeds of all their bodies have
+    /// been below the threshold for the given number of frames, see the
+    /// [`LinearContactSolverSystem`](struct.LinearContactSolverSystem.html)
+    pub fn with_sleeping(mut self, threshold: Real, frames: usize) -> Self {
+        self.sleep = Some(IslandSleep::new(threshold, frames));
+        self
+    }
+
+    /// Set the minimum relative speed along the contact normal for emitting impact events
+    pub fn with_impact_threshold(mut self, impact_threshold: Real) -> Self {
+        self.impact_threshold = impact_threshold;
+        self
+    }
+
+    /// Set the sanity bounds for the velocity and position change of a body from resolving the
+    /// contacts of a frame, above which an instability event is emitted for each of its contacts.
+    /// By default the bounds are infinite, and only changes that are not finite are reported.
+    pub fn with_instability_bounds(
+        mut self,
+        max_velocity_change: Real,
+        max_position_change: Real,
+    ) -> Self {
+        self.max_velocity_change = max_velocity_change;
+        self.max_position_change = max_position_change;
+        self
+    }
+
+    /// Get the solver, e.g. to inspect the number of iterations used in the last frame
+    pub fn solver(&self) -> &SequentialImpulseSolver<Entity, P> {
+        &self.solver
+    }
+
+    /// Get the solver for modification, e.g. to clear the cached impulses after teleporting
+    /// bodies, when there is no [`WarmStartImpulses`](struct.WarmStartImpulses.html) resource
+    pub fn solver_mut(&mut self) -> &mut SequentialImpulseSolver<Entity, P> {
+        &mut self.solver
+    }
+}
+
+impl<'a, P, R> System<'a> for SequentialImpulseSolverSystem<P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'a + 'static,
+    P::Diff: RotationalVector + Debug + Send + Sync + 'static,
+    R: AngularRotation<P::Diff> + Rotation<P> + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        Fetch<'a, DeltaTime>,
+        Option<Fetch<'a, PositionCorrection>>,
+        Fetch<'a, EventChannel<ContactEvent<Entity, P>>>,
+        Option<Fetch<'a, SilentContacts<P>>>,
+        Option<Fetch<'a, MaterialRegistry>>,
+        ReadStorage<'a, ArcadeBounce>,
+        ReadStorage<'a, Rolling>,
+        ReadStorage<'a, MaterialHandle>,
+        ReadStorage<'a, SolverGroup>,
+        Option<FetchMut<'a, EventChannel<ImpactEvent<Entity, P>>>>,
+        Option<FetchMut<'a, EventChannel<InstabilityEvent<Entity, P>>>>,
+        Option<FetchMut<'a, ResolvedContacts<P>>>,
+        Option<FetchMut<'a, ContactIslands>>,
+        Option<FetchMut<'a, WarmStartImpulses<P>>>,
+        Option<FetchMut<'a, ContactSolverState<P>>>,
+        SolverBodyData<'a, P, R>,
+        WriteStorage<'a, ContactImpulse<P::Diff>>,
+        WriteStorage<'a, DistanceConstraint<Entity>>,
+        WriteStorage<'a, ContactSensors>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            time,
+            position_correction,
+            contacts,
+            silent,
+            materials,
+            bounces,
+            rolling,
+            handles,
+            groups,
+            mut impacts,
+            mut instabilities,
+            mut resolved,
+            mut islands,
+            mut warm_start,
+            mut state,
+            bodies,
+            mut impulses,
+            mut distances,
+            mut sensors,
+        ) = data;
+        let mut bodies = SolverBodies::new(bodies);
+        if let (Some(sleep), Some(state)) = (self.sleep.as_mut(), state.as_mut()) {
+            sleep.swap_state(state);
+        }
+        for impulse in (&mut impulses).join() {
+            impulse.reset();
+        }
+        for sensor in (&mut sensors).join() {
+            sensor.reset();
+        }
+        let default_correction = PositionCorrection::default();
+        let position_correction = position_correction
+            .as_deref()
+            .unwrap_or(&default_correction);
+        let materials = materials.as_deref();
+        let pending = contacts
+            .lossy_read(&mut self.contact_reader)
+            .chain(silent.iter().flat_map(|s| s.iter()))
+            .cloned()
+            .collect::<Vec<_>>();
+        for contact in &pending {
+            for &entity in &[contact.bodies.0, contact.bodies.1] {
+                debug_assert!(
+                    bounces.get(entity).is_none() && rolling.get(entity).is_none()
+                        && groups.get(entity).is_none(),
+                    "Entity {:?} has an ArcadeBounce, Rolling or SolverGroup component, which the \
+                     sequential impulse solver does not support",
+                    entity
+                );
+            }
+        }
+
+        if let Some(sleep) = self.sleep.as_mut() {
+            sleep.wake(&pending, &mut bodies);
+        }
+        let contact_islands = find_islands(&pending, &bodies);
+        if let Some(islands) = islands.as_mut() {
+            islands.islands = contact_islands.clone();
+        }
+
+        let stepped = bodies.stepped.as_deref();
+        let (constraint_entities, mut constraints): (Vec<_>, Vec<_>) = (&*entities, &distances)
+            .join()
+            .filter(|&(_, constraint)| {
+                let (a, b) = constraint.bodies;
+                steps_pair(stepped, &bodies.world_ids, a, b)
+            })
+            .map(|(entity, constraint)| (entity, constraint.clone()))
+            .unzip();
+
+        // gather the bodies of all contacts and distance constraints into solver bodies
+        let mut indices = HashMap::new();
+        let mut solver_bodies = Vec::new();
+        let contact_bodies = pending
+            .iter()
+            .flat_map(|contact| vec![contact.bodies.0, contact.bodies.1]);
+        let constrained_bodies = constraints
+            .iter()
+            .flat_map(|constraint| vec![constraint.bodies.0, constraint.bodies.1]);
+        for entity in contact_bodies.chain(constrained_bodies) {
+            if indices.contains_key(&entity) {
+                continue;
+            }
+            let pose = match bodies.poses.get(entity) {
+                Some(pose) => pose.clone(),
+                None => continue,
+            };
+            // kinematic bodies are not moved by the contacts, but still move with their velocity
+            let mass = if bodies.is_kinematic(entity) {
+                Some(Mass::new(Real::INFINITY))
+            } else {
+                bodies.masses.get(entity).map(|m| Mass::new(m.mass()))
+            };
+            let mut body = SolverBody::new(pose, mass);
+            match (
+                bodies.next_poses.get(entity),
+                bodies.next_velocities.get(entity),
+            ) {
+                (Some(next_pose), Some(next_velocity)) => {
+                    body.next_pose = next_pose.clone();
+                    body.next_velocity = next_velocity.clone();
+                    body.frozen = bodies.is_frozen(entity);
+                }
+                _ => body.frozen = true,
+            }
+            indices.insert(entity, solver_bodies.len());
+            solver_bodies.push(body);
+        }
+
+        if let Some(channel) = impacts.as_mut() {
+            for contact in &pending {
+                let event = impact(
+                    contact,
+                    self.impact_threshold,
+                    &handles,
+                    &bodies.next_velocities,
+                );
+                if let Some(event) = event {
+                    channel.single_write(event);
+                }
+            }
+        }
+        let start = solver_bodies
+            .iter()
+            .map(|body| (*body.next_pose.value.position(), body.next_velocity.value.linear))
+            .collect::<Vec<_>>();
+        if let Some(warm_start) = warm_start.as_mut() {
+            mem::swap(self.solver.impulse_cache_mut(), &mut warm_start.impulses);
+        }
+        let total_impulses = self.solver.solve_with_distance_constraints(
+            &pending,
+            &mut constraints,
+            &mut solver_bodies,
+            |entity| indices.get(entity).cloned(),
+            |contact| contact_material(contact, materials, &handles),
+            position_correction,
+        );
+        if let Some(warm_start) = warm_start.as_mut() {
+            mem::swap(self.solver.impulse_cache_mut(), &mut warm_start.impulses);
+            self.solver.clear_cache();
+        }
+
+        for (entity, constraint) in constraint_entities.into_iter().zip(constraints) {
+            if let Some(distance) = distances.get_mut(entity) {
+                *distance = constraint;
+            }
+        }
+        for (&entity, &index) in &indices {
+            let body = &solver_bodies[index];
+            if body.frozen {
+                continue;
+            }
+            if let Some(next_pose) = bodies.next_poses.get_mut(entity) {
+                *next_pose = body.next_pose.clone();
+            }
+            if let Some(next_velocity) = bodies.next_velocities.get_mut(entity) {
+                *next_velocity = body.next_velocity.clone();
+            }
+        }
+        for (contact, impulse) in pending.iter().zip(&total_impulses) {
+            if let Some(accumulator) = impulses.get_mut(contact.bodies.0) {
+                accumulator.add_impulse(*impulse * -1.);
+            }
+            if let Some(accumulator) = impulses.get_mut(contact.bodies.1) {
+                accumulator.add_impulse(*impulse);
+            }
+            let normal_force = if time.delta_seconds > 0. {
+                impulse.magnitude() / time.delta_seconds
+            } else {
+                0.
+            };
+            if let Some(sensors) = sensors.get_mut(contact.bodies.0) {
+                sensors.record(contact.primitives.0, normal_force);
+            }
+            if let Some(sensors) = sensors.get_mut(contact.bodies.1) {
+                sensors.record(contact.primitives.1, normal_force);
+            }
+        }
+        if let Some(channel) = instabilities.as_mut() {
+            // largest change of the next frame velocity and position of either body
+            let change = |entity| match indices.get(&entity) {
+                Some(&index) => {
+                    let (position, velocity) = start[index];
+                    let body = &solver_bodies[index];
+                    (
+                        (body.next_velocity.value.linear - velocity).magnitude(),
+                        (*body.next_pose.value.position() - position).magnitude(),
+                    )
+                }
+                None => (0., 0.),
+            };
+            for contact in &pending {
+                let (velocity_0, position_0) = change(contact.bodies.0);
+                let (velocity_1, position_1) = change(contact.bodies.1);
+                check_stability(
+                    channel,
+                    contact,
+                    (largest_change(velocity_0, velocity_1), self.max_velocity_change),
+                    (largest_change(position_0, position_1), self.max_position_change),
+                );
+            }
+        }
+        if let Some(resolved) = resolved.as_mut() {
+            resolved.contacts.clear();
+            resolved
+                .contacts
+                .extend(pending.into_iter().zip(total_impulses));
+        }
+
+        advance_bodies(&entities, &mut bodies, time.delta_seconds);
+        if let Some(sleep) = self.sleep.as_mut() {
+            sleep.sleep(&contact_islands, &mut bodies);
+            if let Some(state) = state.as_mut() {
+                sleep.swap_state(state);
+                sleep.clear();
+            }
+        }
+    }
+}
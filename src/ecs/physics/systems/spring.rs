@@ -0,0 +1,123 @@
+use std::marker;
+
+use cgmath::{EuclideanSpace, InnerSpace, Rotation, Zero};
+use specs::{Entity, Fetch, Join, ReadStorage, System, WriteStorage};
+
+use {BodyPose, Real};
+use ecs::collide::{is_stepped, steps_pair, Dormant, Frozen, SteppedWorld, WorldId};
+use physics::{BodyType, ForceAccumulator, LinearVector, Spring, SpringAnchor, Velocity};
+use super::linear_impulse::{is_frozen, is_kinematic};
+
+/// Spring system.
+///
+/// Will add the forces of all [`Spring`](../../physics/struct.Spring.html) components to the force
+/// accumulators of the connected bodies, using the current poses and velocities. Bodies without a
+/// velocity are treated as being at rest, and springs where a body has no pose are skipped.
+///
+/// [`Frozen`](../collide/struct.Frozen.html), [`Dormant`](../collide/struct.Dormant.html) and
+/// static bodies are treated as being at rest, and kinematic bodies move with their velocity, see
+/// [`BodyType`](../../physics/enum.BodyType.html). Neither is given any spring force.
+///
+/// Springs between bodies in different simulation worlds are ignored, and so are springs outside
+/// the [`SteppedWorld`](../collide/struct.SteppedWorld.html), if present.
+///
+/// Should run before the contact solver, which consumes the accumulated forces.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct SpringSystem<P, R> {
+    m: marker::PhantomData<(P, R)>,
+}
+
+impl<P, R> SpringSystem<P, R> {
+    /// Create a new spring system
+    pub fn new() -> Self {
+        Self {
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<P, R> Default for SpringSystem<P, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, P, R> System<'a> for SpringSystem<P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: LinearVector + InnerSpace + Send + Sync + 'static,
+    R: Rotation<P> + Send + Sync + 'static,
+{
+    type SystemData = (
+        ReadStorage<'a, Spring<Entity, P>>,
+        ReadStorage<'a, Frozen>,
+        ReadStorage<'a, Dormant>,
+        ReadStorage<'a, BodyType>,
+        ReadStorage<'a, BodyPose<P, R>>,
+        ReadStorage<'a, Velocity<P::Diff>>,
+        WriteStorage<'a, ForceAccumulator<P::Diff>>,
+        ReadStorage<'a, WorldId>,
+        Option<Fetch<'a, SteppedWorld>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            springs,
+            frozen,
+            dormant,
+            body_types,
+            poses,
+            velocities,
+            mut forces,
+            world_ids,
+            stepped,
+        ) = data;
+        let stepped = stepped.as_deref();
+        let fixed = |entity| is_frozen(&frozen, &dormant, &body_types, entity);
+        let immovable = |entity| fixed(entity) || is_kinematic(&body_types, entity);
+        for spring in springs.join() {
+            let simulated = match spring.anchor {
+                SpringAnchor::Body(entity) => steps_pair(stepped, &world_ids, spring.body, entity),
+                SpringAnchor::World(_) => is_stepped(stepped, &world_ids, spring.body),
+            };
+            if !simulated {
+                continue;
+            }
+            let state = |entity| {
+                poses.get(entity).map(|pose| {
+                    let velocity = match velocities.get(entity) {
+                        Some(velocity) if !fixed(entity) => velocity.linear,
+                        _ => P::Diff::zero(),
+                    };
+                    (*pose.position(), velocity)
+                })
+            };
+            let a = match state(spring.body) {
+                Some(a) => a,
+                None => continue,
+            };
+            let (b, other) = match spring.anchor {
+                SpringAnchor::Body(entity) => match state(entity) {
+                    Some(b) => (b, Some(entity)),
+                    None => continue,
+                },
+                SpringAnchor::World(point) => ((point, P::Diff::zero()), None),
+            };
+            let force = spring.force(a, b);
+            if !immovable(spring.body) {
+                if let Some(accumulator) = forces.get_mut(spring.body) {
+                    accumulator.add_force(force);
+                }
+            }
+            if let Some(other) = other.filter(|&entity| !immovable(entity)) {
+                if let Some(accumulator) = forces.get_mut(other) {
+                    accumulator.add_force(force * -1.);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::marker;
+
+use collision::Interpolate;
+use specs::{Component, Entities, Fetch, Join, ReadStorage, System, WriteStorage};
+
+use {NextFrame, Real};
+use ecs::physics::resources::{RenderInterpolation, SyncTransform};
+
+/// Transform sync system.
+///
+/// Copies the physics transform of all bodies into a user supplied render transform component,
+/// using the [`SyncTransform`](trait.SyncTransform.html) conversion trait. Bodies without the
+/// render component are ignored.
+///
+/// If a [`RenderInterpolation`](struct.RenderInterpolation.html) resource is present, the
+/// transform is interpolated between the current pose and the next frame pose, for bodies that
+/// have a next frame pose.
+///
+/// Should run after the contact solver.
+///
+/// ### Type parameters:
+///
+/// - `T`: Physics transform type, usually [`BodyPose`](../../struct.BodyPose.html)
+/// - `C`: Render transform component type
+pub struct TransformSyncSystem<T, C> {
+    m: marker::PhantomData<(T, C)>,
+}
+
+impl<T, C> TransformSyncSystem<T, C> {
+    /// Create a new transform sync system
+    pub fn new() -> Self {
+        Self {
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<T, C> Default for TransformSyncSystem<T, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T, C> System<'a> for TransformSyncSystem<T, C>
+where
+    T: Component + Interpolate<Real> + Send + Sync + 'static,
+    C: Component + SyncTransform<T> + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        Option<Fetch<'a, RenderInterpolation>>,
+        ReadStorage<'a, T>,
+        ReadStorage<'a, NextFrame<T>>,
+        WriteStorage<'a, C>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, interpolation, poses, next_poses, mut render_transforms) = data;
+        for (entity, pose, render_transform) in
+            (&*entities, &poses, &mut render_transforms).join()
+        {
+            match (interpolation.as_ref(), next_poses.get(entity)) {
+                (Some(interpolation), Some(next_pose)) => render_transform
+                    .sync_transform(&pose.interpolate(&next_pose.value, interpolation.alpha)),
+                _ => render_transform.sync_transform(pose),
+            }
+        }
+    }
+}
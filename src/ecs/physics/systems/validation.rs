@@ -0,0 +1,194 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::marker;
+
+use cgmath::{EuclideanSpace, Rotation};
+use collision::{Aabb, Primitive};
+use shrev::EventChannel;
+use specs::{Entities, Entity, FetchMut, Join, ReadStorage, System};
+
+use {BodyPose, NextFrame, Real};
+use collide::CollisionShape;
+use physics::{Mass, Velocity};
+
+/// Problem found with the component setup of an entity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SetupProblem {
+    /// Body with a velocity, but no pose
+    MissingPose,
+    /// Body with a velocity, but no next frame pose
+    MissingNextPose,
+    /// Body with a velocity, but no next frame velocity
+    MissingNextVelocity,
+    /// Collision shape without a pose
+    ShapeWithoutPose,
+    /// Finite mass without a velocity
+    MassWithoutRigidBody,
+}
+
+impl fmt::Display for SetupProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match *self {
+            SetupProblem::MissingPose => {
+                "has a Velocity but no BodyPose, add a BodyPose to simulate the body"
+            }
+            SetupProblem::MissingNextPose => {
+                "has a Velocity but no NextFrame<BodyPose>, the contact solver will never move the \
+                 body, add a NextFrame<BodyPose> or run the NextFrameSetupSystem"
+            }
+            SetupProblem::MissingNextVelocity => {
+                "has a Velocity but no NextFrame<Velocity>, the contact solver will never move the \
+                 body, add a NextFrame<Velocity> or run the NextFrameSetupSystem"
+            }
+            SetupProblem::ShapeWithoutPose => {
+                "has a CollisionShape but no BodyPose, the shape will never collide, add a BodyPose"
+            }
+            SetupProblem::MassWithoutRigidBody => {
+                "has a finite Mass but no Velocity, the body will never be resolved, add a \
+                 Velocity, or use an infinite Mass for static bodies"
+            }
+        };
+        f.write_str(description)
+    }
+}
+
+/// Event written when an entity with an inconsistent component setup is found
+#[derive(Debug, Clone)]
+pub struct SetupDiagnostic {
+    /// The entity with the problem
+    pub entity: Entity,
+    /// The problem found
+    pub problem: SetupProblem,
+}
+
+impl fmt::Display for SetupDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Entity {:?} {}", self.entity, self.problem)
+    }
+}
+
+/// Component setup validation system, only available with the `debug` feature.
+///
+/// Detects entities with inconsistent physics component setups, that would otherwise be silently
+/// ignored by the physics systems. Each problem is reported once per entity, as a
+/// [`SetupDiagnostic`](struct.SetupDiagnostic.html) event. The event channel resource is added
+/// by `world_physics_register` when the `debug` feature is enabled.
+///
+/// ### Type parameters:
+///
+/// - `P`: Collision primitive type
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub struct ValidationSystem<P, R> {
+    reported: HashSet<(Entity, SetupProblem)>,
+    m: marker::PhantomData<(P, R)>,
+}
+
+impl<P, R> ValidationSystem<P, R> {
+    /// Create a new validation system
+    pub fn new() -> Self {
+        Self {
+            reported: HashSet::default(),
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<P, R> Default for ValidationSystem<P, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, P, R> System<'a> for ValidationSystem<P, R>
+where
+    P: Primitive + Send + Sync + 'static,
+    P::Aabb: Aabb<Scalar = Real> + Send + Sync + 'static,
+    P::Point: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    <P::Point as EuclideanSpace>::Diff: Send + Sync + 'static,
+    R: Rotation<P::Point> + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        FetchMut<'a, EventChannel<SetupDiagnostic>>,
+        ReadStorage<'a, Mass>,
+        ReadStorage<'a, Velocity<<P::Point as EuclideanSpace>::Diff>>,
+        ReadStorage<'a, NextFrame<Velocity<<P::Point as EuclideanSpace>::Diff>>>,
+        ReadStorage<'a, BodyPose<P::Point, R>>,
+        ReadStorage<'a, NextFrame<BodyPose<P::Point, R>>>,
+        ReadStorage<'a, CollisionShape<P, BodyPose<P::Point, R>>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut channel,
+            masses,
+            velocities,
+            next_velocities,
+            poses,
+            next_poses,
+            shapes,
+        ) = data;
+        let mut diagnostics = Vec::default();
+        for (entity, _) in (&*entities, &velocities).join() {
+            if poses.get(entity).is_none() {
+                diagnostics.push((entity, SetupProblem::MissingPose));
+            }
+            if next_poses.get(entity).is_none() {
+                diagnostics.push((entity, SetupProblem::MissingNextPose));
+            }
+            if next_velocities.get(entity).is_none() {
+                diagnostics.push((entity, SetupProblem::MissingNextVelocity));
+            }
+        }
+        for (entity, _) in (&*entities, &shapes).join() {
+            if poses.get(entity).is_none() {
+                diagnostics.push((entity, SetupProblem::ShapeWithoutPose));
+            }
+        }
+        for (entity, mass) in (&*entities, &masses).join() {
+            if mass.mass().is_finite() && velocities.get(entity).is_none() {
+                diagnostics.push((entity, SetupProblem::MassWithoutRigidBody));
+            }
+        }
+
+        self.reported
+            .retain(|&(entity, _)| entities.is_alive(entity));
+        for (entity, problem) in diagnostics {
+            if !self.reported.insert((entity, problem)) {
+                continue;
+            }
+            channel.single_write(SetupDiagnostic { entity, problem });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shrev::EventChannel;
+    use specs::{RunNow, World};
+
+    use super::{SetupDiagnostic, SetupProblem};
+    use ecs::physics::prelude2d::*;
+
+    #[test]
+    fn test_diagnostics_are_written_once() {
+        let mut world = World::new();
+        world_physics_register(&mut world);
+        let mut reader = world
+            .write_resource::<EventChannel<SetupDiagnostic>>()
+            .register_reader();
+        let entity = world.create_entity().with(Mass::new(1.)).build();
+
+        let mut system = ValidationSystem2::new();
+        system.run_now(&world.res);
+        system.run_now(&world.res);
+
+        let channel = world.read_resource::<EventChannel<SetupDiagnostic>>();
+        let diagnostics = channel
+            .lossy_read(&mut reader)
+            .map(|diagnostic| (diagnostic.entity, diagnostic.problem))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![(entity, SetupProblem::MassWithoutRigidBody)], diagnostics);
+    }
+}
@@ -0,0 +1,325 @@
+use cgmath::{Basis2, Point2, Vector2, Zero};
+use specs::{Entities, Entity, Fetch, Join, ReadStorage, System, WriteStorage};
+
+use {BodyPose, NextFrame, Real};
+use ecs::collide::{steps_pair, Dormant, Frozen, SteppedWorld, WorldId};
+use ecs::physics::resources::DeltaTime;
+use physics::{solve_wheel_joint, AngularVelocity, BodyType, Inertia, JointControl, Mass, Velocity,
+              WheelBodyData, WheelJoint2};
+use super::linear_impulse::{is_frozen, is_kinematic};
+
+/// Wheel joint system for 2D.
+///
+/// Will apply suspension, axis constraint and motor for all
+/// [`WheelJoint2`](../../physics/struct.WheelJoint2.html) components, which are placed on the wheel
+/// entity. Operates on the next frame velocities and poses, so should run before the contact
+/// solver.
+///
+/// The suspension and axis constraint act on the chassis at the anchor point, and the motor
+/// reaction torque turns the chassis the other way, for chassis bodies with an
+/// [`AngularVelocity`](../../physics/struct.AngularVelocity.html) and an
+/// [`Inertia`](../../physics/struct.Inertia.html).
+///
+/// The motor turns the [`AngularVelocity`](../../physics/struct.AngularVelocity.html) of the
+/// wheel, and the contact solver turns the spin into drive through the contact friction of
+/// [`Rolling`](../../physics/struct.Rolling.html) wheels. The rotation of the wheel is integrated
+/// by the contact solver, the joint does not set it. Wheels without an angular velocity only spin
+/// inside the joint, see [`angle`](../../physics/struct.WheelJoint2.html#method.angle).
+///
+/// Motor commands are read from, and applied torques written to, the
+/// [`JointControl`](../../physics/struct.JointControl.html) component on the wheel entity, if
+/// present.
+///
+/// [`Frozen`](../collide/struct.Frozen.html), [`Dormant`](../collide/struct.Dormant.html) and
+/// static bodies are not moved by the joint, and act as if at rest. Kinematic bodies are not moved
+/// by the joint either, but keep moving with their velocity, see
+/// [`BodyType`](../../physics/enum.BodyType.html).
+///
+/// Wheels attached to a chassis in a different simulation world are ignored, and so are wheels
+/// outside the [`SteppedWorld`](../collide/struct.SteppedWorld.html), if present.
+pub struct WheelJointSystem2;
+
+impl<'a> System<'a> for WheelJointSystem2 {
+    type SystemData = (
+        Entities<'a>,
+        Fetch<'a, DeltaTime>,
+        ReadStorage<'a, Mass>,
+        ReadStorage<'a, Frozen>,
+        ReadStorage<'a, Dormant>,
+        ReadStorage<'a, BodyType>,
+        ReadStorage<'a, Inertia<Vector2<Real>>>,
+        WriteStorage<'a, WheelJoint2<Entity>>,
+        WriteStorage<'a, JointControl>,
+        WriteStorage<'a, NextFrame<Velocity<Vector2<Real>>>>,
+        ReadStorage<'a, NextFrame<BodyPose<Point2<Real>, Basis2<Real>>>>,
+        WriteStorage<'a, AngularVelocity<Vector2<Real>>>,
+        ReadStorage<'a, WorldId>,
+        Option<Fetch<'a, SteppedWorld>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            time,
+            masses,
+            frozen,
+            dormant,
+            body_types,
+            inertias,
+            mut joints,
+            mut controls,
+            mut next_velocities,
+            next_poses,
+            mut angular_velocities,
+            world_ids,
+            stepped,
+        ) = data;
+        let stepped = stepped.as_deref();
+
+        let fixed = |entity| is_frozen(&frozen, &dormant, &body_types, entity);
+        let immovable = |entity| fixed(entity) || is_kinematic(&body_types, entity);
+        let mass = |entity| {
+            if immovable(entity) {
+                None
+            } else {
+                masses.get(entity)
+            }
+        };
+
+        for (wheel, joint) in (&*entities, &mut joints).join() {
+            let chassis = joint.chassis;
+            if !steps_pair(stepped, &world_ids, chassis, wheel) {
+                continue;
+            }
+            let state = |entity| match (next_poses.get(entity), next_velocities.get(entity)) {
+                (Some(p), Some(_)) if fixed(entity) => Some((p.value.clone(), Vector2::zero())),
+                (Some(p), Some(v)) => Some((p.value.clone(), v.value.linear)),
+                _ => None,
+            };
+            let (chassis_pose, chassis_velocity) = match state(chassis) {
+                Some(state) => state,
+                None => continue,
+            };
+            let (wheel_pose, wheel_velocity) = match state(wheel) {
+                Some(state) => state,
+                None => continue,
+            };
+
+            let wheel_angular_velocity = match angular_velocities.get(wheel) {
+                Some(_) if fixed(wheel) => 0.,
+                Some(angular) => angular.angular,
+                None => joint.angular_velocity(),
+            };
+            // a chassis without an angular velocity does not rotate
+            let chassis_angular_velocity = match angular_velocities.get(chassis) {
+                Some(angular) if !fixed(chassis) => angular.angular,
+                _ => 0.,
+            };
+            let chassis_inertia = mass(chassis)
+                .and(angular_velocities.get(chassis))
+                .and(inertias.get(chassis));
+
+            let (
+                chassis_velocity,
+                chassis_angular_velocity,
+                wheel_velocity,
+                wheel_angular_velocity,
+            ) = solve_wheel_joint(
+                joint,
+                WheelBodyData {
+                    pose: &chassis_pose,
+                    velocity: chassis_velocity,
+                    angular_velocity: chassis_angular_velocity,
+                    mass: mass(chassis),
+                    inertia: chassis_inertia,
+                },
+                WheelBodyData {
+                    pose: &wheel_pose,
+                    velocity: wheel_velocity,
+                    angular_velocity: wheel_angular_velocity,
+                    mass: mass(wheel),
+                    inertia: mass(wheel).and(inertias.get(wheel)),
+                },
+                controls.get_mut(wheel),
+                time.delta_seconds,
+            );
+
+            if !immovable(chassis) {
+                if let Some(velocity) = next_velocities.get_mut(chassis) {
+                    velocity.value.linear = chassis_velocity;
+                }
+                if let Some(angular) = angular_velocities.get_mut(chassis) {
+                    angular.angular = chassis_angular_velocity;
+                }
+            }
+            if immovable(wheel) {
+                continue;
+            }
+            if let Some(velocity) = next_velocities.get_mut(wheel) {
+                velocity.value.linear = wheel_velocity;
+            }
+            if let Some(angular) = angular_velocities.get_mut(wheel) {
+                angular.angular = wheel_angular_velocity;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, One, Point2, Vector2, Zero};
+    use shrev::EventChannel;
+    use specs::{Entity, Join, RunNow, World};
+
+    use {NextFrame, Real};
+    use collide::{CollisionStrategy, Contact, ContactEvent};
+    use ecs::physics::prelude2d::*;
+
+    fn body(world: &mut World, y: Real, mass: Real) -> Entity {
+        let pose = BodyPose2::new(Point2::new(0., y), Basis2::one());
+        let velocity = Velocity2 {
+            linear: Vector2::zero(),
+        };
+        world
+            .create_entity()
+            .with(pose.clone())
+            .with(NextFrame { value: pose })
+            .with(velocity.clone())
+            .with(NextFrame { value: velocity })
+            .with(Mass::new(mass))
+            .with(ForceAccumulator2::new())
+            .build()
+    }
+
+    /// Drive a wheel on flat ground for a second, returns the distance moved by the chassis
+    fn drive(rolling: bool) -> Real {
+        let mut world = World::new();
+        world_physics_register(&mut world);
+        world.write_resource::<DeltaTime>().delta_seconds = 1. / 60.;
+        let rubber = world
+            .write_resource::<MaterialRegistry>()
+            .add("rubber", Material::new(0., 1.));
+        let ground = world
+            .create_entity()
+            .with(BodyPose2::new(Point2::new(0., -1.), Basis2::one()))
+            .with(BodyType::Static)
+            .with(rubber)
+            .build();
+        let chassis = body(&mut world, 1.5, 4.);
+        let wheel = body(&mut world, 0.5, 1.);
+        world.write::<MaterialHandle>().insert(wheel, rubber);
+        world.write::<AngularVelocity2>().insert(wheel, AngularVelocity2::default());
+        world.write::<Inertia2>().insert(wheel, Inertia2::new(0.125));
+        if rolling {
+            world.write::<Rolling>().insert(wheel, Rolling);
+        }
+        let joint = WheelJoint2::new(
+            chassis,
+            Vector2::new(0., -0.5),
+            Vector2::new(0., -1.),
+            0.5,
+            200.,
+            20.,
+            0.5,
+        ).with_motor(-20., 10.);
+        world.write::<WheelJoint2<Entity>>().insert(wheel, joint);
+
+        let reader = world
+            .write_resource::<EventChannel<ContactEvent2>>()
+            .register_reader();
+        let mut solver = LinearContactSolverSystem2::new(reader);
+        for _ in 0..60 {
+            for (mass, force) in (&world.read::<Mass>(), &mut world.write::<ForceAccumulator2>())
+                .join()
+            {
+                force.add_force(Vector2::new(0., -10. * mass.mass()));
+            }
+            let center = *world
+                .read::<NextFrame<BodyPose2>>()
+                .get(wheel)
+                .unwrap()
+                .value
+                .position();
+            let contact = ContactEvent::new(
+                (ground, wheel),
+                Contact::new_with_point(
+                    CollisionStrategy::FullResolution,
+                    Vector2::new(0., 1.),
+                    0.,
+                    Point2::new(center.x, center.y - 0.5),
+                ),
+            );
+            world
+                .write_resource::<EventChannel<ContactEvent2>>()
+                .single_write(contact);
+            WheelJointSystem2.run_now(&world.res);
+            solver.run_now(&world.res);
+        }
+        let x = world.read::<BodyPose2>().get(chassis).unwrap().position().x;
+        x
+    }
+
+    #[test]
+    fn test_motorised_wheel_drives_chassis() {
+        // the wheel turns clockwise, and rolls to the right
+        assert!(drive(true) > 1.);
+        // without rolling contacts the spinning wheel gets no traction
+        assert_ulps_eq!(drive(false), 0.);
+    }
+
+    /// Run the wheel joint for a single frame, on a chassis with an inertia and a wheel on a
+    /// suspension to the right of the chassis center, returns the angular velocities of the
+    /// chassis and the wheel
+    fn tilt(wheel_y: Real, motor: bool) -> (Real, Real) {
+        let mut world = World::new();
+        world_physics_register(&mut world);
+        world.write_resource::<DeltaTime>().delta_seconds = 1. / 60.;
+        let chassis = body(&mut world, 1., 4.);
+        world.write::<AngularVelocity2>().insert(chassis, AngularVelocity2::default());
+        world.write::<Inertia2>().insert(chassis, Inertia2::new(1.));
+        let wheel = body(&mut world, 0., 1.);
+        let pose = BodyPose2::new(Point2::new(1., wheel_y), Basis2::one());
+        world.write::<BodyPose2>().insert(wheel, pose.clone());
+        world
+            .write::<NextFrame<BodyPose2>>()
+            .insert(wheel, NextFrame { value: pose });
+        world.write::<AngularVelocity2>().insert(wheel, AngularVelocity2::default());
+        world.write::<Inertia2>().insert(wheel, Inertia2::new(0.125));
+        let joint = WheelJoint2::new(
+            chassis,
+            Vector2::new(1., -0.5),
+            Vector2::new(0., -1.),
+            0.5,
+            200.,
+            20.,
+            0.5,
+        );
+        let joint = if motor { joint.with_motor(-20., 10.) } else { joint };
+        world.write::<WheelJoint2<Entity>>().insert(wheel, joint);
+
+        WheelJointSystem2.run_now(&world.res);
+        let angular = world.read::<AngularVelocity2>();
+        (
+            angular.get(chassis).unwrap().angular,
+            angular.get(wheel).unwrap().angular,
+        )
+    }
+
+    #[test]
+    fn test_suspension_tilts_chassis() {
+        // at rest length, the suspension applies no load
+        assert_ulps_eq!(tilt(0., false).0, 0.);
+        // a compressed suspension pushes up the right side of the chassis
+        assert!(tilt(0.3, false).0 > 0.);
+    }
+
+    #[test]
+    fn test_motor_reaction_tilts_chassis() {
+        // the wheel is driven clockwise, and the chassis turns the other way
+        let (chassis, wheel) = tilt(0., true);
+        assert!(wheel < 0.);
+        assert!(chassis > 0.);
+        // the angular momentum of the motor impulse is balanced
+        assert_relative_eq!(chassis * 1. + wheel * 0.125, 0., epsilon = 1e-6);
+    }
+}
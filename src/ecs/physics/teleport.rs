@@ -0,0 +1,121 @@
+use cgmath::{EuclideanSpace, Rotation};
+use specs::{Entity, World};
+
+use {BodyPose, NextFrame, Real};
+use physics::{AngularRotation, AngularVelocity, RotationalVector, Velocity};
+
+/// Teleport a group of bodies by the same rigid transform.
+///
+/// The root body is moved to the target pose, and all other bodies in the group keep their pose
+/// relative to the root, so joints between the bodies are not stretched by the move. The current
+/// and next frame poses are moved, and the current and next frame velocities, and the angular
+/// velocities, are rotated with the group. All bodies are updated together, before any system sees the new poses, and the spatial
+/// sorting system picks up the changed poses when it next runs.
+///
+/// # Parameters:
+///
+/// - `world`: The world
+/// - `root`: The root body of the group
+/// - `children`: The other bodies in the group, see `jointed_bodies` in the
+///   [2D prelude](prelude2d/index.html) for finding bodies jointed to the root
+/// - `target`: The new pose of the root body
+///
+/// # Returns:
+///
+/// `false` if the root body has no pose, in which case nothing is moved.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+pub fn teleport_group<P, R>(
+    world: &World,
+    root: Entity,
+    children: &[Entity],
+    target: &BodyPose<P, R>,
+) -> bool
+where
+    P: EuclideanSpace<Scalar = Real> + Send + Sync + 'static,
+    P::Diff: RotationalVector + Send + Sync + 'static,
+    R: AngularRotation<P::Diff> + Rotation<P> + Send + Sync + 'static,
+{
+    let mut poses = world.write::<BodyPose<P, R>>();
+    let mut next_poses = world.write::<NextFrame<BodyPose<P, R>>>();
+    let mut velocities = world.write::<Velocity<P::Diff>>();
+    let mut next_velocities = world.write::<NextFrame<Velocity<P::Diff>>>();
+    let mut angular_velocities = world.write::<AngularVelocity<P::Diff>>();
+
+    let (origin, rotation) = match poses.get(root) {
+        Some(pose) => (
+            *pose.position(),
+            *target.rotation() * pose.rotation().invert(),
+        ),
+        None => return false,
+    };
+    let move_pose = |pose: &BodyPose<P, R>| {
+        BodyPose::new(
+            *target.position() + rotation.rotate_vector(*pose.position() - origin),
+            rotation * *pose.rotation(),
+        )
+    };
+
+    let children = children.iter().filter(|&&entity| entity != root);
+    for &entity in Some(&root).into_iter().chain(children) {
+        if let Some(pose) = poses.get_mut(entity) {
+            *pose = move_pose(pose);
+        }
+        if let Some(next_pose) = next_poses.get_mut(entity) {
+            next_pose.value = move_pose(&next_pose.value);
+        }
+        if let Some(velocity) = velocities.get_mut(entity) {
+            velocity.linear = rotation.rotate_vector(velocity.linear);
+        }
+        if let Some(next_velocity) = next_velocities.get_mut(entity) {
+            next_velocity.value.linear = rotation.rotate_vector(next_velocity.value.linear);
+        }
+        if let Some(angular_velocity) = angular_velocities.get_mut(entity) {
+            angular_velocity.angular = rotation.rotate_angular(angular_velocity.angular);
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use cgmath::{One, Point3, Quaternion, Rad, Rotation3, Vector3};
+    use specs::World;
+
+    use {NextFrame, Real};
+    use ecs::physics::prelude3d::*;
+    use super::teleport_group;
+
+    #[test]
+    fn test_teleport_rotates_angular_velocity() {
+        let mut world = World::new();
+        world_physics_register(&mut world);
+        let pose = BodyPose3::new(Point3::new(1., 0., 0.), Quaternion::one());
+        let body = world
+            .create_entity()
+            .with(pose.clone())
+            .with(NextFrame { value: pose })
+            .with(Velocity3 {
+                linear: Vector3::new(1., 0., 0.),
+            })
+            .with(AngularVelocity3::new(Vector3::new(2., 0., 0.)))
+            .build();
+
+        let rotation = Quaternion::from_angle_z(Rad(FRAC_PI_2 as Real));
+        let target = BodyPose3::new(Point3::new(0., 5., 0.), rotation);
+        assert!(teleport_group(&world, body, &[], &target));
+
+        // a spin around the x axis becomes a spin around the y axis
+        let position = *world.read::<BodyPose3>().get(body).unwrap().position();
+        let velocity = world.read::<Velocity3>().get(body).unwrap().linear;
+        let angular = world.read::<AngularVelocity3>().get(body).unwrap().angular;
+        assert_relative_eq!(position, Point3::new(0., 5., 0.), epsilon = 1e-5);
+        assert_relative_eq!(velocity, Vector3::new(0., 1., 0.), epsilon = 1e-5);
+        assert_relative_eq!(angular, Vector3::new(0., 2., 0.), epsilon = 1e-5);
+    }
+}
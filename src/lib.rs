@@ -11,24 +11,32 @@
 //!   * Sweep and Prune
 //! * Narrow phase collision detection using GJK, and optionally EPA for full contact information
 //! * [`specs::System`](https://docs.rs/specs/0.9.5/specs/trait.System.html) for collision
-//!    detection working on user supplied transform, and
-//!    [`CollisionShape`](collide/struct.CollisionShape.html) components.
-//!    Can optionally use broad and/or narrow phase detection.
-//!    Library supplies a transform implementation [`BodyPose`](struct.BodyPose.html) for
-//!    convenience.
+//!   detection working on user supplied transform, and
+//!   [`CollisionShape`](collide/struct.CollisionShape.html) components.
+//!   Can optionally use broad and/or narrow phase detection.
+//!   Library supplies a transform implementation [`BodyPose`](struct.BodyPose.html) for
+//!   convenience.
 //! * Uses single precision as default, can be changed to double precision with the `double`
 //!   feature.
 //! * Has support for doing spatial sort/collision detection using the collision-rs DBVT.
 //! * Support for doing broad phase using the collision-rs DBVT.
 //! * Has support for all primitives in collision-rs
+//! * The `specs` integration is only built with the `ecs` feature. Without the default `std`
+//!   feature, the core (poses, velocity integration, contact resolution and collision
+//!   primitives) is built as `no_std`, using `alloc` for its collections. Note that `cgmath` and
+//!   `collision` still link the standard library themselves.
 //!
 //! # Examples
 //!
 //! See the `examples/` directory for examples.
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(missing_docs, trivial_casts, unsafe_code, unstable_features, unused_import_braces,
         unused_qualifications)]
 
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
 extern crate cgmath;
 extern crate collision;
 #[cfg(feature = "ecs")]
@@ -40,11 +48,22 @@ extern crate specs;
 #[macro_use]
 extern crate approx;
 
+/// Stand-in for the standard library when building without the `std` feature, so the core math
+/// and resolution code can use the same paths with and without it.
+#[cfg(not(feature = "std"))]
+mod std {
+    pub use alloc::{collections, string, vec};
+    pub use core::*;
+}
+
 pub mod collide;
 #[cfg(feature = "ecs")]
 pub mod ecs;
 pub mod physics;
 
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+
+use cgmath::Decomposed;
 use cgmath::prelude::*;
 use collision::prelude::*;
 
@@ -120,6 +139,58 @@ where
     }
 }
 
+/// Translate the pose by the given position delta
+impl<P, R> Add<P::Diff> for BodyPose<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    R: Rotation<P>,
+{
+    type Output = Self;
+
+    fn add(mut self, delta: P::Diff) -> Self {
+        self += delta;
+        self
+    }
+}
+
+/// Translate the pose by the negated position delta
+impl<P, R> Sub<P::Diff> for BodyPose<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    R: Rotation<P>,
+{
+    type Output = Self;
+
+    fn sub(mut self, delta: P::Diff) -> Self {
+        self -= delta;
+        self
+    }
+}
+
+/// Translate the pose by the given position delta. Sets the dirty flag.
+impl<P, R> AddAssign<P::Diff> for BodyPose<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    R: Rotation<P>,
+{
+    fn add_assign(&mut self, delta: P::Diff) {
+        let position = self.position + delta;
+        self.set_position(position);
+    }
+}
+
+/// Translate the pose by the negated position delta. Sets the dirty flag.
+impl<P, R> SubAssign<P::Diff> for BodyPose<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    R: Rotation<P>,
+{
+    fn sub_assign(&mut self, delta: P::Diff) {
+        let position = self.position + delta * -1.;
+        self.set_position(position);
+    }
+}
+
 impl<P, R> Transform<P> for BodyPose<P, R>
 where
     P: EuclideanSpace<Scalar = Real>,
@@ -167,7 +238,7 @@ where
     fn translation_interpolate(&self, other: &Self, amount: P::Scalar) -> Self {
         BodyPose::new(
             P::from_vec(self.position.to_vec().lerp(other.position.to_vec(), amount)),
-            other.rotation.clone(),
+            other.rotation,
         )
     }
 }
@@ -185,3 +256,147 @@ where
         )
     }
 }
+
+/// Transform with a uniform scale, that can be used as the transform component for collision
+/// detection throughout the library.
+///
+/// Wraps a cgmath [`Decomposed`](https://docs.rs/cgmath/0.15.0/cgmath/struct.Decomposed.html).
+/// The scale is applied to the primitives of a collision shape, both when computing the bounding
+/// box of the shape, and in the support mappings used by the narrow phase, so scaled shapes get
+/// correct bounds and contacts. The physics systems still use [`BodyPose`](struct.BodyPose.html).
+#[derive(Clone, Debug)]
+pub struct ScaledPose<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    R: Rotation<P>,
+{
+    transform: Decomposed<P::Diff, R>,
+}
+
+impl<P, R> ScaledPose<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    R: Rotation<P>,
+{
+    /// Create a new [`ScaledPose`](struct.ScaledPose.html) with initial state given by the
+    /// supplied position, rotation and uniform scale.
+    pub fn new(position: P, rotation: R, scale: Real) -> Self {
+        Self {
+            transform: Decomposed {
+                scale,
+                rot: rotation,
+                disp: position.to_vec(),
+            },
+        }
+    }
+
+    /// Set the position
+    pub fn set_position(&mut self, position: P) {
+        self.transform.disp = position.to_vec();
+    }
+
+    /// Set the rotation
+    pub fn set_rotation(&mut self, rotation: R) {
+        self.transform.rot = rotation;
+    }
+
+    /// Set the uniform scale
+    pub fn set_scale(&mut self, scale: Real) {
+        self.transform.scale = scale;
+    }
+
+    /// Get the position
+    pub fn position(&self) -> P {
+        P::from_vec(self.transform.disp)
+    }
+
+    /// Borrows the rotation attribute
+    pub fn rotation(&self) -> &R {
+        &self.transform.rot
+    }
+
+    /// Get the uniform scale
+    pub fn scale(&self) -> Real {
+        self.transform.scale
+    }
+
+    /// Borrows the wrapped transform
+    pub fn decomposed(&self) -> &Decomposed<P::Diff, R> {
+        &self.transform
+    }
+}
+
+impl<P, R> From<Decomposed<P::Diff, R>> for ScaledPose<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    R: Rotation<P>,
+{
+    fn from(transform: Decomposed<P::Diff, R>) -> Self {
+        Self { transform }
+    }
+}
+
+impl<P, R> Transform<P> for ScaledPose<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    R: Rotation<P>,
+{
+    fn one() -> Self {
+        Self {
+            transform: Decomposed::one(),
+        }
+    }
+
+    fn look_at(eye: P, center: P, up: P::Diff) -> Self {
+        Self {
+            transform: Decomposed::look_at(eye, center, up),
+        }
+    }
+
+    fn transform_vector(&self, vec: P::Diff) -> P::Diff {
+        self.transform.transform_vector(vec)
+    }
+
+    fn transform_point(&self, point: P) -> P {
+        self.transform.transform_point(point)
+    }
+
+    fn concat(&self, other: &Self) -> Self {
+        Self {
+            transform: self.transform.concat(&other.transform),
+        }
+    }
+
+    fn inverse_transform(&self) -> Option<Self> {
+        self.transform
+            .inverse_transform()
+            .map(|transform| Self { transform })
+    }
+}
+
+impl<P, R> TranslationInterpolate<P::Scalar> for ScaledPose<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: VectorSpace + InnerSpace,
+    R: Rotation<P> + Clone,
+{
+    fn translation_interpolate(&self, other: &Self, amount: P::Scalar) -> Self {
+        Self {
+            transform: self.transform
+                .translation_interpolate(&other.transform, amount),
+        }
+    }
+}
+
+impl<P, R> Interpolate<P::Scalar> for ScaledPose<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: VectorSpace + InnerSpace,
+    R: Rotation<P> + Interpolate<P::Scalar>,
+{
+    fn interpolate(&self, other: &Self, amount: P::Scalar) -> Self {
+        Self {
+            transform: self.transform.interpolate(&other.transform, amount),
+        }
+    }
+}
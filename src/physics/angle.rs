@@ -0,0 +1,90 @@
+use std::f64::consts::PI;
+
+use cgmath::num_traits::NumCast;
+
+use Real;
+
+pub(crate) fn two_pi() -> Real {
+    NumCast::from(2. * PI).unwrap()
+}
+
+/// Wrap an angle into the range `[-π, π)`
+pub fn wrap_angle(angle: Real) -> Real {
+    let two_pi = two_pi();
+    let half = two_pi * 0.5;
+    angle - two_pi * ((angle + half) / two_pi).floor()
+}
+
+/// Continuous angle readout for revolute joints.
+///
+/// Tracks a wrapped angle, e.g. the angle of a rotation, as it is sampled each step, and unwraps it
+/// into a continuous angle, so the readout does not jump at the `±π` boundary. This assumes the
+/// angle changes by less than half a turn between samples.
+#[derive(Debug, Clone, Default)]
+pub struct UnwrappedAngle {
+    angle: Real,
+    angular_velocity: Real,
+    previous: Option<Real>,
+}
+
+impl UnwrappedAngle {
+    /// Create a new readout, the first sample sets the initial angle
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a sample, and get the continuous angle.
+    ///
+    /// # Parameters:
+    ///
+    /// - `wrapped`: Wrapped angle, in radians
+    /// - `dt`: Time since the last sample, used for the angular velocity
+    pub fn update(&mut self, wrapped: Real, dt: Real) -> Real {
+        let wrapped = wrap_angle(wrapped);
+        match self.previous {
+            Some(previous) => {
+                let delta = wrap_angle(wrapped - previous);
+                self.angle += delta;
+                self.angular_velocity = if dt > 0. { delta / dt } else { 0. };
+            }
+            None => self.angle = wrapped,
+        }
+        self.previous = Some(wrapped);
+        self.angle
+    }
+
+    /// Continuous angle, in radians
+    pub fn angle(&self) -> Real {
+        self.angle
+    }
+
+    /// Angular velocity, in radians per second
+    pub fn angular_velocity(&self) -> Real {
+        self.angular_velocity
+    }
+
+    /// Number of accumulated turns, negative for clockwise rotation
+    pub fn turns(&self) -> Real {
+        self.angle / two_pi()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unwrap_across_boundary() {
+        let mut angle = UnwrappedAngle::new();
+        let step = 0.5;
+        let mut expected = 0.;
+        angle.update(0., 0.1);
+        for _ in 0..20 {
+            expected += step;
+            angle.update(wrap_angle(expected), 0.1);
+        }
+        assert_relative_eq!(expected, angle.angle(), epsilon = 1e-4);
+        assert_relative_eq!(5., angle.angular_velocity(), epsilon = 1e-3);
+        assert!(angle.turns() > 1.5);
+    }
+}
@@ -0,0 +1,183 @@
+use std::fmt::Debug;
+
+use cgmath::{EuclideanSpace, InnerSpace, Rotation, Zero};
+
+use super::{ContactResolution, LinearResolveData, PositionCorrection, Velocity};
+use super::simple::{PoseUpdate, VelocityUpdate};
+use {BodyPose, NextFrame, Real};
+use collide::ContactEvent;
+
+/// Arcade style bouncing.
+///
+/// Bodies with this component do not exchange impulses with the bodies they collide with. Instead
+/// their velocity is simply reflected about the contact normal, scaled by the coefficient, and
+/// they are moved out of the penetration by themselves. The other body is not affected by the
+/// contact, unless it is bouncing too.
+#[derive(Debug, Clone)]
+pub struct ArcadeBounce {
+    /// Fraction of the normal velocity that is kept when bouncing, 1 is a perfect bounce, 0 stops
+    /// the body along the normal
+    pub coefficient: Real,
+}
+
+impl ArcadeBounce {
+    /// Create a new arcade bounce
+    pub fn new(coefficient: Real) -> Self {
+        Self { coefficient }
+    }
+}
+
+/// Reflect a velocity about a surface normal.
+///
+/// Only velocities moving into the surface are reflected, velocities moving away from the surface
+/// are returned unchanged.
+///
+/// # Parameters:
+///
+/// - `velocity`: Velocity of the body
+/// - `normal`: Normalized surface normal, pointing from the surface towards the body
+/// - `coefficient`: Fraction of the normal velocity that is kept
+///
+/// # Returns:
+///
+/// The reflected velocity
+pub fn reflect_velocity<V>(velocity: V, normal: V, coefficient: Real) -> V
+where
+    V: InnerSpace<Scalar = Real>,
+{
+    let normal_velocity = velocity.dot(normal);
+    if normal_velocity >= 0. {
+        return velocity;
+    }
+    velocity - normal * ((1. + coefficient) * normal_velocity)
+}
+
+/// Resolve a contact where at least one of the bodies is bouncing arcade style, see
+/// [`ArcadeBounce`](struct.ArcadeBounce.html).
+///
+/// Like [`solve_contact`](fn.solve_contact.html), if either body has no mass, the contact is only
+/// for collision detection, and is not resolved. Bodies with an infinite mass, or without velocity
+/// and position, never bounce.
+///
+/// # Parameters:
+///
+/// - `contact`: The contact
+/// - `a`: Resolve data of the first body
+/// - `b`: Resolve data of the second body
+/// - `bounces`: Arcade bounce of the two bodies, bodies without one are not affected
+/// - `position_correction`: Penetration correction tunables, the bouncing bodies are moved by the
+///   full correction
+/// - `correct_position`: Whether to correct the penetration
+/// - `delta_seconds`: Length of the step
+///
+/// # Returns:
+///
+/// The resolution of the contact. The impulse is the one applied to the second body if it is
+/// bouncing, otherwise the negated impulse applied to the first body.
+pub fn arcade_resolve_contact<'a, ID, P, R>(
+    contact: &ContactEvent<ID, P>,
+    a: LinearResolveData<'a, P, R>,
+    b: LinearResolveData<'a, P, R>,
+    bounces: (Option<&ArcadeBounce>, Option<&ArcadeBounce>),
+    position_correction: &PositionCorrection,
+    correct_position: bool,
+    delta_seconds: Real,
+) -> ContactResolution<P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + 'a,
+    P::Diff: Debug + Zero + Clone + InnerSpace,
+    R: Rotation<P> + 'a,
+{
+    let mut resolution = ContactResolution {
+        impulse: None,
+        normal_force: 0.,
+        poses: (None, None),
+        velocities: (None, None),
+    };
+    if a.mass.is_none() || b.mass.is_none() {
+        return resolution;
+    }
+    let normal = contact.contact.normal;
+    let correction = position_correction.correction(contact.contact.penetration_depth);
+    let (pose_0, velocity_0, impulse_0) = bounce(&a, bounces.0, normal * -1., correction);
+    let (pose_1, velocity_1, impulse_1) = bounce(&b, bounces.1, normal, correction);
+    resolution.impulse = impulse_1.or_else(|| impulse_0.map(|impulse| impulse * -1.));
+    if let Some(impulse) = resolution.impulse {
+        if delta_seconds > 0. {
+            resolution.normal_force = impulse.magnitude() / delta_seconds;
+        }
+    }
+    if correct_position {
+        resolution.poses = (pose_0, pose_1);
+    }
+    resolution.velocities = (velocity_0, velocity_1);
+    resolution
+}
+
+/// Updated next frame pose and velocity of a bouncing body, and the impulse applied to it
+type BounceUpdate<P, R> = (
+    PoseUpdate<P, R>,
+    VelocityUpdate<<P as EuclideanSpace>::Diff>,
+    Option<<P as EuclideanSpace>::Diff>,
+);
+
+fn bounce<'a, P, R>(
+    body: &LinearResolveData<'a, P, R>,
+    bounce: Option<&ArcadeBounce>,
+    normal: P::Diff,
+    correction: Real,
+) -> BounceUpdate<P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + 'a,
+    P::Diff: Debug + Zero + Clone + InnerSpace,
+    R: Rotation<P> + 'a,
+{
+    let (bounce, mass, pose, velocity) = match (bounce, body.mass, body.position, body.velocity) {
+        (Some(bounce), Some(mass), Some(pose), Some(velocity)) if mass.inverse_mass() > 0. => {
+            (bounce, mass, pose, velocity)
+        }
+        _ => return (None, None, None),
+    };
+    let pose = NextFrame {
+        value: BodyPose::new(
+            *pose.value.position() + normal * correction,
+            *pose.value.rotation(),
+        ),
+    };
+    if velocity.value.linear.dot(normal) >= 0. {
+        return (Some(pose), None, None);
+    }
+    let linear = reflect_velocity(velocity.value.linear, normal, bounce.coefficient);
+    let impulse = (linear - velocity.value.linear) * mass.mass();
+    (
+        Some(pose),
+        Some(NextFrame {
+            value: Velocity { linear },
+        }),
+        Some(impulse),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::Vector2;
+
+    use super::*;
+
+    #[test]
+    fn test_reflect_velocity() {
+        let normal = Vector2::new(0., 1.);
+        assert_ulps_eq!(
+            reflect_velocity(Vector2::new(1., -2.), normal, 1.),
+            Vector2::new(1., 2.)
+        );
+        assert_ulps_eq!(
+            reflect_velocity(Vector2::new(1., -2.), normal, 0.5),
+            Vector2::new(1., 1.)
+        );
+        assert_ulps_eq!(
+            reflect_velocity(Vector2::new(1., 2.), normal, 0.5),
+            Vector2::new(1., 2.)
+        );
+    }
+}
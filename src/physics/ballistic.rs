@@ -0,0 +1,232 @@
+use std::vec::Vec;
+
+use cgmath::{EuclideanSpace, InnerSpace, VectorSpace};
+
+use super::{integrate_linear, DeadReckoning};
+use Real;
+
+/// Lightweight ballistic body, e.g. for bullets, particles and trajectory previews.
+///
+/// Moves using the gravity and drag of a [`DeadReckoning`](struct.DeadReckoning.html) model, with
+/// the same integration scheme as the contact solver, without the cost of a full rigid body.
+///
+/// # Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+#[derive(Debug, Clone)]
+pub struct BallisticBody<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+{
+    /// Current position
+    pub position: P,
+    /// Current velocity
+    pub velocity: P::Diff,
+}
+
+impl<P> BallisticBody<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: VectorSpace<Scalar = Real>,
+{
+    /// Create a new ballistic body
+    pub fn new(position: P, velocity: P::Diff) -> Self {
+        Self { position, velocity }
+    }
+
+    /// Advance the body a single step of the given length
+    pub fn step(&mut self, model: &DeadReckoning<P::Diff>, delta_seconds: Real) {
+        let acceleration = model.acceleration - self.velocity * model.linear_drag;
+        let (position, velocity) =
+            integrate_linear(self.position, self.velocity, acceleration, delta_seconds);
+        self.position = position;
+        self.velocity = velocity;
+    }
+
+    /// Advance the body by the given elapsed time, simulated as whole steps of the model, with any
+    /// remaining time simulated as a final shorter step.
+    pub fn advance(&mut self, model: &DeadReckoning<P::Diff>, elapsed: Real) {
+        for dt in steps(model.step, elapsed) {
+            self.step(model, dt);
+        }
+    }
+}
+
+/// Split the elapsed time into whole steps, and a final shorter step, like
+/// [`DeadReckoning::predict`](struct.DeadReckoning.html#method.predict)
+fn steps(step: Real, elapsed: Real) -> Vec<Real> {
+    let mut steps = Vec::new();
+    let mut remaining = elapsed;
+    if step > 0. {
+        while remaining >= step {
+            steps.push(step);
+            remaining -= step;
+        }
+    }
+    if remaining > 0. {
+        steps.push(remaining);
+    }
+    steps
+}
+
+/// Compute the trajectory of a ballistic body, e.g. for previews.
+///
+/// # Parameters:
+///
+/// - `model`: Gravity and drag model
+/// - `position`: Start position
+/// - `velocity`: Start velocity
+/// - `duration`: Length of the trajectory in seconds
+///
+/// # Returns:
+///
+/// The start position, followed by the position after each step of the model.
+pub fn trajectory<P>(
+    model: &DeadReckoning<P::Diff>,
+    position: P,
+    velocity: P::Diff,
+    duration: Real,
+) -> Vec<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: VectorSpace<Scalar = Real>,
+{
+    let mut body = BallisticBody::new(position, velocity);
+    let mut positions = vec![position];
+    for dt in steps(model.step, duration) {
+        body.step(model, dt);
+        positions.push(body.position);
+    }
+    positions
+}
+
+/// Compute the launch velocity needed to hit a target after the given time of flight.
+///
+/// The velocity is exact for the simulation: a body launched with it, and moved with the given
+/// model, will be at the target after the time of flight.
+///
+/// # Parameters:
+///
+/// - `model`: Gravity and drag model
+/// - `from`: Launch position
+/// - `target`: Target position
+/// - `time_of_flight`: Time until the target is hit, must be positive
+///
+/// # Returns:
+///
+/// The launch velocity, `None` if the time of flight is not positive.
+pub fn launch_velocity<P>(
+    model: &DeadReckoning<P::Diff>,
+    from: P,
+    target: P,
+    time_of_flight: Real,
+) -> Option<P::Diff>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: VectorSpace<Scalar = Real>,
+{
+    if time_of_flight <= 0. {
+        return None;
+    }
+    // the integration is linear in the launch velocity and the acceleration, so the end position
+    // is from + velocity_factor * velocity + acceleration_factor * acceleration
+    let (mut velocity_factor, mut velocity_speed) = (0., 1.);
+    let (mut acceleration_factor, mut acceleration_speed) = (0., 0.);
+    for dt in steps(model.step, time_of_flight) {
+        velocity_speed += -model.linear_drag * velocity_speed * dt;
+        velocity_factor += velocity_speed * dt;
+        acceleration_speed += (1. - model.linear_drag * acceleration_speed) * dt;
+        acceleration_factor += acceleration_speed * dt;
+    }
+    if velocity_factor <= 0. {
+        return None;
+    }
+    Some((target - from - model.acceleration * acceleration_factor) / velocity_factor)
+}
+
+/// Compute the launch velocity needed to hit a target with the given launch speed.
+///
+/// Finds the shortest time of flight where the target can be reached with at most the given
+/// speed, which is the flattest trajectory, and launches with exactly that time of flight, see
+/// [`launch_velocity`](fn.launch_velocity.html).
+///
+/// # Parameters:
+///
+/// - `model`: Gravity and drag model
+/// - `from`: Launch position
+/// - `target`: Target position
+/// - `speed`: Launch speed
+/// - `max_time`: Longest time of flight to consider
+///
+/// # Returns:
+///
+/// The launch velocity and time of flight, `None` if the target is out of reach.
+pub fn launch_velocity_with_speed<P>(
+    model: &DeadReckoning<P::Diff>,
+    from: P,
+    target: P,
+    speed: Real,
+    max_time: Real,
+) -> Option<(P::Diff, Real)>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace<Scalar = Real>,
+{
+    let required_speed = |time| {
+        launch_velocity(model, from, target, time)
+            .map(|velocity| velocity.magnitude())
+            .unwrap_or(Real::INFINITY)
+    };
+    let search_step = if model.step > 0. {
+        model.step
+    } else {
+        max_time / 100.
+    };
+    if search_step <= 0. {
+        return None;
+    }
+    // scan for the first time of flight that is reachable, then refine by bisection
+    let mut low = 0.;
+    let mut high = search_step;
+    while required_speed(high) > speed {
+        if high >= max_time {
+            return None;
+        }
+        low = high;
+        high = (high + search_step).min(max_time);
+    }
+    for _ in 0..32 {
+        let middle = (low + high) / 2.;
+        if required_speed(middle) > speed {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    launch_velocity(model, from, target, high).map(|velocity| (velocity, high))
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Point2, Vector2};
+
+    use super::*;
+
+    #[test]
+    fn test_launch_velocity_hits_target() {
+        let model = DeadReckoning::new(Vector2::new(0., -10.), 0.5, 0.1);
+        let from = Point2::new(0., 0.);
+        let target = Point2::new(10., 2.);
+        let velocity = launch_velocity(&model, from, target, 1.25).unwrap();
+        let mut body = BallisticBody::new(from, velocity);
+        body.advance(&model, 1.25);
+        assert_relative_eq!(body.position, target, epsilon = 1e-3);
+
+        let (velocity, time) =
+            launch_velocity_with_speed(&model, from, target, 20., 10.).unwrap();
+        assert!(velocity.magnitude() <= 20. + 1e-3);
+        let end = *trajectory(&model, from, velocity, time).last().unwrap();
+        assert_relative_eq!(end, target, epsilon = 1e-3);
+        assert!(launch_velocity_with_speed(&model, from, target, 1., 10.).is_none());
+    }
+}
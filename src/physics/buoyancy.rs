@@ -0,0 +1,273 @@
+use std::f64::consts::PI;
+use std::vec::Vec;
+
+use cgmath::{EuclideanSpace, Point2, Transform, Vector2, Zero};
+use cgmath::num_traits::NumCast;
+use collision::HasAabb;
+use collision::primitive::Primitive2;
+
+use Real;
+use collide::CollisionShape;
+
+/// Number of segments used when approximating circles as polygons
+const CIRCLE_SEGMENTS: usize = 16;
+
+/// Water area for 2D buoyancy.
+///
+/// Put on a body with a collision shape, that shape will be the volume of the water. Bodies with
+/// collision shapes that overlap the water will get a buoyancy force applied, proportional to the
+/// submerged area of their shape, and a drag force proportional to their velocity. The forces act
+/// at the centroid of the submerged area, so a tilted body is turned by the buoyancy, and spinning
+/// bodies are slowed down by the angular drag.
+///
+/// The primitives of the water shape should not overlap each other.
+#[derive(Debug, Clone)]
+pub struct WaterArea2 {
+    /// Density of the water
+    pub density: Real,
+    /// Drag coefficient, drag force is `-linear_drag * submerged area * velocity`
+    pub linear_drag: Real,
+    /// Gravity acceleration used for computing buoyancy
+    pub gravity: Vector2<Real>,
+    /// Angular drag coefficient, drag torque is `-angular_drag * submerged area * angular velocity`
+    pub angular_drag: Real,
+}
+
+impl WaterArea2 {
+    /// Create a new water area
+    pub fn new(density: Real, linear_drag: Real, gravity: Vector2<Real>) -> Self {
+        Self {
+            density,
+            linear_drag,
+            gravity,
+            angular_drag: 0.,
+        }
+    }
+
+    /// Set the angular drag coefficient, builder pattern
+    pub fn with_angular_drag(mut self, angular_drag: Real) -> Self {
+        self.angular_drag = angular_drag;
+        self
+    }
+
+    /// Compute the buoyancy and drag force for a body.
+    ///
+    /// # Parameters:
+    ///
+    /// - `area`: Submerged area of the body
+    /// - `velocity`: Linear velocity of the body
+    pub fn force(&self, area: Real, velocity: Vector2<Real>) -> Vector2<Real> {
+        self.gravity * (-self.density * area) - velocity * (self.linear_drag * area)
+    }
+
+    /// Compute the angular drag torque for a body.
+    ///
+    /// # Parameters:
+    ///
+    /// - `area`: Submerged area of the body
+    /// - `angular`: Angular velocity of the body
+    pub fn drag_torque(&self, area: Real, angular: Real) -> Real {
+        -angular * self.angular_drag * area
+    }
+}
+
+/// Compute the submerged area of a shape, and the centroid of the submerged area.
+///
+/// Circles are approximated by polygons, particles have no area.
+///
+/// # Parameters:
+///
+/// - `shape`: The shape of the body
+/// - `transform`: Model-to-world transform of the body
+/// - `water`: The shape of the water
+/// - `water_transform`: Model-to-world transform of the water
+///
+/// # Returns:
+///
+/// The submerged area and its centroid in world space, or `None` if the shape is not submerged.
+pub fn submerged_area<T>(
+    shape: &CollisionShape<Primitive2<Real>, T>,
+    transform: &T,
+    water: &CollisionShape<Primitive2<Real>, T>,
+    water_transform: &T,
+) -> Option<(Real, Point2<Real>)>
+where
+    T: Transform<Point2<Real>>,
+{
+    let water_polygons = water
+        .primitives()
+        .iter()
+        .map(|(p, t)| polygon(p, t, water_transform))
+        .collect::<Vec<_>>();
+    let mut total_area = 0.;
+    let mut weighted_centroid = Vector2::zero();
+    for (p, t) in shape.primitives() {
+        let body_polygon = polygon(p, t, transform);
+        for water_polygon in &water_polygons {
+            let (area, centroid) = area_centroid(&clip(&body_polygon, water_polygon));
+            total_area += area;
+            weighted_centroid += centroid.to_vec() * area;
+        }
+    }
+    if total_area > 0. {
+        Some((total_area, Point2::from_vec(weighted_centroid / total_area)))
+    } else {
+        None
+    }
+}
+
+/// Get the counter clockwise world space polygon for a primitive
+fn polygon<T>(primitive: &Primitive2<Real>, local: &T, transform: &T) -> Vec<Point2<Real>>
+where
+    T: Transform<Point2<Real>>,
+{
+    let points = match *primitive {
+        Primitive2::Particle(_) => Vec::default(),
+        Primitive2::Circle(ref circle) => (0..CIRCLE_SEGMENTS)
+            .map(|i| {
+                let angle: Real =
+                    NumCast::from(2. * PI * i as f64 / CIRCLE_SEGMENTS as f64).unwrap();
+                Point2::new(circle.radius * angle.cos(), circle.radius * angle.sin())
+            })
+            .collect(),
+        Primitive2::Rectangle(ref rectangle) => {
+            let bound = rectangle.get_bound();
+            vec![
+                bound.min,
+                Point2::new(bound.max.x, bound.min.y),
+                bound.max,
+                Point2::new(bound.min.x, bound.max.y),
+            ]
+        }
+        Primitive2::ConvexPolygon(ref polygon) => polygon.vertices.clone(),
+    };
+    let mut points = points
+        .into_iter()
+        .map(|p| transform.transform_point(local.transform_point(p)))
+        .collect::<Vec<_>>();
+    if signed_area(&points) < 0. {
+        points.reverse();
+    }
+    points
+}
+
+pub(crate) fn signed_area(polygon: &[Point2<Real>]) -> Real {
+    (0..polygon.len())
+        .map(|i| {
+            let a = polygon[i];
+            let b = polygon[(i + 1) % polygon.len()];
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<Real>() * 0.5
+}
+
+/// Compute area and centroid of a counter clockwise polygon
+pub(crate) fn area_centroid(polygon: &[Point2<Real>]) -> (Real, Point2<Real>) {
+    let area = signed_area(polygon);
+    if area <= 0. {
+        return (0., Point2::origin());
+    }
+    let centroid = (0..polygon.len())
+        .map(|i| {
+            let a = polygon[i];
+            let b = polygon[(i + 1) % polygon.len()];
+            (a.to_vec() + b.to_vec()) * (a.x * b.y - b.x * a.y)
+        })
+        .fold(Vector2::zero(), |sum, v| sum + v) / (6. * area);
+    (area, Point2::from_vec(centroid))
+}
+
+/// Sutherland-Hodgman clipping of a polygon against a convex counter clockwise polygon
+fn clip(subject: &[Point2<Real>], clip: &[Point2<Real>]) -> Vec<Point2<Real>> {
+    let mut output = subject.to_vec();
+    for i in 0..clip.len() {
+        if output.is_empty() {
+            break;
+        }
+        let a = clip[i];
+        let b = clip[(i + 1) % clip.len()];
+        let edge = b - a;
+        let inside = |p: &Point2<Real>| edge.perp_dot(*p - a);
+        let input = output;
+        output = Vec::with_capacity(input.len() + 1);
+        for j in 0..input.len() {
+            let current = input[j];
+            let previous = input[(j + input.len() - 1) % input.len()];
+            let current_side = inside(&current);
+            let previous_side = inside(&previous);
+            if current_side >= 0. {
+                if previous_side < 0. {
+                    output.push(intersection(previous, current, previous_side, current_side));
+                }
+                output.push(current);
+            } else if previous_side >= 0. {
+                output.push(intersection(previous, current, previous_side, current_side));
+            }
+        }
+    }
+    output
+}
+
+fn intersection(
+    from: Point2<Real>,
+    to: Point2<Real>,
+    from_side: Real,
+    to_side: Real,
+) -> Point2<Real> {
+    from + (to - from) * (from_side / (from_side - to_side))
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, Decomposed, Point2, Rad, Rotation2, Vector2};
+    use collision::primitive::{Circle, Rectangle};
+
+    use super::*;
+    use collide::{CollisionMode, CollisionStrategy};
+
+    fn transform(x: Real, y: Real) -> Decomposed<Vector2<Real>, Basis2<Real>> {
+        Decomposed {
+            disp: Vector2::new(x, y),
+            rot: Rotation2::from_angle(Rad(0.)),
+            scale: 1.,
+        }
+    }
+
+    #[test]
+    fn test_submerged_rectangle() {
+        let water = CollisionShape::new_simple(
+            CollisionStrategy::CollisionOnly,
+            CollisionMode::Discrete,
+            Primitive2::from(Rectangle::new(100., 10.)),
+        );
+        let body = CollisionShape::new_simple(
+            CollisionStrategy::FullResolution,
+            CollisionMode::Discrete,
+            Primitive2::from(Rectangle::new(2., 2.)),
+        );
+        let (area, centroid) =
+            submerged_area(&body, &transform(0., 5.), &water, &transform(0., 0.)).unwrap();
+        assert_ulps_eq!(2., area);
+        assert_ulps_eq!(Point2::new(0., 4.5), centroid);
+
+        assert!(submerged_area(&body, &transform(0., 10.), &water, &transform(0., 0.)).is_none());
+    }
+
+    #[test]
+    fn test_submerged_circle() {
+        let water = CollisionShape::new_simple(
+            CollisionStrategy::CollisionOnly,
+            CollisionMode::Discrete,
+            Primitive2::from(Rectangle::new(100., 10.)),
+        );
+        let body = CollisionShape::new_simple(
+            CollisionStrategy::FullResolution,
+            CollisionMode::Discrete,
+            Primitive2::from(Circle::new(1.)),
+        );
+        let (area, centroid) =
+            submerged_area(&body, &transform(0., 0.), &water, &transform(0., 0.)).unwrap();
+        assert!(area > 3. && area < 3.15);
+        assert_ulps_eq!(Point2::new(0., 0.), centroid);
+    }
+}
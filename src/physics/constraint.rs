@@ -0,0 +1,176 @@
+use cgmath::{EuclideanSpace, InnerSpace, One, Point3, Quaternion, Rotation, Vector3};
+
+use Real;
+
+/// Default fraction of the plane distance error that is corrected each frame
+const DEFAULT_BIAS_FACTOR: Real = 0.2;
+
+/// Plane constraint for 3D.
+///
+/// Keeps a body on a plane in world space, e.g. for making a 2D game with 3D bodies. The velocity
+/// of the body along the plane normal is removed, and any distance from the plane is corrected
+/// over a number of frames, controlled by the bias factor. Motion within the plane is not
+/// affected.
+#[derive(Debug, Clone)]
+pub struct PlaneConstraint3 {
+    /// Plane normal, in world space
+    pub normal: Vector3<Real>,
+    /// Distance of the plane from the origin, along the normal
+    pub distance: Real,
+    /// Fraction of the distance error that is corrected each frame
+    pub bias_factor: Real,
+}
+
+impl PlaneConstraint3 {
+    /// Create a new plane constraint, with the default bias factor.
+    ///
+    /// # Parameters:
+    ///
+    /// - `normal`: Plane normal, in world space
+    /// - `distance`: Distance of the plane from the origin, along the normal
+    pub fn new(normal: Vector3<Real>, distance: Real) -> Self {
+        Self {
+            normal: normal.normalize(),
+            distance,
+            bias_factor: DEFAULT_BIAS_FACTOR,
+        }
+    }
+
+    /// Create a plane constraint for the plane through the given point
+    pub fn through(normal: Vector3<Real>, point: Point3<Real>) -> Self {
+        let normal = normal.normalize();
+        Self::new(normal, point.to_vec().dot(normal))
+    }
+
+    /// Set the bias factor, builder pattern
+    pub fn with_bias_factor(mut self, bias_factor: Real) -> Self {
+        self.bias_factor = bias_factor;
+        self
+    }
+}
+
+/// Upright constraint for 3D.
+///
+/// Keeps an axis of a body, given in model space, aligned with an axis in world space, e.g. to
+/// keep characters or vehicles from tipping over. Rotation around the world axis is not affected.
+///
+/// The strength is the rate at which a misalignment is corrected, per second, so the behaviour
+/// does not depend on the time step. An infinite strength gives a rigid constraint.
+#[derive(Debug, Clone)]
+pub struct UprightConstraint3 {
+    /// Up axis of the body, in model space
+    pub up: Vector3<Real>,
+    /// Up axis to align with, in world space
+    pub world_up: Vector3<Real>,
+    /// Correction rate, per second
+    pub strength: Real,
+}
+
+impl UprightConstraint3 {
+    /// Create a new upright constraint, keeping the Y axis of the body aligned with world Y.
+    ///
+    /// # Parameters:
+    ///
+    /// - `strength`: Correction rate, per second
+    pub fn new(strength: Real) -> Self {
+        Self {
+            up: Vector3::unit_y(),
+            world_up: Vector3::unit_y(),
+            strength,
+        }
+    }
+
+    /// Set the up axis of the body, in model space, builder pattern
+    pub fn with_up(mut self, up: Vector3<Real>) -> Self {
+        self.up = up.normalize();
+        self
+    }
+
+    /// Set the up axis to align with, in world space, builder pattern
+    pub fn with_world_up(mut self, world_up: Vector3<Real>) -> Self {
+        self.world_up = world_up.normalize();
+        self
+    }
+}
+
+/// Plane constraint resolution.
+///
+/// # Parameters:
+///
+/// - `constraint`: The plane constraint
+/// - `position`: Body position for next frame
+/// - `velocity`: Linear velocity for next frame
+/// - `dt`: Time step
+///
+/// # Returns:
+///
+/// The new linear velocity of the body.
+pub fn solve_plane_constraint(
+    constraint: &PlaneConstraint3,
+    position: &Point3<Real>,
+    velocity: Vector3<Real>,
+    dt: Real,
+) -> Vector3<Real> {
+    if dt <= 0. {
+        return velocity;
+    }
+    let error = position.to_vec().dot(constraint.normal) - constraint.distance;
+    let lambda = -(velocity.dot(constraint.normal) + constraint.bias_factor * error / dt);
+    velocity + constraint.normal * lambda
+}
+
+/// Upright constraint resolution.
+///
+/// # Parameters:
+///
+/// - `constraint`: The upright constraint
+/// - `rotation`: Body rotation for next frame
+/// - `dt`: Time step
+///
+/// # Returns:
+///
+/// The new rotation of the body.
+pub fn solve_upright_constraint(
+    constraint: &UprightConstraint3,
+    rotation: &Quaternion<Real>,
+    dt: Real,
+) -> Quaternion<Real> {
+    if dt <= 0. || constraint.strength <= 0. {
+        return *rotation;
+    }
+    let up = rotation.rotate_vector(constraint.up);
+    let correction = Quaternion::from_arc(up, constraint.world_up, None);
+    let fraction = 1. - (-constraint.strength * dt).exp();
+    (Quaternion::one().slerp(correction, fraction) * rotation).normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Rad, Rotation3};
+
+    use super::*;
+
+    #[test]
+    fn test_plane_constraint() {
+        let constraint = PlaneConstraint3::new(Vector3::unit_z(), 0.);
+        let mut position = Point3::new(0., 0., 1.);
+        let mut velocity = Vector3::new(1., 0., 2.);
+        for _ in 0..100 {
+            velocity = solve_plane_constraint(&constraint, &position, velocity, 0.1);
+            position += velocity * 0.1;
+        }
+        assert!(position.z.abs() < 1e-3);
+        assert_ulps_eq!(velocity.x, 1.);
+    }
+
+    #[test]
+    fn test_upright_constraint() {
+        let constraint = UprightConstraint3::new(10.);
+        let mut rotation = Quaternion::from_angle_x(Rad(1.)) * Quaternion::from_angle_y(Rad(0.5));
+        for _ in 0..120 {
+            rotation = solve_upright_constraint(&constraint, &rotation, 1. / 60.);
+        }
+        let up = rotation.rotate_vector(Vector3::unit_y());
+        assert!((up - Vector3::unit_y()).magnitude() < 1e-3);
+    }
+}
@@ -0,0 +1,78 @@
+use cgmath::{EuclideanSpace, VectorSpace, Zero};
+
+use Real;
+
+/// Smoothed correction of the position of a body, e.g. when an authoritative pose arrives from a
+/// server.
+///
+/// Instead of snapping the body to the authoritative position, the error is applied gradually
+/// over a number of frames, while the body keeps being simulated locally. The offset is applied
+/// by the [`PoseCorrectionSystem`](../ecs/physics/struct.PoseCorrectionSystem.html). Only the
+/// position is blended, rotations should be set directly.
+///
+/// # Type parameters:
+///
+/// - `V`: cgmath vector type
+#[derive(Debug, Clone)]
+pub struct PoseCorrection<V> {
+    offset: V,
+    frames: u32,
+}
+
+impl<V> PoseCorrection<V>
+where
+    V: VectorSpace<Scalar = Real> + Zero,
+{
+    /// Create a new inactive pose correction
+    pub fn new() -> Self {
+        Self {
+            offset: V::zero(),
+            frames: 0,
+        }
+    }
+
+    /// Start blending towards an authoritative position, replacing any ongoing correction.
+    ///
+    /// # Parameters:
+    ///
+    /// - `local`: Position of the body in the local simulation
+    /// - `authoritative`: Authoritative position of the body
+    /// - `frames`: Number of frames to blend over, 0 or 1 will snap the body on the next frame
+    pub fn correct<P>(&mut self, local: P, authoritative: P, frames: u32)
+    where
+        P: EuclideanSpace<Scalar = Real, Diff = V>,
+    {
+        self.offset = authoritative - local;
+        self.frames = frames.max(1);
+    }
+
+    /// Is a correction in progress
+    pub fn is_active(&self) -> bool {
+        self.frames > 0
+    }
+
+    /// Get the part of the error that has not been applied yet
+    pub fn remaining(&self) -> V {
+        self.offset
+    }
+
+    /// Get the offset to apply this frame, and advance the correction
+    pub fn next_offset(&mut self) -> V {
+        if self.frames == 0 {
+            return V::zero();
+        }
+        let offset = self.offset / self.frames as Real;
+        self.offset = self.offset - offset;
+        self.frames -= 1;
+        offset
+    }
+}
+
+impl<V> Default for PoseCorrection<V>
+where
+    V: VectorSpace<Scalar = Real> + Zero,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,94 @@
+use super::JointCollision;
+use Real;
+
+/// Distance constraint.
+///
+/// Keeps two bodies within a range of distances from each other. With a single length, the
+/// distance is fixed, e.g. for a pendulum rod, and with only a maximum length it behaves like a
+/// rope, that can go slack but never stretch, e.g. for tethers.
+///
+/// Unlike the [`DistanceJoint`](struct.DistanceJoint.html), which is solved on its own before the
+/// contacts, distance constraints are solved together with the contacts, by the
+/// [`SequentialImpulseSolver`](struct.SequentialImpulseSolver.html), so a tethered body resting
+/// on the ground does not fight the tether. The accumulated impulse is kept in the constraint,
+/// and used for warm starting the next step.
+///
+/// # Type parameters:
+///
+/// - `ID`: The ID type of the bodies. In the ECS case, this will be `Entity`.
+#[derive(Debug, Clone)]
+pub struct DistanceConstraint<ID> {
+    /// The constrained bodies
+    pub bodies: (ID, ID),
+    /// Minimum distance between the body positions
+    pub min_length: Real,
+    /// Maximum distance between the body positions
+    pub max_length: Real,
+    /// Collision between the constrained bodies
+    pub collision: JointCollision,
+    impulse: Real,
+}
+
+impl<ID> DistanceConstraint<ID> {
+    /// Create a new constraint that keeps the bodies at a fixed distance.
+    ///
+    /// # Parameters:
+    ///
+    /// - `a`: The first body
+    /// - `b`: The second body
+    /// - `length`: Distance to keep between the body positions
+    pub fn new(a: ID, b: ID, length: Real) -> Self {
+        Self::range(a, b, length, length)
+    }
+
+    /// Create a new rope constraint, that keeps the bodies at most the given distance apart.
+    ///
+    /// # Parameters:
+    ///
+    /// - `a`: The first body
+    /// - `b`: The second body
+    /// - `max_length`: Maximum distance between the body positions
+    pub fn rope(a: ID, b: ID, max_length: Real) -> Self {
+        Self::range(a, b, 0., max_length)
+    }
+
+    /// Create a new constraint that keeps the distance between the bodies within a range.
+    ///
+    /// # Parameters:
+    ///
+    /// - `a`: The first body
+    /// - `b`: The second body
+    /// - `min_length`: Minimum distance between the body positions
+    /// - `max_length`: Maximum distance between the body positions
+    pub fn range(a: ID, b: ID, min_length: Real, max_length: Real) -> Self {
+        Self {
+            bodies: (a, b),
+            min_length,
+            max_length,
+            collision: JointCollision::default(),
+            impulse: 0.,
+        }
+    }
+
+    /// Set the collision between the constrained bodies, builder pattern
+    pub fn with_collision(mut self, collision: JointCollision) -> Self {
+        self.collision = collision;
+        self
+    }
+
+    /// Check if the distance is fixed
+    pub fn is_fixed(&self) -> bool {
+        self.min_length >= self.max_length
+    }
+
+    /// Get the impulse applied along the constraint during the last step, positive when the
+    /// constraint pushes the bodies apart
+    pub fn impulse(&self) -> Real {
+        self.impulse
+    }
+
+    /// Set the accumulated impulse, used by the solver
+    pub(crate) fn set_impulse(&mut self, impulse: Real) {
+        self.impulse = impulse;
+    }
+}
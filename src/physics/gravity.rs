@@ -0,0 +1,109 @@
+use std::fmt::Debug;
+
+use cgmath::{EuclideanSpace, Rotation, Zero};
+
+use Real;
+
+/// Global gravity, the acceleration applied to all bodies that have a mass.
+///
+/// # Type parameters:
+///
+/// - `V`: cgmath vector type
+#[derive(Debug, Clone)]
+pub struct Gravity<V> {
+    /// Gravitational acceleration
+    pub acceleration: V,
+}
+
+impl<V> Gravity<V> {
+    /// Create new gravity
+    pub fn new(acceleration: V) -> Self {
+        Self { acceleration }
+    }
+}
+
+impl<V> Default for Gravity<V>
+where
+    V: Zero,
+{
+    fn default() -> Self {
+        Self::new(V::zero())
+    }
+}
+
+/// Per body gravity scale, multiplies the gravity applied to the body, after any
+/// [`GravityVolume`](struct.GravityVolume.html) override.
+///
+/// Zero disables gravity for the body, e.g. for flying enemies, and negative values make the body
+/// fall upwards. Bodies without a gravity scale use a scale of one.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct GravityScale(pub Real);
+
+impl Default for GravityScale {
+    fn default() -> Self {
+        GravityScale(1.)
+    }
+}
+
+/// How a [`GravityVolume`](struct.GravityVolume.html) changes the gravity for bodies inside it
+#[derive(Debug, Clone)]
+pub enum GravityOverride<P, R>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    /// Replace the gravity with the given acceleration
+    Replace(P::Diff),
+    /// Rotate the gravity, e.g. for wall walking sections
+    Rotate(R),
+}
+
+/// Volume that overrides gravity for bodies inside it.
+///
+/// The volume uses the collision shape on the same body. If the position of a body is inside
+/// multiple volumes, only the volume with the highest priority is applied.
+///
+/// # Type parameters:
+///
+/// - `P`: cgmath point type
+/// - `R`: cgmath rotation type
+#[derive(Debug, Clone)]
+pub struct GravityVolume<P, R>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    /// Gravity override
+    pub gravity: GravityOverride<P, R>,
+    /// Priority of the volume, when volumes overlap
+    pub priority: i32,
+}
+
+impl<P, R> GravityVolume<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: Debug,
+    R: Rotation<P>,
+{
+    /// Create a new gravity volume, with priority 0
+    pub fn new(gravity: GravityOverride<P, R>) -> Self {
+        Self {
+            gravity,
+            priority: 0,
+        }
+    }
+
+    /// Set the priority of the volume
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Apply the override to the given gravitational acceleration
+    pub fn apply(&self, acceleration: P::Diff) -> P::Diff {
+        match self.gravity {
+            GravityOverride::Replace(ref replacement) => *replacement,
+            GravityOverride::Rotate(ref rotation) => rotation.rotate_vector(acceleration),
+        }
+    }
+}
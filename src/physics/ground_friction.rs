@@ -0,0 +1,76 @@
+use cgmath::VectorSpace;
+
+use super::Mass;
+use Real;
+
+/// Ground friction for top-down worlds.
+///
+/// In a pure top-down setup there is no floor for bodies to have contact friction with, so bodies
+/// with this component get a friction force proportional to their velocity applied instead, as if
+/// they were sliding on a virtual floor.
+#[derive(Debug, Clone)]
+pub struct GroundFriction {
+    /// Friction coefficient, the fraction of the velocity that is lost per second
+    pub coefficient: Real,
+}
+
+impl GroundFriction {
+    /// Create new ground friction
+    pub fn new(coefficient: Real) -> Self {
+        Self { coefficient }
+    }
+}
+
+/// Zone that changes the ground friction for bodies inside it.
+///
+/// The zone uses the collision shape on the same body. If the position of a body is inside
+/// multiple zones, the zone with the highest priority is used.
+#[derive(Debug, Clone)]
+pub struct FrictionZone {
+    /// Multiplier applied to the ground friction coefficient of bodies inside the zone
+    pub multiplier: Real,
+    /// Priority of the zone, when zones overlap
+    pub priority: i32,
+}
+
+impl FrictionZone {
+    /// Create a new friction zone, with priority 0
+    pub fn new(multiplier: Real) -> Self {
+        Self {
+            multiplier,
+            priority: 0,
+        }
+    }
+
+    /// Set the priority of the zone
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// Compute the ground friction force for a body.
+///
+/// The force is limited so that it will never do more than stop the body during the given time
+/// step.
+///
+/// # Parameters:
+///
+/// - `velocity`: current linear velocity of the body
+/// - `mass`: mass of the body
+/// - `coefficient`: ground friction coefficient, including any zone multiplier
+/// - `dt`: time step
+pub fn ground_friction_force<V>(velocity: V, mass: &Mass, coefficient: Real, dt: Real) -> V
+where
+    V: VectorSpace<Scalar = Real>,
+{
+    if !mass.mass().is_finite() {
+        return V::zero();
+    }
+    let coefficient = if dt > 0. {
+        coefficient.min(1. / dt)
+    } else {
+        coefficient
+    };
+    velocity * (-coefficient * mass.mass())
+}
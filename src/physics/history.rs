@@ -0,0 +1,132 @@
+use std::collections::VecDeque;
+use std::vec::Vec;
+
+use cgmath::{EuclideanSpace, Rotation};
+
+use super::Velocity;
+use {BodyPose, Real};
+
+/// A single recorded state in a [`PoseHistory`](struct.PoseHistory.html)
+#[derive(Clone)]
+pub struct PoseSample<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: Clone,
+    R: Rotation<P>,
+{
+    /// Pose of the body
+    pub pose: BodyPose<P, R>,
+    /// Velocity of the body, if it has one
+    pub velocity: Option<Velocity<P::Diff>>,
+}
+
+/// History of the last poses and velocities of a body.
+///
+/// Keeps a fixed number of samples, the oldest sample is dropped when a new sample is recorded
+/// and the history is full. Useful for motion trails, lag compensated hit tests, and for debugging
+/// bodies that teleport. Samples are recorded by the
+/// [`PoseHistorySystem`](../ecs/physics/struct.PoseHistorySystem.html).
+///
+/// # Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+#[derive(Clone)]
+pub struct PoseHistory<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: Clone,
+    R: Rotation<P>,
+{
+    capacity: usize,
+    samples: VecDeque<PoseSample<P, R>>,
+}
+
+impl<P, R> PoseHistory<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: Clone,
+    R: Rotation<P>,
+{
+    /// Create a new empty history, keeping at most `capacity` samples
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Get the maximum number of samples kept
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Get the number of recorded samples
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Is the history empty
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Record a sample, dropping the oldest sample if the history is full
+    pub fn record(&mut self, pose: BodyPose<P, R>, velocity: Option<Velocity<P::Diff>>) {
+        if self.capacity == 0 {
+            return;
+        }
+        while self.samples.len() >= self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(PoseSample { pose, velocity });
+    }
+
+    /// Get the sample recorded the given number of steps ago, 0 is the latest sample
+    pub fn get(&self, steps_ago: usize) -> Option<&PoseSample<P, R>> {
+        if steps_ago >= self.samples.len() {
+            return None;
+        }
+        self.samples.get(self.samples.len() - 1 - steps_ago)
+    }
+
+    /// Get the latest sample
+    pub fn latest(&self) -> Option<&PoseSample<P, R>> {
+        self.get(0)
+    }
+
+    /// Get the recorded positions, oldest first, e.g. for drawing a motion trail
+    pub fn positions(&self) -> Vec<P> {
+        self.samples.iter().map(|s| *s.pose.position()).collect()
+    }
+
+    /// Remove all samples
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, Point2, Rad, Rotation2};
+
+    use super::*;
+
+    #[test]
+    fn test_ring_buffer() {
+        let mut history = PoseHistory::<Point2<Real>, Basis2<Real>>::new(3);
+        for i in 0..5 {
+            history.record(
+                BodyPose::new(Point2::new(i as Real, 0.), Basis2::from_angle(Rad(0.))),
+                None,
+            );
+        }
+        assert_eq!(3, history.len());
+        assert_eq!(
+            vec![Point2::new(2., 0.), Point2::new(3., 0.), Point2::new(4., 0.)],
+            history.positions()
+        );
+        assert_eq!(Point2::new(3., 0.), *history.get(1).unwrap().pose.position());
+        assert!(history.get(3).is_none());
+    }
+}
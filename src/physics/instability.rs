@@ -0,0 +1,41 @@
+use std::fmt::Debug;
+
+use cgmath::EuclideanSpace;
+
+use Real;
+use collide::Contact;
+
+/// Kind of change that exceeded the sanity bounds of the contact solver
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InstabilityKind {
+    /// Velocity change of a body from a single contact
+    Velocity,
+    /// Position change of a body from a single contact
+    Position,
+}
+
+/// Solver instability, e.g. for respawning or freezing runaway bodies.
+///
+/// Emitted by the contact solver when resolving a contact changes the velocity or position of a
+/// body by more than the configured sanity bound, or produces a value that is not finite.
+///
+/// # Type parameters:
+///
+/// - `ID`: The ID type of the body. In the ECS case, this will be `Entity`.
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+#[derive(Debug, Clone)]
+pub struct InstabilityEvent<ID, P>
+where
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    /// The bodies of the offending contact
+    pub bodies: (ID, ID),
+    /// The offending contact
+    pub contact: Contact<P>,
+    /// The kind of change that exceeded the bound
+    pub kind: InstabilityKind,
+    /// Magnitude of the largest change applied to either body, not a number if the change was not
+    /// finite
+    pub magnitude: Real,
+}
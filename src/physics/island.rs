@@ -0,0 +1,211 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::vec::Vec;
+
+use cgmath::EuclideanSpace;
+
+use collide::ContactEvent;
+
+/// Island of bodies that touch each other, directly or through other bodies in the island.
+///
+/// Contacts in different islands never affect each other, so each island can be solved on its
+/// own, e.g. in parallel, and an island can be put to sleep as a whole when all its bodies are at
+/// rest. Static bodies, like the ground, do not connect islands, so two stacks standing on the
+/// same ground are separate islands. Static bodies are not part of any island.
+///
+/// # Type parameters:
+///
+/// - `ID`: The ID type of the bodies. In the ECS case, this will be `Entity`.
+#[derive(Debug, Clone)]
+pub struct Island<ID> {
+    /// The non static bodies in the island
+    pub bodies: Vec<ID>,
+    /// Indices of the contacts in the island, into the contact list the island was built from
+    pub contacts: Vec<usize>,
+}
+
+/// Find the root of a body, with path halving
+fn root(parents: &mut [usize], mut index: usize) -> usize {
+    while parents[index] != index {
+        parents[index] = parents[parents[index]];
+        index = parents[index];
+    }
+    index
+}
+
+/// Group bodies that touch each other into islands.
+///
+/// Contacts between two static bodies are not part of any island.
+///
+/// # Parameters:
+///
+/// - `contacts`: The contacts
+/// - `is_static`: Check if a body is static, e.g. has no mass, an infinite mass, or is frozen
+///
+/// # Returns:
+///
+/// The islands, in the order their first contact appears in the contact list. Bodies are listed
+/// in the order they first appear in the contacts of the island.
+pub fn build_islands<ID, P, F>(contacts: &[ContactEvent<ID, P>], is_static: F) -> Vec<Island<ID>>
+where
+    ID: Copy + Ord,
+    P: EuclideanSpace,
+    P::Diff: Debug,
+    F: Fn(&ID) -> bool,
+{
+    let mut indices = BTreeMap::new();
+    let mut ids = Vec::new();
+    let mut parents = Vec::new();
+    let mut index = |id: ID, ids: &mut Vec<ID>, parents: &mut Vec<usize>| {
+        *indices.entry(id).or_insert_with(|| {
+            ids.push(id);
+            parents.push(parents.len());
+            parents.len() - 1
+        })
+    };
+
+    // the non static body each contact belongs to, before merging
+    let mut contact_bodies = Vec::with_capacity(contacts.len());
+    for contact in contacts {
+        let (a, b) = contact.bodies;
+        let a = if is_static(&a) {
+            None
+        } else {
+            Some(index(a, &mut ids, &mut parents))
+        };
+        let b = if is_static(&b) {
+            None
+        } else {
+            Some(index(b, &mut ids, &mut parents))
+        };
+        if let (Some(a), Some(b)) = (a, b) {
+            let (a, b) = (root(&mut parents, a), root(&mut parents, b));
+            parents[a] = b;
+        }
+        contact_bodies.push(a.or(b));
+    }
+
+    let mut islands: Vec<Island<ID>> = Vec::new();
+    let mut island_indices = BTreeMap::new();
+    for (contact, body) in contact_bodies.into_iter().enumerate() {
+        if let Some(body) = body {
+            let island = *island_indices
+                .entry(root(&mut parents, body))
+                .or_insert_with(|| islands.len());
+            if island == islands.len() {
+                islands.push(Island {
+                    bodies: Vec::new(),
+                    contacts: Vec::new(),
+                });
+            }
+            islands[island].contacts.push(contact);
+        }
+    }
+    for (body, &id) in ids.iter().enumerate() {
+        if let Some(&island) = island_indices.get(&root(&mut parents, body)) {
+            islands[island].bodies.push(id);
+        }
+    }
+    islands
+}
+
+/// Group pairs of bodies into islands, by body index.
+///
+/// # Parameters:
+///
+/// - `count`: The number of bodies
+/// - `pairs`: The indices of the two bodies of each pair, e.g. of a contact constraint
+/// - `is_static`: Check if a body is static, static bodies do not connect islands
+///
+/// # Returns:
+///
+/// The indices of the pairs in each island, in ascending order. Pairs between two static bodies
+/// are not part of any island.
+pub(crate) fn group_pairs<F>(
+    count: usize,
+    pairs: &[(usize, usize)],
+    is_static: F,
+) -> Vec<Vec<usize>>
+where
+    F: Fn(usize) -> bool,
+{
+    let mut parents = (0..count).collect::<Vec<_>>();
+    let mut pair_bodies = Vec::with_capacity(pairs.len());
+    for &(a, b) in pairs {
+        let a = if is_static(a) { None } else { Some(a) };
+        let b = if is_static(b) { None } else { Some(b) };
+        if let (Some(a), Some(b)) = (a, b) {
+            let (a, b) = (root(&mut parents, a), root(&mut parents, b));
+            parents[a] = b;
+        }
+        pair_bodies.push(a.or(b));
+    }
+
+    let mut islands: Vec<Vec<usize>> = Vec::new();
+    let mut island_indices = BTreeMap::new();
+    for (pair, body) in pair_bodies.into_iter().enumerate() {
+        if let Some(body) = body {
+            let island = *island_indices
+                .entry(root(&mut parents, body))
+                .or_insert_with(|| islands.len());
+            if island == islands.len() {
+                islands.push(Vec::new());
+            }
+            islands[island].push(pair);
+        }
+    }
+    islands
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Point2, Vector2};
+
+    use super::*;
+    use Real;
+    use collide::{CollisionStrategy, Contact};
+
+    fn contact(a: u32, b: u32) -> ContactEvent<u32, Point2<Real>> {
+        ContactEvent::new(
+            (a, b),
+            Contact::new_impl(CollisionStrategy::FullResolution, Vector2::new(0., 1.), 0.1),
+        )
+    }
+
+    #[test]
+    fn test_static_bodies_split_islands() {
+        // two stacks on the ground, 0 is the ground
+        let contacts = vec![contact(0, 1), contact(0, 3), contact(1, 2), contact(3, 4)];
+        let islands = build_islands(&contacts, |&id| id == 0);
+        assert_eq!(2, islands.len());
+        assert_eq!(vec![1, 2], islands[0].bodies);
+        assert_eq!(vec![0, 2], islands[0].contacts);
+        assert_eq!(vec![3, 4], islands[1].bodies);
+        assert_eq!(vec![1, 3], islands[1].contacts);
+    }
+
+    #[test]
+    fn test_contacts_merge_islands() {
+        let contacts = vec![
+            contact(1, 2),
+            contact(3, 4),
+            contact(2, 3),
+            contact(0, 5),
+            contact(0, 0),
+        ];
+        let islands = build_islands(&contacts, |&id| id == 0);
+        assert_eq!(2, islands.len());
+        assert_eq!(vec![1, 2, 3, 4], islands[0].bodies);
+        assert_eq!(vec![0, 1, 2], islands[0].contacts);
+        assert_eq!(vec![5], islands[1].bodies);
+        assert_eq!(vec![3], islands[1].contacts);
+    }
+
+    #[test]
+    fn test_group_pairs() {
+        // 0 is static, and 4 is in no pair
+        let pairs = [(1, 2), (0, 3), (2, 1), (0, 0), (3, 0)];
+        let islands = group_pairs(5, &pairs, |index| index == 0);
+        assert_eq!(vec![vec![0, 2], vec![1, 4]], islands);
+    }
+}
@@ -0,0 +1,877 @@
+use std::vec::Vec;
+
+use cgmath::{EuclideanSpace, InnerSpace, Rotation, Zero};
+
+use super::{LinearVector, OrientationError, RotationalResolution, RotationalResolveData,
+            RotationalVector, SpringSettings};
+use Real;
+
+/// Default fraction of the distance error that is corrected each frame
+const DEFAULT_BIAS_FACTOR: Real = 0.2;
+
+/// Default constraint softness
+const DEFAULT_SOFTNESS: Real = 0.01;
+
+/// Collision between the bodies connected by a joint, e.g. to keep the parts of a ragdoll or a
+/// vehicle from colliding with each other.
+///
+/// Enforced by filtering pairs in the broad phase, see
+/// [`JointCollisionSystem`](../ecs/physics/struct.JointCollisionSystem.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum JointCollision {
+    /// The connected bodies collide with each other
+    #[default]
+    Enabled,
+    /// The connected bodies never collide with each other
+    DisableConnected,
+    /// No bodies in the same assembly collide with each other, where the assembly is all bodies
+    /// connected through joints that use this setting
+    DisableAssembly,
+}
+
+/// Distance joint.
+///
+/// Keeps two bodies at a fixed distance from each other. The joint is not owned by either body, so
+/// a body can be connected to any number of joints, and joints can form closed loops, e.g. a
+/// four-bar linkage. Joints are solved together at the velocity level, over a number of
+/// iterations, see [`solve_distance_joint`](fn.solve_distance_joint.html).
+///
+/// The joint is a soft constraint. The softness regularizes the constraint, so redundant
+/// constraints in a loop do not fight each other, and the bias factor controls how fast distance
+/// errors are corrected. Both can instead be given in frequency and damping ratio form, using
+/// [`with_spring`](#method.with_spring), which does not need retuning when the time step changes.
+///
+/// # Type parameters:
+///
+/// - `ID`: The ID type of the bodies. In the ECS case, this will be `Entity`.
+#[derive(Debug, Clone)]
+pub struct DistanceJoint<ID> {
+    /// The connected bodies
+    pub bodies: (ID, ID),
+    /// Distance between the body positions
+    pub length: Real,
+    /// Constraint softness, in units of inverse mass, zero gives a rigid constraint
+    pub softness: Real,
+    /// Fraction of the distance error that is corrected each frame
+    pub bias_factor: Real,
+    /// Spring settings, when set these replace the softness and bias factor
+    pub spring: Option<SpringSettings>,
+    /// Collision between the connected bodies
+    pub collision: JointCollision,
+    impulse: Real,
+}
+
+impl<ID> DistanceJoint<ID> {
+    /// Create a new distance joint, with the default softness and bias factor.
+    ///
+    /// # Parameters:
+    ///
+    /// - `a`: The first body
+    /// - `b`: The second body
+    /// - `length`: Distance to keep between the body positions
+    pub fn new(a: ID, b: ID, length: Real) -> Self {
+        Self {
+            bodies: (a, b),
+            length,
+            softness: DEFAULT_SOFTNESS,
+            bias_factor: DEFAULT_BIAS_FACTOR,
+            spring: None,
+            collision: JointCollision::default(),
+            impulse: 0.,
+        }
+    }
+
+    /// Set the constraint softness, builder pattern
+    pub fn with_softness(mut self, softness: Real) -> Self {
+        self.softness = softness;
+        self
+    }
+
+    /// Set the bias factor, builder pattern
+    pub fn with_bias_factor(mut self, bias_factor: Real) -> Self {
+        self.bias_factor = bias_factor;
+        self
+    }
+
+    /// Make the joint a spring, builder pattern.
+    ///
+    /// The softness and bias factor are computed each step from the spring settings, the
+    /// effective mass of the bodies and the time step.
+    pub fn with_spring(mut self, spring: SpringSettings) -> Self {
+        self.spring = Some(spring);
+        self
+    }
+
+    /// Set the collision between the connected bodies, builder pattern
+    pub fn with_collision(mut self, collision: JointCollision) -> Self {
+        self.collision = collision;
+        self
+    }
+
+    /// Get the impulse applied along the joint during the last step, positive when the joint
+    /// pushes the bodies apart
+    pub fn impulse(&self) -> Real {
+        self.impulse
+    }
+
+    /// Reset the accumulated impulse, should be called once at the start of each step
+    pub fn reset_impulse(&mut self) {
+        self.impulse = 0.;
+    }
+}
+
+/// Body data used for joint resolution
+#[derive(Debug, Clone)]
+pub struct JointBodyData<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+{
+    /// Position for next frame
+    pub position: P,
+    /// Linear velocity for next frame
+    pub velocity: P::Diff,
+    /// Inverse mass, zero for bodies that are not moved by the joint
+    pub inverse_mass: Real,
+}
+
+/// Distance joint resolution, a single iteration.
+///
+/// Computes the impulse that removes the relative velocity along the joint, plus a bias that
+/// corrects the distance error, regularized by the joint softness. The impulse is accumulated in
+/// the joint, so iterating over all joints a number of times converges for closed loops, instead
+/// of each joint undoing the work of the others.
+///
+/// # Parameters:
+///
+/// - `joint`: The joint
+/// - `a`: The first body
+/// - `b`: The second body
+/// - `dt`: Time step
+///
+/// # Returns:
+///
+/// New linear velocities for the first and second body.
+pub fn solve_distance_joint<ID, P>(
+    joint: &mut DistanceJoint<ID>,
+    a: JointBodyData<P>,
+    b: JointBodyData<P>,
+    dt: Real,
+) -> (P::Diff, P::Diff)
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace,
+{
+    let total_inverse_mass = a.inverse_mass + b.inverse_mass;
+    let offset = b.position - a.position;
+    let distance = offset.magnitude();
+    if total_inverse_mass <= 0. || dt <= 0. || distance <= Real::EPSILON {
+        return (a.velocity, b.velocity);
+    }
+    let axis = offset / distance;
+    let relative_velocity = (b.velocity - a.velocity).dot(axis);
+    let (softness, bias_factor) = match joint.spring {
+        Some(ref spring) => spring.soft_constraint(1. / total_inverse_mass, dt),
+        None => (joint.softness, joint.bias_factor),
+    };
+    let bias = bias_factor * (distance - joint.length) / dt;
+    let lambda = -(relative_velocity + bias + softness * joint.impulse)
+        / (total_inverse_mass + softness);
+    joint.impulse += lambda;
+    let impulse = axis * lambda;
+    (
+        a.velocity - impulse * a.inverse_mass,
+        b.velocity + impulse * b.inverse_mass,
+    )
+}
+
+impl<P> JointBodyData<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+{
+    /// Data for a body that is not moved by joints, e.g. a fixed pivot
+    pub fn fixed(position: P) -> Self {
+        Self {
+            position,
+            velocity: P::Diff::zero(),
+            inverse_mass: 0.,
+        }
+    }
+}
+
+/// Kind of a [`Joint`](struct.Joint.html).
+///
+/// Axes are given in the model space of the first body.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JointKind<V> {
+    /// Keeps the anchors together, the bodies can rotate freely
+    Ball,
+    /// Keeps the anchors together, and the hinge axes of the bodies aligned, so the bodies can only
+    /// rotate around the axis relative to each other. In 2D, all rotation is around the same axis,
+    /// so a ball joint should be used instead.
+    Hinge(V),
+    /// Keeps the anchors together, and the relative rotation of the bodies
+    Fixed,
+    /// Keeps the second anchor on the line through the first anchor along the axis, and the
+    /// relative rotation of the bodies
+    Slider(V),
+}
+
+/// Joint between two bodies.
+///
+/// Connects an anchor point on each body, given in the model space of the bodies. Like the
+/// [`DistanceJoint`](struct.DistanceJoint.html), the joint is not owned by either body, and joints
+/// are solved together at the velocity level over a number of iterations, see
+/// [`solve_joint`](fn.solve_joint.html). The linear part of the joint is a soft constraint, with
+/// the same softness and bias factor.
+///
+/// The joint impulses are applied at the anchors, so a body hanging from an anchor away from its
+/// position swings like a pendulum, and the rotational part of hinge, fixed and slider joints is
+/// enforced on the angular velocities. This needs bodies with an inverse inertia, bodies that can
+/// not be turned through their angular velocity only have their rotation projected, see
+/// [`project_joint_rotation`](fn.project_joint_rotation.html). The relative rotation that is kept
+/// by fixed and slider joints, and the hinge axis of the second body, are taken from the rotations
+/// the first time the joint is solved, unless given explicitly.
+///
+/// Each linear axis can be given its own spring, see
+/// [`with_axis_spring`](#method.with_axis_spring), e.g. a slider joint with a spring along the
+/// slider axis acts as a suspension. The rotational part can be made soft as well, see
+/// [`with_angular_spring`](#method.with_angular_spring).
+///
+/// # Type parameters:
+///
+/// - `ID`: The ID type of the bodies. In the ECS case, this will be `Entity`.
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+#[derive(Debug, Clone)]
+pub struct Joint<ID, P, R>
+where
+    P: EuclideanSpace,
+    P::Diff: LinearVector,
+{
+    /// The connected bodies
+    pub bodies: (ID, ID),
+    /// Kind of joint
+    pub kind: JointKind<P::Diff>,
+    /// Anchor points, in the model space of the first and second body
+    pub anchors: (P, P),
+    /// Constraint softness, in units of inverse mass, zero gives a rigid constraint
+    pub softness: Real,
+    /// Fraction of the anchor error that is corrected each frame
+    pub bias_factor: Real,
+    /// Collision between the connected bodies
+    pub collision: JointCollision,
+    /// Springs for single linear axes, given in the model space of the first body, these replace
+    /// the softness and bias factor along the axis
+    pub axis_springs: Vec<(P::Diff, SpringSettings)>,
+    /// Spring for the rotational part, when set this replaces the rigid rotational constraint
+    pub angular_spring: Option<SpringSettings>,
+    relative_rotation: Option<R>,
+    impulse: P::Diff,
+    angular_impulse: <P::Diff as LinearVector>::Angular,
+}
+
+impl<ID, P, R> Joint<ID, P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: LinearVector,
+    R: Rotation<P>,
+{
+    /// Create a new joint, with the anchors at the body positions, and the default softness and
+    /// bias factor.
+    ///
+    /// # Parameters:
+    ///
+    /// - `a`: The first body
+    /// - `b`: The second body
+    /// - `kind`: Kind of joint
+    pub fn new(a: ID, b: ID, kind: JointKind<P::Diff>) -> Self {
+        Self {
+            bodies: (a, b),
+            kind,
+            anchors: (P::origin(), P::origin()),
+            softness: DEFAULT_SOFTNESS,
+            bias_factor: DEFAULT_BIAS_FACTOR,
+            collision: JointCollision::default(),
+            axis_springs: Vec::default(),
+            angular_spring: None,
+            relative_rotation: None,
+            impulse: P::Diff::zero(),
+            angular_impulse: Zero::zero(),
+        }
+    }
+
+    /// Set the anchor points, in the model space of the first and second body, builder pattern
+    pub fn with_anchors(mut self, a: P, b: P) -> Self {
+        self.anchors = (a, b);
+        self
+    }
+
+    /// Set the rotation of the second body relative to the first body, builder pattern.
+    ///
+    /// Used by fixed and slider joints, and for the hinge axis of the second body.
+    pub fn with_relative_rotation(mut self, rotation: R) -> Self {
+        self.relative_rotation = Some(rotation);
+        self
+    }
+
+    /// Set the constraint softness, builder pattern
+    pub fn with_softness(mut self, softness: Real) -> Self {
+        self.softness = softness;
+        self
+    }
+
+    /// Set the bias factor, builder pattern
+    pub fn with_bias_factor(mut self, bias_factor: Real) -> Self {
+        self.bias_factor = bias_factor;
+        self
+    }
+
+    /// Set the collision between the connected bodies, builder pattern
+    pub fn with_collision(mut self, collision: JointCollision) -> Self {
+        self.collision = collision;
+        self
+    }
+
+    /// Add a spring for the motion along a single axis, given in the model space of the first
+    /// body, builder pattern.
+    ///
+    /// The motion along the axis is solved with the spring settings, and the motion across it
+    /// with the joint softness and bias factor. Springs on multiple axes should use orthogonal
+    /// axes. On a slider joint, a spring along the slider axis pulls the anchors together along
+    /// the otherwise free axis.
+    pub fn with_axis_spring(mut self, axis: P::Diff, spring: SpringSettings) -> Self
+    where
+        P::Diff: InnerSpace,
+    {
+        self.axis_springs.push((axis.normalize(), spring));
+        self
+    }
+
+    /// Make the rotational part soft, builder pattern.
+    ///
+    /// The angular velocity constraint is solved with the spring settings, like an axis spring.
+    /// Rotations that are projected, see
+    /// [`project_joint_rotation`](fn.project_joint_rotation.html), are not integrated as a spring.
+    /// Instead, the fraction of the rotation error given by the spring settings is corrected each
+    /// step, so the rotation eases towards the constrained rotation without overshooting.
+    pub fn with_angular_spring(mut self, spring: SpringSettings) -> Self {
+        self.angular_spring = Some(spring);
+        self
+    }
+
+    /// Get the rotation of the second body relative to the first body, if known
+    pub fn relative_rotation(&self) -> Option<&R> {
+        self.relative_rotation.as_ref()
+    }
+
+    /// Get the impulse applied to the second body during the last step, the negated impulse is
+    /// applied to the first body
+    pub fn impulse(&self) -> P::Diff {
+        self.impulse
+    }
+
+    /// Get the angular impulse applied to the second body by the rotational part during the last
+    /// step, the negated angular impulse is applied to the first body
+    pub fn angular_impulse(&self) -> <P::Diff as LinearVector>::Angular {
+        self.angular_impulse
+    }
+
+    /// Reset the accumulated impulses, should be called once at the start of each step
+    pub fn reset_impulse(&mut self) {
+        self.impulse = P::Diff::zero();
+        self.angular_impulse = Zero::zero();
+    }
+}
+
+/// Rotational part of joint resolution, for bodies that can not be turned through their angular
+/// velocity, see [`solve_joint`](fn.solve_joint.html).
+///
+/// Keeps the hinge axes aligned for hinge joints, and the relative rotation for fixed and slider
+/// joints, by rotating the second body, or the first body if the second body has zero inverse
+/// mass. Ball joints are not affected. The rotation is set directly, so it carries no angular
+/// velocity. The relative rotation is taken from the given rotations if
+/// the joint has none. If the joint has an angular spring, only part of the rotation is applied.
+///
+/// # Parameters:
+///
+/// - `joint`: The joint
+/// - `a`: Rotation of the first body, and inverse mass
+/// - `b`: Rotation of the second body, and inverse mass
+/// - `dt`: Time step
+///
+/// # Returns:
+///
+/// New rotations for the first and second body.
+pub fn project_joint_rotation<ID, P, R>(
+    joint: &mut Joint<ID, P, R>,
+    a: (R, Real),
+    b: (R, Real),
+    dt: Real,
+) -> (R, R)
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: RotationalVector,
+    R: Rotation<P> + OrientationError<<P::Diff as LinearVector>::Angular>,
+{
+    let (a_target, b_target) = joint_rotation_targets(joint, a, b);
+    match joint.angular_spring {
+        Some(ref spring) => {
+            let fraction = spring.soft_constraint(1., dt).1;
+            let ease = |rotation: R, target: &R| {
+                let error = rotation.orientation_error(target);
+                rotation.rotated(P::Diff::angular_scale(error, fraction))
+            };
+            (ease(a.0, &a_target), ease(b.0, &b_target))
+        }
+        None => (a_target, b_target),
+    }
+}
+
+/// Fully constrained rotations for the bodies of a joint
+fn joint_rotation_targets<ID, P, R>(
+    joint: &mut Joint<ID, P, R>,
+    a: (R, Real),
+    b: (R, Real),
+) -> (R, R)
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: RotationalVector,
+    R: Rotation<P>,
+{
+    let ((a_rotation, a_inverse_mass), (b_rotation, b_inverse_mass)) = (a, b);
+    let relative = *joint
+        .relative_rotation
+        .get_or_insert_with(|| a_rotation.invert() * b_rotation);
+    if a_inverse_mass <= 0. && b_inverse_mass <= 0. {
+        return (a_rotation, b_rotation);
+    }
+    match joint.kind {
+        JointKind::Ball => (a_rotation, b_rotation),
+        JointKind::Hinge(axis) => {
+            let axis = axis.normalize();
+            let a_axis = a_rotation.rotate_vector(axis);
+            let b_axis = b_rotation.rotate_vector(relative.invert().rotate_vector(axis));
+            if b_inverse_mass > 0. {
+                (a_rotation, R::between_vectors(b_axis, a_axis) * b_rotation)
+            } else {
+                (R::between_vectors(a_axis, b_axis) * a_rotation, b_rotation)
+            }
+        }
+        JointKind::Fixed | JointKind::Slider(_) => {
+            if b_inverse_mass > 0. {
+                (a_rotation, a_rotation * relative)
+            } else {
+                (b_rotation * relative.invert(), b_rotation)
+            }
+        }
+    }
+}
+
+/// Rotation from the rotation of the second body of a joint to its constrained rotation, in world
+/// space, `None` for ball joints
+fn joint_rotation_error<ID, P, R>(
+    joint: &mut Joint<ID, P, R>,
+    a_rotation: &R,
+    b_rotation: &R,
+) -> Option<<P::Diff as LinearVector>::Angular>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: RotationalVector,
+    R: Rotation<P> + OrientationError<<P::Diff as LinearVector>::Angular>,
+{
+    let relative = *joint
+        .relative_rotation
+        .get_or_insert_with(|| a_rotation.invert() * *b_rotation);
+    let target = match joint.kind {
+        JointKind::Ball => return None,
+        JointKind::Hinge(axis) => {
+            let axis = axis.normalize();
+            let a_axis = a_rotation.rotate_vector(axis);
+            let b_axis = b_rotation.rotate_vector(relative.invert().rotate_vector(axis));
+            R::between_vectors(b_axis, a_axis) * *b_rotation
+        }
+        JointKind::Fixed | JointKind::Slider(_) => *a_rotation * relative,
+    };
+    Some(b_rotation.orientation_error(&target))
+}
+
+/// Angular impulse that removes the given relative angular velocity of two bodies, with the given
+/// world space inverse inertias.
+///
+/// The impulse is applied along the relative angular velocity, so for inertias that differ per
+/// axis, the velocity is only removed along that direction, and the rest is removed over the
+/// solver iterations.
+fn angular_impulse<V>(
+    velocity: V::Angular,
+    a: &V::InverseInertia,
+    b: &V::InverseInertia,
+) -> V::Angular
+where
+    V: RotationalVector,
+{
+    let speed = V::angular_dot(velocity, velocity).sqrt();
+    if speed <= Real::EPSILON {
+        return V::Angular::zero();
+    }
+    let direction = V::angular_scale(velocity, 1. / speed);
+    let inverse_inertia = V::angular_dot(
+        direction,
+        V::apply_inverse_inertia(a, direction) + V::apply_inverse_inertia(b, direction),
+    );
+    if inverse_inertia <= 0. {
+        return V::Angular::zero();
+    }
+    V::angular_scale(direction, -speed / inverse_inertia)
+}
+
+/// Joint resolution, a single iteration.
+///
+/// Computes the impulse that removes the relative velocity of the anchors, plus a bias that
+/// corrects the anchor error, regularized by the joint softness. For slider joints, only the
+/// velocity and error across the slider axis are removed. Axes with a spring are solved
+/// separately, using the softness and bias factor of the spring. The impulse is applied at the
+/// anchors, so bodies with an inverse inertia are turned by anchors away from their position, and
+/// the effective mass includes the cross terms of the anchor offsets with the inverse inertia.
+///
+/// For hinge, fixed and slider joints, an angular impulse then removes the relative angular
+/// velocity of the bodies, plus a bias that corrects the rotation error, except around the hinge
+/// axis of hinge joints. The rotational part is rigid, unless the joint has an angular spring.
+///
+/// Both impulses are applied along the velocity they remove, and accumulated in the joint, like
+/// for [`solve_distance_joint`](fn.solve_distance_joint.html), so iterating over all joints a
+/// number of times converges.
+///
+/// # Parameters:
+///
+/// - `joint`: The joint
+/// - `a`: The first body, with its world space inverse inertia, and its rotation
+/// - `b`: The second body, with its world space inverse inertia, and its rotation
+/// - `dt`: Time step
+///
+/// # Returns:
+///
+/// The impulse applied at the anchors, and the new linear and angular velocities of the bodies.
+pub fn solve_joint<ID, P, R>(
+    joint: &mut Joint<ID, P, R>,
+    a: (RotationalResolveData<P>, &R),
+    b: (RotationalResolveData<P>, &R),
+    dt: Real,
+) -> RotationalResolution<P::Diff>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: RotationalVector,
+    R: Rotation<P> + OrientationError<<P::Diff as LinearVector>::Angular>,
+{
+    let ((a, a_rotation), (b, b_rotation)) = (a, b);
+    if a.inverse_mass + b.inverse_mass <= 0. || dt <= 0. {
+        return RotationalResolution {
+            impulse: P::Diff::zero(),
+            linear: (a.linear, b.linear),
+            angular: (a.angular, b.angular),
+        };
+    }
+    let a_offset = a_rotation.rotate_vector(joint.anchors.0.to_vec());
+    let b_offset = b_rotation.rotate_vector(joint.anchors.1.to_vec());
+    // inverse mass of the anchors along a direction
+    let inverse_mass = |direction: P::Diff| {
+        let angular_term = |body: &RotationalResolveData<P>, offset| {
+            let arm = P::Diff::cross(offset, direction);
+            P::Diff::angular_dot(
+                arm,
+                P::Diff::apply_inverse_inertia(&body.inverse_inertia, arm),
+            )
+        };
+        a.inverse_mass + b.inverse_mass + angular_term(&a, a_offset) + angular_term(&b, b_offset)
+    };
+    // slider joints leave the motion along the axis free
+    let constrained = |v: P::Diff| match joint.kind {
+        JointKind::Slider(axis) => {
+            let axis = a_rotation.rotate_vector(axis).normalize();
+            v - axis * v.dot(axis)
+        }
+        _ => v,
+    };
+    let springs = joint
+        .axis_springs
+        .iter()
+        .map(|&(axis, ref spring)| {
+            let axis = a_rotation.rotate_vector(axis);
+            let inverse_mass = inverse_mass(axis);
+            let (softness, bias_factor) = spring.soft_constraint(1. / inverse_mass, dt);
+            (axis, inverse_mass, softness, bias_factor)
+        })
+        .collect::<Vec<_>>();
+    // the axes with a spring are removed from the rigid part
+    let rigid = |v: P::Diff| {
+        constrained(springs.iter().fold(v, |v, &(axis, _, _, _)| v - axis * v.dot(axis)))
+    };
+    let relative_velocity = b.linear + P::Diff::point_velocity(b.angular, b_offset) - a.linear
+        - P::Diff::point_velocity(a.angular, a_offset);
+    let error = (b.center + b_offset) - (a.center + a_offset);
+    let residual = rigid(relative_velocity) + rigid(error) * (joint.bias_factor / dt)
+        + rigid(joint.impulse) * joint.softness;
+    let speed = residual.magnitude();
+    let mut lambda = P::Diff::zero();
+    if speed > Real::EPSILON {
+        let direction = residual / speed;
+        lambda = direction * (-speed / (inverse_mass(direction) + joint.softness));
+    }
+    for &(axis, inverse_mass, softness, bias_factor) in &springs {
+        let speed = relative_velocity.dot(axis)
+            + error.dot(axis) * (bias_factor / dt)
+            + joint.impulse.dot(axis) * softness;
+        lambda = lambda + axis * (-speed / (inverse_mass + softness));
+    }
+    joint.impulse = joint.impulse + lambda;
+    let turn = |body: &RotationalResolveData<P>, offset, impulse| {
+        P::Diff::apply_inverse_inertia(&body.inverse_inertia, P::Diff::cross(offset, impulse))
+    };
+    let linear = (
+        a.linear - lambda * a.inverse_mass,
+        b.linear + lambda * b.inverse_mass,
+    );
+    let mut angular = (
+        a.angular + turn(&a, a_offset, lambda * -1.),
+        b.angular + turn(&b, b_offset, lambda),
+    );
+
+    if let Some(error) = joint_rotation_error(joint, a_rotation, b_rotation) {
+        // the spring softness is relative to the effective mass, so it is given for a unit mass
+        let (softness, bias_factor) = match joint.angular_spring {
+            Some(ref spring) => spring.soft_constraint(1., dt),
+            None => (0., joint.bias_factor),
+        };
+        let scale = P::Diff::angular_scale;
+        // hinge joints leave the rotation around the axis free
+        let constrained = |angular| match joint.kind {
+            JointKind::Hinge(axis) => {
+                let axis = a_rotation.rotate_vector(axis).normalize();
+                angular + scale(P::Diff::angular_component(angular, axis), -1.)
+            }
+            _ => angular,
+        };
+        let residual =
+            constrained(angular.1 + scale(angular.0, -1.) + scale(error, -bias_factor / dt));
+        let removing =
+            angular_impulse::<P::Diff>(residual, &a.inverse_inertia, &b.inverse_inertia);
+        let angular_lambda = scale(
+            removing + scale(joint.angular_impulse, -softness),
+            1. / (1. + softness),
+        );
+        joint.angular_impulse = joint.angular_impulse + angular_lambda;
+        angular = (
+            angular.0
+                + P::Diff::apply_inverse_inertia(&a.inverse_inertia, scale(angular_lambda, -1.)),
+            angular.1 + P::Diff::apply_inverse_inertia(&b.inverse_inertia, angular_lambda),
+        );
+    }
+
+    RotationalResolution {
+        impulse: lambda,
+        linear,
+        angular,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, Matrix3, Point2, Point3, Quaternion, Rad, Rotation2, Rotation3, Vector2,
+                 Vector3};
+
+    use super::*;
+
+    fn body(position: Point2<Real>, linear: Vector2<Real>) -> RotationalResolveData<Point2<Real>> {
+        RotationalResolveData {
+            center: position,
+            linear,
+            angular: 0.,
+            inverse_mass: 1.,
+            inverse_inertia: 0.,
+        }
+    }
+
+    fn pivot(position: Point2<Real>) -> RotationalResolveData<Point2<Real>> {
+        RotationalResolveData {
+            inverse_mass: 0.,
+            ..body(position, Vector2::zero())
+        }
+    }
+
+    #[test]
+    fn test_four_bar_linkage() {
+        // ground link between the fixed pivots, crank, coupler and rocker close the loop
+        let positions = [
+            Point2::new(0., 0.),
+            Point2::new(0., 1.),
+            Point2::new(4., 1.),
+            Point2::new(4., 0.),
+        ];
+        let mut velocities = [
+            Vector2::zero(),
+            Vector2::new(1., 0.),
+            Vector2::new(0., 0.),
+            Vector2::zero(),
+        ];
+        let inverse_masses = [0., 1., 1., 0.];
+        let mut joints = vec![
+            DistanceJoint::new(0, 1, 1.).with_softness(0.),
+            DistanceJoint::new(1, 2, 4.).with_softness(0.),
+            DistanceJoint::new(2, 3, 1.).with_softness(0.),
+        ];
+        for _ in 0..20 {
+            for joint in &mut joints {
+                let (a, b) = joint.bodies;
+                let body = |i: usize| JointBodyData {
+                    position: positions[i],
+                    velocity: velocities[i],
+                    inverse_mass: inverse_masses[i],
+                };
+                let (va, vb) = solve_distance_joint(joint, body(a), body(b), 1. / 60.);
+                velocities[a] = va;
+                velocities[b] = vb;
+            }
+        }
+        for joint in &joints {
+            let (a, b) = joint.bodies;
+            let axis = (positions[b] - positions[a]).normalize();
+            assert_relative_eq!(0., (velocities[b] - velocities[a]).dot(axis), epsilon = 1e-4);
+        }
+        // the crank and rocker swing together
+        assert_relative_eq!(velocities[1].x, velocities[2].x, epsilon = 1e-4);
+        assert!(velocities[1].x > 0.);
+    }
+
+    #[test]
+    fn test_slider_joint_leaves_axis_free() {
+        let rotation = Basis2::from_angle(Rad(0.));
+        let mut joint = Joint::new(0, 1, JointKind::Slider(Vector2::new(1., 0.)))
+            .with_anchors(Point2::new(1., 0.), Point2::origin())
+            .with_softness(0.);
+        let pivot = pivot(Point2::origin());
+        let mut velocity = Vector2::new(1., -1.);
+        for _ in 0..10 {
+            let body = body(Point2::new(1., 0.), velocity);
+            let pivot = (pivot.clone(), &rotation);
+            velocity = solve_joint(&mut joint, pivot, (body, &rotation), 0.1).linear.1;
+        }
+        assert_relative_eq!(velocity, Vector2::new(1., 0.), epsilon = 1e-4);
+
+        joint.kind = JointKind::Ball;
+        joint.reset_impulse();
+        let body = body(Point2::new(1., 0.), velocity);
+        let velocity = solve_joint(&mut joint, (pivot, &rotation), (body, &rotation), 0.1).linear.1;
+        assert_relative_eq!(velocity, Vector2::zero(), epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_fixed_joint_keeps_relative_rotation() {
+        let mut joint: Joint<_, Point2<Real>, _> = Joint::new(0, 1, JointKind::Fixed);
+        let a = Basis2::from_angle(Rad(0.5));
+        let b = Basis2::from_angle(Rad(1.));
+        assert_eq!((a, b), project_joint_rotation(&mut joint, (a, 1.), (b, 1.), 0.1));
+
+        // the second body follows the first
+        let turned = Basis2::from_angle(Rad(1.5));
+        let (_, b) = project_joint_rotation(&mut joint, (turned, 1.), (b, 1.), 0.1);
+        assert_relative_eq!(b, Basis2::from_angle(Rad(2.)), epsilon = 1e-5);
+
+        // unless it is not moved by joints
+        let (a, _) = project_joint_rotation(&mut joint, (turned, 1.), (b, 0.), 0.1);
+        assert_relative_eq!(a, Basis2::from_angle(Rad(1.5)), epsilon = 1e-5);
+        let unturned = Basis2::from_angle(Rad(0.));
+        let (a, _) = project_joint_rotation(&mut joint, (a, 1.), (unturned, 0.), 0.1);
+        assert_relative_eq!(a, Basis2::from_angle(Rad(-0.5)), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_slider_with_axis_spring_is_suspension() {
+        let rotation = Basis2::from_angle(Rad(0.));
+        let mut joint = Joint::new(0, 1, JointKind::Slider(Vector2::new(0., 1.)))
+            .with_softness(0.)
+            .with_axis_spring(Vector2::new(0., 2.), SpringSettings::new(1., 1.));
+        let pivot = pivot(Point2::origin());
+        let dt = 1. / 60.;
+        let mut position = Point2::new(0.5, -1.);
+        let mut velocity = Vector2::new(0., 0.);
+        for _ in 0..240 {
+            joint.reset_impulse();
+            let body = (body(position, velocity), &rotation);
+            velocity = solve_joint(&mut joint, (pivot.clone(), &rotation), body, dt).linear.1;
+            position += velocity * dt;
+            // the rigid part keeps the body on the slider axis
+            assert!(position.x.abs() < 0.5);
+        }
+        assert_relative_eq!(position, Point2::origin(), epsilon = 1e-2);
+    }
+
+    #[test]
+    fn test_angular_spring_eases_rotation() {
+        let mut joint: Joint<_, Point2<Real>, _> =
+            Joint::new(0, 1, JointKind::Fixed).with_angular_spring(SpringSettings::new(1., 1.));
+        let a = Basis2::from_angle(Rad(0.));
+        project_joint_rotation(&mut joint, (a, 1.), (a, 1.), 0.1);
+
+        let turned = Basis2::from_angle(Rad(1.));
+        let (_, b) = project_joint_rotation(&mut joint, (turned, 1.), (a, 1.), 0.1);
+        let angle = a.orientation_error(&b);
+        assert!(angle > 0. && angle < 1.);
+    }
+
+    #[test]
+    fn test_off_center_ball_joint_swings() {
+        // a rod hanging from its end, pushed sideways at its center
+        let rotation = Basis2::from_angle(Rad(0.));
+        let mut joint = Joint::new(0, 1, JointKind::Ball)
+            .with_anchors(Point2::origin(), Point2::new(0., 1.))
+            .with_softness(0.);
+        let pivot = (pivot(Point2::origin()), &rotation);
+        let rod = RotationalResolveData {
+            inverse_inertia: 3.,
+            ..body(Point2::new(0., -1.), Vector2::new(1., 0.))
+        };
+        let mut resolution = solve_joint(&mut joint, pivot.clone(), (rod.clone(), &rotation), 0.1);
+        for _ in 0..10 {
+            let rod = RotationalResolveData {
+                linear: resolution.linear.1,
+                angular: resolution.angular.1,
+                ..rod.clone()
+            };
+            resolution = solve_joint(&mut joint, pivot.clone(), (rod, &rotation), 0.1);
+        }
+        // the rod swings around the pivot, the anchor stays at rest
+        let (linear, angular) = (resolution.linear.1, resolution.angular.1);
+        assert!(angular > 0.);
+        assert!(linear.x > 0.);
+        assert_relative_eq!(
+            linear + Vector2::point_velocity(angular, Vector2::new(0., 1.)),
+            Vector2::zero(),
+            epsilon = 1e-4
+        );
+    }
+
+    #[test]
+    fn test_hinge_joint_leaves_axis_free() {
+        let rotation = Quaternion::from_angle_z(Rad(0.));
+        let mut joint: Joint<_, Point3<Real>, _> =
+            Joint::new(0, 1, JointKind::Hinge(Vector3::new(0., 0., 1.))).with_softness(0.);
+        let body = |angular| RotationalResolveData {
+            center: Point3::origin(),
+            linear: Vector3::zero(),
+            angular,
+            inverse_mass: 1.,
+            inverse_inertia: Matrix3::new(1., 0., 0., 0., 2., 0., 0., 0., 1.),
+        };
+        let fixed = RotationalResolveData {
+            inverse_mass: 0.,
+            inverse_inertia: Matrix3::zero(),
+            ..body(Vector3::zero())
+        };
+        let mut angular = Vector3::new(1., 1., 1.);
+        for _ in 0..20 {
+            let (a, b) = ((fixed.clone(), &rotation), (body(angular), &rotation));
+            angular = solve_joint(&mut joint, a, b, 0.1).angular.1;
+        }
+        // only the spin around the hinge axis is left
+        assert_relative_eq!(angular, Vector3::new(0., 0., 1.), epsilon = 1e-4);
+    }
+}
@@ -0,0 +1,86 @@
+use cgmath::{InnerSpace, VectorSpace};
+
+use Real;
+
+/// Magnetic polarity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Polarity {
+    /// Positive pole
+    Positive,
+    /// Negative pole
+    Negative,
+}
+
+/// Magnet, attracts or repels nearby bodies with a
+/// [`MagnetSusceptible`](struct.MagnetSusceptible.html) component.
+///
+/// Opposite polarities attract each other, and equal polarities repel each other.
+#[derive(Debug, Clone)]
+pub struct Magnet {
+    /// Polarity of the magnet
+    pub polarity: Polarity,
+    /// Strength of the magnet
+    pub strength: Real,
+    /// Maximum distance at which the magnet affects other bodies
+    pub range: Real,
+}
+
+impl Magnet {
+    /// Create a new magnet
+    pub fn new(polarity: Polarity, strength: Real, range: Real) -> Self {
+        Self {
+            polarity,
+            strength,
+            range,
+        }
+    }
+
+    /// Compute the force the magnet applies to a susceptible body.
+    ///
+    /// The force falls off with the square of the distance, and is clamped for distances shorter
+    /// than 1 to avoid singularities.
+    ///
+    /// # Parameters:
+    ///
+    /// - `susceptible`: The susceptible body
+    /// - `offset`: Position of the susceptible body relative to the magnet
+    ///
+    /// # Returns:
+    ///
+    /// The force applied to the susceptible body. The magnet itself gets the opposite force.
+    pub fn force<V>(&self, susceptible: &MagnetSusceptible, offset: V) -> V
+    where
+        V: VectorSpace<Scalar = Real> + InnerSpace,
+    {
+        let distance_squared = offset.magnitude2();
+        if distance_squared > self.range * self.range || distance_squared == 0. {
+            return V::zero();
+        }
+        let magnitude = self.strength * susceptible.susceptibility / distance_squared.max(1.);
+        let direction = offset / distance_squared.sqrt();
+        if self.polarity == susceptible.polarity {
+            direction * magnitude
+        } else {
+            direction * -magnitude
+        }
+    }
+}
+
+/// Body that is affected by [`Magnet`](struct.Magnet.html)s
+#[derive(Debug, Clone)]
+pub struct MagnetSusceptible {
+    /// Polarity of the body
+    pub polarity: Polarity,
+    /// Scale factor for magnetic forces applied to the body
+    pub susceptibility: Real,
+}
+
+impl MagnetSusceptible {
+    /// Create a new susceptible body
+    pub fn new(polarity: Polarity, susceptibility: Real) -> Self {
+        Self {
+            polarity,
+            susceptibility,
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use Real;
+
+/// Surface material of a body
+///
+/// The contact solver uses the restitution of the material, and applies Coulomb friction using
+/// the friction coefficients. A contact sticks as long as the friction impulse needed to stop the
+/// sliding is within the static friction, otherwise the bodies slide with dynamic friction.
+///
+/// The restitution can vary with the impact speed, using a
+/// [`RestitutionCurve`](struct.RestitutionCurve.html), see
+/// [`restitution_at`](#method.restitution_at).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    /// Coefficient of restitution, 0 is perfectly inelastic, and 1 is perfectly elastic
+    pub restitution: Real,
+    /// Speed dependent restitution, replaces the constant restitution when set
+    pub restitution_curve: Option<RestitutionCurve>,
+    /// Coefficient of static friction
+    pub static_friction: Real,
+    /// Coefficient of dynamic friction, should not be larger than the static friction
+    pub dynamic_friction: Real,
+}
+
+impl Material {
+    /// Create a new material, with the same static and dynamic friction
+    pub fn new(restitution: Real, friction: Real) -> Self {
+        Self {
+            restitution,
+            restitution_curve: None,
+            static_friction: friction,
+            dynamic_friction: friction,
+        }
+    }
+
+    /// Set separate static and dynamic friction coefficients, builder pattern
+    pub fn with_friction(mut self, static_friction: Real, dynamic_friction: Real) -> Self {
+        self.static_friction = static_friction;
+        self.dynamic_friction = dynamic_friction;
+        self
+    }
+
+    /// Set a speed dependent restitution, builder pattern
+    pub fn with_restitution_curve(mut self, curve: RestitutionCurve) -> Self {
+        self.restitution_curve = Some(curve);
+        self
+    }
+
+    /// Get the coefficient of restitution for an impact at the given speed
+    ///
+    /// # Parameters:
+    ///
+    /// - `speed`: Speed the bodies approach each other with along the contact normal
+    pub fn restitution_at(&self, speed: Real) -> Real {
+        match self.restitution_curve {
+            Some(ref curve) => curve.restitution(speed),
+            None => self.restitution,
+        }
+    }
+
+    /// Rubber preset
+    pub fn rubber() -> Self {
+        Self::new(0.8, 0.9).with_friction(1., 0.9)
+    }
+
+    /// Ice preset
+    pub fn ice() -> Self {
+        Self::new(0.1, 0.02).with_friction(0.05, 0.02)
+    }
+
+    /// Wood preset
+    pub fn wood() -> Self {
+        Self::new(0.3, 0.5).with_friction(0.6, 0.5)
+    }
+
+    /// Metal preset
+    pub fn metal() -> Self {
+        Self::new(0.2, 0.4).with_friction(0.5, 0.4)
+    }
+
+    /// Bouncy preset, perfectly elastic
+    pub fn bouncy() -> Self {
+        Self::new(1., 0.5)
+    }
+
+    /// Combine two materials into the material used for a contact between them.
+    ///
+    /// Uses the restitution of the bouncier material, including its restitution curve, and the
+    /// geometric mean of the friction coefficients.
+    pub fn combine(&self, other: &Material) -> Material {
+        let bouncier = if other.restitution > self.restitution {
+            other
+        } else {
+            self
+        };
+        Material {
+            restitution: bouncier.restitution,
+            restitution_curve: bouncier.restitution_curve.clone(),
+            static_friction: (self.static_friction * other.static_friction).sqrt(),
+            dynamic_friction: (self.dynamic_friction * other.dynamic_friction).sqrt(),
+        }
+    }
+}
+
+/// Restitution that varies with the impact speed.
+///
+/// Constant restitution makes slow contacts jitter, and fast impacts unrealistically elastic.
+/// The curve blends linearly between the restitution at a low and a high impact speed, and
+/// clamps to those values outside the speed range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestitutionCurve {
+    /// Impact speed at, and below, which the low speed restitution is used
+    pub low_speed: Real,
+    /// Restitution at low impact speeds
+    pub low: Real,
+    /// Impact speed at, and above, which the high speed restitution is used
+    pub high_speed: Real,
+    /// Restitution at high impact speeds
+    pub high: Real,
+}
+
+impl RestitutionCurve {
+    /// Create a new restitution curve
+    ///
+    /// # Parameters:
+    ///
+    /// - `low_speed`: Impact speed at, and below, which `low` is used
+    /// - `low`: Restitution at low impact speeds
+    /// - `high_speed`: Impact speed at, and above, which `high` is used
+    /// - `high`: Restitution at high impact speeds
+    pub fn new(low_speed: Real, low: Real, high_speed: Real, high: Real) -> Self {
+        Self {
+            low_speed,
+            low,
+            high_speed,
+            high,
+        }
+    }
+
+    /// Get the restitution for an impact at the given speed
+    pub fn restitution(&self, speed: Real) -> Real {
+        if speed <= self.low_speed {
+            self.low
+        } else if speed >= self.high_speed {
+            self.high
+        } else {
+            let t = (speed - self.low_speed) / (self.high_speed - self.low_speed);
+            self.low + (self.high - self.low) * t
+        }
+    }
+}
+
+/// Handle to a material in a material registry, used by shapes to reference materials.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MaterialHandle(pub(crate) usize);
+
+/// Impact between two bodies, e.g. for playing impact sounds.
+///
+/// Emitted by the contact solver for contacts where the bodies approach each other faster than
+/// the impact threshold of the solver. The material pair can be used as a key to select a sound,
+/// without looking up any physics components.
+///
+/// # Type parameters:
+///
+/// - `ID`: The ID type of the body. In the ECS case, this will be `Entity`.
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+#[derive(Debug, Clone)]
+pub struct ImpactEvent<ID, P> {
+    /// The bodies that collided
+    pub bodies: (ID, ID),
+    /// The materials of the bodies, in the same order as the bodies
+    pub materials: (Option<MaterialHandle>, Option<MaterialHandle>),
+    /// Relative speed of the bodies along the contact normal, before resolution
+    pub speed: Real,
+    /// Contact point
+    pub point: P,
+}
+
+impl<ID, P> ImpactEvent<ID, P> {
+    /// Get the material pair in a canonical order, so the same two materials always give the same
+    /// key, regardless of which body is first
+    pub fn material_pair(&self) -> (Option<MaterialHandle>, Option<MaterialHandle>) {
+        let (a, b) = self.materials;
+        if a <= b {
+            (a, b)
+        } else {
+            (b, a)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restitution_curve() {
+        let material = Material::new(0.5, 0.5)
+            .with_restitution_curve(RestitutionCurve::new(1., 0.8, 11., 0.3));
+        assert_ulps_eq!(material.restitution_at(0.5), 0.8);
+        assert_ulps_eq!(material.restitution_at(6.), 0.55);
+        assert_ulps_eq!(material.restitution_at(20.), 0.3);
+        assert_ulps_eq!(Material::new(0.5, 0.5).restitution_at(20.), 0.5);
+
+        let combined = Material::new(0.2, 0.5).combine(&material);
+        assert_ulps_eq!(combined.restitution_at(20.), 0.3);
+    }
+}
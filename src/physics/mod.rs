@@ -1,14 +1,81 @@
 //! Physics related functionality
 //!
 
-pub use self::simple::{linear_resolve_contact, LinearResolveData};
+pub use self::angle::{wrap_angle, UnwrappedAngle};
+pub use self::arcade::{arcade_resolve_contact, reflect_velocity, ArcadeBounce};
+pub use self::ballistic::{launch_velocity, launch_velocity_with_speed, trajectory,
+                           BallisticBody};
+pub use self::buoyancy::{submerged_area, WaterArea2};
+pub use self::constraint::{solve_plane_constraint, solve_upright_constraint, PlaneConstraint3,
+                           UprightConstraint3};
+pub use self::correction::PoseCorrection;
+pub use self::distance::DistanceConstraint;
+pub use self::gravity::{Gravity, GravityOverride, GravityScale, GravityVolume};
+pub use self::ground_friction::{ground_friction_force, FrictionZone, GroundFriction};
+pub use self::history::{PoseHistory, PoseSample};
+pub use self::instability::{InstabilityEvent, InstabilityKind};
+pub use self::island::{build_islands, Island};
+pub use self::joint::{project_joint_rotation, solve_distance_joint, solve_joint, DistanceJoint,
+                      Joint, JointBodyData, JointCollision, JointKind};
+pub use self::magnet::{Magnet, MagnetSusceptible, Polarity};
+pub use self::material::{ImpactEvent, Material, MaterialHandle, RestitutionCurve};
+pub use self::orientation::{OrientationController, OrientationError};
+pub use self::path::{Path, PathFollower, PathMode};
+pub use self::pid::{JointAngleController, Pid, PidController, PidTarget};
+pub use self::prediction::{integrate_linear, DeadReckoning};
+pub use self::rotational::{integrate_rotation, relative_point_normal_velocity,
+                           rotational_friction_impulse, rotational_resolve_contact,
+                           world_inverse_inertia, AngularRotation, AngularVelocity, Inertia,
+                           Rolling, RotationalResolution, RotationalResolveData, RotationalVector};
+pub use self::sensor::{ContactSensor, ContactSensors};
+pub use self::sequential_impulse::{ImpulseCache, SequentialImpulseSolver};
+pub use self::simple::{classify_contact, linear_contact_impulse, linear_friction_impulse,
+                       linear_resolve_contact, relative_normal_velocity, ContactKind,
+                       LinearResolveData, PositionCorrection, SolverGroup};
+pub use self::solver::{flip_bodies, flip_frame, integrate_bodies, integrate_body, solve_contact,
+                       solve_contacts, ContactResolution, ContactSettings, SolverBody};
+pub use self::spring::{Spring, SpringAnchor, SpringSettings};
+pub use self::volume::{center_of_mass, recenter_shape, shape_inertia, Centroid, ShapeInertia,
+                       Volume};
+pub use self::wheel::{solve_wheel_joint, JointCommand, JointControl, WheelBodyData, WheelJoint2};
 
 pub mod prelude2d;
 pub mod prelude3d;
 
+use std::fmt::Debug;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
+
+use cgmath::{InnerSpace, Vector2, Vector3, VectorSpace, Zero};
+
 use Real;
 
+mod angle;
+mod arcade;
+mod ballistic;
+mod buoyancy;
+mod constraint;
+mod correction;
+mod distance;
+mod gravity;
+mod ground_friction;
+mod history;
+mod instability;
+mod island;
+mod joint;
+mod magnet;
+mod material;
+mod orientation;
+mod path;
+mod pid;
+mod prediction;
+mod rotational;
+mod sensor;
+mod sequential_impulse;
 mod simple;
+mod solver;
+mod spring;
+mod volume;
+mod wheel;
 
 /// Velocity
 #[derive(Debug, Clone)]
@@ -20,6 +87,72 @@ where
     pub linear: V,
 }
 
+impl<V> Add for Velocity<V>
+where
+    V: VectorSpace<Scalar = Real>,
+{
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Velocity {
+            linear: self.linear + other.linear,
+        }
+    }
+}
+
+impl<V> Sub for Velocity<V>
+where
+    V: VectorSpace<Scalar = Real>,
+{
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Velocity {
+            linear: self.linear - other.linear,
+        }
+    }
+}
+
+impl<V> Mul<Real> for Velocity<V>
+where
+    V: VectorSpace<Scalar = Real>,
+{
+    type Output = Self;
+
+    fn mul(self, scalar: Real) -> Self {
+        Velocity {
+            linear: self.linear * scalar,
+        }
+    }
+}
+
+impl<V> AddAssign for Velocity<V>
+where
+    V: VectorSpace<Scalar = Real>,
+{
+    fn add_assign(&mut self, other: Self) {
+        self.linear = self.linear + other.linear;
+    }
+}
+
+impl<V> SubAssign for Velocity<V>
+where
+    V: VectorSpace<Scalar = Real>,
+{
+    fn sub_assign(&mut self, other: Self) {
+        self.linear = self.linear - other.linear;
+    }
+}
+
+impl<V> MulAssign<Real> for Velocity<V>
+where
+    V: VectorSpace<Scalar = Real>,
+{
+    fn mul_assign(&mut self, scalar: Real) {
+        self.linear = self.linear * scalar;
+    }
+}
+
 /// Mass
 #[derive(Debug)]
 pub struct Mass {
@@ -42,4 +175,275 @@ impl Mass {
             }
         }
     }
+
+    /// Get the mass
+    pub fn mass(&self) -> Real {
+        self.mass
+    }
+
+    /// Get the inverse mass
+    pub fn inverse_mass(&self) -> Real {
+        self.inverse_mass
+    }
+}
+
+/// Density of a body, used to compute the mass of the body from the volume of its collision shape.
+///
+/// Bodies with a density are density-driven, their [`Mass`](struct.Mass.html) and
+/// [`Inertia`](struct.Inertia.html) are recomputed whenever their collision shape or density is
+/// modified.
+#[derive(Debug, Clone)]
+pub struct Density {
+    /// Density, mass per unit volume, or per unit area in 2D
+    pub density: Real,
+}
+
+impl Density {
+    /// Create a new density
+    pub fn new(density: Real) -> Self {
+        Self { density }
+    }
+
+    /// Compute the mass of a body with the given volume
+    pub fn mass(&self, volume: Real) -> Mass {
+        Mass::new(self.density * volume)
+    }
+}
+
+/// Velocity damping of a body, e.g. for air or water resistance.
+///
+/// Damping is applied exponentially during integration, so the velocity is scaled by
+/// `exp(-linear * dt)` every step, which is stable for any step length.
+///
+/// The angular coefficient damps the [`AngularVelocity`](struct.AngularVelocity.html) of the body.
+#[derive(Debug, Clone)]
+pub struct Damping {
+    /// Linear damping coefficient, per second
+    pub linear: Real,
+    /// Angular damping coefficient, per second
+    pub angular: Real,
+}
+
+impl Damping {
+    /// Create a new damping
+    ///
+    /// # Parameters:
+    ///
+    /// - `linear`: Linear damping coefficient, per second
+    /// - `angular`: Angular damping coefficient, per second
+    pub fn new(linear: Real, angular: Real) -> Self {
+        Self { linear, angular }
+    }
+
+    /// Damp a linear velocity over a step
+    pub fn damp_linear<V>(&self, velocity: V, delta_seconds: Real) -> V
+    where
+        V: VectorSpace<Scalar = Real>,
+    {
+        velocity * (-self.linear * delta_seconds).exp()
+    }
+
+    /// Damp an angular velocity over a step
+    pub fn damp_angular<V>(&self, angular: V::Angular, delta_seconds: Real) -> V::Angular
+    where
+        V: RotationalVector,
+    {
+        V::angular_scale(angular, (-self.angular * delta_seconds).exp())
+    }
+}
+
+/// Type of a body, controls how the contact solvers move the body.
+///
+/// Bodies without a body type are dynamic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BodyType {
+    /// Never moved, and not integrated. Acts as an immovable obstacle at rest, with an infinite
+    /// mass, in contacts. Accumulated forces are discarded.
+    Static,
+    /// Moved by forces and contacts
+    #[default]
+    Dynamic,
+    /// Moved by its velocity only, e.g. for moving platforms. Accumulated forces are discarded, and
+    /// contacts treat the body as an immovable obstacle with an infinite mass, moving with its
+    /// velocity.
+    Kinematic,
+}
+
+/// Linear vector type, with the matching type for angular quantities such as torque.
+///
+/// The angular type is a scalar in 2D, and a vector in 3D.
+pub trait LinearVector: VectorSpace<Scalar = Real> {
+    /// Type of angular quantities
+    type Angular: Copy + Debug + Zero + Add<Output = Self::Angular> + Send + Sync + 'static;
+}
+
+impl LinearVector for Vector2<Real> {
+    type Angular = Real;
+}
+
+impl LinearVector for Vector3<Real> {
+    type Angular = Vector3<Real>;
+}
+
+/// Force accumulator, used to accumulate all forces and torques applied to a body during a frame.
+///
+/// The accumulated force is consumed by the contact solver when computing the velocity for the
+/// next frame. The accumulated force, and the last consumed force, can be inspected without
+/// disturbing the integration, e.g. for debugging overlays.
+///
+/// Torques are accumulated in a separate channel, using the angular type of the force vector
+/// type, see [`LinearVector`](trait.LinearVector.html). Forces applied away from the body
+/// position, using [`add_force_at_point`](#method.add_force_at_point), feed both channels. The
+/// contact solver consumes the accumulated torque each frame, and turns bodies that have an
+/// [`AngularVelocity`](struct.AngularVelocity.html) and an [`Inertia`](struct.Inertia.html). The
+/// torque of other bodies is discarded.
+#[derive(Debug, Clone)]
+pub struct ForceAccumulator<F>
+where
+    F: LinearVector,
+{
+    force: F,
+    last_force: F,
+    torque: F::Angular,
+    last_torque: F::Angular,
+}
+
+impl<F> ForceAccumulator<F>
+where
+    F: LinearVector,
+{
+    /// Create a new force accumulator
+    pub fn new() -> Self {
+        Self {
+            force: F::zero(),
+            last_force: F::zero(),
+            torque: F::Angular::zero(),
+            last_torque: F::Angular::zero(),
+        }
+    }
+
+    /// Add a force vector to the accumulator
+    pub fn add_force(&mut self, force: F) {
+        self.force = self.force + force;
+    }
+
+    /// Get the accumulated force, and reset the accumulator
+    pub fn consume_force(&mut self) -> F {
+        let force = self.force;
+        self.force = F::zero();
+        self.last_force = force;
+        force
+    }
+
+    /// Get the force accumulated so far, without resetting the accumulator
+    pub fn peek_force(&self) -> F {
+        self.force
+    }
+
+    /// Get the force that was returned by the last call to
+    /// [`consume_force`](#method.consume_force)
+    pub fn last_consumed_force(&self) -> F {
+        self.last_force
+    }
+
+    /// Add a torque to the accumulator, the torque only turns bodies that have an
+    /// [`AngularVelocity`](struct.AngularVelocity.html) and an [`Inertia`](struct.Inertia.html)
+    pub fn add_torque(&mut self, torque: F::Angular) {
+        self.torque = self.torque + torque;
+    }
+
+    /// Get the accumulated torque, and reset the torque channel of the accumulator
+    pub fn consume_torque(&mut self) -> F::Angular {
+        let torque = self.torque;
+        self.torque = F::Angular::zero();
+        self.last_torque = torque;
+        torque
+    }
+
+    /// Get the torque accumulated so far, without resetting the accumulator
+    pub fn peek_torque(&self) -> F::Angular {
+        self.torque
+    }
+
+    /// Get the torque that was returned by the last call to
+    /// [`consume_torque`](#method.consume_torque)
+    pub fn last_consumed_torque(&self) -> F::Angular {
+        self.last_torque
+    }
+}
+
+/// Add a force vector to the accumulator, same as
+/// [`add_force`](struct.ForceAccumulator.html#method.add_force)
+impl<F> AddAssign<F> for ForceAccumulator<F>
+where
+    F: LinearVector,
+{
+    fn add_assign(&mut self, force: F) {
+        self.add_force(force);
+    }
+}
+
+impl<F> Default for ForceAccumulator<F>
+where
+    F: LinearVector,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Contact impulse accumulator, used to track the total contact impulse applied to a body during a
+/// frame.
+///
+/// The contact solver resets the accumulator at the start of each step, and adds all impulses it
+/// applies to the body during the step. Can be used to scale damage, camera shake or sound effects
+/// by how hard the body was hit.
+#[derive(Debug, Clone)]
+pub struct ContactImpulse<V> {
+    impulse: V,
+    magnitude: Real,
+}
+
+impl<V> ContactImpulse<V>
+where
+    V: VectorSpace<Scalar = Real> + InnerSpace + Zero,
+{
+    /// Create a new contact impulse accumulator
+    pub fn new() -> Self {
+        Self {
+            impulse: V::zero(),
+            magnitude: 0.,
+        }
+    }
+
+    /// Add an impulse to the accumulator
+    pub fn add_impulse(&mut self, impulse: V) {
+        self.impulse = self.impulse + impulse;
+        self.magnitude += impulse.magnitude();
+    }
+
+    /// Reset the accumulator
+    pub fn reset(&mut self) {
+        self.impulse = V::zero();
+        self.magnitude = 0.;
+    }
+
+    /// Get the net impulse applied to the body. Impulses from opposite sides cancel out.
+    pub fn impulse(&self) -> V {
+        self.impulse
+    }
+
+    /// Get the sum of the magnitudes of all impulses applied to the body
+    pub fn magnitude(&self) -> Real {
+        self.magnitude
+    }
+}
+
+impl<V> Default for ContactImpulse<V>
+where
+    V: VectorSpace<Scalar = Real> + InnerSpace + Zero,
+{
+    fn default() -> Self {
+        Self::new()
+    }
 }
@@ -0,0 +1,209 @@
+use cgmath::{Basis2, InnerSpace, Matrix2, Quaternion, Rad, Rotation, Rotation2, Rotation3, Vector3,
+             Zero};
+
+use super::{LinearVector, RotationalVector, SpringSettings};
+use Real;
+
+/// Rotation type that can give the rotation to another orientation as an angular quantity, and be
+/// rotated by an angular quantity.
+///
+/// The angular quantity is the angle in 2D, and the rotation vector, the rotation axis scaled by
+/// the angle, in 3D, see [`LinearVector`](trait.LinearVector.html). The shortest rotation is used,
+/// so the angle is at most half a turn.
+pub trait OrientationError<A> {
+    /// Get the rotation from this orientation to the target orientation, in world space
+    fn orientation_error(&self, target: &Self) -> A;
+
+    /// Apply a rotation, given as an angular quantity in world space
+    fn rotated(&self, rotation: A) -> Self;
+}
+
+impl OrientationError<Real> for Basis2<Real> {
+    fn orientation_error(&self, target: &Self) -> Real {
+        let rotation = *target * self.invert();
+        let m: &Matrix2<Real> = rotation.as_ref();
+        m.x.y.atan2(m.x.x)
+    }
+
+    fn rotated(&self, rotation: Real) -> Self {
+        Basis2::from_angle(Rad(rotation)) * *self
+    }
+}
+
+impl OrientationError<Vector3<Real>> for Quaternion<Real> {
+    fn orientation_error(&self, target: &Self) -> Vector3<Real> {
+        let mut q = (*target * self.conjugate()).normalize();
+        // q and -q are the same rotation, the one with positive real part is the shortest
+        if q.s < 0. {
+            q = -q;
+        }
+        let sin = q.v.magnitude();
+        if sin <= Real::EPSILON {
+            return Vector3::zero();
+        }
+        q.v * (2. * sin.atan2(q.s) / sin)
+    }
+
+    fn rotated(&self, rotation: Vector3<Real>) -> Self {
+        let angle = rotation.magnitude();
+        if angle <= Real::EPSILON {
+            return *self;
+        }
+        Quaternion::from_axis_angle(rotation / angle, Rad(angle)) * *self
+    }
+}
+
+/// Orientation controller component, that rotates a body towards a target orientation by adding
+/// torque to its force accumulator, e.g. for hoverbikes, turrets or self-righting objects.
+///
+/// Acts as a rotational spring, given in frequency and damping ratio form, see
+/// [`SpringSettings`](struct.SpringSettings.html). The default damping ratio of one gives a
+/// critically damped spring, that reaches the target as fast as possible without overshooting. The
+/// torque is scaled by the given moment of inertia of the body.
+///
+/// The angular velocity is estimated from the change in orientation since the last update, so the
+/// controlled body does not need an [`AngularVelocity`](struct.AngularVelocity.html). Changing the
+/// target does not give a kick in the damping. The torque only turns bodies that have an
+/// angular velocity and an [`Inertia`](struct.Inertia.html).
+///
+/// # Type parameters:
+///
+/// - `V`: Linear vector type, usually `Vector2` or `Vector3`
+/// - `R`: Rotational quantity, usually `Basis2` or `Quaternion`
+#[derive(Debug, Clone)]
+pub struct OrientationController<V, R>
+where
+    V: LinearVector,
+{
+    /// Target orientation
+    pub target: R,
+    /// Spring settings
+    pub spring: SpringSettings,
+    /// Moment of inertia of the body around the rotation axes
+    pub inertia: Real,
+    /// Maximum magnitude of the output torque
+    pub max_torque: Real,
+    last_rotation: Option<R>,
+    angular_velocity: V::Angular,
+}
+
+impl<V, R> OrientationController<V, R>
+where
+    V: RotationalVector,
+    R: OrientationError<V::Angular> + Copy,
+{
+    /// Create a new critically damped controller, with unlimited torque.
+    ///
+    /// # Parameters:
+    ///
+    /// - `target`: Target orientation
+    /// - `frequency`: Oscillation frequency of the undamped spring, in hertz, higher is stiffer
+    /// - `inertia`: Moment of inertia of the body around the rotation axes
+    pub fn new(target: R, frequency: Real, inertia: Real) -> Self {
+        Self {
+            target,
+            spring: SpringSettings::new(frequency, 1.),
+            inertia,
+            max_torque: Real::INFINITY,
+            last_rotation: None,
+            angular_velocity: V::Angular::zero(),
+        }
+    }
+
+    /// Set the damping ratio, builder pattern
+    pub fn with_damping_ratio(mut self, damping_ratio: Real) -> Self {
+        self.spring.damping_ratio = damping_ratio;
+        self
+    }
+
+    /// Limit the output torque
+    pub fn with_max_torque(mut self, max_torque: Real) -> Self {
+        self.max_torque = max_torque;
+        self
+    }
+
+    /// Get the angular velocity estimated during the last update
+    pub fn angular_velocity(&self) -> V::Angular {
+        self.angular_velocity
+    }
+
+    /// Forget the last orientation, e.g. after teleporting the body
+    pub fn reset(&mut self) {
+        self.last_rotation = None;
+        self.angular_velocity = V::Angular::zero();
+    }
+
+    /// Compute the torque to apply, and advance the controller.
+    ///
+    /// # Parameters:
+    ///
+    /// - `rotation`: Current orientation of the body
+    /// - `dt`: Time since the last update
+    pub fn control_torque(&mut self, rotation: &R, dt: Real) -> V::Angular {
+        if let Some(last_rotation) = self.last_rotation {
+            if dt > 0. {
+                self.angular_velocity =
+                    V::angular_scale(last_rotation.orientation_error(rotation), 1. / dt);
+            }
+        }
+        self.last_rotation = Some(*rotation);
+        let error = rotation.orientation_error(&self.target);
+        let torque = V::angular_scale(error, self.spring.stiffness(self.inertia))
+            + V::angular_scale(self.angular_velocity, -self.spring.damping(self.inertia));
+        let magnitude = V::angular_dot(torque, torque).sqrt();
+        if magnitude > self.max_torque {
+            V::angular_scale(torque, self.max_torque / magnitude)
+        } else {
+            torque
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::Vector2;
+
+    use super::*;
+
+    #[test]
+    fn test_quaternion_error_is_shortest_rotation() {
+        let rotation = Quaternion::from_angle_z(Rad(0.5));
+        let target = Quaternion::from_angle_z(Rad(-0.5));
+        assert_relative_eq!(
+            rotation.orientation_error(&target),
+            Vector3::new(0., 0., -1.),
+            epsilon = 1e-5
+        );
+        // the same rotation, with a negated quaternion
+        assert_relative_eq!(
+            rotation.orientation_error(&-target),
+            Vector3::new(0., 0., -1.),
+            epsilon = 1e-5
+        );
+        assert_relative_eq!(
+            rotation.rotated(rotation.orientation_error(&target)),
+            target,
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn test_critically_damped_rotation_does_not_overshoot() {
+        let inertia = 2.;
+        let dt = 1. / 60.;
+        let mut controller = OrientationController::<Vector2<Real>, _>::new(
+            Basis2::from_angle(Rad(1.)),
+            1.,
+            inertia,
+        );
+        let mut angle = 0.;
+        let mut angular_velocity = 0.;
+        for _ in 0..180 {
+            let torque = controller.control_torque(&Basis2::from_angle(Rad(angle)), dt);
+            angular_velocity += torque / inertia * dt;
+            angle += angular_velocity * dt;
+            assert!(angle < 1.01);
+        }
+        assert_relative_eq!(angle, 1., epsilon = 1e-2);
+    }
+}
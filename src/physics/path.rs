@@ -0,0 +1,267 @@
+use std::vec::Vec;
+
+use cgmath::{EuclideanSpace, InnerSpace};
+
+use Real;
+
+/// Path for kinematic bodies to follow, see [`PathFollower`](struct.PathFollower.html).
+///
+/// The path is stored as a polyline, splines are sampled into line segments when the path is
+/// created. Positions along the path are addressed by the distance travelled from the start of
+/// the path.
+///
+/// # Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+#[derive(Debug, Clone)]
+pub struct Path<P> {
+    points: Vec<P>,
+    distances: Vec<Real>,
+}
+
+impl<P> Path<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace,
+{
+    /// Create a path of straight line segments between the given points.
+    ///
+    /// # Parameters:
+    ///
+    /// - `points`: The points of the path, must not be empty
+    /// - `closed`: If true, the path continues from the last point back to the first point
+    pub fn linear(mut points: Vec<P>, closed: bool) -> Self {
+        assert!(!points.is_empty(), "A path needs at least one point");
+        if closed {
+            let first = points[0];
+            points.push(first);
+        }
+        let mut distances = Vec::with_capacity(points.len());
+        let mut distance = 0.;
+        distances.push(distance);
+        for i in 1..points.len() {
+            distance += (points[i] - points[i - 1]).magnitude();
+            distances.push(distance);
+        }
+        Self { points, distances }
+    }
+
+    /// Create a Catmull-Rom spline through the given points.
+    ///
+    /// # Parameters:
+    ///
+    /// - `points`: The points the spline passes through, must not be empty
+    /// - `closed`: If true, the spline continues from the last point back to the first point
+    /// - `samples`: Number of line segments each span of the spline is sampled into
+    pub fn catmull_rom(points: Vec<P>, closed: bool, samples: usize) -> Self {
+        assert!(!points.is_empty(), "A path needs at least one point");
+        let count = points.len();
+        let spans = if closed { count } else { count - 1 };
+        let point = |i: isize| {
+            if closed {
+                points[((i % count as isize + count as isize) % count as isize) as usize]
+            } else {
+                points[i.max(0).min(count as isize - 1) as usize]
+            }
+        };
+        let samples = samples.max(1);
+        let mut sampled = Vec::with_capacity(spans * samples + 1);
+        sampled.push(points[0]);
+        for span in 0..spans as isize {
+            let p1 = point(span);
+            let d0 = point(span - 1) - p1;
+            let d2 = point(span + 1) - p1;
+            let d3 = point(span + 2) - p1;
+            for sample in 1..samples + 1 {
+                let t = sample as Real / samples as Real;
+                let offset = (d2 - d0) * (0.5 * t) + (d0 * 2. + d2 * 4. - d3) * (0.5 * t * t)
+                    + (d3 - d0 - d2 * 3.) * (0.5 * t * t * t);
+                sampled.push(p1 + offset);
+            }
+        }
+        Self::linear(sampled, false)
+    }
+
+    /// Total length of the path
+    pub fn length(&self) -> Real {
+        *self.distances.last().unwrap()
+    }
+
+    /// Get the position at the given distance from the start of the path, the distance is clamped
+    /// to the path.
+    pub fn point_at(&self, distance: Real) -> P {
+        if distance <= 0. {
+            return self.points[0];
+        }
+        if distance >= self.length() {
+            return *self.points.last().unwrap();
+        }
+        let index = match self.distances
+            .binary_search_by(|d| d.partial_cmp(&distance).unwrap())
+        {
+            Ok(index) => return self.points[index],
+            Err(index) => index,
+        };
+        let start = self.distances[index - 1];
+        let amount = (distance - start) / (self.distances[index] - start);
+        self.points[index - 1] + (self.points[index] - self.points[index - 1]) * amount
+    }
+}
+
+/// How a [`PathFollower`](struct.PathFollower.html) behaves when reaching the end of the path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathMode {
+    /// Stop at the end of the path
+    Once,
+    /// Restart from the start of the path, use with closed paths for continuous motion
+    Loop,
+    /// Reverse direction at either end of the path
+    PingPong,
+}
+
+/// Moves a kinematic body along a path at a given speed, e.g. for elevators, patrolling platforms
+/// and camera rails.
+///
+/// The body should have an infinite [`Mass`](struct.Mass.html), so it is not pushed by the
+/// bodies it collides with.
+///
+/// # Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+#[derive(Debug, Clone)]
+pub struct PathFollower<P> {
+    /// The path to follow
+    pub path: Path<P>,
+    /// Speed along the path, in distance per second
+    pub speed: Real,
+    /// Behaviour at the end of the path
+    pub mode: PathMode,
+    distance: Real,
+    reverse: bool,
+}
+
+impl<P> PathFollower<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace,
+{
+    /// Create a new path follower, starting at the start of the path, that stops at the end of
+    /// the path
+    pub fn new(path: Path<P>, speed: Real) -> Self {
+        Self {
+            path,
+            speed,
+            mode: PathMode::Once,
+            distance: 0.,
+            reverse: false,
+        }
+    }
+
+    /// Set the behaviour at the end of the path, builder pattern
+    pub fn with_mode(mut self, mode: PathMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Start at the given distance along the path, builder pattern
+    pub fn with_distance(mut self, distance: Real) -> Self {
+        self.distance = distance.max(0.).min(self.path.length());
+        self
+    }
+
+    /// Distance travelled from the start of the path
+    pub fn distance(&self) -> Real {
+        self.distance
+    }
+
+    /// Is the follower moving towards the start of the path
+    pub fn is_reversed(&self) -> bool {
+        self.reverse
+    }
+
+    /// Has the follower reached the end of the path, only happens in `PathMode::Once`
+    pub fn is_finished(&self) -> bool {
+        self.mode == PathMode::Once && self.distance >= self.path.length()
+    }
+
+    /// Current position on the path
+    pub fn position(&self) -> P {
+        self.path.point_at(self.distance)
+    }
+
+    /// Advance along the path.
+    ///
+    /// # Parameters:
+    ///
+    /// - `delta_seconds`: Length of the step
+    ///
+    /// # Returns:
+    ///
+    /// The new position on the path
+    pub fn advance(&mut self, delta_seconds: Real) -> P {
+        let length = self.path.length();
+        let step = self.speed * delta_seconds;
+        if length > 0. {
+            match self.mode {
+                PathMode::Once => self.distance = (self.distance + step).min(length),
+                PathMode::Loop => {
+                    self.distance = (self.distance + step) % length;
+                    if self.distance < 0. {
+                        self.distance += length;
+                    }
+                }
+                PathMode::PingPong => {
+                    let step = if self.reverse { -step } else { step };
+                    let mut distance = (self.distance + step) % (2. * length);
+                    if distance < 0. {
+                        distance += 2. * length;
+                    }
+                    if distance > length {
+                        self.distance = 2. * length - distance;
+                        self.reverse = !self.reverse;
+                    } else {
+                        self.distance = distance;
+                    }
+                }
+            }
+        }
+        self.position()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::Point2;
+
+    use super::*;
+
+    #[test]
+    fn test_path_follower() {
+        let path = Path::linear(vec![Point2::new(0., 0.), Point2::new(2., 0.)], false);
+        assert_ulps_eq!(path.point_at(0.5), Point2::new(0.5, 0.));
+
+        let mut follower = PathFollower::new(path.clone(), 1.).with_mode(PathMode::PingPong);
+        assert_ulps_eq!(follower.advance(1.5), Point2::new(1.5, 0.));
+        assert_ulps_eq!(follower.advance(1.), Point2::new(1.5, 0.));
+        assert!(follower.is_reversed());
+        assert_ulps_eq!(follower.advance(1.), Point2::new(0.5, 0.));
+
+        let mut follower = PathFollower::new(path, 1.);
+        follower.advance(3.);
+        assert!(follower.is_finished());
+        assert_ulps_eq!(follower.position(), Point2::new(2., 0.));
+    }
+
+    #[test]
+    fn test_catmull_rom_passes_through_points() {
+        let points = vec![
+            Point2::new(0., 0.),
+            Point2::new(1., 1.),
+            Point2::new(2., 0.),
+        ];
+        let path = Path::catmull_rom(points, false, 4);
+        assert_ulps_eq!(path.point_at(0.), Point2::new(0., 0.));
+        assert_ulps_eq!(path.point_at(path.length()), Point2::new(2., 0.));
+        assert!(path.length() > 2. * (2. as Real).sqrt() - 0.01);
+    }
+}
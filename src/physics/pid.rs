@@ -0,0 +1,211 @@
+use std::ops::{Add, Mul, Sub};
+
+use cgmath::{EuclideanSpace, InnerSpace, Zero};
+
+use Real;
+
+/// PID controller.
+///
+/// Computes a control output from the error between a target value and the current value, using a
+/// proportional, an integral and a derivative term.
+///
+/// # Type parameters:
+///
+/// - `V`: Error type, either a cgmath vector type or `Real`
+#[derive(Debug, Clone)]
+pub struct Pid<V> {
+    /// Proportional gain
+    pub proportional: Real,
+    /// Integral gain
+    pub integral: Real,
+    /// Derivative gain
+    pub derivative: Real,
+    accumulated: V,
+    previous_error: Option<V>,
+}
+
+impl<V> Pid<V>
+where
+    V: Zero + Copy + Add<Output = V> + Sub<Output = V> + Mul<Real, Output = V>,
+{
+    /// Create a new PID controller.
+    ///
+    /// # Parameters:
+    ///
+    /// - `proportional`: Proportional gain
+    /// - `integral`: Integral gain
+    /// - `derivative`: Derivative gain
+    pub fn new(proportional: Real, integral: Real, derivative: Real) -> Self {
+        Self {
+            proportional,
+            integral,
+            derivative,
+            accumulated: V::zero(),
+            previous_error: None,
+        }
+    }
+
+    /// Compute the control output for the given error, and advance the controller.
+    ///
+    /// The derivative term is zero on the first update after creation or reset.
+    ///
+    /// # Parameters:
+    ///
+    /// - `error`: Target value minus current value
+    /// - `dt`: Time since the last update
+    pub fn update(&mut self, error: V, dt: Real) -> V {
+        if dt <= 0. {
+            return error * self.proportional + self.accumulated * self.integral;
+        }
+        self.accumulated = self.accumulated + error * dt;
+        let derivative = match self.previous_error {
+            Some(previous) => (error - previous) * (1. / dt),
+            None => V::zero(),
+        };
+        self.previous_error = Some(error);
+        error * self.proportional + self.accumulated * self.integral
+            + derivative * self.derivative
+    }
+
+    /// Clear the accumulated error, e.g. when the target changes drastically
+    pub fn reset(&mut self) {
+        self.accumulated = V::zero();
+        self.previous_error = None;
+    }
+}
+
+/// Target for a [`PidController`](struct.PidController.html)
+#[derive(Debug, Clone)]
+pub enum PidTarget<P>
+where
+    P: EuclideanSpace,
+{
+    /// Drive the body towards the given position
+    Position(P),
+    /// Drive the body towards the given linear velocity
+    Velocity(P::Diff),
+}
+
+/// PID controller component, that drives a body towards a target position or velocity by adding
+/// force to its force accumulator, e.g. for hovering or drones.
+///
+/// The output is used as is as a force, so the gains need to be scaled with the mass of the body.
+/// Constant forces such as gravity are compensated by the integral term.
+///
+/// # Type parameters:
+///
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+#[derive(Debug, Clone)]
+pub struct PidController<P>
+where
+    P: EuclideanSpace,
+{
+    /// Current target
+    pub target: PidTarget<P>,
+    /// PID controller
+    pub pid: Pid<P::Diff>,
+    /// Maximum magnitude of the output force
+    pub max_force: Real,
+}
+
+impl<P> PidController<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace,
+{
+    /// Create a new controller, with unlimited force
+    pub fn new(target: PidTarget<P>, pid: Pid<P::Diff>) -> Self {
+        Self {
+            target,
+            pid,
+            max_force: Real::INFINITY,
+        }
+    }
+
+    /// Limit the output force
+    pub fn with_max_force(mut self, max_force: Real) -> Self {
+        self.max_force = max_force;
+        self
+    }
+
+    /// Change the target. The controller is reset when switching between position and velocity
+    /// targets.
+    pub fn set_target(&mut self, target: PidTarget<P>) {
+        match (&self.target, &target) {
+            (&PidTarget::Position(_), &PidTarget::Position(_))
+            | (&PidTarget::Velocity(_), &PidTarget::Velocity(_)) => (),
+            _ => self.pid.reset(),
+        }
+        self.target = target;
+    }
+
+    /// Compute the force to apply, and advance the controller.
+    ///
+    /// # Parameters:
+    ///
+    /// - `position`: Current position of the body
+    /// - `velocity`: Current linear velocity of the body
+    /// - `dt`: Time since the last update
+    pub fn control_force(&mut self, position: P, velocity: P::Diff, dt: Real) -> P::Diff {
+        let error = match self.target {
+            PidTarget::Position(target) => target - position,
+            PidTarget::Velocity(target) => target - velocity,
+        };
+        let force = self.pid.update(error, dt);
+        let magnitude = force.magnitude();
+        if magnitude > self.max_force {
+            force * (self.max_force / magnitude)
+        } else {
+            force
+        }
+    }
+}
+
+/// PID controller component for the angle of a
+/// [`WheelJoint2`](struct.WheelJoint2.html), e.g. for turrets.
+///
+/// The output is commanded as a torque through the
+/// [`JointControl`](struct.JointControl.html) on the same entity, and is clamped by the motor
+/// limits of the joint.
+#[derive(Debug, Clone)]
+pub struct JointAngleController {
+    /// Target angle, in radians
+    pub target: Real,
+    /// PID controller
+    pub pid: Pid<Real>,
+}
+
+impl JointAngleController {
+    /// Create a new joint angle controller
+    pub fn new(target: Real, pid: Pid<Real>) -> Self {
+        Self { target, pid }
+    }
+
+    /// Compute the torque to command, and advance the controller.
+    ///
+    /// # Parameters:
+    ///
+    /// - `angle`: Current angle of the joint
+    /// - `dt`: Time since the last update
+    pub fn control_torque(&mut self, angle: Real, dt: Real) -> Real {
+        self.pid.update(self.target - angle, dt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pid_converges() {
+        let mut pid = Pid::new(4., 2., 1.);
+        let dt = 1. / 60.;
+        let (mut position, mut velocity): (Real, Real) = (0., 0.);
+        for _ in 0..1200 {
+            let force = pid.update(1. - position, dt) - 2.;
+            velocity += force * dt;
+            position += velocity * dt;
+        }
+        assert!((position - 1.).abs() < 0.01);
+    }
+}
@@ -0,0 +1,153 @@
+use cgmath::{EuclideanSpace, Rotation, VectorSpace};
+
+use super::Velocity;
+use {BodyPose, Real};
+
+/// Integrate the linear motion of a body over one step, using semi-implicit Euler.
+///
+/// This is the integration scheme used by the contact solver, the velocity is updated first, and
+/// the new velocity is used to update the position.
+///
+/// # Parameters:
+///
+/// - `position`: Position at the start of the step
+/// - `velocity`: Velocity at the start of the step
+/// - `acceleration`: Acceleration during the step
+/// - `delta_seconds`: Length of the step
+///
+/// # Returns:
+///
+/// The position and velocity at the end of the step.
+pub fn integrate_linear<P>(
+    position: P,
+    velocity: P::Diff,
+    acceleration: P::Diff,
+    delta_seconds: Real,
+) -> (P, P::Diff)
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: VectorSpace<Scalar = Real>,
+{
+    let velocity = velocity + acceleration * delta_seconds;
+    (position + velocity * delta_seconds, velocity)
+}
+
+/// Dead reckoning, predicts the motion of bodies from a snapshot of their pose and velocity, e.g.
+/// for client-side prediction of networked projectiles.
+///
+/// Uses the same integration scheme and fixed step as the contact solver, so the prediction
+/// matches the simulation, as long as the body is only affected by a constant acceleration, like
+/// gravity, and linear drag.
+///
+/// # Type parameters:
+///
+/// - `V`: cgmath vector type
+#[derive(Debug, Clone)]
+pub struct DeadReckoning<V> {
+    /// Constant acceleration, usually the gravity
+    pub acceleration: V,
+    /// Linear drag coefficient, the drag acceleration is `-linear_drag * velocity`
+    pub linear_drag: Real,
+    /// Fixed step used by the simulation, see
+    /// [`DeltaTime`](../ecs/physics/struct.DeltaTime.html)
+    pub step: Real,
+}
+
+impl<V> DeadReckoning<V>
+where
+    V: VectorSpace<Scalar = Real>,
+{
+    /// Create a new dead reckoning predictor
+    pub fn new(acceleration: V, linear_drag: Real, step: Real) -> Self {
+        Self {
+            acceleration,
+            linear_drag,
+            step,
+        }
+    }
+
+    /// Advance the body a single step of the given length
+    pub fn integrate<P, R>(
+        &self,
+        pose: &BodyPose<P, R>,
+        velocity: &Velocity<V>,
+        delta_seconds: Real,
+    ) -> (BodyPose<P, R>, Velocity<V>)
+    where
+        P: EuclideanSpace<Scalar = Real, Diff = V>,
+        R: Rotation<P>,
+    {
+        let acceleration = self.acceleration - velocity.linear * self.linear_drag;
+        let (position, linear) =
+            integrate_linear(*pose.position(), velocity.linear, acceleration, delta_seconds);
+        (
+            BodyPose::new(position, *pose.rotation()),
+            Velocity { linear },
+        )
+    }
+
+    /// Predict the pose and velocity of the body after the given elapsed time.
+    ///
+    /// The elapsed time is simulated as whole steps, any remaining time is simulated as a final
+    /// shorter step.
+    ///
+    /// # Parameters:
+    ///
+    /// - `pose`: Snapshot of the pose
+    /// - `velocity`: Snapshot of the velocity
+    /// - `elapsed`: Time elapsed since the snapshot was taken
+    pub fn predict<P, R>(
+        &self,
+        pose: &BodyPose<P, R>,
+        velocity: &Velocity<V>,
+        elapsed: Real,
+    ) -> (BodyPose<P, R>, Velocity<V>)
+    where
+        P: EuclideanSpace<Scalar = Real, Diff = V>,
+        R: Rotation<P>,
+    {
+        let mut pose = pose.clone();
+        let mut velocity = velocity.clone();
+        let mut remaining = elapsed;
+        if self.step > 0. {
+            while remaining >= self.step {
+                let (next_pose, next_velocity) = self.integrate(&pose, &velocity, self.step);
+                pose = next_pose;
+                velocity = next_velocity;
+                remaining -= self.step;
+            }
+        }
+        if remaining > 0. {
+            let (next_pose, next_velocity) = self.integrate(&pose, &velocity, remaining);
+            pose = next_pose;
+            velocity = next_velocity;
+        }
+        (pose, velocity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, Point2, Rad, Rotation2, Vector2};
+
+    use super::*;
+
+    #[test]
+    fn test_predict_matches_steps() {
+        let reckoning = DeadReckoning::new(Vector2::new(0., -10.), 0., 0.1);
+        let pose = BodyPose::new(Point2::new(0., 0.), Basis2::from_angle(Rad(0.)));
+        let velocity = Velocity {
+            linear: Vector2::new(1., 0.),
+        };
+        let mut position = Point2::new(0., 0.);
+        let mut linear = velocity.linear;
+        for _ in 0..3 {
+            let (p, v) = integrate_linear(position, linear, Vector2::new(0., -10.), 0.1);
+            position = p;
+            linear = v;
+        }
+        let (predicted, predicted_velocity) = reckoning.predict(&pose, &velocity, 0.3);
+        assert_ulps_eq!(position, predicted.position());
+        assert_ulps_eq!(linear, predicted_velocity.linear);
+    }
+}
@@ -1,15 +1,106 @@
 //! 2D structures for physics
 
 pub use collide::prelude2d::*;
-pub use physics::{linear_resolve_contact, Mass};
+pub use physics::{arcade_resolve_contact, build_islands, flip_bodies, flip_frame,
+                  ground_friction_force, integrate_bodies, integrate_body, integrate_linear,
+                  integrate_rotation, launch_velocity, launch_velocity_with_speed,
+                  linear_contact_impulse, linear_friction_impulse, linear_resolve_contact,
+                  project_joint_rotation, reflect_velocity, relative_point_normal_velocity,
+                  rotational_friction_impulse, rotational_resolve_contact, solve_contact,
+                  solve_contacts, solve_distance_joint, solve_joint, solve_wheel_joint,
+                  submerged_area, trajectory, wrap_angle, AngularRotation, ArcadeBounce, BodyType,
+                  ContactSensor, ContactSensors, ContactSettings, Damping, Density,
+                  DistanceConstraint, DistanceJoint, FrictionZone, Gravity, GravityScale,
+                  GroundFriction, ImpactEvent, InstabilityEvent, InstabilityKind, Island,
+                  JointAngleController, JointCollision, JointCommand, JointControl, Magnet,
+                  MagnetSusceptible, Mass, Material, MaterialHandle, OrientationError, PathMode,
+                  Pid, PidTarget, Polarity, PositionCorrection, RestitutionCurve, Rolling,
+                  RotationalVector, SolverGroup, SpringSettings, UnwrappedAngle, Volume, WaterArea2,
+                  WheelBodyData, WheelJoint2};
 
 use cgmath::{Basis2, Point2, Vector2};
 
-use super::{LinearResolveData, Velocity};
+use super::{AngularVelocity, BallisticBody, ContactImpulse, ContactResolution, DeadReckoning,
+            ForceAccumulator, GravityOverride, GravityVolume, Inertia, Joint, JointBodyData,
+            JointKind, LinearResolveData, OrientationController, Path, PathFollower, PidController,
+            PoseCorrection, PoseHistory, RotationalResolution, RotationalResolveData, SolverBody,
+            Spring, SpringAnchor, Velocity};
 use Real;
 
 /// 2D velocity
 pub type Velocity2 = Velocity<Vector2<Real>>;
 
+/// 2D angular velocity
+pub type AngularVelocity2 = AngularVelocity<Vector2<Real>>;
+
+/// 2D rotational inertia
+pub type Inertia2 = Inertia<Vector2<Real>>;
+
+/// 2D force accumulator
+pub type ForceAccumulator2 = ForceAccumulator<Vector2<Real>>;
+
+/// 2D contact impulse accumulator
+pub type ContactImpulse2 = ContactImpulse<Vector2<Real>>;
+
+/// 2D dead reckoning predictor
+pub type DeadReckoning2 = DeadReckoning<Vector2<Real>>;
+
+/// 2D PID controller for body positions or velocities
+pub type PidController2 = PidController<Point2<Real>>;
+
+/// Joint body data for 2D
+pub type JointBodyData2 = JointBodyData<Point2<Real>>;
+
+/// Joint for 2D
+pub type Joint2<ID> = Joint<ID, Point2<Real>, Basis2<Real>>;
+
+/// Joint kind for 2D
+pub type JointKind2 = JointKind<Vector2<Real>>;
+
+/// 2D spring
+pub type Spring2<ID> = Spring<ID, Point2<Real>>;
+
+/// 2D spring anchor
+pub type SpringAnchor2<ID> = SpringAnchor<ID, Point2<Real>>;
+
+/// 2D orientation controller
+pub type OrientationController2 = OrientationController<Vector2<Real>, Basis2<Real>>;
+
+/// 2D pose correction
+pub type PoseCorrection2 = PoseCorrection<Vector2<Real>>;
+
+/// 2D pose history
+pub type PoseHistory2 = PoseHistory<Point2<Real>, Basis2<Real>>;
+
+/// 2D gravity resource
+pub type Gravity2 = Gravity<Vector2<Real>>;
+
+/// 2D gravity override
+pub type GravityOverride2 = GravityOverride<Point2<Real>, Basis2<Real>>;
+
+/// 2D gravity volume
+pub type GravityVolume2 = GravityVolume<Point2<Real>, Basis2<Real>>;
+
 /// 2D linear contact resolution data
 pub type LinearResolveData2<'a> = LinearResolveData<'a, Point2<Real>, Basis2<Real>>;
+
+/// 2D solver body data
+pub type SolverBody2 = SolverBody<Point2<Real>, Basis2<Real>>;
+
+/// 2D contact resolution
+pub type ContactResolution2 = ContactResolution<Point2<Real>, Basis2<Real>>;
+
+/// 2D path
+pub type Path2 = Path<Point2<Real>>;
+
+/// 2D path follower
+pub type PathFollower2 = PathFollower<Point2<Real>>;
+
+/// 2D ballistic body
+pub type BallisticBody2 = BallisticBody<Point2<Real>>;
+
+/// 2D rotational contact resolution data
+pub type RotationalResolveData2 = RotationalResolveData<Point2<Real>>;
+
+/// 2D rotational contact resolution
+pub type RotationalResolution2 = RotationalResolution<Vector2<Real>>;
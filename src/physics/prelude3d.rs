@@ -1,15 +1,105 @@
 //! 3D structures for physics
 
 pub use collide::prelude3d::*;
-pub use physics::{linear_resolve_contact, Mass};
+pub use physics::{arcade_resolve_contact, build_islands, flip_bodies, flip_frame, integrate_bodies,
+                  integrate_body, integrate_linear, integrate_rotation, launch_velocity,
+                  launch_velocity_with_speed, linear_contact_impulse, linear_friction_impulse,
+                  linear_resolve_contact, project_joint_rotation, reflect_velocity,
+                  relative_point_normal_velocity, rotational_friction_impulse,
+                  rotational_resolve_contact, solve_contact, solve_contacts, solve_distance_joint,
+                  solve_joint, solve_plane_constraint, solve_upright_constraint, trajectory,
+                  world_inverse_inertia, wrap_angle, AngularRotation, ArcadeBounce, BodyType,
+                  ContactSensor, ContactSensors, ContactSettings, Damping, Density,
+                  DistanceConstraint, DistanceJoint, Gravity, GravityScale, ImpactEvent,
+                  InstabilityEvent, InstabilityKind, Island, JointCollision, Magnet,
+                  MagnetSusceptible, Mass, Material, MaterialHandle, OrientationError, PathMode,
+                  Pid, PidTarget, PlaneConstraint3, Polarity, PositionCorrection, RestitutionCurve,
+                  Rolling, RotationalVector, SolverGroup, SpringSettings, UnwrappedAngle,
+                  UprightConstraint3, Volume};
 
 use cgmath::{Point3, Quaternion, Vector3};
 
-use super::{LinearResolveData, Velocity};
+use super::{AngularVelocity, BallisticBody, ContactImpulse, ContactResolution, DeadReckoning,
+            ForceAccumulator, GravityOverride, GravityVolume, Inertia, Joint, JointBodyData,
+            JointKind, LinearResolveData, OrientationController, Path, PathFollower, PidController,
+            PoseCorrection, PoseHistory, RotationalResolution, RotationalResolveData, SolverBody,
+            Spring, SpringAnchor, Velocity};
 use Real;
 
 /// 3D velocity
 pub type Velocity3 = Velocity<Vector3<Real>>;
 
+/// 3D angular velocity
+pub type AngularVelocity3 = AngularVelocity<Vector3<Real>>;
+
+/// 3D rotational inertia
+pub type Inertia3 = Inertia<Vector3<Real>>;
+
+/// 3D force accumulator
+pub type ForceAccumulator3 = ForceAccumulator<Vector3<Real>>;
+
+/// 3D contact impulse accumulator
+pub type ContactImpulse3 = ContactImpulse<Vector3<Real>>;
+
+/// 3D dead reckoning predictor
+pub type DeadReckoning3 = DeadReckoning<Vector3<Real>>;
+
+/// 3D PID controller for body positions or velocities
+pub type PidController3 = PidController<Point3<Real>>;
+
+/// Joint body data for 3D
+pub type JointBodyData3 = JointBodyData<Point3<Real>>;
+
+/// Joint for 3D
+pub type Joint3<ID> = Joint<ID, Point3<Real>, Quaternion<Real>>;
+
+/// Joint kind for 3D
+pub type JointKind3 = JointKind<Vector3<Real>>;
+
+/// 3D spring
+pub type Spring3<ID> = Spring<ID, Point3<Real>>;
+
+/// 3D spring anchor
+pub type SpringAnchor3<ID> = SpringAnchor<ID, Point3<Real>>;
+
+/// 3D orientation controller
+pub type OrientationController3 = OrientationController<Vector3<Real>, Quaternion<Real>>;
+
+/// 3D pose correction
+pub type PoseCorrection3 = PoseCorrection<Vector3<Real>>;
+
+/// 3D pose history
+pub type PoseHistory3 = PoseHistory<Point3<Real>, Quaternion<Real>>;
+
+/// 3D gravity resource
+pub type Gravity3 = Gravity<Vector3<Real>>;
+
+/// 3D gravity override
+pub type GravityOverride3 = GravityOverride<Point3<Real>, Quaternion<Real>>;
+
+/// 3D gravity volume
+pub type GravityVolume3 = GravityVolume<Point3<Real>, Quaternion<Real>>;
+
 /// 3D linear contact resolution data
 pub type LinearResolveData3<'a> = LinearResolveData<'a, Point3<Real>, Quaternion<Real>>;
+
+/// 3D solver body data
+pub type SolverBody3 = SolverBody<Point3<Real>, Quaternion<Real>>;
+
+/// 3D contact resolution
+pub type ContactResolution3 = ContactResolution<Point3<Real>, Quaternion<Real>>;
+
+/// 3D path
+pub type Path3 = Path<Point3<Real>>;
+
+/// 3D path follower
+pub type PathFollower3 = PathFollower<Point3<Real>>;
+
+/// 3D ballistic body
+pub type BallisticBody3 = BallisticBody<Point3<Real>>;
+
+/// 3D rotational contact resolution data
+pub type RotationalResolveData3 = RotationalResolveData<Point3<Real>>;
+
+/// 3D rotational contact resolution
+pub type RotationalResolution3 = RotationalResolution<Vector3<Real>>;
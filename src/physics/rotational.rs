@@ -0,0 +1,654 @@
+use std::fmt::Debug;
+
+use cgmath::{Basis2, EuclideanSpace, InnerSpace, Matrix, Matrix3, Quaternion, Rotation,
+             SquareMatrix, Vector2, Vector3, Zero};
+
+use super::{Damping, ForceAccumulator, LinearVector, OrientationError};
+use {BodyPose, Real};
+use collide::ContactEvent;
+
+/// Linear vector type with the operations needed for rotational dynamics, see
+/// [`rotational_resolve_contact`](fn.rotational_resolve_contact.html).
+///
+/// The angular type is a scalar in 2D, and a vector in 3D, see
+/// [`LinearVector`](trait.LinearVector.html).
+pub trait RotationalVector: LinearVector + InnerSpace {
+    /// Type of the inverse inertia, a scalar in 2D, and a tensor in 3D
+    type InverseInertia: Copy + Debug + Zero + Send + Sync + 'static;
+
+    /// Cross product of an offset and a linear vector, e.g. the torque of a force applied at the
+    /// offset
+    fn cross(offset: Self, vector: Self) -> Self::Angular;
+
+    /// Linear velocity of a point at the given offset from the center of a body, due to the angular
+    /// velocity of the body
+    fn point_velocity(angular: Self::Angular, offset: Self) -> Self;
+
+    /// Apply the inverse inertia to an angular quantity, e.g. to get the angular velocity change
+    /// from an angular impulse
+    fn apply_inverse_inertia(
+        inverse_inertia: &Self::InverseInertia,
+        angular: Self::Angular,
+    ) -> Self::Angular;
+
+    /// Apply the inertia given by its inverse to an angular quantity, e.g. to get the angular
+    /// momentum from an angular velocity. Zero for an inverse inertia that can not be inverted,
+    /// since such a body can not be turned.
+    fn apply_inertia(inverse_inertia: &Self::InverseInertia, angular: Self::Angular)
+        -> Self::Angular;
+
+    /// Dot product of two angular quantities
+    fn angular_dot(a: Self::Angular, b: Self::Angular) -> Real;
+
+    /// Scale an angular quantity
+    fn angular_scale(angular: Self::Angular, scale: Real) -> Self::Angular;
+
+    /// Part of an angular quantity around the given unit axis, e.g. the spin around a hinge axis.
+    /// Always zero in 2D, where all rotation is around the axis out of the plane.
+    fn angular_component(angular: Self::Angular, axis: Self) -> Self::Angular;
+}
+
+impl RotationalVector for Vector2<Real> {
+    type InverseInertia = Real;
+
+    fn cross(offset: Self, vector: Self) -> Real {
+        offset.x * vector.y - offset.y * vector.x
+    }
+
+    fn point_velocity(angular: Real, offset: Self) -> Self {
+        Vector2::new(-angular * offset.y, angular * offset.x)
+    }
+
+    fn apply_inverse_inertia(inverse_inertia: &Real, angular: Real) -> Real {
+        inverse_inertia * angular
+    }
+
+    fn apply_inertia(inverse_inertia: &Real, angular: Real) -> Real {
+        if *inverse_inertia > 0. {
+            angular / inverse_inertia
+        } else {
+            0.
+        }
+    }
+
+    fn angular_dot(a: Real, b: Real) -> Real {
+        a * b
+    }
+
+    fn angular_scale(angular: Real, scale: Real) -> Real {
+        angular * scale
+    }
+
+    fn angular_component(_: Real, _: Self) -> Real {
+        0.
+    }
+}
+
+impl RotationalVector for Vector3<Real> {
+    type InverseInertia = Matrix3<Real>;
+
+    fn cross(offset: Self, vector: Self) -> Self {
+        offset.cross(vector)
+    }
+
+    fn point_velocity(angular: Self, offset: Self) -> Self {
+        angular.cross(offset)
+    }
+
+    fn apply_inverse_inertia(inverse_inertia: &Matrix3<Real>, angular: Self) -> Self {
+        inverse_inertia * angular
+    }
+
+    fn apply_inertia(inverse_inertia: &Matrix3<Real>, angular: Self) -> Self {
+        inverse_inertia
+            .invert()
+            .map(|inertia| inertia * angular)
+            .unwrap_or_else(Vector3::zero)
+    }
+
+    fn angular_dot(a: Self, b: Self) -> Real {
+        a.dot(b)
+    }
+
+    fn angular_scale(angular: Self, scale: Real) -> Self {
+        angular * scale
+    }
+
+    fn angular_component(angular: Self, axis: Self) -> Self {
+        axis * angular.dot(axis)
+    }
+}
+
+/// Transform a body space inverse inertia tensor to world space, for a body with the given
+/// rotation
+pub fn world_inverse_inertia(local: &Matrix3<Real>, rotation: Quaternion<Real>) -> Matrix3<Real> {
+    let rotation = Matrix3::from(rotation);
+    rotation * local * rotation.transpose()
+}
+
+/// Rotation type that can be integrated with an angular velocity, see
+/// [`integrate_rotation`](fn.integrate_rotation.html).
+pub trait AngularRotation<V>: OrientationError<V::Angular>
+where
+    V: RotationalVector,
+{
+    /// Transform a body space inverse inertia to world space, for a body with this rotation
+    fn world_inverse_inertia(&self, local: &V::InverseInertia) -> V::InverseInertia;
+
+    /// Rotate an angular quantity, e.g. an angular velocity, by this rotation
+    fn rotate_angular(&self, angular: V::Angular) -> V::Angular;
+}
+
+impl AngularRotation<Vector2<Real>> for Basis2<Real> {
+    fn world_inverse_inertia(&self, local: &Real) -> Real {
+        *local
+    }
+
+    fn rotate_angular(&self, angular: Real) -> Real {
+        angular
+    }
+}
+
+impl AngularRotation<Vector3<Real>> for Quaternion<Real> {
+    fn world_inverse_inertia(&self, local: &Matrix3<Real>) -> Matrix3<Real> {
+        world_inverse_inertia(local, *self)
+    }
+
+    fn rotate_angular(&self, angular: Vector3<Real>) -> Vector3<Real> {
+        self.rotate_vector(angular)
+    }
+}
+
+/// Angular velocity of a body.
+///
+/// Bodies with an angular velocity are rotated by the contact solvers, and any torque accumulated
+/// in their [`ForceAccumulator`](struct.ForceAccumulator.html) is integrated into the angular
+/// velocity, using the [`Inertia`](struct.Inertia.html) of the body. Bodies without an angular
+/// velocity never rotate, and their torque is discarded.
+///
+/// The angular velocity is the velocity used for the next frame. Contacts are resolved through
+/// the center of mass, so they do not change the angular velocity, except for the contact
+/// friction of [`Rolling`](struct.Rolling.html) bodies. [`Joint`](struct.Joint.html)s act at
+/// their anchors, and keep the relative rotation of the bodies, through the angular velocity.
+///
+/// # Type parameters:
+///
+/// - `V`: Linear vector type, usually `Vector2` or `Vector3`, the angular velocity is a scalar in
+///   2D, and a vector in 3D
+#[derive(Debug, Clone)]
+pub struct AngularVelocity<V>
+where
+    V: LinearVector,
+{
+    /// Angular velocity, in radians per second
+    pub angular: V::Angular,
+}
+
+impl<V> AngularVelocity<V>
+where
+    V: LinearVector,
+{
+    /// Create a new angular velocity
+    pub fn new(angular: V::Angular) -> Self {
+        Self { angular }
+    }
+}
+
+impl<V> Default for AngularVelocity<V>
+where
+    V: LinearVector,
+{
+    fn default() -> Self {
+        Self::new(V::Angular::zero())
+    }
+}
+
+/// Rotational inertia of a body, the rotational counterpart of [`Mass`](struct.Mass.html).
+///
+/// The inertia is given in body space, around the position of the body. Bodies without an inertia
+/// are not turned by torque.
+///
+/// # Type parameters:
+///
+/// - `V`: Linear vector type, usually `Vector2` or `Vector3`
+#[derive(Debug, Clone)]
+pub struct Inertia<V>
+where
+    V: RotationalVector,
+{
+    inverse_inertia: V::InverseInertia,
+}
+
+impl<V> Inertia<V>
+where
+    V: RotationalVector,
+{
+    /// Create a new inertia from the body space inverse inertia, zero for bodies that can not be
+    /// turned
+    pub fn from_inverse(inverse_inertia: V::InverseInertia) -> Self {
+        Self { inverse_inertia }
+    }
+
+    /// Get the body space inverse inertia
+    pub fn inverse_inertia(&self) -> V::InverseInertia {
+        self.inverse_inertia
+    }
+}
+
+impl Inertia<Vector2<Real>> {
+    /// Create a new 2D inertia from the moment of inertia, an infinite moment of inertia can not
+    /// be turned
+    pub fn new(inertia: Real) -> Self {
+        if inertia.is_infinite() {
+            Self::from_inverse(0.)
+        } else {
+            Self::from_inverse(1. / inertia)
+        }
+    }
+}
+
+impl Inertia<Vector3<Real>> {
+    /// Create a new 3D inertia from the body space inertia tensor, a tensor that can not be
+    /// inverted can not be turned
+    pub fn new(inertia: Matrix3<Real>) -> Self {
+        Self::from_inverse(inertia.invert().unwrap_or_else(Matrix3::zero))
+    }
+}
+
+/// Integrate the rotation of a body over one step, the way the contact solvers do.
+///
+/// The angular velocity is damped, then accelerated by the torque, and the rotation is turned by
+/// the new angular velocity.
+///
+/// # Parameters:
+///
+/// - `rotation`: Rotation at the start of the step
+/// - `angular`: Angular velocity at the start of the step
+/// - `torque`: Total torque applied during the step
+/// - `inertia`: Inertia of the body, bodies without an inertia are not turned by the torque
+/// - `damping`: Velocity damping of the body, if any
+/// - `delta_seconds`: Length of the step
+///
+/// # Returns:
+///
+/// The rotation and angular velocity at the end of the step.
+pub fn integrate_rotation<V, R>(
+    rotation: &R,
+    angular: V::Angular,
+    torque: V::Angular,
+    inertia: Option<&Inertia<V>>,
+    damping: Option<&Damping>,
+    delta_seconds: Real,
+) -> (R, V::Angular)
+where
+    V: RotationalVector,
+    R: AngularRotation<V>,
+{
+    let acceleration = match inertia {
+        Some(inertia) => V::apply_inverse_inertia(
+            &rotation.world_inverse_inertia(&inertia.inverse_inertia),
+            torque,
+        ),
+        None => V::Angular::zero(),
+    };
+    let angular = match damping {
+        Some(damping) => damping.damp_angular::<V>(angular, delta_seconds),
+        None => angular,
+    };
+    let angular = angular + V::angular_scale(acceleration, delta_seconds);
+    (
+        rotation.rotated(V::angular_scale(angular, delta_seconds)),
+        angular,
+    )
+}
+
+/// Data used for rotational contact resolution
+#[derive(Debug, Clone)]
+pub struct RotationalResolveData<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: RotationalVector,
+{
+    /// World space center of mass
+    pub center: P,
+    /// Linear velocity
+    pub linear: P::Diff,
+    /// Angular velocity
+    pub angular: <P::Diff as LinearVector>::Angular,
+    /// Inverse mass, 0 for immovable bodies
+    pub inverse_mass: Real,
+    /// World space inverse inertia, 0 for bodies that can not rotate
+    pub inverse_inertia: <P::Diff as RotationalVector>::InverseInertia,
+}
+
+/// Result of a rotational contact or joint resolution
+#[derive(Debug, Clone)]
+pub struct RotationalResolution<V>
+where
+    V: RotationalVector,
+{
+    /// Impulse applied to the second body at the contact point or joint anchor, the first body
+    /// gets the negated impulse
+    pub impulse: V,
+    /// New linear velocities of the two bodies
+    pub linear: (V, V),
+    /// New angular velocities of the two bodies
+    pub angular: (V::Angular, V::Angular),
+}
+
+/// Compute the velocity of the second body relative to the first body at the contact point, along
+/// the contact normal. Negative when the bodies approach each other.
+pub fn relative_point_normal_velocity<ID, P>(
+    contact: &ContactEvent<ID, P>,
+    a: &RotationalResolveData<P>,
+    b: &RotationalResolveData<P>,
+) -> Real
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: RotationalVector + Debug,
+{
+    let point = contact.contact.contact_point;
+    let velocity = |body: &RotationalResolveData<P>| {
+        body.linear + P::Diff::point_velocity(body.angular, point - body.center)
+    };
+    (velocity(b) - velocity(a)).dot(contact.contact.normal)
+}
+
+/// Rotational contact resolution.
+///
+/// Unlike [`linear_resolve_contact`](fn.linear_resolve_contact.html), the impulse is applied at
+/// the contact point, so off-center collisions change the angular velocity of the bodies. The
+/// impulse magnitude accounts for the rotational inertia of both bodies, using the cross-product
+/// terms of the contact offsets with the inverse inertia.
+///
+/// Position correction is not done, see
+/// [`linear_resolve_contact`](fn.linear_resolve_contact.html).
+///
+/// # Parameters:
+///
+/// - `contact`: The contact, the contact point is used as the point of application
+/// - `a`: Resolve data of the first body
+/// - `b`: Resolve data of the second body
+/// - `restitution`: Coefficient of restitution
+///
+/// # Returns:
+///
+/// The impulse and the new velocities of the bodies, `None` if the bodies are separating at the
+/// contact point, or both bodies are immovable.
+pub fn rotational_resolve_contact<ID, P>(
+    contact: &ContactEvent<ID, P>,
+    a: &RotationalResolveData<P>,
+    b: &RotationalResolveData<P>,
+    restitution: Real,
+) -> Option<RotationalResolution<P::Diff>>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: RotationalVector + Debug,
+{
+    let normal = contact.contact.normal;
+    let velocity_along_normal = relative_point_normal_velocity(contact, a, b);
+    if velocity_along_normal > 0. {
+        return None;
+    }
+    let point = contact.contact.contact_point;
+    let offset_a = point - a.center;
+    let offset_b = point - b.center;
+    let angular_term = |body: &RotationalResolveData<P>, offset| {
+        let arm = P::Diff::cross(offset, normal);
+        P::Diff::angular_dot(
+            arm,
+            P::Diff::apply_inverse_inertia(&body.inverse_inertia, arm),
+        )
+    };
+    let effective_inverse_mass = a.inverse_mass + b.inverse_mass + angular_term(a, offset_a)
+        + angular_term(b, offset_b);
+    if effective_inverse_mass <= 0. {
+        return None;
+    }
+    let j = -(1. + restitution) * velocity_along_normal / effective_inverse_mass;
+    let impulse = normal * j;
+    let angular_change = |body: &RotationalResolveData<P>, offset| {
+        P::Diff::apply_inverse_inertia(&body.inverse_inertia, P::Diff::cross(offset, impulse))
+    };
+    Some(RotationalResolution {
+        impulse,
+        linear: (
+            a.linear - impulse * a.inverse_mass,
+            b.linear + impulse * b.inverse_mass,
+        ),
+        angular: (
+            a.angular + P::Diff::angular_scale(angular_change(a, offset_a), -1.),
+            b.angular + angular_change(b, offset_b),
+        ),
+    })
+}
+
+/// Compute the Coulomb friction impulse for a contact, applied at the contact point.
+///
+/// Unlike [`linear_friction_impulse`](fn.linear_friction_impulse.html), the sliding is measured
+/// at the contact point, so a spinning body slides on the surface even when its center is at
+/// rest, and the friction impulse changes the angular velocity of the bodies. This is how a
+/// driven wheel gets traction.
+///
+/// # Parameters:
+///
+/// - `contact`: The contact, the contact point is used as the point of application
+/// - `a`: Resolve data of the first body, with the velocities after the normal impulse
+/// - `b`: Resolve data of the second body, with the velocities after the normal impulse
+/// - `normal_impulse`: Magnitude of the normal impulse applied for the contact
+/// - `static_friction`: Coefficient of static friction
+/// - `dynamic_friction`: Coefficient of dynamic friction
+///
+/// # Returns:
+///
+/// The friction impulse and the new velocities of the bodies, `None` if the bodies are not
+/// sliding at the contact point, or both bodies are immovable.
+pub fn rotational_friction_impulse<ID, P>(
+    contact: &ContactEvent<ID, P>,
+    a: &RotationalResolveData<P>,
+    b: &RotationalResolveData<P>,
+    normal_impulse: Real,
+    static_friction: Real,
+    dynamic_friction: Real,
+) -> Option<RotationalResolution<P::Diff>>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: RotationalVector + Debug,
+{
+    let point = contact.contact.contact_point;
+    let normal = contact.contact.normal;
+    let offset_a = point - a.center;
+    let offset_b = point - b.center;
+    let velocity = |body: &RotationalResolveData<P>, offset| {
+        body.linear + P::Diff::point_velocity(body.angular, offset)
+    };
+    let relative_velocity = velocity(b, offset_b) - velocity(a, offset_a);
+    let tangent_velocity = relative_velocity - normal * relative_velocity.dot(normal);
+    let speed = tangent_velocity.magnitude();
+    if speed <= Real::EPSILON {
+        return None;
+    }
+    let tangent = tangent_velocity / speed;
+    let angular_term = |body: &RotationalResolveData<P>, offset| {
+        let arm = P::Diff::cross(offset, tangent);
+        P::Diff::angular_dot(
+            arm,
+            P::Diff::apply_inverse_inertia(&body.inverse_inertia, arm),
+        )
+    };
+    let effective_inverse_mass = a.inverse_mass + b.inverse_mass + angular_term(a, offset_a)
+        + angular_term(b, offset_b);
+    if effective_inverse_mass <= 0. {
+        return None;
+    }
+    let sticking = speed / effective_inverse_mass;
+    let j = if sticking <= static_friction * normal_impulse {
+        sticking
+    } else {
+        (dynamic_friction * normal_impulse).min(sticking)
+    };
+    let impulse = tangent * -j;
+    let angular_change = |body: &RotationalResolveData<P>, offset| {
+        P::Diff::apply_inverse_inertia(&body.inverse_inertia, P::Diff::cross(offset, impulse))
+    };
+    Some(RotationalResolution {
+        impulse,
+        linear: (
+            a.linear - impulse * a.inverse_mass,
+            b.linear + impulse * b.inverse_mass,
+        ),
+        angular: (
+            a.angular + P::Diff::angular_scale(angular_change(a, offset_a), -1.),
+            b.angular + angular_change(b, offset_b),
+        ),
+    })
+}
+
+/// Marker for round bodies that roll on the surfaces they touch, like wheels and balls.
+///
+/// The contact solver applies the contact friction of rolling bodies at the contact point, see
+/// [`rotational_friction_impulse`](fn.rotational_friction_impulse.html), so friction turns their
+/// [`AngularVelocity`](struct.AngularVelocity.html) into motion along the surface, and sliding
+/// makes them spin. The normal impulse is still applied through the center of mass, which is only
+/// correct for round shapes, so bodies with flat sides should not roll.
+#[derive(Debug, Clone, Default)]
+pub struct Rolling;
+
+impl<F> ForceAccumulator<F>
+where
+    F: RotationalVector,
+{
+    /// Add a force applied at a world space point to the accumulator.
+    ///
+    /// The force is added to the linear channel, and the torque of the force around the position
+    /// of the body is added to the torque channel, so an off-center force both pushes and spins
+    /// the body. The body position is used as the center of mass, see
+    /// [`recenter_shape`](fn.recenter_shape.html).
+    ///
+    /// # Parameters:
+    ///
+    /// - `force`: The force vector
+    /// - `point`: World space point where the force is applied
+    /// - `pose`: Current pose of the body
+    pub fn add_force_at_point<P, R>(&mut self, force: F, point: P, pose: &BodyPose<P, R>)
+    where
+        P: EuclideanSpace<Scalar = Real, Diff = F>,
+        R: Rotation<P>,
+    {
+        self.add_force(force);
+        self.add_torque(F::cross(point - *pose.position(), force));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, One, Point2, Vector2, Zero};
+
+    use super::*;
+    use collide::{CollisionStrategy, Contact};
+
+    #[test]
+    fn test_off_center_contact_spins() {
+        let contact = ContactEvent::new(
+            (0, 1),
+            Contact::new_with_point(
+                CollisionStrategy::FullResolution,
+                Vector2::new(0., 1.),
+                0.1,
+                Point2::new(1., 0.),
+            ),
+        );
+        let body = |y, linear| RotationalResolveData {
+            center: Point2::new(0., y),
+            linear,
+            angular: 0.,
+            inverse_mass: 1.,
+            inverse_inertia: 1.,
+        };
+        let a = body(-0.5, Vector2::new(0., 1.));
+        let b = body(0.5, Vector2::new(0., -1.));
+        let resolution = rotational_resolve_contact(&contact, &a, &b, 1.).unwrap();
+        assert!(resolution.angular.0 < 0.);
+        assert!(resolution.angular.1 > 0.);
+        let a = RotationalResolveData {
+            linear: resolution.linear.0,
+            angular: resolution.angular.0,
+            ..a
+        };
+        let b = RotationalResolveData {
+            linear: resolution.linear.1,
+            angular: resolution.angular.1,
+            ..b
+        };
+        assert_ulps_eq!(relative_point_normal_velocity(&contact, &a, &b), 2.);
+    }
+
+    #[test]
+    fn test_spinning_body_gets_traction() {
+        let contact = ContactEvent::new(
+            (0, 1),
+            Contact::new_with_point(
+                CollisionStrategy::FullResolution,
+                Vector2::new(0., 1.),
+                0.,
+                Point2::new(0., 0.),
+            ),
+        );
+        let ground = RotationalResolveData {
+            center: Point2::new(0., -1.),
+            linear: Vector2::zero(),
+            angular: 0.,
+            inverse_mass: 0.,
+            inverse_inertia: 0.,
+        };
+        // a wheel of radius 1 spinning clockwise, the contact point slides backwards
+        let wheel = RotationalResolveData {
+            center: Point2::new(0., 1.),
+            linear: Vector2::zero(),
+            angular: -2.,
+            inverse_mass: 1.,
+            inverse_inertia: 2.,
+        };
+        let resolution =
+            rotational_friction_impulse(&contact, &ground, &wheel, 10., 1., 1.).unwrap();
+        // sticking friction, the wheel rolls without sliding, trading spin for speed
+        assert_ulps_eq!(resolution.linear.1, Vector2::new(2. / 3., 0.));
+        assert_ulps_eq!(resolution.angular.1, -2. / 3.);
+        assert_ulps_eq!(resolution.linear.0, Vector2::zero());
+        // limited by the dynamic friction
+        let resolution =
+            rotational_friction_impulse(&contact, &ground, &wheel, 0.5, 1., 1.).unwrap();
+        assert_ulps_eq!(resolution.impulse, Vector2::new(0.5, 0.));
+    }
+
+    #[test]
+    fn test_force_at_point() {
+        let pose = BodyPose::new(Point2::new(1., 0.), Basis2::one());
+        let mut forces = ForceAccumulator::new();
+        forces.add_force_at_point(Vector2::new(0., 2.), Point2::new(2., 0.), &pose);
+        forces.add_force_at_point(Vector2::new(1., 0.), Point2::new(1., 0.), &pose);
+        assert_ulps_eq!(forces.peek_force(), Vector2::new(1., 2.));
+        assert_ulps_eq!(forces.peek_torque(), 2.);
+    }
+
+    #[test]
+    fn test_integrate_rotation() {
+        let inertia = Inertia::<Vector2<Real>>::new(2.);
+        let (rotation, angular) =
+            integrate_rotation(&Basis2::one(), 0., 2., Some(&inertia), None, 1.);
+        assert_ulps_eq!(angular, 1.);
+        assert_ulps_eq!(
+            rotation.rotate_vector(Vector2::new(1., 0.)),
+            Vector2::new((1. as Real).cos(), (1. as Real).sin())
+        );
+        let (_, angular) = integrate_rotation::<Vector2<Real>, _>(
+            &rotation,
+            angular,
+            2.,
+            None,
+            Some(&Damping::new(0., 1.)),
+            1.,
+        );
+        assert_ulps_eq!(angular, (-1. as Real).exp());
+    }
+}
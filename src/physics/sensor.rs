@@ -0,0 +1,125 @@
+use std::string::String;
+use std::vec::Vec;
+
+use Real;
+
+/// A single named contact sensor, see [`ContactSensors`](struct.ContactSensors.html)
+#[derive(Debug, Clone)]
+pub struct ContactSensor {
+    name: String,
+    primitive: usize,
+    in_contact: bool,
+    normal_force: Real,
+}
+
+impl ContactSensor {
+    /// Name of the sensor
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Index of the sensor primitive in the collision shape of the body
+    pub fn primitive(&self) -> usize {
+        self.primitive
+    }
+
+    /// Did the sensor primitive have a contact in the last step
+    pub fn in_contact(&self) -> bool {
+        self.in_contact
+    }
+
+    /// Estimated normal force on the sensor primitive in the last step, from the contact impulses
+    /// applied by the contact solver
+    pub fn normal_force(&self) -> Real {
+        self.normal_force
+    }
+}
+
+/// Contact sensor array, e.g. for the foot pads of a legged robot.
+///
+/// Each sensor is a named primitive in the collision shape of the body. The contact state and the
+/// normal force estimate of the sensors are updated each step by the contact solver. Contacts with
+/// bodies that have no mass are reported as contacts, but with no normal force.
+#[derive(Debug, Clone, Default)]
+pub struct ContactSensors {
+    sensors: Vec<ContactSensor>,
+}
+
+impl ContactSensors {
+    /// Create a new contact sensor array, without any sensors
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a sensor, builder pattern.
+    ///
+    /// # Parameters:
+    ///
+    /// - `name`: Name of the sensor
+    /// - `primitive`: Index of the sensor primitive in the collision shape of the body
+    pub fn with_sensor(mut self, name: &str, primitive: usize) -> Self {
+        self.sensors.push(ContactSensor {
+            name: String::from(name),
+            primitive,
+            in_contact: false,
+            normal_force: 0.,
+        });
+        self
+    }
+
+    /// Get the sensor with the given name
+    pub fn get(&self, name: &str) -> Option<&ContactSensor> {
+        self.sensors.iter().find(|s| s.name == name)
+    }
+
+    /// Did the sensor with the given name have a contact in the last step
+    pub fn in_contact(&self, name: &str) -> bool {
+        self.get(name).map(|s| s.in_contact).unwrap_or(false)
+    }
+
+    /// Estimated normal force on the sensor with the given name in the last step
+    pub fn normal_force(&self, name: &str) -> Real {
+        self.get(name).map(|s| s.normal_force).unwrap_or(0.)
+    }
+
+    /// Get all sensors
+    pub fn sensors(&self) -> &[ContactSensor] {
+        &self.sensors
+    }
+
+    /// Clear the contact state of all sensors, done at the start of each step
+    pub fn reset(&mut self) {
+        for sensor in &mut self.sensors {
+            sensor.in_contact = false;
+            sensor.normal_force = 0.;
+        }
+    }
+
+    /// Record a contact on the given primitive, with the given normal force
+    pub fn record(&mut self, primitive: usize, normal_force: Real) {
+        for sensor in self.sensors.iter_mut().filter(|s| s.primitive == primitive) {
+            sensor.in_contact = true;
+            sensor.normal_force += normal_force;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let mut sensors = ContactSensors::new()
+            .with_sensor("left", 1)
+            .with_sensor("right", 2);
+        sensors.record(1, 5.);
+        sensors.record(1, 2.);
+        assert!(sensors.in_contact("left"));
+        assert!(!sensors.in_contact("right"));
+        assert_ulps_eq!(7., sensors.normal_force("left"));
+        sensors.reset();
+        assert!(!sensors.in_contact("left"));
+        assert_ulps_eq!(0., sensors.normal_force("left"));
+    }
+}
@@ -0,0 +1,762 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::vec::Vec;
+
+use cgmath::{EuclideanSpace, InnerSpace, Rotation, Zero};
+
+use super::island::group_pairs;
+use super::{DistanceConstraint, Material, PositionCorrection, SolverBody};
+use {BodyPose, Real};
+use collide::ContactEvent;
+
+const DEFAULT_ITERATIONS: usize = 10;
+const DEFAULT_TOLERANCE: Real = 1e-4;
+const DEFAULT_RESTING_THRESHOLD: Real = 0.5;
+
+/// Accumulated normal and friction impulses of the contacts of a step, by bodies and primitives,
+/// used for warm starting the next step
+pub type ImpulseCache<ID, V> = BTreeMap<(ID, ID, usize, usize), (Real, V)>;
+
+/// Contact prepared for the iterations, with the impulses accumulated so far
+struct Constraint<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+{
+    contact: usize,
+    bodies: (usize, usize),
+    inverse_masses: (Real, Real),
+    normal: P::Diff,
+    depth: Real,
+    bias: Real,
+    material: Material,
+    normal_impulse: Real,
+    friction_impulse: P::Diff,
+}
+
+/// Distance constraint prepared for the iterations, only set up when a limit is reached
+struct DistanceLimit<P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+{
+    constraint: usize,
+    bodies: (usize, usize),
+    inverse_masses: (Real, Real),
+    axis: P::Diff,
+    error: Real,
+    bounds: (Real, Real),
+    impulse: Real,
+}
+
+/// Sequential impulse contact solver.
+///
+/// All contacts of a step are resolved together, by applying impulses to each contact in turn,
+/// and repeating for several iterations. The total impulse of each contact is accumulated over
+/// the iterations, and clamped so the contact only ever pushes the bodies apart, which lets the
+/// impulses propagate through stacks and piles, instead of the jitter of resolving each contact
+/// once. Coulomb friction is accumulated the same way, and clamped to the friction cone of the
+/// accumulated normal impulse.
+///
+/// Contacts and constraints are grouped into islands of bodies that affect each other, see
+/// [`Island`](struct.Island.html), and each island is iterated on its own. The iterations of an
+/// island stop early when the largest impulse change of an iteration is below the tolerance, so a
+/// settled island does not pay for the iterations of a busy one. The accumulated impulses of the
+/// step are cached, and used as the starting impulses for the same contacts in the next step (warm
+/// starting), so resting contacts converge in very few iterations. Contacts are identified by the
+/// bodies and the primitives that generated them.
+///
+/// [`DistanceConstraint`](struct.DistanceConstraint.html)s can be solved in the same iterations
+/// as the contacts, see
+/// [`solve_with_distance_constraints`](#method.solve_with_distance_constraints).
+///
+/// Penetration is corrected once per contact, after the velocity iterations, and so are distance
+/// errors.
+///
+/// # Type parameters:
+///
+/// - `ID`: The ID type of the bodies, used to identify contacts between steps
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+#[derive(Debug, Clone)]
+pub struct SequentialImpulseSolver<ID, P>
+where
+    ID: Ord,
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    iterations: usize,
+    tolerance: Real,
+    warm_starting: Real,
+    resting_threshold: Real,
+    last_iterations: usize,
+    cache: ImpulseCache<ID, P::Diff>,
+}
+
+impl<ID, P> SequentialImpulseSolver<ID, P>
+where
+    ID: Clone + Ord,
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace + Debug,
+{
+    /// Create a new solver, with 10 iterations and full warm starting
+    pub fn new() -> Self {
+        Self {
+            iterations: DEFAULT_ITERATIONS,
+            tolerance: DEFAULT_TOLERANCE,
+            warm_starting: 1.,
+            resting_threshold: DEFAULT_RESTING_THRESHOLD,
+            last_iterations: 0,
+            cache: BTreeMap::default(),
+        }
+    }
+
+    /// Set the maximum number of iterations per step, builder pattern
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations.max(1);
+        self
+    }
+
+    /// Set the impulse change below which the iterations are considered converged, builder
+    /// pattern. A tolerance of zero always runs all iterations.
+    pub fn with_tolerance(mut self, tolerance: Real) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Set the fraction of the cached impulses of the last step used as starting impulses,
+    /// builder pattern. Zero disables warm starting.
+    pub fn with_warm_starting(mut self, factor: Real) -> Self {
+        self.warm_starting = factor;
+        self
+    }
+
+    /// Set the relative speed along the contact normal below which contacts are resting contacts,
+    /// and are resolved without restitution, builder pattern
+    pub fn with_resting_threshold(mut self, resting_threshold: Real) -> Self {
+        self.resting_threshold = resting_threshold;
+        self
+    }
+
+    /// Maximum number of iterations per step
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// Number of iterations used in the last step, by the island that needed the most
+    pub fn last_iterations(&self) -> usize {
+        self.last_iterations
+    }
+
+    /// Forget the cached impulses, e.g. after teleporting bodies
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+
+    /// Get the cached impulses for modification, e.g. to save and restore them
+    pub fn impulse_cache_mut(&mut self) -> &mut ImpulseCache<ID, P::Diff> {
+        &mut self.cache
+    }
+
+    /// Resolve all contacts of a step.
+    ///
+    /// Contacts where either body is not in the slice, or has no mass, are collision only, and
+    /// are not resolved. Frozen bodies act as immovable obstacles at rest.
+    ///
+    /// # Parameters:
+    ///
+    /// - `contacts`: All contacts of the step
+    /// - `bodies`: The bodies, the next frame poses and velocities are updated
+    /// - `body_index`: Get the index of a body in the slice
+    /// - `material`: Material of each contact
+    /// - `position_correction`: Penetration correction tunables
+    ///
+    /// # Returns:
+    ///
+    /// The total impulse applied to the second body of each contact, the first body gets the
+    /// negated impulse. Zero for contacts that were not resolved.
+    pub fn solve<R, B, F>(
+        &mut self,
+        contacts: &[ContactEvent<ID, P>],
+        bodies: &mut [SolverBody<P, R>],
+        body_index: B,
+        material: F,
+        position_correction: &PositionCorrection,
+    ) -> Vec<P::Diff>
+    where
+        ID: Debug,
+        R: Rotation<P>,
+        B: Fn(&ID) -> Option<usize>,
+        F: Fn(&ContactEvent<ID, P>) -> Material,
+    {
+        self.solve_with_distance_constraints(
+            contacts,
+            &mut [],
+            bodies,
+            body_index,
+            material,
+            position_correction,
+        )
+    }
+
+    /// Resolve all contacts and distance constraints of a step together.
+    ///
+    /// Distance constraints where either body is not in the slice are ignored. Bodies without
+    /// mass and frozen bodies act as fixed anchors. The impulse of each distance constraint is
+    /// stored in the constraint, and used for warm starting the next step.
+    ///
+    /// # Parameters:
+    ///
+    /// - `contacts`: All contacts of the step
+    /// - `distances`: All distance constraints
+    /// - `bodies`: The bodies, the next frame poses and velocities are updated
+    /// - `body_index`: Get the index of a body in the slice
+    /// - `material`: Material of each contact
+    /// - `position_correction`: Penetration and distance error correction tunables
+    ///
+    /// # Returns:
+    ///
+    /// The total impulse applied to the second body of each contact, the first body gets the
+    /// negated impulse. Zero for contacts that were not resolved.
+    pub fn solve_with_distance_constraints<R, B, F>(
+        &mut self,
+        contacts: &[ContactEvent<ID, P>],
+        distances: &mut [DistanceConstraint<ID>],
+        bodies: &mut [SolverBody<P, R>],
+        body_index: B,
+        material: F,
+        position_correction: &PositionCorrection,
+    ) -> Vec<P::Diff>
+    where
+        ID: Debug,
+        R: Rotation<P>,
+        B: Fn(&ID) -> Option<usize>,
+        F: Fn(&ContactEvent<ID, P>) -> Material,
+    {
+        let mut constraints = self.prepare(contacts, bodies, &body_index, material);
+        let mut limits = self.prepare_distances(distances, bodies, &body_index);
+
+        // islands never affect each other, so each island iterates until its own impulses converge
+        let pairs = constraints
+            .iter()
+            .map(|constraint| constraint.bodies)
+            .chain(limits.iter().map(|limit| limit.bodies))
+            .collect::<Vec<_>>();
+        let islands = group_pairs(bodies.len(), &pairs, |index| {
+            inverse_mass(&bodies[index]).unwrap_or(0.) == 0.
+        });
+        self.last_iterations = 0;
+        for island in islands {
+            let mut iterations = 0;
+            for _ in 0..self.iterations {
+                iterations += 1;
+                let mut largest_change: Real = 0.;
+                for &index in &island {
+                    let change = match index.checked_sub(constraints.len()) {
+                        None => solve_constraint(&mut constraints[index], bodies),
+                        Some(index) => solve_distance_limit(&mut limits[index], bodies),
+                    };
+                    largest_change = largest_change.max(change);
+                }
+                if largest_change <= self.tolerance {
+                    break;
+                }
+            }
+            self.last_iterations = self.last_iterations.max(iterations);
+        }
+
+        for constraint in &constraints {
+            let total_inverse_mass = constraint.inverse_masses.0 + constraint.inverse_masses.1;
+            let correction = constraint.normal
+                * (position_correction.correction(constraint.depth) / total_inverse_mass);
+            let (a, b) = constraint.bodies;
+            move_body(&mut bodies[a], correction * -constraint.inverse_masses.0);
+            move_body(&mut bodies[b], correction * constraint.inverse_masses.1);
+        }
+        for limit in &limits {
+            let total_inverse_mass = limit.inverse_masses.0 + limit.inverse_masses.1;
+            let correction = limit.axis
+                * (position_correction.correction(limit.error.abs()) * limit.error.signum()
+                    / total_inverse_mass);
+            let (a, b) = limit.bodies;
+            move_body(&mut bodies[a], correction * limit.inverse_masses.0);
+            move_body(&mut bodies[b], correction * -limit.inverse_masses.1);
+        }
+        for distance in distances.iter_mut() {
+            distance.set_impulse(0.);
+        }
+        for limit in limits {
+            distances[limit.constraint].set_impulse(limit.impulse);
+        }
+
+        self.cache.clear();
+        let mut impulses = vec![P::Diff::zero(); contacts.len()];
+        for constraint in constraints {
+            let contact = &contacts[constraint.contact];
+            self.cache.insert(
+                key(contact),
+                (constraint.normal_impulse, constraint.friction_impulse),
+            );
+            impulses[constraint.contact] =
+                constraint.normal * constraint.normal_impulse + constraint.friction_impulse;
+        }
+        impulses
+    }
+
+    /// Set up the constraints of the contacts that will be resolved, and apply the warm starting
+    /// impulses
+    fn prepare<R, B, F>(
+        &self,
+        contacts: &[ContactEvent<ID, P>],
+        bodies: &mut [SolverBody<P, R>],
+        body_index: &B,
+        material: F,
+    ) -> Vec<Constraint<P>>
+    where
+        ID: Debug,
+        R: Rotation<P>,
+        B: Fn(&ID) -> Option<usize>,
+        F: Fn(&ContactEvent<ID, P>) -> Material,
+    {
+        let mut constraints = Vec::with_capacity(contacts.len());
+        for (index, contact) in contacts.iter().enumerate() {
+            let (a, b) = match (body_index(&contact.bodies.0), body_index(&contact.bodies.1)) {
+                (Some(a), Some(b)) if a != b => (a, b),
+                _ => continue,
+            };
+            let inverse_masses = match (inverse_mass(&bodies[a]), inverse_mass(&bodies[b])) {
+                (Some(a), Some(b)) if a + b > 0. => (a, b),
+                _ => continue,
+            };
+            let normal = contact.contact.normal;
+            let material = material(contact);
+            let approach_speed = -(velocity(&bodies[b]) - velocity(&bodies[a])).dot(normal);
+            let bias = if approach_speed >= self.resting_threshold {
+                material.restitution_at(approach_speed) * approach_speed
+            } else {
+                0.
+            };
+            let (normal_impulse, friction_impulse) = match self.cache.get(&key(contact)) {
+                Some(&(normal_impulse, friction_impulse)) if self.warm_starting > 0. => {
+                    // only the part of the cached friction in the current tangent plane is kept
+                    let tangent = friction_impulse - normal * friction_impulse.dot(normal);
+                    (
+                        normal_impulse * self.warm_starting,
+                        tangent * self.warm_starting,
+                    )
+                }
+                _ => (0., P::Diff::zero()),
+            };
+            constraints.push(Constraint {
+                contact: index,
+                bodies: (a, b),
+                inverse_masses,
+                normal,
+                depth: contact.contact.penetration_depth,
+                bias,
+                material,
+                normal_impulse,
+                friction_impulse,
+            });
+        }
+        // warm start only after all approach speeds are known, so resting contacts are not
+        // mistaken for impacts
+        for constraint in &constraints {
+            let impulse =
+                constraint.normal * constraint.normal_impulse + constraint.friction_impulse;
+            apply(constraint.bodies, constraint.inverse_masses, bodies, impulse);
+        }
+        constraints
+    }
+
+    /// Set up the distance constraints that have reached a limit, and apply the warm starting
+    /// impulses
+    fn prepare_distances<R, B>(
+        &self,
+        distances: &[DistanceConstraint<ID>],
+        bodies: &mut [SolverBody<P, R>],
+        body_index: &B,
+    ) -> Vec<DistanceLimit<P>>
+    where
+        R: Rotation<P>,
+        B: Fn(&ID) -> Option<usize>,
+    {
+        let mut limits = Vec::with_capacity(distances.len());
+        for (index, distance) in distances.iter().enumerate() {
+            let (a, b) = match (body_index(&distance.bodies.0), body_index(&distance.bodies.1)) {
+                (Some(a), Some(b)) if a != b => (a, b),
+                _ => continue,
+            };
+            let inverse_masses = (
+                inverse_mass(&bodies[a]).unwrap_or(0.),
+                inverse_mass(&bodies[b]).unwrap_or(0.),
+            );
+            if inverse_masses.0 + inverse_masses.1 <= 0. {
+                continue;
+            }
+            let offset =
+                *bodies[b].next_pose.value.position() - *bodies[a].next_pose.value.position();
+            let length = offset.magnitude();
+            if length <= Real::EPSILON {
+                continue;
+            }
+            // the impulse can only pull the bodies together at the maximum length, and only push
+            // them apart at the minimum length
+            let (error, bounds) = if distance.is_fixed() {
+                (length - distance.max_length, (-Real::INFINITY, Real::INFINITY))
+            } else if length >= distance.max_length {
+                (length - distance.max_length, (-Real::INFINITY, 0.))
+            } else if length <= distance.min_length {
+                (length - distance.min_length, (0., Real::INFINITY))
+            } else {
+                continue;
+            };
+            let impulse = (distance.impulse() * self.warm_starting)
+                .max(bounds.0)
+                .min(bounds.1);
+            let limit = DistanceLimit {
+                constraint: index,
+                bodies: (a, b),
+                inverse_masses,
+                axis: offset / length,
+                error,
+                bounds,
+                impulse,
+            };
+            apply(limit.bodies, limit.inverse_masses, bodies, limit.axis * impulse);
+            limits.push(limit);
+        }
+        limits
+    }
+}
+
+impl<ID, P> Default for SequentialImpulseSolver<ID, P>
+where
+    ID: Clone + Ord,
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace + Debug,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn key<ID, P>(contact: &ContactEvent<ID, P>) -> (ID, ID, usize, usize)
+where
+    ID: Clone,
+    P: EuclideanSpace,
+    P::Diff: Debug,
+{
+    (
+        contact.bodies.0.clone(),
+        contact.bodies.1.clone(),
+        contact.primitives.0,
+        contact.primitives.1,
+    )
+}
+
+/// Inverse mass of a body in the solver, `None` for collision only bodies
+fn inverse_mass<P, R>(body: &SolverBody<P, R>) -> Option<Real>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: Debug,
+    R: Rotation<P>,
+{
+    match body.mass {
+        Some(_) if body.frozen => Some(0.),
+        Some(ref mass) => Some(mass.inverse_mass()),
+        None => None,
+    }
+}
+
+/// Velocity of a body in the solver, frozen bodies are at rest
+fn velocity<P, R>(body: &SolverBody<P, R>) -> P::Diff
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: Debug + Zero,
+    R: Rotation<P>,
+{
+    if body.frozen {
+        P::Diff::zero()
+    } else {
+        body.next_velocity.value.linear
+    }
+}
+
+/// Apply an impulse to the second body of a constraint, and the negated impulse to the first body
+fn apply<P, R>(
+    (a, b): (usize, usize),
+    inverse_masses: (Real, Real),
+    bodies: &mut [SolverBody<P, R>],
+    impulse: P::Diff,
+) where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: Debug,
+    R: Rotation<P>,
+{
+    if !bodies[a].frozen {
+        bodies[a].next_velocity.value.linear =
+            bodies[a].next_velocity.value.linear - impulse * inverse_masses.0;
+    }
+    if !bodies[b].frozen {
+        bodies[b].next_velocity.value.linear =
+            bodies[b].next_velocity.value.linear + impulse * inverse_masses.1;
+    }
+}
+
+/// Do a single iteration for a constraint, and return the magnitude of the impulse change
+fn solve_constraint<P, R>(constraint: &mut Constraint<P>, bodies: &mut [SolverBody<P, R>]) -> Real
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace + Debug,
+    R: Rotation<P>,
+{
+    let (a, b) = constraint.bodies;
+    let effective_mass = 1. / (constraint.inverse_masses.0 + constraint.inverse_masses.1);
+    let normal = constraint.normal;
+
+    let normal_velocity = (velocity(&bodies[b]) - velocity(&bodies[a])).dot(normal);
+    let normal_impulse =
+        (constraint.normal_impulse + (constraint.bias - normal_velocity) * effective_mass).max(0.);
+    let normal_change = normal_impulse - constraint.normal_impulse;
+    constraint.normal_impulse = normal_impulse;
+    apply(
+        constraint.bodies,
+        constraint.inverse_masses,
+        bodies,
+        normal * normal_change,
+    );
+
+    let relative_velocity = velocity(&bodies[b]) - velocity(&bodies[a]);
+    let tangent_velocity = relative_velocity - normal * relative_velocity.dot(normal);
+    let mut friction_impulse = constraint.friction_impulse - tangent_velocity * effective_mass;
+    let magnitude = friction_impulse.magnitude();
+    if magnitude > constraint.material.static_friction * normal_impulse {
+        // sliding, kinetic friction
+        let limit = constraint.material.dynamic_friction * normal_impulse;
+        friction_impulse = if magnitude > 0. {
+            friction_impulse * (limit / magnitude)
+        } else {
+            P::Diff::zero()
+        };
+    }
+    let friction_change = friction_impulse - constraint.friction_impulse;
+    constraint.friction_impulse = friction_impulse;
+    apply(
+        constraint.bodies,
+        constraint.inverse_masses,
+        bodies,
+        friction_change,
+    );
+
+    normal_change.abs() + friction_change.magnitude()
+}
+
+/// Do a single iteration for a distance limit, and return the magnitude of the impulse change
+fn solve_distance_limit<P, R>(limit: &mut DistanceLimit<P>, bodies: &mut [SolverBody<P, R>]) -> Real
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace + Debug,
+    R: Rotation<P>,
+{
+    let (a, b) = limit.bodies;
+    let effective_mass = 1. / (limit.inverse_masses.0 + limit.inverse_masses.1);
+    let relative_velocity = (velocity(&bodies[b]) - velocity(&bodies[a])).dot(limit.axis);
+    let impulse = (limit.impulse - relative_velocity * effective_mass)
+        .max(limit.bounds.0)
+        .min(limit.bounds.1);
+    let change = impulse - limit.impulse;
+    limit.impulse = impulse;
+    apply(limit.bodies, limit.inverse_masses, bodies, limit.axis * change);
+    change.abs()
+}
+
+/// Move the next frame pose of a body, frozen bodies are never moved
+fn move_body<P, R>(body: &mut SolverBody<P, R>, offset: P::Diff)
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: Debug,
+    R: Rotation<P>,
+{
+    if !body.frozen {
+        let position = *body.next_pose.value.position() + offset;
+        body.next_pose.value = BodyPose::new(position, *body.next_pose.value.rotation());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, Point2, Rad, Rotation2, Vector2};
+
+    use super::*;
+    use collide::{CollisionStrategy, Contact};
+    use physics::Mass;
+
+    #[test]
+    fn test_stack_converges_with_warm_starting() {
+        let pose = |y| BodyPose::new(Point2::new(0., y), Basis2::from_angle(Rad(0.)));
+        let falling = |y| {
+            let mut body = SolverBody::new(pose(y), Some(Mass::new(1.)));
+            body.next_velocity.value.linear = Vector2::new(0., -1.);
+            body
+        };
+        let mut ground = SolverBody::new(pose(0.), Some(Mass::new(Real::INFINITY)));
+        ground.frozen = true;
+        let mut bodies = vec![ground, falling(1.), falling(2.)];
+        let contact = |bodies| {
+            ContactEvent::new(
+                bodies,
+                Contact::new_impl(CollisionStrategy::FullResolution, Vector2::new(0., 1.), 0.),
+            )
+        };
+        let contacts = vec![contact((0, 1)), contact((1, 2))];
+        let mut solver = SequentialImpulseSolver::new().with_iterations(50);
+        let impulses = solver.solve(
+            &contacts,
+            &mut bodies,
+            |&index| Some(index),
+            |_| Material::new(0., 0.),
+            &PositionCorrection::default(),
+        );
+        for body in &bodies[1..] {
+            assert_relative_eq!(body.next_velocity.value.linear, Vector2::zero(), epsilon = 1e-3);
+        }
+        // the bottom contact carries both boxes
+        assert_relative_eq!(impulses[0], Vector2::new(0., 2.), epsilon = 1e-3);
+        assert_relative_eq!(impulses[1], Vector2::new(0., 1.), epsilon = 1e-3);
+        let cold = solver.last_iterations();
+
+        for body in &mut bodies[1..] {
+            body.next_velocity.value.linear = Vector2::new(0., -1.);
+        }
+        solver.solve(
+            &contacts,
+            &mut bodies,
+            |&index| Some(index),
+            |_| Material::new(0., 0.),
+            &PositionCorrection::default(),
+        );
+        assert!(solver.last_iterations() < cold);
+    }
+
+    #[test]
+    fn test_islands_converge_on_their_own() {
+        let pose = |y| BodyPose::new(Point2::new(0., y), Basis2::from_angle(Rad(0.)));
+        let contact = |bodies| {
+            ContactEvent::new(
+                bodies,
+                Contact::new_impl(CollisionStrategy::FullResolution, Vector2::new(0., 1.), 0.),
+            )
+        };
+        // a stack of the given height on a frozen ground, the ground is the first body
+        let stack = |height: usize, bodies: &mut Vec<SolverBody<Point2<Real>, Basis2<Real>>>| {
+            let mut below = 0;
+            let mut contacts = Vec::new();
+            for i in 0..height {
+                let mut body = SolverBody::new(pose(i as Real + 1.), Some(Mass::new(1.)));
+                body.next_velocity.value.linear = Vector2::new(0., -1.);
+                bodies.push(body);
+                contacts.push(contact((below, bodies.len() - 1)));
+                below = bodies.len() - 1;
+            }
+            contacts
+        };
+        let ground = || {
+            let mut ground = SolverBody::new(pose(0.), Some(Mass::new(Real::INFINITY)));
+            ground.frozen = true;
+            ground
+        };
+        let solve = |contacts: &[_], bodies: &mut Vec<_>| {
+            let mut solver = SequentialImpulseSolver::new()
+                .with_iterations(100)
+                .with_tolerance(1e-2);
+            solver.solve(
+                contacts,
+                bodies,
+                |&index| Some(index),
+                |_| Material::new(0., 0.),
+                &PositionCorrection::default(),
+            );
+            solver.last_iterations()
+        };
+
+        let mut alone = vec![ground()];
+        let contacts = stack(2, &mut alone);
+        let short_iterations = solve(&contacts, &mut alone);
+
+        // a taller stack on the same ground needs more iterations, but the short stack is solved
+        // exactly as if it was alone
+        let mut together = vec![ground()];
+        let mut contacts = stack(2, &mut together);
+        contacts.extend(stack(8, &mut together));
+        assert!(solve(&contacts, &mut together) > short_iterations);
+        for (body, alone) in together.iter().zip(&alone) {
+            assert_eq!(alone.next_velocity.value.linear, body.next_velocity.value.linear);
+        }
+    }
+
+    #[test]
+    fn test_resting_stack_with_restitution_stays_at_rest() {
+        let pose = |y| BodyPose::new(Point2::new(0., y), Basis2::from_angle(Rad(0.)));
+        let mut bodies = vec![SolverBody::new(pose(0.), Some(Mass::new(Real::INFINITY)))];
+        bodies[0].frozen = true;
+        for i in 1..6 {
+            bodies.push(SolverBody::new(pose(i as Real), Some(Mass::new(1.))));
+        }
+        let contacts = (0..5)
+            .map(|i| {
+                ContactEvent::new(
+                    (i, i + 1),
+                    Contact::new_impl(CollisionStrategy::FullResolution, Vector2::new(0., 1.), 0.),
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut solver = SequentialImpulseSolver::new();
+        for _ in 0..30 {
+            for body in &mut bodies[1..] {
+                body.next_velocity.value.linear.y -= 10. / 60.;
+            }
+            solver.solve(
+                &contacts,
+                &mut bodies,
+                |&index| Some(index),
+                |_| Material::new(1., 0.5),
+                &PositionCorrection::default(),
+            );
+        }
+        // warm starting must not turn resting contacts into bouncing impacts
+        for body in &bodies[1..] {
+            assert_relative_eq!(body.next_velocity.value.linear, Vector2::zero(), epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_rope_only_pulls_when_taut() {
+        let pose = |x| BodyPose::new(Point2::new(x, 0.), Basis2::from_angle(Rad(0.)));
+        let mut solver = SequentialImpulseSolver::new();
+        let correction = PositionCorrection::new(0., 1., None);
+        for &(max_length, expected) in &[(3., 1.), (1., 0.)] {
+            let mut body = SolverBody::new(pose(2.), Some(Mass::new(1.)));
+            body.next_velocity.value.linear = Vector2::new(1., 0.);
+            // the pivot has no mass, and acts as a fixed anchor
+            let mut bodies = vec![SolverBody::new(pose(0.), None), body];
+            let mut distances = vec![DistanceConstraint::rope(0, 1, max_length)];
+            solver.solve_with_distance_constraints(
+                &[],
+                &mut distances,
+                &mut bodies,
+                |&index| Some(index),
+                |_| Material::new(0., 0.),
+                &correction,
+            );
+            assert_relative_eq!(
+                bodies[1].next_velocity.value.linear,
+                Vector2::new(expected, 0.),
+                epsilon = 1e-5
+            );
+            assert_relative_eq!(distances[0].impulse(), expected - 1., epsilon = 1e-5);
+            assert_relative_eq!(
+                bodies[1].next_pose.value.position().x,
+                (2. as Real).min(max_length),
+                epsilon = 1e-5
+            );
+        }
+    }
+}
@@ -1,14 +1,112 @@
 use std::fmt::Debug;
 
 use cgmath::{EuclideanSpace, InnerSpace, Rotation, VectorSpace, Zero};
-use cgmath::num_traits::NumCast;
 
 use super::{Mass, Velocity};
 use {BodyPose, NextFrame, Real};
 use collide::ContactEvent;
 
-const POSITIONAL_CORRECTION_PERCENT: f32 = 0.2;
-const POSITIONAL_CORRECTION_K_SLOP: f32 = 0.01;
+/// Updated next frame pose of a body, `None` if unchanged
+pub(crate) type PoseUpdate<P, R> = Option<NextFrame<BodyPose<P, R>>>;
+
+/// Updated next frame velocity of a body, `None` if unchanged
+pub(crate) type VelocityUpdate<V> = Option<NextFrame<Velocity<V>>>;
+
+/// Updated next frame poses and velocities of both bodies in a contact
+pub(crate) type ContactUpdates<P, R, V> = (
+    PoseUpdate<P, R>,
+    PoseUpdate<P, R>,
+    VelocityUpdate<V>,
+    VelocityUpdate<V>,
+);
+
+/// Positional correction tunables for contact resolution.
+///
+/// The right values depend on the scale of the world, the defaults are tuned for bodies that are
+/// around one unit in size.
+///
+/// Deep penetrations, e.g. from bodies spawned inside each other, can be given a separate, lower
+/// correction limit, see [`with_deep_penetration`](#method.with_deep_penetration). The correction
+/// is then spread over multiple frames, instead of pushing the bodies apart in a single step.
+#[derive(Debug, Clone)]
+pub struct PositionCorrection {
+    /// Allowed penetration, only penetration deeper than this is corrected. Keeping a small
+    /// overlap makes resting contacts stable.
+    pub linear_slop: Real,
+    /// Fraction of the penetration beyond the slop that is corrected each step
+    pub percent: Real,
+    /// Maximum position correction per step, as the total separation distance applied to both
+    /// bodies. `None` means no limit.
+    pub max_correction: Option<Real>,
+    /// Penetration depth above which a contact is a deep penetration. `None` means penetrations
+    /// are never treated as deep.
+    pub deep_penetration_threshold: Option<Real>,
+    /// Maximum position correction per step for deep penetrations, as the total separation
+    /// distance applied to both bodies
+    pub deep_max_correction: Real,
+}
+
+impl PositionCorrection {
+    /// Create new position correction tunables
+    pub fn new(linear_slop: Real, percent: Real, max_correction: Option<Real>) -> Self {
+        Self {
+            linear_slop,
+            percent,
+            max_correction,
+            deep_penetration_threshold: None,
+            deep_max_correction: Real::INFINITY,
+        }
+    }
+
+    /// Limit the position correction per step for penetrations deeper than the threshold, builder
+    /// pattern.
+    ///
+    /// # Parameters:
+    ///
+    /// - `threshold`: Penetration depth above which a contact is a deep penetration
+    /// - `max_correction`: Maximum total separation distance applied per step for deep
+    ///   penetrations
+    pub fn with_deep_penetration(mut self, threshold: Real, max_correction: Real) -> Self {
+        self.deep_penetration_threshold = Some(threshold);
+        self.deep_max_correction = max_correction;
+        self
+    }
+
+    /// Check if a penetration depth is a deep penetration
+    pub fn is_deep(&self, penetration_depth: Real) -> bool {
+        self.deep_penetration_threshold
+            .map(|threshold| penetration_depth > threshold)
+            .unwrap_or(false)
+    }
+
+    /// Compute the separation distance to apply for the given penetration depth
+    pub fn correction(&self, penetration_depth: Real) -> Real {
+        let mut correction = (penetration_depth - self.linear_slop).max(0.) * self.percent;
+        if self.is_deep(penetration_depth) {
+            correction = correction.min(self.deep_max_correction);
+        }
+        match self.max_correction {
+            Some(max_correction) => correction.min(max_correction),
+            None => correction,
+        }
+    }
+}
+
+impl Default for PositionCorrection {
+    fn default() -> Self {
+        Self::new(0.01, 0.2, None)
+    }
+}
+
+/// Solver group of a body.
+///
+/// Contacts are resolved in separate passes per group, in ascending group order, so contacts in
+/// higher groups are resolved last, and take priority. The group of a contact is the highest group
+/// of the two bodies, bodies without a group are in group 0. The number of iterations per group
+/// is configured on the contact solver, e.g. to give player-critical contacts more iterations than
+/// background debris.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct SolverGroup(pub u32);
 
 /// Data used for linear contact resolution
 pub struct LinearResolveData<'a, P, R>
@@ -20,59 +118,51 @@ where
     pub velocity: Option<&'a NextFrame<Velocity<P::Diff>>>,
     /// Position for next frame
     pub position: Option<&'a NextFrame<BodyPose<P, R>>>,
-    /// Mass
+    /// Mass, bodies without a mass are collision only, and are never resolved
     pub mass: Option<&'a Mass>,
 }
 
 /// Linear contact resolution
+///
+/// If either body has no mass, the contact is only for collision detection, and is not resolved.
+///
+/// Penetration is corrected using the given [`PositionCorrection`](struct.PositionCorrection.html)
+/// tunables, and the impulse uses the given coefficient of restitution, see
+/// [`Material`](struct.Material.html).
+///
+/// The impulse is applied through the center of mass, so angular effects are ignored, see
+/// [`rotational_resolve_contact`](fn.rotational_resolve_contact.html) for a resolver that makes
+/// off-center collisions spin the bodies.
 pub fn linear_resolve_contact<'a, ID, P, R>(
     contact: &ContactEvent<ID, P>,
     a: LinearResolveData<'a, P, R>,
     b: LinearResolveData<'a, P, R>,
-) -> (
-    Option<NextFrame<BodyPose<P, R>>>,
-    Option<NextFrame<BodyPose<P, R>>>,
-    Option<NextFrame<Velocity<P::Diff>>>,
-    Option<NextFrame<Velocity<P::Diff>>>,
-)
+    position_correction: &PositionCorrection,
+    restitution: Real,
+) -> ContactUpdates<P, R, P::Diff>
 where
     P: EuclideanSpace<Scalar = Real> + 'a,
     R: Rotation<P> + 'a,
     P::Diff: Debug + Zero + Clone + InnerSpace,
 {
-    let a_velocity = a.velocity
-        .map(|v| v.value.linear.clone())
-        .unwrap_or(P::Diff::zero());
-    let b_velocity = b.velocity
-        .map(|v| v.value.linear.clone())
-        .unwrap_or(P::Diff::zero());
-    let a_inverse_mass = a.mass.map(|m| m.inverse_mass).unwrap_or(0.);
-    let b_inverse_mass = b.mass.map(|m| m.inverse_mass).unwrap_or(0.);
+    let (a_inverse_mass, b_inverse_mass) = match inverse_masses(&a, &b) {
+        Some(inverse_masses) => inverse_masses,
+        None => return (None, None, None, None),
+    };
     let total_inverse_mass = a_inverse_mass + b_inverse_mass;
-    // This only happens when we have 2 infinite masses colliding. Such a collision is undefined
-    if total_inverse_mass == 0. {
-        return (None, None, None, None);
-    }
 
-    let k_slop: Real = NumCast::from(POSITIONAL_CORRECTION_K_SLOP).unwrap();
-    let percent: Real = NumCast::from(POSITIONAL_CORRECTION_PERCENT).unwrap();
-    let correction_penetration_depth = contact.contact.penetration_depth - k_slop;
-    let correction_magnitude = correction_penetration_depth.max(0.) / total_inverse_mass * percent;
+    let correction_magnitude =
+        position_correction.correction(contact.contact.penetration_depth) / total_inverse_mass;
     let correction = contact.contact.normal * correction_magnitude;
     let a_position_new = a.position
         .map(|p| new_pose(p, correction * -a_inverse_mass));
     let b_position_new = b.position.map(|p| new_pose(p, correction * b_inverse_mass));
 
-    let rv = b_velocity - a_velocity;
-    let velocity_along_normal = rv.dot(contact.contact.normal);
-    // Bodies are already separating, don't to impulse resolution
-    if velocity_along_normal > 0. {
-        return (a_position_new, b_position_new, None, None);
-    }
-    let e = 1.0; // TODO: restitution
-    let j = -(1. + e) * velocity_along_normal / total_inverse_mass;
-
-    let impulse = contact.contact.normal * j;
+    let impulse = match linear_contact_impulse(contact, &a, &b, restitution) {
+        Some(impulse) => impulse,
+        // Bodies are already separating, don't to impulse resolution
+        None => return (a_position_new, b_position_new, None, None),
+    };
     let a_velocity_new = a.velocity
         .map(|v| new_velocity(v, impulse * -a_inverse_mass));
     let b_velocity_new = b.velocity
@@ -85,6 +175,173 @@ where
     )
 }
 
+/// Compute the impulse that [`linear_resolve_contact`](fn.linear_resolve_contact.html) will apply
+/// for the given contact, with the given coefficient of restitution.
+///
+/// ### Returns
+///
+/// The impulse applied to the second body, the first body gets the negated impulse. `None` if the
+/// contact will not be resolved with an impulse, either because a body has no mass, or because the
+/// bodies are already separating.
+pub fn linear_contact_impulse<'a, ID, P, R>(
+    contact: &ContactEvent<ID, P>,
+    a: &LinearResolveData<'a, P, R>,
+    b: &LinearResolveData<'a, P, R>,
+    restitution: Real,
+) -> Option<P::Diff>
+where
+    P: EuclideanSpace<Scalar = Real> + 'a,
+    R: Rotation<P> + 'a,
+    P::Diff: Debug + Zero + Clone + InnerSpace,
+{
+    let (a_inverse_mass, b_inverse_mass) = inverse_masses(a, b)?;
+    let total_inverse_mass = a_inverse_mass + b_inverse_mass;
+
+    let velocity_along_normal = relative_normal_velocity(contact, a, b);
+    if velocity_along_normal > 0. {
+        return None;
+    }
+    let j = -(1. + restitution) * velocity_along_normal / total_inverse_mass;
+    Some(contact.contact.normal * j)
+}
+
+/// Compute the Coulomb friction impulse for a contact.
+///
+/// The friction impulse opposes the sliding of the bodies along the contact surface. If the
+/// impulse needed to stop the sliding is within the friction cone of the static friction, the
+/// sliding is stopped, otherwise an impulse of the dynamic friction times the normal impulse is
+/// applied.
+///
+/// # Parameters:
+///
+/// - `contact`: The contact
+/// - `a`: Resolve data of the first body, with the velocity after the normal impulse
+/// - `b`: Resolve data of the second body, with the velocity after the normal impulse
+/// - `normal_impulse`: Magnitude of the normal impulse applied for the contact
+/// - `static_friction`: Coefficient of static friction
+/// - `dynamic_friction`: Coefficient of dynamic friction
+///
+/// ### Returns
+///
+/// The friction impulse applied to the second body, the first body gets the negated impulse.
+/// `None` if either body has no mass, or the bodies are not sliding.
+pub fn linear_friction_impulse<'a, ID, P, R>(
+    contact: &ContactEvent<ID, P>,
+    a: &LinearResolveData<'a, P, R>,
+    b: &LinearResolveData<'a, P, R>,
+    normal_impulse: Real,
+    static_friction: Real,
+    dynamic_friction: Real,
+) -> Option<P::Diff>
+where
+    P: EuclideanSpace<Scalar = Real> + 'a,
+    R: Rotation<P> + 'a,
+    P::Diff: Debug + Zero + Clone + InnerSpace,
+{
+    let (a_inverse_mass, b_inverse_mass) = inverse_masses(a, b)?;
+    let total_inverse_mass = a_inverse_mass + b_inverse_mass;
+
+    let a_velocity = a.velocity
+        .map(|v| v.value.linear)
+        .unwrap_or(P::Diff::zero());
+    let b_velocity = b.velocity
+        .map(|v| v.value.linear)
+        .unwrap_or(P::Diff::zero());
+    let relative_velocity = b_velocity - a_velocity;
+    let normal = contact.contact.normal;
+    let tangent_velocity = relative_velocity - normal * relative_velocity.dot(normal);
+    let speed = tangent_velocity.magnitude();
+    if speed <= Real::EPSILON {
+        return None;
+    }
+    let tangent = tangent_velocity / speed;
+    let sticking = speed / total_inverse_mass;
+    if sticking <= static_friction * normal_impulse {
+        Some(tangent * -sticking)
+    } else {
+        Some(tangent * -(dynamic_friction * normal_impulse).min(sticking))
+    }
+}
+
+/// Classification of a contact, based on how fast the bodies approach each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContactKind {
+    /// The bodies approach each other slower than the resting threshold, e.g. a body lying on the
+    /// ground, the contact is resolved without restitution
+    Resting,
+    /// The bodies approach each other at least as fast as the resting threshold, the contact is
+    /// resolved with restitution
+    Impacting,
+}
+
+/// Compute the relative velocity of the bodies along the contact normal.
+///
+/// ### Returns
+///
+/// The relative normal velocity, negative when the bodies approach each other. Bodies without a
+/// velocity are considered at rest.
+pub fn relative_normal_velocity<'a, ID, P, R>(
+    contact: &ContactEvent<ID, P>,
+    a: &LinearResolveData<'a, P, R>,
+    b: &LinearResolveData<'a, P, R>,
+) -> Real
+where
+    P: EuclideanSpace<Scalar = Real> + 'a,
+    R: Rotation<P> + 'a,
+    P::Diff: Debug + Zero + Clone + InnerSpace,
+{
+    let a_velocity = a.velocity
+        .map(|v| v.value.linear)
+        .unwrap_or(P::Diff::zero());
+    let b_velocity = b.velocity
+        .map(|v| v.value.linear)
+        .unwrap_or(P::Diff::zero());
+    (b_velocity - a_velocity).dot(contact.contact.normal)
+}
+
+/// Classify a contact as resting or impacting, by comparing the speed the bodies approach each
+/// other with along the contact normal, to the given resting threshold.
+pub fn classify_contact<'a, ID, P, R>(
+    contact: &ContactEvent<ID, P>,
+    a: &LinearResolveData<'a, P, R>,
+    b: &LinearResolveData<'a, P, R>,
+    resting_threshold: Real,
+) -> ContactKind
+where
+    P: EuclideanSpace<Scalar = Real> + 'a,
+    R: Rotation<P> + 'a,
+    P::Diff: Debug + Zero + Clone + InnerSpace,
+{
+    if -relative_normal_velocity(contact, a, b) < resting_threshold {
+        ContactKind::Resting
+    } else {
+        ContactKind::Impacting
+    }
+}
+
+fn inverse_masses<'a, P, R>(
+    a: &LinearResolveData<'a, P, R>,
+    b: &LinearResolveData<'a, P, R>,
+) -> Option<(Real, Real)>
+where
+    P: EuclideanSpace<Scalar = Real> + 'a,
+    R: Rotation<P> + 'a,
+{
+    match (a.mass, b.mass) {
+        (Some(a_mass), Some(b_mass)) => {
+            // This only happens when we have 2 infinite masses colliding. Such a collision is
+            // undefined
+            if a_mass.inverse_mass + b_mass.inverse_mass == 0. {
+                None
+            } else {
+                Some((a_mass.inverse_mass, b_mass.inverse_mass))
+            }
+        }
+        // Collision only bodies never reach the solver
+        _ => None,
+    }
+}
+
 fn new_pose<P, R>(
     next_frame: &NextFrame<BodyPose<P, R>>,
     correction: P::Diff,
@@ -96,7 +353,7 @@ where
 {
     let new_position = *next_frame.value.position() + correction;
     NextFrame {
-        value: BodyPose::new(new_position, next_frame.value.rotation().clone()),
+        value: BodyPose::new(new_position, *next_frame.value.rotation()),
     }
 }
 
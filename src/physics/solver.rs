@@ -0,0 +1,468 @@
+use std::fmt::Debug;
+
+use cgmath::{EuclideanSpace, InnerSpace, Rotation, VectorSpace, Zero};
+
+use super::{classify_contact, integrate_linear, linear_contact_impulse, linear_friction_impulse,
+            linear_resolve_contact, relative_normal_velocity, ContactKind, Damping,
+            LinearResolveData, Mass, Material, PositionCorrection, Velocity};
+use super::simple::{PoseUpdate, VelocityUpdate};
+use {BodyPose, NextFrame, Real};
+use collide::ContactEvent;
+
+/// Result of resolving a single contact, see [`solve_contact`](fn.solve_contact.html)
+#[derive(Debug, Clone)]
+pub struct ContactResolution<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: Debug,
+    R: Rotation<P>,
+{
+    /// Impulse applied to the second body, the first body gets the negated impulse. `None` if the
+    /// contact was not resolved with an impulse.
+    pub impulse: Option<P::Diff>,
+    /// Normal force of the contact, the magnitude of the impulse divided by the time step
+    pub normal_force: Real,
+    /// Updated next frame poses of the two bodies, `None` if unchanged
+    pub poses: (PoseUpdate<P, R>, PoseUpdate<P, R>),
+    /// Updated next frame velocities of the two bodies, `None` if unchanged
+    pub velocities: (VelocityUpdate<P::Diff>, VelocityUpdate<P::Diff>),
+}
+
+/// Settings for resolving a single contact, see [`solve_contact`](fn.solve_contact.html)
+#[derive(Debug, Clone)]
+pub struct ContactSettings<'a> {
+    /// Penetration correction tunables
+    pub position_correction: &'a PositionCorrection,
+    /// Material of the contact, the restitution at the impact speed is used for impacting
+    /// contacts
+    pub material: Material,
+    /// Relative normal speed below which the contact is a resting contact
+    pub resting_threshold: Real,
+    /// Whether to correct the penetration, should only be done once per contact and step when
+    /// using several iterations
+    pub correct_position: bool,
+    /// Length of the step
+    pub delta_seconds: Real,
+}
+
+/// Resolve a single contact, the way the contact solver does.
+///
+/// The contact is classified as resting or impacting, see
+/// [`classify_contact`](fn.classify_contact.html), and resting contacts are resolved without
+/// restitution. After the normal impulse, Coulomb friction is applied using the friction
+/// coefficients of the material, see
+/// [`linear_friction_impulse`](fn.linear_friction_impulse.html). Immovable bodies, like frozen
+/// bodies in the contact solver, should be given an infinite mass and no velocity or position.
+///
+/// # Parameters:
+///
+/// - `contact`: The contact
+/// - `a`: Resolve data of the first body
+/// - `b`: Resolve data of the second body
+/// - `settings`: Penetration correction, material and step settings
+///
+/// # Returns:
+///
+/// The impulse, normal force and the updated next frame poses and velocities of the bodies. The
+/// caller is responsible for writing the updates back to the bodies.
+pub fn solve_contact<'a, ID, P, R>(
+    contact: &ContactEvent<ID, P>,
+    a: LinearResolveData<'a, P, R>,
+    b: LinearResolveData<'a, P, R>,
+    settings: &ContactSettings,
+) -> ContactResolution<P, R>
+where
+    P: EuclideanSpace<Scalar = Real> + 'a,
+    P::Diff: Debug + Zero + Clone + InnerSpace,
+    R: Rotation<P> + 'a,
+{
+    let material = &settings.material;
+    let restitution = match classify_contact(contact, &a, &b, settings.resting_threshold) {
+        ContactKind::Resting => 0.,
+        ContactKind::Impacting => {
+            material.restitution_at(-relative_normal_velocity(contact, &a, &b))
+        }
+    };
+    let impulse = linear_contact_impulse(contact, &a, &b, restitution);
+    let normal_force = match impulse {
+        Some(impulse) if settings.delta_seconds > 0. => {
+            impulse.magnitude() / settings.delta_seconds
+        }
+        _ => 0.,
+    };
+    let (velocities, masses) = ((a.velocity, b.velocity), (a.mass, b.mass));
+    let (pose_0, pose_1, velocity_0, velocity_1) =
+        linear_resolve_contact(contact, a, b, settings.position_correction, restitution);
+    let poses = if settings.correct_position {
+        (pose_0, pose_1)
+    } else {
+        (None, None)
+    };
+    let friction = impulse.and_then(|impulse| {
+        linear_friction_impulse::<_, P, R>(
+            contact,
+            &LinearResolveData {
+                velocity: velocity_0.as_ref().or(velocities.0),
+                position: None,
+                mass: masses.0,
+            },
+            &LinearResolveData {
+                velocity: velocity_1.as_ref().or(velocities.1),
+                position: None,
+                mass: masses.1,
+            },
+            impulse.magnitude(),
+            material.static_friction,
+            material.dynamic_friction,
+        )
+    });
+    let (velocity_0, velocity_1) = match (friction, masses) {
+        (Some(friction), (Some(mass_0), Some(mass_1))) => (
+            apply_impulse(velocity_0, velocities.0, friction * -mass_0.inverse_mass()),
+            apply_impulse(velocity_1, velocities.1, friction * mass_1.inverse_mass()),
+        ),
+        _ => (velocity_0, velocity_1),
+    };
+    ContactResolution {
+        impulse: match (impulse, friction) {
+            (Some(impulse), Some(friction)) => Some(impulse + friction),
+            (impulse, _) => impulse,
+        },
+        normal_force,
+        poses,
+        velocities: (velocity_0, velocity_1),
+    }
+}
+
+/// Add a velocity change to the updated velocity of a body, or to the current velocity if it was
+/// not updated. Bodies without a velocity are left untouched.
+fn apply_impulse<V>(
+    updated: Option<NextFrame<Velocity<V>>>,
+    current: Option<&NextFrame<Velocity<V>>>,
+    change: V,
+) -> Option<NextFrame<Velocity<V>>>
+where
+    V: VectorSpace<Scalar = Real>,
+{
+    updated
+        .or_else(|| current.cloned())
+        .map(|velocity| NextFrame {
+            value: Velocity {
+                linear: velocity.value.linear + change,
+            },
+        })
+}
+
+/// Integrate a body over one step, the way the contact solver does.
+///
+/// # Parameters:
+///
+/// - `pose`: Pose at the start of the step
+/// - `velocity`: Velocity at the start of the step
+/// - `force`: Total force applied during the step
+/// - `mass`: Mass of the body, bodies without a mass are not accelerated by the force
+/// - `damping`: Velocity damping of the body, if any
+/// - `delta_seconds`: Length of the step
+///
+/// # Returns:
+///
+/// The pose and velocity at the end of the step, for use as the next frame values.
+pub fn integrate_body<P, R>(
+    pose: &BodyPose<P, R>,
+    velocity: &Velocity<P::Diff>,
+    force: P::Diff,
+    mass: Option<&Mass>,
+    damping: Option<&Damping>,
+    delta_seconds: Real,
+) -> (BodyPose<P, R>, Velocity<P::Diff>)
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: VectorSpace<Scalar = Real>,
+    R: Rotation<P>,
+{
+    let acceleration = match mass {
+        Some(mass) => force * mass.inverse_mass(),
+        None => P::Diff::zero(),
+    };
+    let linear = match damping {
+        Some(damping) => damping.damp_linear(velocity.linear, delta_seconds),
+        None => velocity.linear,
+    };
+    let (position, linear) =
+        integrate_linear(*pose.position(), linear, acceleration, delta_seconds);
+    (
+        BodyPose::new(position, *pose.rotation()),
+        Velocity { linear },
+    )
+}
+
+/// Move the next frame value of a body to the current frame, at the end of a step
+pub fn flip_frame<T>(current: &mut T, next: &NextFrame<T>)
+where
+    T: Clone,
+{
+    *current = next.value.clone();
+}
+
+/// Body data for the slice based solver functions, see
+/// [`solve_contacts`](fn.solve_contacts.html).
+#[derive(Debug)]
+pub struct SolverBody<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: Debug,
+    R: Rotation<P>,
+{
+    /// Current pose
+    pub pose: BodyPose<P, R>,
+    /// Current velocity
+    pub velocity: Velocity<P::Diff>,
+    /// Pose for next frame
+    pub next_pose: NextFrame<BodyPose<P, R>>,
+    /// Velocity for next frame
+    pub next_velocity: NextFrame<Velocity<P::Diff>>,
+    /// Mass, bodies without a mass are collision only, and are never resolved
+    pub mass: Option<Mass>,
+    /// Velocity damping, applied during integration
+    pub damping: Option<Damping>,
+    /// Force to apply during the next integration, reset by
+    /// [`integrate_bodies`](fn.integrate_bodies.html)
+    pub force: P::Diff,
+    /// Frozen bodies are never moved, and act as immovable obstacles in contacts
+    pub frozen: bool,
+}
+
+impl<P, R> SolverBody<P, R>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: Debug + Zero,
+    R: Rotation<P>,
+{
+    /// Create a new solver body, at rest at the given pose
+    pub fn new(pose: BodyPose<P, R>, mass: Option<Mass>) -> Self {
+        let velocity = Velocity {
+            linear: P::Diff::zero(),
+        };
+        Self {
+            next_pose: NextFrame {
+                value: pose.clone(),
+            },
+            next_velocity: NextFrame {
+                value: velocity.clone(),
+            },
+            pose,
+            velocity,
+            mass,
+            damping: None,
+            force: P::Diff::zero(),
+            frozen: false,
+        }
+    }
+}
+
+/// Resolve a list of contacts between bodies stored in a slice.
+///
+/// The body ids of the contacts are indices into the body slice. Penetration is corrected in the
+/// first iteration only, further iterations refine the velocities. This is the same as a single
+/// solver group pass of the contact solver.
+///
+/// # Parameters:
+///
+/// - `contacts`: The contacts to resolve
+/// - `bodies`: The bodies, the next frame values are updated
+/// - `position_correction`: Penetration correction tunables
+/// - `material`: Material of each contact
+/// - `resting_threshold`: Relative normal speed below which contacts are resting contacts
+/// - `iterations`: Number of iterations
+/// - `delta_seconds`: Length of the step
+pub fn solve_contacts<P, R, F>(
+    contacts: &[ContactEvent<usize, P>],
+    bodies: &mut [SolverBody<P, R>],
+    position_correction: &PositionCorrection,
+    material: F,
+    resting_threshold: Real,
+    iterations: usize,
+    delta_seconds: Real,
+) where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: Debug + Zero + Clone + InnerSpace,
+    R: Rotation<P>,
+    F: Fn(&ContactEvent<usize, P>) -> Material,
+{
+    let frozen_mass = Mass::new(Real::INFINITY);
+    for iteration in 0..iterations {
+        for contact in contacts {
+            let resolution = {
+                let resolve_data = |index: usize| {
+                    let body = &bodies[index];
+                    if body.frozen {
+                        LinearResolveData {
+                            velocity: None,
+                            position: None,
+                            mass: Some(&frozen_mass),
+                        }
+                    } else {
+                        LinearResolveData {
+                            velocity: Some(&body.next_velocity),
+                            position: Some(&body.next_pose),
+                            mass: body.mass.as_ref(),
+                        }
+                    }
+                };
+                solve_contact(
+                    contact,
+                    resolve_data(contact.bodies.0),
+                    resolve_data(contact.bodies.1),
+                    &ContactSettings {
+                        position_correction,
+                        material: material(contact),
+                        resting_threshold,
+                        correct_position: iteration == 0,
+                        delta_seconds,
+                    },
+                )
+            };
+            let ContactResolution {
+                poses, velocities, ..
+            } = resolution;
+            if let Some(pose) = poses.0 {
+                bodies[contact.bodies.0].next_pose = pose;
+            }
+            if let Some(pose) = poses.1 {
+                bodies[contact.bodies.1].next_pose = pose;
+            }
+            if let Some(velocity) = velocities.0 {
+                bodies[contact.bodies.0].next_velocity = velocity;
+            }
+            if let Some(velocity) = velocities.1 {
+                bodies[contact.bodies.1].next_velocity = velocity;
+            }
+        }
+    }
+}
+
+/// Move the next frame pose and velocity of all bodies that are not frozen to the current frame
+pub fn flip_bodies<P, R>(bodies: &mut [SolverBody<P, R>])
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: Debug + Clone,
+    R: Rotation<P>,
+{
+    for body in bodies.iter_mut().filter(|body| !body.frozen) {
+        flip_frame(&mut body.pose, &body.next_pose);
+        flip_frame(&mut body.velocity, &body.next_velocity);
+    }
+}
+
+/// Compute the next frame pose and velocity of all bodies, consuming the force of each body.
+/// Frozen bodies keep their current pose and velocity, and their force is discarded.
+pub fn integrate_bodies<P, R>(bodies: &mut [SolverBody<P, R>], delta_seconds: Real)
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: Debug + VectorSpace<Scalar = Real>,
+    R: Rotation<P>,
+{
+    for body in bodies.iter_mut() {
+        let force = body.force;
+        body.force = P::Diff::zero();
+        if body.frozen {
+            body.next_pose.value = body.pose.clone();
+            body.next_velocity.value = body.velocity.clone();
+            continue;
+        }
+        let (pose, velocity) = integrate_body(
+            &body.pose,
+            &body.velocity,
+            force,
+            body.mass.as_ref(),
+            body.damping.as_ref(),
+            delta_seconds,
+        );
+        body.next_pose.value = pose;
+        body.next_velocity.value = velocity;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, One, Point2, Vector2};
+
+    use super::*;
+    use collide::{CollisionStrategy, Contact};
+
+    #[test]
+    fn test_solve_contacts() {
+        let pose = |x| BodyPose::new(Point2::new(x, 0.), Basis2::one());
+        let mut bodies = vec![
+            SolverBody::new(pose(0.), Some(Mass::new(1.))),
+            SolverBody::new(pose(0.9), Some(Mass::new(1.))),
+        ];
+        bodies[0].next_velocity.value.linear = Vector2::new(1., 0.);
+        let contacts = vec![
+            ContactEvent::new(
+                (0, 1),
+                Contact::new_with_point(
+                    CollisionStrategy::FullResolution,
+                    Vector2::new(1., 0.),
+                    0.1,
+                    Point2::new(0.5, 0.),
+                ),
+            ),
+        ];
+        let correction = PositionCorrection::default();
+        let material = |_: &ContactEvent<usize, Point2<Real>>| Material::new(1., 0.);
+        solve_contacts(&contacts, &mut bodies, &correction, material, 0.5, 1, 0.1);
+        assert_ulps_eq!(bodies[0].next_velocity.value.linear, Vector2::zero());
+        assert_ulps_eq!(bodies[1].next_velocity.value.linear, Vector2::new(1., 0.));
+        assert!(bodies[1].next_pose.value.position().x > 0.9);
+
+        flip_bodies(&mut bodies);
+        bodies[1].force = Vector2::new(10., 0.);
+        integrate_bodies(&mut bodies, 0.1);
+        assert_ulps_eq!(bodies[1].next_velocity.value.linear, Vector2::new(2., 0.));
+        assert_ulps_eq!(bodies[1].force, Vector2::zero());
+    }
+
+    #[test]
+    fn test_friction() {
+        let pose = |y| BodyPose::new(Point2::new(0., y), Basis2::one());
+        let mut bodies = vec![
+            SolverBody::new(pose(0.), Some(Mass::new(Real::INFINITY))),
+            SolverBody::new(pose(0.9), Some(Mass::new(1.))),
+        ];
+        bodies[1].next_velocity.value.linear = Vector2::new(2., -1.);
+        let contacts = vec![
+            ContactEvent::new(
+                (0, 1),
+                Contact::new_with_point(
+                    CollisionStrategy::FullResolution,
+                    Vector2::new(0., 1.),
+                    0.1,
+                    Point2::new(0., 0.5),
+                ),
+            ),
+        ];
+        let correction = PositionCorrection::default();
+        let sliding = |_: &ContactEvent<usize, Point2<Real>>| Material::new(0., 0.5);
+        solve_contacts(&contacts, &mut bodies, &correction, sliding, 0.5, 1, 0.1);
+        assert_ulps_eq!(bodies[1].next_velocity.value.linear, Vector2::new(1.5, 0.));
+
+        bodies[1].next_velocity.value.linear = Vector2::new(2., -1.);
+        let sticking = |_: &ContactEvent<usize, Point2<Real>>| Material::new(0., 2.);
+        solve_contacts(&contacts, &mut bodies, &correction, sticking, 0.5, 1, 0.1);
+        assert_ulps_eq!(bodies[1].next_velocity.value.linear, Vector2::zero());
+    }
+
+    #[test]
+    fn test_damping() {
+        let pose = BodyPose::new(Point2::new(0., 0.), Basis2::one());
+        let mut bodies = vec![SolverBody::new(pose, Some(Mass::new(1.)))];
+        bodies[0].velocity.linear = Vector2::new(2., 0.);
+        bodies[0].damping = Some(Damping::new(1., 0.));
+        integrate_bodies(&mut bodies, 0.5);
+        let linear = Vector2::new(2. * (-0.5 as Real).exp(), 0.);
+        assert_ulps_eq!(bodies[0].next_velocity.value.linear, linear);
+        let position = Point2::new(0., 0.) + linear * 0.5;
+        assert_ulps_eq!(*bodies[0].next_pose.value.position(), position);
+    }
+}
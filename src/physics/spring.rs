@@ -0,0 +1,230 @@
+use cgmath::{EuclideanSpace, InnerSpace, Zero};
+
+use super::angle::two_pi;
+use Real;
+
+/// Spring parameters, in frequency and damping ratio form.
+///
+/// Stiffness and damping coefficients give different behavior when the time step or the number
+/// of substeps changes, and have to be retuned when the mass of the bodies changes. The frequency
+/// and damping ratio are instead converted to per step coefficients, using the effective mass and
+/// the time step of the step being solved, see [`soft_constraint`](#method.soft_constraint).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpringSettings {
+    /// Oscillation frequency of the spring, in hertz
+    pub frequency: Real,
+    /// Damping ratio, zero gives no damping, one gives critical damping
+    pub damping_ratio: Real,
+}
+
+impl SpringSettings {
+    /// Create new spring settings.
+    ///
+    /// # Parameters:
+    ///
+    /// - `frequency`: Oscillation frequency of the spring, in hertz
+    /// - `damping_ratio`: Damping ratio, one gives critical damping
+    pub fn new(frequency: Real, damping_ratio: Real) -> Self {
+        Self {
+            frequency,
+            damping_ratio,
+        }
+    }
+
+    /// Spring stiffness for the given effective mass
+    pub fn stiffness(&self, mass: Real) -> Real {
+        let omega = two_pi() * self.frequency;
+        mass * omega * omega
+    }
+
+    /// Damping coefficient for the given effective mass
+    pub fn damping(&self, mass: Real) -> Real {
+        2. * mass * self.damping_ratio * two_pi() * self.frequency
+    }
+
+    /// Convert to soft constraint coefficients for a single step.
+    ///
+    /// The spring is integrated implicitly, so it stays stable for any time step.
+    ///
+    /// # Parameters:
+    ///
+    /// - `mass`: Effective mass of the constraint
+    /// - `dt`: Time step
+    ///
+    /// # Returns:
+    ///
+    /// The softness, in units of inverse mass, and the bias factor, the fraction of the position
+    /// error that is corrected in the step. Both are zero if the mass, frequency or time step is
+    /// not positive.
+    pub fn soft_constraint(&self, mass: Real, dt: Real) -> (Real, Real) {
+        let stiffness = self.stiffness(mass);
+        let damping = self.damping(mass);
+        let denominator = damping + dt * stiffness;
+        if mass <= 0. || !mass.is_finite() || dt <= 0. || denominator <= 0. {
+            return (0., 0.);
+        }
+        (1. / (dt * denominator), dt * stiffness / denominator)
+    }
+}
+
+/// Other end of a [`Spring`](struct.Spring.html)
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpringAnchor<ID, P> {
+    /// Attached to another body
+    Body(ID),
+    /// Attached to a fixed point in the world
+    World(P),
+}
+
+/// Spring force generator.
+///
+/// Connects a body to another body, or to a fixed point in the world, with a damped spring. The
+/// spring and damping forces are added to the force accumulators of the bodies each frame, see
+/// [`SpringSystem`](../ecs/physics/struct.SpringSystem.html). Unlike a
+/// [`DistanceJoint`](struct.DistanceJoint.html) with spring settings, the spring is integrated
+/// explicitly, together with the other forces, so very stiff springs can become unstable with a
+/// large time step.
+///
+/// Like joints, the spring is not owned by either body, and in the ECS case is placed on its own
+/// entity.
+///
+/// # Type parameters:
+///
+/// - `ID`: The ID type of the bodies. In the ECS case, this will be `Entity`.
+/// - `P`: Positional quantity, usually `Point2` or `Point3`
+#[derive(Debug, Clone)]
+pub struct Spring<ID, P> {
+    /// The body the spring is attached to
+    pub body: ID,
+    /// The other end of the spring
+    pub anchor: SpringAnchor<ID, P>,
+    /// Length of the spring at rest
+    pub rest_length: Real,
+    /// Force per unit of stretch
+    pub stiffness: Real,
+    /// Force per unit of relative speed along the spring
+    pub damping: Real,
+}
+
+impl<ID, P> Spring<ID, P>
+where
+    P: EuclideanSpace<Scalar = Real>,
+    P::Diff: InnerSpace,
+{
+    /// Create a new spring between two bodies, without damping.
+    ///
+    /// # Parameters:
+    ///
+    /// - `a`: The first body
+    /// - `b`: The second body
+    /// - `rest_length`: Length of the spring at rest
+    /// - `stiffness`: Force per unit of stretch
+    pub fn new(a: ID, b: ID, rest_length: Real, stiffness: Real) -> Self {
+        Self::with_anchor(a, SpringAnchor::Body(b), rest_length, stiffness)
+    }
+
+    /// Create a new spring between a body and a fixed point in the world, without damping.
+    ///
+    /// # Parameters:
+    ///
+    /// - `body`: The body
+    /// - `point`: The point in world space
+    /// - `rest_length`: Length of the spring at rest
+    /// - `stiffness`: Force per unit of stretch
+    pub fn world(body: ID, point: P, rest_length: Real, stiffness: Real) -> Self {
+        Self::with_anchor(body, SpringAnchor::World(point), rest_length, stiffness)
+    }
+
+    fn with_anchor(
+        body: ID,
+        anchor: SpringAnchor<ID, P>,
+        rest_length: Real,
+        stiffness: Real,
+    ) -> Self {
+        Self {
+            body,
+            anchor,
+            rest_length,
+            stiffness,
+            damping: 0.,
+        }
+    }
+
+    /// Set the damping, builder pattern
+    pub fn with_damping(mut self, damping: Real) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    /// Compute the spring force.
+    ///
+    /// # Parameters:
+    ///
+    /// - `a`: Position and velocity of the body
+    /// - `b`: Position and velocity of the other end of the spring
+    ///
+    /// # Returns:
+    ///
+    /// The force on the body, the other end gets the negated force.
+    pub fn force(&self, a: (P, P::Diff), b: (P, P::Diff)) -> P::Diff {
+        let offset = b.0 - a.0;
+        let length = offset.magnitude();
+        if length <= Real::EPSILON {
+            return P::Diff::zero();
+        }
+        let direction = offset / length;
+        let speed = (b.1 - a.1).dot(direction);
+        direction * (self.stiffness * (length - self.rest_length) + self.damping * speed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Point2, Vector2};
+
+    use super::*;
+    use physics::{solve_distance_joint, DistanceJoint, JointBodyData};
+
+    fn simulate(steps: usize) -> Real {
+        let dt = 1. / steps as Real;
+        let mut joint = DistanceJoint::new((), (), 1.).with_spring(SpringSettings::new(2., 0.5));
+        let mut position = Point2::new(1.5, 0.);
+        let mut velocity = Vector2::new(0., 0.);
+        for _ in 0..steps {
+            joint.reset_impulse();
+            let (_, v) = solve_distance_joint(
+                &mut joint,
+                JointBodyData::fixed(Point2::new(0., 0.)),
+                JointBodyData {
+                    position,
+                    velocity,
+                    inverse_mass: 1.,
+                },
+                dt,
+            );
+            velocity = v;
+            position += velocity * dt;
+        }
+        position.x
+    }
+
+    #[test]
+    fn test_spring_force() {
+        let spring = Spring::world((), Point2::new(0., 0.), 1., 10.).with_damping(2.);
+        let at_rest = (Point2::new(0., 0.), Vector2::new(0., 0.));
+        // stretched, and moving away from the anchor
+        let force = spring.force((Point2::new(2., 0.), Vector2::new(1., 0.)), at_rest);
+        assert_relative_eq!(force, Vector2::new(-12., 0.));
+        // compressed
+        let force = spring.force((Point2::new(0., 0.5), Vector2::new(0., 0.)), at_rest);
+        assert_relative_eq!(force, Vector2::new(0., 5.));
+    }
+
+    #[test]
+    fn test_spring_timestep_independent() {
+        let coarse = simulate(60);
+        let fine = simulate(240);
+        assert!(coarse > 0.9 && coarse < 1.1);
+        assert_relative_eq!(coarse, fine, epsilon = 0.02);
+    }
+}
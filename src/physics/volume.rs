@@ -0,0 +1,646 @@
+use std::f64::consts::PI;
+use std::ops::AddAssign;
+use std::vec::Vec;
+
+use cgmath::num_traits::NumCast;
+use cgmath::{Matrix3, Matrix4, Point2, Point3, Vector2, Vector3};
+use cgmath::prelude::*;
+use collision::{Aabb, HasAabb, Primitive, SupportFunction};
+use collision::primitive::{ConvexPolyhedron, Primitive2, Primitive3};
+
+use super::{Inertia, RotationalVector};
+use super::buoyancy::{area_centroid, signed_area};
+use Real;
+use collide::CollisionShape;
+
+/// Volume of a shape, area in 2D
+pub trait Volume {
+    /// Compute the volume
+    fn volume(&self) -> Real;
+}
+
+impl Volume for Primitive2<Real> {
+    fn volume(&self) -> Real {
+        match *self {
+            Primitive2::Particle(_) => 0.,
+            Primitive2::Circle(ref circle) => {
+                let pi: Real = NumCast::from(PI).unwrap();
+                pi * circle.radius * circle.radius
+            }
+            Primitive2::Rectangle(ref rectangle) => bound_volume(&rectangle.get_bound()),
+            Primitive2::ConvexPolygon(ref polygon) => signed_area(&polygon.vertices).abs(),
+        }
+    }
+}
+
+impl Volume for Primitive3<Real> {
+    fn volume(&self) -> Real {
+        match *self {
+            Primitive3::Particle(_) => 0.,
+            Primitive3::Sphere(ref sphere) => {
+                let pi: Real = NumCast::from(PI).unwrap();
+                4. / 3. * pi * sphere.radius * sphere.radius * sphere.radius
+            }
+            Primitive3::Cuboid(ref cuboid) => bound_volume(&cuboid.get_bound()),
+            Primitive3::ConvexPolyhedron(ref polyhedron) => mesh_moments(&hull(polyhedron)).0,
+        }
+    }
+}
+
+/// The volume of a collision shape is the sum of the volumes of its primitives. Overlapping
+/// primitives are counted multiple times, and any scale in the local-to-model transforms is
+/// ignored.
+impl<P, T> Volume for CollisionShape<P, T>
+where
+    P: Volume + Primitive,
+    P::Aabb: Aabb<Scalar = Real>,
+    T: Transform<P::Point>,
+{
+    fn volume(&self) -> Real {
+        self.primitives().iter().map(|(p, _)| p.volume()).sum()
+    }
+}
+
+/// Center of mass of a primitive, in primitive space, assuming uniform density
+pub trait Centroid: Primitive {
+    /// Compute the center of mass
+    fn centroid(&self) -> Self::Point;
+}
+
+impl Centroid for Primitive2<Real> {
+    fn centroid(&self) -> Point2<Real> {
+        match *self {
+            Primitive2::ConvexPolygon(ref polygon) => {
+                if signed_area(&polygon.vertices) < 0. {
+                    let mut vertices = polygon.vertices.clone();
+                    vertices.reverse();
+                    area_centroid(&vertices).1
+                } else {
+                    area_centroid(&polygon.vertices).1
+                }
+            }
+            _ => Point2::origin(),
+        }
+    }
+}
+
+impl Centroid for Primitive3<Real> {
+    fn centroid(&self) -> Point3<Real> {
+        match *self {
+            Primitive3::ConvexPolyhedron(ref polyhedron) => mesh_moments(&hull(polyhedron)).1,
+            _ => Point3::origin(),
+        }
+    }
+}
+
+/// Compute the center of mass of a collision shape, in model space, assuming uniform density.
+///
+/// The centroids of the primitives are weighted by their [`Volume`](trait.Volume.html). If the
+/// shape has no volume, e.g. if it only contains particles, the centroids are weighted equally.
+///
+/// # Parameters:
+///
+/// - `shape`: The collision shape
+///
+/// # Returns:
+///
+/// The center of mass, or the origin if the shape has no primitives.
+pub fn center_of_mass<P, T>(shape: &CollisionShape<P, T>) -> P::Point
+where
+    P: Volume + Centroid,
+    P::Aabb: Aabb<Scalar = Real>,
+    P::Point: EuclideanSpace<Scalar = Real>,
+    T: Transform<P::Point>,
+{
+    let primitives = shape.primitives();
+    let volume = shape.volume();
+    let weights = primitives
+        .iter()
+        .map(|(p, _)| if volume > 0. { p.volume() } else { 1. })
+        .collect::<Vec<_>>();
+    let total = weights.iter().sum::<Real>();
+    if total <= 0. {
+        return P::Point::origin();
+    }
+    let center = primitives
+        .iter()
+        .zip(weights)
+        .map(|((p, t), w)| t.transform_point(p.centroid()).to_vec() * w)
+        .fold(<P::Point as EuclideanSpace>::Diff::zero(), |sum, v| sum + v);
+    P::Point::from_vec(center / total)
+}
+
+/// Move the primitives of a collision shape so that its center of mass is at the model space
+/// origin.
+///
+/// The origin of the body is the pivot it rotates around, so after recentering the body will
+/// rotate around its center of mass. To keep the shape at the same place in the world, move the
+/// body position by the returned offset, rotated by the body rotation. Visuals drawn relative to
+/// the body origin should be moved by the negated offset.
+///
+/// # Parameters:
+///
+/// - `shape`: The collision shape to recenter
+///
+/// # Returns:
+///
+/// The offset from the old model space origin to the center of mass. The primitives have been
+/// moved by the negated offset.
+pub fn recenter_shape<P, T>(shape: &mut CollisionShape<P, T>) -> <P::Point as EuclideanSpace>::Diff
+where
+    P: Volume + Centroid,
+    P::Aabb: Aabb<Scalar = Real>,
+    P::Point: EuclideanSpace<Scalar = Real>,
+    T: Transform<P::Point> + AddAssign<<P::Point as EuclideanSpace>::Diff>,
+{
+    let offset = center_of_mass(shape).to_vec();
+    shape.translate_primitives(offset * -1.);
+    offset
+}
+
+/// Rotational inertia of a collision shape, assuming uniform density
+pub trait ShapeInertia: Volume + Centroid {
+    /// Linear vector type of the primitive, usually `Vector2` or `Vector3`
+    type Vector: RotationalVector;
+
+    /// Compute the inertia of a collision shape made of primitives of this type, around the model
+    /// space origin, which is the position of the body.
+    ///
+    /// # Parameters:
+    ///
+    /// - `shape`: The collision shape
+    /// - `density`: Mass per unit volume, or per unit area in 2D
+    ///
+    /// # Returns:
+    ///
+    /// The inertia of the shape, a shape without volume can not be turned.
+    fn shape_inertia<T>(shape: &CollisionShape<Self, T>, density: Real) -> Inertia<Self::Vector>
+    where
+        Self::Aabb: Aabb<Scalar = Real>,
+        T: Transform<Self::Point>;
+}
+
+impl ShapeInertia for Primitive2<Real> {
+    type Vector = Vector2<Real>;
+
+    fn shape_inertia<T>(shape: &CollisionShape<Self, T>, density: Real) -> Inertia<Vector2<Real>>
+    where
+        T: Transform<Point2<Real>>,
+    {
+        // moment of each primitive around its centroid, moved to the origin by the parallel axis
+        // theorem, rotations do not change the moment in 2D
+        let moment = shape
+            .primitives()
+            .iter()
+            .map(|(primitive, transform)| {
+                let area = primitive.volume();
+                let centroid = primitive.centroid();
+                let offset = transform.transform_point(centroid).to_vec();
+                let moment = match *primitive {
+                    Primitive2::Particle(_) => 0.,
+                    Primitive2::Circle(ref circle) => area * circle.radius * circle.radius / 2.,
+                    Primitive2::Rectangle(ref rectangle) => {
+                        area * rectangle.get_bound().dim().magnitude2() / 12.
+                    }
+                    Primitive2::ConvexPolygon(ref polygon) => {
+                        polygon_moment(&polygon.vertices) - area * centroid.to_vec().magnitude2()
+                    }
+                };
+                moment + area * offset.magnitude2()
+            })
+            .sum::<Real>() * density;
+        if moment > 0. {
+            Inertia::<Vector2<Real>>::new(moment)
+        } else {
+            Inertia::from_inverse(0.)
+        }
+    }
+}
+
+impl ShapeInertia for Primitive3<Real> {
+    type Vector = Vector3<Real>;
+
+    fn shape_inertia<T>(shape: &CollisionShape<Self, T>, density: Real) -> Inertia<Vector3<Real>>
+    where
+        T: Transform<Point3<Real>>,
+    {
+        // tensor of each primitive around its centroid, rotated into model space, and moved to the
+        // origin by the parallel axis theorem
+        let tensor = shape
+            .primitives()
+            .iter()
+            .map(|(primitive, transform)| {
+                let (volume, centroid, tensor) = match *primitive {
+                    Primitive3::Particle(_) => (0., Point3::origin(), Matrix3::zero()),
+                    Primitive3::Sphere(ref sphere) => {
+                        let volume = primitive.volume();
+                        let moment = volume * sphere.radius * sphere.radius * 2. / 5.;
+                        (volume, Point3::origin(), Matrix3::from_value(moment))
+                    }
+                    Primitive3::Cuboid(ref cuboid) => {
+                        let volume = primitive.volume();
+                        let d = cuboid.get_bound().dim();
+                        let (x, y, z) = (d.x * d.x, d.y * d.y, d.z * d.z);
+                        let tensor = Matrix3::from_diagonal(Vector3::new(y + z, x + z, x + y));
+                        (volume, Point3::origin(), tensor * (volume / 12.))
+                    }
+                    Primitive3::ConvexPolyhedron(ref polyhedron) => {
+                        let (volume, centroid, second) = mesh_moments(&hull(polyhedron));
+                        let c = centroid.to_vec();
+                        (volume, centroid, inertia_tensor(second - outer(c, c) * volume))
+                    }
+                };
+                let rotation = Matrix3::from_cols(
+                    transform.transform_vector(Vector3::unit_x()),
+                    transform.transform_vector(Vector3::unit_y()),
+                    transform.transform_vector(Vector3::unit_z()),
+                );
+                let offset = transform.transform_point(centroid).to_vec();
+                rotation * tensor * rotation.transpose()
+                    + inertia_tensor(outer(offset, offset) * volume)
+            })
+            .fold(Matrix3::zero(), |sum, tensor| sum + tensor) * density;
+        Inertia::<Vector3<Real>>::new(tensor)
+    }
+}
+
+/// Compute the inertia of a collision shape with uniform density, around the model space origin,
+/// see [`ShapeInertia`](trait.ShapeInertia.html).
+pub fn shape_inertia<P, T>(shape: &CollisionShape<P, T>, density: Real) -> Inertia<P::Vector>
+where
+    P: ShapeInertia,
+    P::Aabb: Aabb<Scalar = Real>,
+    T: Transform<P::Point>,
+{
+    P::shape_inertia(shape, density)
+}
+
+fn bound_volume<A>(bound: &A) -> Real
+where
+    A: Aabb<Scalar = Real>,
+{
+    bound.dim().product()
+}
+
+/// Second moment of area of a polygon around the origin, with unit density
+fn polygon_moment(vertices: &[Point2<Real>]) -> Real {
+    let moment = (0..vertices.len())
+        .map(|i| {
+            let a = vertices[i].to_vec();
+            let b = vertices[(i + 1) % vertices.len()].to_vec();
+            a.perp_dot(b) * (a.dot(a) + a.dot(b) + b.dot(b))
+        })
+        .sum::<Real>() / 12.;
+    moment.abs()
+}
+
+/// Inertia tensor from the second moment of volume, `I = tr(C) * E - C`
+fn inertia_tensor(second: Matrix3<Real>) -> Matrix3<Real> {
+    Matrix3::from_value(second.trace()) - second
+}
+
+/// Outer product of two vectors, `a * b^T`
+fn outer(a: Vector3<Real>, b: Vector3<Real>) -> Matrix3<Real> {
+    Matrix3::from_cols(a * b.x, a * b.y, a * b.z)
+}
+
+/// Volume, centroid and second moment of volume around the origin of a closed triangle mesh, with
+/// counter clockwise winding seen from the outside.
+fn mesh_moments(triangles: &[[Point3<Real>; 3]]) -> (Real, Point3<Real>, Matrix3<Real>) {
+    let mut volume = 0.;
+    let mut first = Vector3::zero();
+    let mut second = Matrix3::zero();
+    // sum over the tetrahedra spanned by the origin and each triangle
+    for triangle in triangles {
+        let (a, b, c) = (
+            triangle[0].to_vec(),
+            triangle[1].to_vec(),
+            triangle[2].to_vec(),
+        );
+        let tetrahedron = a.dot(b.cross(c)) / 6.;
+        let sum = a + b + c;
+        volume += tetrahedron;
+        first += sum * (tetrahedron / 4.);
+        second += (outer(a, a) + outer(b, b) + outer(c, c) + outer(sum, sum)) * (tetrahedron / 20.);
+    }
+    if volume <= 0. {
+        return (0., Point3::origin(), Matrix3::zero());
+    }
+    (volume, Point3::from_vec(first / volume), second)
+}
+
+/// Triangles of the convex hull of a convex polyhedron, with counter clockwise winding seen from
+/// the outside.
+///
+/// The vertices and faces of the polyhedron are not accessible, so the hull is rebuilt from its
+/// support function. Starting with a tetrahedron of support points, each face whose outward normal
+/// finds a support point beyond the face is replaced, until the support points of all faces lie
+/// on the faces. Flat polyhedra have no triangles.
+fn hull(polyhedron: &ConvexPolyhedron<Real>) -> Vec<[Point3<Real>; 3]> {
+    let identity = Matrix4::<Real>::identity();
+    let support = |direction: Vector3<Real>| polyhedron.support_point(&direction, &identity);
+    let epsilon = polyhedron.get_bound().dim().magnitude() * Real::EPSILON.sqrt();
+    if epsilon.is_nan() || epsilon <= 0. {
+        return Vec::default();
+    }
+
+    // initial tetrahedron, from the two support points farthest apart along the axes, the support
+    // point farthest from the line through them, and the farthest from the plane through all three
+    let axes = [Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()];
+    let candidates = axes
+        .iter()
+        .flat_map(|&axis| vec![support(axis), support(-axis)])
+        .collect::<Vec<_>>();
+    let farthest = |points: &[Point3<Real>], distance: &dyn Fn(Point3<Real>) -> Real| {
+        points
+            .iter()
+            .map(|&p| (p, distance(p)))
+            .fold((points[0], -1.), |best, next| if next.1 > best.1 { next } else { best })
+    };
+    let a = candidates[0];
+    let (a, _) = farthest(&candidates, &|p| (p - a).magnitude());
+    let (b, length) = farthest(&candidates, &|p| (p - a).magnitude());
+    if length <= epsilon {
+        return Vec::default();
+    }
+    let line = (b - a) / length;
+    let across = if line.x.abs() < 0.5 {
+        Vector3::unit_x()
+    } else {
+        Vector3::unit_y()
+    };
+    let across = (across - line * across.dot(line)).normalize();
+    let other = line.cross(across);
+    let directions = [across, -across, other, -other];
+    let points = directions.iter().map(|&d| support(d)).collect::<Vec<_>>();
+    let (c, distance) = farthest(&points, &|p| {
+        let v = p - a;
+        (v - line * v.dot(line)).magnitude()
+    });
+    if distance <= epsilon {
+        return Vec::default();
+    }
+    let normal = (b - a).cross(c - a).normalize();
+    let points = [support(normal), support(-normal)];
+    let (d, distance) = farthest(&points, &|p| (p - a).dot(normal).abs());
+    if distance <= epsilon {
+        return Vec::default();
+    }
+
+    let mut vertices = vec![a, b, c, d];
+    let interior = Point3::centroid(&vertices);
+    let plane = |vertices: &[Point3<Real>], face: [usize; 3]| {
+        let [a, b, c] = [vertices[face[0]], vertices[face[1]], vertices[face[2]]];
+        let normal = (b - a).cross(c - a);
+        let length = normal.magnitude();
+        if length > 0. {
+            Some((normal / length, a))
+        } else {
+            None
+        }
+    };
+    let mut open = [[0, 1, 2], [0, 1, 3], [0, 2, 3], [1, 2, 3]]
+        .iter()
+        .map(|&[i, j, k]| match plane(&vertices, [i, j, k]) {
+            Some((normal, point)) if normal.dot(interior - point) > 0. => [i, k, j],
+            _ => [i, j, k],
+        })
+        .collect::<Vec<_>>();
+    let mut closed = Vec::default();
+
+    while let Some(face) = open.pop() {
+        let (normal, point) = match plane(&vertices, face) {
+            Some(plane) => plane,
+            None => {
+                // degenerate faces have no volume
+                closed.push(face);
+                continue;
+            }
+        };
+        let p = support(normal);
+        if (p - point).dot(normal) <= epsilon || vertices.contains(&p) {
+            closed.push(face);
+            continue;
+        }
+        // replace all faces that see the new point with faces from their horizon to the point
+        vertices.push(p);
+        let index = vertices.len() - 1;
+        let visible = |vertices: &[Point3<Real>], face: &[usize; 3]| match plane(vertices, *face) {
+            Some((normal, point)) => (p - point).dot(normal) > epsilon,
+            None => false,
+        };
+        let mut edges = vec![(face[0], face[1]), (face[1], face[2]), (face[2], face[0])];
+        for faces in [&mut open, &mut closed].iter_mut() {
+            let (seen, kept) = faces
+                .drain(..)
+                .partition::<Vec<_>, _>(|f| visible(&vertices, f));
+            **faces = kept;
+            for f in seen {
+                edges.extend_from_slice(&[(f[0], f[1]), (f[1], f[2]), (f[2], f[0])]);
+            }
+        }
+        for &(i, j) in &edges {
+            if !edges.contains(&(j, i)) {
+                open.push([i, j, index]);
+            }
+        }
+    }
+
+    closed
+        .into_iter()
+        .map(|f| [vertices[f[0]], vertices[f[1]], vertices[f[2]]])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Basis2, Quaternion, Rad, Rotation2, Rotation3};
+    use collision::primitive::{Circle, ConvexPolygon, Cuboid, Particle, Rectangle};
+
+    use super::*;
+    use BodyPose;
+    use collide::{CollisionMode, CollisionStrategy};
+
+    fn shape(
+        primitives: Vec<(Primitive3<Real>, Point3<Real>)>,
+    ) -> CollisionShape<Primitive3<Real>, BodyPose<Point3<Real>, Quaternion<Real>>> {
+        CollisionShape::new_complex(
+            CollisionStrategy::FullResolution,
+            CollisionMode::Discrete,
+            primitives
+                .into_iter()
+                .map(|(p, position)| (p, BodyPose::new(position, Quaternion::one())))
+                .collect(),
+        )
+    }
+
+    fn cube(min: Real, max: Real) -> Primitive3<Real> {
+        let vertices = (0..8)
+            .map(|i| {
+                let corner = |bit| if i & bit == 0 { min } else { max };
+                Point3::new(corner(1), corner(2), corner(4))
+            })
+            .collect();
+        ConvexPolyhedron::new(vertices).into()
+    }
+
+    #[test]
+    fn test_polyhedron_volume_and_centroid() {
+        let tetrahedron = Primitive3::from(ConvexPolyhedron::new(vec![
+            Point3::new(0., 0., 0.),
+            Point3::new(1., 0., 0.),
+            Point3::new(0., 1., 0.),
+            Point3::new(0., 0., 1.),
+        ]));
+        assert_ulps_eq!(1. / 6., tetrahedron.volume(), epsilon = 1e-6);
+        assert_ulps_eq!(
+            Point3::new(0.25, 0.25, 0.25),
+            tetrahedron.centroid(),
+            epsilon = 1e-6
+        );
+
+        let cube = cube(1., 3.);
+        assert_ulps_eq!(8., cube.volume(), epsilon = 1e-5);
+        assert_ulps_eq!(Point3::new(2., 2., 2.), cube.centroid(), epsilon = 1e-5);
+
+        // interior vertices are not part of the hull
+        let octahedron = Primitive3::from(ConvexPolyhedron::new(vec![
+            Point3::new(1., 0., 0.),
+            Point3::new(-1., 0., 0.),
+            Point3::new(0., 1., 0.),
+            Point3::new(0., -1., 0.),
+            Point3::new(0., 0., 1.),
+            Point3::new(0., 0., -1.),
+            Point3::new(0.1, 0.2, 0.1),
+        ]));
+        assert_ulps_eq!(4. / 3., octahedron.volume(), epsilon = 1e-5);
+        assert_ulps_eq!(Point3::origin(), octahedron.centroid(), epsilon = 1e-5);
+
+        let flat = Primitive3::from(ConvexPolyhedron::new(vec![
+            Point3::new(0., 0., 0.),
+            Point3::new(1., 0., 0.),
+            Point3::new(0., 1., 0.),
+        ]));
+        assert_eq!(0., flat.volume());
+    }
+
+    #[test]
+    fn test_shape_inertia_3d() {
+        // a polyhedron cube has the same inertia as a cuboid
+        let cuboid = shape(vec![(Cuboid::new(2., 2., 2.).into(), Point3::origin())]);
+        let polyhedron = shape(vec![(cube(-1., 1.), Point3::origin())]);
+        let expected = Inertia::<Vector3<Real>>::new(Matrix3::from_value(32. / 3.));
+        assert_ulps_eq!(
+            expected.inverse_inertia(),
+            shape_inertia(&cuboid, 2.).inverse_inertia(),
+            epsilon = 1e-5
+        );
+        assert_ulps_eq!(
+            expected.inverse_inertia(),
+            shape_inertia(&polyhedron, 2.).inverse_inertia(),
+            epsilon = 1e-5
+        );
+
+        // moving the primitive away from the origin adds m * d^2 for the axes across the offset
+        let moved = shape(vec![(cube(-1., 1.), Point3::new(0., 0., 2.))]);
+        let tensor = Matrix3::from_diagonal(Vector3::new(32. / 3. + 64., 32. / 3. + 64., 32. / 3.));
+        assert_ulps_eq!(
+            Inertia::<Vector3<Real>>::new(tensor).inverse_inertia(),
+            shape_inertia(&moved, 2.).inverse_inertia(),
+            epsilon = 1e-5
+        );
+
+        // rotating a cuboid around the z axis swaps its x and y moments
+        let rotated = CollisionShape::<_, BodyPose<Point3<Real>, Quaternion<Real>>>::new_complex(
+            CollisionStrategy::FullResolution,
+            CollisionMode::Discrete,
+            vec![(
+                Primitive3::from(Cuboid::new(2., 4., 2.)),
+                BodyPose::new(Point3::origin(), Quaternion::from_angle_z(Rad(PI as Real / 2.))),
+            )],
+        );
+        let tensor = Matrix3::from_diagonal(Vector3::new(32., 80., 80.) / 3.);
+        assert_ulps_eq!(
+            Inertia::<Vector3<Real>>::new(tensor).inverse_inertia(),
+            shape_inertia(&rotated, 1.).inverse_inertia(),
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn test_shape_inertia_2d() {
+        let rotation = Basis2::from_angle(Rad(0.));
+        let shape = |primitive: Primitive2<Real>, x| {
+            CollisionShape::new_complex(
+                CollisionStrategy::FullResolution,
+                CollisionMode::Discrete,
+                vec![(primitive, BodyPose::new(Point2::new(x, 0.), rotation))],
+            )
+        };
+        // a polygon has the same inertia as the rectangle it covers, wherever its vertices are
+        let rectangle = shape(Rectangle::new(2., 4.).into(), 0.);
+        let polygon = shape(
+            ConvexPolygon::new(vec![
+                Point2::new(2., -2.),
+                Point2::new(4., -2.),
+                Point2::new(4., 2.),
+                Point2::new(2., 2.),
+            ]).into(),
+            -3.,
+        );
+        let expected = 8. * 20. / 12. * 3.;
+        assert_ulps_eq!(
+            1. / expected,
+            shape_inertia(&rectangle, 3.).inverse_inertia(),
+            epsilon = 1e-6
+        );
+        assert_ulps_eq!(
+            1. / expected,
+            shape_inertia(&polygon, 3.).inverse_inertia(),
+            epsilon = 1e-6
+        );
+
+        // parallel axis theorem
+        let pi = PI as Real;
+        let circle = shape(Circle::new(1.).into(), 2.);
+        assert_ulps_eq!(
+            1. / (pi / 2. + pi * 4.),
+            shape_inertia(&circle, 1.).inverse_inertia(),
+            epsilon = 1e-6
+        );
+
+        // particles can not be turned
+        let particle = shape(Particle::new().into(), 1.);
+        assert_eq!(0., shape_inertia(&particle, 1.).inverse_inertia());
+    }
+
+    #[test]
+    fn test_recenter_shape() {
+        let rotation = Basis2::from_angle(Rad(0.));
+        let mut shape = CollisionShape::new_complex(
+            CollisionStrategy::FullResolution,
+            CollisionMode::Discrete,
+            vec![
+                (
+                    Primitive2::from(Rectangle::new(2., 2.)),
+                    BodyPose::new(Point2::new(0., 0.), rotation),
+                ),
+                (
+                    Primitive2::from(Rectangle::new(2., 6.)),
+                    BodyPose::new(Point2::new(4., 0.), rotation),
+                ),
+                (
+                    Primitive2::from(Circle::new(0.)),
+                    BodyPose::new(Point2::new(0., 10.), rotation),
+                ),
+            ],
+        );
+        assert_ulps_eq!(Point2::new(3., 0.), center_of_mass(&shape));
+
+        let offset = recenter_shape(&mut shape);
+        assert_ulps_eq!(Vector2::new(3., 0.), offset);
+        assert_ulps_eq!(Point2::origin(), center_of_mass(&shape));
+        assert_ulps_eq!(Point2::new(-4., -3.), shape.base_bound().min);
+        assert_ulps_eq!(Point2::new(2., 10.), shape.base_bound().max);
+    }
+}
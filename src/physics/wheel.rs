@@ -0,0 +1,331 @@
+use cgmath::{Basis2, EuclideanSpace, InnerSpace, Point2, Rotation, Transform, Vector2};
+
+use super::{Inertia, JointCollision, Mass, RotationalVector, SpringSettings};
+use super::angle::{two_pi, wrap_angle};
+use {BodyPose, Real};
+
+/// Fraction of the lateral position error that is corrected each frame
+const LATERAL_CORRECTION_PERCENT: Real = 0.2;
+
+/// Wheel joint for 2D vehicles.
+///
+/// Connects a wheel body to a chassis body. The wheel is kept on a line through the anchor point
+/// along the suspension axis, with a spring/damper along that axis. The wheel spins freely around
+/// its center, and can optionally be driven by a motor.
+///
+/// The motor turns the angular velocity of the wheel body, the wheel only drives the vehicle
+/// through the contact friction with the ground, so the wheel body should be
+/// [`Rolling`](struct.Rolling.html).
+///
+/// The suspension is given as stiffness and damping coefficients, which depend on the time step
+/// and the masses of the bodies, or in frequency and damping ratio form, using
+/// [`with_suspension`](#method.with_suspension).
+///
+/// # Type parameters:
+///
+/// - `ID`: The ID type of the chassis body. In the ECS case, this will be `Entity`.
+#[derive(Debug, Clone)]
+pub struct WheelJoint2<ID> {
+    /// The chassis body the wheel is attached to
+    pub chassis: ID,
+    /// Anchor point of the suspension, in chassis model space
+    pub anchor: Vector2<Real>,
+    /// Suspension axis, in chassis model space. Points from the anchor towards the wheel.
+    pub axis: Vector2<Real>,
+    /// Distance from the anchor to the wheel center along the axis, when the spring is at rest
+    pub rest_length: Real,
+    /// Suspension spring stiffness
+    pub stiffness: Real,
+    /// Suspension damping
+    pub damping: Real,
+    /// Suspension spring settings, when set these replace the stiffness and damping
+    pub suspension: Option<SpringSettings>,
+    /// Wheel radius, used for computing the moment of inertia of wheels without an
+    /// [`Inertia`](struct.Inertia.html)
+    pub radius: Real,
+    /// Target angular speed of the motor, in radians per second
+    pub motor_speed: Real,
+    /// Maximum torque the motor can apply, no motor is active if this is zero
+    pub max_motor_torque: Real,
+    /// Collision between the wheel and the chassis
+    pub collision: JointCollision,
+    angle: Real,
+    angular_velocity: Real,
+}
+
+impl<ID> WheelJoint2<ID> {
+    /// Create a new wheel joint, with the motor disabled.
+    ///
+    /// # Parameters:
+    ///
+    /// - `chassis`: The chassis body
+    /// - `anchor`: Anchor point of the suspension, in chassis model space
+    /// - `axis`: Suspension axis, in chassis model space
+    /// - `rest_length`: Rest length of the suspension spring
+    /// - `stiffness`: Suspension spring stiffness
+    /// - `damping`: Suspension damping
+    /// - `radius`: Wheel radius
+    pub fn new(
+        chassis: ID,
+        anchor: Vector2<Real>,
+        axis: Vector2<Real>,
+        rest_length: Real,
+        stiffness: Real,
+        damping: Real,
+        radius: Real,
+    ) -> Self {
+        Self {
+            chassis,
+            anchor,
+            axis: axis.normalize(),
+            rest_length,
+            stiffness,
+            damping,
+            suspension: None,
+            radius,
+            motor_speed: 0.,
+            max_motor_torque: 0.,
+            collision: JointCollision::default(),
+            angle: 0.,
+            angular_velocity: 0.,
+        }
+    }
+
+    /// Enable the motor
+    pub fn with_motor(mut self, motor_speed: Real, max_motor_torque: Real) -> Self {
+        self.motor_speed = motor_speed;
+        self.max_motor_torque = max_motor_torque;
+        self
+    }
+
+    /// Set the suspension in frequency and damping ratio form, builder pattern
+    pub fn with_suspension(mut self, suspension: SpringSettings) -> Self {
+        self.suspension = Some(suspension);
+        self
+    }
+
+    /// Set the collision between the wheel and the chassis, builder pattern
+    pub fn with_collision(mut self, collision: JointCollision) -> Self {
+        self.collision = collision;
+        self
+    }
+
+    /// Current rotation angle of the wheel, in radians, accumulated from the angular velocity of
+    /// the wheel.
+    ///
+    /// The angle is continuous, and is not wrapped at `±π`, see
+    /// [`wrapped_angle`](#method.wrapped_angle).
+    pub fn angle(&self) -> Real {
+        self.angle
+    }
+
+    /// Current rotation angle of the wheel, wrapped into the range `[-π, π)`
+    pub fn wrapped_angle(&self) -> Real {
+        wrap_angle(self.angle)
+    }
+
+    /// Number of accumulated turns of the wheel, negative for clockwise rotation
+    pub fn turns(&self) -> Real {
+        self.angle / two_pi()
+    }
+
+    /// Current angular velocity of the wheel, in radians per second
+    pub fn angular_velocity(&self) -> Real {
+        self.angular_velocity
+    }
+}
+
+/// Command for a joint motor, see [`JointControl`](struct.JointControl.html)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JointCommand {
+    /// Apply the given torque
+    Torque(Real),
+    /// Drive the joint towards the given angular velocity, in radians per second
+    Velocity(Real),
+}
+
+/// Per-step control input for a joint motor, e.g. for robotics.
+///
+/// A command is written each step, and is consumed by the joint solver. The commanded torque,
+/// or the torque needed to reach the commanded velocity, is clamped by the maximum motor torque of
+/// the joint, and the torque that was actually applied is reported back. When no command is given,
+/// the joint motor runs as configured on the joint.
+#[derive(Debug, Clone, Default)]
+pub struct JointControl {
+    command: Option<JointCommand>,
+    applied_torque: Real,
+}
+
+impl JointControl {
+    /// Create a new joint control, without a command
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Command a torque for the next step
+    pub fn command_torque(&mut self, torque: Real) {
+        self.command = Some(JointCommand::Torque(torque));
+    }
+
+    /// Command a target angular velocity for the next step
+    pub fn command_velocity(&mut self, velocity: Real) {
+        self.command = Some(JointCommand::Velocity(velocity));
+    }
+
+    /// Get the command for the next step, if any
+    pub fn command(&self) -> Option<JointCommand> {
+        self.command
+    }
+
+    /// Get the torque applied by the motor in the last step
+    pub fn applied_torque(&self) -> Real {
+        self.applied_torque
+    }
+}
+
+/// Body data used for wheel joint resolution
+pub struct WheelBodyData<'a> {
+    /// Pose for next frame
+    pub pose: &'a BodyPose<Point2<Real>, Basis2<Real>>,
+    /// Linear velocity for next frame
+    pub velocity: Vector2<Real>,
+    /// Angular velocity for next frame
+    pub angular_velocity: Real,
+    /// Mass
+    pub mass: Option<&'a Mass>,
+    /// Inertia. Wheels with a mass but without an inertia are treated as a solid disc, a chassis
+    /// without an inertia does not rotate.
+    pub inertia: Option<&'a Inertia<Vector2<Real>>>,
+}
+
+/// Wheel joint resolution.
+///
+/// Applies the suspension spring, keeps the wheel on the suspension axis and runs the motor. The
+/// suspension and axis impulses are applied to the chassis at the anchor point, so they turn a
+/// chassis with an inertia, e.g. a two wheeled vehicle pitches when one suspension is compressed.
+/// The motor torque changes the angular velocity of the wheel, and the reaction torque is applied
+/// to the chassis.
+///
+/// If a [`JointControl`](struct.JointControl.html) is given, its command is consumed, and the
+/// applied motor torque is written back to it.
+///
+/// # Returns:
+///
+/// New linear and angular velocities for the chassis, and new linear and angular velocities for
+/// the wheel.
+pub fn solve_wheel_joint<ID>(
+    joint: &mut WheelJoint2<ID>,
+    chassis: WheelBodyData,
+    wheel: WheelBodyData,
+    control: Option<&mut JointControl>,
+    dt: Real,
+) -> (Vector2<Real>, Real, Vector2<Real>, Real) {
+    let mut chassis_velocity = chassis.velocity;
+    let mut chassis_angular_velocity = chassis.angular_velocity;
+    let mut wheel_velocity = wheel.velocity;
+    let chassis_inverse_mass = chassis.mass.map(|m| m.inverse_mass()).unwrap_or(0.);
+    let wheel_inverse_mass = wheel.mass.map(|m| m.inverse_mass()).unwrap_or(0.);
+    let chassis_inverse_inertia = match (chassis.inertia, chassis.mass) {
+        (Some(inertia), Some(_)) => inertia.inverse_inertia(),
+        _ => 0.,
+    };
+    let total_inverse_mass = chassis_inverse_mass + wheel_inverse_mass;
+
+    if total_inverse_mass > 0. && dt > 0. {
+        let axis = chassis.pose.rotation().rotate_vector(joint.axis);
+        let normal = Vector2::new(-axis.y, axis.x);
+        let anchor = chassis.pose.transform_point(Point2::from_vec(joint.anchor));
+        let anchor_offset = anchor - *chassis.pose.position();
+        let offset = *wheel.pose.position() - anchor;
+
+        // velocity of the wheel relative to the anchor point on the chassis
+        let relative_velocity = |wheel_velocity: Vector2<Real>,
+                                 chassis_velocity: Vector2<Real>,
+                                 chassis_angular_velocity: Real| {
+            wheel_velocity - chassis_velocity
+                - Vector2::point_velocity(chassis_angular_velocity, anchor_offset)
+        };
+        // inverse effective mass along a direction, the impulse turns the chassis around its
+        // center
+        let inverse_effective_mass = |direction: Vector2<Real>| {
+            let arm = Vector2::cross(anchor_offset, direction);
+            total_inverse_mass + chassis_inverse_inertia * arm * arm
+        };
+
+        // suspension spring along the axis
+        let extension = offset.dot(axis) - joint.rest_length;
+        let velocity =
+            relative_velocity(wheel_velocity, chassis_velocity, chassis_angular_velocity);
+        let impulse = match joint.suspension {
+            Some(ref suspension) => {
+                let inverse_mass = inverse_effective_mass(axis);
+                let (softness, bias_factor) = suspension.soft_constraint(1. / inverse_mass, dt);
+                let lambda = -(velocity.dot(axis) + bias_factor * extension / dt)
+                    / (inverse_mass + softness);
+                axis * lambda
+            }
+            None => {
+                let force = -(joint.stiffness * extension + joint.damping * velocity.dot(axis));
+                axis * (force * dt)
+            }
+        };
+        wheel_velocity += impulse * wheel_inverse_mass;
+        chassis_velocity -= impulse * chassis_inverse_mass;
+        chassis_angular_velocity -=
+            chassis_inverse_inertia * Vector2::cross(anchor_offset, impulse);
+
+        // keep the wheel on the axis, by removing relative velocity along the normal
+        let velocity =
+            relative_velocity(wheel_velocity, chassis_velocity, chassis_angular_velocity);
+        let lateral_error = offset.dot(normal);
+        let lambda = -(velocity.dot(normal) + LATERAL_CORRECTION_PERCENT * lateral_error / dt)
+            / inverse_effective_mass(normal);
+        let impulse = normal * lambda;
+        wheel_velocity += impulse * wheel_inverse_mass;
+        chassis_velocity -= impulse * chassis_inverse_mass;
+        chassis_angular_velocity -=
+            chassis_inverse_inertia * Vector2::cross(anchor_offset, impulse);
+    }
+
+    // motor, wheels without an inertia are treated as a solid disc. The motor drives the wheel
+    // relative to the chassis, and turns the chassis the other way.
+    let inverse_inertia = match (wheel.inertia, wheel.mass) {
+        (Some(inertia), _) => inertia.inverse_inertia(),
+        (None, Some(mass)) => 2. * mass.inverse_mass() / (joint.radius * joint.radius),
+        (None, None) => 0.,
+    };
+    let mut angular_velocity = wheel.angular_velocity;
+    let mut control = control;
+    let command = control.as_mut().and_then(|c| c.command.take());
+    let mut applied_torque = 0.;
+    if joint.max_motor_torque > 0. && inverse_inertia > 0. && inverse_inertia.is_finite()
+        && dt > 0.
+    {
+        let total_inverse_inertia = inverse_inertia + chassis_inverse_inertia;
+        let relative_angular_velocity = angular_velocity - chassis_angular_velocity;
+        let max_impulse = joint.max_motor_torque * dt;
+        let impulse = match command {
+            Some(JointCommand::Torque(torque)) => torque * dt,
+            Some(JointCommand::Velocity(speed)) => {
+                (speed - relative_angular_velocity) / total_inverse_inertia
+            }
+            None => (joint.motor_speed - relative_angular_velocity) / total_inverse_inertia,
+        };
+        let impulse = impulse.max(-max_impulse).min(max_impulse);
+        angular_velocity += impulse * inverse_inertia;
+        chassis_angular_velocity -= impulse * chassis_inverse_inertia;
+        applied_torque = impulse / dt;
+    }
+    if let Some(control) = control {
+        control.applied_torque = applied_torque;
+    }
+    joint.angular_velocity = angular_velocity;
+    joint.angle += angular_velocity * dt;
+
+    (
+        chassis_velocity,
+        chassis_angular_velocity,
+        wheel_velocity,
+        angular_velocity,
+    )
+}